@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // the descriptor set is embedded for the gRPC reflection service
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("chat_descriptor.bin"))
+        .compile(&["proto/chat.proto"], &["proto"])?;
+    Ok(())
+}
@@ -1 +1,34 @@
--- Add migration script here
+CREATE TABLE chats (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    system_message_id UUID NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    token_usage BIGINT NOT NULL DEFAULT 0,
+    model VARCHAR(255) NOT NULL,
+    model_max_tokens INTEGER NOT NULL,
+    temperature REAL NOT NULL,
+    top_p REAL NOT NULL,
+    n INTEGER NOT NULL,
+    stop TEXT[] NOT NULL DEFAULT '{}',
+    max_tokens BIGINT NOT NULL,
+    presence_penalty REAL NOT NULL,
+    frequency_penalty REAL NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX chats_user_id_idx ON chats (user_id, updated_at DESC);
+
+CREATE TABLE messages (
+    id UUID PRIMARY KEY,
+    chat_id UUID NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    role VARCHAR(32) NOT NULL,
+    content TEXT NOT NULL,
+    tokens BIGINT NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    erased BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX messages_chat_id_idx ON messages (chat_id, position);
@@ -0,0 +1 @@
+ALTER TABLE chats ADD COLUMN trimming_policy VARCHAR(32) NOT NULL DEFAULT 'trim_oldest';
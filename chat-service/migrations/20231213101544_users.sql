@@ -0,0 +1,12 @@
+CREATE TABLE users (
+    id UUID PRIMARY KEY,
+    external_id VARCHAR(255) NOT NULL UNIQUE,
+    display_name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- chats created before users existed keep working, their ids become both id and external_id
+INSERT INTO users (id, external_id, display_name)
+SELECT DISTINCT user_id, user_id::TEXT, user_id::TEXT FROM chats;
+
+ALTER TABLE chats ADD CONSTRAINT chats_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id);
@@ -0,0 +1,10 @@
+CREATE TABLE usage_daily (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, date, model)
+);
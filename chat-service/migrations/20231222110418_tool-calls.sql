@@ -0,0 +1,4 @@
+ALTER TABLE chats ADD COLUMN tools JSONB NOT NULL DEFAULT '[]';
+
+ALTER TABLE messages ADD COLUMN tool_calls JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE messages ADD COLUMN tool_call_id VARCHAR(255);
@@ -0,0 +1 @@
+ALTER TABLE chats ADD COLUMN response_format JSONB NOT NULL DEFAULT '{"type": "text"}';
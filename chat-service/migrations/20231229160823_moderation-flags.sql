@@ -0,0 +1,10 @@
+CREATE TABLE moderation_flags (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    chat_id UUID REFERENCES chats (id) ON DELETE SET NULL,
+    content TEXT NOT NULL,
+    categories TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX moderation_flags_user_id_idx ON moderation_flags (user_id, created_at);
@@ -0,0 +1,12 @@
+-- usage recorded before this migration only exists in the daily aggregates, chats start at zero
+CREATE TABLE chat_usage (
+    chat_id UUID PRIMARY KEY REFERENCES chats (id) ON DELETE CASCADE,
+    requests BIGINT NOT NULL DEFAULT 0,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    cost DOUBLE PRECISION NOT NULL DEFAULT 0
+);
+
+-- chat listings page by last activity with the id as tie breaker
+DROP INDEX chats_user_id_idx;
+CREATE INDEX chats_user_id_idx ON chats (user_id, updated_at DESC, id DESC);
@@ -0,0 +1,4 @@
+-- deleted chats stay until the purge job removes them once the retention has passed
+ALTER TABLE chats ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX chats_deleted_at_idx ON chats (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- an edited user message is stored as a new message pointing at the one it replaces, which stays
+-- erased together with the rest of the discarded branch
+ALTER TABLE messages ADD COLUMN revision_of UUID;
@@ -0,0 +1,3 @@
+-- the title is generated after the first exchange, chats created before stay untitled until
+-- their next reply
+ALTER TABLE chats ADD COLUMN title VARCHAR(255);
@@ -0,0 +1,13 @@
+-- existing rows belong to the default tenant, the nil uuid
+ALTER TABLE users ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+ALTER TABLE chats ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+ALTER TABLE api_keys ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+ALTER TABLE usage_daily ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+ALTER TABLE chat_usage ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+
+-- external ids are only unique within a tenant, two organizations may share an identity provider
+ALTER TABLE users DROP CONSTRAINT users_external_id_key;
+ALTER TABLE users ADD CONSTRAINT users_tenant_id_external_id_key UNIQUE (tenant_id, external_id);
+
+DROP INDEX chats_user_id_idx;
+CREATE INDEX chats_user_id_idx ON chats (tenant_id, user_id, updated_at DESC, id DESC);
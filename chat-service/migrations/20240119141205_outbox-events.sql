@@ -0,0 +1,14 @@
+-- events outlive their chats, so chat_id has no foreign key and purged chats keep their history
+CREATE TABLE outbox_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    chat_id UUID NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    published_at TIMESTAMPTZ
+);
+
+CREATE INDEX outbox_events_unpublished_idx ON outbox_events (occurred_at, id)
+    WHERE published_at IS NULL;
@@ -0,0 +1,22 @@
+-- the postgres server needs the pgvector extension installed, https://github.com/pgvector/pgvector,
+-- every chat deletion clears the embeddings of the chat so the table is needed even with RAG off;
+-- the migrating role needs the right to create the extension, or it is created beforehand
+CREATE EXTENSION IF NOT EXISTS vector;
+
+-- embeddings are searched per user and model, so the vector column has no fixed size and
+-- searches scan the rows of the user instead of an ANN index
+CREATE TABLE embeddings (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    source_id UUID NOT NULL,
+    content TEXT NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    embedding vector NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX embeddings_owner_idx ON embeddings (tenant_id, user_id, model, kind);
+CREATE INDEX embeddings_source_idx ON embeddings (tenant_id, source_id);
@@ -0,0 +1,13 @@
+-- documents keep what users uploaded, the text itself is stored as chunks in embeddings
+CREATE TABLE documents (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    format VARCHAR(32) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    chunk_count INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX documents_owner_idx ON documents (tenant_id, user_id, created_at DESC);
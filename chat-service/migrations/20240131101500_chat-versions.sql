@@ -0,0 +1,3 @@
+-- version is bumped by every save, a save made on top of an older version is rejected so two
+-- replies to the same chat cannot overwrite each other
+ALTER TABLE chats ADD COLUMN version BIGINT NOT NULL DEFAULT 0;
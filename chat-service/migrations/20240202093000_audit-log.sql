@@ -0,0 +1,32 @@
+-- audit_log records every prompt sent to a model provider and every response; it references
+-- nothing so entries outlive the chats and users they mention, and rows are never changed
+CREATE TABLE audit_log (
+    id UUID PRIMARY KEY,
+    request_id UUID NOT NULL,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    chat_id UUID NOT NULL,
+    direction TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    content TEXT NOT NULL,
+    error TEXT,
+    latency_ms BIGINT,
+    prompt_tokens INTEGER NOT NULL,
+    completion_tokens INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX audit_log_created_at_idx ON audit_log (created_at, id);
+CREATE INDEX audit_log_user_id_idx ON audit_log (user_id, created_at);
+CREATE INDEX audit_log_chat_id_idx ON audit_log (chat_id, created_at);
+CREATE INDEX audit_log_request_id_idx ON audit_log (request_id);
+
+CREATE FUNCTION audit_log_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
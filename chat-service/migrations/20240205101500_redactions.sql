@@ -0,0 +1,15 @@
+-- redactions keep the encrypted originals of user messages that had personal data masked; the
+-- chat and message may not be stored yet when the original is, so only the user is referenced
+CREATE TABLE redactions (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    chat_id UUID,
+    message_id UUID NOT NULL,
+    findings TEXT[] NOT NULL DEFAULT '{}',
+    ciphertext BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX redactions_message_id_idx ON redactions (tenant_id, message_id);
+CREATE INDEX redactions_user_id_idx ON redactions (user_id, created_at);
@@ -0,0 +1,14 @@
+-- tenants managed through the admin API; a stored tenant takes precedence over a configured
+-- one with the same id, the model and rate limit columns are null when the defaults apply
+CREATE TABLE tenants (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    model VARCHAR(255),
+    model_max_tokens INTEGER,
+    requests_per_minute INTEGER,
+    tokens_per_minute INTEGER,
+    allowed_models TEXT[] NOT NULL DEFAULT '{}'
+);
+
+-- the usage summary aggregates every tenant over a range of days
+CREATE INDEX usage_daily_date_idx ON usage_daily (date, tenant_id);
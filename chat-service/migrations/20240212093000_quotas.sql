@@ -0,0 +1,5 @@
+-- token quotas a tenant replaces the default ones with, all null when the defaults apply
+ALTER TABLE tenants ADD COLUMN user_daily_tokens BIGINT;
+ALTER TABLE tenants ADD COLUMN user_monthly_tokens BIGINT;
+ALTER TABLE tenants ADD COLUMN tenant_daily_tokens BIGINT;
+ALTER TABLE tenants ADD COLUMN tenant_monthly_tokens BIGINT;
@@ -0,0 +1,2 @@
+-- interrupted marks replies cut short because their client went away while they were streamed
+ALTER TABLE messages ADD COLUMN interrupted BOOLEAN NOT NULL DEFAULT FALSE;
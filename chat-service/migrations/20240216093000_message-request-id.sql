@@ -0,0 +1,2 @@
+-- request_id correlates a message with the log lines of the request it was created for
+ALTER TABLE messages ADD COLUMN request_id VARCHAR(128);
@@ -0,0 +1,11 @@
+-- memories keep the facts extracted from the chats of each user, they go with the user
+CREATE TABLE memories (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    chat_id UUID NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX memories_user_id_idx ON memories (tenant_id, user_id, created_at);
@@ -0,0 +1,15 @@
+-- scheduled_messages wait to be sent into their chat at send_at, they go with the chat
+CREATE TABLE scheduled_messages (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    chat_id UUID NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    send_at TIMESTAMPTZ NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX scheduled_messages_due_idx ON scheduled_messages (status, send_at);
+CREATE INDEX scheduled_messages_user_id_idx ON scheduled_messages (tenant_id, user_id, send_at);
@@ -0,0 +1,45 @@
+-- webhooks are called back on the chat lifecycle events they subscribe to
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX webhooks_tenant_id_idx ON webhooks (tenant_id, created_at);
+
+-- webhook_deliveries wait for a call to succeed, they go with the webhook
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    webhook_id UUID NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_id UUID NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at);
+
+-- webhook_dead_letters keep the deliveries that failed every attempt, even once the webhook
+-- is deleted
+CREATE TABLE webhook_dead_letters (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    webhook_id UUID NOT NULL,
+    event_id UUID NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    failed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX webhook_dead_letters_tenant_id_idx ON webhook_dead_letters (tenant_id, failed_at);
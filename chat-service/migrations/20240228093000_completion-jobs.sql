@@ -0,0 +1,17 @@
+-- completion_jobs wait for a worker to answer them, they go with the user
+CREATE TABLE completion_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    chat_id UUID,
+    user_message TEXT NOT NULL,
+    attachments JSONB NOT NULL,
+    overrides JSONB NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    content TEXT,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX completion_jobs_queued_idx ON completion_jobs (status, created_at);
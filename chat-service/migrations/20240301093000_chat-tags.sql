@@ -0,0 +1,5 @@
+-- tags label a chat for filtering listings, metadata holds what the client attaches to it
+ALTER TABLE chats ADD COLUMN tags JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE chats ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX chats_tags_idx ON chats USING GIN (tags);
@@ -0,0 +1,6 @@
+-- search_vector indexes the words of every message for the full-text search of transcripts, it
+-- is kept up to date by postgres as messages are written
+ALTER TABLE messages ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('english', content)) STORED;
+
+CREATE INDEX messages_search_vector_idx ON messages USING GIN (search_vector);
@@ -0,0 +1,38 @@
+-- chat_summaries is the read model of the chat listings, written in the transaction that saves
+-- the chat so listings never count or read the messages
+CREATE TABLE chat_summaries (
+    chat_id UUID PRIMARY KEY REFERENCES chats (id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    title VARCHAR(255),
+    tags JSONB NOT NULL DEFAULT '[]',
+    metadata JSONB NOT NULL DEFAULT '{}',
+    token_usage BIGINT NOT NULL,
+    message_count BIGINT NOT NULL,
+    last_message_preview TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX chat_summaries_user_id_idx
+    ON chat_summaries (tenant_id, user_id, updated_at DESC, chat_id DESC);
+CREATE INDEX chat_summaries_tags_idx ON chat_summaries USING GIN (tags);
+
+-- listings filtered chats by tag before, they read the summaries now
+DROP INDEX chats_tags_idx;
+
+INSERT INTO chat_summaries (chat_id, tenant_id, user_id, status, model, title, tags, metadata,
+    token_usage, message_count, last_message_preview, created_at, updated_at)
+SELECT c.id, c.tenant_id, c.user_id, c.status, c.model, c.title, c.tags, c.metadata,
+    c.token_usage,
+    (SELECT COUNT(*) FROM messages m WHERE m.chat_id = c.id AND NOT m.erased
+        AND m.id <> c.system_message_id),
+    (SELECT CASE WHEN length(p.content) > 120 THEN left(p.content, 120) || '…' ELSE p.content END
+        FROM (SELECT regexp_replace(btrim(m.content), '\s+', ' ', 'g') AS content
+            FROM messages m WHERE m.chat_id = c.id AND NOT m.erased
+            AND m.role IN ('user', 'assistant') AND m.tool_calls = '[]'
+            AND btrim(m.content) <> '' ORDER BY m.position DESC LIMIT 1) p),
+    c.created_at, c.updated_at
+FROM chats c;
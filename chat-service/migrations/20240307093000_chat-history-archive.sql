@@ -0,0 +1,6 @@
+-- history_archive tells where the messages of a cold chat went, they are back in messages once
+-- it is reopened
+ALTER TABLE chats ADD COLUMN history_archive JSONB;
+
+CREATE INDEX chats_inactive_idx ON chats (updated_at, id)
+    WHERE history_archive IS NULL AND status <> 'deleted';
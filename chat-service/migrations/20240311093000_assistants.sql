@@ -0,0 +1,15 @@
+-- assistants are the bots of a tenant, chats started with one keep its id
+CREATE TABLE assistants (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    instructions TEXT NOT NULL,
+    model VARCHAR(255),
+    temperature REAL,
+    tools JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (tenant_id, name)
+);
+
+ALTER TABLE chats ADD COLUMN assistant_id UUID;
@@ -0,0 +1,2 @@
+-- speaker labels the assistant messages of an orchestrated chat with the agent that wrote them
+ALTER TABLE messages ADD COLUMN speaker VARCHAR(255);
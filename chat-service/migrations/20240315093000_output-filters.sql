@@ -0,0 +1,3 @@
+-- output_filter is the filter a tenant replaces the default one of the assistant responses
+-- with, null when the default applies
+ALTER TABLE tenants ADD COLUMN output_filter JSONB;
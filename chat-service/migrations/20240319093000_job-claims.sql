@@ -0,0 +1,3 @@
+-- claimed_until is when the worker running a job gives it up, a job left running past it by a
+-- worker that stopped is claimed again
+ALTER TABLE completion_jobs ADD COLUMN claimed_until TIMESTAMPTZ;
@@ -0,0 +1,139 @@
+-- the schema of the mysql and sqlite drivers, written in the SQL both understand: ids are
+-- stored as text, timestamps as fixed-width RFC 3339 text in UTC so they sort in time order,
+-- and JSON as text; embeddings and documents are only kept by postgres
+CREATE TABLE users (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    external_id VARCHAR(255) NOT NULL,
+    display_name VARCHAR(255) NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    UNIQUE (tenant_id, external_id)
+);
+
+CREATE TABLE chats (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    system_message_id CHAR(36) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    token_usage BIGINT NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    model_max_tokens BIGINT NOT NULL,
+    temperature DOUBLE NOT NULL,
+    top_p DOUBLE NOT NULL,
+    n BIGINT NOT NULL,
+    stop LONGTEXT NOT NULL,
+    max_tokens BIGINT NOT NULL,
+    presence_penalty DOUBLE NOT NULL,
+    frequency_penalty DOUBLE NOT NULL,
+    trimming_policy VARCHAR(32) NOT NULL,
+    tools LONGTEXT NOT NULL,
+    response_format LONGTEXT NOT NULL,
+    title VARCHAR(255),
+    created_at CHAR(27) NOT NULL,
+    updated_at CHAR(27) NOT NULL,
+    deleted_at CHAR(27),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX chats_user_id_idx ON chats (tenant_id, user_id, updated_at, id);
+CREATE INDEX chats_deleted_at_idx ON chats (deleted_at);
+
+CREATE TABLE messages (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    chat_id CHAR(36) NOT NULL,
+    role VARCHAR(32) NOT NULL,
+    content LONGTEXT NOT NULL,
+    tokens BIGINT NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    erased BIGINT NOT NULL,
+    position BIGINT NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    tool_calls LONGTEXT NOT NULL,
+    tool_call_id VARCHAR(255),
+    revision_of CHAR(36),
+    attachments LONGTEXT NOT NULL,
+    FOREIGN KEY (chat_id) REFERENCES chats (id) ON DELETE CASCADE
+);
+
+CREATE INDEX messages_chat_id_idx ON messages (chat_id, position);
+
+CREATE TABLE api_keys (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    prefix VARCHAR(32) NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at CHAR(27) NOT NULL,
+    revoked_at CHAR(27),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE TABLE usage_daily (
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    date CHAR(10) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    requests BIGINT NOT NULL,
+    prompt_tokens BIGINT NOT NULL,
+    completion_tokens BIGINT NOT NULL,
+    cost DOUBLE NOT NULL,
+    PRIMARY KEY (user_id, date, model),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE TABLE chat_usage (
+    tenant_id CHAR(36) NOT NULL,
+    chat_id CHAR(36) NOT NULL PRIMARY KEY,
+    requests BIGINT NOT NULL,
+    prompt_tokens BIGINT NOT NULL,
+    completion_tokens BIGINT NOT NULL,
+    cost DOUBLE NOT NULL,
+    FOREIGN KEY (chat_id) REFERENCES chats (id) ON DELETE CASCADE
+);
+
+CREATE TABLE moderation_flags (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36),
+    content LONGTEXT NOT NULL,
+    categories LONGTEXT NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    FOREIGN KEY (chat_id) REFERENCES chats (id) ON DELETE SET NULL
+);
+
+CREATE INDEX moderation_flags_user_id_idx ON moderation_flags (user_id, created_at);
+
+CREATE TABLE prompt_templates (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL UNIQUE,
+    content LONGTEXT NOT NULL,
+    created_at CHAR(27) NOT NULL
+);
+
+-- key is quoted, it is a reserved word in mysql
+CREATE TABLE idempotency_keys (
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    `key` VARCHAR(255) NOT NULL,
+    fingerprint VARCHAR(64) NOT NULL,
+    response LONGTEXT,
+    created_at CHAR(27) NOT NULL,
+    expires_at CHAR(27) NOT NULL,
+    PRIMARY KEY (tenant_id, user_id, `key`),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE TABLE outbox_events (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36) NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    payload LONGTEXT NOT NULL,
+    occurred_at CHAR(27) NOT NULL,
+    published_at CHAR(27)
+);
+
+CREATE INDEX outbox_events_unpublished_idx ON outbox_events (published_at, occurred_at, id);
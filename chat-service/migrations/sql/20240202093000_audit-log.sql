@@ -0,0 +1,23 @@
+-- audit_log records every prompt sent to a model provider and every response; it references
+-- nothing so entries outlive the chats and users they mention, and rows are never changed
+CREATE TABLE audit_log (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    request_id CHAR(36) NOT NULL,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36) NOT NULL,
+    direction VARCHAR(32) NOT NULL,
+    provider VARCHAR(255) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    content LONGTEXT NOT NULL,
+    error LONGTEXT,
+    latency_ms BIGINT,
+    prompt_tokens BIGINT NOT NULL,
+    completion_tokens BIGINT NOT NULL,
+    created_at CHAR(27) NOT NULL
+);
+
+CREATE INDEX audit_log_created_at_idx ON audit_log (created_at, id);
+CREATE INDEX audit_log_user_id_idx ON audit_log (user_id, created_at);
+CREATE INDEX audit_log_chat_id_idx ON audit_log (chat_id, created_at);
+CREATE INDEX audit_log_request_id_idx ON audit_log (request_id);
@@ -0,0 +1,16 @@
+-- redactions keep the encrypted originals of user messages that had personal data masked; the
+-- chat and message may not be stored yet when the original is, so only the user is referenced
+CREATE TABLE redactions (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36),
+    message_id CHAR(36) NOT NULL,
+    findings LONGTEXT NOT NULL,
+    ciphertext LONGBLOB NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX redactions_message_id_idx ON redactions (tenant_id, message_id);
+CREATE INDEX redactions_user_id_idx ON redactions (user_id, created_at);
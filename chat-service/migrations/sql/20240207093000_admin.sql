@@ -0,0 +1,15 @@
+-- tenants managed through the admin API; a stored tenant takes precedence over a configured
+-- one with the same id, the model and rate limit columns are null when the defaults apply
+CREATE TABLE tenants (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    model VARCHAR(255),
+    model_max_tokens BIGINT,
+    requests_per_minute BIGINT,
+    tokens_per_minute BIGINT,
+    allowed_models LONGTEXT NOT NULL
+);
+
+-- keys are rotated per user and the usage summary aggregates every tenant over a range of days
+CREATE INDEX api_keys_user_id_idx ON api_keys (tenant_id, user_id);
+CREATE INDEX usage_daily_date_idx ON usage_daily (date, tenant_id);
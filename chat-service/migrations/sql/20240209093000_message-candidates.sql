@@ -0,0 +1,3 @@
+-- candidates are the other replies the model offered when a chat asks for more than one, they
+-- are kept until one is selected; mysql takes no default on text columns so older rows are null
+ALTER TABLE messages ADD COLUMN candidates LONGTEXT;
@@ -0,0 +1,12 @@
+-- memories keep the facts extracted from the chats of each user, they go with the user
+CREATE TABLE memories (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36) NOT NULL,
+    content TEXT NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX memories_user_id_idx ON memories (tenant_id, user_id, created_at);
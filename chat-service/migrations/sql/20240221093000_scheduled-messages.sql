@@ -0,0 +1,16 @@
+-- scheduled_messages wait to be sent into their chat at send_at, they go with the chat
+CREATE TABLE scheduled_messages (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36) NOT NULL,
+    content TEXT NOT NULL,
+    send_at CHAR(27) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    error TEXT,
+    created_at CHAR(27) NOT NULL,
+    FOREIGN KEY (chat_id) REFERENCES chats (id) ON DELETE CASCADE
+);
+
+CREATE INDEX scheduled_messages_due_idx ON scheduled_messages (status, send_at);
+CREATE INDEX scheduled_messages_user_id_idx ON scheduled_messages (tenant_id, user_id, send_at);
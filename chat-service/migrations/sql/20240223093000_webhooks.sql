@@ -0,0 +1,46 @@
+-- webhooks are called back on the chat lifecycle events they subscribe to
+CREATE TABLE webhooks (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    events LONGTEXT NOT NULL,
+    created_at CHAR(27) NOT NULL
+);
+
+CREATE INDEX webhooks_tenant_id_idx ON webhooks (tenant_id, created_at);
+
+-- webhook_deliveries wait for a call to succeed, they go with the webhook
+CREATE TABLE webhook_deliveries (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    webhook_id CHAR(36) NOT NULL,
+    event_id CHAR(36) NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    payload LONGTEXT NOT NULL,
+    attempts BIGINT NOT NULL,
+    next_attempt_at CHAR(27) NOT NULL,
+    last_error TEXT,
+    created_at CHAR(27) NOT NULL,
+    FOREIGN KEY (webhook_id) REFERENCES webhooks (id) ON DELETE CASCADE
+);
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at);
+
+-- webhook_dead_letters keep the deliveries that failed every attempt, even once the webhook
+-- is deleted
+CREATE TABLE webhook_dead_letters (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    webhook_id CHAR(36) NOT NULL,
+    event_id CHAR(36) NOT NULL,
+    event VARCHAR(32) NOT NULL,
+    payload LONGTEXT NOT NULL,
+    attempts BIGINT NOT NULL,
+    next_attempt_at CHAR(27) NOT NULL,
+    last_error TEXT,
+    created_at CHAR(27) NOT NULL,
+    failed_at CHAR(27) NOT NULL
+);
+
+CREATE INDEX webhook_dead_letters_tenant_id_idx ON webhook_dead_letters (tenant_id, failed_at);
@@ -0,0 +1,11 @@
+-- batches keep the prompts sent together and their answers, they go with the user
+CREATE TABLE batches (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    items LONGTEXT NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    completed_at CHAR(27),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
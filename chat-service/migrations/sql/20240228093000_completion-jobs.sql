@@ -0,0 +1,18 @@
+-- completion_jobs wait for a worker to answer them, they go with the user
+CREATE TABLE completion_jobs (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    chat_id CHAR(36),
+    user_message TEXT NOT NULL,
+    attachments LONGTEXT NOT NULL,
+    overrides LONGTEXT NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    content LONGTEXT,
+    error TEXT,
+    created_at CHAR(27) NOT NULL,
+    completed_at CHAR(27),
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX completion_jobs_queued_idx ON completion_jobs (status, created_at);
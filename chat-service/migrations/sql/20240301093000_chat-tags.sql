@@ -0,0 +1,4 @@
+-- tags label a chat for filtering listings, metadata holds what the client attaches to it;
+-- mysql takes no default on text columns so older rows are null
+ALTER TABLE chats ADD COLUMN tags LONGTEXT;
+ALTER TABLE chats ADD COLUMN metadata LONGTEXT;
@@ -0,0 +1,33 @@
+-- chat_summaries is the read model of the chat listings, written in the transaction that saves
+-- the chat so listings never count or read the messages; the previews of older chats are not
+-- put on one line until their next save
+CREATE TABLE chat_summaries (
+    chat_id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    title VARCHAR(255),
+    tags LONGTEXT,
+    metadata LONGTEXT,
+    token_usage BIGINT NOT NULL,
+    message_count BIGINT NOT NULL,
+    last_message_preview LONGTEXT,
+    created_at CHAR(27) NOT NULL,
+    updated_at CHAR(27) NOT NULL,
+    FOREIGN KEY (chat_id) REFERENCES chats (id) ON DELETE CASCADE
+);
+
+CREATE INDEX chat_summaries_user_id_idx ON chat_summaries (tenant_id, user_id, updated_at, chat_id);
+
+INSERT INTO chat_summaries (chat_id, tenant_id, user_id, status, model, title, tags, metadata,
+    token_usage, message_count, last_message_preview, created_at, updated_at)
+SELECT c.id, c.tenant_id, c.user_id, c.status, c.model, c.title, c.tags, c.metadata,
+    c.token_usage,
+    (SELECT COUNT(*) FROM messages m WHERE m.chat_id = c.id AND m.erased = 0
+        AND m.id <> c.system_message_id),
+    (SELECT SUBSTR(m.content, 1, 120) FROM messages m WHERE m.chat_id = c.id AND m.erased = 0
+        AND m.role IN ('user', 'assistant') AND m.tool_calls = '[]' AND TRIM(m.content) <> ''
+        ORDER BY m.position DESC LIMIT 1),
+    c.created_at, c.updated_at
+FROM chats c;
@@ -0,0 +1,5 @@
+-- history_archive tells where the messages of a cold chat went, they are back in messages once
+-- it is reopened
+ALTER TABLE chats ADD COLUMN history_archive LONGTEXT;
+
+CREATE INDEX chats_updated_at_idx ON chats (updated_at, id);
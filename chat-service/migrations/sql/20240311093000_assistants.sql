@@ -0,0 +1,15 @@
+-- assistants are the bots of a tenant, chats started with one keep its id
+CREATE TABLE assistants (
+    id CHAR(36) NOT NULL PRIMARY KEY,
+    tenant_id CHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    instructions LONGTEXT NOT NULL,
+    model VARCHAR(255),
+    temperature DOUBLE,
+    tools LONGTEXT NOT NULL,
+    created_at CHAR(27) NOT NULL,
+    updated_at CHAR(27) NOT NULL,
+    UNIQUE (tenant_id, name)
+);
+
+ALTER TABLE chats ADD COLUMN assistant_id CHAR(36);
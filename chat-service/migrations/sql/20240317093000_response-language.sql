@@ -0,0 +1,4 @@
+-- response_language is the language a chat is answered in, null when it follows the user
+ALTER TABLE chats ADD COLUMN response_language VARCHAR(35);
+-- language is the language a user message was detected to be in
+ALTER TABLE messages ADD COLUMN language VARCHAR(35);
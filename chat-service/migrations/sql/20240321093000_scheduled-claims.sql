@@ -0,0 +1,3 @@
+-- claimed_until is when the worker sending a scheduled message gives it up, a message left
+-- sending past it by a worker that stopped is claimed again
+ALTER TABLE scheduled_messages ADD COLUMN claimed_until CHAR(27);
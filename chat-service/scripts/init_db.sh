@@ -34,7 +34,7 @@ then
     -e POSTGRES_PASSWORD=${DB_PASSWORD} \
     -e POSTGRES_DB=${DB_NAME} \
     -p "${DB_PORT}":5432 \
-    -d postgres \
+    -d pgvector/pgvector:pg16 \
     postgres -N 1000
 fi
 
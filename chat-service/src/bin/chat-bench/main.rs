@@ -0,0 +1,150 @@
+mod report;
+mod target;
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use clap::{Parser, ValueEnum};
+use tokio::task::JoinSet;
+
+use crate::report::{Report, Sample};
+use crate::target::Target;
+
+/// Load the running chat service with concurrent simulated users and report latency and
+/// streaming throughput
+#[derive(Debug, Parser)]
+#[command(name = "chat-bench", version)]
+struct Cli {
+    /// Base URL of the service HTTP API
+    #[arg(
+        long,
+        env = "CHAT_SERVICE_URL",
+        default_value = "http://localhost:8080"
+    )]
+    url: String,
+    /// URL of the service gRPC API
+    #[arg(
+        long,
+        env = "CHAT_SERVICE_GRPC_URL",
+        default_value = "http://localhost:50051"
+    )]
+    grpc_url: String,
+    /// API key or access token every simulated user sends its requests with
+    #[arg(long, env = "CHAT_SERVICE_API_KEY", hide_env_values = true)]
+    api_key: String,
+    /// API the simulated users talk to
+    #[arg(long, value_enum, default_value_t = Protocol::Http)]
+    protocol: Protocol,
+    /// Number of simulated users chatting at the same time
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    users: u32,
+    /// Messages every user sends, all in the same chat
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    turns: u32,
+    /// Message the users send on every turn
+    #[arg(long, default_value = "Tell me a short story about a lighthouse.")]
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Protocol {
+    Http,
+    Grpc,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let target = match cli.protocol {
+        Protocol::Http => Target::http(&cli.url, &cli.api_key),
+        Protocol::Grpc => match Target::grpc(&cli.grpc_url, &cli.api_key).await {
+            Ok(target) => target,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    println!(
+        "{} users, {} turns each over {:?}",
+        cli.users, cli.turns, cli.protocol
+    );
+    let report = run(target, cli.users, cli.turns, &cli.message).await;
+    print!("{}", report);
+
+    if report.failed() > 0 {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+// run starts every user at once and waits for all of them, the report covers the whole run
+async fn run(target: Target, users: u32, turns: u32, message: &str) -> Report {
+    let started = Instant::now();
+    let mut tasks = JoinSet::new();
+    for _ in 0..users {
+        tasks.spawn(simulate_user(target.clone(), turns, message.to_string()));
+    }
+
+    let mut results = vec![];
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(user) => results.extend(user),
+            Err(err) => results.push(Err(err.to_string())),
+        }
+    }
+
+    let mut report = Report::new(started.elapsed());
+    for result in results {
+        match result {
+            Ok(sample) => report.record(sample),
+            Err(err) => report.record_error(err),
+        }
+    }
+    report
+}
+
+// simulate_user chats the way a person does, one message after the reply to the previous one;
+// a failed turn ends the chat since the ones after it would not have a chat to go to
+async fn simulate_user(target: Target, turns: u32, message: String) -> Vec<Result<Sample, String>> {
+    let mut chat_id = None;
+    let mut results = vec![];
+    for _ in 0..turns {
+        match target.send(chat_id, &message).await {
+            Ok((id, sample)) => {
+                chat_id = Some(id);
+                results.push(Ok(sample));
+            }
+            Err(err) => {
+                results.push(Err(err));
+                break;
+            }
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli() {
+        let cli = Cli::try_parse_from([
+            "chat-bench",
+            "--api-key",
+            "chs_key",
+            "--protocol",
+            "grpc",
+            "--users",
+            "50",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.protocol, Protocol::Grpc);
+        assert_eq!(cli.users, 50);
+        assert_eq!(cli.turns, 5);
+        assert!(Cli::try_parse_from(["chat-bench", "--api-key", "k", "--users", "0"]).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+// Sample is one reply as a simulated user saw it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub latency: Duration,
+    // first_token is how long the first delta took, None when the reply was not streamed
+    pub first_token: Option<Duration>,
+    // tokens counts the streamed deltas, the service sends one per token the model writes
+    pub tokens: usize,
+}
+
+// Report sums up a run: the replies, the failures and how long the whole run took
+#[derive(Debug, Default)]
+pub struct Report {
+    samples: Vec<Sample>,
+    errors: BTreeMap<String, usize>,
+    elapsed: Duration,
+}
+
+impl Report {
+    pub fn new(elapsed: Duration) -> Self {
+        Self {
+            elapsed,
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&mut self, sample: Sample) {
+        self.samples.push(sample);
+    }
+
+    // record_error counts failures by message so a flood of the same one stays readable
+    pub fn record_error(&mut self, error: String) {
+        *self.errors.entry(error).or_default() += 1;
+    }
+
+    pub fn failed(&self) -> usize {
+        self.errors.values().sum()
+    }
+
+    fn latencies(&self) -> Vec<Duration> {
+        sorted(self.samples.iter().map(|sample| sample.latency))
+    }
+
+    fn first_tokens(&self) -> Vec<Duration> {
+        sorted(self.samples.iter().filter_map(|sample| sample.first_token))
+    }
+
+    // tokens_per_sec is the rate a single stream is written at and the rate of the whole run
+    fn tokens_per_sec(&self) -> (f64, f64) {
+        let streamed = self
+            .samples
+            .iter()
+            .filter(|sample| sample.first_token.is_some());
+        let (tokens, streaming) = streamed.fold((0, Duration::ZERO), |(tokens, time), sample| {
+            (tokens + sample.tokens, time + sample.latency)
+        });
+
+        (rate(tokens, streaming), rate(tokens, self.elapsed))
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requests = self.samples.len() + self.failed();
+        writeln!(
+            f,
+            "requests     {} ok, {} failed in {:.1}s ({:.1} req/s)",
+            self.samples.len(),
+            self.failed(),
+            self.elapsed.as_secs_f64(),
+            rate(requests, self.elapsed)
+        )?;
+        writeln!(f, "latency      {}", percentiles(&self.latencies()))?;
+        writeln!(f, "first token  {}", percentiles(&self.first_tokens()))?;
+
+        let (per_stream, overall) = self.tokens_per_sec();
+        writeln!(
+            f,
+            "tokens/sec   {:.1} per stream, {:.1} overall",
+            per_stream, overall
+        )?;
+
+        if !self.errors.is_empty() {
+            writeln!(f, "errors")?;
+            for (error, count) in &self.errors {
+                writeln!(f, "  {:>5}x {}", count, error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// percentile returns the nearest-rank percentile of durations sorted ascending
+pub fn percentile(sorted: &[Duration], percent: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn percentiles(sorted: &[Duration]) -> String {
+    let show = |percent: f64| match percentile(sorted, percent) {
+        Some(duration) => format!("{}ms", duration.as_millis()),
+        None => "-".to_string(),
+    };
+
+    format!("p50 {}  p95 {}  p99 {}", show(50.0), show(95.0), show(99.0))
+}
+
+fn sorted(durations: impl Iterator<Item = Duration>) -> Vec<Duration> {
+    let mut durations: Vec<Duration> = durations.collect();
+    durations.sort();
+    durations
+}
+
+fn rate(count: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+
+    count as f64 / elapsed.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let durations: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(
+            percentile(&durations, 50.0),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            percentile(&durations, 95.0),
+            Some(Duration::from_millis(95))
+        );
+        assert_eq!(
+            percentile(&durations, 99.0),
+            Some(Duration::from_millis(99))
+        );
+        assert_eq!(percentile(&durations, 0.0), Some(Duration::from_millis(1)));
+        assert_eq!(
+            percentile(&durations[..1], 99.0),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_report() {
+        let mut report = Report::new(Duration::from_secs(2));
+        report.record(Sample {
+            latency: Duration::from_millis(500),
+            first_token: None,
+            tokens: 0,
+        });
+        report.record(Sample {
+            latency: Duration::from_secs(1),
+            first_token: Some(Duration::from_millis(100)),
+            tokens: 50,
+        });
+        report.record_error("request failed: connection refused".to_string());
+        report.record_error("request failed: connection refused".to_string());
+
+        assert_eq!(report.failed(), 2);
+        assert_eq!(report.tokens_per_sec(), (50.0, 25.0));
+
+        let printed = report.to_string();
+        assert!(printed.contains("2 ok, 2 failed in 2.0s (2.0 req/s)"));
+        assert!(printed.contains("first token  p50 100ms"));
+        assert!(printed.contains("2x request failed: connection refused"));
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::Channel;
+use uuid::Uuid;
+
+use chat_service::internal::infra::client::http::ChatClient;
+use chat_service::internal::infra::grpc::pb::chat_service_client::ChatServiceClient;
+use chat_service::internal::infra::grpc::pb::ChatRequest;
+use chat_service::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+
+use crate::report::Sample;
+
+const STREAM_BUFFER_SIZE: usize = 32;
+
+// Target is the API the simulated users talk to
+#[derive(Clone)]
+pub enum Target {
+    Http(ChatClient),
+    Grpc {
+        client: ChatServiceClient<Channel>,
+        authorization: MetadataValue<Ascii>,
+    },
+}
+
+impl Target {
+    pub fn http(url: &str, api_key: &str) -> Self {
+        Target::Http(ChatClient::new(url, api_key))
+    }
+
+    pub async fn grpc(url: &str, api_key: &str) -> Result<Self, String> {
+        let client = ChatServiceClient::connect(url.to_string())
+            .await
+            .map_err(|err| format!("cannot connect to {}: {}", url, err))?;
+        let authorization = format!("Bearer {}", api_key)
+            .parse()
+            .map_err(|_| "api key is not a valid header value".to_string())?;
+
+        Ok(Target::Grpc {
+            client,
+            authorization,
+        })
+    }
+
+    // send sends a message to the chat, or starts one when there is none yet, and returns the
+    // chat along with how the reply came back; over HTTP a chat can only be started without
+    // streaming, so the first reply has no first token
+    pub async fn send(
+        &self,
+        chat_id: Option<Uuid>,
+        message: &str,
+    ) -> Result<(Uuid, Sample), String> {
+        match self {
+            Target::Http(client) => match chat_id {
+                Some(chat_id) => http_stream(client, chat_id, message).await,
+                None => {
+                    let started = Instant::now();
+                    let output = client
+                        .create_chat(message, None, &HashMap::new())
+                        .await
+                        .map_err(|err| err.to_string())?;
+
+                    Ok((
+                        output.chat_id,
+                        Sample {
+                            latency: started.elapsed(),
+                            first_token: None,
+                            tokens: 0,
+                        },
+                    ))
+                }
+            },
+            Target::Grpc {
+                client,
+                authorization,
+            } => grpc_stream(client.clone(), authorization.clone(), chat_id, message).await,
+        }
+    }
+}
+
+async fn http_stream(
+    client: &ChatClient,
+    chat_id: Uuid,
+    message: &str,
+) -> Result<(Uuid, Sample), String> {
+    let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(STREAM_BUFFER_SIZE);
+    let started = Instant::now();
+
+    let count = async move {
+        let mut first_token = None;
+        let mut tokens = 0;
+        while receiver.recv().await.is_some() {
+            first_token.get_or_insert_with(|| started.elapsed());
+            tokens += 1;
+        }
+        (first_token, tokens)
+    };
+    let (result, (first_token, tokens)) =
+        tokio::join!(client.stream_message(chat_id, message, sender), count);
+    result.map_err(|err| err.to_string())?;
+
+    Ok((
+        chat_id,
+        Sample {
+            latency: started.elapsed(),
+            first_token,
+            tokens,
+        },
+    ))
+}
+
+async fn grpc_stream(
+    mut client: ChatServiceClient<Channel>,
+    authorization: MetadataValue<Ascii>,
+    chat_id: Option<Uuid>,
+    message: &str,
+) -> Result<(Uuid, Sample), String> {
+    let mut request = tonic::Request::new(ChatRequest {
+        chat_id: chat_id.map(|id| id.to_string()).unwrap_or_default(),
+        user_message: message.to_string(),
+        ..ChatRequest::default()
+    });
+    request
+        .metadata_mut()
+        .insert("authorization", authorization);
+    let started = Instant::now();
+
+    let mut stream = client
+        .chat_stream(request)
+        .await
+        .map_err(|status| status.message().to_string())?
+        .into_inner();
+    let mut chat_id = chat_id;
+    let mut first_token = None;
+    let mut tokens = 0;
+    while let Some(response) = stream
+        .message()
+        .await
+        .map_err(|status| status.message().to_string())?
+    {
+        first_token.get_or_insert_with(|| started.elapsed());
+        tokens += 1;
+        if chat_id.is_none() {
+            chat_id = Uuid::parse_str(&response.chat_id).ok();
+        }
+    }
+    let chat_id = chat_id.ok_or_else(|| "stream ended without a reply".to_string())?;
+
+    Ok((
+        chat_id,
+        Sample {
+            latency: started.elapsed(),
+            first_token,
+            tokens,
+        },
+    ))
+}
@@ -0,0 +1,200 @@
+mod repl;
+mod transcript;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use chat_service::internal::infra::client::error::ClientError;
+use chat_service::internal::infra::client::http::ChatClient;
+use chat_service::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+
+use crate::repl::Repl;
+use crate::transcript::Transcript;
+
+const STREAM_BUFFER_SIZE: usize = 32;
+
+/// Talk to the chat service from the terminal
+#[derive(Debug, Parser)]
+#[command(name = "chat-cli", version)]
+struct Cli {
+    /// Base URL of the service HTTP API
+    #[arg(
+        long,
+        env = "CHAT_SERVICE_URL",
+        default_value = "http://localhost:8080"
+    )]
+    url: String,
+    /// API key or access token the requests are sent with
+    #[arg(long, env = "CHAT_SERVICE_API_KEY", hide_env_values = true)]
+    api_key: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Start a chat with a first message and print the reply
+    New {
+        message: String,
+        /// Prompt template the system message is rendered from
+        #[arg(long)]
+        template: Option<String>,
+        /// Template variable as name=value, repeat for each variable
+        #[arg(long = "var", value_parser = parse_variable)]
+        variables: Vec<(String, String)>,
+    },
+    /// Send a message to a chat and stream the reply
+    Send { chat_id: Uuid, message: String },
+    /// List your chats, most recent activity first
+    List {
+        #[arg(long)]
+        limit: Option<usize>,
+        /// next_cursor printed with the previous page
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Chat interactively, replies stream as they are written
+    Repl {
+        /// Chat to continue, a new one starts with the first message when omitted
+        chat_id: Option<Uuid>,
+        /// Directory the transcripts are saved to, ~/.chat-cli/transcripts by default
+        #[arg(long, env = "CHAT_CLI_TRANSCRIPT_DIR")]
+        transcript_dir: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = ChatClient::new(&cli.url, &cli.api_key);
+
+    match run(&client, cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(client: &ChatClient, command: Command) -> Result<(), ClientError> {
+    match command {
+        Command::New {
+            message,
+            template,
+            variables,
+        } => {
+            let variables: HashMap<String, String> = variables.into_iter().collect();
+            let output = client
+                .create_chat(&message, template.as_deref(), &variables)
+                .await?;
+            println!("chat {}", output.chat_id);
+            println!("{}", output.content);
+        }
+        Command::Send { chat_id, message } => {
+            stream(client, chat_id, &message).await?;
+        }
+        Command::List { limit, cursor } => {
+            let page = client.list_chats(limit, cursor.as_deref()).await?;
+            for chat in page.chats {
+                println!(
+                    "{}  {:<8}  {:>4} messages  {}  {}",
+                    chat.id,
+                    chat.status,
+                    chat.message_count,
+                    chat.last_activity_at.format("%Y-%m-%d %H:%M"),
+                    chat.title.unwrap_or_default()
+                );
+            }
+            if let Some(cursor) = page.next_cursor {
+                println!("next cursor: {}", cursor);
+            }
+        }
+        Command::Repl {
+            chat_id,
+            transcript_dir,
+        } => {
+            let transcript =
+                Transcript::new(transcript_dir.unwrap_or_else(Transcript::default_dir));
+            let mut repl = Repl::new(client.clone(), transcript);
+            if let Some(chat_id) = chat_id {
+                repl.resume(chat_id).await?;
+            }
+            if let Err(err) = repl.run().await {
+                eprintln!("error: {}", err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// stream prints the reply as it arrives, delta by delta, and returns it whole
+async fn stream(client: &ChatClient, chat_id: Uuid, message: &str) -> Result<String, ClientError> {
+    let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(STREAM_BUFFER_SIZE);
+
+    let print = async move {
+        let mut stdout = std::io::stdout();
+        let mut reply = String::new();
+        while let Some(output) = receiver.recv().await {
+            let _ = write!(stdout, "{}", output.content);
+            let _ = stdout.flush();
+            reply.push_str(&output.content);
+        }
+        println!();
+        reply
+    };
+
+    let (result, reply) = tokio::join!(client.stream_message(chat_id, message, sender), print);
+    result.map(|_| reply)
+}
+
+// parse_variable reads a template variable given as name=value
+fn parse_variable(value: &str) -> Result<(String, String), Box<dyn Error + Send + Sync>> {
+    let (name, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected name=value, got {}", value))?;
+    if name.trim().is_empty() {
+        return Err("variable name is empty".into());
+    }
+
+    Ok((name.trim().to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_variable() {
+        assert_eq!(
+            parse_variable("name=Ada = Lovelace").unwrap(),
+            ("name".to_string(), "Ada = Lovelace".to_string())
+        );
+        assert!(parse_variable("name").is_err());
+        assert!(parse_variable("=value").is_err());
+    }
+
+    #[test]
+    fn test_cli() {
+        let cli = Cli::try_parse_from([
+            "chat-cli",
+            "--api-key",
+            "chs_key",
+            "send",
+            "6f1c0a8e-8c1b-4c47-9b8a-1d2f3e4a5b6c",
+            "Hello!",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.api_key, "chs_key");
+        assert!(matches!(cli.command, Command::Send { ref message, .. } if message == "Hello!"));
+    }
+}
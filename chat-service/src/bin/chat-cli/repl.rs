@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use uuid::Uuid;
+
+use chat_service::internal::infra::client::error::ClientError;
+use chat_service::internal::infra::client::http::{ChatClient, ChatUpdate};
+use chat_service::internal::usecase::get_chat::dto::ChatOutputDTO;
+
+use crate::stream;
+use crate::transcript::Transcript;
+
+const HELP: &str = "\
+/system <message>      replace the system message of the chat
+/model <name>          switch the chat to another model
+/temperature <value>   change the sampling temperature, between 0 and 2
+/end                   end the chat and leave
+/help                  show this help
+Ctrl-D leaves and keeps the chat open.";
+
+// ReplCommand is a line typed in the REPL, anything but a slash command is a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplCommand {
+    Message(String),
+    System(String),
+    Model(String),
+    Temperature(f32),
+    End,
+    Help,
+}
+
+// parse_line reads a line of input, blank lines are skipped
+pub fn parse_line(line: &str) -> Result<Option<ReplCommand>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let Some(command) = line.strip_prefix('/') else {
+        return Ok(Some(ReplCommand::Message(line.to_string())));
+    };
+
+    let (name, argument) = command
+        .split_once(char::is_whitespace)
+        .map(|(name, argument)| (name, argument.trim()))
+        .unwrap_or((command, ""));
+    let required = |usage: &str| {
+        if argument.is_empty() {
+            Err(format!("usage: /{} {}", name, usage))
+        } else {
+            Ok(argument.to_string())
+        }
+    };
+
+    let command = match name {
+        "system" => ReplCommand::System(required("<message>")?),
+        "model" => ReplCommand::Model(required("<name>")?),
+        "temperature" => {
+            let value = required("<value>")?;
+            ReplCommand::Temperature(
+                value
+                    .parse()
+                    .map_err(|_| format!("temperature {} is not a number", value))?,
+            )
+        }
+        "end" => ReplCommand::End,
+        "help" => ReplCommand::Help,
+        _ => return Err(format!("unknown command /{}, type /help", name)),
+    };
+
+    Ok(Some(command))
+}
+
+// Repl keeps one chat open, the chat is started by the first message when there is none yet
+pub struct Repl {
+    client: ChatClient,
+    chat_id: Option<Uuid>,
+    transcript: Transcript,
+}
+
+impl Repl {
+    pub fn new(client: ChatClient, transcript: Transcript) -> Self {
+        Self {
+            client,
+            chat_id: None,
+            transcript,
+        }
+    }
+
+    // resume continues an existing chat
+    pub async fn resume(&mut self, chat_id: Uuid) -> Result<(), ClientError> {
+        let chat = self.client.get_chat(chat_id).await?;
+        println!("{}", describe(&chat));
+        self.open(chat_id);
+        Ok(())
+    }
+
+    // run reads commands until /end or the end of the input; a failed command is reported
+    // and the session goes on
+    pub async fn run(mut self) -> std::io::Result<()> {
+        println!("type a message to chat, /help for commands");
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+        loop {
+            print!("> ");
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next_line().await? else {
+                println!();
+                return Ok(());
+            };
+
+            let command = match parse_line(&line) {
+                Ok(Some(command)) => command,
+                Ok(None) => continue,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+            };
+            let end = command == ReplCommand::End;
+            match self.execute(command).await {
+                Ok(()) if end => return Ok(()),
+                Ok(()) => {}
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+    }
+
+    async fn execute(&mut self, command: ReplCommand) -> Result<(), ClientError> {
+        let update = match command {
+            ReplCommand::Message(message) => return self.send(&message).await,
+            ReplCommand::Help => {
+                println!("{}", HELP);
+                return Ok(());
+            }
+            ReplCommand::System(system_message) => ChatUpdate {
+                system_message: Some(system_message),
+                ..Default::default()
+            },
+            ReplCommand::Model(model) => ChatUpdate {
+                model: Some(model),
+                ..Default::default()
+            },
+            ReplCommand::Temperature(temperature) => ChatUpdate {
+                temperature: Some(temperature),
+                ..Default::default()
+            },
+            ReplCommand::End => ChatUpdate {
+                status: Some("ended".to_string()),
+                ..Default::default()
+            },
+        };
+
+        let Some(chat_id) = self.chat_id else {
+            if update.status.is_none() {
+                eprintln!("send a message first, the chat starts with it");
+            }
+            return Ok(());
+        };
+        let chat = self.client.update_chat(chat_id, &update).await?;
+        println!("{}", describe(&chat));
+
+        let note = match update {
+            ChatUpdate {
+                system_message: Some(system_message),
+                ..
+            } => format!("system message: {}", system_message),
+            ChatUpdate {
+                status: Some(_), ..
+            } => "chat ended".to_string(),
+            _ => format!("model {}, temperature {}", chat.model, chat.temperature),
+        };
+        self.record(|transcript| transcript.note(&note));
+
+        Ok(())
+    }
+
+    // send streams the reply to the open chat, the first message starts the chat and its
+    // reply is printed once complete
+    async fn send(&mut self, message: &str) -> Result<(), ClientError> {
+        let reply = match self.chat_id {
+            Some(chat_id) => stream(&self.client, chat_id, message).await?,
+            None => {
+                let output = self
+                    .client
+                    .create_chat(message, None, &HashMap::new())
+                    .await?;
+                println!("{}", output.content);
+                println!("(chat {})", output.chat_id);
+                self.open(output.chat_id);
+                output.content
+            }
+        };
+
+        self.record(|transcript| transcript.user(message));
+        self.record(|transcript| transcript.assistant(&reply));
+        Ok(())
+    }
+
+    fn open(&mut self, chat_id: Uuid) {
+        self.chat_id = Some(chat_id);
+        match self.transcript.open(chat_id) {
+            Ok(path) => println!("transcript: {}", path.display()),
+            Err(err) => eprintln!("transcript is not saved: {}", err),
+        }
+    }
+
+    // record keeps the session going when the transcript cannot be written
+    fn record(&mut self, write: impl FnOnce(&mut Transcript) -> std::io::Result<()>) {
+        if let Err(err) = write(&mut self.transcript) {
+            eprintln!("transcript is not saved: {}", err);
+        }
+    }
+}
+
+fn describe(chat: &ChatOutputDTO) -> String {
+    format!(
+        "chat {} · {} · model {} · temperature {}",
+        chat.id, chat.status, chat.model, chat.temperature
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line("   "), Ok(None));
+        assert_eq!(
+            parse_line(" Hello! "),
+            Ok(Some(ReplCommand::Message("Hello!".to_string())))
+        );
+        assert_eq!(
+            parse_line("/system  Answer like a pirate."),
+            Ok(Some(ReplCommand::System(
+                "Answer like a pirate.".to_string()
+            )))
+        );
+        assert_eq!(
+            parse_line("/model gpt-4o"),
+            Ok(Some(ReplCommand::Model("gpt-4o".to_string())))
+        );
+        assert_eq!(
+            parse_line("/temperature 0.3"),
+            Ok(Some(ReplCommand::Temperature(0.3)))
+        );
+        assert_eq!(parse_line("/end"), Ok(Some(ReplCommand::End)));
+        assert_eq!(parse_line("/help"), Ok(Some(ReplCommand::Help)));
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert_eq!(
+            parse_line("/model"),
+            Err("usage: /model <name>".to_string())
+        );
+        assert!(parse_line("/temperature warm").is_err());
+        assert!(parse_line("/quit").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+// Transcript appends the conversation to <dir>/<chat_id>.md as it happens, a chat opened again
+// continues its own file
+pub struct Transcript {
+    dir: PathBuf,
+    file: Option<(PathBuf, File)>,
+}
+
+impl Transcript {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, file: None }
+    }
+
+    // default_dir is ~/.chat-cli/transcripts, or a transcripts directory in the working
+    // directory when there is no home
+    pub fn default_dir() -> PathBuf {
+        match std::env::var_os("HOME") {
+            Some(home) => Path::new(&home).join(".chat-cli").join("transcripts"),
+            None => PathBuf::from("transcripts"),
+        }
+    }
+
+    pub fn open(&mut self, chat_id: Uuid) -> io::Result<&Path> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.md", chat_id));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(self.file.insert((path, file)).0.as_path())
+    }
+
+    pub fn user(&mut self, content: &str) -> io::Result<()> {
+        self.write(&entry("you", content))
+    }
+
+    pub fn assistant(&mut self, content: &str) -> io::Result<()> {
+        self.write(&entry("assistant", content))
+    }
+
+    // note records a change to the chat, such as a new model
+    pub fn note(&mut self, content: &str) -> io::Result<()> {
+        self.write(&format!("> {}\n\n", content))
+    }
+
+    // write is a no-op until a chat is open
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        match &mut self.file {
+            Some((_, file)) => file.write_all(text.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn entry(author: &str, content: &str) -> String {
+    format!(
+        "### {} · {}\n\n{}\n\n",
+        author,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
+        content.trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript() {
+        let dir = std::env::temp_dir().join(format!("chat-cli-{}", Uuid::new_v4()));
+        let chat_id = Uuid::new_v4();
+        let mut transcript = Transcript::new(dir.clone());
+
+        transcript.user("ignored before a chat is open").unwrap();
+        let path = transcript.open(chat_id).unwrap().to_path_buf();
+        transcript.user("Hello!").unwrap();
+        transcript.assistant("Hi, how can I help?\n").unwrap();
+        transcript.note("model gpt-4o").unwrap();
+
+        let mut reopened = Transcript::new(dir.clone());
+        reopened.open(chat_id).unwrap();
+        reopened.user("Still there?").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(path, dir.join(format!("{}.md", chat_id)));
+        assert!(!content.contains("ignored"));
+        assert!(content.starts_with("### you · "));
+        assert!(content.contains("Hello!\n\n### assistant · "));
+        assert!(content.contains("Hi, how can I help?\n\n> model gpt-4o\n\n### you · "));
+        assert!(content.ends_with("Still there?\n\n"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
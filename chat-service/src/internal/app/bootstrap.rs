@@ -0,0 +1,319 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::app::container::Container;
+use crate::internal::app::error::AppError;
+use crate::internal::app::gateways::Gateways;
+use crate::internal::config::error::SettingsError;
+use crate::internal::config::settings::Settings;
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::gateway::health::HealthCheck;
+use crate::internal::domain::gateway::moderation::ModerationGateway;
+use crate::internal::domain::gateway::speech::SpeechGateway;
+use crate::internal::domain::gateway::token_verifier::TokenVerifier;
+use crate::internal::domain::gateway::transcription::TranscriptionGateway;
+use crate::internal::infra::anthropic::chat_completion::{
+    AnthropicGateway, DEFAULT_BASE_URL as ANTHROPIC_BASE_URL,
+};
+use crate::internal::infra::blob::gzip::GzipBlobStore;
+use crate::internal::infra::blob::s3::S3BlobStore;
+use crate::internal::infra::blob::sigv4::SigV4;
+use crate::internal::infra::cache::chat::{CachedChatRepository, CachedUnitOfWork};
+use crate::internal::infra::cache::redis::{RedisChatCache, RedisCompletionCache};
+use crate::internal::infra::health::cached::CachedHealthCheck;
+use crate::internal::infra::health::http::HttpHealthCheck;
+use crate::internal::infra::jwt::jwks::{JwksVerifier, JwtConfig};
+use crate::internal::infra::ollama::chat_completion::OllamaGateway;
+use crate::internal::infra::openai::chat_completion::OpenAIGateway;
+use crate::internal::infra::openai::embeddings::OpenAIEmbeddingsGateway;
+use crate::internal::infra::openai::endpoint::{
+    AzureConfig, DEFAULT_AZURE_API_VERSION, DEFAULT_BASE_URL,
+};
+use crate::internal::infra::openai::moderation::OpenAIModerationGateway;
+use crate::internal::infra::openai::speech::OpenAISpeechGateway;
+use crate::internal::infra::openai::transcription::OpenAITranscriptionGateway;
+use crate::internal::infra::provider::circuit_breaker::CircuitBreaker;
+use crate::internal::infra::provider::router::ProviderRouter;
+use crate::internal::infra::repository::factory::Repositories;
+
+// bootstrap builds the container the binary serves from: the chat repository and the response
+// cache are put in Redis when it is configured, and every gateway talks to the configured
+// provider
+pub async fn bootstrap(
+    settings: Settings,
+    mut repositories: Repositories,
+) -> Result<Container, AppError> {
+    let mut checks: Vec<Arc<dyn HealthCheck>> = vec![];
+    if let Some(redis_url) = &settings.cache.redis_url {
+        let cache = Arc::new(
+            RedisChatCache::connect(redis_url, Duration::from_secs(settings.cache.ttl_secs))
+                .await
+                .map_err(|e| AppError::Connect {
+                    service: "redis cache",
+                    message: e.to_string(),
+                })?,
+        );
+        checks.push(cache.clone());
+        repositories.chats = Arc::new(CachedChatRepository::new(repositories.chats, cache.clone()));
+        repositories.unit_of_work =
+            Arc::new(CachedUnitOfWork::new(repositories.unit_of_work, cache));
+    }
+
+    let mut gateways = gateways(&settings)?;
+    let redis_url = settings.cache.redis_url.as_ref();
+    if let Some(redis_url) = redis_url.filter(|_| settings.response_cache.enabled) {
+        let cache = RedisCompletionCache::connect(
+            redis_url,
+            Duration::from_secs(settings.response_cache.ttl_secs),
+        )
+        .await
+        .map_err(|e| AppError::Connect {
+            service: "redis response cache",
+            message: e.to_string(),
+        })?;
+        gateways.completion_cache = Some(Arc::new(cache));
+    }
+    checks.append(&mut gateways.checks);
+    gateways.checks = checks;
+
+    Container::build(settings, repositories, gateways).await
+}
+
+// gateways talks to the providers of the settings, the optional services are only built when
+// they are enabled
+pub fn gateways(settings: &Settings) -> Result<Gateways, SettingsError> {
+    Ok(Gateways {
+        chat_completion: Arc::new(provider_router(settings)),
+        embeddings: embeddings_gateway(settings),
+        moderation: moderation_gateway(settings),
+        transcription: transcription_gateway(settings),
+        speech: speech_gateway(settings),
+        token_verifier: token_verifier(settings),
+        blob_store: blob_store(settings)?,
+        completion_cache: None,
+        checks: provider_checks(settings)?,
+    })
+}
+
+// moderation_gateway screens user messages with OpenAI when moderation is enabled
+fn moderation_gateway(settings: &Settings) -> Option<Arc<dyn ModerationGateway>> {
+    if !settings.moderation.enabled {
+        return None;
+    }
+
+    let openai = &settings.openai;
+    let mut gateway = match &openai.base_url {
+        Some(base_url) => {
+            OpenAIModerationGateway::with_base_url(openai.api_key.clone(), base_url.clone())
+        }
+        None => OpenAIModerationGateway::new(openai.api_key.clone()),
+    }
+    .with_retry_policy(settings.retry_policy());
+    if let Some(model) = &settings.moderation.model {
+        gateway = gateway.with_model(model.clone());
+    }
+
+    Some(Arc::new(gateway))
+}
+
+// token_verifier accepts access tokens signed by the identity provider when JWT auth is set up
+fn token_verifier(settings: &Settings) -> Option<Arc<dyn TokenVerifier>> {
+    let jwt = settings.auth.jwt.as_ref()?;
+
+    Some(Arc::new(JwksVerifier::new(JwtConfig {
+        jwks_url: jwt.jwks_url.clone(),
+        issuer: jwt.issuer.clone(),
+        audience: jwt.audience.clone(),
+        user_claim: jwt.user_claim.clone(),
+        tenant_claim: jwt.tenant_claim.clone(),
+        cache_ttl: Duration::from_secs(jwt.jwks_cache_ttl_secs),
+    })))
+}
+
+// blob_store keeps the archived histories gzipped in the bucket when one is set
+fn blob_store(settings: &Settings) -> Result<Option<Arc<dyn BlobStore>>, SettingsError> {
+    let archive = &settings.archive;
+    let Some(bucket) = &archive.bucket else {
+        return Ok(None);
+    };
+
+    let signer = SigV4::new(
+        archive.access_key_id.clone().unwrap_or_default(),
+        archive.secret_access_key.clone().unwrap_or_default(),
+        archive.region.clone(),
+    );
+    let s3 = S3BlobStore::new(
+        &archive.endpoint,
+        bucket.clone(),
+        archive.prefix.clone(),
+        signer,
+        settings.archive_timeout(),
+    )
+    .map_err(|e| SettingsError::Invalid(format!("archive.endpoint: {}", e)))?;
+
+    Ok(Some(Arc::new(GzipBlobStore::new(Arc::new(s3)))))
+}
+
+// embeddings_gateway embeds documents and messages with OpenAI when retrieval is enabled
+fn embeddings_gateway(settings: &Settings) -> Option<Arc<dyn EmbeddingsGateway>> {
+    if !settings.rag.enabled {
+        return None;
+    }
+
+    let openai = &settings.openai;
+    let mut embeddings = match &openai.base_url {
+        Some(base_url) => {
+            OpenAIEmbeddingsGateway::with_base_url(openai.api_key.clone(), base_url.clone())
+        }
+        None => OpenAIEmbeddingsGateway::new(openai.api_key.clone()),
+    }
+    .with_model(settings.embeddings.model.clone())
+    .with_retry_policy(settings.retry_policy());
+    if let Some(dimensions) = settings.embeddings.dimensions {
+        embeddings = embeddings.with_dimensions(dimensions);
+    }
+
+    Some(Arc::new(embeddings))
+}
+
+// transcription_gateway transcribes recorded messages with OpenAI when transcription is enabled
+fn transcription_gateway(settings: &Settings) -> Option<Arc<dyn TranscriptionGateway>> {
+    if !settings.transcription.enabled {
+        return None;
+    }
+
+    let openai = &settings.openai;
+    let transcription = match &openai.base_url {
+        Some(base_url) => {
+            OpenAITranscriptionGateway::with_base_url(openai.api_key.clone(), base_url.clone())
+        }
+        None => OpenAITranscriptionGateway::new(openai.api_key.clone()),
+    }
+    .with_model(settings.transcription.model.clone())
+    .with_retry_policy(settings.retry_policy());
+
+    Some(Arc::new(transcription))
+}
+
+// speech_gateway reads replies aloud with OpenAI when speech is enabled
+fn speech_gateway(settings: &Settings) -> Option<Arc<dyn SpeechGateway>> {
+    if !settings.speech.enabled {
+        return None;
+    }
+
+    let openai = &settings.openai;
+    let speech = match &openai.base_url {
+        Some(base_url) => {
+            OpenAISpeechGateway::with_base_url(openai.api_key.clone(), base_url.clone())
+        }
+        None => OpenAISpeechGateway::new(openai.api_key.clone()),
+    }
+    .with_model(settings.speech.model.clone())
+    .with_retry_policy(settings.retry_policy());
+
+    Some(Arc::new(speech))
+}
+
+// provider_checks probes the API of every provider in the model chain, results are cached
+// so readiness probes do not hit the providers every few seconds
+fn provider_checks(settings: &Settings) -> Result<Vec<Arc<dyn HealthCheck>>, SettingsError> {
+    if !settings.health.check_providers {
+        return Ok(vec![]);
+    }
+
+    let ttl = Duration::from_secs(settings.health.provider_ttl_secs);
+    let checks = settings
+        .providers()?
+        .into_iter()
+        .filter_map(|provider| {
+            let url = match provider.as_str() {
+                "openai" => match (&settings.openai.azure, &settings.openai.base_url) {
+                    (Some(azure), _) => azure.endpoint.clone(),
+                    (None, Some(base_url)) => format!("{}/models", base_url.trim_end_matches('/')),
+                    (None, None) => format!("{}/models", DEFAULT_BASE_URL),
+                },
+                "anthropic" => format!(
+                    "{}/models",
+                    settings
+                        .anthropic
+                        .base_url
+                        .as_deref()
+                        .unwrap_or(ANTHROPIC_BASE_URL)
+                        .trim_end_matches('/')
+                ),
+                "ollama" => format!(
+                    "{}/api/tags",
+                    settings.ollama.base_url.trim_end_matches('/')
+                ),
+                _ => return None,
+            };
+            let check = HttpHealthCheck::new(format!("provider:{}", provider), url);
+
+            Some(Arc::new(CachedHealthCheck::new(Arc::new(check), ttl)) as Arc<dyn HealthCheck>)
+        })
+        .collect();
+
+    Ok(checks)
+}
+
+// provider_router registers a gateway per configured provider, each behind its own circuit breaker
+fn provider_router(settings: &Settings) -> ProviderRouter {
+    let retry = settings.retry_policy();
+    let breaker = settings.circuit_breaker_config();
+    let guard = |provider: &str,
+                 gateway: Arc<dyn ChatCompletionGateway>|
+     -> Arc<dyn ChatCompletionGateway> {
+        match breaker {
+            Some(config) => Arc::new(CircuitBreaker::new(provider, gateway, config)),
+            None => gateway,
+        }
+    };
+
+    let openai = &settings.openai;
+    let openai_gateway = match (&openai.azure, &openai.base_url) {
+        (Some(azure), _) => OpenAIGateway::azure(
+            openai.api_key.clone(),
+            AzureConfig {
+                endpoint: azure.endpoint.clone(),
+                deployment: azure.deployment.clone(),
+                api_version: azure
+                    .api_version
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string()),
+            },
+        ),
+        (None, Some(base_url)) => {
+            OpenAIGateway::with_base_url(openai.api_key.clone(), base_url.clone())
+        }
+        (None, None) => OpenAIGateway::new(openai.api_key.clone()),
+    }
+    .with_retry_policy(retry);
+
+    let mut router = ProviderRouter::new()
+        .with_provider("openai", guard("openai", Arc::new(openai_gateway)))
+        .with_provider(
+            "ollama",
+            guard(
+                "ollama",
+                Arc::new(
+                    OllamaGateway::new(settings.ollama.base_url.clone()).with_retry_policy(retry),
+                ),
+            ),
+        );
+
+    let anthropic = &settings.anthropic;
+    if !anthropic.api_key.is_empty() {
+        let gateway = match &anthropic.base_url {
+            Some(base_url) => {
+                AnthropicGateway::with_base_url(anthropic.api_key.clone(), base_url.clone())
+            }
+            None => AnthropicGateway::new(anthropic.api_key.clone()),
+        }
+        .with_retry_policy(retry);
+        router = router.with_provider("anthropic", guard("anthropic", Arc::new(gateway)));
+    }
+
+    router
+}
@@ -0,0 +1,373 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::app::error::AppError;
+use crate::internal::app::gateways::Gateways;
+use crate::internal::config::settings::Settings;
+use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+use crate::internal::domain::gateway::health::HealthCheck;
+use crate::internal::domain::memory_extractor::MemoryExtractor;
+use crate::internal::domain::message_indexer::MessageIndexer;
+use crate::internal::domain::moderator::Moderator;
+use crate::internal::domain::output_filter::{OutputFilter, OutputFilterStats};
+use crate::internal::domain::prompt_guard::PromptGuardPolicy;
+use crate::internal::domain::quota::QuotaEnforcer;
+use crate::internal::domain::rate_limiter::RateLimiter;
+use crate::internal::domain::redactor::Redactor;
+use crate::internal::domain::summarizer::Summarizer;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::domain::title_generator::TitleGenerator;
+use crate::internal::domain::usage_tracker::UsageTracker;
+use crate::internal::infra::cache::completion::InMemoryCompletionCache;
+use crate::internal::infra::guard::heuristic::HeuristicPromptGuard;
+use crate::internal::infra::guard::topic::ModelTopicClassifier;
+use crate::internal::infra::provider::audit::AuditedGateway;
+use crate::internal::infra::provider::billing::BilledGateway;
+use crate::internal::infra::provider::cache::{CacheStats, CachedGateway};
+use crate::internal::infra::provider::fallback::FallbackGateway;
+use crate::internal::infra::repository::factory::Repositories;
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::infra::webhook::vault::EncryptedWebhookRepository;
+use crate::internal::usecase::authenticate::usecase::AuthenticateUseCase;
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::check_readiness::usecase::CheckReadinessUseCase;
+use crate::internal::usecase::import_chat::usecase::ImportChatUseCase;
+use crate::internal::usecase::openai_chat_completion::usecase::OpenAIChatCompletionUseCase;
+
+// Container holds everything the service is made of, wired once at startup: the servers, jobs
+// and consumers are built from it, and tests build it over in-memory repositories and fake
+// gateways
+pub struct Container {
+    pub settings: Settings,
+    pub repositories: Repositories,
+    pub gateways: Gateways,
+    pub tenants: Arc<TenantRegistry>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub quota: Arc<QuotaEnforcer>,
+    // response_cache counts the hits of the response cache, None when it is disabled
+    pub response_cache: Option<Arc<CacheStats>>,
+    // output_filter counts the checks of the output filter, None when it is disabled
+    pub output_filter: Option<Arc<OutputFilterStats>>,
+    pub chat_completion: Arc<ChatCompletionUseCase>,
+    pub chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+    pub import_chat: Arc<ImportChatUseCase>,
+    pub openai_chat_completion: Arc<OpenAIChatCompletionUseCase>,
+    pub authenticate: Arc<AuthenticateUseCase>,
+    pub check_readiness: Arc<CheckReadinessUseCase>,
+    pub shutdown: Shutdown,
+}
+
+impl Container {
+    // build wires the use cases over the repositories and gateways as the settings ask; the
+    // model gateway is wrapped in the audit, fallback and response cache configured, the tenants
+    // stored through the admin API are loaded over the configured ones and the secrets of the
+    // webhooks are encrypted once a key is configured
+    pub async fn build(
+        settings: Settings,
+        mut repositories: Repositories,
+        mut gateways: Gateways,
+    ) -> Result<Self, AppError> {
+        if settings.webhooks.encryption_key.is_some() {
+            repositories.webhooks = Arc::new(EncryptedWebhookRepository::new(
+                repositories.webhooks,
+                Arc::new(settings.webhook_cipher()?),
+            ));
+        }
+        let model = settings.model()?;
+        let config = settings.chat_config()?;
+        let repository = repositories.chats.clone();
+        let users = repositories.users.clone();
+        let usage = repositories.usage.clone();
+        let templates = repositories.templates.clone();
+
+        // the audit sits under the fallback so every attempt is logged with the provider it went to
+        if settings.audit.enabled {
+            gateways.chat_completion = Arc::new(AuditedGateway::new(
+                gateways.chat_completion,
+                repositories.audit.clone(),
+            ));
+        }
+        let fallbacks = settings.fallback_models()?;
+        let timeout = settings.model_timeout();
+        if !fallbacks.is_empty() || timeout.is_some() {
+            let mut fallback = FallbackGateway::new(gateways.chat_completion, fallbacks);
+            if let Some(timeout) = timeout {
+                fallback = fallback.with_timeout(timeout);
+            }
+            gateways.chat_completion = Arc::new(fallback);
+        }
+        // the cache sits on top, a cached reply is neither sent to a provider nor logged
+        let mut response_cache = None;
+        if settings.response_cache.enabled {
+            let cache = gateways.completion_cache.clone().unwrap_or_else(|| {
+                Arc::new(InMemoryCompletionCache::new(
+                    Duration::from_secs(settings.response_cache.ttl_secs),
+                    settings.response_cache.max_entries,
+                ))
+            });
+            let cached = CachedGateway::new(gateways.chat_completion, cache);
+            response_cache = Some(cached.stats());
+            gateways.chat_completion = Arc::new(cached);
+        }
+        let gateway: Arc<dyn ChatCompletionGateway> = gateways.chat_completion.clone();
+
+        let tenants = Arc::new(settings.tenant_registry()?);
+        for tenant in repositories.tenants.list_tenants().await? {
+            tenants.register(tenant)?;
+        }
+        let rate_limiter = Arc::new(tenants.rate_limits().into_iter().fold(
+            RateLimiter::new(settings.rate_limit_config()),
+            |rate_limiter, (tenant_id, config)| rate_limiter.with_tenant_config(tenant_id, config),
+        ));
+        let quota = Arc::new(
+            QuotaEnforcer::new(usage.clone(), settings.quota_config())
+                .with_tenants(tenants.clone()),
+        );
+        let usage_tracker = Arc::new(UsageTracker::new(usage));
+        // the titles and classifications made for a user are billed to the user, the chat turns
+        // are billed by their use cases
+        let billed: Arc<dyn ChatCompletionGateway> = Arc::new(
+            BilledGateway::new(gateway.clone(), usage_tracker.clone())
+                .with_quota_enforcer(quota.clone()),
+        );
+        let summarizer = Arc::new(Summarizer::new(
+            gateway.clone(),
+            settings.summarizer_config(),
+        ));
+        let moderator = gateways.moderation.clone().map(|moderation| {
+            Arc::new(Moderator::new(moderation, repositories.moderation.clone()))
+        });
+        let prompt_guard = if settings.prompt_guard.enabled {
+            let mut guard = HeuristicPromptGuard::new();
+            if settings.prompt_guard.classifier {
+                guard = guard.with_classifier(billed.clone(), model.clone());
+            }
+            Some(Arc::new(PromptGuardPolicy::new(
+                Arc::new(guard),
+                repositories.moderation.clone(),
+                settings.prompt_guard_action()?,
+                settings.prompt_guard.threshold,
+            )))
+        } else {
+            None
+        };
+        // the banned topics are told apart by the chat model, it is only asked when the config
+        // of the response's tenant bans some
+        let output_filter = settings.output_filter.enabled.then(|| {
+            Arc::new(
+                OutputFilter::new(settings.output_filter_config())
+                    .with_tenants(tenants.clone())
+                    .with_classifier(Arc::new(ModelTopicClassifier::new(
+                        billed.clone(),
+                        model.clone(),
+                    ))),
+            )
+        });
+        let redactor = if settings.redaction.enabled {
+            Some(Arc::new(Redactor::new(
+                settings.redaction_detectors()?,
+                Arc::new(settings.redaction_cipher()?),
+                repositories.redactions.clone(),
+            )))
+        } else {
+            None
+        };
+        let title_generator = settings
+            .chat
+            .auto_title
+            .then(|| Arc::new(TitleGenerator::new(billed.clone(), repository.clone())));
+        let memory_extractor = settings.memory.enabled.then(|| {
+            let mut memory_extractor = MemoryExtractor::new(
+                gateway.clone(),
+                repositories.memories.clone(),
+                settings.memory.max_memories,
+            )
+            .with_quota_enforcer(quota.clone())
+            .with_usage_tracker(usage_tracker.clone());
+            if let Some(prompt_guard) = &prompt_guard {
+                memory_extractor = memory_extractor.with_prompt_guard(prompt_guard.clone());
+            }
+            Arc::new(memory_extractor)
+        });
+
+        let import_chat = ImportChatUseCase::new(
+            repository.clone(),
+            users.clone(),
+            model.clone(),
+            config.clone(),
+        )
+        .with_tenants(tenants.clone());
+        let mut chat_completion_stream = ChatCompletionStreamUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users.clone(),
+            model.clone(),
+            config.clone(),
+        )
+        .with_rate_limiter(rate_limiter.clone())
+        .with_quota_enforcer(quota.clone())
+        .with_usage_tracker(usage_tracker.clone())
+        .with_summarizer(summarizer.clone())
+        .with_templates(templates.clone())
+        .with_assistants(repositories.assistants.clone())
+        .with_tenants(tenants.clone())
+        .with_unit_of_work(repositories.unit_of_work.clone());
+        let mut chat_completion =
+            ChatCompletionUseCase::new(gateway, repository, users.clone(), model, config.clone())
+                .with_rate_limiter(rate_limiter.clone())
+                .with_quota_enforcer(quota.clone())
+                .with_usage_tracker(usage_tracker)
+                .with_summarizer(summarizer)
+                .with_templates(templates)
+                .with_assistants(repositories.assistants.clone())
+                .with_tenants(tenants.clone())
+                .with_idempotency(repositories.idempotency.clone(), settings.idempotency_ttl())
+                .with_unit_of_work(repositories.unit_of_work.clone());
+        if let Some(title_generator) = title_generator {
+            chat_completion_stream =
+                chat_completion_stream.with_title_generator(title_generator.clone());
+            chat_completion = chat_completion.with_title_generator(title_generator);
+        }
+        if let Some(memory_extractor) = memory_extractor {
+            chat_completion_stream =
+                chat_completion_stream.with_memory_extractor(memory_extractor.clone());
+            chat_completion = chat_completion.with_memory_extractor(memory_extractor);
+        }
+        if let Some(redactor) = redactor {
+            chat_completion_stream = chat_completion_stream.with_redactor(redactor.clone());
+            chat_completion = chat_completion.with_redactor(redactor);
+        }
+        if let Some(moderator) = moderator {
+            chat_completion_stream = chat_completion_stream.with_moderator(moderator.clone());
+            chat_completion = chat_completion.with_moderator(moderator);
+        }
+        if let Some(prompt_guard) = prompt_guard {
+            chat_completion_stream = chat_completion_stream.with_prompt_guard(prompt_guard.clone());
+            chat_completion = chat_completion.with_prompt_guard(prompt_guard);
+        }
+        if let Some(output_filter) = &output_filter {
+            chat_completion_stream =
+                chat_completion_stream.with_output_filter(output_filter.clone());
+            chat_completion = chat_completion.with_output_filter(output_filter.clone());
+        }
+        if let Some(embeddings) = &gateways.embeddings {
+            let message_indexer = Arc::new(MessageIndexer::new(
+                embeddings.clone(),
+                repositories.vectors.clone(),
+            ));
+            chat_completion_stream =
+                chat_completion_stream.with_message_indexer(message_indexer.clone());
+            chat_completion = chat_completion.with_message_indexer(message_indexer);
+        }
+
+        let chat_completion = Arc::new(chat_completion);
+        let chat_completion_stream = Arc::new(chat_completion_stream);
+        // imported user messages are screened like the ones sent to a chat
+        let import_chat = Arc::new(import_chat.with_chat_completion(chat_completion.clone()));
+        let openai_chat_completion = Arc::new(OpenAIChatCompletionUseCase::new(
+            chat_completion.clone(),
+            chat_completion_stream.clone(),
+            users.clone(),
+            config,
+        ));
+
+        let mut authenticate = AuthenticateUseCase::new(repositories.api_keys.clone(), users)
+            .with_tenants(tenants.clone());
+        if let Some(token_verifier) = &gateways.token_verifier {
+            authenticate = authenticate.with_token_verifier(token_verifier.clone());
+        }
+
+        let checks: Vec<Arc<dyn HealthCheck>> = repositories
+            .health
+            .clone()
+            .into_iter()
+            .chain(gateways.checks.iter().cloned())
+            .collect();
+        let check_readiness = Arc::new(
+            CheckReadinessUseCase::new(checks).with_timeout(settings.health_check_timeout()),
+        );
+
+        Ok(Self {
+            settings,
+            repositories,
+            gateways,
+            tenants,
+            rate_limiter,
+            quota,
+            response_cache,
+            output_filter: output_filter.map(|output_filter| output_filter.stats()),
+            chat_completion,
+            chat_completion_stream,
+            import_chat,
+            openai_chat_completion,
+            authenticate: Arc::new(authenticate),
+            check_readiness,
+            shutdown: Shutdown::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::testing::gateway::{FakeCompletionGateway, DEFAULT_REPLY};
+    use crate::internal::usecase::chat_completion::dto::{
+        ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+    };
+
+    #[tokio::test]
+    async fn test_build() {
+        let repositories = Repositories::memory();
+        let user_id = Uuid::new_v4();
+        repositories
+            .users
+            .create_user(&User::new(
+                user_id,
+                &user_id.to_string(),
+                "Ada",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+
+        let container = Container::build(
+            Settings::default(),
+            repositories,
+            Gateways::new(Arc::new(FakeCompletionGateway::new())),
+        )
+        .await
+        .unwrap();
+
+        let output = container
+            .chat_completion
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.content, DEFAULT_REPLY);
+
+        let chat = container
+            .repositories
+            .chats
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap();
+        assert!(chat.is_some());
+    }
+}
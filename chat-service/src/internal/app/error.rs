@@ -0,0 +1,23 @@
+use crate::internal::config::error::SettingsError;
+use crate::internal::domain::error::ConfigError;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error("could not connect to {service}: {message}")]
+    Connect {
+        service: &'static str,
+        message: String,
+    },
+    #[error("{server} server stopped: {message}")]
+    Server {
+        server: &'static str,
+        message: String,
+    },
+}
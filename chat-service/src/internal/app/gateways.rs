@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::gateway::health::HealthCheck;
+use crate::internal::domain::gateway::moderation::ModerationGateway;
+use crate::internal::domain::gateway::speech::SpeechGateway;
+use crate::internal::domain::gateway::token_verifier::TokenVerifier;
+use crate::internal::domain::gateway::transcription::TranscriptionGateway;
+use crate::internal::infra::cache::completion::CompletionCache;
+
+// Gateways are the external services behind the use cases, built from the settings at startup
+// and replaced by fakes in tests; an optional one is only set when its feature is enabled
+pub struct Gateways {
+    pub chat_completion: Arc<dyn ChatCompletionGateway>,
+    pub embeddings: Option<Arc<dyn EmbeddingsGateway>>,
+    pub moderation: Option<Arc<dyn ModerationGateway>>,
+    pub transcription: Option<Arc<dyn TranscriptionGateway>>,
+    pub speech: Option<Arc<dyn SpeechGateway>>,
+    pub token_verifier: Option<Arc<dyn TokenVerifier>>,
+    // blob_store keeps the histories of cold chats when archiving is set up
+    pub blob_store: Option<Arc<dyn BlobStore>>,
+    // completion_cache stores the cached replies when the response cache is enabled, replies
+    // are kept in the process when it is not set
+    pub completion_cache: Option<Arc<dyn CompletionCache>>,
+    // checks are probed for readiness along with the database
+    pub checks: Vec<Arc<dyn HealthCheck>>,
+}
+
+impl Gateways {
+    // new only talks to the model, every optional service is off
+    pub fn new(chat_completion: Arc<dyn ChatCompletionGateway>) -> Self {
+        Self {
+            chat_completion,
+            embeddings: None,
+            moderation: None,
+            transcription: None,
+            speech: None,
+            token_verifier: None,
+            blob_store: None,
+            completion_cache: None,
+            checks: vec![],
+        }
+    }
+}
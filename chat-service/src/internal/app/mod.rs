@@ -0,0 +1,5 @@
+pub mod bootstrap;
+pub mod container;
+pub mod error;
+pub mod gateways;
+pub mod server;
@@ -0,0 +1,469 @@
+use std::sync::Arc;
+
+use crate::internal::app::container::Container;
+use crate::internal::app::error::AppError;
+use crate::internal::domain::gateway::event_publisher::EventPublisher;
+use crate::internal::infra::event::fanout::FanoutPublisher;
+use crate::internal::infra::event::redis::RedisStreamPublisher;
+use crate::internal::infra::event::webhook::WebhookPublisher;
+use crate::internal::infra::grpc::server::GrpcServer;
+use crate::internal::infra::job::archive::ArchiveJob;
+use crate::internal::infra::job::completion::CompletionJob;
+use crate::internal::infra::job::purge::PurgeJob;
+use crate::internal::infra::job::relay::RelayJob;
+use crate::internal::infra::job::schedule::ScheduleJob;
+use crate::internal::infra::job::webhook::WebhookJob;
+#[cfg(feature = "kafka")]
+use crate::internal::infra::kafka::consumer::{KafkaChatConsumer, KafkaConfig};
+#[cfg(feature = "kafka")]
+use crate::internal::infra::kafka::handler::KafkaRequestHandler;
+use crate::internal::infra::shutdown::signal;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::infra::web::resume::ReplyStreams;
+use crate::internal::infra::web::server::WebServer;
+use crate::internal::infra::webhook::http::HttpWebhookSender;
+use crate::internal::usecase::archive_chats::usecase::ArchiveChatsUseCase;
+use crate::internal::usecase::batch_completion::usecase::BatchCompletionUseCase;
+use crate::internal::usecase::cancel_scheduled_message::usecase::CancelScheduledMessageUseCase;
+use crate::internal::usecase::create_api_key::usecase::CreateApiKeyUseCase;
+use crate::internal::usecase::create_assistant::usecase::CreateAssistantUseCase;
+use crate::internal::usecase::create_prompt_template::usecase::CreatePromptTemplateUseCase;
+use crate::internal::usecase::create_tenant::usecase::CreateTenantUseCase;
+use crate::internal::usecase::create_user::usecase::CreateUserUseCase;
+use crate::internal::usecase::create_webhook::usecase::CreateWebhookUseCase;
+use crate::internal::usecase::delete_assistant::usecase::DeleteAssistantUseCase;
+use crate::internal::usecase::delete_chat::usecase::DeleteChatUseCase;
+use crate::internal::usecase::delete_document::usecase::DeleteDocumentUseCase;
+use crate::internal::usecase::delete_memory::usecase::DeleteMemoryUseCase;
+use crate::internal::usecase::delete_webhook::usecase::DeleteWebhookUseCase;
+use crate::internal::usecase::deliver_webhooks::usecase::DeliverWebhooksUseCase;
+use crate::internal::usecase::export_chat::usecase::ExportChatUseCase;
+use crate::internal::usecase::fork_chat::usecase::ForkChatUseCase;
+use crate::internal::usecase::get_batch::usecase::GetBatchUseCase;
+use crate::internal::usecase::get_billing_report::usecase::GetBillingReportUseCase;
+use crate::internal::usecase::get_chat::usecase::GetChatUseCase;
+use crate::internal::usecase::get_job::usecase::GetJobUseCase;
+use crate::internal::usecase::get_quota::usecase::GetQuotaUseCase;
+use crate::internal::usecase::get_usage::usecase::GetUsageUseCase;
+use crate::internal::usecase::get_usage_summary::usecase::GetUsageSummaryUseCase;
+use crate::internal::usecase::ingest_document::usecase::IngestDocumentUseCase;
+use crate::internal::usecase::list_assistants::usecase::ListAssistantsUseCase;
+use crate::internal::usecase::list_audit_entries::usecase::ListAuditEntriesUseCase;
+use crate::internal::usecase::list_chat_messages::usecase::ListChatMessagesUseCase;
+use crate::internal::usecase::list_chats::usecase::ListChatsUseCase;
+use crate::internal::usecase::list_dead_letters::usecase::ListDeadLettersUseCase;
+use crate::internal::usecase::list_documents::usecase::ListDocumentsUseCase;
+use crate::internal::usecase::list_memories::usecase::ListMemoriesUseCase;
+use crate::internal::usecase::list_scheduled_messages::usecase::ListScheduledMessagesUseCase;
+use crate::internal::usecase::list_tenants::usecase::ListTenantsUseCase;
+use crate::internal::usecase::list_webhooks::usecase::ListWebhooksUseCase;
+use crate::internal::usecase::orchestrate_chat::usecase::OrchestrateChatUseCase;
+use crate::internal::usecase::purge_deleted_chats::usecase::PurgeDeletedChatsUseCase;
+use crate::internal::usecase::rag_chat_completion::usecase::RagChatCompletionUseCase;
+use crate::internal::usecase::regenerate_message::usecase::RegenerateMessageUseCase;
+use crate::internal::usecase::relay_events::usecase::RelayEventsUseCase;
+use crate::internal::usecase::rotate_api_key::usecase::RotateApiKeyUseCase;
+use crate::internal::usecase::run_jobs::usecase::RunJobsUseCase;
+use crate::internal::usecase::schedule_message::usecase::ScheduleMessageUseCase;
+use crate::internal::usecase::search_messages::usecase::SearchMessagesUseCase;
+use crate::internal::usecase::search_transcripts::usecase::SearchTranscriptsUseCase;
+use crate::internal::usecase::select_candidate::usecase::SelectCandidateUseCase;
+use crate::internal::usecase::send_scheduled_messages::usecase::SendScheduledMessagesUseCase;
+use crate::internal::usecase::submit_job::usecase::SubmitJobUseCase;
+use crate::internal::usecase::synthesize_speech::usecase::SynthesizeSpeechUseCase;
+use crate::internal::usecase::transcribe_message::usecase::TranscribeMessageUseCase;
+use crate::internal::usecase::update_assistant::usecase::UpdateAssistantUseCase;
+use crate::internal::usecase::update_chat::usecase::UpdateChatUseCase;
+use crate::internal::usecase::update_system_prompt::usecase::UpdateSystemPromptUseCase;
+use crate::internal::usecase::update_tenant::usecase::UpdateTenantUseCase;
+
+impl Container {
+    // web_state builds the use cases of the HTTP API, the routes of a disabled feature are not
+    // served
+    pub fn web_state(&self) -> AppState {
+        let settings = &self.settings;
+        let repositories = &self.repositories;
+        let gateways = &self.gateways;
+        let create_api_key = Arc::new(CreateApiKeyUseCase::new(
+            repositories.api_keys.clone(),
+            repositories.users.clone(),
+        ));
+
+        AppState {
+            chat_completion: self.chat_completion.clone(),
+            chat_completion_stream: self.chat_completion_stream.clone(),
+            streams: Arc::new(ReplyStreams::new(settings.stream_resume_window())),
+            request_timeout: settings.request_timeout(),
+            regenerate_message: Arc::new(RegenerateMessageUseCase::new(
+                repositories.chats.clone(),
+                self.chat_completion.clone(),
+            )),
+            select_candidate: Arc::new(SelectCandidateUseCase::new(repositories.chats.clone())),
+            rag_chat_completion: gateways.embeddings.clone().map(|embeddings| {
+                Arc::new(
+                    RagChatCompletionUseCase::new(
+                        self.chat_completion.clone(),
+                        embeddings,
+                        repositories.vectors.clone(),
+                    )
+                    .with_config(settings.rag_config()),
+                )
+            }),
+            ingest_document: gateways.embeddings.clone().map(|embeddings| {
+                Arc::new(
+                    IngestDocumentUseCase::new(
+                        repositories.documents.clone(),
+                        embeddings,
+                        repositories.vectors.clone(),
+                    )
+                    .with_chunking(settings.chunking_config())
+                    .with_max_size_bytes(settings.documents.max_size_bytes),
+                )
+            }),
+            list_documents: Arc::new(ListDocumentsUseCase::new(repositories.documents.clone())),
+            search_messages: gateways.embeddings.clone().map(|embeddings| {
+                Arc::new(
+                    SearchMessagesUseCase::new(embeddings, repositories.vectors.clone())
+                        .with_min_score(settings.rag.min_score),
+                )
+            }),
+            search_transcripts: Arc::new(SearchTranscriptsUseCase::new(repositories.chats.clone())),
+            synthesize_speech: gateways
+                .speech
+                .clone()
+                .map(|speech| Arc::new(SynthesizeSpeechUseCase::new(speech))),
+            transcribe_message: gateways.transcription.clone().map(|transcription| {
+                Arc::new(
+                    TranscribeMessageUseCase::new(transcription, self.chat_completion.clone())
+                        .with_max_size_bytes(settings.transcription.max_size_bytes),
+                )
+            }),
+            delete_document: Arc::new(DeleteDocumentUseCase::new(
+                repositories.documents.clone(),
+                repositories.vectors.clone(),
+            )),
+            list_memories: Arc::new(ListMemoriesUseCase::new(repositories.memories.clone())),
+            delete_memory: Arc::new(DeleteMemoryUseCase::new(repositories.memories.clone())),
+            batch_completion: Arc::new(BatchCompletionUseCase::new(
+                repositories.batches.clone(),
+                repositories.jobs.clone(),
+                self.chat_completion.clone(),
+                settings.batch.max_prompts,
+                settings.batch.concurrency,
+            )),
+            get_batch: Arc::new(GetBatchUseCase::new(
+                repositories.batches.clone(),
+                repositories.jobs.clone(),
+            )),
+            submit_job: Arc::new(SubmitJobUseCase::new(
+                repositories.jobs.clone(),
+                repositories.chats.clone(),
+                self.chat_completion.clone(),
+            )),
+            get_job: Arc::new(GetJobUseCase::new(repositories.jobs.clone())),
+            schedule_message: Arc::new(ScheduleMessageUseCase::new(
+                repositories.chats.clone(),
+                repositories.scheduled.clone(),
+            )),
+            list_scheduled_messages: Arc::new(ListScheduledMessagesUseCase::new(
+                repositories.scheduled.clone(),
+            )),
+            cancel_scheduled_message: Arc::new(CancelScheduledMessageUseCase::new(
+                repositories.scheduled.clone(),
+            )),
+            get_chat: Arc::new(GetChatUseCase::new(repositories.chats.clone())),
+            update_chat: Arc::new({
+                let update_chat = UpdateChatUseCase::new(repositories.chats.clone())
+                    .with_tenants(self.tenants.clone());
+                match &gateways.blob_store {
+                    Some(blob_store) => update_chat.with_blob_store(blob_store.clone()),
+                    None => update_chat,
+                }
+            }),
+            update_system_prompt: Arc::new(UpdateSystemPromptUseCase::new(
+                repositories.chats.clone(),
+                repositories.audit.clone(),
+            )),
+            delete_chat: Arc::new(
+                DeleteChatUseCase::new(repositories.chats.clone())
+                    .with_vector_store(repositories.vectors.clone()),
+            ),
+            fork_chat: Arc::new({
+                let fork_chat = ForkChatUseCase::new(repositories.chats.clone());
+                match &gateways.blob_store {
+                    Some(blob_store) => fork_chat.with_blob_store(blob_store.clone()),
+                    None => fork_chat,
+                }
+            }),
+            export_chat: Arc::new({
+                let export_chat = ExportChatUseCase::new(repositories.chats.clone());
+                match &gateways.blob_store {
+                    Some(blob_store) => export_chat.with_blob_store(blob_store.clone()),
+                    None => export_chat,
+                }
+            }),
+            import_chat: self.import_chat.clone(),
+            list_chat_messages: Arc::new({
+                let list_chat_messages = ListChatMessagesUseCase::new(repositories.chats.clone());
+                match &gateways.blob_store {
+                    Some(blob_store) => list_chat_messages.with_blob_store(blob_store.clone()),
+                    None => list_chat_messages,
+                }
+            }),
+            openai_chat_completion: self.openai_chat_completion.clone(),
+            list_chats: Arc::new(ListChatsUseCase::new(
+                repositories.chats.clone(),
+                repositories.usage.clone(),
+            )),
+            get_usage: Arc::new(GetUsageUseCase::new(repositories.usage.clone())),
+            get_quota: Arc::new(GetQuotaUseCase::new(self.quota.clone())),
+            create_user: Arc::new(
+                CreateUserUseCase::new(repositories.users.clone(), repositories.api_keys.clone())
+                    .with_tenants(self.tenants.clone()),
+            ),
+            create_api_key: create_api_key.clone(),
+            create_prompt_template: Arc::new(CreatePromptTemplateUseCase::new(
+                repositories.templates.clone(),
+            )),
+            list_audit_entries: Arc::new(ListAuditEntriesUseCase::new(repositories.audit.clone())),
+            list_tenants: Arc::new(ListTenantsUseCase::new(self.tenants.clone())),
+            create_tenant: Arc::new(CreateTenantUseCase::new(
+                repositories.tenants.clone(),
+                self.tenants.clone(),
+                self.rate_limiter.clone(),
+            )),
+            update_tenant: Arc::new(UpdateTenantUseCase::new(
+                repositories.tenants.clone(),
+                self.tenants.clone(),
+                self.rate_limiter.clone(),
+            )),
+            rotate_api_key: Arc::new(RotateApiKeyUseCase::new(
+                repositories.api_keys.clone(),
+                create_api_key,
+            )),
+            create_webhook: Arc::new(CreateWebhookUseCase::new(
+                repositories.webhooks.clone(),
+                self.tenants.clone(),
+            )),
+            list_webhooks: Arc::new(ListWebhooksUseCase::new(repositories.webhooks.clone())),
+            delete_webhook: Arc::new(DeleteWebhookUseCase::new(repositories.webhooks.clone())),
+            list_dead_letters: Arc::new(ListDeadLettersUseCase::new(repositories.webhooks.clone())),
+            create_assistant: Arc::new(CreateAssistantUseCase::new(
+                repositories.assistants.clone(),
+                self.tenants.clone(),
+            )),
+            list_assistants: Arc::new(ListAssistantsUseCase::new(repositories.assistants.clone())),
+            update_assistant: Arc::new(UpdateAssistantUseCase::new(
+                repositories.assistants.clone(),
+                self.tenants.clone(),
+            )),
+            delete_assistant: Arc::new(DeleteAssistantUseCase::new(
+                repositories.assistants.clone(),
+            )),
+            orchestrate_chat: Arc::new(OrchestrateChatUseCase::new(
+                self.chat_completion_stream.clone(),
+                repositories.assistants.clone(),
+            )),
+            get_usage_summary: Arc::new(GetUsageSummaryUseCase::new(repositories.usage.clone())),
+            get_billing_report: Arc::new(GetBillingReportUseCase::new(repositories.usage.clone())),
+            response_cache: self.response_cache.clone(),
+            output_filter: self.output_filter.clone(),
+            admin_token: settings.auth.admin_token.clone(),
+            authenticate: self.authenticate.clone(),
+            check_readiness: self.check_readiness.clone(),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
+    pub fn grpc_server(&self) -> GrpcServer {
+        GrpcServer::new(
+            self.chat_completion_stream.clone(),
+            self.authenticate.clone(),
+            self.settings.server.grpc_port,
+        )
+        .with_readiness(self.check_readiness.clone())
+        .with_shutdown(self.shutdown.clone())
+        .with_request_timeout(self.settings.request_timeout())
+        .with_reflection(self.settings.server.grpc_reflection)
+    }
+
+    // spawn_jobs starts the background work the settings enable: purging deleted chats,
+    // archiving the histories of inactive ones, sending scheduled messages, answering
+    // completion jobs, relaying chat events to Redis and the tenants' webhooks, calling those
+    // webhooks and answering chat requests from Kafka
+    pub async fn spawn_jobs(&self) -> Result<(), AppError> {
+        let settings = &self.settings;
+
+        if settings.purge.enabled {
+            let mut purge = PurgeDeletedChatsUseCase::new(
+                self.repositories.chats.clone(),
+                settings.purge_retention(),
+            );
+            if let Some(blob_store) = &self.gateways.blob_store {
+                purge = purge.with_blob_store(blob_store.clone());
+            }
+            tokio::spawn(
+                PurgeJob::new(Arc::new(purge), settings.purge_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        if let Some(blob_store) = &self.gateways.blob_store {
+            let archive = ArchiveChatsUseCase::new(
+                self.repositories.chats.clone(),
+                blob_store.clone(),
+                settings.archive_inactivity(),
+                settings.archive.batch_size,
+            );
+            tokio::spawn(
+                ArchiveJob::new(Arc::new(archive), settings.archive_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        if settings.schedule.enabled {
+            let send = SendScheduledMessagesUseCase::new(
+                self.repositories.scheduled.clone(),
+                self.chat_completion.clone(),
+                settings.schedule.batch_size,
+            );
+            tokio::spawn(
+                ScheduleJob::new(Arc::new(send), settings.schedule_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        if settings.jobs.enabled {
+            let mut run = RunJobsUseCase::new(
+                self.repositories.jobs.clone(),
+                self.chat_completion.clone(),
+                settings.jobs.batch_size,
+            );
+            if settings.webhooks.enabled {
+                run = run.with_webhooks(self.repositories.webhooks.clone());
+            }
+            tokio::spawn(
+                CompletionJob::new(Arc::new(run), settings.job_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        let mut publishers: Vec<Arc<dyn EventPublisher>> = Vec::new();
+        if let Some(redis_url) = &settings.events.redis_url {
+            let publisher = RedisStreamPublisher::connect(
+                redis_url,
+                &settings.events.stream,
+                settings.events.stream_max_len,
+            )
+            .await
+            .map_err(|e| AppError::Connect {
+                service: "redis event stream",
+                message: e.to_string(),
+            })?;
+            publishers.push(Arc::new(publisher));
+        }
+        if settings.webhooks.enabled {
+            publishers.push(Arc::new(WebhookPublisher::new(
+                self.repositories.webhooks.clone(),
+            )));
+
+            let sender = HttpWebhookSender::new(settings.webhook_timeout()).map_err(|e| {
+                AppError::Connect {
+                    service: "webhook client",
+                    message: e.to_string(),
+                }
+            })?;
+            let deliver = DeliverWebhooksUseCase::new(
+                self.repositories.webhooks.clone(),
+                Arc::new(sender),
+                settings.webhooks.batch_size,
+                settings.webhooks.max_attempts,
+                settings.webhook_backoff(),
+            );
+            tokio::spawn(
+                WebhookJob::new(Arc::new(deliver), settings.webhook_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        if !publishers.is_empty() {
+            let publisher: Arc<dyn EventPublisher> = if publishers.len() == 1 {
+                publishers.remove(0)
+            } else {
+                Arc::new(FanoutPublisher::new(publishers))
+            };
+            let relay = RelayEventsUseCase::new(
+                self.repositories.outbox.clone(),
+                publisher,
+                settings.events.batch_size,
+            );
+            tokio::spawn(
+                RelayJob::new(Arc::new(relay), settings.event_relay_interval())
+                    .run(self.shutdown.clone()),
+            );
+        }
+        // settings with brokers are refused at startup by builds without kafka
+        #[cfg(feature = "kafka")]
+        if let Some(brokers) = &settings.kafka.brokers {
+            let consumer = KafkaChatConsumer::new(
+                &KafkaConfig {
+                    brokers: brokers.clone(),
+                    group_id: settings.kafka.group_id.clone(),
+                    request_topic: settings.kafka.request_topic.clone(),
+                    reply_topic: settings.kafka.reply_topic.clone(),
+                },
+                KafkaRequestHandler::new(
+                    self.chat_completion.clone(),
+                    self.chat_completion_stream.clone(),
+                ),
+            )
+            .map_err(|e| AppError::Connect {
+                service: "kafka",
+                message: e.to_string(),
+            })?;
+            tokio::spawn(consumer.run(self.shutdown.clone()));
+        }
+
+        Ok(())
+    }
+
+    // serve runs the jobs and both servers until a signal, or whichever server stops first,
+    // takes the process down: both servers stop accepting requests, in-flight ones get until
+    // the shutdown timeout to finish, then the pool closes
+    pub async fn serve(self) -> Result<(), AppError> {
+        self.spawn_jobs().await?;
+
+        let web =
+            tokio::spawn(WebServer::new(self.web_state(), self.settings.server.http_port).start());
+        let grpc = tokio::spawn(self.grpc_server().start());
+
+        let result = tokio::select! {
+            result = web => stopped("http", result),
+            result = grpc => stopped("grpc", result),
+            _ = signal::terminated() => Ok(()),
+        };
+        self.shutdown.trigger();
+        if !self.shutdown.drain(self.settings.shutdown_timeout()).await {
+            tracing::warn!(
+                in_flight = self.shutdown.in_flight(),
+                "shutdown timeout elapsed, abandoning in-flight requests"
+            );
+        }
+        self.repositories.close().await;
+
+        result
+    }
+}
+
+// stopped flattens the outcome of a server task
+fn stopped<E: std::fmt::Display>(
+    server: &'static str,
+    result: Result<Result<(), E>, tokio::task::JoinError>,
+) -> Result<(), AppError> {
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(AppError::Server {
+            server,
+            message: e.to_string(),
+        }),
+        Err(e) => Err(AppError::Server {
+            server,
+            message: e.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,19 @@
+use crate::internal::domain::error::ConfigError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("could not read config file {path}: {message}")]
+    Read { path: String, message: String },
+    #[error("could not parse config file {path}: {message}")]
+    Parse { path: String, message: String },
+    #[error("unsupported config file format {0}, expected .toml, .yaml or .yml")]
+    UnsupportedFormat(String),
+    #[error("environment variable {key} is invalid: {message}")]
+    InvalidEnv { key: String, message: String },
+    #[error("missing required setting {0}")]
+    Missing(&'static str),
+    #[error("invalid setting: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Chat(#[from] ConfigError),
+}
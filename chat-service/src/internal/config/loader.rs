@@ -0,0 +1,783 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::internal::config::error::SettingsError;
+use crate::internal::config::settings::{
+    default_jwks_cache_ttl_secs, default_user_claim, AzureSettings, JwtSettings, Settings,
+};
+
+pub const CONFIG_FILE_ENV: &str = "CONFIG_FILE";
+
+// load reads the file named by CONFIG_FILE when set, applies environment overrides and validates
+pub fn load() -> Result<Settings, SettingsError> {
+    load_with(|key| std::env::var(key).ok())
+}
+
+// load_with is load with a custom environment lookup
+pub fn load_with(env: impl Fn(&str) -> Option<String>) -> Result<Settings, SettingsError> {
+    let mut settings = match env(CONFIG_FILE_ENV) {
+        Some(path) => read_file(Path::new(&path))?,
+        None => Settings::default(),
+    };
+
+    apply_env(&mut settings, &env)?;
+    settings.validate()?;
+
+    Ok(settings)
+}
+
+// read_file parses a TOML or YAML file, picked by extension
+pub fn read_file(path: &Path) -> Result<Settings, SettingsError> {
+    let display = path.display().to_string();
+    let content = std::fs::read_to_string(path).map_err(|e| SettingsError::Read {
+        path: display.clone(),
+        message: e.to_string(),
+    })?;
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+
+    let parsed = match extension {
+        "toml" => toml::from_str(&content).map_err(|e| e.to_string()),
+        "yaml" | "yml" => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+        _ => return Err(SettingsError::UnsupportedFormat(display)),
+    };
+
+    parsed.map_err(|message| SettingsError::Parse {
+        path: display,
+        message,
+    })
+}
+
+// apply_env overrides file values with the environment, which always wins
+pub fn apply_env(
+    settings: &mut Settings,
+    env: &impl Fn(&str) -> Option<String>,
+) -> Result<(), SettingsError> {
+    if let Some(api_key) = env("OPENAI_API_KEY") {
+        settings.openai.api_key = api_key;
+    }
+    if let Some(base_url) = env("OPENAI_BASE_URL") {
+        settings.openai.base_url = Some(base_url);
+    }
+    if let Some(endpoint) = env("AZURE_OPENAI_ENDPOINT") {
+        let deployment = env("AZURE_OPENAI_DEPLOYMENT")
+            .ok_or(SettingsError::Missing("AZURE_OPENAI_DEPLOYMENT"))?;
+
+        settings.openai.azure = Some(AzureSettings {
+            endpoint,
+            deployment,
+            api_version: env("AZURE_OPENAI_API_VERSION"),
+        });
+    }
+    if let Some(api_key) = env("ANTHROPIC_API_KEY") {
+        settings.anthropic.api_key = api_key;
+    }
+    if let Some(base_url) = env("ANTHROPIC_BASE_URL") {
+        settings.anthropic.base_url = Some(base_url);
+    }
+    if let Some(base_url) = env("OLLAMA_BASE_URL") {
+        settings.ollama.base_url = base_url;
+    }
+    if let Some(driver) = parse_env(env, "DATABASE_DRIVER")? {
+        settings.database.driver = driver;
+    }
+    if let Some(url) = env("DATABASE_URL") {
+        settings.database.url = url;
+    }
+    if let Some(migrate) = parse_env(env, "DATABASE_MIGRATE_ON_START")? {
+        settings.database.migrate_on_start = migrate;
+    }
+    if let Some(port) = parse_env(env, "HTTP_PORT")? {
+        settings.server.http_port = port;
+    }
+    if let Some(port) = parse_env(env, "GRPC_PORT")? {
+        settings.server.grpc_port = port;
+    }
+    if let Some(timeout) = parse_env(env, "SHUTDOWN_TIMEOUT_SECS")? {
+        settings.server.shutdown_timeout_secs = timeout;
+    }
+    if let Some(window) = parse_env(env, "STREAM_RESUME_SECS")? {
+        settings.server.stream_resume_secs = window;
+    }
+    if let Some(timeout) = parse_env(env, "REQUEST_TIMEOUT_SECS")? {
+        settings.server.request_timeout_secs = timeout;
+    }
+    if let Some(reflection) = parse_env(env, "GRPC_REFLECTION")? {
+        settings.server.grpc_reflection = reflection;
+    }
+    if let Some(name) = env("MODEL_NAME") {
+        settings.model.name = name;
+    }
+    if let Some(max_tokens) = parse_env(env, "MODEL_MAX_TOKENS")? {
+        settings.model.max_tokens = Some(max_tokens);
+    }
+    if let Some(fallbacks) = env("MODEL_FALLBACKS") {
+        settings.model.fallbacks = fallbacks
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if let Some(timeout) = parse_env(env, "MODEL_TIMEOUT_SECS")? {
+        settings.model.timeout_secs = Some(timeout);
+    }
+    if let Some(temperature) = parse_env(env, "CHAT_TEMPERATURE")? {
+        settings.chat.temperature = temperature;
+    }
+    if let Some(top_p) = parse_env(env, "CHAT_TOP_P")? {
+        settings.chat.top_p = top_p;
+    }
+    if let Some(n) = parse_env(env, "CHAT_N")? {
+        settings.chat.n = n;
+    }
+    if let Some(stop) = env("CHAT_STOP") {
+        settings.chat.stop = stop
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if let Some(max_tokens) = parse_env(env, "CHAT_MAX_TOKENS")? {
+        settings.chat.max_tokens = Some(max_tokens);
+    }
+    if let Some(penalty) = parse_env(env, "CHAT_PRESENCE_PENALTY")? {
+        settings.chat.presence_penalty = penalty;
+    }
+    if let Some(penalty) = parse_env(env, "CHAT_FREQUENCY_PENALTY")? {
+        settings.chat.frequency_penalty = penalty;
+    }
+    if let Some(policy) = parse_env(env, "CHAT_TRIMMING_POLICY")? {
+        settings.chat.trimming_policy = policy;
+    }
+    if let Some(message) = env("INITIAL_SYSTEM_MESSAGE") {
+        settings.chat.initial_system_message = message;
+    }
+    if let Some(format) = parse_env(env, "CHAT_RESPONSE_FORMAT")? {
+        settings.chat.response_format = format;
+    }
+    if let Some(threshold) = parse_env(env, "CHAT_SUMMARY_THRESHOLD")? {
+        settings.chat.summary_threshold = threshold;
+    }
+    if let Some(keep_recent) = parse_env(env, "CHAT_SUMMARY_KEEP_RECENT")? {
+        settings.chat.summary_keep_recent = keep_recent;
+    }
+    if let Some(auto_title) = parse_env(env, "CHAT_AUTO_TITLE")? {
+        settings.chat.auto_title = auto_title;
+    }
+    if let Some(limit) = parse_env(env, "RATE_LIMIT_REQUESTS_PER_MINUTE")? {
+        settings.rate_limit.requests_per_minute = limit;
+    }
+    if let Some(limit) = parse_env(env, "RATE_LIMIT_TOKENS_PER_MINUTE")? {
+        settings.rate_limit.tokens_per_minute = limit;
+    }
+    if let Some(limit) = parse_env(env, "QUOTA_USER_DAILY_TOKENS")? {
+        settings.quota.user_daily_tokens = limit;
+    }
+    if let Some(limit) = parse_env(env, "QUOTA_USER_MONTHLY_TOKENS")? {
+        settings.quota.user_monthly_tokens = limit;
+    }
+    if let Some(limit) = parse_env(env, "QUOTA_TENANT_DAILY_TOKENS")? {
+        settings.quota.tenant_daily_tokens = limit;
+    }
+    if let Some(limit) = parse_env(env, "QUOTA_TENANT_MONTHLY_TOKENS")? {
+        settings.quota.tenant_monthly_tokens = limit;
+    }
+    if let Some(url) = env("REDIS_URL") {
+        settings.cache.redis_url = Some(url);
+    }
+    if let Some(ttl) = parse_env(env, "CACHE_TTL_SECS")? {
+        settings.cache.ttl_secs = ttl;
+    }
+    if let Some(enabled) = parse_env(env, "RESPONSE_CACHE_ENABLED")? {
+        settings.response_cache.enabled = enabled;
+    }
+    if let Some(ttl) = parse_env(env, "RESPONSE_CACHE_TTL_SECS")? {
+        settings.response_cache.ttl_secs = ttl;
+    }
+    if let Some(max_entries) = parse_env(env, "RESPONSE_CACHE_MAX_ENTRIES")? {
+        settings.response_cache.max_entries = max_entries;
+    }
+    if let Some(retries) = parse_env(env, "RETRY_MAX_RETRIES")? {
+        settings.retry.max_retries = retries;
+    }
+    if let Some(backoff) = parse_env(env, "RETRY_INITIAL_BACKOFF_MS")? {
+        settings.retry.initial_backoff_ms = backoff;
+    }
+    if let Some(backoff) = parse_env(env, "RETRY_MAX_BACKOFF_MS")? {
+        settings.retry.max_backoff_ms = backoff;
+    }
+    if let Some(elapsed) = parse_env(env, "RETRY_MAX_ELAPSED_MS")? {
+        settings.retry.max_elapsed_ms = elapsed;
+    }
+    if let Some(threshold) = parse_env(env, "CIRCUIT_BREAKER_FAILURE_THRESHOLD")? {
+        settings.circuit_breaker.failure_threshold = threshold;
+    }
+    if let Some(open) = parse_env(env, "CIRCUIT_BREAKER_OPEN_SECS")? {
+        settings.circuit_breaker.open_secs = open;
+    }
+    if let Some(name) = env("OTEL_SERVICE_NAME") {
+        settings.telemetry.service_name = name;
+    }
+    if let Some(endpoint) = env("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        settings.telemetry.otlp_endpoint = Some(endpoint);
+    }
+    if let Some(filter) = env("RUST_LOG") {
+        settings.telemetry.log_filter = filter;
+    }
+    if let Some(json) = parse_env(env, "LOG_JSON")? {
+        settings.telemetry.json_logs = json;
+    }
+    if let Some(timeout) = parse_env(env, "HEALTH_CHECK_TIMEOUT_MS")? {
+        settings.health.check_timeout_ms = timeout;
+    }
+    if let Some(check) = parse_env(env, "HEALTH_CHECK_PROVIDERS")? {
+        settings.health.check_providers = check;
+    }
+    if let Some(ttl) = parse_env(env, "HEALTH_PROVIDER_TTL_SECS")? {
+        settings.health.provider_ttl_secs = ttl;
+    }
+    if let Some(enabled) = parse_env(env, "PURGE_ENABLED")? {
+        settings.purge.enabled = enabled;
+    }
+    if let Some(days) = parse_env(env, "PURGE_RETENTION_DAYS")? {
+        settings.purge.retention_days = days;
+    }
+    if let Some(interval) = parse_env(env, "PURGE_INTERVAL_SECS")? {
+        settings.purge.interval_secs = interval;
+    }
+    if let Some(bucket) = env("ARCHIVE_BUCKET") {
+        settings.archive.bucket = Some(bucket);
+    }
+    if let Some(endpoint) = env("ARCHIVE_ENDPOINT") {
+        settings.archive.endpoint = endpoint;
+    }
+    if let Some(region) = env("ARCHIVE_REGION") {
+        settings.archive.region = region;
+    }
+    if let Some(key) = env("ARCHIVE_ACCESS_KEY_ID") {
+        settings.archive.access_key_id = Some(key);
+    }
+    if let Some(secret) = env("ARCHIVE_SECRET_ACCESS_KEY") {
+        settings.archive.secret_access_key = Some(secret);
+    }
+    if let Some(prefix) = env("ARCHIVE_PREFIX") {
+        settings.archive.prefix = prefix;
+    }
+    if let Some(days) = parse_env(env, "ARCHIVE_INACTIVE_DAYS")? {
+        settings.archive.inactive_days = days;
+    }
+    if let Some(batch_size) = parse_env(env, "ARCHIVE_BATCH_SIZE")? {
+        settings.archive.batch_size = batch_size;
+    }
+    if let Some(interval) = parse_env(env, "ARCHIVE_INTERVAL_SECS")? {
+        settings.archive.interval_secs = interval;
+    }
+    if let Some(timeout) = parse_env(env, "ARCHIVE_TIMEOUT_SECS")? {
+        settings.archive.timeout_secs = timeout;
+    }
+    if let Some(enabled) = parse_env(env, "SCHEDULE_ENABLED")? {
+        settings.schedule.enabled = enabled;
+    }
+    if let Some(interval) = parse_env(env, "SCHEDULE_INTERVAL_SECS")? {
+        settings.schedule.interval_secs = interval;
+    }
+    if let Some(batch_size) = parse_env(env, "SCHEDULE_BATCH_SIZE")? {
+        settings.schedule.batch_size = batch_size;
+    }
+    if let Some(enabled) = parse_env(env, "WEBHOOKS_ENABLED")? {
+        settings.webhooks.enabled = enabled;
+    }
+    if let Some(interval) = parse_env(env, "WEBHOOKS_INTERVAL_SECS")? {
+        settings.webhooks.interval_secs = interval;
+    }
+    if let Some(batch_size) = parse_env(env, "WEBHOOKS_BATCH_SIZE")? {
+        settings.webhooks.batch_size = batch_size;
+    }
+    if let Some(max_attempts) = parse_env(env, "WEBHOOKS_MAX_ATTEMPTS")? {
+        settings.webhooks.max_attempts = max_attempts;
+    }
+    if let Some(backoff) = parse_env(env, "WEBHOOKS_BACKOFF_SECS")? {
+        settings.webhooks.backoff_secs = backoff;
+    }
+    if let Some(timeout) = parse_env(env, "WEBHOOKS_TIMEOUT_SECS")? {
+        settings.webhooks.timeout_secs = timeout;
+    }
+    if let Some(key) = env("WEBHOOKS_ENCRYPTION_KEY") {
+        settings.webhooks.encryption_key = Some(key);
+    }
+    if let Some(max_prompts) = parse_env(env, "BATCH_MAX_PROMPTS")? {
+        settings.batch.max_prompts = max_prompts;
+    }
+    if let Some(concurrency) = parse_env(env, "BATCH_CONCURRENCY")? {
+        settings.batch.concurrency = concurrency;
+    }
+    if let Some(enabled) = parse_env(env, "JOBS_ENABLED")? {
+        settings.jobs.enabled = enabled;
+    }
+    if let Some(interval) = parse_env(env, "JOBS_INTERVAL_SECS")? {
+        settings.jobs.interval_secs = interval;
+    }
+    if let Some(batch_size) = parse_env(env, "JOBS_BATCH_SIZE")? {
+        settings.jobs.batch_size = batch_size;
+    }
+    if let Some(ttl) = parse_env(env, "IDEMPOTENCY_TTL_SECS")? {
+        settings.idempotency.ttl_secs = ttl;
+    }
+    if let Some(url) = env("EVENTS_REDIS_URL") {
+        settings.events.redis_url = Some(url);
+    }
+    if let Some(stream) = env("EVENTS_STREAM") {
+        settings.events.stream = stream;
+    }
+    if let Some(max_len) = parse_env(env, "EVENTS_STREAM_MAX_LEN")? {
+        settings.events.stream_max_len = max_len;
+    }
+    if let Some(batch_size) = parse_env(env, "EVENTS_BATCH_SIZE")? {
+        settings.events.batch_size = batch_size;
+    }
+    if let Some(interval) = parse_env(env, "EVENTS_INTERVAL_MS")? {
+        settings.events.interval_ms = interval;
+    }
+    if let Some(brokers) = env("KAFKA_BROKERS") {
+        settings.kafka.brokers = Some(brokers);
+    }
+    if let Some(group_id) = env("KAFKA_GROUP_ID") {
+        settings.kafka.group_id = group_id;
+    }
+    if let Some(topic) = env("KAFKA_REQUEST_TOPIC") {
+        settings.kafka.request_topic = topic;
+    }
+    if let Some(topic) = env("KAFKA_REPLY_TOPIC") {
+        settings.kafka.reply_topic = topic;
+    }
+    if let Some(model) = env("EMBEDDINGS_MODEL") {
+        settings.embeddings.model = model;
+    }
+    if let Some(dimensions) = parse_env(env, "EMBEDDINGS_DIMENSIONS")? {
+        settings.embeddings.dimensions = Some(dimensions);
+    }
+    if let Some(enabled) = parse_env(env, "RAG_ENABLED")? {
+        settings.rag.enabled = enabled;
+    }
+    if let Some(top_k) = parse_env(env, "RAG_TOP_K")? {
+        settings.rag.top_k = top_k;
+    }
+    if let Some(min_score) = parse_env(env, "RAG_MIN_SCORE")? {
+        settings.rag.min_score = min_score;
+    }
+    if let Some(max_tokens) = parse_env(env, "RAG_MAX_CONTEXT_TOKENS")? {
+        settings.rag.max_context_tokens = max_tokens;
+    }
+    if let Some(size) = parse_env(env, "DOCUMENTS_CHUNK_SIZE")? {
+        settings.documents.chunk_size = size;
+    }
+    if let Some(overlap) = parse_env(env, "DOCUMENTS_CHUNK_OVERLAP")? {
+        settings.documents.chunk_overlap = overlap;
+    }
+    if let Some(max_size) = parse_env(env, "DOCUMENTS_MAX_SIZE_BYTES")? {
+        settings.documents.max_size_bytes = max_size;
+    }
+    if let Some(enabled) = parse_env(env, "TRANSCRIPTION_ENABLED")? {
+        settings.transcription.enabled = enabled;
+    }
+    if let Some(model) = env("TRANSCRIPTION_MODEL") {
+        settings.transcription.model = model;
+    }
+    if let Some(max_size) = parse_env(env, "TRANSCRIPTION_MAX_SIZE_BYTES")? {
+        settings.transcription.max_size_bytes = max_size;
+    }
+    if let Some(enabled) = parse_env(env, "SPEECH_ENABLED")? {
+        settings.speech.enabled = enabled;
+    }
+    if let Some(model) = env("SPEECH_MODEL") {
+        settings.speech.model = model;
+    }
+    if let Some(enabled) = parse_env(env, "AUDIT_ENABLED")? {
+        settings.audit.enabled = enabled;
+    }
+    if let Some(enabled) = parse_env(env, "REDACTION_ENABLED")? {
+        settings.redaction.enabled = enabled;
+    }
+    if let Some(detectors) = env("REDACTION_DETECTORS") {
+        settings.redaction.detectors = detectors
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if let Some(key) = env("REDACTION_ENCRYPTION_KEY") {
+        settings.redaction.encryption_key = Some(key);
+    }
+    if let Some(token) = env("ADMIN_TOKEN") {
+        settings.auth.admin_token = Some(token);
+    }
+    if let Some(enabled) = parse_env(env, "MODERATION_ENABLED")? {
+        settings.moderation.enabled = enabled;
+    }
+    if let Some(model) = env("MODERATION_MODEL") {
+        settings.moderation.model = Some(model);
+    }
+    if let Some(enabled) = parse_env(env, "PROMPT_GUARD_ENABLED")? {
+        settings.prompt_guard.enabled = enabled;
+    }
+    if let Some(action) = env("PROMPT_GUARD_ACTION") {
+        settings.prompt_guard.action = action;
+    }
+    if let Some(threshold) = parse_env(env, "PROMPT_GUARD_THRESHOLD")? {
+        settings.prompt_guard.threshold = threshold;
+    }
+    if let Some(classifier) = parse_env(env, "PROMPT_GUARD_CLASSIFIER")? {
+        settings.prompt_guard.classifier = classifier;
+    }
+    if let Some(enabled) = parse_env(env, "OUTPUT_FILTER_ENABLED")? {
+        settings.output_filter.enabled = enabled;
+    }
+    if let Some(topics) = env("OUTPUT_FILTER_BANNED_TOPICS") {
+        settings.output_filter.banned_topics = topics
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    if let Some(mask_profanity) = parse_env(env, "OUTPUT_FILTER_MASK_PROFANITY")? {
+        settings.output_filter.mask_profanity = mask_profanity;
+    }
+    if let Some(max_length) = parse_env(env, "OUTPUT_FILTER_MAX_LENGTH")? {
+        settings.output_filter.max_length = max_length;
+    }
+    if let Some(enabled) = parse_env(env, "MEMORY_ENABLED")? {
+        settings.memory.enabled = enabled;
+    }
+    if let Some(max_memories) = parse_env(env, "MEMORY_MAX_MEMORIES")? {
+        settings.memory.max_memories = max_memories;
+    }
+    if let Some(jwks_url) = env("JWT_JWKS_URL") {
+        let jwt = settings.auth.jwt.get_or_insert_with(|| JwtSettings {
+            jwks_url: String::new(),
+            issuer: None,
+            audience: None,
+            user_claim: default_user_claim(),
+            tenant_claim: None,
+            jwks_cache_ttl_secs: default_jwks_cache_ttl_secs(),
+        });
+        jwt.jwks_url = jwks_url;
+    }
+    if let Some(jwt) = settings.auth.jwt.as_mut() {
+        if let Some(issuer) = env("JWT_ISSUER") {
+            jwt.issuer = Some(issuer);
+        }
+        if let Some(audience) = env("JWT_AUDIENCE") {
+            jwt.audience = Some(audience);
+        }
+        if let Some(claim) = env("JWT_USER_CLAIM") {
+            jwt.user_claim = claim;
+        }
+        if let Some(claim) = env("JWT_TENANT_CLAIM") {
+            jwt.tenant_claim = Some(claim);
+        }
+        if let Some(ttl) = parse_env(env, "JWT_JWKS_CACHE_TTL_SECS")? {
+            jwt.jwks_cache_ttl_secs = ttl;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_env<T>(
+    env: &impl Fn(&str) -> Option<String>,
+    key: &str,
+) -> Result<Option<T>, SettingsError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    env(key)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|e: T::Err| SettingsError::InvalidEnv {
+                    key: key.to_string(),
+                    message: e.to_string(),
+                })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::prompt_guard::GuardAction;
+    use crate::internal::infra::repository::driver::DatabaseDriver;
+
+    // env_from reads the variables, the database driver is one the tests were built with unless
+    // it is set
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let mut vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        vars.entry("DATABASE_DRIVER".to_string())
+            .or_insert_with(|| DatabaseDriver::first_compiled().to_string());
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_load_from_env() {
+        let settings = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_URL", "postgres://localhost/chat-service"),
+            ("HTTP_PORT", "3000"),
+            ("MODEL_NAME", "gpt-4o"),
+            ("MODEL_FALLBACKS", "gpt-4o-mini, gpt-3.5-turbo"),
+            ("CHAT_TEMPERATURE", "0.2"),
+            ("CHAT_STOP", "END,STOP"),
+            ("CHAT_TRIMMING_POLICY", "reject_new"),
+            ("RATE_LIMIT_REQUESTS_PER_MINUTE", "30"),
+            ("QUOTA_USER_MONTHLY_TOKENS", "1000000"),
+            ("REDIS_URL", "redis://localhost:6379"),
+            ("RESPONSE_CACHE_ENABLED", "true"),
+            ("MODERATION_ENABLED", "true"),
+            ("PROMPT_GUARD_ENABLED", "true"),
+            ("PROMPT_GUARD_ACTION", "annotate"),
+            ("PROMPT_GUARD_THRESHOLD", "0.7"),
+            ("OUTPUT_FILTER_ENABLED", "true"),
+            ("OUTPUT_FILTER_BANNED_TOPICS", "politics, medicine"),
+            ("OUTPUT_FILTER_MASK_PROFANITY", "true"),
+            ("OUTPUT_FILTER_MAX_LENGTH", "4000"),
+            ("MEMORY_ENABLED", "true"),
+            ("MEMORY_MAX_MEMORIES", "20"),
+            ("RETRY_MAX_RETRIES", "5"),
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4317"),
+            ("HEALTH_CHECK_PROVIDERS", "false"),
+            ("SHUTDOWN_TIMEOUT_SECS", "10"),
+            ("STREAM_RESUME_SECS", "120"),
+            ("REQUEST_TIMEOUT_SECS", "45"),
+            ("GRPC_REFLECTION", "true"),
+            ("PURGE_RETENTION_DAYS", "7"),
+            ("ARCHIVE_BUCKET", "chat-archive"),
+            ("ARCHIVE_ENDPOINT", "http://localhost:9000"),
+            ("ARCHIVE_ACCESS_KEY_ID", "minio"),
+            ("ARCHIVE_SECRET_ACCESS_KEY", "minio-secret"),
+            ("ARCHIVE_INACTIVE_DAYS", "30"),
+            ("SCHEDULE_ENABLED", "true"),
+            ("SCHEDULE_INTERVAL_SECS", "1"),
+            ("WEBHOOKS_ENABLED", "true"),
+            ("WEBHOOKS_INTERVAL_SECS", "2"),
+            ("WEBHOOKS_BATCH_SIZE", "20"),
+            ("WEBHOOKS_MAX_ATTEMPTS", "8"),
+            (
+                "WEBHOOKS_ENCRYPTION_KEY",
+                "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+            ),
+            ("BATCH_CONCURRENCY", "8"),
+            ("JOBS_BATCH_SIZE", "4"),
+            ("AUDIT_ENABLED", "true"),
+            ("REDACTION_ENABLED", "true"),
+            ("REDACTION_DETECTORS", "email, credit_card"),
+            (
+                "REDACTION_ENCRYPTION_KEY",
+                "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=",
+            ),
+        ]))
+        .unwrap();
+
+        assert_eq!(settings.server.http_port, 3000);
+        assert_eq!(settings.server.grpc_port, 50051);
+        assert!(settings.server.grpc_reflection);
+        assert_eq!(settings.model().unwrap().max_tokens, 128000);
+        assert_eq!(
+            settings.model.fallbacks,
+            vec!["gpt-4o-mini", "gpt-3.5-turbo"]
+        );
+        assert_eq!(settings.chat.temperature, 0.2);
+        assert_eq!(settings.chat.stop, vec!["END", "STOP"]);
+        assert_eq!(settings.chat.trimming_policy, TrimmingPolicy::RejectNew);
+        assert_eq!(settings.rate_limit.requests_per_minute, 30);
+        assert_eq!(settings.rate_limit.tokens_per_minute, 0);
+        assert_eq!(settings.quota_config().user_monthly_tokens, 1_000_000);
+        assert_eq!(settings.quota.user_daily_tokens, 0);
+        assert_eq!(
+            settings.cache.redis_url.as_deref(),
+            Some("redis://localhost:6379")
+        );
+        assert_eq!(settings.cache.ttl_secs, 300);
+        assert!(settings.response_cache.enabled);
+        assert_eq!(settings.response_cache.ttl_secs, 3600);
+        assert!(settings.moderation.enabled);
+        assert_eq!(settings.moderation.model, None);
+        assert!(settings.prompt_guard.enabled);
+        assert_eq!(
+            settings.prompt_guard_action().unwrap(),
+            GuardAction::Annotate
+        );
+        assert_eq!(settings.prompt_guard.threshold, 0.7);
+        assert!(!settings.prompt_guard.classifier);
+        assert!(settings.output_filter.enabled);
+        assert_eq!(
+            settings.output_filter.banned_topics,
+            vec!["politics", "medicine"]
+        );
+        assert!(settings.output_filter.mask_profanity);
+        assert_eq!(settings.output_filter.max_length, 4000);
+        assert!(settings.memory.enabled);
+        assert_eq!(settings.memory.max_memories, 20);
+        assert_eq!(settings.retry_policy().max_retries, 5);
+        assert_eq!(
+            settings.telemetry.otlp_endpoint.as_deref(),
+            Some("http://localhost:4317")
+        );
+        assert_eq!(settings.telemetry.service_name, "chat-service");
+        assert!(!settings.health.check_providers);
+        assert_eq!(
+            settings.shutdown_timeout(),
+            std::time::Duration::from_secs(10)
+        );
+        assert_eq!(
+            settings.stream_resume_window(),
+            std::time::Duration::from_secs(120)
+        );
+        assert_eq!(
+            settings.request_timeout(),
+            Some(std::time::Duration::from_secs(45))
+        );
+        assert_eq!(settings.health.provider_ttl_secs, 30);
+        assert_eq!(settings.purge.retention_days, 7);
+        assert_eq!(settings.purge.interval_secs, 3600);
+        assert_eq!(settings.archive.bucket.as_deref(), Some("chat-archive"));
+        assert_eq!(settings.archive.endpoint, "http://localhost:9000");
+        assert_eq!(settings.archive.region, "us-east-1");
+        assert_eq!(settings.archive.access_key_id.as_deref(), Some("minio"));
+        assert_eq!(settings.archive.inactive_days, 30);
+        assert_eq!(settings.archive.batch_size, 100);
+        assert!(settings.schedule.enabled);
+        assert_eq!(settings.schedule.interval_secs, 1);
+        assert_eq!(settings.schedule.batch_size, 20);
+        assert!(settings.webhooks.enabled);
+        assert_eq!(settings.webhooks.interval_secs, 2);
+        assert_eq!(settings.webhooks.batch_size, 20);
+        assert_eq!(settings.webhooks.max_attempts, 8);
+        assert!(settings.webhook_cipher().is_ok());
+        assert_eq!(settings.webhooks.backoff_secs, 30);
+        assert_eq!(settings.batch.max_prompts, 50);
+        assert_eq!(settings.batch.concurrency, 8);
+        assert!(settings.jobs.enabled);
+        assert_eq!(settings.jobs.interval_secs, 2);
+        assert_eq!(settings.jobs.batch_size, 4);
+        assert!(settings.audit.enabled);
+        assert!(settings.redaction.enabled);
+        assert_eq!(settings.redaction.detectors, vec!["email", "credit_card"]);
+        assert_eq!(settings.auth.admin_token, None);
+        assert_eq!(
+            settings.retry_policy().initial_backoff,
+            std::time::Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn test_invalid_env() {
+        let result = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_URL", "postgres://localhost/chat-service"),
+            ("GRPC_PORT", "not-a-port"),
+        ]));
+
+        assert!(matches!(
+            result,
+            Err(SettingsError::InvalidEnv { key, .. }) if key == "GRPC_PORT"
+        ));
+
+        // the memory driver, the only one without features, reads no url
+        if DatabaseDriver::first_compiled() != DatabaseDriver::Memory {
+            let result = load_with(env_from(&[("OPENAI_API_KEY", "sk-test")]));
+            assert!(matches!(
+                result,
+                Err(SettingsError::Missing("database.url"))
+            ));
+        }
+
+        let result = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_DRIVER", "oracle"),
+        ]));
+        assert!(matches!(
+            result,
+            Err(SettingsError::InvalidEnv { key, .. }) if key == "DATABASE_DRIVER"
+        ));
+
+        let settings = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_DRIVER", "memory"),
+        ]))
+        .unwrap();
+        assert_eq!(settings.database.driver, DatabaseDriver::Memory);
+        assert!(!settings.database.migrate_on_start);
+    }
+
+    #[test]
+    fn test_load_jwt_from_env() {
+        let settings = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_URL", "postgres://localhost/chat-service"),
+            ("JWT_ISSUER", "https://ignored.example.com/"),
+        ]))
+        .unwrap();
+        assert_eq!(settings.auth.jwt, None);
+
+        let settings = load_with(env_from(&[
+            ("OPENAI_API_KEY", "sk-test"),
+            ("DATABASE_URL", "postgres://localhost/chat-service"),
+            (
+                "JWT_JWKS_URL",
+                "https://example.auth0.com/.well-known/jwks.json",
+            ),
+            ("JWT_ISSUER", "https://example.auth0.com/"),
+        ]))
+        .unwrap();
+
+        let jwt = settings.auth.jwt.unwrap();
+        assert_eq!(jwt.issuer.as_deref(), Some("https://example.auth0.com/"));
+        assert_eq!(jwt.audience, None);
+        assert_eq!(jwt.user_claim, "sub");
+        assert_eq!(jwt.tenant_claim, None);
+        assert_eq!(jwt.jwks_cache_ttl_secs, 300);
+    }
+
+    #[test]
+    fn test_azure_requires_deployment() {
+        let result = load_with(env_from(&[
+            ("OPENAI_API_KEY", "azure-key"),
+            ("DATABASE_URL", "postgres://localhost/chat-service"),
+            (
+                "AZURE_OPENAI_ENDPOINT",
+                "https://my-resource.openai.azure.com",
+            ),
+        ]));
+
+        assert!(matches!(
+            result,
+            Err(SettingsError::Missing("AZURE_OPENAI_DEPLOYMENT"))
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let path = std::env::temp_dir().join("chat-service-settings.ini");
+        std::fs::write(&path, "[server]").unwrap();
+
+        assert!(matches!(
+            read_file(&path),
+            Err(SettingsError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+use std::future::Future;
+
+tokio::task_local! {
+    // BYPASS is set for the requests whose client asked for a fresh completion, the response
+    // cache neither serves them nor stores their replies
+    static BYPASS: ();
+}
+
+// with_cache_bypass runs the future of a request without the response cache
+pub async fn with_cache_bypass<F: Future>(future: F) -> F::Output {
+    BYPASS.scope((), future).await
+}
+
+// is_bypassed tells whether the request being served asked to skip the response cache
+pub fn is_bypassed() -> bool {
+    BYPASS.try_with(|_| ()).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_cache_bypass() {
+        assert!(!is_bypassed());
+        assert!(with_cache_bypass(async { is_bypassed() }).await);
+    }
+}
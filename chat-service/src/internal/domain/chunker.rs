@@ -0,0 +1,119 @@
+// ChunkingConfig sets the size of the chunks a document is split into, in characters, and how
+// many characters consecutive chunks share so text cut at a boundary is whole in one of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkingConfig {
+    pub size: usize,
+    pub overlap: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            size: 1000,
+            overlap: 200,
+        }
+    }
+}
+
+// TextChunk is a piece of a document, start is the offset of its first character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk {
+    pub position: usize,
+    pub start: usize,
+    pub content: String,
+}
+
+// BREAKS are where a chunk preferably ends, from the best to the worst
+const BREAKS: [&str; 4] = ["\n\n", "\n", ". ", " "];
+
+// chunk_text splits the text into chunks of at most size characters; a chunk ends at the last
+// paragraph, line, sentence or word break of its second half so it is rarely cut mid-word
+pub fn chunk_text(text: &str, config: &ChunkingConfig) -> Vec<TextChunk> {
+    let chars: Vec<char> = text.chars().collect();
+    let size = config.size.max(1);
+    let overlap = config.overlap.min(size - 1);
+
+    let mut chunks = vec![];
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + size).min(chars.len());
+        if end < chars.len() {
+            end = break_before(&chars, start + size / 2, end).unwrap_or(end);
+        }
+
+        let content: String = chars[start..end].iter().collect();
+        if !content.trim().is_empty() {
+            chunks.push(TextChunk {
+                position: chunks.len(),
+                start,
+                content: content.trim().to_string(),
+            });
+        }
+
+        if end == chars.len() {
+            break;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+
+    chunks
+}
+
+// break_before returns the end of the best break found between from and to
+fn break_before(chars: &[char], from: usize, to: usize) -> Option<usize> {
+    BREAKS.iter().find_map(|separator| {
+        let separator: Vec<char> = separator.chars().collect();
+        (from..=to.saturating_sub(separator.len()))
+            .rev()
+            .find(|&i| chars[i..i + separator.len()] == separator[..])
+            .map(|i| i + separator.len())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text() {
+        let text = "First paragraph here.\n\nSecond one is a bit longer. It has two sentences.";
+        let chunks = chunk_text(
+            text,
+            &ChunkingConfig {
+                size: 40,
+                overlap: 10,
+            },
+        );
+
+        assert_eq!(chunks[0].content, "First paragraph here.");
+        assert_eq!(chunks[0].start, 0);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.content.chars().count() <= 40));
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.position)
+                .collect::<Vec<_>>(),
+            (0..chunks.len()).collect::<Vec<_>>()
+        );
+        assert!(chunks.last().unwrap().content.ends_with("two sentences."));
+    }
+
+    #[test]
+    fn test_chunk_text_overlap() {
+        let text = "abcdefghij".repeat(3);
+        let chunks = chunk_text(
+            &text,
+            &ChunkingConfig {
+                size: 10,
+                overlap: 4,
+            },
+        );
+
+        assert_eq!(chunks[0].content, "abcdefghij");
+        assert_eq!(chunks[1].start, 6);
+        assert_eq!(chunks[1].content, "ghijabcdef");
+        assert!(chunk_text("  \n ", &ChunkingConfig::default()).is_empty());
+    }
+}
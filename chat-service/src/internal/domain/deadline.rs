@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+tokio::task_local! {
+    // DEADLINE is when the request being served has to be answered, the provider calls and
+    // queries made for it read it to bound their own timeouts
+    static DEADLINE: Instant;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("request deadline of {}ms exceeded", timeout.as_millis())]
+pub struct DeadlineExceeded {
+    pub timeout: Duration,
+}
+
+// with_deadline runs the future of a request under a deadline, the future is dropped once the
+// deadline passes, which cancels the provider call or query it was waiting on; a deadline set
+// inside another one never extends it
+pub async fn with_deadline<F: Future>(
+    timeout: Duration,
+    future: F,
+) -> Result<F::Output, DeadlineExceeded> {
+    let mut deadline = Instant::now() + timeout;
+    if let Some(outer) = current() {
+        deadline = deadline.min(outer);
+    }
+
+    DEADLINE
+        .scope(deadline, tokio::time::timeout_at(deadline, future))
+        .await
+        .map_err(|_| DeadlineExceeded { timeout })
+}
+
+// current returns the deadline of the request being served, None outside of a request or
+// when it has none
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+// remaining is the time left until the deadline of the request being served
+pub fn remaining() -> Option<Duration> {
+    current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+// request_timeout is the deadline a request runs under, the shorter of the configured one and
+// the one the client asked for
+pub fn request_timeout(
+    configured: Option<Duration>,
+    requested: Option<Duration>,
+) -> Option<Duration> {
+    match (configured, requested) {
+        (Some(configured), Some(requested)) => Some(configured.min(requested)),
+        (configured, requested) => configured.or(requested),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_deadline() {
+        assert_eq!(remaining(), None);
+
+        let result = with_deadline(Duration::from_secs(5), async {
+            let remaining = remaining().unwrap();
+            assert!(remaining <= Duration::from_secs(5));
+            assert!(remaining > Duration::from_secs(4));
+            "done"
+        })
+        .await;
+        assert_eq!(result, Ok("done"));
+
+        let timeout = Duration::from_millis(20);
+        let result = with_deadline(timeout, tokio::time::sleep(Duration::from_secs(5))).await;
+        assert_eq!(result, Err(DeadlineExceeded { timeout }));
+    }
+
+    #[tokio::test]
+    async fn test_nested_deadline_does_not_extend() {
+        let outer = Duration::from_millis(20);
+        let inner = Duration::from_secs(5);
+        let started = Instant::now();
+
+        let result = with_deadline(outer, async {
+            with_deadline(inner, async {
+                assert!(remaining().unwrap() <= outer);
+                tokio::time::sleep(inner).await
+            })
+            .await
+        })
+        .await;
+
+        // both scopes share the outer deadline, the inner one is polled first and gives up
+        assert_eq!(result, Ok(Err(DeadlineExceeded { timeout: inner })));
+        assert!(started.elapsed() >= outer);
+        assert!(started.elapsed() < inner);
+    }
+
+    #[test]
+    fn test_request_timeout() {
+        let secs = Duration::from_secs;
+
+        assert_eq!(request_timeout(None, None), None);
+        assert_eq!(request_timeout(Some(secs(60)), None), Some(secs(60)));
+        assert_eq!(request_timeout(None, Some(secs(5))), Some(secs(5)));
+        assert_eq!(
+            request_timeout(Some(secs(60)), Some(secs(5))),
+            Some(secs(5))
+        );
+        assert_eq!(
+            request_timeout(Some(secs(60)), Some(secs(90))),
+            Some(secs(60))
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const API_KEY_PREFIX: &str = "chs_";
+const API_KEY_BYTES: usize = 32;
+// DISPLAY_PREFIX_LENGTH is how much of the key is kept in clear to tell keys apart
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+// ApiKey authenticates a user, only the SHA-256 of the key is ever stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    // tenant_id is the tenant of the user, it scopes every request made with the key
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub prefix: String,
+    pub key_hash: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ApiKey {
+    // generate creates a random key for the user and returns it next to the entity,
+    // the plaintext key must be handed to the user right away as it cannot be recovered
+    pub fn generate(tenant_id: Uuid, user_id: Uuid) -> (Self, String) {
+        let mut bytes = [0u8; API_KEY_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let key = format!("{}{}", API_KEY_PREFIX, hex::encode(bytes));
+
+        let api_key = Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            prefix: key[..DISPLAY_PREFIX_LENGTH].to_string(),
+            key_hash: hash_key(&key),
+            created_at: chrono::Utc::now(),
+            revoked_at: None,
+        };
+
+        (api_key, key)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+
+    pub fn revoke(&mut self) {
+        self.revoked_at.get_or_insert_with(chrono::Utc::now);
+    }
+}
+
+// hash_key returns the hex encoded SHA-256 of the key, used to look keys up
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let tenant_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let (api_key, key) = ApiKey::generate(tenant_id, user_id);
+
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + API_KEY_BYTES * 2);
+        assert!(key.starts_with(&api_key.prefix));
+        assert_eq!(api_key.tenant_id, tenant_id);
+        assert_eq!(api_key.user_id, user_id);
+        assert_eq!(api_key.key_hash, hash_key(&key));
+        assert_ne!(api_key.key_hash, key);
+        assert!(api_key.is_active());
+
+        let (other, other_key) = ApiKey::generate(tenant_id, user_id);
+        assert_ne!(key, other_key);
+        assert_ne!(api_key.key_hash, other.key_hash);
+    }
+
+    #[test]
+    fn test_revoke() {
+        let (mut api_key, _) = ApiKey::generate(Uuid::new_v4(), Uuid::new_v4());
+
+        api_key.revoke();
+        let revoked_at = api_key.revoked_at;
+        api_key.revoke();
+
+        assert!(!api_key.is_active());
+        assert_eq!(api_key.revoked_at, revoked_at);
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::error::{ChatError, ConfigError};
+
+pub const MAX_ASSISTANT_NAME_LENGTH: usize = 255;
+
+// Assistant is a bot configured by a tenant; a chat started with it gets its instructions as
+// system message, filled like a prompt template, its model and temperature when set, and is
+// only offered its tools
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assistant {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub instructions: String,
+    // model names a registry model, chats use the tenant's one when it is not set
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    // tools are the names of the registered tools the assistant may call
+    pub tools: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Assistant {
+    pub fn new(
+        id: Uuid,
+        tenant_id: Uuid,
+        name: &str,
+        instructions: &str,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id,
+            tenant_id,
+            name: name.trim().to_string(),
+            instructions: instructions.to_string(),
+            model: None,
+            temperature: None,
+            tools: vec![],
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: Option<f32>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<String>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.name.is_empty() {
+            return Err(ChatError::InvalidAssistant("name is empty".to_string()));
+        }
+
+        if self.name.len() > MAX_ASSISTANT_NAME_LENGTH {
+            return Err(ChatError::InvalidAssistant("name is too long".to_string()));
+        }
+
+        if matches!(&self.model, Some(model) if model.trim().is_empty()) {
+            return Err(ChatError::InvalidAssistant("model is empty".to_string()));
+        }
+
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err(ConfigError::TemperatureOutOfRange(temperature).into());
+            }
+        }
+
+        for (index, tool) in self.tools.iter().enumerate() {
+            if tool.trim().is_empty() {
+                return Err(ChatError::InvalidAssistant(
+                    "a tool name is empty".to_string(),
+                ));
+            }
+            if self.tools[..index].contains(tool) {
+                return Err(ChatError::InvalidAssistant(format!(
+                    "tool {} is listed twice",
+                    tool
+                )));
+            }
+        }
+
+        self.template().validate()
+    }
+
+    // render_instructions fills the variables of the instructions like a prompt template does
+    pub fn render_instructions(
+        &self,
+        values: &HashMap<String, String>,
+    ) -> Result<String, ChatError> {
+        self.template().render(values)
+    }
+
+    // offers tells whether the assistant may call the tool
+    pub fn offers(&self, tool: &str) -> bool {
+        self.tools.iter().any(|name| name == tool)
+    }
+
+    fn template(&self) -> PromptTemplate {
+        PromptTemplate::new(self.id, &self.name, &self.instructions, self.created_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assistant(instructions: &str) -> Assistant {
+        Assistant::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            " Support bot ",
+            instructions,
+            chrono::Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = assistant("You help {{user_name}} with billing.")
+            .with_model(Some("gpt-4o".to_string()))
+            .with_temperature(Some(0.2))
+            .with_tools(vec!["get_invoice".to_string()]);
+        assert_eq!(valid.name, "Support bot");
+        assert!(valid.validate().is_ok());
+        assert!(valid.offers("get_invoice"));
+        assert!(!valid.offers("get_weather"));
+
+        for invalid in [
+            Assistant {
+                name: String::new(),
+                ..valid.clone()
+            },
+            Assistant {
+                name: "a".repeat(MAX_ASSISTANT_NAME_LENGTH + 1),
+                ..valid.clone()
+            },
+            valid.clone().with_model(Some(" ".to_string())),
+            valid
+                .clone()
+                .with_tools(vec!["get_invoice".to_string(), "get_invoice".to_string()]),
+        ] {
+            assert!(matches!(
+                invalid.validate(),
+                Err(ChatError::InvalidAssistant(_))
+            ));
+        }
+        assert_eq!(
+            valid.clone().with_temperature(Some(2.5)).validate(),
+            Err(ChatError::InvalidConfig(
+                ConfigError::TemperatureOutOfRange(2.5)
+            ))
+        );
+        assert!(matches!(
+            assistant("You help {{user name}}.").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            assistant("  ").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_instructions() {
+        let assistant = assistant("You help {{user_name}} with billing.");
+
+        assert_eq!(
+            assistant
+                .render_instructions(&HashMap::from([(
+                    "user_name".to_string(),
+                    "Ada".to_string()
+                )]))
+                .unwrap(),
+            "You help Ada with billing."
+        );
+        assert_eq!(
+            assistant.render_instructions(&HashMap::new()),
+            Err(ChatError::MissingTemplateVariables(vec![
+                "user_name".to_string()
+            ]))
+        );
+    }
+}
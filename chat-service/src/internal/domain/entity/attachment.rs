@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::error::ChatError;
+
+pub const MAX_ATTACHMENTS_PER_MESSAGE: usize = 10;
+// MAX_ATTACHMENT_SIZE_BYTES is the largest image OpenAI accepts
+pub const MAX_ATTACHMENT_SIZE_BYTES: usize = 20 * 1024 * 1024;
+pub const MAX_ATTACHMENT_URL_LENGTH: usize = 2048;
+pub const IMAGE_MIME_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+// AttachmentSource is where the image is read from, a URL the provider downloads or the
+// base64 encoded bytes sent along with the message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentSource {
+    Url(String),
+    Base64(String),
+}
+
+// Attachment is an image sent with a user message, e.g. {"url": "https://..."} or
+// {"mime_type": "image/png", "base64": "iVBORw0..."}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    // mime_type is required with base64 bytes, a URL can leave it to the provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(flatten)]
+    pub source: AttachmentSource,
+}
+
+impl Attachment {
+    pub fn url(url: &str) -> Self {
+        Self {
+            mime_type: None,
+            source: AttachmentSource::Url(url.trim().to_string()),
+        }
+    }
+
+    pub fn base64(mime_type: &str, data: &str) -> Self {
+        Self {
+            mime_type: Some(mime_type.trim().to_lowercase()),
+            source: AttachmentSource::Base64(data.trim().to_string()),
+        }
+    }
+
+    // data_url returns the URL of the image, base64 bytes become a data URL
+    pub fn data_url(&self) -> String {
+        match &self.source {
+            AttachmentSource::Url(url) => url.clone(),
+            AttachmentSource::Base64(data) => format!(
+                "data:{};base64,{}",
+                self.mime_type.as_deref().unwrap_or_default(),
+                data
+            ),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if let Some(mime_type) = &self.mime_type {
+            if !IMAGE_MIME_TYPES.contains(&mime_type.as_str()) {
+                return Err(ChatError::InvalidAttachment(format!(
+                    "unsupported type {}, attach {}",
+                    mime_type,
+                    IMAGE_MIME_TYPES.join(", ")
+                )));
+            }
+        }
+
+        match &self.source {
+            AttachmentSource::Url(url) => {
+                if !url.starts_with("https://") && !url.starts_with("http://") {
+                    return Err(ChatError::InvalidAttachment(
+                        "url must be http or https".to_string(),
+                    ));
+                }
+
+                if url.len() > MAX_ATTACHMENT_URL_LENGTH {
+                    return Err(ChatError::InvalidAttachment("url is too long".to_string()));
+                }
+            }
+            AttachmentSource::Base64(data) => {
+                if self.mime_type.is_none() {
+                    return Err(ChatError::InvalidAttachment(
+                        "mime_type is required with base64 data".to_string(),
+                    ));
+                }
+
+                if data.is_empty() {
+                    return Err(ChatError::InvalidAttachment("data is empty".to_string()));
+                }
+
+                if !data
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+                {
+                    return Err(ChatError::InvalidAttachment(
+                        "data is not valid base64".to_string(),
+                    ));
+                }
+
+                // every 4 base64 characters encode 3 bytes
+                if data.len() / 4 * 3 > MAX_ATTACHMENT_SIZE_BYTES {
+                    return Err(ChatError::InvalidAttachment(format!(
+                        "image is larger than {} bytes",
+                        MAX_ATTACHMENT_SIZE_BYTES
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde() {
+        let attachment: Attachment =
+            serde_json::from_str(r#"{"url": "https://example.com/cat.png"}"#).unwrap();
+        assert_eq!(attachment, Attachment::url("https://example.com/cat.png"));
+
+        let attachment = Attachment::base64("image/png", "iVBORw0=");
+        assert_eq!(
+            serde_json::to_value(&attachment).unwrap(),
+            serde_json::json!({"mime_type": "image/png", "base64": "iVBORw0="})
+        );
+        assert_eq!(attachment.data_url(), "data:image/png;base64,iVBORw0=");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            Attachment::url("https://example.com/cat.png").validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Attachment::base64("image/jpeg", "/9j/4AAQ").validate(),
+            Ok(())
+        );
+
+        for attachment in [
+            Attachment::url("ftp://example.com/cat.png"),
+            Attachment::base64("application/pdf", "JVBERi0="),
+            Attachment::base64("image/png", "not base64!"),
+            Attachment::base64("image/png", ""),
+            Attachment {
+                mime_type: None,
+                source: AttachmentSource::Base64("iVBORw0=".to_string()),
+            },
+        ] {
+            assert!(matches!(
+                attachment.validate(),
+                Err(ChatError::InvalidAttachment(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::error::ChatError;
+
+// AudioFormat is an encoding of recorded speech the transcription model reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Flac,
+    M4a,
+    Mp3,
+    Mp4,
+    Mpeg,
+    Mpga,
+    Ogg,
+    Wav,
+    Webm,
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl AudioFormat {
+    // detect reads the format from the content type, or from the file extension when the
+    // content type is missing or too generic to tell, e.g. application/octet-stream
+    pub fn detect(name: Option<&str>, content_type: Option<&str>) -> Result<Self, ChatError> {
+        let mime = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(|mime| mime.trim().to_lowercase());
+        match mime.as_deref() {
+            Some("audio/flac") | Some("audio/x-flac") => return Ok(AudioFormat::Flac),
+            Some("audio/m4a") | Some("audio/x-m4a") => return Ok(AudioFormat::M4a),
+            Some("audio/mpeg") | Some("audio/mp3") => return Ok(AudioFormat::Mp3),
+            Some("audio/mp4") | Some("video/mp4") => return Ok(AudioFormat::Mp4),
+            Some("audio/ogg") => return Ok(AudioFormat::Ogg),
+            Some("audio/wav") | Some("audio/x-wav") | Some("audio/wave") => {
+                return Ok(AudioFormat::Wav)
+            }
+            Some("audio/webm") | Some("video/webm") => return Ok(AudioFormat::Webm),
+            _ => {}
+        }
+
+        let extension = name
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("flac") => Ok(AudioFormat::Flac),
+            Some("m4a") => Ok(AudioFormat::M4a),
+            Some("mp3") => Ok(AudioFormat::Mp3),
+            Some("mp4") => Ok(AudioFormat::Mp4),
+            Some("mpeg") => Ok(AudioFormat::Mpeg),
+            Some("mpga") => Ok(AudioFormat::Mpga),
+            Some("ogg") | Some("oga") => Ok(AudioFormat::Ogg),
+            Some("wav") => Ok(AudioFormat::Wav),
+            Some("webm") => Ok(AudioFormat::Webm),
+            _ => Err(ChatError::InvalidAudio(
+                "unsupported format, send flac, m4a, mp3, mp4, mpeg, mpga, ogg, wav or webm"
+                    .to_string(),
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Flac => "flac",
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Mp4 => "mp4",
+            AudioFormat::Mpeg => "mpeg",
+            AudioFormat::Mpga => "mpga",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Webm => "webm",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::M4a | AudioFormat::Mp4 => "audio/mp4",
+            AudioFormat::Mp3 | AudioFormat::Mpeg | AudioFormat::Mpga => "audio/mpeg",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Webm => "audio/webm",
+        }
+    }
+}
+
+// Audio is a recording sent to be transcribed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audio {
+    pub format: AudioFormat,
+    pub content: Vec<u8>,
+}
+
+impl Audio {
+    pub fn new(format: AudioFormat, content: Vec<u8>) -> Self {
+        Self { format, content }
+    }
+
+    // file_name is the name the recording is uploaded under, providers read the format from it
+    pub fn file_name(&self) -> String {
+        format!("audio.{}", self.format)
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.content.is_empty() {
+            return Err(ChatError::InvalidAudio("audio is empty".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            AudioFormat::detect(None, Some("audio/webm; codecs=opus")),
+            Ok(AudioFormat::Webm)
+        );
+        assert_eq!(
+            AudioFormat::detect(Some("memo.M4A"), Some("application/octet-stream")),
+            Ok(AudioFormat::M4a)
+        );
+        assert!(matches!(
+            AudioFormat::detect(Some("memo.aiff"), None),
+            Err(ChatError::InvalidAudio(_))
+        ));
+        assert!(matches!(
+            AudioFormat::detect(None, None),
+            Err(ChatError::InvalidAudio(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let audio = Audio::new(AudioFormat::Wav, vec![1, 2, 3]);
+        assert_eq!(audio.file_name(), "audio.wav");
+        assert_eq!(audio.validate(), Ok(()));
+        assert!(Audio::new(AudioFormat::Wav, vec![]).validate().is_err());
+    }
+}
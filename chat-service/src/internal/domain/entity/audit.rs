@@ -0,0 +1,287 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::DurationRound;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::request_id;
+
+// AuditDirection tells whether an entry is a prompt sent to a provider, what came back or a
+// change of the system prompt of a chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDirection {
+    Prompt,
+    Response,
+    SystemPrompt,
+}
+
+impl fmt::Display for AuditDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self {
+            AuditDirection::Prompt => "prompt",
+            AuditDirection::Response => "response",
+            AuditDirection::SystemPrompt => "system_prompt",
+        };
+        f.write_str(direction)
+    }
+}
+
+impl FromStr for AuditDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prompt" => Ok(AuditDirection::Prompt),
+            "response" => Ok(AuditDirection::Response),
+            "system_prompt" => Ok(AuditDirection::SystemPrompt),
+            _ => Err(format!("audit direction {} is invalid", s)),
+        }
+    }
+}
+
+// AuditEntry is a line of the append-only log of what was sent to model providers and what
+// they answered, a prompt and its response share the request_id, the one of the request they
+// were made for; the changes of the system prompts of chats are logged along with them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub request_id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub direction: AuditDirection,
+    pub provider: String,
+    pub model: String,
+    // content is the messages of a prompt as JSON, the text of a response, or the previous and
+    // current system prompts as JSON
+    pub content: String,
+    // error is set on the response of a request the provider failed
+    pub error: Option<String>,
+    // latency_ms is how long the provider took to respond, prompts have none
+    pub latency_ms: Option<u64>,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AuditEntry {
+    // prompt records the chat the way it is sent to the provider, system message first
+    pub fn prompt(request_id: Uuid, chat: &Chat) -> Result<Self, serde_json::Error> {
+        let messages = chat.prompt_messages();
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            request_id,
+            tenant_id: chat.tenant_id,
+            user_id: chat.user_id,
+            chat_id: chat.id,
+            direction: AuditDirection::Prompt,
+            provider: chat.config.model.provider().to_string(),
+            model: chat.config.model.name.clone(),
+            content: serde_json::to_string(&messages)?,
+            error: None,
+            latency_ms: None,
+            prompt_tokens: chat.token_usage as u32,
+            completion_tokens: 0,
+            created_at: logged_at(),
+        })
+    }
+
+    // response records the reply to the prompt, or the error the request failed with; the reply
+    // names the model that served it, which differs from the chat's one after a fallback
+    pub fn response(
+        request_id: Uuid,
+        chat: &Chat,
+        result: Result<&Message, &GatewayError>,
+        latency: Duration,
+    ) -> Self {
+        let model = match result {
+            Ok(reply) => &reply.model,
+            Err(_) => &chat.config.model,
+        };
+
+        Self {
+            id: Uuid::new_v4(),
+            request_id,
+            tenant_id: chat.tenant_id,
+            user_id: chat.user_id,
+            chat_id: chat.id,
+            direction: AuditDirection::Response,
+            provider: model.provider().to_string(),
+            model: model.name.clone(),
+            content: result
+                .map(|reply| reply.content.clone())
+                .unwrap_or_default(),
+            error: result.err().map(|err| err.to_string()),
+            latency_ms: Some(latency.as_millis() as u64),
+            prompt_tokens: chat.token_usage as u32,
+            completion_tokens: result.map_or(0, |reply| reply.tokens as u32),
+            created_at: logged_at(),
+        }
+    }
+
+    // system_prompt records the replacement of the system prompt of the chat, the previous one
+    // is kept in the entry since the chat only holds the current one
+    pub fn system_prompt(
+        request_id: Uuid,
+        chat: &Chat,
+        previous: &Message,
+    ) -> Result<Self, serde_json::Error> {
+        let change = SystemPromptChange {
+            previous: &previous.content,
+            current: &chat.initial_system_message().content,
+        };
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            request_id,
+            tenant_id: chat.tenant_id,
+            user_id: chat.user_id,
+            chat_id: chat.id,
+            direction: AuditDirection::SystemPrompt,
+            provider: chat.config.model.provider().to_string(),
+            model: chat.config.model.name.clone(),
+            content: serde_json::to_string(&change)?,
+            error: None,
+            latency_ms: None,
+            prompt_tokens: chat.token_usage as u32,
+            completion_tokens: 0,
+            created_at: logged_at(),
+        })
+    }
+}
+
+// SystemPromptChange is the content of a system_prompt entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SystemPromptChange<'a> {
+    pub previous: &'a str,
+    pub current: &'a str,
+}
+
+// current_request_id is the id of the request being served when the client sent a uuid, so the
+// entries can be found from the request, a new one otherwise
+pub fn current_request_id() -> Uuid {
+    request_id::current()
+        .and_then(|request_id| Uuid::parse_str(&request_id).ok())
+        .unwrap_or_else(Uuid::new_v4)
+}
+
+// logged_at is the current time to the microsecond, the precision the log is stored with, so
+// entries compare the same before and after a round trip through the database
+fn logged_at() -> chrono::DateTime<chrono::Utc> {
+    let now = chrono::Utc::now();
+    now.duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+
+    fn chat() -> Chat {
+        let model = Model::new("anthropic/claude-3-5-sonnet".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        chat.add_message(Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model,
+            chrono::Utc::now(),
+        ))
+        .unwrap();
+        chat
+    }
+
+    #[test]
+    fn test_prompt_and_response() {
+        let chat = chat();
+        let request_id = Uuid::new_v4();
+
+        let prompt = AuditEntry::prompt(request_id, &chat).unwrap();
+        assert_eq!(prompt.direction, AuditDirection::Prompt);
+        assert_eq!(prompt.provider, "anthropic");
+        let sent: Vec<Message> = serde_json::from_str(&prompt.content).unwrap();
+        assert_eq!(
+            sent.iter()
+                .map(|message| message.content.as_str())
+                .collect::<Vec<_>>(),
+            vec!["You are a helpful assistant.", "Hello!"]
+        );
+        assert_eq!(prompt.prompt_tokens, chat.token_usage as u32);
+        assert_eq!(prompt.latency_ms, None);
+
+        let reply = Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            "Hi, how can I help?",
+            0,
+            Model::new("gpt-4o".to_string(), 4096),
+            chrono::Utc::now(),
+        );
+        let response =
+            AuditEntry::response(request_id, &chat, Ok(&reply), Duration::from_millis(120));
+        assert_eq!(response.request_id, request_id);
+        assert_eq!(response.direction, AuditDirection::Response);
+        assert_eq!(
+            (response.provider.as_str(), response.model.as_str()),
+            ("openai", "gpt-4o")
+        );
+        assert_eq!(response.content, "Hi, how can I help?");
+        assert_eq!(response.completion_tokens, reply.tokens as u32);
+        assert_eq!(response.latency_ms, Some(120));
+
+        let failed = AuditEntry::response(
+            request_id,
+            &chat,
+            Err(&GatewayError::EmptyResponse),
+            Duration::from_millis(80),
+        );
+        assert_eq!(failed.provider, "anthropic");
+        assert_eq!(failed.content, "");
+        assert_eq!(failed.error, Some(GatewayError::EmptyResponse.to_string()));
+        assert_eq!(failed.completion_tokens, 0);
+    }
+
+    #[test]
+    fn test_system_prompt() {
+        let mut chat = chat();
+        let previous = chat
+            .replace_system_message("Answer like a pirate.")
+            .unwrap();
+
+        let entry = AuditEntry::system_prompt(Uuid::new_v4(), &chat, &previous).unwrap();
+        assert_eq!(entry.direction, AuditDirection::SystemPrompt);
+        assert_eq!(entry.direction.to_string().parse(), Ok(entry.direction));
+        let change: SystemPromptChange = serde_json::from_str(&entry.content).unwrap();
+        assert_eq!(change.previous, "You are a helpful assistant.");
+        assert_eq!(change.current, "Answer like a pirate.");
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::error::ChatError;
+
+// BatchStatus tells whether the prompts of a batch are still being answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Running,
+    // Completed has an answer or an error for every prompt
+    Completed,
+}
+
+impl fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            BatchStatus::Running => "running",
+            BatchStatus::Completed => "completed",
+        };
+        f.write_str(status)
+    }
+}
+
+impl FromStr for BatchStatus {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(BatchStatus::Running),
+            "completed" => Ok(BatchStatus::Completed),
+            _ => Err(ChatError::InvalidMessage(format!(
+                "unknown batch status {}",
+                s
+            ))),
+        }
+    }
+}
+
+// BatchItem is one prompt of a batch, answered in a chat of its own; error tells why a prompt
+// got no answer, the other prompts are answered regardless
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub prompt: String,
+    pub chat_id: Option<Uuid>,
+    pub content: Option<String>,
+    pub error: Option<String>,
+    // job_id is the completion job answering the prompt of a batch run in the background
+    #[serde(default)]
+    pub job_id: Option<Uuid>,
+}
+
+// Batch is a set of independent prompts of a user answered together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Batch {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub status: BatchStatus,
+    pub items: Vec<BatchItem>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Batch {
+    // new starts a batch of at least one prompt, none of them empty
+    pub fn new(tenant_id: Uuid, user_id: Uuid, prompts: Vec<String>) -> Result<Self, ChatError> {
+        if prompts.is_empty() {
+            return Err(ChatError::InvalidMessage(
+                "a batch needs at least one prompt".to_string(),
+            ));
+        }
+        if let Some(index) = prompts.iter().position(|prompt| prompt.trim().is_empty()) {
+            return Err(ChatError::InvalidMessage(format!(
+                "prompt {} of the batch is empty",
+                index
+            )));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            status: BatchStatus::Running,
+            items: prompts
+                .into_iter()
+                .map(|prompt| BatchItem {
+                    prompt,
+                    ..BatchItem::default()
+                })
+                .collect(),
+            created_at: chrono::Utc::now(),
+            completed_at: None,
+        })
+    }
+
+    // complete records the outcome of every prompt, in the order they were sent
+    pub fn complete(&mut self, items: Vec<BatchItem>) {
+        self.items = items;
+        self.status = BatchStatus::Completed;
+        self.completed_at = Some(chrono::Utc::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let tenant_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let batch = Batch::new(
+            tenant_id,
+            user_id,
+            vec!["Hi".to_string(), "Bye".to_string()],
+        )
+        .unwrap();
+        assert_eq!(batch.status, BatchStatus::Running);
+        assert_eq!(batch.items.len(), 2);
+        assert_eq!(batch.items[1].prompt, "Bye");
+        assert!(batch.items[1].content.is_none());
+
+        assert!(Batch::new(tenant_id, user_id, vec![]).is_err());
+        assert!(Batch::new(tenant_id, user_id, vec!["Hi".to_string(), " ".to_string()]).is_err());
+        assert_eq!(
+            "completed".parse::<BatchStatus>(),
+            Ok(BatchStatus::Completed)
+        );
+    }
+}
@@ -1,11 +1,102 @@
-use std::io::Error;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent, RecordedEvent};
+use crate::internal::domain::entity::message::{Message, Role};
 use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+use crate::internal::domain::entity::tool::ToolDefinition;
+use crate::internal::domain::error::{ChatError, ConfigError};
+use crate::internal::domain::language::language_name;
+use crate::internal::domain::token_counter::prompt_tokens;
 
-#[derive(PartialEq)]
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_LENGTH: usize = 32;
+pub const MAX_METADATA_ENTRIES: usize = 32;
+pub const MAX_METADATA_KEY_LENGTH: usize = 64;
+pub const MAX_METADATA_VALUE_LENGTH: usize = 512;
+// PREVIEW_LENGTH bounds the characters of the last message shown in chat listings
+pub const PREVIEW_LENGTH: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatStatus {
+    Active,
+    Ended,
+    Archived,
+    Deleted,
+}
+
+impl fmt::Display for ChatStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            ChatStatus::Active => "active",
+            ChatStatus::Ended => "ended",
+            ChatStatus::Archived => "archived",
+            ChatStatus::Deleted => "deleted",
+        };
+        f.write_str(status)
+    }
+}
+
+impl FromStr for ChatStatus {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(ChatStatus::Active),
+            "ended" => Ok(ChatStatus::Ended),
+            "archived" => Ok(ChatStatus::Archived),
+            "deleted" => Ok(ChatStatus::Deleted),
+            _ => Err(ChatError::InvalidStatus(s.to_string())),
+        }
+    }
+}
+
+// TrimmingPolicy decides what happens when a new message does not fit in the token budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimmingPolicy {
+    // TrimOldest evicts the oldest messages until the new one fits
+    #[default]
+    TrimOldest,
+    // RejectNew refuses the new message and keeps the history untouched
+    RejectNew,
+    // SummarizeAndTrim compresses older messages into a summary close to the budget,
+    // and evicts like TrimOldest when the budget is still exceeded
+    SummarizeAndTrim,
+}
+
+impl fmt::Display for TrimmingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let policy = match self {
+            TrimmingPolicy::TrimOldest => "trim_oldest",
+            TrimmingPolicy::RejectNew => "reject_new",
+            TrimmingPolicy::SummarizeAndTrim => "summarize_and_trim",
+        };
+        f.write_str(policy)
+    }
+}
+
+impl FromStr for TrimmingPolicy {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trim_oldest" => Ok(TrimmingPolicy::TrimOldest),
+            "reject_new" => Ok(TrimmingPolicy::RejectNew),
+            "summarize_and_trim" => Ok(TrimmingPolicy::SummarizeAndTrim),
+            _ => Err(ConfigError::UnknownTrimmingPolicy(s.to_string()).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChatConfig {
     pub model: Model,
     pub temperature: f32,
@@ -15,32 +106,223 @@ pub struct ChatConfig {
     pub max_tokens: usize,
     pub presence_penalty: f32,
     pub frequency_penalty: f32,
+    pub trimming_policy: TrimmingPolicy,
+    // tools are the functions offered to the model, empty disables tool calling
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    #[serde(default)]
+    pub response_format: ResponseFormat,
+    // response_language is the ISO 639-1 code of the language the model is told to answer in,
+    // None answers in the language of the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_language: Option<String>,
+}
+
+impl ChatConfig {
+    // default_for returns the OpenAI defaults, using the whole model context as the token budget
+    pub fn default_for(model: Model) -> Self {
+        let max_tokens = model.max_tokens as usize;
+
+        Self {
+            model,
+            temperature: 1.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::default(),
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        }
+    }
+
+    pub fn builder(model: Model) -> ChatConfigBuilder {
+        ChatConfigBuilder {
+            config: Self::default_for(model),
+        }
+    }
+
+    // validate checks the sampling parameters against the ranges accepted by the API
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(0.0..=2.0).contains(&self.temperature) {
+            return Err(ConfigError::TemperatureOutOfRange(self.temperature));
+        }
+
+        if !(0.0..=1.0).contains(&self.top_p) {
+            return Err(ConfigError::TopPOutOfRange(self.top_p));
+        }
+
+        if self.n < 1 {
+            return Err(ConfigError::InvalidN);
+        }
+
+        let context = self.model.max_tokens as usize;
+        if self.max_tokens > context {
+            return Err(ConfigError::MaxTokensExceedsContext {
+                max_tokens: self.max_tokens,
+                context,
+            });
+        }
+
+        for tool in &self.tools {
+            tool.validate()?;
+        }
+
+        self.response_format.validate()?;
+
+        if let Some(language) = &self.response_language {
+            if language_name(language).is_none() {
+                return Err(ConfigError::UnknownLanguage(language.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ChatConfigBuilder starts from ChatConfig::default_for and validates the result on build
+#[derive(Debug, Clone)]
+pub struct ChatConfigBuilder {
+    config: ChatConfig,
+}
+
+impl ChatConfigBuilder {
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.config.temperature = temperature;
+        self
+    }
+
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.config.top_p = top_p;
+        self
+    }
+
+    pub fn n(mut self, n: u32) -> Self {
+        self.config.n = n;
+        self
+    }
+
+    pub fn stop(mut self, stop: Vec<String>) -> Self {
+        self.config.stop = stop;
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.config.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn presence_penalty(mut self, presence_penalty: f32) -> Self {
+        self.config.presence_penalty = presence_penalty;
+        self
+    }
+
+    pub fn frequency_penalty(mut self, frequency_penalty: f32) -> Self {
+        self.config.frequency_penalty = frequency_penalty;
+        self
+    }
+
+    pub fn trimming_policy(mut self, trimming_policy: TrimmingPolicy) -> Self {
+        self.config.trimming_policy = trimming_policy;
+        self
+    }
+
+    pub fn tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.config.tools = tools;
+        self
+    }
+
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.config.response_format = response_format;
+        self
+    }
+
+    pub fn response_language(mut self, response_language: Option<String>) -> Self {
+        self.config.response_language = response_language;
+        self
+    }
+
+    pub fn build(self) -> Result<ChatConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+// ConfigOverrides changes the model and sampling of a single turn, unset fields keep the
+// chat's config
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverrides {
+    pub model: Option<Model>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Option<Vec<String>>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
 }
 
-pub struct Chat<'a> {
+impl ConfigOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chat {
     pub id: Uuid,
+    // tenant_id is the tenant of the user, the chat is only ever read within it
+    #[serde(default)]
+    pub tenant_id: Uuid,
     pub user_id: Uuid,
-    pub initial_system_message: Message<'a>,
-    pub messages: Vec<Message<'a>>,
-    pub erased_messages: Vec<Message<'a>>,
-    pub status: String,
+    // initial_system_message is only changed through replace_system_message, so the change is
+    // audited, and set_model, which counts it again with the new encoding
+    initial_system_message: Message,
+    pub messages: Vec<Message>,
+    pub erased_messages: Vec<Message>,
+    pub status: ChatStatus,
     pub token_usage: usize,
     pub config: ChatConfig,
+    // title is generated from the first exchange, it stays empty until then
+    #[serde(default)]
+    pub title: Option<String>,
+    // tags label the chat for filtering listings, they are kept normalized by set_tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // metadata is attached by the client and handed back as is, the service never reads it
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    // version counts the saves of the chat, a save is only accepted on top of the version the
+    // chat was loaded at
+    #[serde(default)]
+    pub version: u64,
+    // history_archive is set while the messages are kept in the blob store, the chat is cold
+    // until they are restored
+    #[serde(default)]
+    pub history_archive: Option<HistoryArchive>,
+    // assistant_id is the assistant the chat was started with, its tools are the ones offered
+    #[serde(default)]
+    pub assistant_id: Option<Uuid>,
+    // events are recorded by the chat until the repository stores them in the outbox
+    #[serde(skip)]
+    pub events: Vec<RecordedEvent>,
 }
 
-impl<'a> Chat<'a> {
+impl Chat {
     pub fn new(
         id: Uuid,
         user_id: Uuid,
-        initial_system_message: Message<'a>,
-        messages: Vec<Message<'a>>,
-        erased_messages: Vec<Message<'a>>,
-        status: String,
+        initial_system_message: Message,
+        messages: Vec<Message>,
+        erased_messages: Vec<Message>,
+        status: ChatStatus,
         token_usage: usize,
         config: ChatConfig,
     ) -> Self {
         Self {
             id,
+            tenant_id: DEFAULT_TENANT_ID,
             user_id,
             initial_system_message,
             messages,
@@ -48,183 +330,853 @@ impl<'a> Chat<'a> {
             status,
             token_usage,
             config,
+            title: None,
+            tags: vec![],
+            metadata: BTreeMap::new(),
+            version: 0,
+            history_archive: None,
+            assistant_id: None,
+            events: vec![],
         }
     }
 
-    // validate checks if the chat is valid
-    pub fn validate(&self) -> Result<(), Error> {
-        if self.status != "active" && self.status != "ended" {
-            return Err(Error::new(
-                std::io::ErrorKind::Other,
-                "Chat status is invalid",
-            ));
+    pub fn initial_system_message(&self) -> &Message {
+        &self.initial_system_message
+    }
+
+    pub fn with_tenant(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_history_archive(mut self, history_archive: Option<HistoryArchive>) -> Self {
+        self.history_archive = history_archive;
+        self
+    }
+
+    pub fn with_assistant(mut self, assistant_id: Option<Uuid>) -> Self {
+        self.assistant_id = assistant_id;
+        self
+    }
+
+    // set_tags replaces the tags of the chat with their normalized form, repeated tags are kept
+    // once in the order they were first given
+    pub fn set_tags(&mut self, tags: &[String]) -> Result<(), ChatError> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = normalize_tag(tag)?;
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        if normalized.len() > MAX_TAGS {
+            return Err(ChatError::InvalidTag(format!(
+                "a chat takes at most {} tags",
+                MAX_TAGS
+            )));
         }
 
-        if self.token_usage > self.config.max_tokens {
-            return Err(Error::new(
-                std::io::ErrorKind::Other,
-                "Chat token usage is invalid",
-            ));
+        self.tags = normalized;
+        Ok(())
+    }
+
+    // set_metadata replaces the metadata of the chat, keys and values are bounded so listings
+    // stay small
+    pub fn set_metadata(&mut self, metadata: BTreeMap<String, String>) -> Result<(), ChatError> {
+        if metadata.len() > MAX_METADATA_ENTRIES {
+            return Err(ChatError::InvalidMetadata(format!(
+                "a chat takes at most {} entries",
+                MAX_METADATA_ENTRIES
+            )));
+        }
+        for (key, value) in &metadata {
+            if key.trim().is_empty() || key.chars().count() > MAX_METADATA_KEY_LENGTH {
+                return Err(ChatError::InvalidMetadata(format!(
+                    "key {:?} must have 1 to {} characters",
+                    key, MAX_METADATA_KEY_LENGTH
+                )));
+            }
+            if value.chars().count() > MAX_METADATA_VALUE_LENGTH {
+                return Err(ChatError::InvalidMetadata(format!(
+                    "value of {} is longer than {} characters",
+                    key, MAX_METADATA_VALUE_LENGTH
+                )));
+            }
         }
 
-        if self.status != "ended" && self.status != "active" {
-            return Err(Error::new(
-                std::io::ErrorKind::Other,
-                "Chat status is invalid",
-            ));
+        self.metadata = metadata;
+        Ok(())
+    }
+
+    // record keeps an event that happened to the chat for the outbox
+    pub fn record(&mut self, event: ChatEvent) {
+        self.events.push(RecordedEvent::new(event));
+    }
+
+    // outbox_events returns the recorded events with the chat they belong to
+    pub fn outbox_events(&self) -> Vec<OutboxEvent> {
+        self.events
+            .iter()
+            .map(|recorded| OutboxEvent {
+                id: recorded.id,
+                tenant_id: self.tenant_id,
+                user_id: self.user_id,
+                chat_id: self.id,
+                event: recorded.event.clone(),
+                occurred_at: recorded.occurred_at,
+            })
+            .collect()
+    }
+
+    // first_exchange returns the first user message and the assistant answer that followed it,
+    // tool requests in between are skipped
+    pub fn first_exchange(&self) -> Option<(&Message, &Message)> {
+        let start = self
+            .messages
+            .iter()
+            .position(|message| message.role == Role::User)?;
+        let question = &self.messages[start];
+        let answer = self.messages[start + 1..].iter().find(|message| {
+            message.role == Role::Assistant
+                && message.tool_calls.is_empty()
+                && !message.content.trim().is_empty()
+        })?;
+
+        Some((question, answer))
+    }
+
+    // last_exchange returns the last user message and the assistant messages that answered it,
+    // tool calls and results are left out
+    pub fn last_exchange(&self) -> Vec<&Message> {
+        let start = self
+            .messages
+            .iter()
+            .rposition(|message| message.role == Role::User)
+            .unwrap_or(self.messages.len());
+
+        self.messages[start..]
+            .iter()
+            .filter(|message| matches!(message.role, Role::User | Role::Assistant))
+            .filter(|message| message.tool_calls.is_empty() && !message.content.trim().is_empty())
+            .collect()
+    }
+
+    // last_message_preview is the start of the last user or assistant message on one line,
+    // tool calls and results are skipped
+    pub fn last_message_preview(&self) -> Option<String> {
+        if let Some(archive) = &self.history_archive {
+            return archive.last_message_preview.clone();
         }
+        let message = self.messages.iter().rev().find(|message| {
+            matches!(message.role, Role::User | Role::Assistant)
+                && message.tool_calls.is_empty()
+                && !message.content.trim().is_empty()
+        })?;
+        let content = message
+            .content
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Some(match content.char_indices().nth(PREVIEW_LENGTH) {
+            Some((end, _)) => format!("{}…", &content[..end]),
+            None => content,
+        })
+    }
+
+    // validate checks if the chat is valid
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.token_usage > self.config.max_tokens {
+            return Err(ChatError::TokenLimitExceeded {
+                usage: self.token_usage,
+                limit: self.config.max_tokens,
+            });
+        }
+
+        self.config.model.validate()?;
 
         Ok(())
     }
 
-    // add_message adds a message to the chat
-    pub fn add_message(&mut self, message: Message<'a>) -> Result<(), Error> {
-        if self.status == "ended" {
-            return Err(Error::new(
-                std::io::ErrorKind::Other,
-                "Chat has already ended",
+    // add_message adds a message to the chat, applying the trimming policy when the budget is exceeded
+    pub fn add_message(&mut self, message: Message) -> Result<(), ChatError> {
+        self.ensure_active()?;
+        if !message.attachments.is_empty() && !self.config.model.supports_vision() {
+            return Err(ChatError::AttachmentsNotSupported(
+                self.config.model.name.clone(),
             ));
         }
+        self.refresh_token_usage();
+
+        let minimum_usage = prompt_tokens(&self.initial_system_message, &[]) + message.tokens;
+        if minimum_usage > self.config.max_tokens {
+            return Err(ChatError::TokenLimitExceeded {
+                usage: minimum_usage,
+                limit: self.config.max_tokens,
+            });
+        }
 
-        if self.config.max_tokens >= message.tokens + self.token_usage {
-            self.messages.push(message.clone());
-            self.refresh_token_usage();
-        } else {
-            self.erased_messages.push(message.clone());
+        if self.token_usage + message.tokens > self.config.max_tokens {
+            match self.config.trimming_policy {
+                TrimmingPolicy::RejectNew => {
+                    return Err(ChatError::TokenLimitExceeded {
+                        usage: self.token_usage + message.tokens,
+                        limit: self.config.max_tokens,
+                    });
+                }
+                TrimmingPolicy::TrimOldest | TrimmingPolicy::SummarizeAndTrim => {
+                    self.evict_oldest(message.tokens);
+                }
+            }
         }
 
+        self.record(ChatEvent::MessageAdded {
+            message_id: message.id,
+            role: message.role,
+            tokens: message.tokens,
+        });
+        self.messages.push(message);
+        self.refresh_token_usage();
+
         Ok(())
     }
 
-    // refresh_token_usage is called after a message is added to the chat to update the token_usage
-    pub fn refresh_token_usage(&mut self) {
-        self.token_usage = self
+    // rewind_to drops the user message and everything after it so the chat continues from an
+    // edited revision, the dropped branch moves to erased_messages so the history is kept
+    pub fn rewind_to(&mut self, message_id: Uuid) -> Result<Message, ChatError> {
+        self.ensure_active()?;
+
+        let position = self
             .messages
             .iter()
-            .fold(0, |acc, message| acc + message.tokens);
-    }
+            .position(|message| message.id == message_id)
+            .ok_or_else(|| {
+                ChatError::InvalidMessage(format!("message {} is not in the chat", message_id))
+            })?;
+        if self.messages[position].role != Role::User {
+            return Err(ChatError::InvalidMessage(
+                "only user messages can be edited".to_string(),
+            ));
+        }
 
-    // get_messages returns a copy of the messages
-    pub fn get_messages(&self) -> Vec<Message<'a>> {
-        self.messages.iter().map(|msg| msg.clone()).collect()
-    }
+        let rewound = self.messages[position].clone();
+        self.erased_messages.extend(self.messages.drain(position..));
+        self.refresh_token_usage();
 
-    pub fn count_messages(&self) -> usize {
-        self.messages.len()
+        Ok(rewound)
     }
 
-    // end sets the status of the chat to "ended"
-    pub fn end(&mut self) {
-        self.status = "ended".to_string();
+    // select_candidate makes one of the candidates the model offered for the last reply its
+    // content, the others are discarded; only the reply the chat continues from can be changed
+    pub fn select_candidate(
+        &mut self,
+        message_id: Uuid,
+        candidate: usize,
+    ) -> Result<&Message, ChatError> {
+        self.ensure_active()?;
+
+        let position = self
+            .messages
+            .iter()
+            .position(|message| message.id == message_id)
+            .ok_or_else(|| {
+                ChatError::InvalidMessage(format!("message {} is not in the chat", message_id))
+            })?;
+        let message = &self.messages[position];
+        if position + 1 != self.messages.len() || message.role != Role::Assistant {
+            return Err(ChatError::InvalidMessage(
+                "only the last reply can take a candidate".to_string(),
+            ));
+        }
+        let content = message.candidates.get(candidate).ok_or_else(|| {
+            ChatError::InvalidMessage(format!("message has no candidate {}", candidate))
+        })?;
+
+        let mut selected = message.clone().with_content(content);
+        selected.candidates.clear();
+        let previous = std::mem::replace(&mut self.messages[position], selected);
+        let usage = prompt_tokens(&self.initial_system_message, &self.messages);
+        if usage > self.config.max_tokens {
+            self.messages[position] = previous;
+            return Err(ChatError::TokenLimitExceeded {
+                usage,
+                limit: self.config.max_tokens,
+            });
+        }
+        self.token_usage = usage;
+
+        Ok(&self.messages[position])
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // fork copies the chat up to and including the given message, or entirely when there is none,
+    // into a new active chat with the same owner and config; every message gets a new id so the
+    // chats never share one
+    pub fn fork(&self, id: Uuid, up_to: Option<Uuid>) -> Result<Chat, ChatError> {
+        if self.is_cold() {
+            return Err(ChatError::ChatArchived);
+        }
+        let end = match up_to {
+            Some(message_id) => {
+                self.messages
+                    .iter()
+                    .position(|message| message.id == message_id)
+                    .ok_or_else(|| {
+                        ChatError::InvalidMessage(format!(
+                            "message {} is not in the chat",
+                            message_id
+                        ))
+                    })?
+                    + 1
+            }
+            None => self.messages.len(),
+        };
+        if matches!(self.messages.get(end), Some(next) if next.role == Role::Tool) {
+            return Err(ChatError::InvalidMessage(
+                "cannot fork before the tool calls are answered".to_string(),
+            ));
+        }
 
-    #[test]
-    fn test_invalid_chat() {
-        let id = Uuid::new_v4();
-        let user_id = Uuid::new_v4();
-        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
-        let initial_system_message = Message::new(
-            Uuid::new_v4(),
-            "system",
-            "Hello, I'm the system. How can I help you?",
-            0,
-            &model,
-            chrono::Utc::now(),
-        );
-        let messages = vec![];
-        let erased_messages = vec![];
-        let status = "invalid";
-        let token_usage = 0;
-        let config = ChatConfig {
-            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
-            temperature: 0.0,
-            top_p: 0.0,
-            n: 0,
-            stop: vec![],
-            max_tokens: 0,
-            presence_penalty: 0.0,
-            frequency_penalty: 0.0,
+        let copy = |message: &Message| Message {
+            id: Uuid::new_v4(),
+            ..message.clone()
         };
-        let chat = Chat::new(
+        let mut fork = Chat::new(
             id,
-            user_id,
-            initial_system_message,
-            messages,
-            erased_messages,
-            status.to_string(),
-            token_usage,
-            config,
-        );
+            self.user_id,
+            copy(&self.initial_system_message),
+            self.messages[..end].iter().map(copy).collect(),
+            vec![],
+            ChatStatus::Active,
+            0,
+            self.config.clone(),
+        )
+        .with_tenant(self.tenant_id)
+        .with_assistant(self.assistant_id);
+        fork.refresh_token_usage();
+        fork.record(ChatEvent::ChatCreated {
+            model: fork.config.model.name.clone(),
+        });
 
-        assert_eq!(
-            chat.validate().unwrap_err().kind(),
-            std::io::ErrorKind::Other
-        );
+        Ok(fork)
     }
 
-    #[test]
-    fn test_validate_chat() {
-        let id = Uuid::new_v4();
-        let user_id = Uuid::new_v4();
-        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
-        let initial_system_message = Message::new(
-            Uuid::new_v4(),
-            "system",
-            "Hello, I'm the system. How can I help you?",
+    // replace_system_message is the only way the instructions of an active chat change, it
+    // returns the ones replaced so the change can be audited; the history has to fit the token
+    // budget along with the new ones
+    pub fn replace_system_message(&mut self, content: &str) -> Result<Message, ChatError> {
+        self.ensure_active()?;
+        self.put_system_message(content)
+    }
+
+    // with_context returns the chat as one turn should be prompted with, the context appended to
+    // its instructions; the chat itself keeps them, like it keeps its config on with_overrides
+    pub fn with_context(&self, context: &str) -> Result<Chat, ChatError> {
+        let mut chat = self.clone();
+        let content = format!("{}\n\n{}", self.initial_system_message.content, context);
+        chat.put_system_message(&content)?;
+
+        Ok(chat)
+    }
+
+    // seen_by returns the chat as an agent of an orchestrated chat is prompted with it: its
+    // instructions replace the system message and the replies of the other agents become user
+    // messages prefixed with their speaker, the tool exchanges of the other agents are left out
+    pub fn seen_by(&self, speaker: &str, instructions: &str) -> Result<Chat, ChatError> {
+        let mut chat = self.clone();
+        chat.messages = self
+            .messages
+            .iter()
+            .filter_map(|message| match &message.speaker {
+                Some(other) if other != speaker => (message.role == Role::Assistant
+                    && message.tool_calls.is_empty()
+                    && !message.content.is_empty())
+                .then(|| {
+                    Message::new(
+                        message.id,
+                        Role::User,
+                        &format!("{}: {}", other, message.content),
+                        message.tokens,
+                        message.model.clone(),
+                        message.created_at,
+                    )
+                }),
+                _ => Some(message.clone()),
+            })
+            .collect();
+        chat.put_system_message(instructions)?;
+
+        Ok(chat)
+    }
+
+    // prompt_messages are the messages in the order a provider is sent them, the system message
+    // is pinned first whatever the history holds
+    pub fn prompt_messages(&self) -> Vec<&Message> {
+        std::iter::once(&self.initial_system_message)
+            .chain(&self.messages)
+            .collect()
+    }
+
+    fn put_system_message(&mut self, content: &str) -> Result<Message, ChatError> {
+        let system = Message::new(
+            self.initial_system_message.id,
+            Role::System,
+            content,
             0,
-            &model,
+            self.config.model.clone(),
             chrono::Utc::now(),
         );
-        let messages = vec![];
-        let erased_messages = vec![];
-        let status = "active";
-        let token_usage = 0;
-        let config = ChatConfig {
-            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
-            temperature: 0.0,
-            top_p: 0.0,
-            n: 0,
-            stop: vec![],
-            max_tokens: 0,
-            presence_penalty: 0.0,
-            frequency_penalty: 0.0,
-        };
-        let chat = Chat::new(
-            id,
-            user_id,
-            initial_system_message,
-            messages,
-            erased_messages,
-            status.to_string(),
-            token_usage,
-            config,
-        );
+        system.validate()?;
+        let usage = prompt_tokens(&system, &self.messages);
+        if usage > self.config.max_tokens {
+            return Err(ChatError::TokenLimitExceeded {
+                usage,
+                limit: self.config.max_tokens,
+            });
+        }
 
-        assert_eq!(chat.validate().unwrap(), ());
+        self.token_usage = usage;
+
+        Ok(std::mem::replace(&mut self.initial_system_message, system))
     }
 
-    #[test]
-    fn test_ended_chat() {
-        let id = Uuid::new_v4();
-        let user_id = Uuid::new_v4();
-        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
-        let initial_system_message = Message::new(
-            Uuid::new_v4(),
-            "system",
-            "Hello, I'm the system. How can I help you?",
-            0,
-            &model,
-            chrono::Utc::now(),
+    // set_model moves an active chat to another model, the token budget shrinks to a smaller
+    // context and the history has to fit in it; the history is never trimmed to make room
+    pub fn set_model(&mut self, model: Model) -> Result<(), ChatError> {
+        self.ensure_active()?;
+        model.validate()?;
+
+        let mut config = self.config.clone();
+        config.max_tokens = config.max_tokens.min(model.max_tokens as usize);
+        config.model = model;
+        config.validate()?;
+
+        // the system message is counted again with the encoding of the new model
+        let system = Message::new(
+            self.initial_system_message.id,
+            Role::System,
+            &self.initial_system_message.content,
+            self.initial_system_message.tokens,
+            config.model.clone(),
+            self.initial_system_message.created_at,
         );
-        let messages = vec![];
-        let erased_messages = vec![];
-        let status = "ended";
-        let token_usage = 0;
+        let usage = prompt_tokens(&system, &self.messages);
+        if usage > config.max_tokens {
+            return Err(ChatError::TokenLimitExceeded {
+                usage,
+                limit: config.max_tokens,
+            });
+        }
+
+        self.config = config;
+        self.initial_system_message = system;
+        self.token_usage = usage;
+
+        Ok(())
+    }
+
+    pub fn set_temperature(&mut self, temperature: f32) -> Result<(), ChatError> {
+        self.ensure_active()?;
+
+        let mut config = self.config.clone();
+        config.temperature = temperature;
+        config.validate()?;
+        self.config = config;
+
+        Ok(())
+    }
+
+    pub fn set_response_language(&mut self, language: Option<String>) -> Result<(), ChatError> {
+        self.ensure_active()?;
+
+        let mut config = self.config.clone();
+        config.response_language = language;
+        config.validate()?;
+        self.config = config;
+
+        Ok(())
+    }
+
+    // with_overrides returns the chat as one turn should be prompted with, the chat itself keeps
+    // its config; the history has to fit an overriding model like it does on set_model
+    pub fn with_overrides(&self, overrides: &ConfigOverrides) -> Result<Chat, ChatError> {
+        let mut chat = self.clone();
+        if let Some(model) = &overrides.model {
+            chat.set_model(model.clone())?;
+        }
+
+        let config = &mut chat.config;
+        if let Some(temperature) = overrides.temperature {
+            config.temperature = temperature;
+        }
+        if let Some(top_p) = overrides.top_p {
+            config.top_p = top_p;
+        }
+        if let Some(stop) = &overrides.stop {
+            config.stop = stop.clone();
+        }
+        if let Some(presence_penalty) = overrides.presence_penalty {
+            config.presence_penalty = presence_penalty;
+        }
+        if let Some(frequency_penalty) = overrides.frequency_penalty {
+            config.frequency_penalty = frequency_penalty;
+        }
+        config.validate()?;
+
+        Ok(chat)
+    }
+
+    fn ensure_active(&self) -> Result<(), ChatError> {
+        match self.status {
+            ChatStatus::Active => Ok(()),
+            ChatStatus::Ended => Err(ChatError::ChatEnded),
+            ChatStatus::Archived => Err(ChatError::ChatArchived),
+            ChatStatus::Deleted => Err(ChatError::ChatDeleted),
+        }
+    }
+
+    // evict_oldest moves the oldest messages to erased_messages until there is room for tokens,
+    // the system message is not part of the history so it is never evicted
+    fn evict_oldest(&mut self, tokens: usize) {
+        while !self.messages.is_empty() && self.token_usage + tokens > self.config.max_tokens {
+            let evicted = self.messages.remove(0);
+            self.token_usage -= evicted.tokens;
+            self.erased_messages.push(evicted);
+        }
+    }
+
+    // summarize replaces every message but the last keep_recent with the summary,
+    // the replaced messages move to erased_messages so the full history is kept; the summary
+    // opens the history, after the system message
+    pub fn summarize(&mut self, keep_recent: usize, summary: Message) {
+        let summarized = self.messages.len().saturating_sub(keep_recent);
+        if summarized == 0 {
+            return;
+        }
+
+        self.erased_messages
+            .extend(self.messages.drain(..summarized));
+        self.messages.insert(0, summary);
+        self.refresh_token_usage();
+    }
+
+    // refresh_token_usage recomputes the prompt size: system message, history and reply priming
+    pub fn refresh_token_usage(&mut self) {
+        self.token_usage = prompt_tokens(&self.initial_system_message, &self.messages);
+    }
+
+    // get_messages returns a copy of the messages
+    pub fn get_messages(&self) -> Vec<Message> {
+        self.messages.clone()
+    }
+
+    // count_messages counts the archived messages of a cold chat
+    pub fn count_messages(&self) -> usize {
+        match &self.history_archive {
+            Some(archive) => archive.message_count,
+            None => self.messages.len(),
+        }
+    }
+
+    pub fn is_cold(&self) -> bool {
+        self.history_archive.is_some()
+    }
+
+    // archive_history archives the chat and takes its messages out once they are stored under
+    // key; the system message stays, and listings keep showing the count and preview
+    pub fn archive_history(&mut self, key: String) -> Result<(), ChatError> {
+        if self.is_cold() {
+            return Err(ChatError::ChatArchived);
+        }
+        if self.status != ChatStatus::Archived {
+            self.archive()?;
+        }
+
+        self.history_archive = Some(HistoryArchive {
+            key,
+            message_count: self.messages.len(),
+            last_message_preview: self.last_message_preview(),
+            archived_at: chrono::Utc::now(),
+        });
+        self.messages.clear();
+        self.erased_messages.clear();
+
+        Ok(())
+    }
+
+    // restore_history puts back the messages read from the archive of a cold chat, which stays
+    // archived until it is reopened
+    pub fn restore_history(&mut self, messages: Vec<Message>, erased_messages: Vec<Message>) {
+        self.messages = messages;
+        self.erased_messages = erased_messages;
+        self.history_archive = None;
+    }
+
+    // end closes an active chat for new messages
+    pub fn end(&mut self) -> Result<(), ChatError> {
+        self.transition(ChatStatus::Ended, &[ChatStatus::Active])?;
+        self.record(ChatEvent::ChatEnded);
+
+        Ok(())
+    }
+
+    // archive hides an active or ended chat, archived chats can only be reopened
+    pub fn archive(&mut self) -> Result<(), ChatError> {
+        self.transition(
+            ChatStatus::Archived,
+            &[ChatStatus::Active, ChatStatus::Ended],
+        )
+    }
+
+    // reopen makes an ended or archived chat active again, a cold chat needs its history
+    // restored first
+    pub fn reopen(&mut self) -> Result<(), ChatError> {
+        if self.is_cold() {
+            return Err(ChatError::ChatArchived);
+        }
+        self.transition(
+            ChatStatus::Active,
+            &[ChatStatus::Ended, ChatStatus::Archived],
+        )
+    }
+
+    // delete marks the chat for purging, deleted chats cannot be reopened
+    pub fn delete(&mut self) -> Result<(), ChatError> {
+        self.transition(
+            ChatStatus::Deleted,
+            &[ChatStatus::Active, ChatStatus::Ended, ChatStatus::Archived],
+        )
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.status == ChatStatus::Deleted
+    }
+
+    fn transition(&mut self, to: ChatStatus, allowed_from: &[ChatStatus]) -> Result<(), ChatError> {
+        if !allowed_from.contains(&self.status) {
+            return Err(ChatError::InvalidTransition {
+                from: self.status.to_string(),
+                to: to.to_string(),
+            });
+        }
+
+        self.status = to;
+        Ok(())
+    }
+}
+
+// normalize_tag trims and lowercases the tag, which may only hold letters, digits and - _ : .
+pub fn normalize_tag(tag: &str) -> Result<String, ChatError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(ChatError::InvalidTag(format!(
+            "{:?} must have 1 to {} characters",
+            tag, MAX_TAG_LENGTH
+        )));
+    }
+    if let Some(c) = tag
+        .chars()
+        .find(|c| !c.is_alphanumeric() && !matches!(c, '-' | '_' | ':' | '.'))
+    {
+        return Err(ChatError::InvalidTag(format!("{:?} contains {:?}", tag, c)));
+    }
+
+    Ok(tag)
+}
+
+// HistoryArchive is where the messages of a cold chat are kept, with what listings show of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryArchive {
+    pub key: String,
+    pub message_count: usize,
+    pub last_message_preview: Option<String>,
+    pub archived_at: chrono::DateTime<chrono::Utc>,
+}
+
+// ChatSummary is what chat listings show, the repositories keep it as a projection written
+// with every chat so listings never read the messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatSummary {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub status: ChatStatus,
+    pub model: String,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
+    pub token_usage: usize,
+    pub message_count: usize,
+    pub last_message_preview: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // last_activity_at is when the chat was last saved
+    pub last_activity_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ChatSummary {
+    // of projects the chat stored at created_at and last saved at last_activity_at
+    pub fn of(
+        chat: &Chat,
+        created_at: chrono::DateTime<chrono::Utc>,
+        last_activity_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id: chat.id,
+            tenant_id: chat.tenant_id,
+            user_id: chat.user_id,
+            status: chat.status,
+            model: chat.config.model.name.clone(),
+            title: chat.title.clone(),
+            tags: chat.tags.clone(),
+            metadata: chat.metadata.clone(),
+            token_usage: chat.token_usage,
+            message_count: chat.count_messages(),
+            last_message_preview: chat.last_message_preview(),
+            created_at,
+            last_activity_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::attachment::Attachment;
+    use crate::internal::domain::entity::tool::ToolCall;
+
+    #[test]
+    fn test_invalid_chat() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 10;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 0,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        assert_eq!(
+            chat.validate().unwrap_err(),
+            ChatError::TokenLimitExceeded {
+                usage: 10,
+                limit: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_chat() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 0;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 0,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        assert_eq!(chat.validate().unwrap(), ());
+    }
+
+    #[test]
+    fn test_ended_chat() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Ended;
+        let token_usage = 0;
         let config = ChatConfig {
             model: Model::new("gpt-3.5-turbo".to_string(), 4096),
             temperature: 0.0,
@@ -234,6 +1186,10 @@ mod tests {
             max_tokens: 0,
             presence_penalty: 0.0,
             frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
         };
         let mut chat = Chat::new(
             id,
@@ -241,23 +1197,23 @@ mod tests {
             initial_system_message,
             messages,
             erased_messages,
-            status.to_string(),
+            status,
             token_usage,
             config,
         );
 
         let message = Message::new(
             Uuid::new_v4(),
-            "user",
+            Role::User,
             "Hello, I'm the user. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
 
         assert_eq!(
-            chat.add_message(message.clone()).unwrap_err().kind(),
-            std::io::ErrorKind::Other
+            chat.add_message(message.clone()).unwrap_err(),
+            ChatError::ChatEnded
         );
     }
 
@@ -268,15 +1224,15 @@ mod tests {
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let initial_system_message = Message::new(
             Uuid::new_v4(),
-            "system",
+            Role::System,
             "Hello, I'm the system. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
         let messages = vec![];
         let erased_messages = vec![];
-        let status = "active";
+        let status = ChatStatus::Active;
         let token_usage = 0;
         let config = ChatConfig {
             model: Model::new("gpt-3.5-turbo".to_string(), 4096),
@@ -287,6 +1243,10 @@ mod tests {
             max_tokens: 5000,
             presence_penalty: 0.0,
             frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
         };
         let mut chat = Chat::new(
             id,
@@ -294,50 +1254,49 @@ mod tests {
             initial_system_message,
             messages,
             erased_messages,
-            status.to_string(),
+            status,
             token_usage,
             config,
         );
 
-        let message = Message::new(
+        let mut message = Message::new(
             Uuid::new_v4(),
-            "user",
+            Role::User,
             "Hello, I'm the user. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
-
-        // check number of tokens on message
-        assert_eq!(message.tokens, 4083);
+        message.tokens = 3000;
+        let overhead = prompt_tokens(&chat.initial_system_message, &[]);
 
         chat.add_message(message.clone()).unwrap();
         assert_eq!(chat.messages.len(), 1);
         assert_eq!(chat.erased_messages.len(), 0);
-        assert_eq!(chat.token_usage, 4083);
+        assert_eq!(chat.token_usage, overhead + 3000);
 
         chat.add_message(message.clone()).unwrap();
         assert_eq!(chat.messages.len(), 1);
         assert_eq!(chat.erased_messages.len(), 1);
-        assert_eq!(chat.token_usage, 4083);
+        assert_eq!(chat.token_usage, overhead + 3000);
     }
 
     #[test]
-    fn test_refresh_token_usage() {
+    fn test_add_message_trims_oldest() {
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let initial_system_message = Message::new(
             Uuid::new_v4(),
-            "system",
+            Role::System,
             "Hello, I'm the system. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
         let messages = vec![];
         let erased_messages = vec![];
-        let status = "active";
+        let status = ChatStatus::Active;
         let token_usage = 0;
         let config = ChatConfig {
             model: Model::new("gpt-3.5-turbo".to_string(), 4096),
@@ -345,9 +1304,13 @@ mod tests {
             top_p: 0.0,
             n: 0,
             stop: vec![],
-            max_tokens: 0,
+            max_tokens: 5000,
             presence_penalty: 0.0,
             frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
         };
         let mut chat = Chat::new(
             id,
@@ -355,55 +1318,56 @@ mod tests {
             initial_system_message,
             messages,
             erased_messages,
-            status.to_string(),
+            status,
             token_usage,
             config,
         );
 
-        let message = Message::new(
+        let mut message = Message::new(
             Uuid::new_v4(),
-            "user",
+            Role::User,
             "Hello, I'm the user. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
+        message.tokens = 2000;
+        let overhead = prompt_tokens(&chat.initial_system_message, &[]);
 
-        chat.add_message(message.clone()).unwrap();
-        assert_eq!(chat.token_usage, 0);
-
-        chat.refresh_token_usage();
-        assert_eq!(chat.token_usage, 0);
-
-        chat.add_message(message.clone()).unwrap();
-        assert_eq!(chat.token_usage, 0);
+        let first = message.clone();
+        chat.add_message(first.clone()).unwrap();
 
-        chat.refresh_token_usage();
-        assert_eq!(chat.token_usage, 0);
+        let mut second = message.clone();
+        second.id = Uuid::new_v4();
+        chat.add_message(second.clone()).unwrap();
 
-        chat.add_message(message.clone()).unwrap();
-        assert_eq!(chat.token_usage, 0);
+        let mut third = message.clone();
+        third.id = Uuid::new_v4();
+        chat.add_message(third.clone()).unwrap();
 
-        chat.refresh_token_usage();
-        assert_eq!(chat.token_usage, 0);
+        let ids: Vec<Uuid> = chat.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![second.id, third.id]);
+        assert_eq!(chat.erased_messages[0].id, first.id);
+        assert_eq!(chat.token_usage, overhead + 4000);
+        assert_eq!(chat.prompt_messages()[0], &chat.initial_system_message);
     }
 
     #[test]
-    fn test_new() {
+    fn test_add_message_reject_new() {
         let id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let initial_system_message = Message::new(
             Uuid::new_v4(),
-            "system",
+            Role::System,
             "Hello, I'm the system. How can I help you?",
             0,
-            &model,
+            model.clone(),
             chrono::Utc::now(),
         );
         let messages = vec![];
         let erased_messages = vec![];
-        let status = "active";
+        let status = ChatStatus::Active;
         let token_usage = 0;
         let config = ChatConfig {
             model: Model::new("gpt-3.5-turbo".to_string(), 4096),
@@ -411,24 +1375,1209 @@ mod tests {
             top_p: 0.0,
             n: 0,
             stop: vec![],
-            max_tokens: 0,
+            max_tokens: 5000,
             presence_penalty: 0.0,
             frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::RejectNew,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
         };
-        let chat = Chat::new(
+        let mut chat = Chat::new(
             id,
             user_id,
             initial_system_message,
             messages,
             erased_messages,
-            status.to_string(),
+            status,
             token_usage,
             config,
         );
 
-        assert_eq!(chat.id, id);
-        assert_eq!(chat.user_id, user_id);
-        assert_eq!(chat.status, status);
-        assert_eq!(chat.token_usage, token_usage);
+        let mut message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, I'm the user. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        message.tokens = 3000;
+        let overhead = prompt_tokens(&chat.initial_system_message, &[]);
+
+        chat.add_message(message.clone()).unwrap();
+
+        assert_eq!(
+            chat.add_message(message.clone()).unwrap_err(),
+            ChatError::TokenLimitExceeded {
+                usage: overhead + 6000,
+                limit: 5000
+            }
+        );
+        assert_eq!(chat.messages.len(), 1);
+        assert_eq!(chat.erased_messages.len(), 0);
+        assert_eq!(chat.token_usage, overhead + 3000);
+    }
+
+    #[test]
+    fn test_add_message_larger_than_budget() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 0;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 5000,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let mut chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        let mut message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, I'm the user. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        message.tokens = 6000;
+        let overhead = prompt_tokens(&chat.initial_system_message, &[]);
+
+        assert_eq!(
+            chat.add_message(message).unwrap_err(),
+            ChatError::TokenLimitExceeded {
+                usage: overhead + 6000,
+                limit: 5000
+            }
+        );
+        assert!(chat.messages.is_empty());
+        assert!(chat.erased_messages.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_token_usage() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 0;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 5000,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let mut chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        let mut message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, I'm the user. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        message.tokens = 10;
+        let overhead = prompt_tokens(&chat.initial_system_message, &[]);
+
+        chat.messages.push(message.clone());
+        assert_eq!(chat.token_usage, 0);
+
+        chat.refresh_token_usage();
+        assert_eq!(chat.token_usage, overhead + 10);
+
+        chat.messages.push(message.clone());
+        chat.refresh_token_usage();
+        assert_eq!(chat.token_usage, overhead + 20);
+
+        chat.messages.clear();
+        chat.refresh_token_usage();
+        assert_eq!(chat.token_usage, overhead);
+    }
+
+    #[test]
+    fn test_trimming_policy_from_str() {
+        for policy in [
+            TrimmingPolicy::TrimOldest,
+            TrimmingPolicy::RejectNew,
+            TrimmingPolicy::SummarizeAndTrim,
+        ] {
+            assert_eq!(policy.to_string().parse::<TrimmingPolicy>(), Ok(policy));
+        }
+
+        assert!("drop_everything".parse::<TrimmingPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_new() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 0;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 0,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        assert_eq!(chat.id, id);
+        assert_eq!(chat.user_id, user_id);
+        assert_eq!(chat.status, status);
+        assert_eq!(chat.token_usage, token_usage);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = vec![];
+        let erased_messages = vec![];
+        let status = ChatStatus::Active;
+        let token_usage = 0;
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let mut chat = Chat::new(
+            id,
+            user_id,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage,
+            config,
+        );
+
+        chat.add_message(Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        ))
+        .unwrap();
+
+        let value = serde_json::to_value(&chat).unwrap();
+
+        assert_eq!(value["id"], id.to_string());
+        assert_eq!(value["status"], "active");
+        assert_eq!(value["config"]["trimming_policy"], "trim_oldest");
+        assert_eq!(value["config"]["model"]["name"], "gpt-3.5-turbo");
+        assert_eq!(value["messages"][0]["role"], "user");
+        assert_eq!(value["messages"][0]["content"], "Hello!");
+        // recorded events go to the outbox, they are not part of the serialized chat
+        assert!(value.get("events").is_none());
+
+        let decoded: Chat = serde_json::from_value(value).unwrap();
+        chat.events.clear();
+        assert_eq!(decoded, chat);
+    }
+
+    #[test]
+    fn test_config_default_for() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let config = ChatConfig::default_for(model.clone());
+
+        assert_eq!(config.model, model);
+        assert_eq!(config.max_tokens, 4096);
+        assert_eq!(config.n, 1);
+        assert_eq!(config.trimming_policy, TrimmingPolicy::TrimOldest);
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+
+        let config = ChatConfig::builder(model.clone())
+            .temperature(0.2)
+            .top_p(0.9)
+            .max_tokens(2048)
+            .stop(vec!["\n".to_string()])
+            .trimming_policy(TrimmingPolicy::RejectNew)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.temperature, 0.2);
+        assert_eq!(config.top_p, 0.9);
+        assert_eq!(config.max_tokens, 2048);
+        assert_eq!(config.stop, vec!["\n".to_string()]);
+        assert_eq!(config.trimming_policy, TrimmingPolicy::RejectNew);
+
+        assert_eq!(
+            ChatConfig::builder(model.clone()).temperature(2.5).build(),
+            Err(ConfigError::TemperatureOutOfRange(2.5))
+        );
+        assert_eq!(
+            ChatConfig::builder(model.clone()).top_p(-0.1).build(),
+            Err(ConfigError::TopPOutOfRange(-0.1))
+        );
+        assert_eq!(
+            ChatConfig::builder(model.clone()).n(0).build(),
+            Err(ConfigError::InvalidN)
+        );
+        assert_eq!(
+            ChatConfig::builder(model).max_tokens(8192).build(),
+            Err(ConfigError::MaxTokensExceedsContext {
+                max_tokens: 8192,
+                context: 4096
+            })
+        );
+    }
+
+    #[test]
+    fn test_status_from_str() {
+        assert_eq!("active".parse::<ChatStatus>(), Ok(ChatStatus::Active));
+        assert_eq!("ended".parse::<ChatStatus>(), Ok(ChatStatus::Ended));
+        assert_eq!("archived".parse::<ChatStatus>(), Ok(ChatStatus::Archived));
+        assert_eq!("deleted".parse::<ChatStatus>(), Ok(ChatStatus::Deleted));
+        assert_eq!(
+            "invalid".parse::<ChatStatus>(),
+            Err(ChatError::InvalidStatus("invalid".to_string()))
+        );
+        assert_eq!(ChatStatus::Archived.to_string(), "archived");
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "Hello, I'm the system. How can I help you?",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.0,
+            top_p: 0.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 0,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        );
+
+        assert_eq!(
+            chat.reopen(),
+            Err(ChatError::InvalidTransition {
+                from: "active".to_string(),
+                to: "active".to_string()
+            })
+        );
+
+        chat.end().unwrap();
+        assert_eq!(chat.status, ChatStatus::Ended);
+        assert!(chat.end().is_err());
+
+        chat.archive().unwrap();
+        assert_eq!(chat.status, ChatStatus::Archived);
+        assert!(chat.archive().is_err());
+        assert!(chat.end().is_err());
+
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        assert_eq!(chat.add_message(message), Err(ChatError::ChatArchived));
+
+        chat.reopen().unwrap();
+        assert_eq!(chat.status, ChatStatus::Active);
+
+        chat.delete().unwrap();
+        assert!(chat.is_deleted());
+        assert!(chat.delete().is_err());
+        assert!(chat.reopen().is_err());
+        assert_eq!(
+            chat.add_message(Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                "Hello again!",
+                0,
+                model,
+                chrono::Utc::now(),
+            )),
+            Err(ChatError::ChatDeleted)
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        for content in ["one", "two", "three", "four"] {
+            let message = Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            );
+            chat.add_message(message).unwrap();
+        }
+        let summary = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "The user counted to two.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+
+        chat.summarize(2, summary.clone());
+
+        assert_eq!(chat.messages.len(), 3);
+        assert_eq!(chat.messages[0], summary);
+        assert_eq!(chat.messages[1].content, "three");
+        assert_eq!(chat.erased_messages.len(), 2);
+        assert_eq!(
+            chat.token_usage,
+            prompt_tokens(&chat.initial_system_message, &chat.messages)
+        );
+
+        // the system message stays pinned first, ahead of the summary
+        let prompt = chat.prompt_messages();
+        assert_eq!(prompt[0], &chat.initial_system_message);
+        assert_eq!(prompt[1], &summary);
+
+        chat.summarize(3, summary);
+        assert_eq!(chat.messages.len(), 3);
+    }
+
+    #[test]
+    fn test_rewind_to() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        for (role, content) in [
+            (Role::User, "Hello!"),
+            (Role::Assistant, "Hi, how can I help?"),
+            (Role::User, "Tell me a joke"),
+            (Role::Assistant, "Why did the chicken cross the road?"),
+        ] {
+            let message = Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            );
+            chat.add_message(message).unwrap();
+        }
+        let assistant = chat.messages[1].id;
+        let edited = chat.messages[2].clone();
+
+        assert!(matches!(
+            chat.rewind_to(assistant),
+            Err(ChatError::InvalidMessage(_))
+        ));
+        assert!(matches!(
+            chat.rewind_to(Uuid::new_v4()),
+            Err(ChatError::InvalidMessage(_))
+        ));
+
+        assert_eq!(chat.rewind_to(edited.id), Ok(edited.clone()));
+        assert_eq!(chat.messages.len(), 2);
+        assert_eq!(chat.erased_messages.len(), 2);
+        assert_eq!(chat.erased_messages[0], edited);
+        assert_eq!(
+            chat.token_usage,
+            prompt_tokens(&chat.initial_system_message, &chat.messages)
+        );
+
+        chat.end().unwrap();
+        assert_eq!(
+            chat.rewind_to(chat.messages[0].id),
+            Err(ChatError::ChatEnded)
+        );
+    }
+
+    #[test]
+    fn test_select_candidate() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi!").with_candidates(vec!["Hello there!".to_string()]),
+                message(Role::User, "Tell me a joke"),
+                message(Role::Assistant, "Why did the chicken cross the road?").with_candidates(
+                    vec![
+                        "Knock knock.".to_string(),
+                        "A horse walks into a bar.".to_string(),
+                    ],
+                ),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let earlier = chat.messages[1].id;
+        let last = chat.messages[3].id;
+
+        assert!(matches!(
+            chat.select_candidate(earlier, 0),
+            Err(ChatError::InvalidMessage(_))
+        ));
+        assert!(matches!(
+            chat.select_candidate(last, 2),
+            Err(ChatError::InvalidMessage(_))
+        ));
+
+        let selected = chat.select_candidate(last, 1).unwrap().clone();
+        assert_eq!(selected.id, last);
+        assert_eq!(selected.content, "A horse walks into a bar.");
+        assert!(selected.candidates.is_empty());
+        assert_eq!(chat.messages[3], selected);
+        assert_eq!(
+            chat.token_usage,
+            prompt_tokens(&chat.initial_system_message, &chat.messages)
+        );
+        assert!(matches!(
+            chat.select_candidate(last, 0),
+            Err(ChatError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn test_fork() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi, how can I help?"),
+                message(Role::User, "What time is it?"),
+                message(Role::Assistant, "").with_tool_calls(vec![]),
+                message(Role::Tool, "12:00").with_tool_call_id("call_1"),
+                message(Role::Assistant, "It is noon."),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        chat.end().unwrap();
+        let fork_id = Uuid::new_v4();
+
+        let fork = chat.fork(fork_id, Some(chat.messages[1].id)).unwrap();
+
+        assert_eq!(fork.id, fork_id);
+        assert_eq!(fork.user_id, chat.user_id);
+        assert_eq!(fork.status, ChatStatus::Active);
+        assert_eq!(fork.config, chat.config);
+        assert_eq!(fork.messages.len(), 2);
+        assert_eq!(fork.messages[1].content, "Hi, how can I help?");
+        assert_ne!(fork.messages[1].id, chat.messages[1].id);
+        assert_ne!(
+            fork.initial_system_message.id,
+            chat.initial_system_message.id
+        );
+        assert_eq!(
+            fork.token_usage,
+            prompt_tokens(&fork.initial_system_message, &fork.messages)
+        );
+
+        assert_eq!(chat.fork(fork_id, None).unwrap().messages.len(), 6);
+        assert!(matches!(
+            chat.fork(fork_id, Some(chat.messages[3].id)),
+            Err(ChatError::InvalidMessage(_))
+        ));
+        assert!(matches!(
+            chat.fork(fork_id, Some(Uuid::new_v4())),
+            Err(ChatError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_message_with_attachments() {
+        let message = |model: &Model| {
+            Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                "What is in this image?",
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+            .with_attachments(vec![Attachment::url("https://example.com/cat.png")])
+        };
+        let chat = |model: &Model| {
+            Chat::new(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Message::new(
+                    Uuid::new_v4(),
+                    Role::System,
+                    "You are a helpful assistant.",
+                    0,
+                    model.clone(),
+                    chrono::Utc::now(),
+                ),
+                vec![],
+                vec![],
+                ChatStatus::Active,
+                0,
+                ChatConfig::default_for(model.clone()),
+            )
+        };
+
+        let vision = Model::new("gpt-4o".to_string(), 128000);
+        let mut vision_chat = chat(&vision);
+        assert_eq!(vision_chat.add_message(message(&vision)), Ok(()));
+
+        let text = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let mut text_chat = chat(&text);
+        assert_eq!(
+            text_chat.add_message(message(&text)),
+            Err(ChatError::AttachmentsNotSupported(
+                "gpt-3.5-turbo".to_string()
+            ))
+        );
+        assert!(text_chat.messages.is_empty());
+    }
+
+    #[test]
+    fn test_first_exchange() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "clock".to_string(),
+            arguments: "{}".to_string(),
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![message(Role::User, "What time is it?")],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        assert!(chat.first_exchange().is_none());
+
+        chat.messages.extend([
+            message(Role::Assistant, "").with_tool_calls(vec![call]),
+            message(Role::Tool, "12:00").with_tool_call_id("call_1"),
+            message(Role::Assistant, "It is noon."),
+        ]);
+
+        let (question, answer) = chat.first_exchange().unwrap();
+        assert_eq!(question.content, "What time is it?");
+        assert_eq!(answer.content, "It is noon.");
+    }
+
+    #[test]
+    fn test_last_message_preview() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        assert_eq!(chat.last_message_preview(), None);
+
+        chat.messages.extend([
+            message(Role::User, "What time\nis it?"),
+            message(Role::Tool, "12:00").with_tool_call_id("call_1"),
+        ]);
+        assert_eq!(chat.last_message_preview().unwrap(), "What time is it?");
+
+        chat.messages
+            .push(message(Role::Assistant, &"é".repeat(PREVIEW_LENGTH + 1)));
+        let preview = chat.last_message_preview().unwrap();
+        assert_eq!(preview.chars().count(), PREVIEW_LENGTH + 1);
+        assert!(preview.ends_with('…'));
+    }
+
+    #[test]
+    fn test_archive_history() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi!"),
+            ],
+            vec![message(Role::User, "Hey")],
+            ChatStatus::Ended,
+            10,
+            ChatConfig::default_for(model.clone()),
+        );
+        let messages = chat.messages.clone();
+        let erased_messages = chat.erased_messages.clone();
+
+        chat.archive_history("chats/1.jsonl".to_string()).unwrap();
+        assert!(chat.is_cold());
+        assert_eq!(chat.status, ChatStatus::Archived);
+        assert!(chat.messages.is_empty() && chat.erased_messages.is_empty());
+        assert_eq!(chat.count_messages(), 2);
+        assert_eq!(chat.last_message_preview().as_deref(), Some("Hi!"));
+        assert_eq!(
+            chat.archive_history("chats/2.jsonl".to_string()),
+            Err(ChatError::ChatArchived)
+        );
+        assert_eq!(chat.reopen(), Err(ChatError::ChatArchived));
+        assert_eq!(
+            chat.fork(Uuid::new_v4(), None).err(),
+            Some(ChatError::ChatArchived)
+        );
+
+        chat.restore_history(messages.clone(), erased_messages);
+        assert!(!chat.is_cold());
+        assert_eq!(chat.messages, messages);
+        chat.reopen().unwrap();
+        assert_eq!(chat.status, ChatStatus::Active);
+    }
+
+    #[test]
+    fn test_records_events() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        )
+        .with_tenant(Uuid::new_v4());
+        assert!(chat.events.is_empty());
+
+        let hello = message(Role::User, "Hello!");
+        chat.add_message(hello.clone()).unwrap();
+        chat.end().unwrap();
+        assert!(chat.end().is_err());
+
+        let events = chat.outbox_events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].event,
+            ChatEvent::MessageAdded {
+                message_id: hello.id,
+                role: Role::User,
+                tokens: hello.tokens,
+            }
+        );
+        assert_eq!(events[1].event, ChatEvent::ChatEnded);
+        assert!(events.iter().all(|event| event.chat_id == chat.id
+            && event.tenant_id == chat.tenant_id
+            && event.user_id == chat.user_id));
+
+        let fork = chat.fork(Uuid::new_v4(), None).unwrap();
+        assert_eq!(fork.events.len(), 1);
+        assert_eq!(
+            fork.events[0].event,
+            ChatEvent::ChatCreated {
+                model: "gpt-3.5-turbo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_reconfigure() {
+        let model = Model::new("gpt-4".to_string(), 8192);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![message(Role::User, &"word ".repeat(100))],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        chat.refresh_token_usage();
+        let system_id = chat.initial_system_message.id;
+
+        let previous = chat
+            .replace_system_message("Answer like a pirate.")
+            .unwrap();
+        assert_eq!(previous.content, "You are a helpful assistant.");
+        assert_eq!(chat.initial_system_message.id, system_id);
+        assert_eq!(chat.initial_system_message.content, "Answer like a pirate.");
+        assert_eq!(
+            chat.token_usage,
+            prompt_tokens(&chat.initial_system_message, &chat.messages)
+        );
+        assert!(matches!(
+            chat.replace_system_message(""),
+            Err(ChatError::InvalidMessage(_))
+        ));
+
+        // the context only reaches the prompt of one turn, the chat keeps its instructions
+        let prompt = chat.with_context("Context: ahoy").unwrap();
+        assert_eq!(
+            prompt.prompt_messages()[0].content,
+            "Answer like a pirate.\n\nContext: ahoy"
+        );
+        assert_eq!(chat.initial_system_message.content, "Answer like a pirate.");
+
+        chat.set_model(Model::new("gpt-4o".to_string(), 4096))
+            .unwrap();
+        assert_eq!(chat.config.model.name, "gpt-4o");
+        assert_eq!(chat.config.max_tokens, 4096);
+        assert_eq!(chat.initial_system_message.model.name, "gpt-4o");
+        assert!(matches!(
+            chat.set_model(Model::new("tiny".to_string(), 16)),
+            Err(ChatError::TokenLimitExceeded { .. })
+        ));
+        assert_eq!(chat.config.model.name, "gpt-4o");
+
+        chat.set_temperature(0.2).unwrap();
+        assert_eq!(chat.config.temperature, 0.2);
+        assert!(matches!(
+            chat.set_temperature(3.0),
+            Err(ChatError::InvalidConfig(_))
+        ));
+
+        chat.set_response_language(Some("fr".to_string())).unwrap();
+        assert_eq!(chat.config.response_language.as_deref(), Some("fr"));
+        assert_eq!(
+            chat.set_response_language(Some("klingon".to_string())),
+            Err(ChatError::InvalidConfig(ConfigError::UnknownLanguage(
+                "klingon".to_string()
+            )))
+        );
+        assert_eq!(chat.config.response_language.as_deref(), Some("fr"));
+
+        chat.end().unwrap();
+        assert!(matches!(
+            chat.set_temperature(0.5),
+            Err(ChatError::ChatEnded)
+        ));
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let model = Model::new("gpt-4".to_string(), 8192);
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                "You are a helpful assistant.",
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            ),
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        );
+        assert_eq!(
+            chat.with_overrides(&ConfigOverrides::default()).unwrap(),
+            chat
+        );
+
+        let prompt = chat
+            .with_overrides(&ConfigOverrides {
+                model: Some(Model::new("gpt-4o".to_string(), 4096)),
+                temperature: Some(0.2),
+                stop: Some(vec!["END".to_string()]),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(prompt.config.model.name, "gpt-4o");
+        assert_eq!(prompt.config.max_tokens, 4096);
+        assert_eq!(prompt.config.temperature, 0.2);
+        assert_eq!(prompt.config.stop, vec!["END".to_string()]);
+        assert_eq!(prompt.config.top_p, chat.config.top_p);
+        assert_eq!(chat.config.model.name, "gpt-4");
+
+        assert!(matches!(
+            chat.with_overrides(&ConfigOverrides {
+                top_p: Some(1.5),
+                ..Default::default()
+            }),
+            Err(ChatError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            chat.with_overrides(&ConfigOverrides {
+                model: Some(Model::new("tiny".to_string(), 4)),
+                ..Default::default()
+            }),
+            Err(ChatError::TokenLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_seen_by() {
+        let model = Model::new("gpt-4".to_string(), 8192);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: "{}".to_string(),
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Write a haiku."),
+                message(Role::Assistant, "")
+                    .with_tool_calls(vec![call])
+                    .with_speaker("Writer"),
+                message(Role::Tool, "sunny")
+                    .with_tool_call_id("call_1")
+                    .with_speaker("Writer"),
+                message(Role::Assistant, "Sun on the water").with_speaker("Writer"),
+                message(Role::Assistant, "Too short.").with_speaker("Critic"),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+
+        let writer = chat.seen_by("Writer", "You write haikus.").unwrap();
+        assert_eq!(writer.initial_system_message.content, "You write haikus.");
+        assert_eq!(writer.messages.len(), 5);
+        assert_eq!(writer.messages[3], chat.messages[3]);
+        assert_eq!(writer.messages[4].role, Role::User);
+        assert_eq!(writer.messages[4].content, "Critic: Too short.");
+
+        let critic = chat.seen_by("Critic", "You review haikus.").unwrap();
+        let seen: Vec<_> = critic
+            .messages
+            .iter()
+            .map(|message| (message.role, message.content.as_str()))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                (Role::User, "Write a haiku."),
+                (Role::User, "Writer: Sun on the water"),
+                (Role::Assistant, "Too short."),
+            ]
+        );
+        let mut refreshed = critic.clone();
+        refreshed.refresh_token_usage();
+        assert_eq!(critic.token_usage, refreshed.token_usage);
+        assert_eq!(
+            chat.initial_system_message.content,
+            "You are a helpful assistant."
+        );
+    }
+
+    #[test]
+    fn test_set_tags_and_metadata() {
+        let model = Model::new("gpt-4".to_string(), 8192);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Archived,
+            0,
+            ChatConfig::default_for(model),
+        );
+
+        chat.set_tags(&[
+            " Support ".to_string(),
+            "billing:eu".to_string(),
+            "support".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(chat.tags, vec!["support", "billing:eu"]);
+        assert!(matches!(
+            chat.set_tags(&["two words".to_string()]),
+            Err(ChatError::InvalidTag(_))
+        ));
+        assert!(matches!(
+            chat.set_tags(&[" ".to_string()]),
+            Err(ChatError::InvalidTag(_))
+        ));
+        let many: Vec<String> = (0..=MAX_TAGS).map(|i| format!("tag-{}", i)).collect();
+        assert!(matches!(
+            chat.set_tags(&many),
+            Err(ChatError::InvalidTag(_))
+        ));
+        assert_eq!(chat.tags, vec!["support", "billing:eu"]);
+
+        let metadata = BTreeMap::from([("ticket".to_string(), "T-42".to_string())]);
+        chat.set_metadata(metadata.clone()).unwrap();
+        assert_eq!(chat.metadata, metadata);
+        assert!(matches!(
+            chat.set_metadata(BTreeMap::from([(String::new(), "x".to_string())])),
+            Err(ChatError::InvalidMetadata(_))
+        ));
+        assert!(matches!(
+            chat.set_metadata(BTreeMap::from([(
+                "notes".to_string(),
+                "x".repeat(MAX_METADATA_VALUE_LENGTH + 1)
+            )])),
+            Err(ChatError::InvalidMetadata(_))
+        ));
+        assert_eq!(chat.metadata, metadata);
     }
 }
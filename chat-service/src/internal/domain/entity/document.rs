@@ -0,0 +1,179 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::error::ChatError;
+
+pub const MAX_DOCUMENT_NAME_LENGTH: usize = 255;
+
+// DocumentFormat is how an uploaded document is turned into text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentFormat {
+    Text,
+    Markdown,
+    Pdf,
+}
+
+impl fmt::Display for DocumentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self {
+            DocumentFormat::Text => "text",
+            DocumentFormat::Markdown => "markdown",
+            DocumentFormat::Pdf => "pdf",
+        };
+        f.write_str(format)
+    }
+}
+
+impl FromStr for DocumentFormat {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DocumentFormat::Text),
+            "markdown" => Ok(DocumentFormat::Markdown),
+            "pdf" => Ok(DocumentFormat::Pdf),
+            _ => Err(ChatError::InvalidDocument(format!(
+                "unknown document format {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl DocumentFormat {
+    // detect reads the format from the content type, or from the file extension when the
+    // content type is missing or too generic to tell, e.g. application/octet-stream
+    pub fn detect(name: &str, content_type: Option<&str>) -> Result<Self, ChatError> {
+        let mime = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(|mime| mime.trim().to_lowercase());
+        match mime.as_deref() {
+            Some("text/plain") => return Ok(DocumentFormat::Text),
+            Some("text/markdown") | Some("text/x-markdown") => return Ok(DocumentFormat::Markdown),
+            Some("application/pdf") => return Ok(DocumentFormat::Pdf),
+            _ => {}
+        }
+
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("txt") | Some("text") => Ok(DocumentFormat::Text),
+            Some("md") | Some("markdown") => Ok(DocumentFormat::Markdown),
+            Some("pdf") => Ok(DocumentFormat::Pdf),
+            _ => Err(ChatError::InvalidDocument(format!(
+                "unsupported format for {}, upload plain text, Markdown or PDF",
+                name
+            ))),
+        }
+    }
+
+    // extract_text returns the text of the document; PDF needs the pdf feature
+    pub fn extract_text(&self, content: &[u8]) -> Result<String, ChatError> {
+        match self {
+            DocumentFormat::Text | DocumentFormat::Markdown => String::from_utf8(content.to_vec())
+                .map_err(|_| ChatError::InvalidDocument("document is not valid UTF-8".to_string())),
+            DocumentFormat::Pdf => extract_pdf_text(content),
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+fn extract_pdf_text(content: &[u8]) -> Result<String, ChatError> {
+    pdf_extract::extract_text_from_mem(content)
+        .map_err(|e| ChatError::InvalidDocument(format!("unreadable PDF: {}", e)))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn extract_pdf_text(_content: &[u8]) -> Result<String, ChatError> {
+    Err(ChatError::InvalidDocument(
+        "PDF documents are not supported by this build".to_string(),
+    ))
+}
+
+// Document is a file a user uploaded, its text lives in the vector store as chunks whose
+// source is the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub format: DocumentFormat,
+    pub size_bytes: usize,
+    pub chunk_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Document {
+    pub fn new(
+        id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        name: &str,
+        format: DocumentFormat,
+        size_bytes: usize,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id,
+            tenant_id,
+            user_id,
+            name: name.trim().to_string(),
+            format,
+            size_bytes,
+            chunk_count: 0,
+            created_at,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.name.is_empty() {
+            return Err(ChatError::InvalidDocument("name is empty".to_string()));
+        }
+
+        if self.name.len() > MAX_DOCUMENT_NAME_LENGTH {
+            return Err(ChatError::InvalidDocument("name is too long".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            DocumentFormat::detect("notes", Some("text/markdown; charset=utf-8")),
+            Ok(DocumentFormat::Markdown)
+        );
+        assert_eq!(
+            DocumentFormat::detect("guide.PDF", Some("application/octet-stream")),
+            Ok(DocumentFormat::Pdf)
+        );
+        assert_eq!(
+            DocumentFormat::detect("readme.txt", None),
+            Ok(DocumentFormat::Text)
+        );
+        assert!(matches!(
+            DocumentFormat::detect("photo.png", Some("image/png")),
+            Err(ChatError::InvalidDocument(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_text() {
+        assert_eq!(
+            DocumentFormat::Markdown.extract_text("# Title".as_bytes()),
+            Ok("# Title".to_string())
+        );
+        assert!(DocumentFormat::Text.extract_text(&[0xff, 0xfe]).is_err());
+    }
+}
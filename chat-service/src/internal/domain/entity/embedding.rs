@@ -0,0 +1,122 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::error::ChatError;
+
+// VectorKind tells what an embedded text was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorKind {
+    // Message is a chat message, its source is the chat
+    Message,
+    // DocumentChunk is a piece of an uploaded document, its source is the document
+    DocumentChunk,
+}
+
+impl fmt::Display for VectorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            VectorKind::Message => "message",
+            VectorKind::DocumentChunk => "document_chunk",
+        };
+        f.write_str(kind)
+    }
+}
+
+impl FromStr for VectorKind {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "message" => Ok(VectorKind::Message),
+            "document_chunk" => Ok(VectorKind::DocumentChunk),
+            _ => Err(ChatError::InvalidMessage(format!(
+                "unknown vector kind {}",
+                s
+            ))),
+        }
+    }
+}
+
+// VectorRecord is an embedded text of a user; vectors are only compared with vectors of the
+// same model
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorRecord {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub kind: VectorKind,
+    pub source_id: Uuid,
+    pub content: String,
+    pub model: String,
+    pub embedding: Vec<f32>,
+    // metadata holds what the kind needs to point back at its source, e.g. a chunk position
+    pub metadata: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// VectorMatch is a record found by a search, score is the cosine similarity to the query
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorMatch {
+    pub id: Uuid,
+    pub kind: VectorKind,
+    pub source_id: Uuid,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub score: f32,
+}
+
+impl VectorRecord {
+    pub fn to_match(&self, score: f32) -> VectorMatch {
+        VectorMatch {
+            id: self.id,
+            kind: self.kind,
+            source_id: self.source_id,
+            content: self.content.clone(),
+            metadata: self.metadata.clone(),
+            score,
+        }
+    }
+}
+
+// cosine_similarity is 1 for vectors pointing the same way, 0 for unrelated ones; vectors of
+// different lengths or without magnitude are not similar at all
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_round_trip() {
+        for kind in [VectorKind::Message, VectorKind::DocumentChunk] {
+            assert_eq!(kind.to_string().parse::<VectorKind>().unwrap(), kind);
+        }
+        assert!("chat".parse::<VectorKind>().is_err());
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert!((cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::message::Role;
+
+// ChatEvent is something that happened to a chat that downstream consumers may care about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    ChatCreated {
+        model: String,
+    },
+    MessageAdded {
+        message_id: Uuid,
+        role: Role,
+        tokens: usize,
+    },
+    ChatEnded,
+    TokensConsumed {
+        model: String,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+    },
+}
+
+impl ChatEvent {
+    // name is the type of the event, as published to the sink
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChatEvent::ChatCreated { .. } => "chat_created",
+            ChatEvent::MessageAdded { .. } => "message_added",
+            ChatEvent::ChatEnded => "chat_ended",
+            ChatEvent::TokensConsumed { .. } => "tokens_consumed",
+        }
+    }
+}
+
+// RecordedEvent is an event a chat went through that is not in the outbox yet,
+// its id lets saving the same chat twice store it once
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub id: Uuid,
+    pub event: ChatEvent,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl RecordedEvent {
+    pub fn new(event: ChatEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+}
+
+// OutboxEvent is an event stored with the chat it happened to, waiting for the relay to
+// publish it; consumers dedupe by id since delivery is at least once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub event: ChatEvent,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = ChatEvent::TokensConsumed {
+            model: "gpt-3.5-turbo".to_string(),
+            prompt_tokens: 12,
+            completion_tokens: 30,
+        };
+
+        let value = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(value["type"], event.name());
+        assert_eq!(value["prompt_tokens"], 12);
+        assert_eq!(serde_json::from_value::<ChatEvent>(value).unwrap(), event);
+        assert_eq!(
+            serde_json::to_value(ChatEvent::ChatEnded).unwrap()["type"],
+            "chat_ended"
+        );
+    }
+}
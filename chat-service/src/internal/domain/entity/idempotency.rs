@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+// IdempotencyRecord remembers the response to a request sent with an idempotency key,
+// so a retry of the same request gets it back instead of running it again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub key: String,
+    // fingerprint identifies the request the key was first sent with
+    pub fingerprint: String,
+    // response is None while the first request is still running
+    pub response: Option<serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl IdempotencyRecord {
+    pub fn new(
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+        fingerprint: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        ttl: chrono::Duration,
+    ) -> Self {
+        Self {
+            tenant_id,
+            user_id,
+            key: key.to_string(),
+            fingerprint: fingerprint.to_string(),
+            response: None,
+            created_at: now,
+            expires_at: now + ttl,
+        }
+    }
+
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+// validate_idempotency_key checks a key sent by a client before it is stored
+pub fn validate_idempotency_key(key: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("idempotency key is empty".to_string());
+    }
+
+    if key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err("idempotency key is too long".to_string());
+    }
+
+    Ok(())
+}
+
+// fingerprint hashes the parts of a request that must stay the same across its retries
+pub fn fingerprint(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_idempotency_key() {
+        assert!(validate_idempotency_key("retry-1").is_ok());
+        assert!(validate_idempotency_key("  ").is_err());
+        assert!(validate_idempotency_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint(&["send", "Hello!"]),
+            fingerprint(&["send", "Hello!"])
+        );
+        assert_ne!(
+            fingerprint(&["send", "Hello!"]),
+            fingerprint(&["send", "Hi!"])
+        );
+        assert_ne!(fingerprint(&["ab", "c"]), fingerprint(&["a", "bc"]));
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = chrono::Utc::now();
+        let record = IdempotencyRecord::new(
+            Uuid::nil(),
+            Uuid::new_v4(),
+            "retry-1",
+            "fingerprint",
+            now,
+            chrono::Duration::seconds(60),
+        );
+
+        assert!(!record.is_expired(now));
+        assert!(record.is_expired(now + chrono::Duration::seconds(60)));
+    }
+}
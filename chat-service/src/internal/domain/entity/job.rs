@@ -0,0 +1,159 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::error::ChatError;
+
+// JobStatus is where a completion job is on its way to an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    // Queued waits for a worker
+    Queued,
+    // Running is claimed by a worker, the completion is in flight
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        };
+        f.write_str(status)
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(ChatError::InvalidMessage(format!(
+                "unknown job status {}",
+                s
+            ))),
+        }
+    }
+}
+
+// Job is a completion answered by a background worker instead of while the client waits, the
+// client polls it or is called back once it is done
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // chat_id is the chat the message is sent to, a new chat is started when there is none;
+    // it is the chat answered once the job succeeded
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    pub attachments: Vec<Attachment>,
+    // overrides are the model and sampling overrides of the request, kept as sent
+    pub overrides: serde_json::Value,
+    pub status: JobStatus,
+    pub content: Option<String>,
+    // error tells why a failed job got no answer
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    // claimed_until is when the worker running the job gives it up, another worker claims it
+    // again after that when it is still not done
+    pub claimed_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Job {
+    // new queues a non-empty message
+    pub fn new(
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        user_message: &str,
+        attachments: Vec<Attachment>,
+        overrides: serde_json::Value,
+    ) -> Result<Self, ChatError> {
+        if user_message.trim().is_empty() {
+            return Err(ChatError::InvalidMessage(
+                "job message is empty".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            chat_id,
+            user_message: user_message.to_string(),
+            attachments,
+            overrides,
+            status: JobStatus::Queued,
+            content: None,
+            error: None,
+            created_at: chrono::Utc::now(),
+            completed_at: None,
+            claimed_until: None,
+        })
+    }
+
+    pub fn succeed(&mut self, chat_id: Uuid, content: String) {
+        self.chat_id = Some(chat_id);
+        self.content = Some(content);
+        self.status = JobStatus::Succeeded;
+        self.completed_at = Some(chrono::Utc::now());
+        self.claimed_until = None;
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+        self.status = JobStatus::Failed;
+        self.completed_at = Some(chrono::Utc::now());
+        self.claimed_until = None;
+    }
+
+    pub fn is_done(&self) -> bool {
+        matches!(self.status, JobStatus::Succeeded | JobStatus::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_and_complete() {
+        let overrides = serde_json::json!({ "temperature": 0.2 });
+        let mut job = Job::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            None,
+            "Summarize the report",
+            vec![],
+            overrides.clone(),
+        )
+        .unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.overrides, overrides);
+        assert!(!job.is_done());
+
+        let chat_id = Uuid::new_v4();
+        job.succeed(chat_id, "It went well.".to_string());
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert_eq!(job.chat_id, Some(chat_id));
+        assert!(job.is_done());
+        assert!(job.completed_at.is_some());
+
+        assert!(Job::new(Uuid::nil(), Uuid::nil(), None, " ", vec![], overrides).is_err());
+        assert_eq!("failed".parse::<JobStatus>(), Ok(JobStatus::Failed));
+    }
+}
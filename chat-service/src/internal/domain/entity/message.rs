@@ -1,67 +1,210 @@
-use tiktoken_rs::get_completion_max_tokens;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::internal::domain::entity::attachment::{Attachment, MAX_ATTACHMENTS_PER_MESSAGE};
 use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::entity::tool::ToolCall;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::request_id;
+use crate::internal::domain::token_counter::TokenCounter;
+
+// ATTACHMENT_TOKENS estimates the prompt tokens of an image, what OpenAI charges for a
+// 1024x1024 image in high detail
+pub const ATTACHMENT_TOKENS: usize = 765;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        };
+        f.write_str(role)
+    }
+}
+
+impl FromStr for Role {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "system" => Ok(Role::System),
+            "user" => Ok(Role::User),
+            "assistant" => Ok(Role::Assistant),
+            "tool" => Ok(Role::Tool),
+            _ => Err(ChatError::InvalidMessage("role is invalid".to_string())),
+        }
+    }
+}
 
-#[derive(Debug)]
-pub struct Message<'a> {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
     pub id: Uuid,
-    pub role: String,
+    pub role: Role,
     pub content: String,
     pub tokens: usize,
-    pub model: &'a Model,
+    pub model: Model,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    // tool_calls are the calls requested by an assistant message
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+    // tool_call_id links a tool message to the call it answers
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+    // revision_of links an edited user message to the message it replaces
+    #[serde(default)]
+    pub revision_of: Option<Uuid>,
+    // attachments are the images sent with a user message
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    // candidates are the other replies the model offered when asked for more than one, they are
+    // kept until one is selected but never sent back to the model
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    // interrupted marks an assistant reply cut short because its client went away while it was
+    // streamed, its content is what was received until then
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    // request_id is the id of the request the message was created for, to find its log lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // speaker labels an assistant message of an orchestrated chat with the agent that wrote it,
+    // and the tool messages of its calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    // language is the ISO 639-1 code of the language a user message was detected to be in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // redaction is the original of a user message that had personal data masked, it is kept
+    // with the message until its chat is saved and is never stored with it
+    #[serde(skip)]
+    pub redaction: Option<RedactionRecord>,
 }
 
-impl<'a> Message<'a> {
-    // implementation of trait clone
-    pub fn clone(&self) -> Self {
-        Self {
-            id: self.id,
-            role: self.role.clone(),
-            content: self.content.clone(),
-            tokens: self.tokens,
-            model: self.model,
-            created_at: self.created_at,
-        }
-    }
-
+impl Message {
+    // new counts the content tokens with the model's encoding, tokens is used when the model has none;
+    // the message is tagged with the id of the request being served
     pub fn new(
         id: Uuid,
-        role: &'a str,
-        content: &'a str,
+        role: Role,
+        content: &str,
         tokens: usize,
-        model: &'a Model,
+        model: Model,
         created_at: chrono::DateTime<chrono::Utc>,
     ) -> Self {
-        let total_tokens = get_completion_max_tokens(&model.name, content);
-
-        match total_tokens {
-            Ok(total_tokens) => Self {
-                id,
-                role: role.to_string(),
-                content: content.to_string(),
-                tokens: total_tokens,
-                model,
-                created_at,
-            },
-            Err(_) => Self {
-                id,
-                role: role.to_string(),
-                content: content.to_string(),
-                tokens,
-                model,
-                created_at,
-            },
+        let tokens = TokenCounter::for_model(&model)
+            .map(|counter| counter.count_message(role, content))
+            .unwrap_or(tokens);
+
+        Self {
+            id,
+            role,
+            content: content.to_string(),
+            tokens,
+            model,
+            created_at,
+            tool_calls: vec![],
+            tool_call_id: None,
+            revision_of: None,
+            attachments: vec![],
+            candidates: vec![],
+            interrupted: false,
+            request_id: request_id::current(),
+            speaker: None,
+            language: None,
+            redaction: None,
+        }
+    }
+
+    // with_tool_calls attaches the calls requested by the model, their arguments count as tokens
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        if let Some(counter) = TokenCounter::for_model(&self.model) {
+            self.tokens += tool_calls
+                .iter()
+                .map(|call| counter.count(&call.name) + counter.count(&call.arguments))
+                .sum::<usize>();
+        }
+
+        self.tool_calls = tool_calls;
+        self
+    }
+
+    // with_tool_call_id marks a tool message as the result of the given call
+    pub fn with_tool_call_id(mut self, tool_call_id: &str) -> Self {
+        self.tool_call_id = Some(tool_call_id.to_string());
+        self
+    }
+
+    // with_attachments adds images to the message, each counts as ATTACHMENT_TOKENS
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.tokens += attachments.len() * ATTACHMENT_TOKENS;
+        self.attachments = attachments;
+        self
+    }
+
+    // with_content replaces the content, the tokens of the content are counted again
+    pub fn with_content(mut self, content: &str) -> Self {
+        if let Some(counter) = TokenCounter::for_model(&self.model) {
+            self.tokens = self
+                .tokens
+                .saturating_sub(counter.count_message(self.role, &self.content))
+                + counter.count_message(self.role, content);
         }
+
+        self.content = content.to_string();
+        self
+    }
+
+    // with_candidates keeps the other replies the model offered, they don't count as tokens
+    pub fn with_candidates(mut self, candidates: Vec<String>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    // with_interrupted marks a reply whose stream stopped before the model was done
+    pub fn with_interrupted(mut self) -> Self {
+        self.interrupted = true;
+        self
+    }
+
+    // with_language records the language the message was detected to be in
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    // with_speaker labels the message with the agent that wrote it
+    pub fn with_speaker(mut self, speaker: &str) -> Self {
+        self.speaker = Some(speaker.to_string());
+        self
+    }
+
+    // with_revision_of marks the message as a new revision of the given one
+    pub fn with_revision_of(mut self, message_id: Uuid) -> Self {
+        self.revision_of = Some(message_id);
+        self
     }
 
     pub fn id(&self) -> Uuid {
         self.id
     }
 
-    pub fn role(&self) -> &str {
-        &self.role
+    pub fn role(&self) -> Role {
+        self.role
     }
 
     pub fn content(&self) -> &str {
@@ -80,19 +223,38 @@ impl<'a> Message<'a> {
         &self.created_at
     }
 
-    pub fn validate(&self) -> Result<(), String> {
-        let valid_role = self.role == "user" || self.role == "system" || self.role == "assistant";
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.content.is_empty() && self.tool_calls.is_empty() && self.attachments.is_empty() {
+            return Err(ChatError::InvalidMessage("content is empty".to_string()));
+        }
+
+        if !self.attachments.is_empty() && self.role != Role::User {
+            return Err(ChatError::InvalidAttachment(
+                "only user messages take attachments".to_string(),
+            ));
+        }
+
+        if self.attachments.len() > MAX_ATTACHMENTS_PER_MESSAGE {
+            return Err(ChatError::InvalidAttachment(format!(
+                "a message takes at most {} attachments",
+                MAX_ATTACHMENTS_PER_MESSAGE
+            )));
+        }
 
-        if !valid_role {
-            return Err("role is invalid".to_string());
+        for attachment in &self.attachments {
+            attachment.validate()?;
         }
 
-        if self.content.is_empty() {
-            return Err("content is empty".to_string());
+        if self.role == Role::Tool && self.tool_call_id.is_none() {
+            return Err(ChatError::InvalidMessage(
+                "tool message has no tool_call_id".to_string(),
+            ));
         }
 
         if self.created_at > chrono::Utc::now() {
-            return Err("created_at is invalid".to_string());
+            return Err(ChatError::InvalidMessage(
+                "created_at is invalid".to_string(),
+            ));
         }
 
         Ok(())
@@ -106,83 +268,206 @@ mod tests {
     #[test]
     fn test_new() {
         let id = Uuid::new_v4();
-        let role = "user";
+        let role = Role::User;
         let content = "Hello, world!";
         let tokens = 4092;
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let created_at = chrono::Utc::now();
-        let message = Message::new(id, role, content, tokens, &model, created_at);
+        let message = Message::new(id, role, content, tokens, model.clone(), created_at);
 
         assert_eq!(message.id, id);
         assert_eq!(message.role, role);
         assert_eq!(message.content, content);
-        assert_eq!(message.tokens, tokens);
-        assert_eq!(message.model, &model);
+        assert_eq!(message.tokens, 9);
+        assert_eq!(message.model, model);
         assert_eq!(message.created_at, created_at);
     }
 
+    #[test]
+    fn test_new_unknown_model() {
+        let model = Model::new("llama2".to_string(), 4096);
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, world!",
+            42,
+            model,
+            chrono::Utc::now(),
+        );
+
+        assert_eq!(message.tokens, 42);
+    }
+
+    #[test]
+    fn test_with_content() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, world!",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        )
+        .with_attachments(vec![Attachment::url("https://example.com/cat.png")]);
+
+        let message = message.with_content("Hi");
+        assert_eq!(message.content, "Hi");
+        assert_eq!(
+            message.tokens,
+            TokenCounter::for_model(&model)
+                .unwrap()
+                .count_message(Role::User, "Hi")
+                + ATTACHMENT_TOKENS
+        );
+    }
+
     #[test]
     fn test_validate() {
         let id = Uuid::new_v4();
-        let role = "user";
+        let role = Role::User;
         let content = "Hello, world!";
         let tokens = 4092;
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let created_at = chrono::Utc::now();
-        let message = Message::new(id, role, content, tokens, &model, created_at);
+        let message = Message::new(id, role, content, tokens, model.clone(), created_at);
 
         assert_eq!(message.validate(), Ok(()));
     }
 
     #[test]
     fn test_invalid_role() {
-        let id = Uuid::new_v4();
-        let role = "invalid";
-        let content = "Hello, world!";
-        let tokens = 4092;
-        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
-        let created_at = chrono::Utc::now();
-        let message = Message::new(id, role, content, tokens, &model, created_at);
-
-        assert_eq!(message.validate(), Err("role is invalid".to_string()));
+        assert_eq!(
+            "invalid".parse::<Role>(),
+            Err(ChatError::InvalidMessage("role is invalid".to_string()))
+        );
     }
 
     #[test]
     fn test_empty_content() {
         let id = Uuid::new_v4();
-        let role = "user";
+        let role = Role::User;
         let content = "";
         let tokens = 4092;
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let created_at = chrono::Utc::now();
-        let message = Message::new(id, role, content, tokens, &model, created_at);
+        let message = Message::new(id, role, content, tokens, model.clone(), created_at);
 
-        assert_eq!(message.validate(), Err("content is empty".to_string()));
+        assert_eq!(
+            message.validate(),
+            Err(ChatError::InvalidMessage("content is empty".to_string()))
+        );
     }
 
     #[test]
     fn test_role_is_empty() {
-        let id = Uuid::new_v4();
-        let role = "";
-        let content = "Hello, world!";
-        let tokens = 4092;
-        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
-        let created_at = chrono::Utc::now();
-        let message = Message::new(id, role, content, tokens, &model, created_at);
+        assert_eq!(
+            "".parse::<Role>(),
+            Err(ChatError::InvalidMessage("role is invalid".to_string()))
+        );
+    }
 
-        assert_eq!(message.validate(), Err("role is invalid".to_string()));
+    #[test]
+    fn test_role_round_trip() {
+        for role in [Role::System, Role::User, Role::Assistant, Role::Tool] {
+            assert_eq!(role.to_string().parse::<Role>(), Ok(role));
+            assert_eq!(
+                serde_json::to_string(&role).unwrap(),
+                format!("\"{}\"", role)
+            );
+        }
     }
 
     #[test]
     fn test_created_at_is_invalid() {
         let id = Uuid::new_v4();
-        let role = "user";
+        let role = Role::User;
         let content = "Hello, world!";
         let tokens = 4092;
         let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
         let created_at = chrono::Utc::now() + chrono::Duration::days(1);
-        let message = Message::new(id, role, content, tokens, &model, created_at);
+        let message = Message::new(id, role, content, tokens, model.clone(), created_at);
+
+        assert_eq!(
+            message.validate(),
+            Err(ChatError::InvalidMessage(
+                "created_at is invalid".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tool_messages() {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: r#"{"city":"Lisbon"}"#.to_string(),
+        };
+        let request = Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            "",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let empty_tokens = request.tokens;
+        let request = request.with_tool_calls(vec![call]);
+
+        assert!(request.tokens > empty_tokens);
+        assert_eq!(request.validate(), Ok(()));
+
+        let result = Message::new(
+            Uuid::new_v4(),
+            Role::Tool,
+            "18C and sunny",
+            0,
+            model,
+            chrono::Utc::now(),
+        );
+        assert!(result.validate().is_err());
+        assert_eq!(result.with_tool_call_id("call_1").validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_attachments() {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let image = Attachment::url("https://example.com/cat.png");
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let text_tokens = message.tokens;
+        let message = message.with_attachments(vec![image.clone()]);
+
+        assert_eq!(message.tokens, text_tokens + ATTACHMENT_TOKENS);
+        assert_eq!(message.validate(), Ok(()));
+
+        let too_many = message
+            .clone()
+            .with_attachments(vec![image.clone(); MAX_ATTACHMENTS_PER_MESSAGE + 1]);
+        assert!(matches!(
+            too_many.validate(),
+            Err(ChatError::InvalidAttachment(_))
+        ));
 
-        assert_eq!(message.validate(), Err("created_at is invalid".to_string()));
+        let assistant = Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            "A cat.",
+            0,
+            model,
+            chrono::Utc::now(),
+        )
+        .with_attachments(vec![image]);
+        assert!(matches!(
+            assistant.validate(),
+            Err(ChatError::InvalidAttachment(_))
+        ));
     }
 }
@@ -1,3 +1,26 @@
+pub mod api_key;
+pub mod assistant;
+pub mod attachment;
+pub mod audio;
+pub mod audit;
+pub mod batch;
 pub mod chat;
+pub mod document;
+pub mod embedding;
+pub mod event;
+pub mod idempotency;
+pub mod job;
 pub mod message;
 pub mod model;
+pub mod moderation;
+pub mod prompt_template;
+pub mod redaction;
+pub mod response_format;
+pub mod scheduled_message;
+pub mod speech;
+pub mod tenant;
+pub mod tool;
+pub mod usage;
+pub mod user;
+pub mod user_memory;
+pub mod webhook;
@@ -1,4 +1,13 @@
-#[derive(Debug, PartialEq)]
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::error::ChatError;
+
+pub const DEFAULT_PROVIDER: &str = "openai";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Model {
     pub name: String,
     pub max_tokens: u32,
@@ -16,6 +25,183 @@ impl Model {
     pub fn max_tokens(&self) -> u32 {
         self.max_tokens
     }
+
+    // supports_vision tells whether the model reads image attachments, per the registry
+    pub fn supports_vision(&self) -> bool {
+        ModelRegistry::get(&self.name).is_some_and(|info| info.vision)
+    }
+
+    // provider returns the prefix of names like anthropic/claude-3-5-sonnet, openai when there is none
+    pub fn provider(&self) -> &str {
+        match self.name.split_once('/') {
+            Some((provider, _)) => provider,
+            None => DEFAULT_PROVIDER,
+        }
+    }
+
+    // api_name returns the name without the provider prefix, as the provider's API expects it
+    pub fn api_name(&self) -> &str {
+        match self.name.split_once('/') {
+            Some((_, name)) => name,
+            None => &self.name,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.name.is_empty() {
+            return Err(ChatError::InvalidModel("name is empty".to_string()));
+        }
+
+        if self.max_tokens == 0 {
+            return Err(ChatError::InvalidModel("max_tokens is zero".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+// ModelInfo is the metadata the registry keeps for a model, prices are in USD per 1K tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub context_window: u32,
+    pub prompt_price_per_1k: f64,
+    pub completion_price_per_1k: f64,
+    #[serde(default)]
+    pub deprecated: bool,
+    // vision marks the models that take image attachments
+    #[serde(default)]
+    pub vision: bool,
+}
+
+impl ModelInfo {
+    pub fn new(
+        name: &str,
+        context_window: u32,
+        prompt_price_per_1k: f64,
+        completion_price_per_1k: f64,
+        deprecated: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            context_window,
+            prompt_price_per_1k,
+            completion_price_per_1k,
+            deprecated,
+            vision: false,
+        }
+    }
+
+    pub fn with_vision(mut self) -> Self {
+        self.vision = true;
+        self
+    }
+
+    // model returns a Model whose token budget is the full context window
+    pub fn model(&self) -> Model {
+        Model::new(self.name.clone(), self.context_window)
+    }
+
+    // cost returns the price in USD of a request with the given token counts
+    pub fn cost(&self, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+        (prompt_tokens as f64 * self.prompt_price_per_1k
+            + completion_tokens as f64 * self.completion_price_per_1k)
+            / 1000.0
+    }
+}
+
+// ModelPricing replaces the prices of a registered model, in USD per 1K tokens, so that costs
+// follow the provider's price list without redefining the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub model: String,
+    pub prompt_price_per_1k: f64,
+    pub completion_price_per_1k: f64,
+}
+
+fn known_models() -> Vec<ModelInfo> {
+    vec![
+        ModelInfo::new("gpt-3.5-turbo", 16385, 0.0005, 0.0015, false),
+        ModelInfo::new("gpt-3.5-turbo-16k", 16385, 0.003, 0.004, true),
+        ModelInfo::new("gpt-4", 8192, 0.03, 0.06, false),
+        ModelInfo::new("gpt-4-32k", 32768, 0.06, 0.12, true),
+        ModelInfo::new("gpt-4-1106-preview", 128000, 0.01, 0.03, true),
+        ModelInfo::new("gpt-4-vision-preview", 128000, 0.01, 0.03, true).with_vision(),
+        ModelInfo::new("gpt-4-turbo", 128000, 0.01, 0.03, false).with_vision(),
+        ModelInfo::new("gpt-4o", 128000, 0.005, 0.015, false).with_vision(),
+        ModelInfo::new("gpt-4o-mini", 128000, 0.00015, 0.0006, false).with_vision(),
+        ModelInfo::new("anthropic/claude-3-5-sonnet", 200000, 0.003, 0.015, false),
+        ModelInfo::new("anthropic/claude-3-opus", 200000, 0.015, 0.075, false),
+        ModelInfo::new("anthropic/claude-3-haiku", 200000, 0.00025, 0.00125, false),
+    ]
+}
+
+fn registry() -> &'static RwLock<HashMap<String, ModelInfo>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, ModelInfo>>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        let models = known_models()
+            .into_iter()
+            .map(|info| (info.name.clone(), info))
+            .collect();
+        RwLock::new(models)
+    })
+}
+
+// ModelRegistry is the process-wide catalog of models, seeded with the common OpenAI models
+pub struct ModelRegistry;
+
+impl ModelRegistry {
+    // get looks the model up by name, dated snapshots like gpt-4o-2024-05-13 resolve to their family
+    pub fn get(name: &str) -> Option<ModelInfo> {
+        let models = registry().read().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(info) = models.get(name) {
+            return Some(info.clone());
+        }
+
+        models
+            .values()
+            .filter(|info| {
+                name.strip_prefix(info.name.as_str())
+                    .is_some_and(|rest| rest.starts_with('-'))
+            })
+            .max_by_key(|info| info.name.len())
+            .cloned()
+    }
+
+    // register adds a custom model or overrides the metadata of a known one
+    pub fn register(info: ModelInfo) {
+        let mut models = registry().write().unwrap_or_else(|e| e.into_inner());
+        models.insert(info.name.clone(), info);
+    }
+
+    pub fn register_all(infos: impl IntoIterator<Item = ModelInfo>) {
+        let mut models = registry().write().unwrap_or_else(|e| e.into_inner());
+        for info in infos {
+            models.insert(info.name.clone(), info);
+        }
+    }
+
+    // set_pricing replaces the prices of a registered model, false when no model has the name
+    pub fn set_pricing(pricing: &ModelPricing) -> bool {
+        let mut models = registry().write().unwrap_or_else(|e| e.into_inner());
+        let Some(info) = models.get_mut(&pricing.model) else {
+            return false;
+        };
+        info.prompt_price_per_1k = pricing.prompt_price_per_1k;
+        info.completion_price_per_1k = pricing.completion_price_per_1k;
+
+        true
+    }
+
+    // list returns every registered model sorted by name
+    pub fn list() -> Vec<ModelInfo> {
+        let models = registry().read().unwrap_or_else(|e| e.into_inner());
+        let mut infos: Vec<ModelInfo> = models.values().cloned().collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
 }
 
 #[cfg(test)]
@@ -49,4 +235,94 @@ mod tests {
 
         assert_eq!(model.max_tokens(), max_tokens);
     }
+
+    #[test]
+    fn test_provider() {
+        let model = Model::new("anthropic/claude-3-5-sonnet".to_string(), 200000);
+        assert_eq!(model.provider(), "anthropic");
+        assert_eq!(model.api_name(), "claude-3-5-sonnet");
+
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        assert_eq!(model.provider(), "openai");
+        assert_eq!(model.api_name(), "gpt-3.5-turbo");
+    }
+
+    #[test]
+    fn test_validate() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        assert_eq!(model.validate(), Ok(()));
+
+        let model = Model::new("".to_string(), 4096);
+        assert_eq!(
+            model.validate(),
+            Err(ChatError::InvalidModel("name is empty".to_string()))
+        );
+
+        let model = Model::new("gpt-3.5-turbo".to_string(), 0);
+        assert_eq!(
+            model.validate(),
+            Err(ChatError::InvalidModel("max_tokens is zero".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_registry_get() {
+        let info = ModelRegistry::get("gpt-4o").unwrap();
+        assert_eq!(info.context_window, 128000);
+        assert!(!info.deprecated);
+        assert_eq!(info.model(), Model::new("gpt-4o".to_string(), 128000));
+
+        let info = ModelRegistry::get("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(info.name, "gpt-4o-mini");
+
+        assert!(ModelRegistry::get("gpt-4-32k").unwrap().deprecated);
+        assert_eq!(ModelRegistry::get("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_registry_register() {
+        let info = ModelInfo::new("acme-chat-large", 32000, 0.002, 0.004, false);
+        ModelRegistry::register(info.clone());
+
+        assert_eq!(ModelRegistry::get("acme-chat-large"), Some(info));
+        assert!(ModelRegistry::list()
+            .iter()
+            .any(|info| info.name == "acme-chat-large"));
+    }
+
+    #[test]
+    fn test_registry_set_pricing() {
+        ModelRegistry::register(ModelInfo::new("acme-chat-small", 8000, 0.001, 0.002, false));
+        let pricing = ModelPricing {
+            model: "acme-chat-small".to_string(),
+            prompt_price_per_1k: 0.0005,
+            completion_price_per_1k: 0.001,
+        };
+
+        assert!(ModelRegistry::set_pricing(&pricing));
+        let info = ModelRegistry::get("acme-chat-small").unwrap();
+        assert_eq!(info.prompt_price_per_1k, 0.0005);
+        assert_eq!(info.completion_price_per_1k, 0.001);
+        assert_eq!(info.context_window, 8000);
+
+        assert!(!ModelRegistry::set_pricing(&ModelPricing {
+            model: "unknown-model".to_string(),
+            ..pricing
+        }));
+    }
+
+    #[test]
+    fn test_supports_vision() {
+        assert!(Model::new("gpt-4o".to_string(), 128000).supports_vision());
+        assert!(Model::new("gpt-4o-2024-05-13".to_string(), 128000).supports_vision());
+        assert!(!Model::new("gpt-3.5-turbo".to_string(), 16385).supports_vision());
+        assert!(!Model::new("llama2".to_string(), 4096).supports_vision());
+    }
+
+    #[test]
+    fn test_cost() {
+        let info = ModelInfo::new("gpt-4", 8192, 0.03, 0.06, false);
+
+        assert!((info.cost(1000, 500) - 0.06).abs() < f64::EPSILON);
+    }
 }
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ModerationFlag is the audit trail of a user message rejected by moderation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationFlag {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    // chat_id is empty when the flagged message would have started a new chat
+    pub chat_id: Option<Uuid>,
+    pub content: String,
+    pub categories: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
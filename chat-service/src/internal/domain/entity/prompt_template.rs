@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::error::ChatError;
+
+pub const MAX_TEMPLATE_NAME_LENGTH: usize = 255;
+
+// PromptTemplate is a named system message with {{variables}} filled when a chat is created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Segment is a piece of a template, either literal text or a variable to fill
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+impl PromptTemplate {
+    pub fn new(
+        id: Uuid,
+        name: &str,
+        content: &str,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id,
+            name: name.trim().to_string(),
+            content: content.to_string(),
+            created_at,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.name.is_empty() {
+            return Err(ChatError::InvalidTemplate("name is empty".to_string()));
+        }
+
+        if self.name.len() > MAX_TEMPLATE_NAME_LENGTH {
+            return Err(ChatError::InvalidTemplate("name is too long".to_string()));
+        }
+
+        if self.content.trim().is_empty() {
+            return Err(ChatError::InvalidTemplate("content is empty".to_string()));
+        }
+
+        self.segments()?;
+
+        Ok(())
+    }
+
+    // variables returns the distinct variable names in the order they first appear
+    pub fn variables(&self) -> Result<Vec<&str>, ChatError> {
+        let mut variables = vec![];
+        for segment in self.segments()? {
+            if let Segment::Variable(name) = segment {
+                if !variables.contains(&name) {
+                    variables.push(name);
+                }
+            }
+        }
+
+        Ok(variables)
+    }
+
+    // render fills every variable, variables that are not used by the template are ignored
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String, ChatError> {
+        let missing: Vec<String> = self
+            .variables()?
+            .into_iter()
+            .filter(|name| !values.contains_key(*name))
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ChatError::MissingTemplateVariables(missing));
+        }
+
+        let mut rendered = String::with_capacity(self.content.len());
+        for segment in self.segments()? {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Variable(name) => rendered.push_str(&values[name]),
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    // segments splits the content on {{name}} placeholders, spaces inside the braces are allowed
+    fn segments(&self) -> Result<Vec<Segment<'_>>, ChatError> {
+        let mut segments = vec![];
+        let mut rest = self.content.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(&rest[..start]));
+            }
+
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                ChatError::InvalidTemplate("a {{ placeholder is not closed".to_string())
+            })?;
+            let name = after[..end].trim();
+            if !is_variable_name(name) {
+                return Err(ChatError::InvalidTemplate(format!(
+                    "{{{{{}}}}} is not a valid variable",
+                    &after[..end]
+                )));
+            }
+
+            segments.push(Segment::Variable(name));
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest));
+        }
+
+        Ok(segments)
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(first) if first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(content: &str) -> PromptTemplate {
+        PromptTemplate::new(Uuid::new_v4(), "support", content, chrono::Utc::now())
+    }
+
+    #[test]
+    fn test_render() {
+        let template =
+            template("You help {{user_name}} with {{ product }}. Address {{user_name}} politely.");
+        let values = HashMap::from([
+            ("user_name".to_string(), "Ada".to_string()),
+            ("product".to_string(), "the billing API".to_string()),
+            ("unused".to_string(), "ignored".to_string()),
+        ]);
+
+        assert_eq!(template.validate(), Ok(()));
+        assert_eq!(template.variables(), Ok(vec!["user_name", "product"]));
+        assert_eq!(
+            template.render(&values),
+            Ok("You help Ada with the billing API. Address Ada politely.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_missing_variables() {
+        let template = template("You help {{user_name}} with {{product}}.");
+        let values = HashMap::from([("user_name".to_string(), "Ada".to_string())]);
+
+        assert_eq!(
+            template.render(&values),
+            Err(ChatError::MissingTemplateVariables(vec![
+                "product".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(template("No variables at all.").validate(), Ok(()));
+        assert!(matches!(
+            template("Hello {{user_name").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            template("Hello {{user name}}").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            template("Hello {{}}").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            template("  ").validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            PromptTemplate::new(Uuid::new_v4(), " ", "Hello", chrono::Utc::now()).validate(),
+            Err(ChatError::InvalidTemplate(_))
+        ));
+    }
+}
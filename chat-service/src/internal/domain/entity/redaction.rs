@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// RedactionRecord keeps the original of a user message whose personal data was masked before it
+// was sent to a provider, encrypted so it is only readable with the service key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRecord {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // chat_id is empty for the records stored before the chat of their message was
+    pub chat_id: Option<Uuid>,
+    pub message_id: Uuid,
+    // findings names the kind of every masked span in order, e.g. ["email", "phone"]
+    pub findings: Vec<String>,
+    pub ciphertext: Vec<u8>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
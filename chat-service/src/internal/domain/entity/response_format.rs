@@ -0,0 +1,249 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::internal::domain::error::{ChatError, ConfigError};
+
+// ResponseFormat constrains what the assistant answers with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    #[default]
+    Text,
+    // JsonObject requires the answer to be a JSON object
+    JsonObject,
+    // JsonSchema requires the answer to be JSON matching the schema
+    JsonSchema {
+        name: String,
+        schema: Value,
+        #[serde(default)]
+        strict: bool,
+    },
+}
+
+impl ResponseFormat {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let ResponseFormat::JsonSchema { name, schema, .. } = self {
+            if name.trim().is_empty() {
+                return Err(ConfigError::InvalidResponseFormat(
+                    "json_schema name is empty".to_string(),
+                ));
+            }
+            if !schema.is_object() {
+                return Err(ConfigError::InvalidResponseFormat(format!(
+                    "schema of {} must be a JSON object",
+                    name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    // check_response verifies the assistant content against the format
+    pub fn check_response(&self, content: &str) -> Result<(), ChatError> {
+        let schema = match self {
+            ResponseFormat::Text => return Ok(()),
+            ResponseFormat::JsonObject => None,
+            ResponseFormat::JsonSchema { schema, .. } => Some(schema),
+        };
+
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| ChatError::ResponseFormatMismatch(format!("invalid JSON: {}", e)))?;
+
+        match schema {
+            Some(schema) => check_schema(schema, &value, "$"),
+            None if value.is_object() => Ok(()),
+            None => Err(ChatError::ResponseFormatMismatch(
+                "$ is not an object".to_string(),
+            )),
+        }
+    }
+}
+
+// from_str parses the formats that need no schema, json_schema is only set from a config file
+impl FromStr for ResponseFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ResponseFormat::Text),
+            "json_object" => Ok(ResponseFormat::JsonObject),
+            _ => Err(ConfigError::InvalidResponseFormat(format!(
+                "{} is not text or json_object",
+                s
+            ))),
+        }
+    }
+}
+
+// check_schema validates the subset of JSON schema accepted by providers in strict mode:
+// type, enum, properties, required, additionalProperties and items
+fn check_schema(schema: &Value, value: &Value, path: &str) -> Result<(), ChatError> {
+    let mismatch = |message: String| Err(ChatError::ResponseFormatMismatch(message));
+
+    if let Some(types) = schema.get("type") {
+        let matches = match types {
+            Value::String(kind) => is_type(value, kind),
+            Value::Array(kinds) => kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|kind| is_type(value, kind)),
+            _ => true,
+        };
+        if !matches {
+            return mismatch(format!("{} is not of type {}", path, types));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return mismatch(format!(
+                "{} is not one of {}",
+                path,
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return mismatch(format!("{}.{} is required", path, key));
+                }
+            }
+        }
+
+        for (key, field) in object {
+            let field_path = format!("{}.{}", path, key);
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(field_schema) => check_schema(field_schema, field, &field_path)?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return mismatch(format!("{} is not allowed", field_path));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            check_schema(item_schema, item, &format!("{}[{}]", path, index))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn weather_format() -> ResponseFormat {
+        ResponseFormat::JsonSchema {
+            name: "weather".to_string(),
+            schema: json!({
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string"},
+                    "celsius": {"type": "integer"},
+                    "sky": {"type": "string", "enum": ["sunny", "cloudy"]},
+                    "alerts": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["city", "celsius"],
+                "additionalProperties": false
+            }),
+            strict: true,
+        }
+    }
+
+    #[test]
+    fn test_check_response() {
+        let format = weather_format();
+
+        assert_eq!(ResponseFormat::Text.check_response("not json"), Ok(()));
+        assert_eq!(
+            ResponseFormat::JsonObject.check_response(r#"{"ok": true}"#),
+            Ok(())
+        );
+        assert!(ResponseFormat::JsonObject.check_response("[1]").is_err());
+        assert_eq!(
+            format.check_response(
+                r#"{"city": "Lisbon", "celsius": 18, "sky": "sunny", "alerts": []}"#
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_check_response_mismatch() {
+        let format = weather_format();
+        let cases = [
+            ("Sunny in Lisbon", "invalid JSON"),
+            (r#"{"city": "Lisbon"}"#, "$.celsius is required"),
+            (
+                r#"{"city": "Lisbon", "celsius": 18.5}"#,
+                "$.celsius is not of type",
+            ),
+            (
+                r#"{"city": "Lisbon", "celsius": 18, "sky": "rainy"}"#,
+                "$.sky is not one of",
+            ),
+            (
+                r#"{"city": "Lisbon", "celsius": 18, "alerts": [1]}"#,
+                "$.alerts[0]",
+            ),
+            (
+                r#"{"city": "Lisbon", "celsius": 18, "wind": 3}"#,
+                "$.wind is not allowed",
+            ),
+        ];
+
+        for (content, expected) in cases {
+            match format.check_response(content) {
+                Err(ChatError::ResponseFormatMismatch(message)) => {
+                    assert!(message.contains(expected), "{}: {}", content, message)
+                }
+                other => panic!("{}: unexpected {:?}", content, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(weather_format().validate().is_ok());
+        assert!(matches!(
+            ResponseFormat::JsonSchema {
+                name: "weather".to_string(),
+                schema: json!(true),
+                strict: false,
+            }
+            .validate(),
+            Err(ConfigError::InvalidResponseFormat(_))
+        ));
+
+        let parsed: ResponseFormat =
+            serde_json::from_value(json!({"type": "json_object"})).unwrap();
+        assert_eq!(parsed, ResponseFormat::JsonObject);
+        assert_eq!("json_object".parse(), Ok(ResponseFormat::JsonObject));
+        assert!("json_schema".parse::<ResponseFormat>().is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::error::ChatError;
+
+// ScheduledStatus is where a scheduled message is on its way into the chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledStatus {
+    // Pending waits for send_at
+    Pending,
+    // Sending is claimed by a worker until claimed_until, the completion is in flight
+    Sending,
+    Sent,
+    Failed,
+    Cancelled,
+}
+
+impl fmt::Display for ScheduledStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            ScheduledStatus::Pending => "pending",
+            ScheduledStatus::Sending => "sending",
+            ScheduledStatus::Sent => "sent",
+            ScheduledStatus::Failed => "failed",
+            ScheduledStatus::Cancelled => "cancelled",
+        };
+        f.write_str(status)
+    }
+}
+
+impl FromStr for ScheduledStatus {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ScheduledStatus::Pending),
+            "sending" => Ok(ScheduledStatus::Sending),
+            "sent" => Ok(ScheduledStatus::Sent),
+            "failed" => Ok(ScheduledStatus::Failed),
+            "cancelled" => Ok(ScheduledStatus::Cancelled),
+            _ => Err(ChatError::InvalidMessage(format!(
+                "unknown scheduled message status {}",
+                s
+            ))),
+        }
+    }
+}
+
+// ScheduledMessage is a user message to be sent into a chat at send_at, the assistant answers
+// it like any other message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub content: String,
+    pub send_at: chrono::DateTime<chrono::Utc>,
+    pub status: ScheduledStatus,
+    // error tells why a failed message was not sent
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // claimed_until is when the worker sending the message gives it up
+    pub claimed_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ScheduledMessage {
+    // new schedules a non-empty message at a time still to come
+    pub fn new(
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Uuid,
+        content: &str,
+        send_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Self, ChatError> {
+        if content.trim().is_empty() {
+            return Err(ChatError::InvalidMessage(
+                "scheduled message content is empty".to_string(),
+            ));
+        }
+        let now = chrono::Utc::now();
+        if send_at <= now {
+            return Err(ChatError::InvalidMessage(
+                "send_at must be in the future".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            chat_id,
+            content: content.to_string(),
+            send_at,
+            status: ScheduledStatus::Pending,
+            error: None,
+            created_at: now,
+            claimed_until: None,
+        })
+    }
+
+    // is_due tells whether the message should be sent at now, it is pending past its send_at or
+    // was left sending by a worker whose claim is over
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.status {
+            ScheduledStatus::Pending => self.send_at <= now,
+            ScheduledStatus::Sending => self.claimed_until.is_none_or(|until| until < now),
+            ScheduledStatus::Sent | ScheduledStatus::Failed | ScheduledStatus::Cancelled => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_and_is_due() {
+        let send_at = chrono::Utc::now() + chrono::Duration::minutes(5);
+        let scheduled = ScheduledMessage::new(
+            Uuid::nil(),
+            Uuid::nil(),
+            Uuid::nil(),
+            "Good morning",
+            send_at,
+        )
+        .unwrap();
+        assert_eq!(scheduled.status, ScheduledStatus::Pending);
+        assert!(!scheduled.is_due(chrono::Utc::now()));
+        assert!(scheduled.is_due(send_at));
+        let sending = ScheduledMessage {
+            status: ScheduledStatus::Sending,
+            claimed_until: Some(send_at),
+            ..scheduled
+        };
+        assert!(!sending.is_due(send_at));
+        assert!(sending.is_due(send_at + chrono::Duration::seconds(1)));
+
+        let past = chrono::Utc::now() - chrono::Duration::minutes(5);
+        assert!(ScheduledMessage::new(Uuid::nil(), Uuid::nil(), Uuid::nil(), "Hi", past).is_err());
+        assert!(
+            ScheduledMessage::new(Uuid::nil(), Uuid::nil(), Uuid::nil(), " ", send_at).is_err()
+        );
+
+        assert_eq!(
+            "cancelled".parse::<ScheduledStatus>(),
+            Ok(ScheduledStatus::Cancelled)
+        );
+        assert!("later".parse::<ScheduledStatus>().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// SPEECH_MIME_TYPE is the encoding speech is synthesized in
+pub const SPEECH_MIME_TYPE: &str = "audio/mpeg";
+
+// Voice is the speaker a reply is read aloud with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Voice {
+    Alloy,
+    Echo,
+    Fable,
+    Onyx,
+    Nova,
+    Shimmer,
+}
+
+impl fmt::Display for Voice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Voice::Alloy => "alloy",
+            Voice::Echo => "echo",
+            Voice::Fable => "fable",
+            Voice::Onyx => "onyx",
+            Voice::Nova => "nova",
+            Voice::Shimmer => "shimmer",
+        })
+    }
+}
+
+// Speech is text read aloud, encoded as SPEECH_MIME_TYPE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Speech {
+    pub voice: Voice,
+    pub content: Vec<u8>,
+}
+
+impl Speech {
+    pub fn new(voice: Voice, content: Vec<u8>) -> Self {
+        Self { voice, content }
+    }
+
+    // append adds the speech of the next piece of text, MP3 frames play back to back
+    pub fn append(&mut self, other: Speech) {
+        self.content.extend(other.content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_serde() {
+        assert_eq!(serde_json::to_string(&Voice::Nova).unwrap(), "\"nova\"");
+        assert_eq!(
+            serde_json::from_str::<Voice>("\"shimmer\"").unwrap(),
+            Voice::Shimmer
+        );
+        assert!(serde_json::from_str::<Voice>("\"robot\"").is_err());
+        assert_eq!(Voice::Onyx.to_string(), "onyx");
+    }
+}
@@ -0,0 +1,148 @@
+use uuid::Uuid;
+
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::error::ConfigError;
+use crate::internal::domain::output_filter::OutputFilterConfig;
+use crate::internal::domain::quota::QuotaConfig;
+use crate::internal::domain::rate_limiter::RateLimitConfig;
+
+pub const MAX_TENANT_NAME_LENGTH: usize = 255;
+
+// DEFAULT_TENANT_ID is the tenant of users that were not assigned to an organization,
+// it always exists and uses the service defaults
+pub const DEFAULT_TENANT_ID: Uuid = Uuid::nil();
+
+// TenantConfig overrides the service defaults for the users of a tenant, unset fields keep them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TenantConfig {
+    // model is the model new chats of the tenant are created with
+    pub model: Option<Model>,
+    pub rate_limit: Option<RateLimitConfig>,
+    // allowed_models names the models the tenant's chats may use, every model when empty
+    pub allowed_models: Vec<String>,
+    pub quota: Option<QuotaConfig>,
+    // output_filter replaces the default filter of the assistant responses
+    pub output_filter: Option<OutputFilterConfig>,
+}
+
+// Tenant is an organization, its users, chats and usage are never visible to other tenants
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub name: String,
+    pub config: TenantConfig,
+}
+
+impl Tenant {
+    pub fn new(id: Uuid, name: &str, config: TenantConfig) -> Self {
+        Self {
+            id,
+            name: name.trim().to_string(),
+            config,
+        }
+    }
+
+    pub fn default_tenant() -> Self {
+        Self::new(DEFAULT_TENANT_ID, "default", TenantConfig::default())
+    }
+
+    pub fn allows_model(&self, name: &str) -> bool {
+        self.config.allowed_models.is_empty()
+            || self
+                .config
+                .allowed_models
+                .iter()
+                .any(|allowed| allowed == name)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.name.is_empty() {
+            return Err(ConfigError::InvalidTenant(format!(
+                "tenant {} has an empty name",
+                self.id
+            )));
+        }
+
+        if self.name.len() > MAX_TENANT_NAME_LENGTH {
+            return Err(ConfigError::InvalidTenant(format!(
+                "tenant {} has a name longer than {} bytes",
+                self.id, MAX_TENANT_NAME_LENGTH
+            )));
+        }
+
+        if let Some(model) = &self.config.model {
+            model.validate().map_err(|e| {
+                ConfigError::InvalidTenant(format!("tenant {} model: {}", self.id, e))
+            })?;
+            if !self.allows_model(&model.name) {
+                return Err(ConfigError::InvalidTenant(format!(
+                    "tenant {} model {} is not one of its allowed models",
+                    self.id, model.name
+                )));
+            }
+        }
+
+        if let Some(output_filter) = &self.config.output_filter {
+            output_filter.validate().map_err(|e| {
+                ConfigError::InvalidTenant(format!("tenant {} output filter: {}", self.id, e))
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let tenant = Tenant::new(Uuid::new_v4(), " Acme ", TenantConfig::default());
+        assert_eq!(tenant.name, "Acme");
+        assert!(tenant.validate().is_ok());
+        assert!(Tenant::default_tenant().validate().is_ok());
+
+        let unnamed = Tenant::new(Uuid::new_v4(), "  ", TenantConfig::default());
+        assert!(matches!(
+            unnamed.validate(),
+            Err(ConfigError::InvalidTenant(_))
+        ));
+
+        let invalid_model = Tenant::new(
+            Uuid::new_v4(),
+            "Acme",
+            TenantConfig {
+                model: Some(Model::new("".to_string(), 4096)),
+                rate_limit: None,
+                allowed_models: vec![],
+                quota: None,
+                output_filter: None,
+            },
+        );
+        assert!(matches!(
+            invalid_model.validate(),
+            Err(ConfigError::InvalidTenant(_))
+        ));
+
+        let mut restricted = Tenant::new(
+            Uuid::new_v4(),
+            "Acme",
+            TenantConfig {
+                model: Some(Model::new("gpt-4o".to_string(), 128000)),
+                rate_limit: None,
+                allowed_models: vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()],
+                quota: None,
+                output_filter: None,
+            },
+        );
+        assert!(restricted.validate().is_ok());
+        assert!(restricted.allows_model("gpt-4o-mini"));
+        assert!(!restricted.allows_model("gpt-3.5-turbo"));
+        restricted.config.allowed_models = vec!["gpt-4o-mini".to_string()];
+        assert!(matches!(
+            restricted.validate(),
+            Err(ConfigError::InvalidTenant(_))
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::error::ConfigError;
+
+const MAX_TOOL_NAME_LENGTH: usize = 64;
+
+// ToolDefinition describes a function the model may call, parameters is a JSON schema object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+impl ToolDefinition {
+    pub fn new(name: &str, description: &str, parameters: serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+        }
+    }
+
+    // validate applies the provider naming rules: 1 to 64 ASCII letters, digits, _ or -
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= MAX_TOOL_NAME_LENGTH
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return Err(ConfigError::InvalidTool(format!(
+                "name {:?} must be 1 to {} letters, digits, _ or -",
+                self.name, MAX_TOOL_NAME_LENGTH
+            )));
+        }
+
+        if !self.parameters.is_object() {
+            return Err(ConfigError::InvalidTool(format!(
+                "parameters of {} must be a JSON schema object",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+// ToolCall is a call requested by the model, arguments is the raw JSON it produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate() {
+        let parameters = json!({"type": "object", "properties": {}});
+
+        assert!(ToolDefinition::new("get_weather", "", parameters.clone())
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            ToolDefinition::new("get weather", "", parameters.clone()).validate(),
+            Err(ConfigError::InvalidTool(_))
+        ));
+        assert!(matches!(
+            ToolDefinition::new("", "", parameters).validate(),
+            Err(ConfigError::InvalidTool(_))
+        ));
+        assert!(matches!(
+            ToolDefinition::new("get_weather", "", json!("city")).validate(),
+            Err(ConfigError::InvalidTool(_))
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// UsageRecord is what a single completion consumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub model: String,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    // cost is estimated in USD from the registry pricing, zero for models without pricing
+    pub cost: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl UsageRecord {
+    pub fn date(&self) -> chrono::NaiveDate {
+        self.created_at.date_naive()
+    }
+}
+
+// DailyUsage aggregates the usage of a user for one model on one UTC day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub user_id: Uuid,
+    pub date: chrono::NaiveDate,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl DailyUsage {
+    pub fn from_record(record: &UsageRecord) -> Self {
+        Self {
+            user_id: record.user_id,
+            date: record.date(),
+            model: record.model.clone(),
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+        }
+    }
+
+    // add folds a record of the same user, day and model into the aggregate
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.cost += record.cost;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+// ChatUsage aggregates everything the completions of one chat consumed, across models and days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatUsage {
+    pub chat_id: Uuid,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl ChatUsage {
+    pub fn new(chat_id: Uuid) -> Self {
+        Self {
+            chat_id,
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+        }
+    }
+
+    // add folds a record of the same chat into the aggregate
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.cost += record.cost;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+// TenantUsage aggregates the usage of a tenant for one model over a range of days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenantUsage {
+    pub tenant_id: Uuid,
+    pub model: String,
+    // users is how many users of the tenant used the model
+    pub users: u64,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl TenantUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+// UserUsage aggregates the usage of a user for one model over a range of days, what the user
+// is billed for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserUsage {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let record = UsageRecord {
+            tenant_id: Uuid::nil(),
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            model: "gpt-4o".to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 20,
+            cost: 0.0008,
+            created_at: chrono::Utc::now(),
+        };
+        let mut usage = DailyUsage::from_record(&record);
+
+        usage.add(&record);
+        usage.add(&record);
+
+        assert_eq!(usage.date, record.date());
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.prompt_tokens, 200);
+        assert_eq!(usage.completion_tokens, 40);
+        assert_eq!(usage.total_tokens(), 240);
+        assert!((usage.cost - 0.0016).abs() < 1e-9);
+    }
+}
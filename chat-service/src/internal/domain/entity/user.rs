@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+use crate::internal::domain::error::ChatError;
+
+pub const MAX_EXTERNAL_ID_LENGTH: usize = 255;
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 255;
+
+// User owns chats, external_id is the identifier given by the caller's identity system
+// and is unique within the tenant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: Uuid,
+    #[serde(default)]
+    pub tenant_id: Uuid,
+    pub external_id: String,
+    pub display_name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl User {
+    pub fn new(
+        id: Uuid,
+        external_id: &str,
+        display_name: &str,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            id,
+            tenant_id: DEFAULT_TENANT_ID,
+            external_id: external_id.trim().to_string(),
+            display_name: display_name.trim().to_string(),
+            created_at,
+        }
+    }
+
+    pub fn with_tenant(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ChatError> {
+        if self.external_id.is_empty() {
+            return Err(ChatError::InvalidUser("external_id is empty".to_string()));
+        }
+
+        if self.external_id.len() > MAX_EXTERNAL_ID_LENGTH {
+            return Err(ChatError::InvalidUser(
+                "external_id is too long".to_string(),
+            ));
+        }
+
+        if self.display_name.is_empty() {
+            return Err(ChatError::InvalidUser("display_name is empty".to_string()));
+        }
+
+        if self.display_name.len() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(ChatError::InvalidUser(
+                "display_name is too long".to_string(),
+            ));
+        }
+
+        if self.created_at > chrono::Utc::now() {
+            return Err(ChatError::InvalidUser("created_at is invalid".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let id = Uuid::new_v4();
+        let created_at = chrono::Utc::now();
+        let user = User::new(id, " auth0|42 ", "Ada ", created_at);
+
+        assert_eq!(user.id, id);
+        assert_eq!(user.external_id, "auth0|42");
+        assert_eq!(user.display_name, "Ada");
+        assert_eq!(user.created_at, created_at);
+        assert!(user.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let now = chrono::Utc::now();
+
+        let user = User::new(Uuid::new_v4(), "", "Ada", now);
+        assert_eq!(
+            user.validate(),
+            Err(ChatError::InvalidUser("external_id is empty".to_string()))
+        );
+
+        let user = User::new(Uuid::new_v4(), "auth0|42", "  ", now);
+        assert_eq!(
+            user.validate(),
+            Err(ChatError::InvalidUser("display_name is empty".to_string()))
+        );
+
+        let user = User::new(
+            Uuid::new_v4(),
+            "auth0|42",
+            &"a".repeat(MAX_DISPLAY_NAME_LENGTH + 1),
+            now,
+        );
+        assert!(user.validate().is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// MAX_MEMORY_LENGTH bounds a memory in characters, a memory is a single fact
+pub const MAX_MEMORY_LENGTH: usize = 500;
+
+// Memory is a fact about a user extracted from their chats, e.g. their name or a preference,
+// new chats of the user are told about it in their system message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Memory {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // chat_id is the chat the fact was extracted from
+    pub chat_id: Uuid,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Memory {
+    pub fn new(tenant_id: Uuid, user_id: Uuid, chat_id: Uuid, content: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            chat_id,
+            content: content.trim().chars().take(MAX_MEMORY_LENGTH).collect(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    // is_about tells whether the memory already holds the fact, case and spacing aside
+    pub fn is_about(&self, fact: &str) -> bool {
+        normalize(&self.content) == normalize(fact)
+    }
+}
+
+fn normalize(fact: &str) -> String {
+    fact.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_and_is_about() {
+        let memory = Memory::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "  The user is called Ana. ",
+        );
+        assert_eq!(memory.content, "The user is called Ana.");
+        assert!(memory.is_about("the user  is called ana"));
+        assert!(!memory.is_about("The user lives in Lisbon."));
+
+        let long = Memory::new(Uuid::nil(), Uuid::nil(), Uuid::nil(), &"a".repeat(600));
+        assert_eq!(long.content.len(), MAX_MEMORY_LENGTH);
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::entity::message::Role;
+use crate::internal::domain::error::ChatError;
+
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+const WEBHOOK_SECRET_BYTES: usize = 32;
+// MIN_WEBHOOK_SECRET_LENGTH keeps secrets chosen by tenants hard to guess
+pub const MIN_WEBHOOK_SECRET_LENGTH: usize = 16;
+
+// WebhookEvent is a chat lifecycle event tenants can be called back on, or the end of a
+// completion job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "chat.created")]
+    ChatCreated,
+    // MessageCompleted is an assistant reply saved to the chat
+    #[serde(rename = "message.completed")]
+    MessageCompleted,
+    #[serde(rename = "chat.ended")]
+    ChatEnded,
+    // JobCompleted is a completion job that succeeded or failed
+    #[serde(rename = "job.completed")]
+    JobCompleted,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 4] = [
+        WebhookEvent::ChatCreated,
+        WebhookEvent::MessageCompleted,
+        WebhookEvent::ChatEnded,
+        WebhookEvent::JobCompleted,
+    ];
+
+    // of returns the webhook event a chat event is, the chat events no webhook is about are
+    // None
+    pub fn of(event: &ChatEvent) -> Option<Self> {
+        match event {
+            ChatEvent::ChatCreated { .. } => Some(WebhookEvent::ChatCreated),
+            ChatEvent::MessageAdded {
+                role: Role::Assistant,
+                ..
+            } => Some(WebhookEvent::MessageCompleted),
+            ChatEvent::ChatEnded => Some(WebhookEvent::ChatEnded),
+            ChatEvent::MessageAdded { .. } | ChatEvent::TokensConsumed { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let event = match self {
+            WebhookEvent::ChatCreated => "chat.created",
+            WebhookEvent::MessageCompleted => "message.completed",
+            WebhookEvent::ChatEnded => "chat.ended",
+            WebhookEvent::JobCompleted => "job.completed",
+        };
+        f.write_str(event)
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = ChatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chat.created" => Ok(WebhookEvent::ChatCreated),
+            "message.completed" => Ok(WebhookEvent::MessageCompleted),
+            "chat.ended" => Ok(WebhookEvent::ChatEnded),
+            "job.completed" => Ok(WebhookEvent::JobCompleted),
+            _ => Err(ChatError::InvalidWebhook(format!("unknown event {}", s))),
+        }
+    }
+}
+
+// Webhook is a URL of a tenant called back on the events it subscribes to, every call is
+// signed with the secret so the tenant can tell it comes from the service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Webhook {
+    // new registers an https URL of a public host for the events, every event when none is
+    // given; a random secret is generated unless the tenant brings its own
+    pub fn new(
+        tenant_id: Uuid,
+        url: &str,
+        secret: Option<&str>,
+        events: &[WebhookEvent],
+    ) -> Result<Self, ChatError> {
+        let url = url.trim();
+        let host = url
+            .strip_prefix("https://")
+            .map(host_of)
+            .unwrap_or_default();
+        if host.is_empty() || url.chars().any(char::is_whitespace) {
+            return Err(ChatError::InvalidWebhook(format!(
+                "{} is not an https URL",
+                url
+            )));
+        }
+        if !is_public_host(&host) {
+            return Err(ChatError::InvalidWebhook(format!(
+                "{} is not a public host",
+                host
+            )));
+        }
+
+        let secret = match secret {
+            Some(secret) if secret.len() < MIN_WEBHOOK_SECRET_LENGTH => {
+                return Err(ChatError::InvalidWebhook(format!(
+                    "secret must be at least {} characters",
+                    MIN_WEBHOOK_SECRET_LENGTH
+                )));
+            }
+            Some(secret) => secret.to_string(),
+            None => generate_secret(),
+        };
+
+        let mut subscribed: Vec<WebhookEvent> = Vec::new();
+        for event in events {
+            if !subscribed.contains(event) {
+                subscribed.push(*event);
+            }
+        }
+        if subscribed.is_empty() {
+            subscribed = WebhookEvent::ALL.to_vec();
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            url: url.to_string(),
+            secret,
+            events: subscribed,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+// host_of returns the lowercase host of the authority the rest of a URL starts with, without
+// the user info, the port or the brackets of an IPv6 address
+fn host_of(rest: &str) -> String {
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host_port.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+// is_public_host tells whether a host can be called back: localhost and the addresses of
+// private networks are not, a name is checked again once it is resolved
+fn is_public_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".localhost") {
+        return false;
+    }
+
+    match host.parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => true,
+    }
+}
+
+// is_public_ip tells whether an address is reachable on the internet, loopback, private,
+// link-local (cloud metadata among them) and unspecified addresses are not
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || first == 0
+                // shared address space of carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && (second & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                // unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; WEBHOOK_SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", WEBHOOK_SECRET_PREFIX, hex::encode(bytes))
+}
+
+// WebhookPayload is the JSON body a webhook is called with; id is the id of the chat event or
+// of the job, the same for every webhook and attempt, so receivers can drop the calls they
+// already handled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event: WebhookEvent,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // chat_id is only missing for a job that failed before its chat was started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<Uuid>,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub data: serde_json::Value,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent, outbox: &OutboxEvent) -> Self {
+        let data = match &outbox.event {
+            ChatEvent::ChatCreated { model } => serde_json::json!({ "model": model }),
+            ChatEvent::MessageAdded {
+                message_id, tokens, ..
+            } => serde_json::json!({ "message_id": message_id, "tokens": tokens }),
+            ChatEvent::ChatEnded | ChatEvent::TokensConsumed { .. } => serde_json::json!({}),
+        };
+
+        Self {
+            id: outbox.id,
+            event,
+            tenant_id: outbox.tenant_id,
+            user_id: outbox.user_id,
+            chat_id: Some(outbox.chat_id),
+            occurred_at: outbox.occurred_at,
+            data,
+        }
+    }
+
+    // job is the payload of a completed job, carrying its answer or why it has none
+    pub fn job(job: &Job) -> Self {
+        Self {
+            id: job.id,
+            event: WebhookEvent::JobCompleted,
+            tenant_id: job.tenant_id,
+            user_id: job.user_id,
+            chat_id: job.chat_id,
+            occurred_at: job.completed_at.unwrap_or(job.created_at),
+            data: serde_json::json!({
+                "job_id": job.id,
+                "status": job.status,
+                "content": job.content,
+                "error": job.error,
+            }),
+        }
+    }
+}
+
+// WebhookDelivery is a call of a webhook waiting to succeed; the payload is kept as sent so
+// every attempt carries the same body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub webhook_id: Uuid,
+    // event_id is the id of the chat event, as in the payload
+    pub event_id: Uuid,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub attempts: u32,
+    pub next_attempt_at: chrono::DateTime<chrono::Utc>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WebhookDelivery {
+    // new is due right away
+    pub fn new(webhook: &Webhook, payload: &WebhookPayload) -> Result<Self, serde_json::Error> {
+        let now = chrono::Utc::now();
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            tenant_id: webhook.tenant_id,
+            webhook_id: webhook.id,
+            event_id: payload.id,
+            event: payload.event,
+            payload: serde_json::to_string(payload)?,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+        })
+    }
+}
+
+// DeadLetter is a delivery that failed every attempt, kept for the tenant to look into
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    #[serde(flatten)]
+    pub delivery: WebhookDelivery,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let webhook = Webhook::new(
+            Uuid::nil(),
+            " https://example.com/hooks ",
+            None,
+            &[WebhookEvent::ChatEnded, WebhookEvent::ChatEnded],
+        )
+        .unwrap();
+        assert_eq!(webhook.url, "https://example.com/hooks");
+        assert!(webhook.secret.starts_with(WEBHOOK_SECRET_PREFIX));
+        assert_eq!(webhook.events, vec![WebhookEvent::ChatEnded]);
+        assert!(!webhook.subscribes_to(WebhookEvent::ChatCreated));
+
+        let every = Webhook::new(Uuid::nil(), "https://Hooks.Example.com:8443", None, &[]).unwrap();
+        assert_eq!(every.events, WebhookEvent::ALL.to_vec());
+
+        assert!(Webhook::new(Uuid::nil(), "ftp://example.com", None, &[]).is_err());
+        assert!(Webhook::new(Uuid::nil(), "http://example.com", None, &[]).is_err());
+        for internal in [
+            "https://localhost:8080",
+            "https://api.localhost/hooks",
+            "https://127.0.0.1/hooks",
+            "https://10.0.0.7/hooks",
+            "https://192.168.1.1",
+            "https://169.254.169.254/latest/meta-data",
+            "https://user@172.16.0.1:8443/hooks",
+            "https://[::1]:8080/hooks",
+            "https://[fd00::1]/hooks",
+            "https://[::ffff:127.0.0.1]/hooks",
+        ] {
+            assert!(
+                Webhook::new(Uuid::nil(), internal, None, &[]).is_err(),
+                "{} should be rejected",
+                internal
+            );
+        }
+        assert!(Webhook::new(Uuid::nil(), "https://", None, &[]).is_err());
+        assert!(Webhook::new(Uuid::nil(), "https://example.com", Some("short"), &[]).is_err());
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for public in ["93.184.216.34", "8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public_ip(public.parse().unwrap()), "{}", public);
+        }
+        for internal in [
+            "127.0.0.1",
+            "0.0.0.0",
+            "100.64.0.1",
+            "169.254.169.254",
+            "::",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_public_ip(internal.parse().unwrap()), "{}", internal);
+        }
+    }
+
+    #[test]
+    fn test_of_and_payload() {
+        let message_id = Uuid::new_v4();
+        let reply = ChatEvent::MessageAdded {
+            message_id,
+            role: Role::Assistant,
+            tokens: 12,
+        };
+        assert_eq!(
+            WebhookEvent::of(&reply),
+            Some(WebhookEvent::MessageCompleted)
+        );
+        assert_eq!(
+            WebhookEvent::of(&ChatEvent::MessageAdded {
+                message_id,
+                role: Role::User,
+                tokens: 3,
+            }),
+            None
+        );
+        assert_eq!(
+            "chat.ended".parse::<WebhookEvent>(),
+            Ok(WebhookEvent::ChatEnded)
+        );
+
+        let outbox = OutboxEvent {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            event: reply,
+            occurred_at: chrono::Utc::now(),
+        };
+        let value =
+            serde_json::to_value(WebhookPayload::new(WebhookEvent::MessageCompleted, &outbox))
+                .unwrap();
+        assert_eq!(value["id"], outbox.id.to_string());
+        assert_eq!(value["type"], "message.completed");
+        assert_eq!(value["data"]["message_id"], message_id.to_string());
+        assert_eq!(value["data"]["tokens"], 12);
+    }
+
+    #[test]
+    fn test_job_payload() {
+        let mut job = Job::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        job.fail("model provider returned an empty response".to_string());
+
+        let value = serde_json::to_value(WebhookPayload::job(&job)).unwrap();
+        assert_eq!(value["id"], job.id.to_string());
+        assert_eq!(value["type"], "job.completed");
+        assert!(value.get("chat_id").is_none());
+        assert_eq!(value["data"]["status"], "failed");
+        assert_eq!(
+            value["data"]["error"],
+            "model provider returned an empty response"
+        );
+    }
+}
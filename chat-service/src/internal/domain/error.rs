@@ -0,0 +1,79 @@
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ChatError {
+    #[error("chat status is invalid: {0}")]
+    InvalidStatus(String),
+    #[error("chat token usage {usage} exceeds the limit of {limit}")]
+    TokenLimitExceeded { usage: usize, limit: usize },
+    #[error("chat has already ended")]
+    ChatEnded,
+    #[error("chat is archived")]
+    ChatArchived,
+    #[error("chat is deleted")]
+    ChatDeleted,
+    #[error("chat cannot go from {from} to {to}")]
+    InvalidTransition { from: String, to: String },
+    #[error("invalid message: {0}")]
+    InvalidMessage(String),
+    #[error("invalid user: {0}")]
+    InvalidUser(String),
+    #[error("invalid model: {0}")]
+    InvalidModel(String),
+    #[error("message was flagged by moderation for {}", .0.join(", "))]
+    ContentFlagged(Vec<String>),
+    #[error("message looks like a prompt injection: {}", .0.join(", "))]
+    PromptInjection(Vec<String>),
+    #[error("assistant response does not match the response format: {0}")]
+    ResponseFormatMismatch(String),
+    #[error("invalid prompt template: {0}")]
+    InvalidTemplate(String),
+    #[error("prompt template variables are missing: {}", .0.join(", "))]
+    MissingTemplateVariables(Vec<String>),
+    #[error("invalid assistant: {0}")]
+    InvalidAssistant(String),
+    #[error("invalid document: {0}")]
+    InvalidDocument(String),
+    #[error("invalid attachment: {0}")]
+    InvalidAttachment(String),
+    #[error("model {0} does not accept image attachments")]
+    AttachmentsNotSupported(String),
+    #[error("model {0} is not allowed for the tenant")]
+    ModelNotAllowed(String),
+    #[error("invalid audio: {0}")]
+    InvalidAudio(String),
+    #[error("invalid webhook: {0}")]
+    InvalidWebhook(String),
+    #[error("invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("invalid metadata: {0}")]
+    InvalidMetadata(String),
+    #[error("invalid chat config: {0}")]
+    InvalidConfig(#[from] ConfigError),
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("temperature {0} is outside 0..=2")]
+    TemperatureOutOfRange(f32),
+    #[error("top_p {0} is outside 0..=1")]
+    TopPOutOfRange(f32),
+    #[error("n must be at least 1")]
+    InvalidN,
+    #[error("max_tokens {max_tokens} exceeds the model context of {context}")]
+    MaxTokensExceedsContext { max_tokens: usize, context: usize },
+    #[error("unknown trimming policy {0}")]
+    UnknownTrimmingPolicy(String),
+    #[error("invalid tool: {0}")]
+    InvalidTool(String),
+    #[error("invalid response format: {0}")]
+    InvalidResponseFormat(String),
+    #[error("invalid tenant: {0}")]
+    InvalidTenant(String),
+    #[error("unknown database driver {0}, expected postgres, mysql, sqlite or memory")]
+    UnknownDatabaseDriver(String),
+    #[error("unknown prompt guard action {0}, expected block, flag or annotate")]
+    UnknownGuardAction(String),
+    #[error("invalid output filter: {0}")]
+    InvalidOutputFilter(String),
+    #[error("unknown language {0}")]
+    UnknownLanguage(String),
+}
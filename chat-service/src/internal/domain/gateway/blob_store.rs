@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("blob store error: {0}")]
+pub struct BlobError(pub String);
+
+// BlobStore keeps opaque blobs by key, such as the objects of an S3 compatible bucket; keys are
+// slash separated paths
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    // put stores the blob under the key, replacing the one stored there
+    async fn put(&self, key: &str, content: Vec<u8>) -> Result<(), BlobError>;
+
+    // get returns None when nothing is stored under the key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BlobError>;
+
+    // delete succeeds when nothing is stored under the key
+    async fn delete(&self, key: &str) -> Result<(), BlobError>;
+}
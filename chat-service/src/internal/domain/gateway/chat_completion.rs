@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::quota::QuotaError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GatewayError {
+    #[error("request to model provider failed: {0}")]
+    Request(String),
+    #[error("model provider returned status {status}: {body}")]
+    Api { status: u16, body: String },
+    #[error("model provider returned an empty response")]
+    EmptyResponse,
+    #[error("no gateway configured for provider {0}")]
+    UnknownProvider(String),
+    #[error("provider {0} is unavailable, retry later")]
+    ProviderUnavailable(String),
+    #[error("model provider did not answer within {}s", .0.as_secs())]
+    Timeout(Duration),
+    #[error("could not write the audit log: {0}")]
+    Audit(String),
+    #[error(transparent)]
+    Quota(#[from] QuotaError),
+}
+
+impl GatewayError {
+    // is_provider_failure tells whether the error says something about the provider's health,
+    // requests the provider rejected as invalid do not count
+    pub fn is_provider_failure(&self) -> bool {
+        match self {
+            GatewayError::Request(_)
+            | GatewayError::EmptyResponse
+            | GatewayError::ProviderUnavailable(_)
+            | GatewayError::Timeout(_) => true,
+            GatewayError::Api { status, .. } => *status == 429 || *status >= 500,
+            GatewayError::UnknownProvider(_) | GatewayError::Audit(_) | GatewayError::Quota(_) => {
+                false
+            }
+        }
+    }
+}
+
+// ChatCompletionGateway sends the chat history to a model and returns the assistant reply
+#[async_trait]
+pub trait ChatCompletionGateway: Send + Sync {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError>;
+
+    // create_chat_completion_stream sends every content delta to the sender as it arrives
+    // and returns the assembled assistant message once the model is done
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError>;
+}
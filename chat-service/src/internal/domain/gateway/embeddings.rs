@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+
+// EmbeddingsGateway turns texts into vectors whose distance tells how related the texts are
+#[async_trait]
+pub trait EmbeddingsGateway: Send + Sync {
+    // model names the embedding model, vectors of different models cannot be compared
+    fn model(&self) -> &str;
+
+    // embed returns one vector per input, in the order of the inputs
+    async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError>;
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("could not publish events: {0}")]
+pub struct PublishError(pub String);
+
+// EventPublisher delivers chat events to the sink downstream consumers read from, such as
+// a Kafka topic, a NATS subject or a Redis stream; events are published in order
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, events: &[OutboxEvent]) -> Result<(), PublishError>;
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{0}")]
+pub struct HealthError(pub String);
+
+// HealthCheck probes a dependency the service cannot serve requests without
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    // name identifies the dependency in readiness reports, e.g. postgres or provider:openai
+    fn name(&self) -> &str;
+
+    async fn check(&self) -> Result<(), HealthError>;
+}
@@ -0,0 +1,10 @@
+pub mod blob_store;
+pub mod chat_completion;
+pub mod embeddings;
+pub mod event_publisher;
+pub mod health;
+pub mod moderation;
+pub mod speech;
+pub mod token_verifier;
+pub mod transcription;
+pub mod webhook;
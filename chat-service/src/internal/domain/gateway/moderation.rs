@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+
+// ModerationResult tells whether content breaks the provider policies and which ones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    pub categories: Vec<String>,
+}
+
+// ModerationGateway classifies user content before it reaches the model
+#[async_trait]
+pub trait ModerationGateway: Send + Sync {
+    async fn moderate(&self, content: &str) -> Result<ModerationResult, GatewayError>;
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::speech::{Speech, Voice};
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+
+// SpeechGateway reads text aloud
+#[async_trait]
+pub trait SpeechGateway: Send + Sync {
+    // max_input_chars is the longest text synthesize accepts in one call
+    fn max_input_chars(&self) -> usize;
+
+    async fn synthesize(&self, text: &str, voice: Voice) -> Result<Speech, GatewayError>;
+}
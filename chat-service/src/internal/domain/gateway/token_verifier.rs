@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error("token is invalid: {0}")]
+    Invalid(String),
+    #[error("signing keys are unavailable: {0}")]
+    KeysUnavailable(String),
+}
+
+// VerifiedToken is what the service needs from a validated access token
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedToken {
+    // subject is the value of the configured user claim, matched against User.external_id
+    pub subject: String,
+    pub name: Option<String>,
+    // tenant is the value of the configured tenant claim, the default tenant when absent
+    pub tenant: Option<String>,
+}
+
+// TokenVerifier validates bearer tokens issued by an external identity provider
+#[async_trait]
+pub trait TokenVerifier: Send + Sync {
+    async fn verify(&self, token: &str) -> Result<VerifiedToken, TokenError>;
+}
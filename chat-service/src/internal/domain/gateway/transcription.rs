@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::audio::Audio;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+
+// TranscriptionGateway turns recorded speech into text
+#[async_trait]
+pub trait TranscriptionGateway: Send + Sync {
+    // transcribe returns the text spoken in the audio, empty when no speech was recognized
+    async fn transcribe(&self, audio: &Audio) -> Result<String, GatewayError>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::webhook::{Webhook, WebhookDelivery};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("could not deliver webhook: {0}")]
+pub struct WebhookError(pub String);
+
+// WebhookSender calls a webhook with the payload of a delivery, signed with the secret of the
+// webhook; anything but a 2xx answer is a failed attempt
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    async fn send(&self, webhook: &Webhook, delivery: &WebhookDelivery)
+        -> Result<(), WebhookError>;
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+
+// HistoryLine is a line of an archived history, the erased messages follow the others
+#[derive(Serialize, Deserialize)]
+struct HistoryLine {
+    #[serde(default)]
+    erased: bool,
+    #[serde(flatten)]
+    message: Message,
+}
+
+// encode_history writes the messages of the chat as JSON lines in conversation order, the system
+// message stays with the chat and is left out
+pub fn encode_history(chat: &Chat) -> Result<Vec<u8>, serde_json::Error> {
+    let mut content = Vec::new();
+    let lines = chat
+        .messages
+        .iter()
+        .map(|message| (false, message))
+        .chain(chat.erased_messages.iter().map(|message| (true, message)));
+    for (erased, message) in lines {
+        serde_json::to_writer(
+            &mut content,
+            &HistoryLine {
+                erased,
+                message: message.clone(),
+            },
+        )?;
+        content.push(b'\n');
+    }
+
+    Ok(content)
+}
+
+// decode_history reads the messages and the erased messages of an encoded history
+pub fn decode_history(content: &[u8]) -> Result<(Vec<Message>, Vec<Message>), serde_json::Error> {
+    let mut messages = vec![];
+    let mut erased_messages = vec![];
+    for line in content.split(|byte| *byte == b'\n') {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let line: HistoryLine = serde_json::from_slice(line)?;
+        if line.erased {
+            erased_messages.push(line.message);
+        } else {
+            messages.push(line.message);
+        }
+    }
+
+    Ok((messages, erased_messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+
+    #[test]
+    fn test_history_round_trip() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                3,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!\nAre you there?"),
+                message(Role::Assistant, "Hi!"),
+            ],
+            vec![message(Role::User, "Hey")],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+
+        let content = encode_history(&chat).unwrap();
+        assert_eq!(content.iter().filter(|byte| **byte == b'\n').count(), 3);
+
+        let (messages, erased_messages) = decode_history(&content).unwrap();
+        assert_eq!(messages, chat.messages);
+        assert_eq!(erased_messages, chat.erased_messages);
+        assert!(decode_history(b"{not json}\n").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+// LANGUAGES are the languages a chat can be told to answer in, by ISO 639-1 code
+pub const LANGUAGES: [(&str, &str); 20] = [
+    ("ar", "Arabic"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("zh", "Chinese"),
+];
+
+// MIN_STOPWORDS is how many common words of a language written in the Latin script a text needs
+// before it is said to be in that language
+const MIN_STOPWORDS: usize = 2;
+
+// STOPWORDS are the most common words of the languages written in the Latin script, a text is
+// in the language whose words it uses the most
+const STOPWORDS: [(&str, &[&str]); 8] = [
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "you", "of", "to", "what", "how", "with", "this", "that",
+            "it", "for", "my", "can", "please", "do", "i", "was", "have",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "que", "de", "y", "es", "en", "por", "para", "con", "una",
+            "un", "como", "qué", "cómo", "está", "mi", "puedes", "del", "al", "yo", "pero",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "des", "est", "et", "que", "une", "un", "pour", "dans", "avec",
+            "vous", "je", "pas", "ce", "qui", "mon", "comment", "du", "au", "suis", "tu",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ich", "du", "sie", "mit", "ein", "eine",
+            "zu", "wie", "was", "für", "auf", "mein", "bitte", "es", "den", "dem", "kannst",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "gli", "che", "di", "è", "un", "una", "per", "con", "non", "come", "sono",
+            "mi", "cosa", "del", "della", "ciao", "puoi", "ho", "questo",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "que", "de", "é", "um", "uma", "para", "com", "não", "como", "você", "meu",
+            "do", "da", "em", "está", "eu", "isso", "olá", "pode",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "van", "ik", "je", "niet", "dat", "met", "voor", "op",
+            "hoe", "wat", "zijn", "mijn", "kun", "jij", "dit",
+        ],
+    ),
+    (
+        "pl",
+        &[
+            "i", "w", "nie", "na", "się", "jest", "to", "że", "z", "co", "jak", "mój", "czy",
+            "proszę", "możesz", "dla", "jestem",
+        ],
+    ),
+];
+
+// language_name returns the English name of the language of the code, None when it is not one
+// of the LANGUAGES
+pub fn language_name(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, name)| *name)
+}
+
+// response_instruction tells the model to answer in the language of the code
+pub fn response_instruction(code: &str) -> String {
+    format!(
+        "Always answer in {}, whatever the language of the messages.",
+        language_name(code).unwrap_or(code)
+    )
+}
+
+// detect_language returns the code of the language the text is written in: the script tells
+// most languages apart, the common words the ones sharing the Latin script; None when the text
+// is too short or too mixed to tell
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut scripts: Vec<(&'static str, usize)> = vec![];
+    let mut latin = 0;
+    let mut kana = false;
+    let mut ukrainian = false;
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        let script = match c {
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => {
+                latin += 1;
+                continue;
+            }
+            '\u{3040}'..='\u{30FF}' => {
+                kana = true;
+                "ja"
+            }
+            '\u{4E00}'..='\u{9FFF}' => "zh",
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' => "ko",
+            '\u{0400}'..='\u{04FF}' => {
+                ukrainian |= matches!(c, 'і' | 'ї' | 'є' | 'ґ' | 'І' | 'Ї' | 'Є' | 'Ґ');
+                "ru"
+            }
+            '\u{0600}'..='\u{06FF}' => "ar",
+            '\u{0590}'..='\u{05FF}' => "he",
+            '\u{0370}'..='\u{03FF}' => "el",
+            '\u{0900}'..='\u{097F}' => "hi",
+            '\u{0E00}'..='\u{0E7F}' => "th",
+            _ => continue,
+        };
+        match scripts.iter_mut().find(|(known, _)| *known == script) {
+            Some((_, count)) => *count += 1,
+            None => scripts.push((script, 1)),
+        }
+    }
+
+    let (script, count) = scripts
+        .iter()
+        .copied()
+        .max_by_key(|(_, count)| *count)
+        .unwrap_or(("", 0));
+    if count > latin {
+        // Japanese mixes kanji with kana, Chinese never uses kana
+        return Some(match script {
+            "zh" | "ja" if kana => "ja",
+            "ru" if ukrainian => "uk",
+            script => script,
+        });
+    }
+
+    detect_latin(text)
+}
+
+// detect_latin counts the common words of each language in the text, the language using the
+// most of them wins unless another one uses as many
+fn detect_latin(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| {
+            let score = words
+                .iter()
+                .filter(|word| stopwords.contains(&word.as_str()))
+                .count();
+            (*code, score)
+        })
+        .collect();
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    match scores.as_slice() {
+        [(code, best), (_, second), ..] if *best >= MIN_STOPWORDS && best > second => Some(code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        let cases = [
+            ("How do I reset my password?", Some("en")),
+            (
+                "¿Cómo puedo cambiar la contraseña de mi cuenta?",
+                Some("es"),
+            ),
+            (
+                "Comment est-ce que je peux changer mon mot de passe ?",
+                Some("fr"),
+            ),
+            (
+                "Wie kann ich mein Passwort ändern? Bitte hilf mir.",
+                Some("de"),
+            ),
+            (
+                "Ciao, come posso cambiare la password del mio account?",
+                Some("it"),
+            ),
+            (
+                "Olá, como eu posso mudar a senha da minha conta?",
+                Some("pt"),
+            ),
+            (
+                "Hoe kan ik mijn wachtwoord veranderen voor dit account?",
+                Some("nl"),
+            ),
+            ("Как изменить пароль?", Some("ru")),
+            ("Як змінити пароль від мого облікового запису?", Some("uk")),
+            ("パスワードを変更するにはどうすればいいですか", Some("ja")),
+            ("我怎样才能更改密码", Some("zh")),
+            ("비밀번호를 어떻게 변경하나요", Some("ko")),
+            ("كيف يمكنني تغيير كلمة المرور", Some("ar")),
+            ("Hello!", None),
+            ("42", None),
+            ("", None),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(detect_language(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_response_instruction() {
+        assert_eq!(language_name("fr"), Some("French"));
+        assert_eq!(language_name("xx"), None);
+        assert_eq!(
+            response_instruction("fr"),
+            "Always answer in French, whatever the language of the messages."
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::embedding::{VectorKind, VectorRecord};
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::vector_store::VectorStore;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error(transparent)]
+    Gateway(#[from] GatewayError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+// MessageIndexer embeds the user and assistant messages of chats so past conversations can be
+// searched by meaning; a message is stored under its own id, indexing it again replaces it
+pub struct MessageIndexer {
+    embeddings: Arc<dyn EmbeddingsGateway>,
+    store: Arc<dyn VectorStore>,
+}
+
+impl MessageIndexer {
+    pub fn new(embeddings: Arc<dyn EmbeddingsGateway>, store: Arc<dyn VectorStore>) -> Self {
+        Self { embeddings, store }
+    }
+
+    // spawn indexes the last exchange of the chat in a background task so the reply is not
+    // delayed, a failure is only logged and the exchange stays out of the search results
+    pub fn spawn(self: &Arc<Self>, chat: &Chat) {
+        let messages: Vec<Message> = chat.last_exchange().into_iter().cloned().collect();
+        if messages.is_empty() {
+            return;
+        }
+
+        let indexer = self.clone();
+        let chat = chat.clone();
+        tokio::spawn(async move {
+            if let Err(err) = indexer.index(&chat, &messages).await {
+                tracing::warn!(chat_id = %chat.id, error = %err, "could not index the messages");
+            }
+        });
+    }
+
+    // index embeds the messages and stores them with the chat as their source
+    pub async fn index(&self, chat: &Chat, messages: &[Message]) -> Result<(), IndexError> {
+        let contents: Vec<String> = messages
+            .iter()
+            .map(|message| message.content.clone())
+            .collect();
+        let vectors = self.embeddings.embed(&contents).await?;
+
+        let records: Vec<VectorRecord> = messages
+            .iter()
+            .zip(vectors)
+            .map(|(message, embedding)| VectorRecord {
+                id: message.id,
+                tenant_id: chat.tenant_id,
+                user_id: chat.user_id,
+                kind: VectorKind::Message,
+                source_id: chat.id,
+                content: message.content.clone(),
+                model: self.embeddings.model().to_string(),
+                embedding,
+                metadata: serde_json::json!({ "role": message.role }),
+                created_at: message.created_at,
+            })
+            .collect();
+        self.store.upsert(&records).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::tool::ToolCall;
+    use crate::internal::domain::repository::vector_store::VectorQuery;
+    use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+
+    struct UnitEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingsGateway for UnitEmbeddings {
+        fn model(&self) -> &str {
+            "unit"
+        }
+
+        async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+            Ok(inputs.iter().map(|_| vec![1.0]).collect())
+        }
+    }
+
+    fn message(role: Role, content: &str) -> Message {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        Message::new(Uuid::new_v4(), role, content, 0, model, chrono::Utc::now())
+    }
+
+    #[tokio::test]
+    async fn test_index_last_exchange() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let tool_call = message(Role::Assistant, "").with_tool_calls(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "get_time".to_string(),
+            arguments: "{}".to_string(),
+        }]);
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi!"),
+                message(Role::User, "What time is it?"),
+                tool_call,
+                message(Role::Tool, "12:00").with_tool_call_id("call_1"),
+                message(Role::Assistant, "It is noon."),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        );
+
+        let exchange = chat.last_exchange();
+        assert_eq!(
+            exchange
+                .iter()
+                .map(|message| message.content.as_str())
+                .collect::<Vec<_>>(),
+            vec!["What time is it?", "It is noon."]
+        );
+
+        let store = Arc::new(InMemoryVectorStore::new());
+        let indexer = MessageIndexer::new(Arc::new(UnitEmbeddings), store.clone());
+        let messages: Vec<Message> = exchange.into_iter().cloned().collect();
+        indexer.index(&chat, &messages).await.unwrap();
+        indexer.index(&chat, &messages).await.unwrap();
+
+        let found = store
+            .search(&VectorQuery {
+                tenant_id: chat.tenant_id,
+                user_id: chat.user_id,
+                model: "unit".to_string(),
+                embedding: vec![1.0],
+                kinds: vec![VectorKind::Message],
+                limit: 10,
+                min_score: 0.0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|found| found.source_id == chat.id));
+    }
+}
@@ -1 +1,26 @@
+pub mod cache_bypass;
+pub mod chunker;
+pub mod deadline;
 pub mod entity;
+pub mod error;
+pub mod gateway;
+pub mod history_codec;
+pub mod language;
+pub mod memory_extractor;
+pub mod message_indexer;
+pub mod moderator;
+pub mod output_filter;
+pub mod prompt_guard;
+pub mod quota;
+pub mod rate_limiter;
+pub mod redactor;
+pub mod repository;
+pub mod request_id;
+pub mod stream_buffer;
+pub mod summarizer;
+pub mod tenant_registry;
+pub mod text_search;
+pub mod title_generator;
+pub mod token_counter;
+pub mod tool_registry;
+pub mod usage_tracker;
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::moderation::ModerationFlag;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::moderation::ModerationGateway;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::moderation::ModerationRepository;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModerationError {
+    #[error(transparent)]
+    Flagged(#[from] ChatError),
+    #[error(transparent)]
+    Gateway(#[from] GatewayError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+// Moderator screens user messages before they are sent to the model and records the flagged ones
+pub struct Moderator {
+    gateway: Arc<dyn ModerationGateway>,
+    repository: Arc<dyn ModerationRepository>,
+}
+
+impl Moderator {
+    pub fn new(
+        gateway: Arc<dyn ModerationGateway>,
+        repository: Arc<dyn ModerationRepository>,
+    ) -> Self {
+        Self {
+            gateway,
+            repository,
+        }
+    }
+
+    // check fails with ChatError::ContentFlagged once the flag is stored,
+    // a moderation provider failure rejects the message as well
+    pub async fn check(
+        &self,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        content: &str,
+    ) -> Result<(), ModerationError> {
+        let result = self.gateway.moderate(content).await?;
+        if !result.flagged {
+            return Ok(());
+        }
+
+        let flag = ModerationFlag {
+            id: Uuid::new_v4(),
+            user_id,
+            chat_id,
+            content: content.to_string(),
+            categories: result.categories,
+            created_at: chrono::Utc::now(),
+        };
+        self.repository.record_flag(&flag).await?;
+        tracing::warn!(
+            user_id = %user_id,
+            categories = ?flag.categories,
+            "user message flagged by moderation"
+        );
+
+        Err(ChatError::ContentFlagged(flag.categories).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+
+    use crate::internal::domain::gateway::moderation::ModerationResult;
+    use crate::internal::infra::repository::memory::moderation::InMemoryModerationRepository;
+
+    // KeywordGateway flags any content containing its keyword
+    struct KeywordGateway(&'static str);
+
+    #[async_trait]
+    impl ModerationGateway for KeywordGateway {
+        async fn moderate(&self, content: &str) -> Result<ModerationResult, GatewayError> {
+            if !content.contains(self.0) {
+                return Ok(ModerationResult::default());
+            }
+
+            Ok(ModerationResult {
+                flagged: true,
+                categories: vec!["violence".to_string()],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let repository = Arc::new(InMemoryModerationRepository::new());
+        let moderator = Moderator::new(Arc::new(KeywordGateway("attack")), repository.clone());
+        let user_id = Uuid::new_v4();
+        let chat_id = Uuid::new_v4();
+
+        assert!(moderator
+            .check(user_id, Some(chat_id), "Hello!")
+            .await
+            .is_ok());
+
+        let err = moderator
+            .check(user_id, Some(chat_id), "How do I attack my neighbour?")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ModerationError::Flagged(ChatError::ContentFlagged(categories))
+                if categories == vec!["violence"]
+        ));
+
+        let flags = repository.list_flags_by_user(user_id).await.unwrap();
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].chat_id, Some(chat_id));
+        assert_eq!(flags[0].content, "How do I attack my neighbour?");
+    }
+}
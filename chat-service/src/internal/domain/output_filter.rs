@@ -0,0 +1,488 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::error::ConfigError;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+
+// DEFAULT_REFUSAL replaces the responses about a banned topic when the config has no refusal
+pub const DEFAULT_REFUSAL: &str = "I'm sorry, but I can't help with that topic.";
+// DEFAULT_REPLACEMENT replaces the matches of a rule without a replacement
+pub const DEFAULT_REPLACEMENT: &str = "[removed]";
+
+// PROFANITY matches the words masked by mask_profanity along with their inflections
+const PROFANITY: &str = r"(?i)\b(fuck\w*|motherfuck\w*|shit\w*|bullshit\w*|bitch\w*|asshole\w*|bastard\w*|cunt\w*|dickhead\w*|wanker\w*)";
+
+// OutputRule replaces the parts of a response matching its pattern, a regular expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputRule {
+    pub name: String,
+    pub pattern: String,
+    // replacement may refer to the groups of the pattern, e.g. $1
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+// OutputFilterConfig is what the filter does to the responses of a tenant: a response about a
+// banned topic is replaced by the refusal, then the rules rewrite it, profanity is masked and
+// it is cut to max_length characters, zero keeps any length
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputFilterConfig {
+    pub rules: Vec<OutputRule>,
+    pub banned_topics: Vec<String>,
+    pub refusal: Option<String>,
+    pub mask_profanity: bool,
+    pub max_length: usize,
+}
+
+impl OutputFilterConfig {
+    // is_empty tells whether the config leaves every response as is
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+            && self.banned_topics.is_empty()
+            && !self.mask_profanity
+            && self.max_length == 0
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for rule in &self.rules {
+            if rule.name.trim().is_empty() {
+                return Err(ConfigError::InvalidOutputFilter(
+                    "a rule has an empty name".to_string(),
+                ));
+            }
+            Regex::new(&rule.pattern).map_err(|e| {
+                ConfigError::InvalidOutputFilter(format!("rule {}: {}", rule.name, e))
+            })?;
+        }
+
+        if self
+            .banned_topics
+            .iter()
+            .any(|topic| topic.trim().is_empty())
+        {
+            return Err(ConfigError::InvalidOutputFilter(
+                "a banned topic is empty".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn refusal(&self) -> &str {
+        self.refusal.as_deref().unwrap_or(DEFAULT_REFUSAL)
+    }
+}
+
+// TopicClassifier tells which of the topics a response is about, a classifier asking a model
+// bills the tokens to the tenant and user the response is for
+#[async_trait]
+pub trait TopicClassifier: Send + Sync {
+    async fn classify(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+        topics: &[String],
+    ) -> Result<Vec<String>, GatewayError>;
+}
+
+// OutputFilterStats counts the responses the filter changed and how often each check triggered:
+// rule:<name>, banned_topic:<topic>, profanity and max_length
+#[derive(Debug, Default)]
+pub struct OutputFilterStats {
+    filtered: AtomicU64,
+    triggered: Mutex<BTreeMap<String, u64>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OutputFilterStatsSnapshot {
+    pub filtered: u64,
+    pub triggered: BTreeMap<String, u64>,
+}
+
+impl OutputFilterStats {
+    pub fn snapshot(&self) -> OutputFilterStatsSnapshot {
+        OutputFilterStatsSnapshot {
+            filtered: self.filtered.load(Ordering::Relaxed),
+            triggered: self
+                .triggered
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        }
+    }
+
+    fn record(&self, triggered: &[String]) {
+        if triggered.is_empty() {
+            return;
+        }
+
+        self.filtered.fetch_add(1, Ordering::Relaxed);
+        let mut counts = self.triggered.lock().unwrap_or_else(|e| e.into_inner());
+        for check in triggered {
+            *counts.entry(check.clone()).or_default() += 1;
+        }
+    }
+}
+
+// OutputFilter post-processes the assistant responses before they are saved or returned, with
+// the config of the response's tenant or the default one when the tenant has none
+pub struct OutputFilter {
+    config: OutputFilterConfig,
+    tenants: Option<Arc<TenantRegistry>>,
+    classifier: Option<Arc<dyn TopicClassifier>>,
+    stats: Arc<OutputFilterStats>,
+    profanity: Regex,
+    // patterns caches the compiled rules, tenant configs are read again for every response
+    patterns: Mutex<HashMap<String, Regex>>,
+}
+
+impl OutputFilter {
+    pub fn new(config: OutputFilterConfig) -> Self {
+        Self {
+            config,
+            tenants: None,
+            classifier: None,
+            stats: Arc::new(OutputFilterStats::default()),
+            profanity: Regex::new(PROFANITY).expect("the profanity pattern is valid"),
+            patterns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // with_tenants lets the tenants replace the default config with their own
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    // with_classifier checks the banned topics, without a classifier they are ignored
+    pub fn with_classifier(mut self, classifier: Arc<dyn TopicClassifier>) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    pub fn stats(&self) -> Arc<OutputFilterStats> {
+        self.stats.clone()
+    }
+
+    // applies_to tells whether the responses of the tenant may be changed, streamed responses
+    // are only sent once filtered when they may
+    pub fn applies_to(&self, tenant_id: Uuid) -> bool {
+        !self.config_for(tenant_id).is_empty()
+    }
+
+    // apply filters the content and the candidates of the response to the user
+    pub async fn apply(&self, tenant_id: Uuid, user_id: Uuid, mut message: Message) -> Message {
+        let config = self.config_for(tenant_id);
+        if config.is_empty() {
+            return message;
+        }
+
+        let mut triggered = vec![];
+        message.content = self
+            .filter(
+                tenant_id,
+                user_id,
+                &config,
+                &message.content,
+                &mut triggered,
+            )
+            .await;
+        for candidate in &mut message.candidates {
+            *candidate = self
+                .filter(tenant_id, user_id, &config, candidate, &mut triggered)
+                .await;
+        }
+        if !triggered.is_empty() {
+            tracing::info!(
+                %tenant_id,
+                message_id = %message.id,
+                ?triggered,
+                "assistant response filtered"
+            );
+        }
+        self.stats.record(&triggered);
+
+        message
+    }
+
+    async fn filter(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        config: &OutputFilterConfig,
+        content: &str,
+        triggered: &mut Vec<String>,
+    ) -> String {
+        let banned = self
+            .banned_topics(tenant_id, user_id, config, content)
+            .await;
+        if !banned.is_empty() {
+            triggered.extend(banned.iter().map(|topic| format!("banned_topic:{}", topic)));
+            return config.refusal().to_string();
+        }
+
+        let mut content = content.to_string();
+        for rule in &config.rules {
+            let Some(pattern) = self.pattern(&rule.pattern) else {
+                continue;
+            };
+            if pattern.is_match(&content) {
+                let replacement = rule.replacement.as_deref().unwrap_or(DEFAULT_REPLACEMENT);
+                content = pattern.replace_all(&content, replacement).into_owned();
+                triggered.push(format!("rule:{}", rule.name));
+            }
+        }
+
+        if config.mask_profanity && self.profanity.is_match(&content) {
+            content = self
+                .profanity
+                .replace_all(&content, |captures: &regex::Captures| mask(&captures[0]))
+                .into_owned();
+            triggered.push("profanity".to_string());
+        }
+
+        if config.max_length > 0 && content.chars().count() > config.max_length {
+            content = content.chars().take(config.max_length).collect();
+            triggered.push("max_length".to_string());
+        }
+
+        content
+    }
+
+    // banned_topics returns the banned topics the content is about; a failed classification
+    // lets the content through, the other checks still apply
+    async fn banned_topics(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        config: &OutputFilterConfig,
+        content: &str,
+    ) -> Vec<String> {
+        let Some(classifier) = &self.classifier else {
+            return vec![];
+        };
+        if config.banned_topics.is_empty() || content.trim().is_empty() {
+            return vec![];
+        }
+
+        match classifier
+            .classify(tenant_id, user_id, content, &config.banned_topics)
+            .await
+        {
+            Ok(topics) => topics,
+            Err(err) => {
+                tracing::warn!(error = %err, "topic classifier failed, banned topics are skipped");
+                vec![]
+            }
+        }
+    }
+
+    fn config_for(&self, tenant_id: Uuid) -> OutputFilterConfig {
+        self.tenants
+            .as_ref()
+            .and_then(|tenants| tenants.output_filter(tenant_id))
+            .unwrap_or_else(|| self.config.clone())
+    }
+
+    // pattern compiles the pattern once, an invalid one is skipped as configs are validated
+    // before they are registered
+    fn pattern(&self, pattern: &str) -> Option<Regex> {
+        let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(regex) = patterns.get(pattern) {
+            return Some(regex.clone());
+        }
+
+        let regex = Regex::new(pattern).ok()?;
+        patterns.insert(pattern.to_string(), regex.clone());
+        Some(regex)
+    }
+}
+
+// mask keeps the first letter of the word and hides the others
+fn mask(word: &str) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(i, c)| if i == 0 { c } else { '*' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+    use crate::internal::testing::builder::test_model;
+
+    struct FakeClassifier {
+        result: Result<Vec<String>, ()>,
+    }
+
+    #[async_trait]
+    impl TopicClassifier for FakeClassifier {
+        async fn classify(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _content: &str,
+            _topics: &[String],
+        ) -> Result<Vec<String>, GatewayError> {
+            self.result.clone().map_err(|_| GatewayError::EmptyResponse)
+        }
+    }
+
+    fn response(content: &str) -> Message {
+        Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            content,
+            0,
+            test_model(),
+            chrono::Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = OutputFilterConfig::default();
+        assert!(config.is_empty());
+        assert!(config.validate().is_ok());
+
+        config.rules.push(OutputRule {
+            name: "ticket".to_string(),
+            pattern: r"TICKET-\d+".to_string(),
+            replacement: None,
+        });
+        assert!(!config.is_empty());
+        assert!(config.validate().is_ok());
+
+        config.rules[0].pattern = "(".to_string();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidOutputFilter(_))
+        ));
+
+        let config = OutputFilterConfig {
+            banned_topics: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidOutputFilter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply() {
+        let filter = OutputFilter::new(OutputFilterConfig {
+            rules: vec![OutputRule {
+                name: "ticket".to_string(),
+                pattern: r"TICKET-(\d+)".to_string(),
+                replacement: Some("ticket #$1".to_string()),
+            }],
+            mask_profanity: true,
+            max_length: 20,
+            ..Default::default()
+        });
+        let tenant_id = Uuid::new_v4();
+        assert!(filter.applies_to(tenant_id));
+
+        let message = filter
+            .apply(
+                tenant_id,
+                Uuid::new_v4(),
+                response("Shit, see TICKET-42 for the details")
+                    .with_candidates(vec!["Nothing to see".to_string()]),
+            )
+            .await;
+        assert_eq!(message.content, "S***, see ticket #42");
+        assert_eq!(message.candidates, vec!["Nothing to see"]);
+
+        let message = filter
+            .apply(tenant_id, Uuid::new_v4(), response("All good"))
+            .await;
+        assert_eq!(message.content, "All good");
+
+        let stats = filter.stats().snapshot();
+        assert_eq!(stats.filtered, 1);
+        assert_eq!(
+            stats.triggered,
+            BTreeMap::from([
+                ("max_length".to_string(), 1),
+                ("profanity".to_string(), 1),
+                ("rule:ticket".to_string(), 1),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_banned_topics() {
+        let config = OutputFilterConfig {
+            banned_topics: vec!["politics".to_string(), "medicine".to_string()],
+            refusal: Some("Let's talk about something else.".to_string()),
+            ..Default::default()
+        };
+        let filter = OutputFilter::new(config.clone()).with_classifier(Arc::new(FakeClassifier {
+            result: Ok(vec!["politics".to_string()]),
+        }));
+        let message = filter
+            .apply(DEFAULT_TENANT_ID, Uuid::new_v4(), response("Vote for them"))
+            .await;
+        assert_eq!(message.content, "Let's talk about something else.");
+        assert_eq!(
+            filter.stats().snapshot().triggered,
+            BTreeMap::from([("banned_topic:politics".to_string(), 1)])
+        );
+
+        // a failed classification lets the response through
+        let filter =
+            OutputFilter::new(config).with_classifier(Arc::new(FakeClassifier { result: Err(()) }));
+        let message = filter
+            .apply(DEFAULT_TENANT_ID, Uuid::new_v4(), response("Vote for them"))
+            .await;
+        assert_eq!(message.content, "Vote for them");
+        assert_eq!(filter.stats().snapshot().filtered, 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_tenant_config() {
+        let acme = Uuid::new_v4();
+        let tenants = Arc::new(
+            TenantRegistry::new()
+                .with_tenant(Tenant::new(
+                    acme,
+                    "Acme",
+                    TenantConfig {
+                        output_filter: Some(OutputFilterConfig {
+                            max_length: 4,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                ))
+                .unwrap(),
+        );
+        let filter = OutputFilter::new(OutputFilterConfig::default()).with_tenants(tenants);
+
+        assert!(filter.applies_to(acme));
+        assert!(!filter.applies_to(DEFAULT_TENANT_ID));
+        let message = filter
+            .apply(acme, Uuid::new_v4(), response("Hello there"))
+            .await;
+        assert_eq!(message.content, "Hell");
+        let message = filter
+            .apply(DEFAULT_TENANT_ID, Uuid::new_v4(), response("Hello there"))
+            .await;
+        assert_eq!(message.content, "Hello there");
+    }
+}
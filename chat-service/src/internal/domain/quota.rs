@@ -0,0 +1,397 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::{DailyUsage, TenantUsage};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+
+// QuotaConfig caps the tokens spent per UTC day and month, a zero cap is disabled; the user caps
+// apply to every user of a tenant on their own, the tenant caps to all of them together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaConfig {
+    pub user_daily_tokens: u64,
+    pub user_monthly_tokens: u64,
+    pub tenant_daily_tokens: u64,
+    pub tenant_monthly_tokens: u64,
+}
+
+impl QuotaConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.user_daily_tokens > 0
+            || self.user_monthly_tokens > 0
+            || self.tenant_daily_tokens > 0
+            || self.tenant_monthly_tokens > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaScope {
+    User,
+    Tenant,
+}
+
+impl fmt::Display for QuotaScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaScope::User => write!(f, "user"),
+            QuotaScope::Tenant => write!(f, "tenant"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPeriod {
+    Daily,
+    Monthly,
+}
+
+impl QuotaPeriod {
+    // start is the first day of the period the day is in
+    pub fn start(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            QuotaPeriod::Daily => day,
+            QuotaPeriod::Monthly => day.with_day(1).unwrap_or(day),
+        }
+    }
+
+    // resets_at is the start of the period that follows the one the day is in
+    pub fn resets_at(&self, day: NaiveDate) -> DateTime<Utc> {
+        let next = match self {
+            QuotaPeriod::Daily => day.succ_opt(),
+            QuotaPeriod::Monthly if day.month() == 12 => {
+                NaiveDate::from_ymd_opt(day.year() + 1, 1, 1)
+            }
+            QuotaPeriod::Monthly => NaiveDate::from_ymd_opt(day.year(), day.month() + 1, 1),
+        };
+
+        next.unwrap_or(NaiveDate::MAX)
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc()
+    }
+}
+
+impl fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaPeriod::Daily => write!(f, "daily"),
+            QuotaPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+// Quota is one enabled cap along with the tokens spent in its current period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quota {
+    pub scope: QuotaScope,
+    pub period: QuotaPeriod,
+    pub limit: u64,
+    pub used: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+impl Quota {
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.used)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.used >= self.limit
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{scope} {period} quota of {limit} tokens exhausted, it resets at {resets_at}")]
+pub struct QuotaExceeded {
+    pub scope: QuotaScope,
+    pub period: QuotaPeriod,
+    pub limit: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+impl QuotaExceeded {
+    // retry_after is how long until the quota resets
+    pub fn retry_after(&self) -> Duration {
+        (self.resets_at - Utc::now()).to_std().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error(transparent)]
+    Exceeded(#[from] QuotaExceeded),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+// QuotaEnforcer holds users and tenants to their token quotas, the tokens spent are read from
+// the usage aggregates so every instance sees the same totals
+pub struct QuotaEnforcer {
+    repository: Arc<dyn UsageRepository>,
+    config: QuotaConfig,
+    tenants: Option<Arc<TenantRegistry>>,
+}
+
+impl QuotaEnforcer {
+    pub fn new(repository: Arc<dyn UsageRepository>, config: QuotaConfig) -> Self {
+        Self {
+            repository,
+            config,
+            tenants: None,
+        }
+    }
+
+    // with_tenants lets tenants replace the default quotas with their own
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    pub fn config_for(&self, tenant_id: Uuid) -> QuotaConfig {
+        self.tenants
+            .as_ref()
+            .and_then(|tenants| tenants.find(tenant_id))
+            .and_then(|tenant| tenant.config.quota)
+            .unwrap_or(self.config)
+    }
+
+    // check fails on the first exhausted quota of the user or its tenant; the size of a reply is
+    // only known once it is done, so a request is let through while any tokens are left
+    pub async fn check(&self, tenant_id: Uuid, user_id: Uuid) -> Result<(), QuotaError> {
+        let quotas = self.quotas(tenant_id, user_id).await?;
+        match quotas.into_iter().find(Quota::is_exhausted) {
+            Some(quota) => Err(QuotaError::Exceeded(QuotaExceeded {
+                scope: quota.scope,
+                period: quota.period,
+                limit: quota.limit,
+                resets_at: quota.resets_at,
+            })),
+            None => Ok(()),
+        }
+    }
+
+    // quotas returns the enabled quotas of the user and its tenant, user quotas first
+    pub async fn quotas(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Quota>, RepositoryError> {
+        self.quotas_on(tenant_id, user_id, Utc::now().date_naive())
+            .await
+    }
+
+    async fn quotas_on(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        today: NaiveDate,
+    ) -> Result<Vec<Quota>, RepositoryError> {
+        let config = self.config_for(tenant_id);
+        let month = QuotaPeriod::Monthly.start(today);
+        let mut quotas = vec![];
+        let mut push = |scope, period: QuotaPeriod, limit: u64, used: u64| {
+            if limit > 0 {
+                quotas.push(Quota {
+                    scope,
+                    period,
+                    limit,
+                    used,
+                    resets_at: period.resets_at(today),
+                });
+            }
+        };
+
+        if config.user_daily_tokens > 0 || config.user_monthly_tokens > 0 {
+            let days = self
+                .repository
+                .list_daily_usage(tenant_id, user_id, month, today)
+                .await?;
+            let used_today = days
+                .iter()
+                .filter(|usage| usage.date == today)
+                .map(DailyUsage::total_tokens)
+                .sum();
+            let used_this_month = days.iter().map(DailyUsage::total_tokens).sum();
+
+            push(
+                QuotaScope::User,
+                QuotaPeriod::Daily,
+                config.user_daily_tokens,
+                used_today,
+            );
+            push(
+                QuotaScope::User,
+                QuotaPeriod::Monthly,
+                config.user_monthly_tokens,
+                used_this_month,
+            );
+        }
+
+        if config.tenant_daily_tokens > 0 {
+            let used_today = self.tenant_usage(tenant_id, today, today).await?;
+            push(
+                QuotaScope::Tenant,
+                QuotaPeriod::Daily,
+                config.tenant_daily_tokens,
+                used_today,
+            );
+        }
+        if config.tenant_monthly_tokens > 0 {
+            let used_this_month = self.tenant_usage(tenant_id, month, today).await?;
+            push(
+                QuotaScope::Tenant,
+                QuotaPeriod::Monthly,
+                config.tenant_monthly_tokens,
+                used_this_month,
+            );
+        }
+
+        Ok(quotas)
+    }
+
+    // tenant_usage sums the tokens every user of the tenant spent between from and to inclusive
+    async fn tenant_usage(
+        &self,
+        tenant_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<u64, RepositoryError> {
+        let usage = self
+            .repository
+            .summarize_usage(Some(tenant_id), from, to)
+            .await?;
+
+        Ok(usage.iter().map(TenantUsage::total_tokens).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn record(tenant_id: Uuid, user_id: Uuid, tokens: usize) -> UsageRecord {
+        UsageRecord {
+            tenant_id,
+            user_id,
+            chat_id: Uuid::new_v4(),
+            model: "gpt-4o".to_string(),
+            prompt_tokens: tokens,
+            completion_tokens: 0,
+            cost: 0.0,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_resets_at() {
+        let day = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+
+        assert_eq!(
+            QuotaPeriod::Daily.resets_at(day).date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        assert_eq!(
+            QuotaPeriod::Monthly.resets_at(day).date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+        assert_eq!(
+            QuotaPeriod::Monthly.start(day),
+            NaiveDate::from_ymd_opt(2024, 12, 1).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        let enforcer = QuotaEnforcer::new(
+            repository.clone(),
+            QuotaConfig {
+                user_daily_tokens: 100,
+                tenant_monthly_tokens: 150,
+                ..Default::default()
+            },
+        );
+
+        assert!(enforcer.check(DEFAULT_TENANT_ID, user_id).await.is_ok());
+
+        repository
+            .record_usage(&record(DEFAULT_TENANT_ID, user_id, 60))
+            .await
+            .unwrap();
+        let quotas = enforcer.quotas(DEFAULT_TENANT_ID, user_id).await.unwrap();
+        assert_eq!(quotas.len(), 2);
+        assert_eq!(quotas[0].scope, QuotaScope::User);
+        assert_eq!(quotas[0].remaining(), 40);
+        assert_eq!(quotas[1].scope, QuotaScope::Tenant);
+        assert_eq!(quotas[1].remaining(), 90);
+        assert!(enforcer.check(DEFAULT_TENANT_ID, user_id).await.is_ok());
+
+        // another user of the tenant spends what is left of its monthly quota
+        repository
+            .record_usage(&record(DEFAULT_TENANT_ID, Uuid::new_v4(), 90))
+            .await
+            .unwrap();
+        assert!(matches!(
+            enforcer.check(DEFAULT_TENANT_ID, user_id).await,
+            Err(QuotaError::Exceeded(QuotaExceeded {
+                scope: QuotaScope::Tenant,
+                period: QuotaPeriod::Monthly,
+                limit: 150,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_tenant_config() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let acme = Tenant::new(
+            Uuid::new_v4(),
+            "Acme",
+            TenantConfig {
+                quota: Some(QuotaConfig {
+                    user_monthly_tokens: 50,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+        let user_id = Uuid::new_v4();
+        let enforcer = QuotaEnforcer::new(repository.clone(), QuotaConfig::default()).with_tenants(
+            Arc::new(TenantRegistry::new().with_tenant(acme.clone()).unwrap()),
+        );
+        repository
+            .record_usage(&record(acme.id, user_id, 50))
+            .await
+            .unwrap();
+        repository
+            .record_usage(&record(DEFAULT_TENANT_ID, user_id, 50))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            enforcer.check(acme.id, user_id).await,
+            Err(QuotaError::Exceeded(QuotaExceeded {
+                scope: QuotaScope::User,
+                period: QuotaPeriod::Monthly,
+                ..
+            }))
+        ));
+        assert!(enforcer.check(DEFAULT_TENANT_ID, user_id).await.is_ok());
+        assert!(enforcer
+            .quotas(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("rate limit exceeded, retry after {}s", retry_after.as_secs())]
+pub struct RateLimitExceeded {
+    pub retry_after: Duration,
+}
+
+// RateLimitConfig sets the per-user budgets, a zero limit disables that budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: u32,
+    pub tokens_per_minute: u32,
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_minute > 0 || self.tokens_per_minute > 0
+    }
+}
+
+// TokenBucket holds up to capacity units and refills capacity units per minute
+#[derive(Debug, Clone, PartialEq)]
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            available: capacity as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let refilled = self.available + elapsed * self.capacity / WINDOW.as_secs_f64();
+
+        self.available = refilled.min(self.capacity);
+        self.updated_at = now;
+    }
+
+    // wait_for returns how long until amount units are available
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount - self.available;
+        if missing <= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(missing * WINDOW.as_secs_f64() / self.capacity)
+    }
+}
+
+// UserBuckets are sized for the config they were created with, they are replaced when the
+// budgets of the tenant change
+#[derive(Debug, Clone)]
+struct UserBuckets {
+    config: RateLimitConfig,
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+// RateLimiter enforces per-user request and token budgets with token buckets, the budgets
+// of a user are the ones of its tenant
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    tenants: RwLock<HashMap<Uuid, RateLimitConfig>>,
+    buckets: Mutex<HashMap<Uuid, UserBuckets>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            tenants: RwLock::new(HashMap::new()),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // with_tenant_config gives the users of the tenant other budgets than the default ones
+    pub fn with_tenant_config(self, tenant_id: Uuid, config: RateLimitConfig) -> Self {
+        self.set_tenant_config(tenant_id, Some(config));
+        self
+    }
+
+    // set_tenant_config changes the budgets of the tenant's users at runtime, None brings back
+    // the default ones; users start over with full buckets of the new size
+    pub fn set_tenant_config(&self, tenant_id: Uuid, config: Option<RateLimitConfig>) {
+        let mut tenants = self.tenants.write().unwrap_or_else(|e| e.into_inner());
+        match config {
+            Some(config) => tenants.insert(tenant_id, config),
+            None => tenants.remove(&tenant_id),
+        };
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config
+    }
+
+    pub fn config_for(&self, tenant_id: Uuid) -> RateLimitConfig {
+        self.tenants
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tenant_id)
+            .copied()
+            .unwrap_or(self.config)
+    }
+
+    // acquire takes one request from the user's budget, it fails while the user is out of requests
+    // or still paying back tokens consumed by earlier completions
+    pub fn acquire(&self, tenant_id: Uuid, user_id: Uuid) -> Result<(), RateLimitExceeded> {
+        self.acquire_at(tenant_id, user_id, Instant::now())
+    }
+
+    // consume_tokens charges the tokens of a finished completion, the token budget may go
+    // negative as the size of a reply is only known once it is done
+    pub fn consume_tokens(&self, tenant_id: Uuid, user_id: Uuid, tokens: usize) {
+        self.consume_tokens_at(tenant_id, user_id, tokens, Instant::now())
+    }
+
+    // release gives back the request taken by acquire when it was dropped before its
+    // completion finished, so a client that went away does not cost the user a request
+    pub fn release(&self, tenant_id: Uuid, user_id: Uuid) {
+        self.release_at(tenant_id, user_id, Instant::now())
+    }
+
+    fn acquire_at(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        now: Instant,
+    ) -> Result<(), RateLimitExceeded> {
+        let config = self.config_for(tenant_id);
+        if !config.is_enabled() {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let user = buckets
+            .entry(user_id)
+            .or_insert_with(|| new_buckets(&config, now));
+        if user.config != config {
+            *user = new_buckets(&config, now);
+        }
+
+        let mut retry_after = Duration::ZERO;
+
+        if let Some(tokens) = user.tokens.as_mut() {
+            tokens.refill(now);
+            // any positive balance lets the request through
+            retry_after = retry_after.max(tokens.wait_for(f64::MIN_POSITIVE));
+        }
+
+        if let Some(requests) = user.requests.as_mut() {
+            requests.refill(now);
+            retry_after = retry_after.max(requests.wait_for(1.0));
+        }
+
+        if !retry_after.is_zero() {
+            return Err(RateLimitExceeded {
+                retry_after: round_up(retry_after),
+            });
+        }
+
+        if let Some(requests) = user.requests.as_mut() {
+            requests.available -= 1.0;
+        }
+
+        Ok(())
+    }
+
+    fn consume_tokens_at(&self, tenant_id: Uuid, user_id: Uuid, tokens: usize, now: Instant) {
+        let config = self.config_for(tenant_id);
+        if config.tokens_per_minute == 0 {
+            return;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let user = buckets
+            .entry(user_id)
+            .or_insert_with(|| new_buckets(&config, now));
+        if user.config != config {
+            *user = new_buckets(&config, now);
+        }
+
+        if let Some(bucket) = user.tokens.as_mut() {
+            bucket.refill(now);
+            bucket.available -= tokens as f64;
+        }
+    }
+
+    fn release_at(&self, tenant_id: Uuid, user_id: Uuid, now: Instant) {
+        let config = self.config_for(tenant_id);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        // buckets created for other budgets start over full, there is nothing to give back
+        let Some(user) = buckets
+            .get_mut(&user_id)
+            .filter(|user| user.config == config)
+        else {
+            return;
+        };
+
+        if let Some(bucket) = user.requests.as_mut() {
+            bucket.refill(now);
+            bucket.available = (bucket.available + 1.0).min(bucket.capacity);
+        }
+    }
+}
+
+fn new_buckets(config: &RateLimitConfig, now: Instant) -> UserBuckets {
+    let bucket = |limit: u32| (limit > 0).then(|| TokenBucket::new(limit, now));
+
+    UserBuckets {
+        config: *config,
+        requests: bucket(config.requests_per_minute),
+        tokens: bucket(config.tokens_per_minute),
+    }
+}
+
+// round_up reports whole seconds as that is what Retry-After carries
+fn round_up(duration: Duration) -> Duration {
+    let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    Duration::from_secs(secs.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+    #[test]
+    fn test_requests_per_minute() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 2,
+            tokens_per_minute: 0,
+        });
+        let user_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+        assert_eq!(
+            limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now),
+            Err(RateLimitExceeded {
+                retry_after: Duration::from_secs(30)
+            })
+        );
+
+        // other users have their own budget
+        assert!(limiter
+            .acquire_at(DEFAULT_TENANT_ID, Uuid::new_v4(), now)
+            .is_ok());
+
+        assert!(limiter
+            .acquire_at(DEFAULT_TENANT_ID, user_id, now + Duration::from_secs(30))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tokens_per_minute() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 0,
+            tokens_per_minute: 1000,
+        });
+        let user_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+        limiter.consume_tokens_at(DEFAULT_TENANT_ID, user_id, 1500, now);
+
+        assert_eq!(
+            limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now),
+            Err(RateLimitExceeded {
+                retry_after: Duration::from_secs(30)
+            })
+        );
+        assert!(limiter
+            .acquire_at(DEFAULT_TENANT_ID, user_id, now + Duration::from_secs(31))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_release() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 1,
+            tokens_per_minute: 0,
+        });
+        let user_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_err());
+
+        limiter.release_at(DEFAULT_TENANT_ID, user_id, now);
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+
+        // the budget never grows past its capacity
+        limiter.release_at(DEFAULT_TENANT_ID, user_id, now);
+        limiter.release_at(DEFAULT_TENANT_ID, user_id, now);
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_ok());
+        assert!(limiter.acquire_at(DEFAULT_TENANT_ID, user_id, now).is_err());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let user_id = Uuid::new_v4();
+
+        assert!(!limiter.config().is_enabled());
+        for _ in 0..1000 {
+            assert!(limiter.acquire(DEFAULT_TENANT_ID, user_id).is_ok());
+        }
+        limiter.consume_tokens(DEFAULT_TENANT_ID, user_id, 1_000_000);
+        assert!(limiter.acquire(DEFAULT_TENANT_ID, user_id).is_ok());
+    }
+
+    #[test]
+    fn test_tenant_config() {
+        let acme = Uuid::new_v4();
+        let limiter = RateLimiter::new(RateLimitConfig::default()).with_tenant_config(
+            acme,
+            RateLimitConfig {
+                requests_per_minute: 1,
+                tokens_per_minute: 0,
+            },
+        );
+        let now = Instant::now();
+
+        assert!(limiter.config_for(acme).is_enabled());
+        assert!(!limiter.config_for(DEFAULT_TENANT_ID).is_enabled());
+
+        let user_id = Uuid::new_v4();
+        assert!(limiter.acquire_at(acme, user_id, now).is_ok());
+        assert!(limiter.acquire_at(acme, user_id, now).is_err());
+
+        let other_user = Uuid::new_v4();
+        for _ in 0..10 {
+            assert!(limiter
+                .acquire_at(DEFAULT_TENANT_ID, other_user, now)
+                .is_ok());
+        }
+
+        limiter.set_tenant_config(
+            acme,
+            Some(RateLimitConfig {
+                requests_per_minute: 3,
+                tokens_per_minute: 0,
+            }),
+        );
+        for _ in 0..3 {
+            assert!(limiter.acquire_at(acme, user_id, now).is_ok());
+        }
+        assert!(limiter.acquire_at(acme, user_id, now).is_err());
+
+        limiter.set_tenant_config(acme, None);
+        assert!(!limiter.config_for(acme).is_enabled());
+        assert!(limiter.acquire_at(acme, user_id, now).is_ok());
+    }
+}
@@ -0,0 +1,275 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::redaction::RedactionRepository;
+
+// PiiDetector finds one kind of personal data in a text
+pub trait PiiDetector: Send + Sync {
+    // kind names what is found, e.g. email; masked spans read as the kind in brackets
+    fn kind(&self) -> &str;
+
+    // find returns the byte ranges of the matches in the text
+    fn find(&self, text: &str) -> Vec<Range<usize>>;
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("cipher failed: {0}")]
+pub struct CipherError(pub String);
+
+// Cipher encrypts the originals of masked messages, only the service can read them back
+pub trait Cipher: Send + Sync {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherError>;
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RedactionError {
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+// Redactor masks personal data in user messages before they are sent to a provider, the
+// originals are stored encrypted
+pub struct Redactor {
+    detectors: Vec<Arc<dyn PiiDetector>>,
+    cipher: Arc<dyn Cipher>,
+    repository: Arc<dyn RedactionRepository>,
+}
+
+impl Redactor {
+    pub fn new(
+        detectors: Vec<Arc<dyn PiiDetector>>,
+        cipher: Arc<dyn Cipher>,
+        repository: Arc<dyn RedactionRepository>,
+    ) -> Self {
+        Self {
+            detectors,
+            cipher,
+            repository,
+        }
+    }
+
+    // mask replaces every match with its kind in brackets, e.g. [EMAIL], and returns the kinds
+    // masked in order; of overlapping matches the first and then longest one is masked
+    pub fn mask(&self, text: &str) -> (String, Vec<String>) {
+        let mut matches: Vec<(Range<usize>, &str)> = self
+            .detectors
+            .iter()
+            .flat_map(|detector| {
+                detector
+                    .find(text)
+                    .into_iter()
+                    .map(|range| (range, detector.kind()))
+            })
+            .filter(|(range, _)| !range.is_empty() && text.get(range.clone()).is_some())
+            .collect();
+        matches.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+        let mut masked = String::with_capacity(text.len());
+        let mut findings = vec![];
+        let mut end = 0;
+        for (range, kind) in matches {
+            if range.start < end {
+                continue;
+            }
+
+            masked.push_str(&text[end..range.start]);
+            masked.push_str(&format!("[{}]", kind.to_uppercase()));
+            findings.push(kind.to_string());
+            end = range.end;
+        }
+        masked.push_str(&text[end..]);
+
+        (masked, findings)
+    }
+
+    // redact returns the message with its personal data masked, the encrypted original goes
+    // with it until record stores it once the chat of the message is saved
+    pub fn redact(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        message: Message,
+    ) -> Result<Message, RedactionError> {
+        let (masked, findings) = self.mask(&message.content);
+        if findings.is_empty() {
+            return Ok(message);
+        }
+
+        let record = RedactionRecord {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id,
+            chat_id,
+            message_id: message.id,
+            findings,
+            ciphertext: self.cipher.encrypt(message.content.as_bytes())?,
+            created_at: chrono::Utc::now(),
+        };
+        tracing::info!(
+            user_id = %user_id,
+            message_id = %message.id,
+            findings = ?record.findings,
+            "personal data masked in user message"
+        );
+
+        let mut message = message.with_content(&masked);
+        message.redaction = Some(record);
+        Ok(message)
+    }
+
+    // record stores the originals the masked messages of the saved chat carry, a message whose
+    // chat was never saved, because its request failed, leaves no record behind
+    pub async fn record(&self, chat: &mut Chat) -> Result<(), RedactionError> {
+        for message in chat.messages.iter_mut().chain(&mut chat.erased_messages) {
+            let Some(record) = &message.redaction else {
+                continue;
+            };
+            let record = RedactionRecord {
+                chat_id: Some(chat.id),
+                ..record.clone()
+            };
+            self.repository.record_redaction(&record).await?;
+            message.redaction = None;
+        }
+
+        Ok(())
+    }
+
+    // original decrypts the content the message of the record had before it was masked
+    pub fn original(&self, record: &RedactionRecord) -> Result<String, RedactionError> {
+        let plaintext = self.cipher.decrypt(&record.ciphertext)?;
+
+        String::from_utf8(plaintext).map_err(|e| CipherError(e.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::repository::memory::redaction::InMemoryRedactionRepository;
+    use crate::internal::testing::builder::ChatBuilder;
+
+    // WordDetector finds every occurrence of its word
+    struct WordDetector(&'static str, &'static str);
+
+    impl PiiDetector for WordDetector {
+        fn kind(&self) -> &str {
+            self.0
+        }
+
+        fn find(&self, text: &str) -> Vec<Range<usize>> {
+            text.match_indices(self.1)
+                .map(|(start, word)| start..start + word.len())
+                .collect()
+        }
+    }
+
+    // ReversingCipher stands in for encryption, it is enough to tell ciphertext from plaintext
+    struct ReversingCipher;
+
+    impl Cipher for ReversingCipher {
+        fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherError> {
+            Ok(plaintext.iter().rev().copied().collect())
+        }
+
+        fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+            self.encrypt(ciphertext)
+        }
+    }
+
+    fn redactor(repository: Arc<InMemoryRedactionRepository>) -> Redactor {
+        Redactor::new(
+            vec![
+                Arc::new(WordDetector("email", "ada@example.com")),
+                Arc::new(WordDetector("name", "ada")),
+                Arc::new(WordDetector("phone", "555-0100")),
+            ],
+            Arc::new(ReversingCipher),
+            repository,
+        )
+    }
+
+    #[test]
+    fn test_mask() {
+        let redactor = redactor(Arc::new(InMemoryRedactionRepository::new()));
+
+        let (masked, findings) =
+            redactor.mask("Mail ada@example.com or call 555-0100, ask for ada");
+        assert_eq!(masked, "Mail [EMAIL] or call [PHONE], ask for [NAME]");
+        assert_eq!(findings, vec!["email", "phone", "name"]);
+
+        let (masked, findings) = redactor.mask("Hello!");
+        assert_eq!(masked, "Hello!");
+        assert!(findings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redact() {
+        let repository = Arc::new(InMemoryRedactionRepository::new());
+        let redactor = redactor(repository.clone());
+        let tenant_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "My number is 555-0100",
+            0,
+            Model::new("gpt-4o".to_string(), 128000),
+            chrono::Utc::now(),
+        );
+
+        let redacted = redactor
+            .redact(tenant_id, user_id, None, message.clone())
+            .unwrap();
+        assert_eq!(redacted.id, message.id);
+        assert_eq!(redacted.content, "My number is [PHONE]");
+        let pending = redacted.redaction.clone().unwrap();
+        assert_eq!(pending.findings, vec!["phone"]);
+        assert_ne!(pending.ciphertext, message.content.as_bytes());
+        assert_eq!(redactor.original(&pending).unwrap(), message.content);
+
+        // the original is only stored once the chat of the message is
+        assert!(repository
+            .find_redaction(tenant_id, message.id)
+            .await
+            .unwrap()
+            .is_none());
+        let mut chat = ChatBuilder::new()
+            .tenant_id(tenant_id)
+            .user_id(user_id)
+            .message(redacted)
+            .build();
+        redactor.record(&mut chat).await.unwrap();
+        let record = repository
+            .find_redaction(tenant_id, message.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.chat_id, Some(chat.id));
+        assert_eq!(redactor.original(&record).unwrap(), message.content);
+        assert!(chat
+            .messages
+            .iter()
+            .all(|message| message.redaction.is_none()));
+
+        let clean = message.with_content("Hello!");
+        let unchanged = redactor
+            .redact(tenant_id, user_id, None, clean.clone())
+            .unwrap();
+        assert_eq!(unchanged, clean);
+    }
+}
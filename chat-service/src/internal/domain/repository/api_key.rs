@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// ApiKeyRepository stores hashed API keys, keys are looked up by their hash
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create_api_key(&self, api_key: &ApiKey) -> Result<(), RepositoryError>;
+
+    async fn find_api_key_by_hash(&self, key_hash: &str)
+        -> Result<Option<ApiKey>, RepositoryError>;
+
+    // revoke_api_keys revokes every active key of the user but the kept one and returns how
+    // many were revoked
+    async fn revoke_api_keys(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        keep: Uuid,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, RepositoryError>;
+}
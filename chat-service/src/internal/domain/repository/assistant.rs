@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// AssistantRepository keeps the assistants of the tenants, names are unique within a tenant
+#[async_trait]
+pub trait AssistantRepository: Send + Sync {
+    async fn create_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError>;
+
+    async fn find_assistant_by_id(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<Option<Assistant>, RepositoryError>;
+
+    // list_assistants returns the assistants of the tenant by name
+    async fn list_assistants(&self, tenant_id: Uuid) -> Result<Vec<Assistant>, RepositoryError>;
+
+    // update_assistant replaces everything but the id, tenant and creation time of the assistant
+    async fn update_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError>;
+
+    // delete_assistant removes the assistant, the chats started with it keep their messages and
+    // config but are offered no tools anymore
+    async fn delete_assistant(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<(), RepositoryError>;
+}
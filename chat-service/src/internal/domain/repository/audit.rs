@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::audit::AuditEntry;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// AuditCursor is the position of the last entry of a page in log order, the next page starts
+// right after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditCursor {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub id: Uuid,
+}
+
+impl AuditCursor {
+    pub fn of(entry: &AuditEntry) -> Self {
+        Self {
+            created_at: entry.created_at,
+            id: entry.id,
+        }
+    }
+}
+
+// AuditQuery selects a page of the audit log oldest first, a filter left empty matches every
+// entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditQuery {
+    pub tenant_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub chat_id: Option<Uuid>,
+    pub request_id: Option<Uuid>,
+    // from and to bound created_at, from is inclusive and to exclusive
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub after: Option<AuditCursor>,
+    pub limit: usize,
+}
+
+impl AuditQuery {
+    // matches tells whether the entry passes the filters and follows the cursor
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let is = |filter: Option<Uuid>, id: Uuid| filter.is_none_or(|filter| filter == id);
+        let from = !matches!(self.from, Some(from) if entry.created_at < from);
+        let to = !matches!(self.to, Some(to) if entry.created_at >= to);
+        let after = self
+            .after
+            .is_none_or(|after| (entry.created_at, entry.id) > (after.created_at, after.id));
+
+        is(self.tenant_id, entry.tenant_id)
+            && is(self.user_id, entry.user_id)
+            && is(self.chat_id, entry.chat_id)
+            && is(self.request_id, entry.request_id)
+            && from
+            && to
+            && after
+    }
+}
+
+// AuditRepository keeps the audit log of provider requests; entries are only ever appended,
+// they outlive the chats and users they mention
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn append(&self, entry: &AuditEntry) -> Result<(), RepositoryError>;
+
+    async fn list_entries(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, RepositoryError>;
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::Batch;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// BatchRepository keeps the batches of prompts so their answers can be polled
+#[async_trait]
+pub trait BatchRepository: Send + Sync {
+    async fn create_batch(&self, batch: &Batch) -> Result<(), RepositoryError>;
+
+    async fn find_batch_by_id(
+        &self,
+        tenant_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<Option<Batch>, RepositoryError>;
+
+    // complete_batch saves the status, items and completion time of the batch
+    async fn complete_batch(&self, batch: &Batch) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatSummary};
+use crate::internal::domain::entity::message::{Message, Role};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RepositoryError {
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("chat {0} was saved by another request since it was loaded")]
+    ConcurrentModification(Uuid),
+}
+
+// PurgedChat is a chat removed by purge_deleted_chats, history_key is the blob of its archived
+// history, still to be removed from the blob store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgedChat {
+    pub id: Uuid,
+    pub history_key: Option<String>,
+}
+
+// ChatCursor is the position of the last chat of a page in last activity order,
+// the next page starts right after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatCursor {
+    pub last_activity_at: chrono::DateTime<chrono::Utc>,
+    pub id: Uuid,
+}
+
+impl ChatCursor {
+    pub fn of(summary: &ChatSummary) -> Self {
+        Self {
+            last_activity_at: summary.last_activity_at,
+            id: summary.id,
+        }
+    }
+}
+
+// MessageQuery selects a page of the chat history in conversation order, the system message
+// and erased messages are never part of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageQuery {
+    // roles keeps the messages of these roles, every role when empty
+    pub roles: Vec<Role>,
+    // from and to bound created_at, from is inclusive and to exclusive
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    // after is the last message of the previous page, nothing follows a message that was erased since
+    pub after: Option<Uuid>,
+    pub limit: usize,
+}
+
+impl MessageQuery {
+    // matches tells whether the message passes the role and time filters
+    pub fn matches(&self, message: &Message) -> bool {
+        let role = self.roles.is_empty() || self.roles.contains(&message.role);
+        let from = !matches!(self.from, Some(from) if message.created_at < from);
+        let to = !matches!(self.to, Some(to) if message.created_at >= to);
+
+        role && from && to
+    }
+
+    // page selects the page of the messages, in conversation order, the query asks for
+    pub fn page(&self, messages: &[Message]) -> Vec<Message> {
+        let start = match self.after {
+            Some(after) => match messages.iter().position(|message| message.id == after) {
+                Some(position) => position + 1,
+                None => return vec![],
+            },
+            None => 0,
+        };
+
+        messages[start..]
+            .iter()
+            .filter(|message| self.matches(message))
+            .take(self.limit)
+            .cloned()
+            .collect()
+    }
+}
+
+// TranscriptMatch is a message of a chat found by a full-text search
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptMatch {
+    pub chat_id: Uuid,
+    pub chat_title: Option<String>,
+    pub message_id: Uuid,
+    pub role: Role,
+    // highlight is the part of the message around the matched words, marked as text_search does
+    pub highlight: String,
+    // rank orders the matches of one search, it is not comparable across backends
+    pub rank: f32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// ChatRepository persists chats together with their messages, every read is scoped by tenant
+// and a chat of another tenant is reported as missing
+#[async_trait]
+pub trait ChatRepository: Send + Sync {
+    async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError>;
+
+    async fn find_chat_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Chat>, RepositoryError>;
+
+    // save_chat keeps the stored title when the chat has none, a title may be set meanwhile,
+    // and leaves a chat stored under another tenant untouched; it bumps the version of the chat
+    // once stored and rejects a chat saved by someone else since it was loaded with
+    // ConcurrentModification
+    async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError>;
+
+    // update_chat_title sets the title without touching the rest of the chat
+    async fn update_chat_title(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        title: &str,
+    ) -> Result<(), RepositoryError>;
+
+    // list_chats_by_user returns the user's chats that are not deleted, most recently updated first
+    async fn list_chats_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Chat>, RepositoryError>;
+
+    // find_chat_summary reads the chat without its messages
+    async fn find_chat_summary(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ChatSummary>, RepositoryError>;
+
+    // list_messages reads the messages of the chat matching the query without loading the chat
+    async fn list_messages(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, RepositoryError>;
+
+    // list_chat_summaries returns up to limit chats of the user after the cursor, most recent
+    // activity first and by descending id for chats active at the same instant, deleted chats
+    // are left out; a normalized tag keeps the chats labelled with it
+    async fn list_chat_summaries(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        tag: Option<&str>,
+        after: Option<ChatCursor>,
+        limit: usize,
+    ) -> Result<Vec<ChatSummary>, RepositoryError>;
+
+    // search_transcripts returns up to limit user and assistant messages of the user's chats
+    // containing every word of the query, best matches first; erased messages and deleted chats
+    // are left out
+    async fn search_transcripts(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<TranscriptMatch>, RepositoryError>;
+
+    // list_inactive_chats returns up to limit chats last active before the instant whose history
+    // is still stored with them, least recently active first; deleted chats are left out, it is
+    // maintenance and spans tenants
+    async fn list_inactive_chats(
+        &self,
+        inactive_before: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError>;
+
+    // archive_chat_history stores the chat once archive_history took its messages out, removing
+    // them from the database; unlike save_chat it leaves the last activity alone, it checks and
+    // bumps the version the same way
+    async fn archive_chat_history(&self, chat: &mut Chat) -> Result<(), RepositoryError>;
+
+    // purge_deleted_chats removes chats deleted before the given instant together with their
+    // messages and returns the removed chats, it is maintenance and spans tenants
+    async fn purge_deleted_chats(
+        &self,
+        deleted_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PurgedChat>, RepositoryError>;
+}
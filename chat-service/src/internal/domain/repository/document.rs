@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::document::Document;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// DocumentRepository persists the documents users uploaded, their chunks are in the
+// VectorStore
+#[async_trait]
+pub trait DocumentRepository: Send + Sync {
+    async fn create_document(&self, document: &Document) -> Result<(), RepositoryError>;
+
+    async fn find_document_by_id(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<Option<Document>, RepositoryError>;
+
+    // list_documents_by_user returns the documents of the user, newest first
+    async fn list_documents_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Document>, RepositoryError>;
+
+    async fn delete_document(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<(), RepositoryError>;
+}
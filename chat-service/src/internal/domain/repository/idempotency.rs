@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::idempotency::IdempotencyRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// IdempotencyRepository stores the responses of requests sent with an idempotency key,
+// keys are unique per user and forgotten once they expire
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    // reserve stores the record unless the key is already taken by a live record,
+    // which is returned instead
+    async fn reserve(
+        &self,
+        record: &IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError>;
+
+    // complete stores the response of a reserved key
+    async fn complete(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), RepositoryError>;
+
+    // release drops the reservation of a request that failed, so it can be retried
+    async fn release(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+    ) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// JobRepository keeps the completion jobs waiting for a worker and their outcome
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    async fn create_job(&self, job: &Job) -> Result<(), RepositoryError>;
+
+    async fn find_job_by_id(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<Job>, RepositoryError>;
+
+    // list_claimable returns at most limit jobs that are queued, or running with a claim over
+    // before now, oldest first
+    async fn list_claimable(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Job>, RepositoryError>;
+
+    // claim_job moves a queued job, or a running one whose claim was over before now, to
+    // running until claimed_until; it returns false when another worker claimed it first
+    async fn claim_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError>;
+
+    // complete_job saves the status, chat, answer, error and completion time of a job still
+    // running under the claim until claimed_until and releases it; it returns false when the
+    // claim was lost, another worker claimed the job once it was over
+    async fn complete_job(
+        &self,
+        job: &Job,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError>;
+}
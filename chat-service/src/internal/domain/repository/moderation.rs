@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::moderation::ModerationFlag;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// ModerationRepository keeps the flagged messages for audit
+#[async_trait]
+pub trait ModerationRepository: Send + Sync {
+    async fn record_flag(&self, flag: &ModerationFlag) -> Result<(), RepositoryError>;
+
+    // list_flags_by_user returns the flags of a user, oldest first
+    async fn list_flags_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ModerationFlag>, RepositoryError>;
+}
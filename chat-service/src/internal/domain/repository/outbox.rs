@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// OutboxRepository reads the chat events the chat repository stored with the chats they
+// happened to, so they are published even when the process dies right after the save
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    // unpublished_events returns at most limit events not published yet, oldest first
+    async fn unpublished_events(&self, limit: usize) -> Result<Vec<OutboxEvent>, RepositoryError>;
+
+    async fn mark_published(
+        &self,
+        ids: &[Uuid],
+        published_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), RepositoryError>;
+}
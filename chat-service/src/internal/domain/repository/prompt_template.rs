@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// PromptTemplateRepository persists the templates system messages are built from,
+// template names are unique
+#[async_trait]
+pub trait PromptTemplateRepository: Send + Sync {
+    async fn create_template(&self, template: &PromptTemplate) -> Result<(), RepositoryError>;
+
+    async fn find_template_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<PromptTemplate>, RepositoryError>;
+}
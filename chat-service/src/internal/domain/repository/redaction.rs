@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// RedactionRepository keeps the encrypted originals of the messages that had personal data masked
+#[async_trait]
+pub trait RedactionRepository: Send + Sync {
+    async fn record_redaction(&self, record: &RedactionRecord) -> Result<(), RepositoryError>;
+
+    // find_redaction returns the record of the message, none when nothing was masked in it
+    async fn find_redaction(
+        &self,
+        tenant_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<Option<RedactionRecord>, RepositoryError>;
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// ScheduledMessageRepository keeps the messages waiting to be sent into chats
+#[async_trait]
+pub trait ScheduledMessageRepository: Send + Sync {
+    async fn create_scheduled_message(
+        &self,
+        scheduled: &ScheduledMessage,
+    ) -> Result<(), RepositoryError>;
+
+    async fn find_scheduled_message_by_id(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+    ) -> Result<Option<ScheduledMessage>, RepositoryError>;
+
+    // list_pending_by_user returns the pending messages of the user, soonest first
+    async fn list_pending_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError>;
+
+    // list_due returns up to limit messages of every tenant that are due at now, pending ones
+    // whose send_at is not after now and sending ones whose claim was over before now, soonest
+    // first
+    async fn list_due(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError>;
+
+    // claim_due moves a message that is due at now to sending until claimed_until, it returns
+    // false when the message is not due anymore, so that a message is claimed by a single worker
+    async fn claim_due(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError>;
+
+    // transition moves the message from one status to another, records the error and releases
+    // the claim, it returns false and changes nothing when the message is no longer in from, so
+    // that a message being sent cannot be cancelled
+    async fn transition(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        from: ScheduledStatus,
+        to: ScheduledStatus,
+        error: Option<&str>,
+    ) -> Result<bool, RepositoryError>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::tenant::Tenant;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// TenantRepository stores the tenants managed through the admin API, a stored tenant takes
+// precedence over a configured one with the same id
+#[async_trait]
+pub trait TenantRepository: Send + Sync {
+    // save_tenant creates the tenant or replaces the stored one with the same id
+    async fn save_tenant(&self, tenant: &Tenant) -> Result<(), RepositoryError>;
+
+    // list_tenants returns every stored tenant ordered by name
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, RepositoryError>;
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// UnitOfWork opens the transactions a request writes its chat and usage in, so the exchange
+// is stored whole or not at all
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    async fn begin(&self) -> Result<Box<dyn ChatTransaction>, RepositoryError>;
+}
+
+// ChatTransaction writes like the chat and usage repositories, nothing is visible until commit
+// and dropping the transaction without committing rolls every write back
+#[async_trait]
+pub trait ChatTransaction: Send {
+    async fn create_chat(&mut self, chat: &Chat) -> Result<(), RepositoryError>;
+
+    async fn save_chat(&mut self, chat: &mut Chat) -> Result<(), RepositoryError>;
+
+    async fn record_usage(&mut self, record: &UsageRecord) -> Result<(), RepositoryError>;
+
+    async fn commit(self: Box<Self>) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::{
+    ChatUsage, DailyUsage, TenantUsage, UsageRecord, UserUsage,
+};
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// UsageRepository keeps per-user daily usage aggregates and per-chat totals, reads only see
+// the usage recorded for the given tenant
+#[async_trait]
+pub trait UsageRepository: Send + Sync {
+    // record_usage adds the record to the aggregate of its user, day and model and to its chat
+    async fn record_usage(&self, record: &UsageRecord) -> Result<(), RepositoryError>;
+
+    // list_daily_usage returns the aggregates between from and to inclusive, oldest first
+    async fn list_daily_usage(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<DailyUsage>, RepositoryError>;
+
+    // list_chat_usage returns the totals of the given chats, chats without usage are left out
+    async fn list_chat_usage(
+        &self,
+        tenant_id: Uuid,
+        chat_ids: &[Uuid],
+    ) -> Result<Vec<ChatUsage>, RepositoryError>;
+
+    // summarize_usage aggregates the usage per tenant and model between from and to inclusive,
+    // of every tenant unless one is given, ordered by tenant then model; it is for the admin
+    async fn summarize_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<TenantUsage>, RepositoryError>;
+
+    // summarize_user_usage aggregates the usage per user and model between from and to
+    // inclusive, of every tenant unless one is given, ordered by tenant, user then model; it
+    // is for the admin
+    async fn summarize_user_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<UserUsage>, RepositoryError>;
+}
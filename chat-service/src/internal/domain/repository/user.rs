@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// UserRepository persists the users chats belong to, users are only found within their tenant
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn create_user(&self, user: &User) -> Result<(), RepositoryError>;
+
+    async fn find_user_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<User>, RepositoryError>;
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: Uuid,
+        external_id: &str,
+    ) -> Result<Option<User>, RepositoryError>;
+}
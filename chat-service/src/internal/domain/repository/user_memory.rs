@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user_memory::Memory;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// MemoryRepository keeps the facts known about each user
+#[async_trait]
+pub trait MemoryRepository: Send + Sync {
+    // create_memories stores the memories in order while their user has fewer than
+    // max_memories, it returns how many were stored
+    async fn create_memories(
+        &self,
+        memories: &[Memory],
+        max_memories: usize,
+    ) -> Result<usize, RepositoryError>;
+
+    async fn find_memory_by_id(
+        &self,
+        tenant_id: Uuid,
+        memory_id: Uuid,
+    ) -> Result<Option<Memory>, RepositoryError>;
+
+    // list_memories_by_user returns the memories of the user, oldest first
+    async fn list_memories_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Memory>, RepositoryError>;
+
+    async fn delete_memory(&self, tenant_id: Uuid, memory_id: Uuid) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::embedding::{VectorKind, VectorMatch, VectorRecord};
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// VectorQuery looks for the records of a user closest to the embedding among those of the
+// same model
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorQuery {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub model: String,
+    pub embedding: Vec<f32>,
+    // kinds restricts the search to some kinds of records, every kind when empty
+    pub kinds: Vec<VectorKind>,
+    pub limit: usize,
+    // min_score drops the matches less similar than it
+    pub min_score: f32,
+}
+
+// VectorStore keeps embedded texts and finds the ones closest to a query
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    // upsert stores the records, replacing those with the same id
+    async fn upsert(&self, records: &[VectorRecord]) -> Result<(), RepositoryError>;
+
+    // search returns at most limit matches, most similar first
+    async fn search(&self, query: &VectorQuery) -> Result<Vec<VectorMatch>, RepositoryError>;
+
+    // delete_by_source removes every record taken from the source, e.g. a deleted document
+    async fn delete_by_source(
+        &self,
+        tenant_id: Uuid,
+        source_id: Uuid,
+    ) -> Result<(), RepositoryError>;
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, Webhook, WebhookDelivery};
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// WebhookRepository keeps the webhooks of the tenants, the deliveries waiting to succeed and
+// the dead letters of the deliveries that never did
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<(), RepositoryError>;
+
+    async fn find_webhook_by_id(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<Option<Webhook>, RepositoryError>;
+
+    // list_webhooks returns the webhooks of the tenant, oldest first
+    async fn list_webhooks(&self, tenant_id: Uuid) -> Result<Vec<Webhook>, RepositoryError>;
+
+    // delete_webhook removes the webhook with its pending deliveries, its dead letters are kept
+    async fn delete_webhook(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<(), RepositoryError>;
+
+    async fn enqueue_deliveries(
+        &self,
+        deliveries: &[WebhookDelivery],
+    ) -> Result<(), RepositoryError>;
+
+    // due_deliveries returns up to limit deliveries of every tenant whose next attempt is not
+    // after now, the most overdue first
+    async fn due_deliveries(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError>;
+
+    // claim_delivery moves the next attempt of a delivery still due at next_attempt_at to
+    // lease_until, it returns false when another worker claimed it first
+    async fn claim_delivery(
+        &self,
+        delivery_id: Uuid,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        lease_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError>;
+
+    // reschedule_delivery stores the attempts, next attempt and last error of the delivery
+    async fn reschedule_delivery(&self, delivery: &WebhookDelivery) -> Result<(), RepositoryError>;
+
+    // complete_delivery removes a delivery that needs no other attempt
+    async fn complete_delivery(&self, delivery_id: Uuid) -> Result<(), RepositoryError>;
+
+    // dead_letter moves the delivery to the dead letters in one transaction
+    async fn dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), RepositoryError>;
+
+    // list_dead_letters returns up to limit dead letters of the tenant, newest first
+    async fn list_dead_letters(
+        &self,
+        tenant_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepositoryError>;
+}
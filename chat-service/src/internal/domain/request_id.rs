@@ -0,0 +1,79 @@
+use std::future::Future;
+
+use uuid::Uuid;
+
+// MAX_REQUEST_ID_LENGTH bounds the ids accepted from clients, they end up in every log line
+pub const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    // REQUEST_ID correlates the log lines, error responses and messages of the request being
+    // served
+    static REQUEST_ID: String;
+}
+
+// with_request_id runs the future of a request under the given id
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+// inherit runs a task spawned for the request being served under its id, task locals are not
+// carried over by tokio::spawn; the id is read when the task is created, not when it is polled
+pub fn inherit<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let request_id = current();
+    async move {
+        match request_id {
+            Some(request_id) => with_request_id(request_id, future).await,
+            None => future.await,
+        }
+    }
+}
+
+// current returns the id of the request being served, None outside of a request
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+// generate is the id of a request the client sent none for
+pub fn generate() -> String {
+    Uuid::new_v4().to_string()
+}
+
+// parse accepts the id a client sent when it is short and made of visible ASCII, anything else
+// is replaced with a generated one rather than rejected
+pub fn parse(value: &str) -> Option<String> {
+    let value = value.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value.chars().all(|c| c.is_ascii_graphic());
+
+    valid.then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_with_request_id() {
+        assert_eq!(current(), None);
+
+        let request_id = with_request_id("req-1".to_string(), async {
+            let spawned = tokio::spawn(inherit(async { current() }));
+            assert_eq!(spawned.await.unwrap(), Some("req-1".to_string()));
+            current()
+        })
+        .await;
+        assert_eq!(request_id, Some("req-1".to_string()));
+        assert_eq!(inherit(async { current() }).await, None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(" req-1 "), Some("req-1".to_string()));
+        assert_eq!(parse(&generate()).map(|id| id.len()), Some(36));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("has space"), None);
+        assert_eq!(parse("é"), None);
+        assert_eq!(parse(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)), None);
+    }
+}
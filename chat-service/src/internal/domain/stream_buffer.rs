@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{watch, Notify};
+use uuid::Uuid;
+
+// StreamBuffer keeps the chunks of every streamed reply so a client that lost its connection
+// can resume from the last chunk it received; a stream stays available for the resume window
+// once it is done, and it is abandoned when nobody followed it for that long
+pub struct StreamBuffer<T> {
+    resume_window: Duration,
+    streams: Mutex<HashMap<Uuid, Arc<BufferedStream<T>>>>,
+}
+
+struct BufferedStream<T> {
+    id: Uuid,
+    // owner is the only user allowed to resume the stream
+    owner: Uuid,
+    state: Mutex<StreamState<T>>,
+    changed: Notify,
+    followers: watch::Sender<usize>,
+}
+
+struct StreamState<T> {
+    chunks: Vec<T>,
+    finished_at: Option<Instant>,
+}
+
+// StreamWriter appends the chunks of one stream, the stream is finished when it is dropped
+pub struct StreamWriter<T> {
+    stream: Arc<BufferedStream<T>>,
+    resume_window: Duration,
+}
+
+// StreamSubscription reads the chunks of one stream in order, waiting for the next one while the
+// stream is being written
+pub struct StreamSubscription<T> {
+    stream: Arc<BufferedStream<T>>,
+    next: usize,
+}
+
+impl<T: Clone> StreamBuffer<T> {
+    pub fn new(resume_window: Duration) -> Self {
+        Self {
+            resume_window,
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // open starts a new stream of the user along with the subscription of its first client
+    pub fn open(&self, owner: Uuid) -> (StreamWriter<T>, StreamSubscription<T>) {
+        let stream = Arc::new(BufferedStream {
+            id: Uuid::new_v4(),
+            owner,
+            state: Mutex::new(StreamState {
+                chunks: vec![],
+                finished_at: None,
+            }),
+            changed: Notify::new(),
+            followers: watch::Sender::new(0),
+        });
+
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict_expired(&mut streams, Instant::now());
+        streams.insert(stream.id, stream.clone());
+
+        let writer = StreamWriter {
+            stream: stream.clone(),
+            resume_window: self.resume_window,
+        };
+        (writer, StreamSubscription::new(stream, 0))
+    }
+
+    // resume follows the stream again from the chunk after the given sequence number, None once
+    // the stream expired or when it belongs to another user
+    pub fn resume(&self, id: Uuid, owner: Uuid, after: u64) -> Option<StreamSubscription<T>> {
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict_expired(&mut streams, Instant::now());
+
+        let stream = streams.get(&id).filter(|stream| stream.owner == owner)?;
+        Some(StreamSubscription::new(stream.clone(), after as usize))
+    }
+
+    pub fn len(&self) -> usize {
+        self.streams.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn evict_expired(&self, streams: &mut HashMap<Uuid, Arc<BufferedStream<T>>>, now: Instant) {
+        streams.retain(|_, stream| {
+            let state = stream.state.lock().unwrap_or_else(|e| e.into_inner());
+            match state.finished_at {
+                Some(finished_at) => now.duration_since(finished_at) < self.resume_window,
+                None => true,
+            }
+        });
+    }
+}
+
+impl<T> StreamWriter<T> {
+    pub fn id(&self) -> Uuid {
+        self.stream.id
+    }
+
+    // push appends a chunk and returns its sequence number, the first chunk is 1
+    pub fn push(&self, chunk: T) -> u64 {
+        let mut state = self.stream.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.chunks.push(chunk);
+        let seq = state.chunks.len() as u64;
+        drop(state);
+
+        self.stream.changed.notify_waiters();
+        seq
+    }
+
+    // abandoned resolves once the stream went without followers for the resume window, the
+    // reply is not worth finishing anymore then
+    pub async fn abandoned(&self) {
+        let mut followers = self.stream.followers.subscribe();
+
+        loop {
+            if followers.wait_for(|count| *count == 0).await.is_err() {
+                return;
+            }
+            let followed = followers.wait_for(|count| *count > 0);
+            if tokio::time::timeout(self.resume_window, followed)
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+impl<T> Drop for StreamWriter<T> {
+    fn drop(&mut self) {
+        let mut state = self.stream.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.finished_at = Some(Instant::now());
+        drop(state);
+
+        self.stream.changed.notify_waiters();
+    }
+}
+
+impl<T: Clone> StreamSubscription<T> {
+    fn new(stream: Arc<BufferedStream<T>>, next: usize) -> Self {
+        stream.followers.send_modify(|count| *count += 1);
+        Self { stream, next }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.stream.id
+    }
+
+    // next returns the next chunk along with its sequence number, None once the stream is
+    // finished and every chunk was read
+    pub async fn next(&mut self) -> Option<(u64, T)> {
+        loop {
+            // the waiter is registered before the state is read so no chunk is missed
+            let changed = self.stream.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            {
+                let state = self.stream.state.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(chunk) = state.chunks.get(self.next) {
+                    self.next += 1;
+                    return Some((self.next as u64, chunk.clone()));
+                }
+                if state.finished_at.is_some() {
+                    return None;
+                }
+            }
+
+            changed.await;
+        }
+    }
+}
+
+impl<T> Drop for StreamSubscription<T> {
+    fn drop(&mut self) {
+        self.stream.followers.send_modify(|count| *count -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn test_subscription_follows_writer() {
+        let buffer = StreamBuffer::new(WINDOW);
+        let (writer, mut subscription) = buffer.open(Uuid::new_v4());
+        assert_eq!(writer.id(), subscription.id());
+
+        let write = async move {
+            for chunk in ["Hi", ", there"] {
+                writer.push(chunk.to_string());
+                tokio::task::yield_now().await;
+            }
+        };
+        let read = async {
+            let mut chunks = vec![];
+            while let Some(chunk) = subscription.next().await {
+                chunks.push(chunk);
+            }
+            chunks
+        };
+        let (_, chunks) = tokio::join!(write, read);
+
+        assert_eq!(
+            chunks,
+            vec![(1, "Hi".to_string()), (2, ", there".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume() {
+        let buffer = StreamBuffer::new(WINDOW);
+        let owner = Uuid::new_v4();
+        let (writer, subscription) = buffer.open(owner);
+        let id = writer.id();
+        for chunk in ["a", "b", "c"] {
+            writer.push(chunk.to_string());
+        }
+        drop(subscription);
+        drop(writer);
+
+        let mut resumed = buffer.resume(id, owner, 1).unwrap();
+        assert_eq!(resumed.next().await, Some((2, "b".to_string())));
+        assert_eq!(resumed.next().await, Some((3, "c".to_string())));
+        assert_eq!(resumed.next().await, None);
+
+        // other users cannot read the stream
+        assert!(buffer.resume(id, Uuid::new_v4(), 0).is_none());
+        assert!(buffer.resume(Uuid::new_v4(), owner, 0).is_none());
+
+        // finished streams expire after the resume window
+        tokio::time::sleep(WINDOW * 2).await;
+        assert!(buffer.resume(id, owner, 0).is_none());
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned() {
+        let buffer = StreamBuffer::<String>::new(WINDOW);
+        let owner = Uuid::new_v4();
+        let (writer, subscription) = buffer.open(owner);
+
+        // a follower coming back within the window keeps the stream going
+        drop(subscription);
+        tokio::time::sleep(WINDOW / 2).await;
+        let resumed = buffer.resume(writer.id(), owner, 0).unwrap();
+        let abandoned = tokio::time::timeout(WINDOW * 2, writer.abandoned()).await;
+        assert!(abandoned.is_err());
+
+        drop(resumed);
+        let abandoned = tokio::time::timeout(WINDOW * 4, writer.abandoned()).await;
+        assert!(abandoned.is_ok());
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatStatus, TrimmingPolicy};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+
+const SUMMARY_INSTRUCTION: &str = "Summarize the conversation below in a few sentences. \
+     Keep names, facts, decisions and open questions, the summary replaces the conversation \
+     for the rest of the chat.";
+const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:\n";
+
+// SummarizerConfig sets when a chat is summarized and how many recent messages stay verbatim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SummarizerConfig {
+    // threshold is the share of the token budget that triggers a summary, between 0 and 1
+    pub threshold: f32,
+    pub keep_recent: usize,
+}
+
+impl Default for SummarizerConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            keep_recent: 4,
+        }
+    }
+}
+
+// Summarizer compresses the older history of summarize_and_trim chats into a system message
+// before the budget runs out, so the context survives instead of being evicted
+pub struct Summarizer {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    config: SummarizerConfig,
+}
+
+impl Summarizer {
+    pub fn new(gateway: Arc<dyn ChatCompletionGateway>, config: SummarizerConfig) -> Self {
+        Self { gateway, config }
+    }
+
+    pub fn needs_summary(&self, chat: &Chat) -> bool {
+        chat.config.trimming_policy == TrimmingPolicy::SummarizeAndTrim
+            && chat.messages.len() > self.config.keep_recent
+            && chat.token_usage as f32 >= chat.config.max_tokens as f32 * self.config.threshold
+    }
+
+    // summarize_if_needed asks the model for a summary of the older messages and applies it,
+    // it returns whether the chat was summarized
+    pub async fn summarize_if_needed(&self, chat: &mut Chat) -> Result<bool, GatewayError> {
+        if !self.needs_summary(chat) {
+            return Ok(false);
+        }
+
+        let summarized = chat.messages.len() - self.config.keep_recent;
+        let transcript = chat.messages[..summarized]
+            .iter()
+            .map(|message| format!("{}: {}", message.role, message.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let model = chat.config.model.clone();
+        let now = chrono::Utc::now();
+        let mut request = Chat::new(
+            Uuid::new_v4(),
+            chat.user_id,
+            Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                SUMMARY_INSTRUCTION,
+                0,
+                model.clone(),
+                now,
+            ),
+            vec![Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                &transcript,
+                0,
+                model.clone(),
+                now,
+            )],
+            vec![],
+            ChatStatus::Active,
+            0,
+            chat.config.clone(),
+        );
+        request.refresh_token_usage();
+
+        let response = self.gateway.create_chat_completion(&request).await?;
+        let summary = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            &format!("{}{}", SUMMARY_PREFIX, response.content.trim()),
+            0,
+            model,
+            chrono::Utc::now(),
+        );
+        chat.summarize(self.config.keep_recent, summary);
+        tracing::info!(chat_id = %chat.id, summarized, "chat history summarized");
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use crate::internal::domain::entity::chat::ChatConfig;
+    use crate::internal::domain::entity::model::Model;
+
+    #[derive(Default)]
+    struct RecordingGateway {
+        requests: Mutex<Vec<Chat>>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for RecordingGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            self.requests.lock().unwrap().push(chat.clone());
+
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                "The user said hello several times.",
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    fn chat(policy: TrimmingPolicy, messages: usize) -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let mut config = ChatConfig::default_for(model.clone());
+        config.max_tokens = 60;
+        config.trimming_policy = policy;
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let messages = (0..messages)
+            .map(|i| {
+                Message::new(
+                    Uuid::new_v4(),
+                    Role::User,
+                    &format!("Hello number {}", i),
+                    0,
+                    model.clone(),
+                    chrono::Utc::now(),
+                )
+            })
+            .collect();
+
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            messages,
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        );
+        chat.refresh_token_usage();
+        chat
+    }
+
+    #[tokio::test]
+    async fn test_summarize_if_needed() {
+        let gateway = Arc::new(RecordingGateway::default());
+        let summarizer = Summarizer::new(gateway.clone(), SummarizerConfig::default());
+        let mut chat = chat(TrimmingPolicy::SummarizeAndTrim, 8);
+        assert!(chat.token_usage >= 48);
+
+        assert!(summarizer.summarize_if_needed(&mut chat).await.unwrap());
+
+        let requests = gateway.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].messages[0]
+            .content
+            .contains("user: Hello number 0"));
+        assert!(!requests[0].messages[0].content.contains("Hello number 4"));
+
+        assert_eq!(chat.messages.len(), 5);
+        assert_eq!(chat.messages[0].role, Role::System);
+        assert!(chat.messages[0]
+            .content
+            .ends_with("The user said hello several times."));
+        assert_eq!(chat.messages[1].content, "Hello number 4");
+        assert_eq!(chat.erased_messages.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_skipped() {
+        let gateway = Arc::new(RecordingGateway::default());
+        let summarizer = Summarizer::new(gateway.clone(), SummarizerConfig::default());
+
+        let mut trimmed = chat(TrimmingPolicy::TrimOldest, 8);
+        assert!(!summarizer.summarize_if_needed(&mut trimmed).await.unwrap());
+
+        let mut short = chat(TrimmingPolicy::SummarizeAndTrim, 2);
+        assert!(!summarizer.summarize_if_needed(&mut short).await.unwrap());
+
+        assert!(gateway.requests.lock().unwrap().is_empty());
+        assert_eq!(trimmed.messages.len(), 8);
+    }
+}
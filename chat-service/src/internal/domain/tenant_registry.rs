@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard};
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::tenant::Tenant;
+use crate::internal::domain::error::{ChatError, ConfigError};
+use crate::internal::domain::output_filter::OutputFilterConfig;
+use crate::internal::domain::rate_limiter::RateLimitConfig;
+
+// TenantRegistry holds the tenants users can belong to, the default tenant is always registered;
+// tenants registered at runtime are seen by the next request
+#[derive(Debug)]
+pub struct TenantRegistry {
+    tenants: RwLock<HashMap<Uuid, Tenant>>,
+}
+
+impl Default for TenantRegistry {
+    fn default() -> Self {
+        let default_tenant = Tenant::default_tenant();
+
+        Self {
+            tenants: RwLock::new(HashMap::from([(default_tenant.id, default_tenant)])),
+        }
+    }
+}
+
+impl TenantRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // with_tenant registers the tenant, replacing any previous one with the same id
+    pub fn with_tenant(self, tenant: Tenant) -> Result<Self, ConfigError> {
+        self.register(tenant)?;
+
+        Ok(self)
+    }
+
+    // register adds the tenant or replaces the one with the same id
+    pub fn register(&self, tenant: Tenant) -> Result<(), ConfigError> {
+        tenant.validate()?;
+        self.tenants
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(tenant.id, tenant);
+
+        Ok(())
+    }
+
+    pub fn find(&self, id: Uuid) -> Option<Tenant> {
+        self.read().get(&id).cloned()
+    }
+
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.read().contains_key(&id)
+    }
+
+    // list returns every tenant ordered by name
+    pub fn list(&self) -> Vec<Tenant> {
+        let mut tenants: Vec<Tenant> = self.read().values().cloned().collect();
+        tenants.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+        tenants
+    }
+
+    // model returns the model the tenant overrides the default one with
+    pub fn model(&self, id: Uuid) -> Option<Model> {
+        self.read()
+            .get(&id)
+            .and_then(|tenant| tenant.config.model.clone())
+    }
+
+    // check_model fails when the tenant restricts its chats to other models, unknown tenants
+    // have no restriction
+    pub fn check_model(&self, id: Uuid, model: &Model) -> Result<(), ChatError> {
+        match self.read().get(&id) {
+            Some(tenant) if !tenant.allows_model(&model.name) => {
+                Err(ChatError::ModelNotAllowed(model.name.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // output_filter returns the config the tenant filters its assistant responses with
+    pub fn output_filter(&self, id: Uuid) -> Option<OutputFilterConfig> {
+        self.read()
+            .get(&id)
+            .and_then(|tenant| tenant.config.output_filter.clone())
+    }
+
+    // rate_limits returns the tenants overriding the default rate limit with their budgets
+    pub fn rate_limits(&self) -> Vec<(Uuid, RateLimitConfig)> {
+        self.read()
+            .values()
+            .filter_map(|tenant| tenant.config.rate_limit.map(|limit| (tenant.id, limit)))
+            .collect()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<Uuid, Tenant>> {
+        self.tenants.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::{TenantConfig, DEFAULT_TENANT_ID};
+
+    #[test]
+    fn test_registry() {
+        let acme = Uuid::new_v4();
+        let limit = RateLimitConfig {
+            requests_per_minute: 10,
+            tokens_per_minute: 0,
+        };
+        let registry = TenantRegistry::new()
+            .with_tenant(Tenant::new(
+                acme,
+                "Acme",
+                TenantConfig {
+                    model: Some(Model::new("gpt-4o".to_string(), 128000)),
+                    rate_limit: Some(limit),
+                    allowed_models: vec!["gpt-4o".to_string()],
+                    quota: None,
+                    output_filter: None,
+                },
+            ))
+            .unwrap();
+
+        assert!(registry.contains(DEFAULT_TENANT_ID));
+        assert!(registry.contains(acme));
+        assert!(!registry.contains(Uuid::new_v4()));
+        assert_eq!(registry.model(acme).unwrap().name, "gpt-4o");
+        assert_eq!(registry.model(DEFAULT_TENANT_ID), None);
+        assert_eq!(registry.rate_limits(), vec![(acme, limit)]);
+        assert!(registry
+            .check_model(acme, &Model::new("gpt-4o".to_string(), 128000))
+            .is_ok());
+        assert!(matches!(
+            registry.check_model(acme, &Model::new("gpt-3.5-turbo".to_string(), 4096)),
+            Err(ChatError::ModelNotAllowed(name)) if name == "gpt-3.5-turbo"
+        ));
+        assert!(registry
+            .check_model(
+                DEFAULT_TENANT_ID,
+                &Model::new("gpt-3.5-turbo".to_string(), 4096)
+            )
+            .is_ok());
+
+        let globex = Uuid::new_v4();
+        registry
+            .register(Tenant::new(globex, "Globex", TenantConfig::default()))
+            .unwrap();
+        assert_eq!(
+            registry
+                .list()
+                .iter()
+                .map(|tenant| tenant.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Acme", "Globex", "default"]
+        );
+
+        assert!(TenantRegistry::new()
+            .with_tenant(Tenant::new(acme, "", TenantConfig::default()))
+            .is_err());
+    }
+}
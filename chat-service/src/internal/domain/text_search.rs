@@ -0,0 +1,202 @@
+// HIGHLIGHT_START and HIGHLIGHT_END surround the matched words of a highlight, the rest of the
+// highlight is HTML escaped so they are the only markup in it
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+// MATCH_START and MATCH_END are the control characters a backend surrounds the matched words
+// with before the highlight is escaped by render_highlight
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+// HIGHLIGHT_WORDS bounds the words of a highlight
+pub const HIGHLIGHT_WORDS: usize = 30;
+// HIGHLIGHT_LEAD is how many words before the first match a highlight starts
+const HIGHLIGHT_LEAD: usize = 5;
+
+// TextMatch is how well a text matched the search terms and the part of it that shows why
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatch {
+    pub rank: f32,
+    pub highlight: String,
+}
+
+// search_terms splits the query into the distinct lowercase words a text has to contain
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = vec![];
+    for word in query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let word = word.to_lowercase();
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+
+    terms
+}
+
+// match_text finds every term in the text, a word matches the terms it starts with so plurals
+// and tenses are found like stemming would; the rank is the share of matching words
+pub fn match_text(text: &str, terms: &[String]) -> Option<TextMatch> {
+    let words = word_spans(text);
+    let lowered: Vec<String> = words
+        .iter()
+        .map(|&(start, end)| text[start..end].to_lowercase())
+        .collect();
+    if terms.is_empty()
+        || !terms
+            .iter()
+            .all(|term| lowered.iter().any(|word| word.starts_with(term.as_str())))
+    {
+        return None;
+    }
+
+    let hits: Vec<bool> = lowered
+        .iter()
+        .map(|word| terms.iter().any(|term| word.starts_with(term.as_str())))
+        .collect();
+    let first = hits.iter().position(|hit| *hit)?;
+    let from = first.saturating_sub(HIGHLIGHT_LEAD);
+    let to = (from + HIGHLIGHT_WORDS).min(words.len());
+
+    let mut highlight = String::new();
+    let mut last = words[from].0;
+    for (index, &(start, end)) in words.iter().enumerate().take(to).skip(from) {
+        highlight.push_str(&escape_html(&text[last..start]));
+        if hits[index] {
+            highlight.push_str(HIGHLIGHT_START);
+            highlight.push_str(&escape_html(&text[start..end]));
+            highlight.push_str(HIGHLIGHT_END);
+        } else {
+            highlight.push_str(&escape_html(&text[start..end]));
+        }
+        last = end;
+    }
+
+    Some(TextMatch {
+        rank: hits.iter().filter(|hit| **hit).count() as f32 / words.len() as f32,
+        highlight,
+    })
+}
+
+// render_highlight escapes a highlight whose matched words are surrounded by MATCH_START and
+// MATCH_END, and marks those words with HIGHLIGHT_START and HIGHLIGHT_END instead
+pub fn render_highlight(marked: &str) -> String {
+    let mut highlight = String::with_capacity(marked.len());
+    for c in marked.chars() {
+        match c {
+            MATCH_START => highlight.push_str(HIGHLIGHT_START),
+            MATCH_END => highlight.push_str(HIGHLIGHT_END),
+            c => push_escaped(&mut highlight, c),
+        }
+    }
+
+    highlight
+}
+
+// escape_html escapes the characters that would be read as markup
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut escaped, c);
+    }
+
+    escaped
+}
+
+fn push_escaped(escaped: &mut String, c: char) {
+    match c {
+        '&' => escaped.push_str("&amp;"),
+        '<' => escaped.push_str("&lt;"),
+        '>' => escaped.push_str("&gt;"),
+        '"' => escaped.push_str("&quot;"),
+        '\'' => escaped.push_str("&#39;"),
+        c => escaped.push(c),
+    }
+}
+
+// word_spans returns the byte ranges of the runs of letters and digits of the text
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = vec![];
+    let mut start = None;
+    for (index, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                spans.push((from, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        spans.push((from, text.len()));
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(
+            search_terms("Rust, rust & borrow-checker!"),
+            vec!["rust", "borrow", "checker"]
+        );
+        assert!(search_terms(" ?! ").is_empty());
+    }
+
+    #[test]
+    fn test_match_text() {
+        let terms = search_terms("rust borrow");
+        let found = match_text("How do I satisfy the Rust borrow checker?", &terms).unwrap();
+        assert_eq!(
+            found.highlight,
+            "How do I satisfy the <mark>Rust</mark> <mark>borrow</mark> checker"
+        );
+        assert!(found.rank > 0.0);
+
+        let found = match_text("Borrowing rules of rust.", &terms).unwrap();
+        assert_eq!(
+            found.highlight,
+            "<mark>Borrowing</mark> rules of <mark>rust</mark>"
+        );
+
+        assert_eq!(match_text("Rust only", &terms), None);
+        assert_eq!(match_text("anything", &[]), None);
+    }
+
+    #[test]
+    fn test_match_text_escapes_the_content() {
+        let terms = search_terms("script");
+        let found = match_text("<script>alert('x') & more</script>", &terms).unwrap();
+
+        assert_eq!(
+            found.highlight,
+            "<mark>script</mark>&gt;alert(&#39;x&#39;) &amp; more&lt;/<mark>script</mark>"
+        );
+    }
+
+    #[test]
+    fn test_render_highlight() {
+        assert_eq!(
+            render_highlight("a <b> \u{2}rust\u{3} & \u{2}borrow\u{3}"),
+            "a &lt;b&gt; <mark>rust</mark> &amp; <mark>borrow</mark>"
+        );
+    }
+
+    #[test]
+    fn test_match_text_bounds_the_highlight() {
+        let text = (0..100)
+            .map(|i| format!("word{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let found = match_text(&text, &["word50".to_string()]).unwrap();
+
+        assert_eq!(found.highlight.split(' ').count(), HIGHLIGHT_WORDS);
+        assert!(found.highlight.starts_with("word45 "));
+        assert!(found.highlight.contains("<mark>word50</mark>"));
+    }
+}
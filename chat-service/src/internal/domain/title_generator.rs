@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatStatus};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+
+const TITLE_INSTRUCTION: &str = "Write a short title of at most six words for the conversation \
+     below. Answer with the title only, without quotes or trailing punctuation.";
+// MAX_EXCERPT_LENGTH bounds how much of each message is sent, the opening is enough for a title
+const MAX_EXCERPT_LENGTH: usize = 1000;
+pub const MAX_TITLE_LENGTH: usize = 80;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TitleError {
+    #[error(transparent)]
+    Gateway(#[from] GatewayError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+}
+
+// TitleGenerator names chats after their first exchange so listings have something to show
+pub struct TitleGenerator {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    repository: Arc<dyn ChatRepository>,
+}
+
+impl TitleGenerator {
+    pub fn new(
+        gateway: Arc<dyn ChatCompletionGateway>,
+        repository: Arc<dyn ChatRepository>,
+    ) -> Self {
+        Self {
+            gateway,
+            repository,
+        }
+    }
+
+    pub fn needs_title(&self, chat: &Chat) -> bool {
+        chat.title.is_none() && chat.first_exchange().is_some()
+    }
+
+    // spawn titles the chat in a background task so the reply is not delayed,
+    // a failure is only logged and the next reply tries again
+    pub fn spawn(self: &Arc<Self>, chat: &Chat) {
+        if !self.needs_title(chat) {
+            return;
+        }
+
+        let generator = self.clone();
+        let chat = chat.clone();
+        tokio::spawn(async move {
+            if let Err(err) = generator.title_chat(&chat).await {
+                tracing::warn!(chat_id = %chat.id, error = %err, "could not title the chat");
+            }
+        });
+    }
+
+    // title_chat asks the model for a title of the first exchange and stores it,
+    // it returns the stored title
+    pub async fn title_chat(&self, chat: &Chat) -> Result<Option<String>, TitleError> {
+        let Some((question, answer)) = chat.first_exchange() else {
+            return Ok(None);
+        };
+
+        let transcript = [question, answer]
+            .iter()
+            .map(|message| format!("{}: {}", message.role, excerpt(&message.content)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let model = chat.config.model.clone();
+        let now = chrono::Utc::now();
+        // the title is plain text whatever the chat expects, and no tool is offered; the request
+        // carries the chat so the call is billed to it
+        let mut config = chat.config.clone();
+        config.tools = vec![];
+        config.response_format = ResponseFormat::default();
+        let mut request = Chat::new(
+            chat.id,
+            chat.user_id,
+            Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                TITLE_INSTRUCTION,
+                0,
+                model.clone(),
+                now,
+            ),
+            vec![Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                &transcript,
+                0,
+                model,
+                now,
+            )],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        )
+        .with_tenant(chat.tenant_id);
+        request.refresh_token_usage();
+
+        let response = self.gateway.create_chat_completion(&request).await?;
+        let Some(title) = clean_title(&response.content) else {
+            return Ok(None);
+        };
+        self.repository
+            .update_chat_title(chat.tenant_id, chat.id, &title)
+            .await?;
+        tracing::info!(chat_id = %chat.id, "chat titled");
+
+        Ok(Some(title))
+    }
+}
+
+fn excerpt(content: &str) -> String {
+    content.chars().take(MAX_EXCERPT_LENGTH).collect()
+}
+
+// clean_title keeps the first line of the answer without quotes or trailing punctuation
+fn clean_title(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    let title: String = line
+        .trim_matches(|c| c == '"' || c == '\'')
+        .trim_end_matches(['.', '!'])
+        .trim()
+        .chars()
+        .take(MAX_TITLE_LENGTH)
+        .collect();
+
+    match title.trim_end() {
+        "" => None,
+        title => Some(title.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use crate::internal::domain::entity::chat::ChatConfig;
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    #[derive(Default)]
+    struct RecordingGateway {
+        requests: Mutex<Vec<Chat>>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for RecordingGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            self.requests.lock().unwrap().push(chat.clone());
+
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                "\"Planning a trip to Lisbon.\"\nI hope this helps!",
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    fn chat(messages: &[(Role, &str)]) -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content: &str| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            messages
+                .iter()
+                .map(|(role, content)| message(*role, content))
+                .collect(),
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_title_chat() {
+        let gateway = Arc::new(RecordingGateway::default());
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let generator = TitleGenerator::new(gateway.clone(), repository.clone());
+        let chat = chat(&[
+            (Role::User, "I want to visit Lisbon in May"),
+            (Role::Assistant, "Great choice! May is sunny."),
+        ]);
+        repository.create_chat(&chat).await.unwrap();
+        assert!(generator.needs_title(&chat));
+
+        let title = generator.title_chat(&chat).await.unwrap();
+
+        assert_eq!(title.as_deref(), Some("Planning a trip to Lisbon"));
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title, title);
+        assert!(!generator.needs_title(&stored));
+
+        let requests = gateway.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].id, chat.id);
+        assert_eq!(requests[0].tenant_id, chat.tenant_id);
+        assert_eq!(requests[0].user_id, chat.user_id);
+        assert_eq!(
+            requests[0].messages[0].content,
+            "user: I want to visit Lisbon in May\nassistant: Great choice! May is sunny."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_title_chat_without_answer() {
+        let gateway = Arc::new(RecordingGateway::default());
+        let generator =
+            TitleGenerator::new(gateway.clone(), Arc::new(InMemoryChatRepository::new()));
+        let chat = chat(&[(Role::User, "I want to visit Lisbon in May")]);
+
+        assert!(!generator.needs_title(&chat));
+        assert_eq!(generator.title_chat(&chat).await.unwrap(), None);
+        assert!(gateway.requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("  Lisbon trip  "),
+            Some("Lisbon trip".to_string())
+        );
+        assert_eq!(
+            clean_title("\n'Lisbon trip.'"),
+            Some("Lisbon trip".to_string())
+        );
+        assert_eq!(clean_title(" \n \"\" "), None);
+        assert_eq!(
+            clean_title(&"a".repeat(200)).unwrap().len(),
+            MAX_TITLE_LENGTH
+        );
+    }
+}
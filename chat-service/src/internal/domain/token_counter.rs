@@ -0,0 +1,123 @@
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::{
+    cl100k_base_singleton, o200k_base_singleton, p50k_base_singleton, p50k_edit_singleton,
+    r50k_base_singleton,
+};
+
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+
+// every reply is primed with <|start|>assistant<|message|>
+pub const REPLY_PRIMING_TOKENS: usize = 3;
+
+// TokenCounter counts tokens the way OpenAI bills a chat completion prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenCounter {
+    tokenizer: Tokenizer,
+    tokens_per_message: usize,
+}
+
+impl TokenCounter {
+    // for_model returns None when tiktoken does not know the model's encoding
+    pub fn for_model(model: &Model) -> Option<Self> {
+        let tokenizer = get_tokenizer(&model.name)?;
+
+        // gpt-3.5 wraps every message as <|im_start|>{role}\n{content}<|im_end|>\n
+        let tokens_per_message = if model.name.starts_with("gpt-3.5") {
+            4
+        } else {
+            3
+        };
+
+        Some(Self {
+            tokenizer,
+            tokens_per_message,
+        })
+    }
+
+    // count returns the number of tokens in text
+    pub fn count(&self, text: &str) -> usize {
+        let bpe = match self.tokenizer {
+            Tokenizer::O200kBase => o200k_base_singleton(),
+            Tokenizer::Cl100kBase => cl100k_base_singleton(),
+            Tokenizer::P50kBase => p50k_base_singleton(),
+            Tokenizer::P50kEdit => p50k_edit_singleton(),
+            Tokenizer::R50kBase | Tokenizer::Gpt2 => r50k_base_singleton(),
+        };
+        let bpe = bpe.lock();
+
+        bpe.encode_with_special_tokens(text).len()
+    }
+
+    // count_message returns the tokens a message takes in the prompt, role and framing included
+    pub fn count_message(&self, role: Role, content: &str) -> usize {
+        self.tokens_per_message + self.count(&role.to_string()) + self.count(content)
+    }
+}
+
+// prompt_tokens returns the tokens used by a prompt made of the system message and the history
+pub fn prompt_tokens(system_message: &Message, messages: &[Message]) -> usize {
+    messages.iter().fold(
+        system_message.tokens + REPLY_PRIMING_TOKENS,
+        |acc, message| acc + message.tokens,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_count() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let counter = TokenCounter::for_model(&model).unwrap();
+
+        assert_eq!(counter.count("Hello, world!"), 4);
+        assert_eq!(counter.count(""), 0);
+    }
+
+    #[test]
+    fn test_count_message() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let counter = TokenCounter::for_model(&model).unwrap();
+        assert_eq!(counter.count_message(Role::User, "Hello, world!"), 9);
+
+        let model = Model::new("gpt-4".to_string(), 8192);
+        let counter = TokenCounter::for_model(&model).unwrap();
+        assert_eq!(counter.count_message(Role::User, "Hello, world!"), 8);
+    }
+
+    #[test]
+    fn test_unknown_model() {
+        let model = Model::new("llama2".to_string(), 4096);
+
+        assert_eq!(TokenCounter::for_model(&model), None);
+    }
+
+    #[test]
+    fn test_prompt_tokens() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello, world!",
+            0,
+            model,
+            chrono::Utc::now(),
+        );
+
+        assert_eq!(
+            prompt_tokens(&system_message, std::slice::from_ref(&message)),
+            system_message.tokens + message.tokens + REPLY_PRIMING_TOKENS
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+use crate::internal::domain::error::ConfigError;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ToolError {
+    #[error("unknown tool {0}")]
+    Unknown(String),
+    #[error("invalid arguments for {name}: {message}")]
+    InvalidArguments { name: String, message: String },
+    #[error("tool {name} failed: {message}")]
+    Failed { name: String, message: String },
+}
+
+// ToolHandler runs a tool with the arguments produced by the model and returns its result
+#[async_trait]
+pub trait ToolHandler: Send + Sync {
+    async fn call(&self, arguments: serde_json::Value) -> Result<String, String>;
+}
+
+// any async closure taking the arguments can be registered as a handler
+#[async_trait]
+impl<F, Fut> ToolHandler for F
+where
+    F: Fn(serde_json::Value) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<String, String>> + Send + 'static,
+{
+    async fn call(&self, arguments: serde_json::Value) -> Result<String, String> {
+        self(arguments).await
+    }
+}
+
+struct RegisteredTool {
+    definition: ToolDefinition,
+    handler: Arc<dyn ToolHandler>,
+}
+
+// ToolRegistry holds the tools offered to the model and runs the calls it requests
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, RegisteredTool>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // with_tool registers a handler under the definition name, replacing any previous one
+    pub fn with_tool(
+        mut self,
+        definition: ToolDefinition,
+        handler: impl ToolHandler + 'static,
+    ) -> Result<Self, ConfigError> {
+        definition.validate()?;
+        self.tools.insert(
+            definition.name.clone(),
+            RegisteredTool {
+                definition,
+                handler: Arc::new(handler),
+            },
+        );
+
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    // contains tells whether a tool is registered under the name
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.contains_key(name)
+    }
+
+    // definitions returns the registered tools sorted by name
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .values()
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+
+    pub async fn execute(&self, call: &ToolCall) -> Result<String, ToolError> {
+        let tool = self
+            .tools
+            .get(&call.name)
+            .ok_or_else(|| ToolError::Unknown(call.name.clone()))?;
+
+        let arguments = if call.arguments.trim().is_empty() {
+            serde_json::Value::Object(Default::default())
+        } else {
+            serde_json::from_str(&call.arguments).map_err(|e| ToolError::InvalidArguments {
+                name: call.name.clone(),
+                message: e.to_string(),
+            })?
+        };
+
+        tool.handler
+            .call(arguments)
+            .await
+            .map_err(|message| ToolError::Failed {
+                name: call.name.clone(),
+                message,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry() -> ToolRegistry {
+        ToolRegistry::new()
+            .with_tool(
+                ToolDefinition::new(
+                    "get_weather",
+                    "Current weather of a city",
+                    json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+                ),
+                |arguments: serde_json::Value| async move {
+                    match arguments["city"].as_str() {
+                        Some(city) => Ok(format!("18C and sunny in {}", city)),
+                        None => Err("city is required".to_string()),
+                    }
+                },
+            )
+            .unwrap()
+    }
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let registry = registry();
+
+        assert_eq!(registry.definitions()[0].name, "get_weather");
+        assert!(registry.contains("get_weather"));
+        assert!(!registry.contains("get_time"));
+        assert_eq!(
+            registry
+                .execute(&call("get_weather", r#"{"city":"Lisbon"}"#))
+                .await,
+            Ok("18C and sunny in Lisbon".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_errors() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.execute(&call("get_time", "{}")).await,
+            Err(ToolError::Unknown("get_time".to_string()))
+        );
+        assert!(matches!(
+            registry.execute(&call("get_weather", "{city")).await,
+            Err(ToolError::InvalidArguments { .. })
+        ));
+        assert!(matches!(
+            registry.execute(&call("get_weather", "")).await,
+            Err(ToolError::Failed { message, .. }) if message == "city is required"
+        ));
+    }
+
+    #[test]
+    fn test_with_invalid_tool() {
+        let result = ToolRegistry::new().with_tool(
+            ToolDefinition::new("get weather", "", json!({"type": "object"})),
+            |_: serde_json::Value| async { Ok(String::new()) },
+        );
+
+        assert!(matches!(result, Err(ConfigError::InvalidTool(_))));
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::internal::domain::entity::model::{Model, ModelRegistry};
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::usage::UsageRepository;
+
+// UsageTracker prices every completion with the registry and stores it in the daily aggregates
+pub struct UsageTracker {
+    repository: Arc<dyn UsageRepository>,
+}
+
+impl UsageTracker {
+    pub fn new(repository: Arc<dyn UsageRepository>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn record(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Uuid,
+        model: &Model,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+    ) -> Result<UsageRecord, RepositoryError> {
+        let record = self.price(
+            tenant_id,
+            user_id,
+            chat_id,
+            model,
+            prompt_tokens,
+            completion_tokens,
+        );
+        self.save(&record).await?;
+
+        Ok(record)
+    }
+
+    // price builds the record of a completion without storing it, models missing from the
+    // registry cost nothing
+    pub fn price(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Uuid,
+        model: &Model,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+    ) -> UsageRecord {
+        let cost = ModelRegistry::get(&model.name)
+            .map(|info| info.cost(prompt_tokens, completion_tokens))
+            .unwrap_or_default();
+
+        UsageRecord {
+            tenant_id,
+            user_id,
+            chat_id,
+            model: model.name.clone(),
+            prompt_tokens,
+            completion_tokens,
+            cost,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    pub async fn save(&self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        self.repository.record_usage(record).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    #[tokio::test]
+    async fn test_record() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let tracker = UsageTracker::new(repository.clone());
+        let user_id = Uuid::new_v4();
+        let model = Model::new("gpt-4o".to_string(), 128000);
+
+        let record = tracker
+            .record(
+                DEFAULT_TENANT_ID,
+                user_id,
+                Uuid::new_v4(),
+                &model,
+                1000,
+                500,
+            )
+            .await
+            .unwrap();
+
+        let info = ModelRegistry::get("gpt-4o").unwrap();
+        assert_eq!(record.cost, info.cost(1000, 500));
+
+        let unknown = Model::new("acme-unknown".to_string(), 4096);
+        let record = tracker
+            .record(
+                DEFAULT_TENANT_ID,
+                user_id,
+                Uuid::new_v4(),
+                &unknown,
+                1000,
+                500,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.cost, 0.0);
+
+        let today = record.date();
+        let usage = repository
+            .list_daily_usage(DEFAULT_TENANT_ID, user_id, today, today)
+            .await
+            .unwrap();
+        assert_eq!(usage.len(), 2);
+    }
+}
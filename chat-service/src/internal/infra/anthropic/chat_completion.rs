@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::infra::anthropic::types::{
+    parse_stream_line, MessagesRequest, MessagesResponse, MessagesStreamEvent, StreamDelta,
+};
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+pub const API_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicGateway {
+    client: RetryClient,
+    api_key: String,
+    base_url: String,
+}
+
+impl AnthropicGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            base_url,
+        }
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+
+    async fn send(&self, request: &MessagesRequest) -> Result<reqwest::Response, GatewayError> {
+        let request = self
+            .client
+            .post(format!("{}/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(request);
+
+        self.client.send(request).await
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for AnthropicGateway {
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let request = MessagesRequest::from_chat(chat);
+        let response = self.send(&request).await?;
+
+        let completion: MessagesResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        let content = completion.text();
+        if content.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            &content,
+            0,
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        ))
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let request = MessagesRequest::from_chat(chat).streaming();
+        let response = self.send(&request).await?;
+
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut content = String::new();
+
+        'stream: while let Some(bytes) = stream.next().await {
+            let bytes = bytes.map_err(|e| GatewayError::Request(e.to_string()))?;
+            buffer.extend_from_slice(&bytes);
+
+            while let Some(position) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=position).collect();
+                let line = String::from_utf8_lossy(&line);
+
+                let event = match parse_stream_line(&line) {
+                    Some(event) => event.map_err(|e| GatewayError::Request(e.to_string()))?,
+                    None => continue,
+                };
+
+                let delta = match event {
+                    MessagesStreamEvent::ContentBlockDelta {
+                        delta: StreamDelta::TextDelta { text },
+                        ..
+                    } => text,
+                    MessagesStreamEvent::MessageStop => break 'stream,
+                    MessagesStreamEvent::Error { error } => {
+                        return Err(GatewayError::Request(format!(
+                            "{}: {}",
+                            error.kind, error.message
+                        )));
+                    }
+                    _ => continue,
+                };
+
+                if !delta.is_empty() {
+                    content.push_str(&delta);
+                    let _ = sender.send(delta).await;
+                }
+            }
+        }
+
+        if content.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            &content,
+            0,
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        ))
+    }
+}
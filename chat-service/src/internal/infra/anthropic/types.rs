@@ -0,0 +1,309 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Role;
+
+// MAX_OUTPUT_TOKENS caps max_tokens, which the Messages API requires on every request
+pub const MAX_OUTPUT_TOKENS: usize = 4096;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessagesRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MessagesMessage {
+    pub role: MessagesRole,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessagesRequest {
+    pub model: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub system: String,
+    pub messages: Vec<MessagesMessage>,
+    pub max_tokens: usize,
+    pub temperature: f32,
+    pub top_p: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+impl MessagesRequest {
+    // from_chat moves system messages to the top-level system field, the API only accepts
+    // user and assistant turns in messages
+    pub fn from_chat(chat: &Chat) -> Self {
+        let mut system = vec![chat.initial_system_message().content.clone()];
+        let mut messages = Vec::with_capacity(chat.messages.len());
+
+        for message in &chat.messages {
+            let role = match message.role {
+                Role::User | Role::Tool => MessagesRole::User,
+                Role::Assistant => MessagesRole::Assistant,
+                Role::System => {
+                    system.push(message.content.clone());
+                    continue;
+                }
+            };
+
+            messages.push(MessagesMessage {
+                role,
+                content: message.content.clone(),
+            });
+        }
+
+        let max_tokens = chat
+            .config
+            .max_tokens
+            .saturating_sub(chat.token_usage)
+            .clamp(1, MAX_OUTPUT_TOKENS);
+
+        Self {
+            model: chat.config.model.api_name().to_string(),
+            system: system.join("\n\n"),
+            messages,
+            max_tokens,
+            // Anthropic accepts temperatures up to 1.0 only
+            temperature: chat.config.temperature.min(1.0),
+            top_p: chat.config.top_p,
+            stop_sequences: chat.config.stop.clone(),
+            // the API has no n nor penalties, a reply always carries a single candidate
+            stream: None,
+        }
+    }
+
+    // streaming switches the request to server-sent events
+    pub fn streaming(mut self) -> Self {
+        self.stream = Some(true);
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessagesUsage {
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessagesResponse {
+    pub id: String,
+    pub model: String,
+    pub content: Vec<ContentBlock>,
+    pub stop_reason: Option<String>,
+    pub usage: Option<MessagesUsage>,
+}
+
+impl MessagesResponse {
+    // text concatenates the text blocks of the reply
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                ContentBlock::Other => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamDelta {
+    TextDelta {
+        text: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamError {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagesStreamEvent {
+    ContentBlockDelta {
+        index: u32,
+        delta: StreamDelta,
+    },
+    MessageStop,
+    Error {
+        error: StreamError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+// parse_stream_line decodes a single server-sent event line, the event name is repeated in the
+// data payload's type field so event: lines are skipped
+pub fn parse_stream_line(line: &str) -> Option<Result<MessagesStreamEvent, serde_json::Error>> {
+    let data = line.trim().strip_prefix("data:")?.trim();
+
+    Some(serde_json::from_str(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Message;
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::testing::golden::{assert_golden, golden_chat};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_from_chat() {
+        let model = Model::new("anthropic/claude-3-5-sonnet".to_string(), 200000);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let config = ChatConfig {
+            temperature: 1.5,
+            stop: vec!["\n\nHuman:".to_string()],
+            ..ChatConfig::default_for(model.clone())
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        );
+        for (role, content) in [
+            (Role::User, "Hello!"),
+            (Role::System, "Summary of earlier turns."),
+            (Role::Assistant, "Hi there!"),
+        ] {
+            let message = Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            );
+            chat.messages.push(message);
+        }
+
+        let request = MessagesRequest::from_chat(&chat);
+
+        assert_eq!(request.model, "claude-3-5-sonnet");
+        assert_eq!(
+            request.system,
+            "You are a helpful assistant.\n\nSummary of earlier turns."
+        );
+        assert_eq!(
+            request.messages,
+            vec![
+                MessagesMessage {
+                    role: MessagesRole::User,
+                    content: "Hello!".to_string(),
+                },
+                MessagesMessage {
+                    role: MessagesRole::Assistant,
+                    content: "Hi there!".to_string(),
+                },
+            ]
+        );
+        assert_eq!(request.max_tokens, MAX_OUTPUT_TOKENS);
+        assert_eq!(request.temperature, 1.0);
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["stop_sequences"][0], "\n\nHuman:");
+        assert!(body.get("stream").is_none());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = r#"{
+            "id": "msg_013Zva2CMHLNnXjNJJKqJ2EF",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-sonnet-20240620",
+            "content": [{"type": "text", "text": "Hi there!"}],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 25}
+        }"#;
+
+        let response: MessagesResponse = serde_json::from_str(body).unwrap();
+
+        assert_eq!(response.text(), "Hi there!");
+        assert_eq!(response.usage.unwrap().output_tokens, 25);
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let line = r#"data: {"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hello"}}"#;
+
+        match parse_stream_line(line) {
+            Some(Ok(MessagesStreamEvent::ContentBlockDelta {
+                delta: StreamDelta::TextDelta { text },
+                ..
+            })) => assert_eq!(text, "Hello"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert!(matches!(
+            parse_stream_line(r#"data: {"type": "message_stop"}"#),
+            Some(Ok(MessagesStreamEvent::MessageStop))
+        ));
+        assert!(matches!(
+            parse_stream_line(r#"data: {"type": "ping"}"#),
+            Some(Ok(MessagesStreamEvent::Other))
+        ));
+        assert!(matches!(
+            parse_stream_line(
+                r#"data: {"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#
+            ),
+            Some(Ok(MessagesStreamEvent::Error { .. }))
+        ));
+        assert!(parse_stream_line("event: content_block_delta").is_none());
+        assert!(parse_stream_line("").is_none());
+    }
+
+    #[test]
+    fn test_request_golden() {
+        let chat = golden_chat(Model::new(
+            "anthropic/claude-3-5-sonnet".to_string(),
+            200000,
+        ));
+
+        assert_golden(
+            "anthropic_messages_request",
+            &MessagesRequest::from_chat(&chat),
+        );
+        assert_golden(
+            "anthropic_messages_stream_request",
+            &MessagesRequest::from_chat(&chat).streaming(),
+        );
+    }
+}
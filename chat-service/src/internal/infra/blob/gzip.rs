@@ -0,0 +1,76 @@
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::internal::domain::gateway::blob_store::{BlobError, BlobStore};
+
+// GzipBlobStore compresses the blobs of the inner store, their keys get a .gz suffix
+pub struct GzipBlobStore {
+    inner: Arc<dyn BlobStore>,
+}
+
+impl GzipBlobStore {
+    pub fn new(inner: Arc<dyn BlobStore>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl BlobStore for GzipBlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>) -> Result<(), BlobError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&content)
+            .map_err(|e| BlobError(e.to_string()))?;
+        let compressed = encoder.finish().map_err(|e| BlobError(e.to_string()))?;
+
+        self.inner.put(&compressed_key(key), compressed).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BlobError> {
+        let Some(compressed) = self.inner.get(&compressed_key(key)).await? else {
+            return Ok(None);
+        };
+        let mut content = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut content)
+            .map_err(|e| BlobError(e.to_string()))?;
+
+        Ok(Some(content))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobError> {
+        self.inner.delete(&compressed_key(key)).await
+    }
+}
+
+fn compressed_key(key: &str) -> String {
+    format!("{}.gz", key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let inner = Arc::new(InMemoryBlobStore::new());
+        let store = GzipBlobStore::new(inner.clone());
+        let content = "{\"role\":\"user\"}\n".repeat(100).into_bytes();
+
+        store.put("chats/1.jsonl", content.clone()).await.unwrap();
+        assert_eq!(inner.keys(), vec!["chats/1.jsonl.gz"]);
+        let compressed = inner.get("chats/1.jsonl.gz").await.unwrap().unwrap();
+        assert!(compressed.len() < content.len());
+        assert_eq!(store.get("chats/1.jsonl").await.unwrap(), Some(content));
+
+        store.delete("chats/1.jsonl").await.unwrap();
+        assert_eq!(store.get("chats/1.jsonl").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::gateway::blob_store::{BlobError, BlobStore};
+
+// InMemoryBlobStore keeps the blobs in the process, for tests and single node setups without a
+// bucket
+#[derive(Default)]
+pub struct InMemoryBlobStore {
+    blobs: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // keys returns the stored keys in order
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .blobs
+            .read()
+            .map(|blobs| blobs.keys().cloned().collect())
+            .unwrap_or_default();
+        keys.sort();
+
+        keys
+    }
+}
+
+#[async_trait]
+impl BlobStore for InMemoryBlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>) -> Result<(), BlobError> {
+        self.blobs
+            .write()
+            .map_err(|e| BlobError(e.to_string()))?
+            .insert(key.to_string(), content);
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BlobError> {
+        let blobs = self.blobs.read().map_err(|e| BlobError(e.to_string()))?;
+
+        Ok(blobs.get(key).cloned())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobError> {
+        self.blobs
+            .write()
+            .map_err(|e| BlobError(e.to_string()))?
+            .remove(key);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let store = InMemoryBlobStore::new();
+        assert_eq!(store.get("chats/1.jsonl").await.unwrap(), None);
+
+        store.put("chats/1.jsonl", b"one".to_vec()).await.unwrap();
+        store.put("chats/1.jsonl", b"two".to_vec()).await.unwrap();
+        assert_eq!(
+            store.get("chats/1.jsonl").await.unwrap(),
+            Some(b"two".to_vec())
+        );
+        assert_eq!(store.keys(), vec!["chats/1.jsonl"]);
+
+        store.delete("chats/1.jsonl").await.unwrap();
+        store.delete("chats/1.jsonl").await.unwrap();
+        assert_eq!(store.get("chats/1.jsonl").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod gzip;
+pub mod memory;
+pub mod s3;
+pub mod sigv4;
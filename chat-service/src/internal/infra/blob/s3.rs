@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode, Url};
+
+use crate::internal::domain::gateway::blob_store::{BlobError, BlobStore};
+use crate::internal::infra::blob::sigv4::{amz_date, encode_path, payload_hash_of, SigV4};
+
+// S3BlobStore keeps the blobs as objects of a bucket of S3 or a compatible store, addressed
+// path style as {endpoint}/{bucket}/{prefix}/{key} so custom endpoints need no DNS per bucket
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    endpoint: Url,
+    host: String,
+    bucket: String,
+    prefix: String,
+    signer: SigV4,
+}
+
+impl S3BlobStore {
+    pub fn new(
+        endpoint: &str,
+        bucket: String,
+        prefix: String,
+        signer: SigV4,
+        timeout: Duration,
+    ) -> Result<Self, BlobError> {
+        let endpoint = Url::parse(endpoint).map_err(|e| BlobError(e.to_string()))?;
+        // the host header is signed, it carries the port unless it is the default one
+        let host = match (endpoint.host_str(), endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(BlobError(format!("endpoint {} has no host", endpoint))),
+        };
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| BlobError(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            endpoint,
+            host,
+            bucket,
+            prefix: prefix.trim_matches('/').to_string(),
+            signer,
+        })
+    }
+
+    // send signs and sends a request for the object of the key
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        content: Vec<u8>,
+    ) -> Result<reqwest::Response, BlobError> {
+        let object = match self.prefix.as_str() {
+            "" => key.to_string(),
+            prefix => format!("{}/{}", prefix, key),
+        };
+        let path = encode_path(&format!("/{}/{}", self.bucket, object));
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let now = chrono::Utc::now();
+        let date = amz_date(now);
+        let payload_hash = payload_hash_of(&content);
+        let authorization = self.signer.authorization(
+            method.as_str(),
+            &path,
+            &[
+                ("host", &self.host),
+                ("x-amz-content-sha256", &payload_hash),
+                ("x-amz-date", &date),
+            ],
+            &payload_hash,
+            now,
+        );
+
+        self.client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", date)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(content)
+            .send()
+            .await
+            .map_err(|e| BlobError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>) -> Result<(), BlobError> {
+        let response = self.send(Method::PUT, key, content).await?;
+        check(key, response.status())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BlobError> {
+        let response = self.send(Method::GET, key, vec![]).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        check(key, response.status())?;
+
+        let content = response
+            .bytes()
+            .await
+            .map_err(|e| BlobError(e.to_string()))?;
+
+        Ok(Some(content.to_vec()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobError> {
+        let response = self.send(Method::DELETE, key, vec![]).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check(key, response.status())
+    }
+}
+
+fn check(key: &str, status: StatusCode) -> Result<(), BlobError> {
+    if !status.is_success() {
+        return Err(BlobError(format!("object {} answered {}", key, status)));
+    }
+
+    Ok(())
+}
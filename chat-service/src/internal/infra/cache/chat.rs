@@ -0,0 +1,625 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatSummary};
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::{
+    ChatCursor, ChatRepository, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+};
+use crate::internal::domain::repository::unit_of_work::{ChatTransaction, UnitOfWork};
+
+#[derive(Debug, thiserror::Error)]
+#[error("cache error: {0}")]
+pub struct CacheError(pub String);
+
+// ChatCache keeps serialized chats so a turn does not reload the whole history
+#[async_trait]
+pub trait ChatCache: Send + Sync {
+    async fn get_chat(&self, id: Uuid) -> Result<Option<Chat>, CacheError>;
+
+    async fn set_chat(&self, chat: &Chat) -> Result<(), CacheError>;
+
+    async fn delete_chat(&self, id: Uuid) -> Result<(), CacheError>;
+}
+
+// CachedChatRepository reads through the cache and writes through it after the repository,
+// the repository stays the source of truth so cache failures never fail a request
+pub struct CachedChatRepository {
+    repository: Arc<dyn ChatRepository>,
+    cache: Arc<dyn ChatCache>,
+}
+
+impl CachedChatRepository {
+    pub fn new(repository: Arc<dyn ChatRepository>, cache: Arc<dyn ChatCache>) -> Self {
+        Self { repository, cache }
+    }
+
+    async fn refresh(&self, chat: &Chat) {
+        refresh(self.cache.as_ref(), chat).await
+    }
+}
+
+#[async_trait]
+impl ChatRepository for CachedChatRepository {
+    async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+        self.repository.create_chat(chat).await?;
+        self.refresh(chat).await;
+
+        Ok(())
+    }
+
+    // find_chat_by_id only serves a cached chat of the requested tenant,
+    // the cache is keyed by chat id alone
+    async fn find_chat_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Chat>, RepositoryError> {
+        match self.cache.get_chat(id).await {
+            Ok(Some(chat)) if chat.tenant_id == tenant_id => return Ok(Some(chat)),
+            Ok(Some(_)) => return Ok(None),
+            _ => {}
+        }
+
+        let chat = self.repository.find_chat_by_id(tenant_id, id).await?;
+        if let Some(chat) = &chat {
+            let _ = self.cache.set_chat(chat).await;
+        }
+
+        Ok(chat)
+    }
+
+    async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        if let Err(err) = self.repository.save_chat(chat).await {
+            evict_if_stale(self.cache.as_ref(), chat, &err).await;
+            return Err(err);
+        }
+        self.refresh(chat).await;
+
+        Ok(())
+    }
+
+    // update_chat_title evicts the chat, the next read loads it with its title
+    async fn update_chat_title(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        title: &str,
+    ) -> Result<(), RepositoryError> {
+        self.repository
+            .update_chat_title(tenant_id, id, title)
+            .await?;
+        let _ = self.cache.delete_chat(id).await;
+
+        Ok(())
+    }
+
+    async fn list_chats_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        self.repository.list_chats_by_user(tenant_id, user_id).await
+    }
+
+    // list_chat_summaries always reads the repository, summaries are not cached
+    async fn list_chat_summaries(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        tag: Option<&str>,
+        after: Option<ChatCursor>,
+        limit: usize,
+    ) -> Result<Vec<ChatSummary>, RepositoryError> {
+        self.repository
+            .list_chat_summaries(tenant_id, user_id, tag, after, limit)
+            .await
+    }
+
+    async fn find_chat_summary(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ChatSummary>, RepositoryError> {
+        self.repository.find_chat_summary(tenant_id, id).await
+    }
+
+    // list_messages reads pages from the repository, the cached chat only holds the full history
+    async fn list_messages(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        self.repository
+            .list_messages(tenant_id, chat_id, query)
+            .await
+    }
+
+    // search_transcripts always reads the repository, the cache holds no index of the messages
+    async fn search_transcripts(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+        self.repository
+            .search_transcripts(tenant_id, user_id, query, limit)
+            .await
+    }
+
+    async fn list_inactive_chats(
+        &self,
+        inactive_before: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        self.repository
+            .list_inactive_chats(inactive_before, limit)
+            .await
+    }
+
+    // archive_chat_history evicts the chat, a cached copy would still hold the archived messages
+    async fn archive_chat_history(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let archived = self.repository.archive_chat_history(chat).await;
+        let _ = self.cache.delete_chat(chat.id).await;
+
+        archived
+    }
+
+    // purge_deleted_chats evicts the purged chats so a stale copy cannot outlive them
+    async fn purge_deleted_chats(
+        &self,
+        deleted_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PurgedChat>, RepositoryError> {
+        let purged = self.repository.purge_deleted_chats(deleted_before).await?;
+        for chat in &purged {
+            let _ = self.cache.delete_chat(chat.id).await;
+        }
+
+        Ok(purged)
+    }
+}
+
+// CachedUnitOfWork refreshes the chats a transaction wrote once it commits, a rolled back
+// transaction leaves the cache alone
+pub struct CachedUnitOfWork {
+    unit_of_work: Arc<dyn UnitOfWork>,
+    cache: Arc<dyn ChatCache>,
+}
+
+impl CachedUnitOfWork {
+    pub fn new(unit_of_work: Arc<dyn UnitOfWork>, cache: Arc<dyn ChatCache>) -> Self {
+        Self {
+            unit_of_work,
+            cache,
+        }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for CachedUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn ChatTransaction>, RepositoryError> {
+        Ok(Box::new(CachedChatTransaction {
+            tx: self.unit_of_work.begin().await?,
+            cache: self.cache.clone(),
+            written: vec![],
+        }))
+    }
+}
+
+struct CachedChatTransaction {
+    tx: Box<dyn ChatTransaction>,
+    cache: Arc<dyn ChatCache>,
+    written: Vec<Chat>,
+}
+
+#[async_trait]
+impl ChatTransaction for CachedChatTransaction {
+    async fn create_chat(&mut self, chat: &Chat) -> Result<(), RepositoryError> {
+        self.tx.create_chat(chat).await?;
+        self.written.push(chat.clone());
+
+        Ok(())
+    }
+
+    async fn save_chat(&mut self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        if let Err(err) = self.tx.save_chat(chat).await {
+            evict_if_stale(self.cache.as_ref(), chat, &err).await;
+            return Err(err);
+        }
+        self.written.push(chat.clone());
+
+        Ok(())
+    }
+
+    async fn record_usage(&mut self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        self.tx.record_usage(record).await
+    }
+
+    // commit refreshes the last write of every chat, earlier ones are stale by then
+    async fn commit(self: Box<Self>) -> Result<(), RepositoryError> {
+        self.tx.commit().await?;
+
+        for (position, chat) in self.written.iter().enumerate() {
+            if !self.written[position + 1..]
+                .iter()
+                .any(|later| later.id == chat.id)
+            {
+                refresh(self.cache.as_ref(), chat).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// refresh stores the saved chat, and evicts it when that fails so no stale copy is served
+async fn refresh(cache: &dyn ChatCache, chat: &Chat) {
+    if let Err(err) = cache.set_chat(chat).await {
+        tracing::warn!(chat_id = %chat.id, error = %err, "could not refresh the cached chat");
+        let _ = cache.delete_chat(chat.id).await;
+    }
+}
+
+// evict_if_stale drops the cached chat when a save found it was saved meanwhile, the copy the
+// save was loaded from may be the cached one and the next load has to read the repository
+async fn evict_if_stale(cache: &dyn ChatCache, chat: &Chat, err: &RepositoryError) {
+    if matches!(err, RepositoryError::ConcurrentModification(_)) {
+        let _ = cache.delete_chat(chat.id).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::unit_of_work::InMemoryUnitOfWork;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    #[derive(Default)]
+    struct FakeCache {
+        chats: Mutex<HashMap<Uuid, Chat>>,
+        failing: AtomicBool,
+    }
+
+    impl FakeCache {
+        fn check(&self) -> Result<(), CacheError> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(CacheError("connection refused".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl ChatCache for FakeCache {
+        async fn get_chat(&self, id: Uuid) -> Result<Option<Chat>, CacheError> {
+            self.check()?;
+            Ok(self.chats.lock().unwrap().get(&id).cloned())
+        }
+
+        async fn set_chat(&self, chat: &Chat) -> Result<(), CacheError> {
+            self.check()?;
+            self.chats.lock().unwrap().insert(chat.id, chat.clone());
+            Ok(())
+        }
+
+        async fn delete_chat(&self, id: Uuid) -> Result<(), CacheError> {
+            self.check()?;
+            self.chats.lock().unwrap().remove(&id);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingRepository {
+        chats: Mutex<HashMap<Uuid, Chat>>,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ChatRepository for CountingRepository {
+        async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+            self.chats.lock().unwrap().insert(chat.id, chat.clone());
+            Ok(())
+        }
+
+        async fn find_chat_by_id(
+            &self,
+            tenant_id: Uuid,
+            id: Uuid,
+        ) -> Result<Option<Chat>, RepositoryError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(self
+                .chats
+                .lock()
+                .unwrap()
+                .get(&id)
+                .filter(|chat| chat.tenant_id == tenant_id)
+                .cloned())
+        }
+
+        async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+            let mut chats = self.chats.lock().unwrap();
+            if matches!(chats.get(&chat.id), Some(stored) if stored.version != chat.version) {
+                return Err(RepositoryError::ConcurrentModification(chat.id));
+            }
+            chat.version += 1;
+            chats.insert(chat.id, chat.clone());
+            Ok(())
+        }
+
+        async fn update_chat_title(
+            &self,
+            _tenant_id: Uuid,
+            id: Uuid,
+            title: &str,
+        ) -> Result<(), RepositoryError> {
+            if let Some(chat) = self.chats.lock().unwrap().get_mut(&id) {
+                chat.title = Some(title.to_string());
+            }
+            Ok(())
+        }
+
+        async fn list_chats_by_user(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_chat_summaries(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _tag: Option<&str>,
+            _after: Option<ChatCursor>,
+            _limit: usize,
+        ) -> Result<Vec<ChatSummary>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn find_chat_summary(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<ChatSummary>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn list_messages(
+            &self,
+            _tenant_id: Uuid,
+            _chat_id: Uuid,
+            _query: &MessageQuery,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn search_transcripts(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_inactive_chats(
+            &self,
+            _inactive_before: chrono::DateTime<chrono::Utc>,
+            _limit: usize,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn archive_chat_history(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn purge_deleted_chats(
+            &self,
+            _deleted_before: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<PurgedChat>, RepositoryError> {
+            let mut chats = self.chats.lock().unwrap();
+            let purged: Vec<PurgedChat> = chats
+                .values()
+                .filter(|chat| chat.is_deleted())
+                .map(|chat| PurgedChat {
+                    id: chat.id,
+                    history_key: None,
+                })
+                .collect();
+            for chat in &purged {
+                chats.remove(&chat.id);
+            }
+            Ok(purged)
+        }
+    }
+
+    fn chat() -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::builder(model).build().unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reads_hit_the_cache() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let chat = chat();
+
+        cached.create_chat(&chat).await.unwrap();
+        assert_eq!(
+            cached
+                .find_chat_by_id(chat.tenant_id, chat.id)
+                .await
+                .unwrap(),
+            Some(chat.clone())
+        );
+        assert_eq!(repository.reads.load(Ordering::SeqCst), 0);
+
+        cache.chats.lock().unwrap().clear();
+        cached
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap();
+        cached
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap();
+        assert_eq!(repository.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_writes_through() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let mut chat = chat();
+        cached.create_chat(&chat).await.unwrap();
+
+        chat.token_usage = 42;
+        cached.save_chat(&mut chat).await.unwrap();
+
+        assert_eq!(cache.chats.lock().unwrap()[&chat.id].token_usage, 42);
+        assert_eq!(cache.chats.lock().unwrap()[&chat.id].version, 1);
+        assert_eq!(repository.chats.lock().unwrap()[&chat.id].token_usage, 42);
+    }
+
+    #[tokio::test]
+    async fn test_stale_save_evicts() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let mut chat = chat();
+        cached.create_chat(&chat).await.unwrap();
+        let mut stale = chat.clone();
+        cached.save_chat(&mut chat).await.unwrap();
+
+        assert!(matches!(
+            cached.save_chat(&mut stale).await,
+            Err(RepositoryError::ConcurrentModification(_))
+        ));
+        assert!(cache.chats.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_failures_fall_back_to_repository() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let chat = chat();
+        cache.failing.store(true, Ordering::SeqCst);
+
+        cached.create_chat(&chat).await.unwrap();
+
+        assert_eq!(
+            cached
+                .find_chat_by_id(chat.tenant_id, chat.id)
+                .await
+                .unwrap(),
+            Some(chat)
+        );
+        assert_eq!(repository.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cached_chats_are_scoped_by_tenant() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let chat = chat().with_tenant(Uuid::new_v4());
+        cached.create_chat(&chat).await.unwrap();
+
+        assert_eq!(
+            cached
+                .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(repository.reads.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_evicts_cached_chats() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let cached = CachedChatRepository::new(repository.clone(), cache.clone());
+        let mut deleted = chat();
+        let kept = chat();
+        cached.create_chat(&kept).await.unwrap();
+        deleted.delete().unwrap();
+        cached.create_chat(&deleted).await.unwrap();
+
+        let purged = cached
+            .purge_deleted_chats(chrono::Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, deleted.id);
+        assert!(!cache.chats.lock().unwrap().contains_key(&deleted.id));
+        assert!(cache.chats.lock().unwrap().contains_key(&kept.id));
+    }
+
+    #[tokio::test]
+    async fn test_unit_of_work_refreshes_on_commit() {
+        let repository = Arc::new(CountingRepository::default());
+        let cache = Arc::new(FakeCache::default());
+        let unit_of_work = CachedUnitOfWork::new(
+            Arc::new(InMemoryUnitOfWork::new(
+                repository.clone(),
+                Arc::new(InMemoryUsageRepository::new()),
+            )),
+            cache.clone(),
+        );
+        let mut chat = chat();
+
+        let mut tx = unit_of_work.begin().await.unwrap();
+        tx.create_chat(&chat).await.unwrap();
+        drop(tx);
+        assert!(cache.chats.lock().unwrap().is_empty());
+
+        let mut tx = unit_of_work.begin().await.unwrap();
+        tx.create_chat(&chat).await.unwrap();
+        chat.token_usage = 42;
+        tx.save_chat(&mut chat).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(cache.chats.lock().unwrap()[&chat.id].token_usage, 42);
+        assert_eq!(repository.chats.lock().unwrap()[&chat.id].token_usage, 42);
+    }
+}
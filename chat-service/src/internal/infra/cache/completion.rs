@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::message::Message;
+use crate::internal::infra::cache::chat::CacheError;
+
+// CompletionCache keeps the replies of the model by the hash of the request they answer
+#[async_trait]
+pub trait CompletionCache: Send + Sync {
+    async fn get_completion(&self, key: &str) -> Result<Option<Message>, CacheError>;
+
+    async fn set_completion(&self, key: &str, message: &Message) -> Result<(), CacheError>;
+}
+
+// InMemoryCompletionCache keeps the replies in the process for the ttl, the oldest reply is
+// dropped once max_entries are kept
+pub struct InMemoryCompletionCache {
+    entries: Mutex<HashMap<String, (Instant, Message)>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl InMemoryCompletionCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            max_entries,
+        }
+    }
+}
+
+#[async_trait]
+impl CompletionCache for InMemoryCompletionCache {
+    async fn get_completion(&self, key: &str) -> Result<Option<Message>, CacheError> {
+        let entries = self.entries.lock().map_err(|e| CacheError(e.to_string()))?;
+
+        Ok(entries
+            .get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < self.ttl)
+            .map(|(_, message)| message.clone()))
+    }
+
+    async fn set_completion(&self, key: &str, message: &Message) -> Result<(), CacheError> {
+        let mut entries = self.entries.lock().map_err(|e| CacheError(e.to_string()))?;
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key.to_string(), (Instant::now(), message.clone()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::testing::builder::test_model;
+
+    fn reply(content: &str) -> Message {
+        Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            content,
+            0,
+            test_model(),
+            chrono::Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_keeps_the_newest_replies() {
+        let cache = InMemoryCompletionCache::new(Duration::from_secs(60), 2);
+        cache.set_completion("a", &reply("A")).await.unwrap();
+        cache.set_completion("b", &reply("B")).await.unwrap();
+        cache.set_completion("c", &reply("C")).await.unwrap();
+
+        assert!(cache.get_completion("a").await.unwrap().is_none());
+        assert_eq!(
+            cache.get_completion("c").await.unwrap().unwrap().content,
+            "C"
+        );
+
+        let expired = InMemoryCompletionCache::new(Duration::ZERO, 2);
+        expired.set_completion("a", &reply("A")).await.unwrap();
+        assert!(expired.get_completion("a").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+use crate::internal::infra::cache::chat::{CacheError, ChatCache};
+use crate::internal::infra::cache::completion::CompletionCache;
+
+const KEY_PREFIX: &str = "chat-service:chat:";
+const COMPLETION_KEY_PREFIX: &str = "chat-service:completion:";
+
+// RedisChatCache stores chats as JSON with a TTL, so idle chats expire on their own
+pub struct RedisChatCache {
+    connection: ConnectionManager,
+    ttl: Duration,
+}
+
+impl RedisChatCache {
+    pub async fn connect(url: &str, ttl: Duration) -> Result<Self, CacheError> {
+        let client = redis::Client::open(url).map_err(cache_error)?;
+        let connection = ConnectionManager::new(client).await.map_err(cache_error)?;
+
+        Ok(Self { connection, ttl })
+    }
+
+    fn key(id: Uuid) -> String {
+        format!("{}{}", KEY_PREFIX, id)
+    }
+}
+
+fn cache_error(err: impl std::fmt::Display) -> CacheError {
+    CacheError(err.to_string())
+}
+
+#[async_trait]
+impl ChatCache for RedisChatCache {
+    async fn get_chat(&self, id: Uuid) -> Result<Option<Chat>, CacheError> {
+        let mut connection = self.connection.clone();
+        let value: Option<String> = connection.get(Self::key(id)).await.map_err(cache_error)?;
+
+        value
+            .map(|value| serde_json::from_str(&value).map_err(cache_error))
+            .transpose()
+    }
+
+    async fn set_chat(&self, chat: &Chat) -> Result<(), CacheError> {
+        let value = serde_json::to_string(chat).map_err(cache_error)?;
+        let mut connection = self.connection.clone();
+
+        connection
+            .set_ex(Self::key(chat.id), value, self.ttl.as_secs().max(1))
+            .await
+            .map_err(cache_error)
+    }
+
+    async fn delete_chat(&self, id: Uuid) -> Result<(), CacheError> {
+        let mut connection = self.connection.clone();
+
+        connection.del(Self::key(id)).await.map_err(cache_error)
+    }
+}
+
+#[async_trait]
+impl HealthCheck for RedisChatCache {
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    async fn check(&self) -> Result<(), HealthError> {
+        let mut connection = self.connection.clone();
+
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut connection)
+            .await
+            .map_err(|err| HealthError(err.to_string()))?;
+
+        Ok(())
+    }
+}
+
+// RedisCompletionCache stores the replies of the model as JSON with a TTL, shared by every
+// instance of the service
+pub struct RedisCompletionCache {
+    connection: ConnectionManager,
+    ttl: Duration,
+}
+
+impl RedisCompletionCache {
+    pub async fn connect(url: &str, ttl: Duration) -> Result<Self, CacheError> {
+        let client = redis::Client::open(url).map_err(cache_error)?;
+        let connection = ConnectionManager::new(client).await.map_err(cache_error)?;
+
+        Ok(Self { connection, ttl })
+    }
+
+    fn key(key: &str) -> String {
+        format!("{}{}", COMPLETION_KEY_PREFIX, key)
+    }
+}
+
+#[async_trait]
+impl CompletionCache for RedisCompletionCache {
+    async fn get_completion(&self, key: &str) -> Result<Option<Message>, CacheError> {
+        let mut connection = self.connection.clone();
+        let value: Option<String> = connection.get(Self::key(key)).await.map_err(cache_error)?;
+
+        value
+            .map(|value| serde_json::from_str(&value).map_err(cache_error))
+            .transpose()
+    }
+
+    async fn set_completion(&self, key: &str, message: &Message) -> Result<(), CacheError> {
+        let value = serde_json::to_string(message).map_err(cache_error)?;
+        let mut connection = self.connection.clone();
+
+        connection
+            .set_ex(Self::key(key), value, self.ttl.as_secs().max(1))
+            .await
+            .map_err(cache_error)
+    }
+}
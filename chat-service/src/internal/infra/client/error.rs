@@ -0,0 +1,9 @@
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("request failed: {0}")]
+    Request(String),
+    #[error("{message} ({status})")]
+    Api { status: u16, message: String },
+    #[error("invalid response: {0}")]
+    Decode(String),
+}
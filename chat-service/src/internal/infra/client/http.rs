@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::internal::infra::client::error::ClientError;
+use crate::internal::infra::client::sse::{SseDecoder, StreamItem};
+use crate::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+use crate::internal::usecase::list_chats::dto::ChatListOutputDTO;
+
+// ChatUpdate holds the settings an update changes, the fields left unset keep their value
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChatUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+// ChatClient talks to the HTTP API of the service with an API key or access token
+#[derive(Clone)]
+pub struct ChatClient {
+    client: reqwest::Client,
+    base_url: String,
+    credential: String,
+}
+
+impl ChatClient {
+    pub fn new(base_url: &str, credential: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            credential: credential.to_string(),
+        }
+    }
+
+    // create_chat starts a chat with the first message, rendering the system message from
+    // the template when one is named
+    pub async fn create_chat(
+        &self,
+        user_message: &str,
+        template: Option<&str>,
+        variables: &HashMap<String, String>,
+    ) -> Result<ChatCompletionOutputDTO, ClientError> {
+        let body = json!({
+            "user_message": user_message,
+            "template": template,
+            "variables": variables,
+        });
+        let request = self.client.post(self.url("/chats")).json(&body);
+
+        self.send(request).await
+    }
+
+    pub async fn send_message(
+        &self,
+        chat_id: Uuid,
+        user_message: &str,
+    ) -> Result<ChatCompletionOutputDTO, ClientError> {
+        let request = self
+            .client
+            .post(self.url(&format!("/chats/{}/messages", chat_id)))
+            .json(&json!({ "user_message": user_message }));
+
+        self.send(request).await
+    }
+
+    // stream_message sends a message and forwards every delta of the reply until the server
+    // ends the stream
+    pub async fn stream_message(
+        &self,
+        chat_id: Uuid,
+        user_message: &str,
+        sender: mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<(), ClientError> {
+        let request = self
+            .client
+            .get(self.url(&format!("/chats/{}/stream", chat_id)))
+            .query(&[("user_message", user_message)]);
+        let response = self.execute(request).await?;
+
+        let mut body = response.bytes_stream();
+        let mut decoder = SseDecoder::default();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|err| ClientError::Request(err.to_string()))?;
+            for event in decoder.push(&chunk) {
+                match event.into_item()? {
+                    StreamItem::Delta(output) => {
+                        if sender.send(output).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    StreamItem::Done => return Ok(()),
+                }
+            }
+        }
+
+        Err(ClientError::Decode(
+            "stream ended before the reply was complete".to_string(),
+        ))
+    }
+
+    pub async fn get_chat(&self, chat_id: Uuid) -> Result<ChatOutputDTO, ClientError> {
+        let request = self.client.get(self.url(&format!("/chats/{}", chat_id)));
+
+        self.send(request).await
+    }
+
+    pub async fn update_chat(
+        &self,
+        chat_id: Uuid,
+        update: &ChatUpdate,
+    ) -> Result<ChatOutputDTO, ClientError> {
+        let request = self
+            .client
+            .patch(self.url(&format!("/chats/{}", chat_id)))
+            .json(update);
+
+        self.send(request).await
+    }
+
+    // list_chats returns a page of the caller's chats, most recent activity first
+    pub async fn list_chats(
+        &self,
+        limit: Option<usize>,
+        cursor: Option<&str>,
+    ) -> Result<ChatListOutputDTO, ClientError> {
+        let mut request = self.client.get(self.url("/chats"));
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+        if let Some(cursor) = cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+
+        self.send(request).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ClientError> {
+        self.execute(request)
+            .await?
+            .json::<T>()
+            .await
+            .map_err(|err| ClientError::Decode(err.to_string()))
+    }
+
+    // execute authenticates the request and turns an error response into the message the
+    // server returned
+    async fn execute(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ClientError> {
+        let response = request
+            .bearer_auth(&self.credential)
+            .send()
+            .await
+            .map_err(|err| ClientError::Request(err.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = response
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|body| body.get("error")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| {
+                status
+                    .canonical_reason()
+                    .unwrap_or("request failed")
+                    .to_string()
+            });
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            message,
+        })
+    }
+}
@@ -0,0 +1,172 @@
+use serde::Deserialize;
+
+use crate::internal::infra::client::error::ClientError;
+use crate::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+
+pub const DONE_DATA: &str = "[DONE]";
+pub const ERROR_EVENT: &str = "error";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+// StreamItem is what an event of the chat stream carries, deltas until the terminal [DONE]
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamItem {
+    Delta(ChatCompletionOutputDTO),
+    Done,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamFailure {
+    code: u16,
+    error: String,
+}
+
+impl SseEvent {
+    // into_item reads a delta or the end of the stream, an error event becomes the
+    // failure the server reported
+    pub fn into_item(self) -> Result<StreamItem, ClientError> {
+        if self.event.as_deref() == Some(ERROR_EVENT) {
+            let failure: StreamFailure = serde_json::from_str(&self.data)
+                .map_err(|err| ClientError::Decode(err.to_string()))?;
+            return Err(ClientError::Api {
+                status: failure.code,
+                message: failure.error,
+            });
+        }
+        if self.data == DONE_DATA {
+            return Ok(StreamItem::Done);
+        }
+
+        serde_json::from_str(&self.data)
+            .map(StreamItem::Delta)
+            .map_err(|err| ClientError::Decode(err.to_string()))
+    }
+}
+
+// SseDecoder splits a server-sent event stream into events, chunks may end anywhere, even
+// inside a character, and the remainder waits for the next chunk
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    // push returns the events the chunk completed; comments such as keep-alives and events
+    // without data are skipped
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer
+            .extend(chunk.iter().filter(|byte| **byte != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|pair| pair == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = None;
+    let mut data: Vec<&str> = Vec::new();
+
+    for line in block.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_string()),
+            "data" => data.push(value),
+            _ => {}
+        }
+    }
+
+    if data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    #[test]
+    fn test_push() {
+        let mut decoder = SseDecoder::default();
+
+        assert!(decoder.push(b"data: {\"a\"").is_empty());
+        assert_eq!(
+            decoder.push(b":1}\r\n\r\n: keep-alive\n\nevent: error\ndata: x\n\ndata: [DO"),
+            vec![
+                SseEvent {
+                    event: None,
+                    data: "{\"a\":1}".to_string(),
+                },
+                SseEvent {
+                    event: Some("error".to_string()),
+                    data: "x".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            decoder.push(b"NE]\n\n"),
+            vec![SseEvent {
+                event: None,
+                data: DONE_DATA.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_push_split_character() {
+        let mut decoder = SseDecoder::default();
+        let bytes = "data: olá\n\n".as_bytes();
+
+        assert!(decoder.push(&bytes[..8]).is_empty());
+        assert_eq!(decoder.push(&bytes[8..])[0].data, "olá");
+    }
+
+    #[test]
+    fn test_into_item() {
+        let output = ChatCompletionOutputDTO {
+            chat_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            content: "Hi".to_string(),
+            candidates: vec![],
+        };
+        let delta = SseEvent {
+            event: None,
+            data: serde_json::to_string(&output).unwrap(),
+        };
+        assert_eq!(delta.into_item().unwrap(), StreamItem::Delta(output));
+
+        let done = SseEvent {
+            event: None,
+            data: DONE_DATA.to_string(),
+        };
+        assert_eq!(done.into_item().unwrap(), StreamItem::Done);
+
+        let failure = SseEvent {
+            event: Some(ERROR_EVENT.to_string()),
+            data: r#"{"code":409,"error":"chat has ended"}"#.to_string(),
+        };
+        assert!(matches!(
+            failure.into_item(),
+            Err(ClientError::Api { status: 409, .. })
+        ));
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::gateway::event_publisher::{EventPublisher, PublishError};
+
+// FanoutPublisher publishes every batch to each publisher in turn; a failure stops the batch,
+// which is published again to all of them, so the ones before it may see it twice
+pub struct FanoutPublisher {
+    publishers: Vec<Arc<dyn EventPublisher>>,
+}
+
+impl FanoutPublisher {
+    pub fn new(publishers: Vec<Arc<dyn EventPublisher>>) -> Self {
+        Self { publishers }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for FanoutPublisher {
+    async fn publish(&self, events: &[OutboxEvent]) -> Result<(), PublishError> {
+        for publisher in &self.publishers {
+            publisher.publish(events).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod fanout;
+pub mod redis;
+pub mod webhook;
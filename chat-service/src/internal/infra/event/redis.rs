@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::gateway::event_publisher::{EventPublisher, PublishError};
+
+// RedisStreamPublisher appends every event to a Redis stream, which keeps about max_len
+// entries so consumers that fall too far behind lose the oldest ones
+pub struct RedisStreamPublisher {
+    connection: ConnectionManager,
+    stream: String,
+    max_len: usize,
+}
+
+impl RedisStreamPublisher {
+    pub async fn connect(url: &str, stream: &str, max_len: usize) -> Result<Self, PublishError> {
+        let client = redis::Client::open(url).map_err(publish_error)?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(publish_error)?;
+
+        Ok(Self {
+            connection,
+            stream: stream.to_string(),
+            max_len,
+        })
+    }
+}
+
+fn publish_error(err: impl std::fmt::Display) -> PublishError {
+    PublishError(err.to_string())
+}
+
+#[async_trait]
+impl EventPublisher for RedisStreamPublisher {
+    // publish sends the batch in one atomic pipeline so it lands in the stream in order
+    async fn publish(&self, events: &[OutboxEvent]) -> Result<(), PublishError> {
+        let mut pipeline = redis::pipe();
+        pipeline.atomic();
+        for event in events {
+            let payload = serde_json::to_string(event).map_err(publish_error)?;
+            pipeline
+                .cmd("XADD")
+                .arg(&self.stream)
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(self.max_len)
+                .arg("*")
+                .arg("id")
+                .arg(event.id.to_string())
+                .arg("type")
+                .arg(event.event.name())
+                .arg("chat_id")
+                .arg(event.chat_id.to_string())
+                .arg("payload")
+                .arg(payload)
+                .ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        pipeline
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(publish_error)
+    }
+}
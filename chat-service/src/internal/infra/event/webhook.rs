@@ -0,0 +1,149 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::entity::webhook::{
+    Webhook, WebhookDelivery, WebhookEvent, WebhookPayload,
+};
+use crate::internal::domain::gateway::event_publisher::{EventPublisher, PublishError};
+use crate::internal::domain::repository::webhook::WebhookRepository;
+
+// WebhookPublisher turns the lifecycle events of the chats into deliveries for the webhooks of
+// their tenant that subscribe to them, the webhook job makes the calls
+pub struct WebhookPublisher {
+    repository: Arc<dyn WebhookRepository>,
+}
+
+impl WebhookPublisher {
+    pub fn new(repository: Arc<dyn WebhookRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+fn publish_error(err: impl std::fmt::Display) -> PublishError {
+    PublishError(err.to_string())
+}
+
+#[async_trait]
+impl EventPublisher for WebhookPublisher {
+    // publish enqueues the deliveries of the whole batch at once, so a failed batch enqueues
+    // nothing and is published again
+    async fn publish(&self, events: &[OutboxEvent]) -> Result<(), PublishError> {
+        let mut webhooks: HashMap<Uuid, Vec<Webhook>> = HashMap::new();
+        let mut deliveries = Vec::new();
+        for event in events {
+            let Some(kind) = WebhookEvent::of(&event.event) else {
+                continue;
+            };
+            let subscribed = match webhooks.entry(event.tenant_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    self.repository
+                        .list_webhooks(event.tenant_id)
+                        .await
+                        .map_err(publish_error)?,
+                ),
+            };
+
+            let payload = WebhookPayload::new(kind, event);
+            for webhook in subscribed.iter() {
+                if webhook.subscribes_to(kind) {
+                    deliveries
+                        .push(WebhookDelivery::new(webhook, &payload).map_err(publish_error)?);
+                }
+            }
+        }
+
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+        self.repository
+            .enqueue_deliveries(&deliveries)
+            .await
+            .map_err(publish_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::event::ChatEvent;
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+
+    fn outbox(tenant_id: Uuid, event: ChatEvent) -> OutboxEvent {
+        OutboxEvent {
+            id: Uuid::new_v4(),
+            tenant_id,
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            event,
+            occurred_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_enqueues_subscribed_events() {
+        let repository = Arc::new(InMemoryWebhookRepository::new());
+        let every = Webhook::new(DEFAULT_TENANT_ID, "https://example.com/all", None, &[]).unwrap();
+        let ended = Webhook::new(
+            DEFAULT_TENANT_ID,
+            "https://example.com/ended",
+            None,
+            &[WebhookEvent::ChatEnded],
+        )
+        .unwrap();
+        repository.create_webhook(&every).await.unwrap();
+        repository.create_webhook(&ended).await.unwrap();
+        let publisher = WebhookPublisher::new(repository.clone());
+
+        let created = outbox(
+            DEFAULT_TENANT_ID,
+            ChatEvent::ChatCreated {
+                model: "gpt-3.5-turbo".to_string(),
+            },
+        );
+        publisher
+            .publish(&[
+                created.clone(),
+                outbox(
+                    DEFAULT_TENANT_ID,
+                    ChatEvent::MessageAdded {
+                        message_id: Uuid::new_v4(),
+                        role: Role::User,
+                        tokens: 3,
+                    },
+                ),
+                outbox(DEFAULT_TENANT_ID, ChatEvent::ChatEnded),
+                outbox(Uuid::new_v4(), ChatEvent::ChatEnded),
+            ])
+            .await
+            .unwrap();
+
+        let due = repository
+            .due_deliveries(chrono::Utc::now(), 10)
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 3);
+        assert_eq!(
+            due.iter()
+                .filter(|delivery| delivery.webhook_id == ended.id)
+                .map(|delivery| delivery.event)
+                .collect::<Vec<_>>(),
+            vec![WebhookEvent::ChatEnded]
+        );
+        let first = due
+            .iter()
+            .find(|delivery| delivery.event == WebhookEvent::ChatCreated)
+            .unwrap();
+        assert_eq!(first.event_id, created.id);
+        let payload: WebhookPayload = serde_json::from_str(&first.payload).unwrap();
+        assert_eq!(payload.chat_id, Some(created.chat_id));
+    }
+}
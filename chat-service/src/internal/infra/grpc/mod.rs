@@ -0,0 +1,9 @@
+pub mod server;
+pub mod service;
+
+pub mod pb {
+    tonic::include_proto!("pb");
+
+    // FILE_DESCRIPTOR_SET describes the services of chat.proto to the reflection service
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("chat_descriptor");
+}
@@ -0,0 +1,134 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::transport::Server;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::internal::infra::grpc::pb;
+use crate::internal::infra::grpc::pb::chat_service_server::ChatServiceServer;
+use crate::internal::infra::grpc::service::ChatGrpcService;
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::authenticate::usecase::AuthenticateUseCase;
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::check_readiness::usecase::CheckReadinessUseCase;
+
+// HEALTH_REPORT_INTERVAL is how often the readiness checks refresh the gRPC health status
+pub const HEALTH_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct GrpcServer {
+    pub usecase: Arc<ChatCompletionStreamUseCase>,
+    pub authenticate: Arc<AuthenticateUseCase>,
+    pub port: u16,
+    pub readiness: Option<Arc<CheckReadinessUseCase>>,
+    pub shutdown: Shutdown,
+    pub request_timeout: Option<Duration>,
+    pub reflection: bool,
+}
+
+impl GrpcServer {
+    pub fn new(
+        usecase: Arc<ChatCompletionStreamUseCase>,
+        authenticate: Arc<AuthenticateUseCase>,
+        port: u16,
+    ) -> Self {
+        Self {
+            usecase,
+            authenticate,
+            port,
+            readiness: None,
+            shutdown: Shutdown::new(),
+            request_timeout: None,
+            reflection: false,
+        }
+    }
+
+    // with_shutdown stops the server once the shutdown is triggered and tracks open streams
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    // with_request_timeout bounds every call, clients can ask for a shorter deadline with
+    // grpc-timeout
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    // with_reflection serves grpc.reflection.v1alpha for the ChatService and the health service
+    pub fn with_reflection(mut self, reflection: bool) -> Self {
+        self.reflection = reflection;
+        self
+    }
+
+    // with_readiness reports the ChatService as not serving while a dependency is unavailable,
+    // without it the health service always reports serving
+    pub fn with_readiness(mut self, readiness: Arc<CheckReadinessUseCase>) -> Self {
+        self.readiness = Some(readiness);
+        self
+    }
+
+    // start serves the ChatService, the standard grpc.health.v1 service and the reflection
+    // service when enabled until the shutdown is triggered, then stops accepting calls and lets
+    // the open ones finish
+    pub async fn start(self) -> Result<(), tonic::transport::Error> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
+        let shutdown = self.shutdown.clone();
+        let service = ChatGrpcService::new(self.usecase, self.authenticate)
+            .with_shutdown(self.shutdown)
+            .with_request_timeout(self.request_timeout);
+        let (mut reporter, health) = tonic_health::server::health_reporter();
+        match self.readiness {
+            Some(readiness) => {
+                tokio::spawn(report_readiness(reporter, readiness));
+            }
+            None => {
+                reporter
+                    .set_serving::<ChatServiceServer<ChatGrpcService>>()
+                    .await
+            }
+        }
+
+        let reflection = self.reflection.then(|| {
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(pb::FILE_DESCRIPTOR_SET)
+                .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+                .build()
+                // both descriptor sets are generated at build time
+                .expect("the embedded descriptor sets are valid")
+        });
+
+        Server::builder()
+            .add_service(health)
+            .add_service(ChatServiceServer::new(service))
+            .add_optional_service(reflection)
+            .serve_with_shutdown(addr, async move { shutdown.triggered().await })
+            .await
+    }
+}
+
+// report_readiness keeps the health status of the server and the ChatService in line with
+// the readiness checks
+async fn report_readiness(mut reporter: HealthReporter, readiness: Arc<CheckReadinessUseCase>) {
+    let mut interval = tokio::time::interval(HEALTH_REPORT_INTERVAL);
+    loop {
+        interval.tick().await;
+        if readiness.execute().await.ready {
+            reporter
+                .set_service_status("", ServingStatus::Serving)
+                .await;
+            reporter
+                .set_serving::<ChatServiceServer<ChatGrpcService>>()
+                .await;
+        } else {
+            reporter
+                .set_service_status("", ServingStatus::NotServing)
+                .await;
+            reporter
+                .set_not_serving::<ChatServiceServer<ChatGrpcService>>()
+                .await;
+        }
+    }
+}
@@ -0,0 +1,755 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::{Ascii, MetadataMap, MetadataValue};
+use tonic::{Code, Request, Response, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::deadline::{request_timeout, with_deadline};
+use crate::internal::domain::error::{ChatError, ConfigError};
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::request_id::{self, with_request_id};
+use crate::internal::infra::grpc::pb::chat_service_server::ChatService;
+use crate::internal::infra::grpc::pb::{ChatRequest, ChatResponse};
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::infra::telemetry::propagation::{continue_trace, MetadataExtractor};
+use crate::internal::usecase::authenticate::dto::AuthenticationOutputDTO;
+use crate::internal::usecase::authenticate::usecase::AuthenticateUseCase;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatCompletionOutputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::error::UseCaseError;
+
+const STREAM_BUFFER_SIZE: usize = 32;
+pub const API_KEY_METADATA: &str = "x-api-key";
+pub const TIMEOUT_METADATA: &str = "grpc-timeout";
+pub const ERROR_DOMAIN: &str = "chat-service";
+pub const REQUEST_ID_METADATA: &str = "x-request-id";
+
+pub struct ChatGrpcService {
+    usecase: Arc<ChatCompletionStreamUseCase>,
+    authenticate: Arc<AuthenticateUseCase>,
+    shutdown: Shutdown,
+    request_timeout: Option<Duration>,
+}
+
+impl ChatGrpcService {
+    pub fn new(
+        usecase: Arc<ChatCompletionStreamUseCase>,
+        authenticate: Arc<AuthenticateUseCase>,
+    ) -> Self {
+        Self {
+            usecase,
+            authenticate,
+            shutdown: Shutdown::new(),
+            request_timeout: None,
+        }
+    }
+
+    // with_shutdown keeps every stream in flight until it ends, so the shutdown can drain them
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    // with_request_timeout is the deadline of every call, the shorter grpc-timeout of the client
+    // wins
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    // chat_stream_in runs the use case in a task that keeps the request span,
+    // so the streamed completion stays part of the caller's trace
+    async fn chat_stream_in(
+        &self,
+        request: Request<ChatRequest>,
+    ) -> Result<Response<ReceiverStream<Result<ChatResponse, Status>>>, Status> {
+        let credential = credentials(request.metadata())
+            .ok_or_else(|| to_status(UseCaseError::Unauthenticated))?;
+        let requested = match request.metadata().get(TIMEOUT_METADATA) {
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(parse_grpc_timeout)
+                    .ok_or_else(|| Status::invalid_argument("grpc-timeout is invalid"))?,
+            ),
+            None => None,
+        };
+        let timeout = request_timeout(self.request_timeout, requested);
+        let authenticated = self
+            .authenticate
+            .execute(&credential)
+            .await
+            .map_err(to_status)?;
+        let input = to_input(request.into_inner(), authenticated).map_err(|status| *status)?;
+        let in_flight = self
+            .shutdown
+            .begin()
+            .ok_or_else(|| Status::unavailable("server is shutting down"))?;
+        let usecase = self.usecase.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+
+        tokio::spawn(
+            request_id::inherit(async move {
+                let _in_flight = in_flight;
+                let (output_sender, mut output_receiver) =
+                    mpsc::channel::<ChatCompletionOutputDTO>(STREAM_BUFFER_SIZE);
+                let chunk_sender = sender.clone();
+
+                // a cancelled call drops the receiver, the use case stops once this stops forwarding
+                let forward = async move {
+                    loop {
+                        let output = tokio::select! {
+                            output = output_receiver.recv() => output,
+                            _ = chunk_sender.closed() => None,
+                        };
+                        let Some(output) = output else { break };
+                        if chunk_sender.send(Ok(to_response(output))).await.is_err() {
+                            break;
+                        }
+                    }
+                    drop(output_receiver);
+                };
+
+                // the deadline drops the use case along with the upstream completion it waits on
+                let execute = async {
+                    let execute = usecase.execute(input, output_sender);
+                    match timeout {
+                        Some(timeout) => with_deadline(timeout, execute).await?,
+                        None => execute.await,
+                    }
+                };
+                let (result, _) = tokio::join!(execute, forward);
+                if let Err(err) = result {
+                    tracing::error!(error = %err, "chat stream failed");
+                    let _ = sender.send(Err(to_status(err))).await;
+                }
+            })
+            .instrument(tracing::Span::current()),
+        );
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+#[tonic::async_trait]
+impl ChatService for ChatGrpcService {
+    type ChatStreamStream = ReceiverStream<Result<ChatResponse, Status>>;
+
+    async fn chat_stream(
+        &self,
+        request: Request<ChatRequest>,
+    ) -> Result<Response<Self::ChatStreamStream>, Status> {
+        let request_id = request
+            .metadata()
+            .get(REQUEST_ID_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .and_then(request_id::parse)
+            .unwrap_or_else(request_id::generate);
+        let span = tracing::info_span!(
+            "grpc_request",
+            rpc = "ChatService/ChatStream",
+            request_id = %request_id,
+        );
+        continue_trace(&span, &MetadataExtractor(request.metadata()));
+
+        let result = with_request_id(request_id.clone(), self.chat_stream_in(request))
+            .instrument(span)
+            .await;
+        // the id is echoed like the HTTP API does, on the response or on the failure
+        let Ok(value) = request_id.parse::<MetadataValue<Ascii>>() else {
+            return result;
+        };
+        match result {
+            Ok(mut response) => {
+                response.metadata_mut().insert(REQUEST_ID_METADATA, value);
+                Ok(response)
+            }
+            Err(mut status) => {
+                status.metadata_mut().insert(REQUEST_ID_METADATA, value);
+                Err(status)
+            }
+        }
+    }
+}
+
+// credentials reads an API key or access token from the authorization metadata as a bearer
+// token, or an API key from x-api-key
+fn credentials(metadata: &MetadataMap) -> Option<String> {
+    let bearer = metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    bearer
+        .or_else(|| {
+            metadata
+                .get(API_KEY_METADATA)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+// to_input validates the identifiers sent by the client, an empty chat_id starts a new chat;
+// user_id is optional and must match the authenticated user when set, the chat is looked up
+// in the tenant of the user; the status is boxed as it is large next to the input
+fn to_input(
+    request: ChatRequest,
+    authenticated: AuthenticationOutputDTO,
+) -> Result<ChatCompletionInputDTO, Box<Status>> {
+    let user_id = authenticated.user_id;
+    if !request.user_id.is_empty() {
+        let requested =
+            Uuid::parse_str(&request.user_id).map_err(|_| Box::new(invalid_field("user_id")))?;
+        if requested != user_id {
+            return Err(Box::new(Status::permission_denied(
+                "user_id does not match the authenticated user",
+            )));
+        }
+    }
+
+    let chat_id = if request.chat_id.is_empty() {
+        None
+    } else {
+        Some(Uuid::parse_str(&request.chat_id).map_err(|_| Box::new(invalid_field("chat_id")))?)
+    };
+
+    Ok(ChatCompletionInputDTO {
+        tenant_id: authenticated.tenant_id,
+        user_id,
+        chat_id,
+        user_message: request.user_message,
+        attachments: vec![],
+        template: None,
+        assistant: None,
+        idempotency_key: None,
+        // an empty stop list keeps the chat's stop sequences, proto3 cannot tell it from unset
+        overrides: ChatOverridesInputDTO {
+            model: request.model,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop: (!request.stop.is_empty()).then_some(request.stop),
+            presence_penalty: request.presence_penalty,
+            frequency_penalty: request.frequency_penalty,
+        },
+        labels: ChatLabelsInputDTO::default(),
+    })
+}
+
+// parse_grpc_timeout reads the grpc-timeout of a call, up to 8 digits followed by its unit
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (amount, unit) = value.split_at(value.len() - 1);
+    if !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 60 * 60)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+fn to_response(output: ChatCompletionOutputDTO) -> ChatResponse {
+    ChatResponse {
+        chat_id: output.chat_id.to_string(),
+        user_id: output.user_id.to_string(),
+        content: output.content,
+    }
+}
+
+// to_status maps use case failures to gRPC status codes, with google.rpc details clients can
+// act on: an ErrorInfo naming the failure, RetryInfo when a retry may succeed and the quota,
+// request field, precondition or resource at fault
+pub fn to_status(err: UseCaseError) -> Status {
+    let retry_after = match &err {
+        UseCaseError::RateLimited { retry_after } => Some(*retry_after),
+        UseCaseError::QuotaExceeded(err) => Some(err.retry_after()),
+        _ => None,
+    };
+    let mut details = error_details(&err);
+    if let Some(retry_after) = retry_after {
+        details.set_retry_info(Some(retry_after));
+    }
+
+    let mut status = Status::with_error_details(status_code(&err), err.to_string(), details);
+    if let Some(retry_after) = retry_after {
+        status
+            .metadata_mut()
+            .insert("retry-after", retry_after.as_secs().into());
+    }
+    status
+}
+
+// invalid_field rejects a malformed request field, naming it in a BadRequest detail
+fn invalid_field(field: &str) -> Status {
+    let description = format!("{field} is invalid");
+    let mut details = ErrorDetails::with_error_info("INVALID_INPUT", ERROR_DOMAIN, HashMap::new());
+    details.add_bad_request_violation(field, description.clone());
+
+    Status::with_error_details(Code::InvalidArgument, description, details)
+}
+
+fn status_code(err: &UseCaseError) -> Code {
+    match err {
+        UseCaseError::Unauthenticated => Code::Unauthenticated,
+        UseCaseError::ChatNotFound(_)
+        | UseCaseError::MessageNotFound(_)
+        | UseCaseError::TemplateNotFound(_)
+        | UseCaseError::AssistantNotFound(_)
+        | UseCaseError::DocumentNotFound(_)
+        | UseCaseError::MemoryNotFound(_)
+        | UseCaseError::ScheduledMessageNotFound(_)
+        | UseCaseError::WebhookNotFound(_)
+        | UseCaseError::BatchNotFound(_)
+        | UseCaseError::JobNotFound(_)
+        | UseCaseError::UserNotFound(_)
+        | UseCaseError::TenantNotFound(_) => Code::NotFound,
+        UseCaseError::UserAlreadyExists(_)
+        | UseCaseError::TenantAlreadyExists(_)
+        | UseCaseError::TemplateAlreadyExists(_)
+        | UseCaseError::AssistantAlreadyExists(_) => Code::AlreadyExists,
+        UseCaseError::Forbidden(_) => Code::PermissionDenied,
+        UseCaseError::IdempotencyKeyReused(_) => Code::FailedPrecondition,
+        UseCaseError::IdempotencyKeyInProgress(_) => Code::Aborted,
+        UseCaseError::InvalidInput(_) => Code::InvalidArgument,
+        UseCaseError::RateLimited { .. } | UseCaseError::QuotaExceeded(_) => {
+            Code::ResourceExhausted
+        }
+        UseCaseError::Domain(err) => match err {
+            ChatError::InvalidMessage(_)
+            | ChatError::InvalidUser(_)
+            | ChatError::InvalidModel(_)
+            | ChatError::InvalidTemplate(_)
+            | ChatError::MissingTemplateVariables(_)
+            | ChatError::InvalidDocument(_)
+            | ChatError::InvalidAttachment(_)
+            | ChatError::AttachmentsNotSupported(_)
+            | ChatError::InvalidAudio(_)
+            | ChatError::InvalidWebhook(_)
+            | ChatError::InvalidAssistant(_)
+            | ChatError::InvalidTag(_)
+            | ChatError::InvalidMetadata(_)
+            | ChatError::InvalidConfig(_)
+            | ChatError::ContentFlagged(_)
+            | ChatError::PromptInjection(_) => Code::InvalidArgument,
+            ChatError::InvalidStatus(_)
+            | ChatError::ChatEnded
+            | ChatError::ChatArchived
+            | ChatError::ChatDeleted
+            | ChatError::InvalidTransition { .. } => Code::FailedPrecondition,
+            ChatError::ModelNotAllowed(_) => Code::PermissionDenied,
+            ChatError::TokenLimitExceeded { .. } => Code::ResourceExhausted,
+            ChatError::ResponseFormatMismatch(_) => Code::Aborted,
+        },
+        UseCaseError::Gateway(GatewayError::Timeout(_)) | UseCaseError::DeadlineExceeded(_) => {
+            Code::DeadlineExceeded
+        }
+        UseCaseError::Gateway(GatewayError::Audit(_)) => Code::Internal,
+        UseCaseError::Gateway(_) | UseCaseError::Publish(_) | UseCaseError::Blob(_) => {
+            Code::Unavailable
+        }
+        UseCaseError::ToolRoundsExceeded(_) => Code::Aborted,
+        UseCaseError::StreamCancelled(_) => Code::Cancelled,
+        UseCaseError::Repository(RepositoryError::ConcurrentModification(_)) => Code::Aborted,
+        UseCaseError::Repository(_) => Code::Internal,
+    }
+}
+
+// error_details describes the failure beyond its code; the ErrorInfo reason is stable so
+// clients can branch on it rather than on the message, the RequestInfo is the id to quote to
+// support
+fn error_details(err: &UseCaseError) -> ErrorDetails {
+    let mut details = ErrorDetails::with_error_info(reason(err), ERROR_DOMAIN, HashMap::new());
+    if let Some(request_id) = request_id::current() {
+        details.set_request_info(request_id, "");
+    }
+    let message = err.to_string();
+
+    match err {
+        UseCaseError::ChatNotFound(id) | UseCaseError::Forbidden(id) => {
+            details.set_resource_info("chat", id.to_string(), "", message);
+        }
+        UseCaseError::MessageNotFound(id) => {
+            details.set_resource_info("message", id.to_string(), "", message);
+        }
+        UseCaseError::TemplateNotFound(name) => {
+            details.set_resource_info("prompt_template", name.clone(), "", message);
+        }
+        UseCaseError::DocumentNotFound(id) => {
+            details.set_resource_info("document", id.to_string(), "", message);
+        }
+        UseCaseError::MemoryNotFound(id) => {
+            details.set_resource_info("memory", id.to_string(), "", message);
+        }
+        UseCaseError::ScheduledMessageNotFound(id) => {
+            details.set_resource_info("scheduled_message", id.to_string(), "", message);
+        }
+        UseCaseError::WebhookNotFound(id) => {
+            details.set_resource_info("webhook", id.to_string(), "", message);
+        }
+        UseCaseError::AssistantNotFound(id) => {
+            details.set_resource_info("assistant", id.to_string(), "", message);
+        }
+        UseCaseError::BatchNotFound(id) => {
+            details.set_resource_info("batch", id.to_string(), "", message);
+        }
+        UseCaseError::JobNotFound(id) => {
+            details.set_resource_info("job", id.to_string(), "", message);
+        }
+        UseCaseError::UserNotFound(id) => {
+            details.set_resource_info("user", id.to_string(), "", message);
+        }
+        UseCaseError::TenantNotFound(id) => {
+            details.set_resource_info("tenant", id.to_string(), "", message);
+        }
+        UseCaseError::IdempotencyKeyReused(key) => {
+            details.add_precondition_failure_violation("IDEMPOTENCY_KEY", key.clone(), message);
+        }
+        UseCaseError::QuotaExceeded(err) => {
+            details.add_quota_failure_violation(
+                format!("{}:{}", err.scope, err.period),
+                format!("{} tokens per {} period", err.limit, err.period),
+            );
+        }
+        UseCaseError::Domain(ChatError::TokenLimitExceeded { limit, .. }) => {
+            details.add_quota_failure_violation("chat", format!("{limit} tokens per chat"));
+        }
+        UseCaseError::Domain(
+            ChatError::InvalidStatus(_)
+            | ChatError::ChatEnded
+            | ChatError::ChatArchived
+            | ChatError::ChatDeleted
+            | ChatError::InvalidTransition { .. },
+        ) => {
+            details.add_precondition_failure_violation("CHAT_STATUS", "chat", message);
+        }
+        UseCaseError::Domain(err) => {
+            if let Some(field) = invalid_field_name(err) {
+                details.add_bad_request_violation(field, message);
+            }
+        }
+        _ => {}
+    }
+    details
+}
+
+// invalid_field_name is the ChatRequest field a validation error is about
+fn invalid_field_name(err: &ChatError) -> Option<&'static str> {
+    match err {
+        ChatError::InvalidMessage(_)
+        | ChatError::ContentFlagged(_)
+        | ChatError::PromptInjection(_) => Some("user_message"),
+        ChatError::InvalidUser(_) => Some("user_id"),
+        ChatError::InvalidModel(_) => Some("model"),
+        ChatError::InvalidConfig(ConfigError::TemperatureOutOfRange(_)) => Some("temperature"),
+        ChatError::InvalidConfig(ConfigError::TopPOutOfRange(_)) => Some("top_p"),
+        _ => None,
+    }
+}
+
+fn reason(err: &UseCaseError) -> &'static str {
+    match err {
+        UseCaseError::Unauthenticated => "UNAUTHENTICATED",
+        UseCaseError::ChatNotFound(_) => "CHAT_NOT_FOUND",
+        UseCaseError::MessageNotFound(_) => "MESSAGE_NOT_FOUND",
+        UseCaseError::TemplateNotFound(_) => "TEMPLATE_NOT_FOUND",
+        UseCaseError::TemplateAlreadyExists(_) => "TEMPLATE_ALREADY_EXISTS",
+        UseCaseError::AssistantNotFound(_) => "ASSISTANT_NOT_FOUND",
+        UseCaseError::AssistantAlreadyExists(_) => "ASSISTANT_ALREADY_EXISTS",
+        UseCaseError::DocumentNotFound(_) => "DOCUMENT_NOT_FOUND",
+        UseCaseError::MemoryNotFound(_) => "MEMORY_NOT_FOUND",
+        UseCaseError::ScheduledMessageNotFound(_) => "SCHEDULED_MESSAGE_NOT_FOUND",
+        UseCaseError::WebhookNotFound(_) => "WEBHOOK_NOT_FOUND",
+        UseCaseError::BatchNotFound(_) => "BATCH_NOT_FOUND",
+        UseCaseError::JobNotFound(_) => "JOB_NOT_FOUND",
+        UseCaseError::UserNotFound(_) => "USER_NOT_FOUND",
+        UseCaseError::UserAlreadyExists(_) => "USER_ALREADY_EXISTS",
+        UseCaseError::TenantNotFound(_) => "TENANT_NOT_FOUND",
+        UseCaseError::TenantAlreadyExists(_) => "TENANT_ALREADY_EXISTS",
+        UseCaseError::Forbidden(_) => "FORBIDDEN",
+        UseCaseError::IdempotencyKeyReused(_) => "IDEMPOTENCY_KEY_REUSED",
+        UseCaseError::IdempotencyKeyInProgress(_) => "IDEMPOTENCY_KEY_IN_PROGRESS",
+        UseCaseError::InvalidInput(_) => "INVALID_INPUT",
+        UseCaseError::RateLimited { .. } => "RATE_LIMITED",
+        UseCaseError::QuotaExceeded(_) => "QUOTA_EXCEEDED",
+        UseCaseError::StreamCancelled(_) => "STREAM_CANCELLED",
+        UseCaseError::DeadlineExceeded(_) => "DEADLINE_EXCEEDED",
+        UseCaseError::ToolRoundsExceeded(_) => "TOOL_ROUNDS_EXCEEDED",
+        UseCaseError::Domain(err) => match err {
+            ChatError::InvalidStatus(_) => "INVALID_CHAT_STATUS",
+            ChatError::TokenLimitExceeded { .. } => "TOKEN_LIMIT_EXCEEDED",
+            ChatError::ChatEnded => "CHAT_ENDED",
+            ChatError::ChatArchived => "CHAT_ARCHIVED",
+            ChatError::ChatDeleted => "CHAT_DELETED",
+            ChatError::InvalidTransition { .. } => "INVALID_CHAT_TRANSITION",
+            ChatError::InvalidMessage(_) => "INVALID_MESSAGE",
+            ChatError::InvalidUser(_) => "INVALID_USER",
+            ChatError::InvalidModel(_) => "INVALID_MODEL",
+            ChatError::ContentFlagged(_) => "CONTENT_FLAGGED",
+            ChatError::PromptInjection(_) => "PROMPT_INJECTION",
+            ChatError::ResponseFormatMismatch(_) => "RESPONSE_FORMAT_MISMATCH",
+            ChatError::InvalidTemplate(_) => "INVALID_TEMPLATE",
+            ChatError::MissingTemplateVariables(_) => "MISSING_TEMPLATE_VARIABLES",
+            ChatError::InvalidDocument(_) => "INVALID_DOCUMENT",
+            ChatError::InvalidAttachment(_) => "INVALID_ATTACHMENT",
+            ChatError::AttachmentsNotSupported(_) => "ATTACHMENTS_NOT_SUPPORTED",
+            ChatError::ModelNotAllowed(_) => "MODEL_NOT_ALLOWED",
+            ChatError::InvalidAudio(_) => "INVALID_AUDIO",
+            ChatError::InvalidWebhook(_) => "INVALID_WEBHOOK",
+            ChatError::InvalidAssistant(_) => "INVALID_ASSISTANT",
+            ChatError::InvalidTag(_) => "INVALID_TAG",
+            ChatError::InvalidMetadata(_) => "INVALID_METADATA",
+            ChatError::InvalidConfig(_) => "INVALID_CONFIG",
+        },
+        UseCaseError::Gateway(GatewayError::Timeout(_)) => "UPSTREAM_TIMEOUT",
+        UseCaseError::Gateway(_) => "UPSTREAM_FAILURE",
+        UseCaseError::Repository(RepositoryError::ConcurrentModification(_)) => {
+            "CONCURRENT_MODIFICATION"
+        }
+        UseCaseError::Repository(_) => "STORAGE_FAILURE",
+        UseCaseError::Publish(_) => "PUBLISH_FAILURE",
+        UseCaseError::Blob(_) => "ARCHIVE_FAILURE",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::deadline::DeadlineExceeded;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+    fn authenticated(user_id: Uuid) -> AuthenticationOutputDTO {
+        AuthenticationOutputDTO {
+            user_id,
+            tenant_id: DEFAULT_TENANT_ID,
+        }
+    }
+
+    #[test]
+    fn test_to_input() {
+        let user_id = Uuid::new_v4();
+        let request = ChatRequest {
+            chat_id: "".to_string(),
+            user_id: user_id.to_string(),
+            user_message: "Hello!".to_string(),
+            model: Some("gpt-4o".to_string()),
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+
+        let input = to_input(request, authenticated(user_id)).unwrap();
+
+        assert_eq!(input.user_id, user_id);
+        assert_eq!(input.tenant_id, DEFAULT_TENANT_ID);
+        assert_eq!(input.chat_id, None);
+        assert_eq!(input.user_message, "Hello!");
+        assert_eq!(
+            input.overrides,
+            ChatOverridesInputDTO {
+                model: Some("gpt-4o".to_string()),
+                temperature: Some(0.2),
+                ..Default::default()
+            }
+        );
+
+        let request = ChatRequest {
+            chat_id: "".to_string(),
+            user_id: "".to_string(),
+            user_message: "Hello!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_input(request, authenticated(user_id)).unwrap().user_id,
+            user_id
+        );
+
+        let request = ChatRequest {
+            chat_id: "".to_string(),
+            user_id: Uuid::new_v4().to_string(),
+            user_message: "Hello!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_input(request, authenticated(user_id))
+                .unwrap_err()
+                .code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_credentials() {
+        let mut metadata = MetadataMap::new();
+        assert_eq!(credentials(&metadata), None);
+
+        metadata.insert(API_KEY_METADATA, "chs_header".parse().unwrap());
+        assert_eq!(credentials(&metadata), Some("chs_header".to_string()));
+
+        metadata.insert("authorization", "Bearer chs_bearer".parse().unwrap());
+        assert_eq!(credentials(&metadata), Some("chs_bearer".to_string()));
+    }
+
+    #[test]
+    fn test_to_input_invalid_ids() {
+        let request = ChatRequest {
+            chat_id: "".to_string(),
+            user_id: "not-a-uuid".to_string(),
+            user_message: "Hello!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_input(request, authenticated(Uuid::new_v4()))
+                .unwrap_err()
+                .code(),
+            tonic::Code::InvalidArgument
+        );
+
+        let request = ChatRequest {
+            chat_id: "not-a-uuid".to_string(),
+            user_id: "".to_string(),
+            user_message: "Hello!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_input(request, authenticated(Uuid::new_v4()))
+                .unwrap_err()
+                .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_to_status() {
+        let chat_id = Uuid::new_v4();
+
+        assert_eq!(
+            to_status(UseCaseError::Unauthenticated).code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            to_status(UseCaseError::ChatNotFound(chat_id)).code(),
+            tonic::Code::NotFound
+        );
+        assert_eq!(
+            to_status(UseCaseError::Forbidden(chat_id)).code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            to_status(UseCaseError::Domain(ChatError::ChatEnded)).code(),
+            tonic::Code::FailedPrecondition
+        );
+        assert_eq!(
+            to_status(UseCaseError::Domain(ChatError::TokenLimitExceeded {
+                usage: 5000,
+                limit: 4096
+            }))
+            .code(),
+            tonic::Code::ResourceExhausted
+        );
+        assert_eq!(
+            to_status(UseCaseError::DeadlineExceeded(DeadlineExceeded {
+                timeout: Duration::from_secs(1)
+            }))
+            .code(),
+            tonic::Code::DeadlineExceeded
+        );
+    }
+
+    #[tokio::test]
+    async fn test_to_status_details() {
+        let status = to_status(UseCaseError::RateLimited {
+            retry_after: Duration::from_secs(3),
+        });
+        let details = status.get_error_details();
+        assert_eq!(details.error_info().unwrap().reason, "RATE_LIMITED");
+        assert_eq!(details.error_info().unwrap().domain, ERROR_DOMAIN);
+        assert_eq!(
+            details.retry_info().unwrap().retry_delay,
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(status.metadata().get("retry-after").unwrap(), "3");
+
+        let status = to_status(UseCaseError::Domain(ChatError::TokenLimitExceeded {
+            usage: 5000,
+            limit: 4096,
+        }));
+        let details = status.get_error_details();
+        let violations = &details.quota_failure().unwrap().violations;
+        assert_eq!(violations[0].subject, "chat");
+
+        let status = to_status(UseCaseError::Domain(ChatError::InvalidConfig(
+            ConfigError::TemperatureOutOfRange(3.0),
+        )));
+        let details = status.get_error_details();
+        let violations = &details.bad_request().unwrap().field_violations;
+        assert_eq!(violations[0].field, "temperature");
+
+        let chat_id = Uuid::new_v4();
+        let status = to_status(UseCaseError::ChatNotFound(chat_id));
+        let details = status.get_error_details();
+        let resource = details.resource_info().unwrap();
+        assert_eq!(resource.resource_type, "chat");
+        assert_eq!(resource.resource_name, chat_id.to_string());
+        assert!(details.request_info().is_none());
+
+        let status = with_request_id("req-1".to_string(), async {
+            to_status(UseCaseError::ChatNotFound(chat_id))
+        })
+        .await;
+        assert_eq!(
+            status
+                .get_error_details()
+                .request_info()
+                .unwrap()
+                .request_id,
+            "req-1"
+        );
+
+        let request = ChatRequest {
+            chat_id: "not-a-uuid".to_string(),
+            user_message: "Hello!".to_string(),
+            ..Default::default()
+        };
+        let status = to_input(request, authenticated(Uuid::new_v4())).unwrap_err();
+        let details = status.get_error_details();
+        let violations = &details.bad_request().unwrap().field_violations;
+        assert_eq!(violations[0].field, "chat_id");
+    }
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_grpc_timeout("30S"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(
+            parse_grpc_timeout("99999999u"),
+            Some(Duration::from_micros(99999999))
+        );
+        assert_eq!(parse_grpc_timeout("5n"), Some(Duration::from_nanos(5)));
+        assert_eq!(parse_grpc_timeout("S"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("10s"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod heuristic;
+pub mod topic;
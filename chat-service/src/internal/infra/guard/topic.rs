@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::domain::output_filter::TopicClassifier;
+
+const CLASSIFIER_INSTRUCTION: &str = "You check the responses of an assistant against a list \
+     of numbered topics. Answer with the numbers of the topics the response below is about, \
+     separated by commas, or with 0 when it is about none of them, and nothing else.";
+// MAX_CLASSIFIED_LENGTH bounds how much of a response the classifier reads, in characters
+const MAX_CLASSIFIED_LENGTH: usize = 4000;
+
+// ModelTopicClassifier asks the chat model which of the topics a response is about
+pub struct ModelTopicClassifier {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    model: Model,
+}
+
+impl ModelTopicClassifier {
+    pub fn new(gateway: Arc<dyn ChatCompletionGateway>, model: Model) -> Self {
+        Self { gateway, model }
+    }
+}
+
+#[async_trait]
+impl TopicClassifier for ModelTopicClassifier {
+    async fn classify(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+        topics: &[String],
+    ) -> Result<Vec<String>, GatewayError> {
+        let listed: String = topics
+            .iter()
+            .enumerate()
+            .map(|(i, topic)| format!("\n{}. {}", i + 1, topic))
+            .collect();
+        let excerpt: String = content.chars().take(MAX_CLASSIFIED_LENGTH).collect();
+        let now = chrono::Utc::now();
+        let mut config = ChatConfig::default_for(self.model.clone());
+        config.temperature = 0.0;
+        config.response_format = ResponseFormat::default();
+        let mut request = Chat::new(
+            Uuid::new_v4(),
+            user_id,
+            Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                &format!("{}\n\nTopics:{}", CLASSIFIER_INSTRUCTION, listed),
+                0,
+                self.model.clone(),
+                now,
+            ),
+            vec![Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                &excerpt,
+                0,
+                self.model.clone(),
+                now,
+            )],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        )
+        .with_tenant(tenant_id);
+        request.refresh_token_usage();
+
+        let response = self.gateway.create_chat_completion(&request).await?;
+        Ok(parse_topics(&response.content, topics))
+    }
+}
+
+// parse_topics reads the topic numbers of the answer, the numbers out of the list are ignored
+fn parse_topics(answer: &str, topics: &[String]) -> Vec<String> {
+    let mut found: Vec<String> = vec![];
+    for topic in answer
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|word| word.parse::<usize>().ok())
+        .filter_map(|number| topics.get(number.checked_sub(1)?))
+    {
+        if !found.contains(topic) {
+            found.push(topic.clone());
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+
+    #[tokio::test]
+    async fn test_classify() {
+        let topics = vec!["politics".to_string(), "medicine".to_string()];
+        let gateway = Arc::new(
+            FakeCompletionGateway::new()
+                .reply("2")
+                .reply("0")
+                .reply("1, 2, 7"),
+        );
+        let classifier = ModelTopicClassifier::new(gateway.clone(), test_model());
+        let user_id = Uuid::new_v4();
+
+        assert_eq!(
+            classifier
+                .classify(DEFAULT_TENANT_ID, user_id, "Take two aspirins", &topics)
+                .await
+                .unwrap(),
+            vec!["medicine"]
+        );
+        assert!(classifier
+            .classify(DEFAULT_TENANT_ID, user_id, "The weather is nice", &topics)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            classifier
+                .classify(DEFAULT_TENANT_ID, user_id, "Both", &topics)
+                .await
+                .unwrap(),
+            vec!["politics", "medicine"]
+        );
+
+        let request = &gateway.received()[0];
+        assert!(request
+            .initial_system_message()
+            .content
+            .contains("2. medicine"));
+        assert_eq!(request.messages[0].content, "Take two aspirins");
+        // the classification is billed to the user the response is for
+        assert_eq!(request.tenant_id, DEFAULT_TENANT_ID);
+        assert_eq!(request.user_id, user_id);
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+
+// CachedHealthCheck reuses the last result for ttl, so frequent probes do not turn into
+// a request to a paid or rate limited API every few seconds
+pub struct CachedHealthCheck {
+    check: Arc<dyn HealthCheck>,
+    ttl: Duration,
+    last: Mutex<Option<(Instant, Result<(), HealthError>)>>,
+}
+
+impl CachedHealthCheck {
+    pub fn new(check: Arc<dyn HealthCheck>, ttl: Duration) -> Self {
+        Self {
+            check,
+            ttl,
+            last: Mutex::new(None),
+        }
+    }
+
+    fn cached_at(&self, now: Instant) -> Option<Result<(), HealthError>> {
+        match &*self.last.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some((checked_at, result)) if now.saturating_duration_since(*checked_at) < self.ttl => {
+                Some(result.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn store_at(&self, result: &Result<(), HealthError>, now: Instant) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Some((now, result.clone()));
+    }
+}
+
+#[async_trait]
+impl HealthCheck for CachedHealthCheck {
+    fn name(&self) -> &str {
+        self.check.name()
+    }
+
+    async fn check(&self) -> Result<(), HealthError> {
+        if let Some(result) = self.cached_at(Instant::now()) {
+            return result;
+        }
+
+        let result = self.check.check().await;
+        self.store_at(&result, Instant::now());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Default)]
+    struct CountingCheck {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl HealthCheck for CountingCheck {
+        fn name(&self) -> &str {
+            "provider:openai"
+        }
+
+        async fn check(&self) -> Result<(), HealthError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reuses_result_within_ttl() {
+        let inner = Arc::new(CountingCheck::default());
+        let check = CachedHealthCheck::new(inner.clone(), Duration::from_secs(30));
+
+        assert_eq!(check.name(), "provider:openai");
+        assert!(check.check().await.is_ok());
+        assert!(check.check().await.is_ok());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_expires_after_ttl() {
+        let check =
+            CachedHealthCheck::new(Arc::new(CountingCheck::default()), Duration::from_secs(30));
+        let now = Instant::now();
+        let failure = Err(HealthError("unreachable".to_string()));
+
+        assert_eq!(check.cached_at(now), None);
+        check.store_at(&failure, now);
+
+        assert_eq!(
+            check.cached_at(now + Duration::from_secs(29)),
+            Some(failure)
+        );
+        assert_eq!(check.cached_at(now + Duration::from_secs(30)), None);
+    }
+}
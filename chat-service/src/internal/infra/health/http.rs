@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+
+// HttpHealthCheck tells whether a provider API can be reached, any answer below 500 counts,
+// since probes go out without credentials and an unauthorized reply still proves reachability
+pub struct HttpHealthCheck {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpHealthCheck {
+    pub fn new(name: String, url: String) -> Self {
+        Self {
+            name,
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for HttpHealthCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), HealthError> {
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(|err| HealthError(err.to_string()))?;
+
+        if response.status().is_server_error() {
+            return Err(HealthError(format!(
+                "{} answered {}",
+                self.url,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
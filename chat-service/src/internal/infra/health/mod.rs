@@ -0,0 +1,6 @@
+pub mod cached;
+pub mod http;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+pub mod sql;
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+
+// PostgresHealthCheck makes sure a connection can be acquired and answers a query
+pub struct PostgresHealthCheck {
+    pool: PgPool,
+}
+
+impl PostgresHealthCheck {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for PostgresHealthCheck {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    async fn check(&self) -> Result<(), HealthError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|err| HealthError(err.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use sqlx::AnyPool;
+
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+
+// SqlHealthCheck makes sure a mysql or sqlite connection can be acquired and answers a query
+pub struct SqlHealthCheck {
+    name: String,
+    pool: AnyPool,
+}
+
+impl SqlHealthCheck {
+    pub fn new(name: &str, pool: AnyPool) -> Self {
+        Self {
+            name: name.to_string(),
+            pool,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for SqlHealthCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), HealthError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|err| HealthError(err.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use crate::internal::domain::deadline;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::infra::http::retry::{is_retryable_status, parse_retry_after, RetryPolicy};
+
+// RetryClient sends provider requests, retrying rate limits, transient server errors
+// and connection failures as the policy allows
+#[derive(Clone, Default)]
+pub struct RetryClient {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl RetryClient {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            policy,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn post(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.client.post(url)
+    }
+
+    // send returns the first successful response, the last failure becomes a GatewayError;
+    // only the request is retried, a stream that breaks after the response started is not.
+    // Within a request deadline every attempt is bounded by the time left, and no retry is
+    // scheduled past it
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, GatewayError> {
+        let started = Instant::now();
+        let mut attempt = 0;
+
+        loop {
+            let mut current = request
+                .try_clone()
+                .ok_or_else(|| GatewayError::Request("request cannot be retried".to_string()))?;
+            if let Some(remaining) = deadline::remaining() {
+                current = current.timeout(remaining);
+            }
+
+            let (error, retry_after) = match current.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| parse_retry_after(value, chrono::Utc::now()));
+                    let body = response.text().await.unwrap_or_default();
+                    let error = GatewayError::Api { status, body };
+
+                    if !is_retryable_status(status) {
+                        return Err(error);
+                    }
+                    (error, retry_after)
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    (GatewayError::Request(e.to_string()), None)
+                }
+                Err(e) => return Err(GatewayError::Request(e.to_string())),
+            };
+
+            match self
+                .policy
+                .next_delay(attempt, retry_after, started.elapsed())
+            {
+                Some(delay)
+                    if deadline::remaining().is_some_and(|remaining| delay >= remaining) =>
+                {
+                    return Err(error)
+                }
+                Some(delay) => {
+                    tracing::warn!(attempt, ?delay, error = %error, "retrying provider request");
+                    tokio::time::sleep(delay).await
+                }
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+// RetryPolicy bounds how often and how long a failed provider request is retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    // max_retries is the number of attempts after the first one, zero disables retries
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // max_elapsed caps the total time spent waiting between attempts
+    pub max_elapsed: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            max_elapsed: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    // backoff doubles the initial backoff on every attempt up to max_backoff,
+    // then picks a random delay in its upper half so clients do not retry in lockstep
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        let floor = ceiling / 2;
+
+        if ceiling <= floor {
+            return ceiling;
+        }
+
+        rand::thread_rng().gen_range(floor..=ceiling)
+    }
+
+    // next_delay returns how long to wait before retrying the given attempt, counted from zero,
+    // or None once the retries or the time budget are spent; a Retry-After from the provider
+    // wins over the computed backoff
+    pub fn next_delay(
+        &self,
+        attempt: u32,
+        retry_after: Option<Duration>,
+        elapsed: Duration,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+
+        let delay = retry_after.unwrap_or_else(|| self.backoff(attempt));
+        if elapsed.saturating_add(delay) > self.max_elapsed {
+            return None;
+        }
+
+        Some(delay)
+    }
+}
+
+// is_retryable_status tells whether a provider status is worth another attempt:
+// timeouts, rate limits and transient server errors
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+// parse_retry_after reads a Retry-After header given in seconds or as an HTTP date
+pub fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - now;
+
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+
+        for attempt in 0..10 {
+            let ceiling = (policy.initial_backoff * 2u32.pow(attempt)).min(policy.max_backoff);
+            let delay = policy.backoff(attempt);
+
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+        assert!(policy.backoff(u32::MAX) <= policy.max_backoff);
+    }
+
+    #[test]
+    fn test_next_delay() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            max_elapsed: Duration::from_secs(5),
+        };
+
+        assert!(policy.next_delay(0, None, Duration::ZERO).is_some());
+        assert_eq!(policy.next_delay(2, None, Duration::ZERO), None);
+        assert_eq!(
+            policy.next_delay(1, Some(Duration::from_secs(3)), Duration::ZERO),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            policy.next_delay(1, Some(Duration::from_secs(3)), Duration::from_secs(4)),
+            None
+        );
+        assert_eq!(
+            RetryPolicy::disabled().next_delay(0, None, Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_retryable_status() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(400));
+        assert!(!is_retryable_status(401));
+        assert!(!is_retryable_status(501));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::archive_chats::usecase::ArchiveChatsUseCase;
+
+// ArchiveJob periodically moves the histories of inactive chats to the blob store
+pub struct ArchiveJob {
+    usecase: Arc<ArchiveChatsUseCase>,
+    interval: Duration,
+}
+
+impl ArchiveJob {
+    pub fn new(usecase: Arc<ArchiveChatsUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run archives on every interval until the shutdown starts, a run in progress counts as in
+    // flight so no history is left half moved when the process stops
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(archived) => tracing::info!(archived, "archived inactive chats"),
+                Err(err) => tracing::warn!(error = %err, "could not archive inactive chats"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    #[tokio::test]
+    async fn test_run_archives_until_shutdown() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![message(Role::User, "Hello!")],
+            vec![],
+            ChatStatus::Ended,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+        let blobs = Arc::new(InMemoryBlobStore::new());
+
+        let usecase = Arc::new(ArchiveChatsUseCase::new(
+            repository.clone(),
+            blobs.clone(),
+            Duration::ZERO,
+            10,
+        ));
+        let shutdown = Shutdown::new();
+        let job =
+            tokio::spawn(ArchiveJob::new(usecase, Duration::from_millis(10)).run(shutdown.clone()));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_cold());
+        assert_eq!(blobs.keys().len(), 1);
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), job)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
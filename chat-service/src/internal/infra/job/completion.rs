@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::run_jobs::usecase::RunJobsUseCase;
+
+// CompletionJob periodically answers the completion jobs clients have queued
+pub struct CompletionJob {
+    usecase: Arc<RunJobsUseCase>,
+    interval: Duration,
+}
+
+impl CompletionJob {
+    pub fn new(usecase: Arc<RunJobsUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run answers the queued jobs on every interval until the shutdown starts, jobs being
+    // answered count as in flight so their results are saved before the pool closes
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(succeeded) => tracing::info!(succeeded, "answered completion jobs"),
+                Err(err) => tracing::warn!(error = %err, "could not run completion jobs"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::job::{Job, JobStatus};
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::repository::job::JobRepository;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::job::InMemoryJobRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+    use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+
+    #[tokio::test]
+    async fn test_run_answers_until_shutdown() {
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let repository = Arc::new(InMemoryJobRepository::new());
+        let job = Job::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        repository.create_job(&job).await.unwrap();
+
+        let chat_completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            Arc::new(InMemoryChatRepository::new()),
+            Arc::new(users),
+            test_model(),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+        let usecase = Arc::new(RunJobsUseCase::new(repository.clone(), chat_completion, 10));
+        let shutdown = Shutdown::new();
+        let worker = tokio::spawn(
+            CompletionJob::new(usecase, Duration::from_millis(10)).run(shutdown.clone()),
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let answered = repository
+            .find_job_by_id(DEFAULT_TENANT_ID, job.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(answered.status, JobStatus::Succeeded);
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), worker)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
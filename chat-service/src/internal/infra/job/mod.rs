@@ -0,0 +1,6 @@
+pub mod archive;
+pub mod completion;
+pub mod purge;
+pub mod relay;
+pub mod schedule;
+pub mod webhook;
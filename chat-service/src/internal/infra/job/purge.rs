@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::purge_deleted_chats::usecase::PurgeDeletedChatsUseCase;
+
+// PurgeJob periodically hard deletes the chats whose retention has passed
+pub struct PurgeJob {
+    usecase: Arc<PurgeDeletedChatsUseCase>,
+    interval: Duration,
+}
+
+impl PurgeJob {
+    pub fn new(usecase: Arc<PurgeDeletedChatsUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run purges on every interval until the shutdown starts, a purge in progress counts as
+    // in flight so the process waits for it before closing the pool
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(purged, "purged deleted chats"),
+                Err(err) => tracing::warn!(error = %err, "could not purge deleted chats"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    #[tokio::test]
+    async fn test_run_purges_until_shutdown() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Deleted,
+            0,
+            ChatConfig::default_for(model),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        let usecase = Arc::new(PurgeDeletedChatsUseCase::new(
+            repository.clone(),
+            Duration::ZERO,
+        ));
+        let shutdown = Shutdown::new();
+        let job =
+            tokio::spawn(PurgeJob::new(usecase, Duration::from_millis(10)).run(shutdown.clone()));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), job)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::relay_events::usecase::RelayEventsUseCase;
+
+// RelayJob periodically publishes the chat events waiting in the outbox
+pub struct RelayJob {
+    usecase: Arc<RelayEventsUseCase>,
+    interval: Duration,
+}
+
+impl RelayJob {
+    pub fn new(usecase: Arc<RelayEventsUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run relays on every interval until the shutdown starts, a relay in progress counts as
+    // in flight so its batch is marked published before the pool closes
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(published) => tracing::debug!(published, "published chat events"),
+                Err(err) => tracing::warn!(error = %err, "could not publish chat events"),
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::send_scheduled_messages::usecase::SendScheduledMessagesUseCase;
+
+// ScheduleJob periodically sends the scheduled messages whose send_at has been reached
+pub struct ScheduleJob {
+    usecase: Arc<SendScheduledMessagesUseCase>,
+    interval: Duration,
+}
+
+impl ScheduleJob {
+    pub fn new(usecase: Arc<SendScheduledMessagesUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run sends the due messages on every interval until the shutdown starts, messages being
+    // sent count as in flight so their replies are saved before the pool closes
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(sent) => tracing::info!(sent, "sent scheduled messages"),
+                Err(err) => tracing::warn!(error = %err, "could not send scheduled messages"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+    use crate::internal::infra::repository::memory::scheduled_message::InMemoryScheduledMessageRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::{test_model, ChatBuilder};
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+    use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+
+    #[tokio::test]
+    async fn test_run_sends_until_shutdown() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let chat = ChatBuilder::new().build();
+        chats.create_chat(&chat).await.unwrap();
+        let repository = Arc::new(InMemoryScheduledMessageRepository::new());
+        let scheduled = ScheduledMessage {
+            send_at: chrono::Utc::now(),
+            ..ScheduledMessage::new(
+                chat.tenant_id,
+                chat.user_id,
+                chat.id,
+                "Good morning",
+                chrono::Utc::now() + chrono::Duration::hours(1),
+            )
+            .unwrap()
+        };
+        repository
+            .create_scheduled_message(&scheduled)
+            .await
+            .unwrap();
+
+        let chat_completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            chats,
+            Arc::new(InMemoryUserRepository::new()),
+            test_model(),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+        let usecase = Arc::new(SendScheduledMessagesUseCase::new(
+            repository.clone(),
+            chat_completion,
+            10,
+        ));
+        let shutdown = Shutdown::new();
+        let job = tokio::spawn(
+            ScheduleJob::new(usecase, Duration::from_millis(10)).run(shutdown.clone()),
+        );
+
+        // the job is polled until it sent the message rather than given a fixed time to do it
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let found = repository
+                    .find_scheduled_message_by_id(scheduled.tenant_id, scheduled.id)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                if found.status == ScheduledStatus::Sent {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), job)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
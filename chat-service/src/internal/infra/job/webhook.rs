@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::usecase::deliver_webhooks::usecase::DeliverWebhooksUseCase;
+
+// WebhookJob periodically calls the webhooks of the due deliveries
+pub struct WebhookJob {
+    usecase: Arc<DeliverWebhooksUseCase>,
+    interval: Duration,
+}
+
+impl WebhookJob {
+    pub fn new(usecase: Arc<DeliverWebhooksUseCase>, interval: Duration) -> Self {
+        Self { usecase, interval }
+    }
+
+    // run delivers on every interval until the shutdown starts, a batch being delivered counts
+    // as in flight so its outcome is saved before the pool closes
+    pub async fn run(self, shutdown: Shutdown) {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                _ = interval.tick() => {}
+            }
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.usecase.execute().await {
+                Ok(0) => {}
+                Ok(delivered) => tracing::info!(delivered, "delivered webhooks"),
+                Err(err) => tracing::warn!(error = %err, "could not deliver webhooks"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::webhook::{
+        Webhook, WebhookDelivery, WebhookEvent, WebhookPayload,
+    };
+    use crate::internal::domain::gateway::webhook::{WebhookError, WebhookSender};
+    use crate::internal::domain::repository::webhook::WebhookRepository;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+
+    struct AcceptingSender;
+
+    #[async_trait]
+    impl WebhookSender for AcceptingSender {
+        async fn send(&self, _: &Webhook, _: &WebhookDelivery) -> Result<(), WebhookError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_delivers_until_shutdown() {
+        let repository = Arc::new(InMemoryWebhookRepository::new());
+        let webhook =
+            Webhook::new(DEFAULT_TENANT_ID, "https://example.com/hooks", None, &[]).unwrap();
+        repository.create_webhook(&webhook).await.unwrap();
+        let outbox = OutboxEvent {
+            id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            event: ChatEvent::ChatEnded,
+            occurred_at: chrono::Utc::now(),
+        };
+        let delivery = WebhookDelivery::new(
+            &webhook,
+            &WebhookPayload::new(WebhookEvent::ChatEnded, &outbox),
+        )
+        .unwrap();
+        repository.enqueue_deliveries(&[delivery]).await.unwrap();
+
+        let usecase = Arc::new(DeliverWebhooksUseCase::new(
+            repository.clone(),
+            Arc::new(AcceptingSender),
+            10,
+            3,
+            Duration::from_secs(30),
+        ));
+        let shutdown = Shutdown::new();
+        let job =
+            tokio::spawn(WebhookJob::new(usecase, Duration::from_millis(10)).run(shutdown.clone()));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(repository
+            .due_deliveries(chrono::Utc::now(), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), job)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
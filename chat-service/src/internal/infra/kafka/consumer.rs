@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::Message;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::Offset;
+use tokio::sync::mpsc;
+
+use crate::internal::infra::kafka::handler::KafkaRequestHandler;
+use crate::internal::infra::kafka::message::KafkaChatReply;
+use crate::internal::infra::shutdown::Shutdown;
+
+const REPLY_BUFFER_SIZE: usize = 32;
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+const SEEK_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct KafkaConfig {
+    pub brokers: String,
+    pub group_id: String,
+    pub request_topic: String,
+    pub reply_topic: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConsumerError {
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error("could not encode reply: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+// KafkaChatConsumer answers the chat requests of a topic on a reply topic, at least once:
+// the offset of a request is committed only after its chat is saved and its replies are
+// written; a request read again after a crash is answered from its stored response, except
+// streamed ones which run again
+pub struct KafkaChatConsumer {
+    consumer: StreamConsumer,
+    producer: FutureProducer,
+    handler: KafkaRequestHandler,
+    reply_topic: String,
+}
+
+impl KafkaChatConsumer {
+    pub fn new(config: &KafkaConfig, handler: KafkaRequestHandler) -> Result<Self, ConsumerError> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group_id)
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()?;
+        consumer.subscribe(&[config.request_topic.as_str()])?;
+
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("enable.idempotence", "true")
+            .create()?;
+
+        Ok(Self {
+            consumer,
+            producer,
+            handler,
+            reply_topic: config.reply_topic.clone(),
+        })
+    }
+
+    // run handles one request at a time until the shutdown starts, a request in progress counts
+    // as in flight so its offset is committed before the process exits
+    pub async fn run(self, shutdown: Shutdown) {
+        loop {
+            let received = tokio::select! {
+                biased;
+                _ = shutdown.triggered() => return,
+                received = self.consumer.recv() => received,
+            };
+            let message = match received {
+                Ok(message) => message,
+                Err(err) => {
+                    tracing::warn!(error = %err, "could not read chat request");
+                    tokio::time::sleep(RETRY_BACKOFF).await;
+                    continue;
+                }
+            };
+
+            let Some(_in_flight) = shutdown.begin() else {
+                return;
+            };
+            match self.process(message.payload().unwrap_or_default()).await {
+                Ok(()) => {
+                    if let Err(err) = self.consumer.commit_message(&message, CommitMode::Async) {
+                        tracing::warn!(error = %err, "could not commit chat request");
+                    }
+                }
+                Err(err) => {
+                    // reading the request again keeps it at least once
+                    tracing::warn!(error = %err, offset = message.offset(), "could not reply to chat request");
+                    if let Err(err) = self.consumer.seek(
+                        message.topic(),
+                        message.partition(),
+                        Offset::Offset(message.offset()),
+                        SEEK_TIMEOUT,
+                    ) {
+                        tracing::error!(error = %err, "could not rewind to chat request");
+                    }
+                    tokio::time::sleep(RETRY_BACKOFF).await;
+                }
+            }
+        }
+    }
+
+    // process runs the request while its replies are written, it fails when any reply was not
+    async fn process(&self, payload: &[u8]) -> Result<(), ConsumerError> {
+        let (sender, mut receiver) = mpsc::channel(REPLY_BUFFER_SIZE);
+        let write = async {
+            while let Some(reply) = receiver.recv().await {
+                self.send(&reply).await?;
+            }
+            Ok::<(), ConsumerError>(())
+        };
+
+        let (_, written) = tokio::join!(self.handler.handle(payload, sender), write);
+        written
+    }
+
+    async fn send(&self, reply: &KafkaChatReply) -> Result<(), ConsumerError> {
+        let payload = serde_json::to_vec(reply)?;
+        let key = reply.key();
+
+        self.producer
+            .send(
+                FutureRecord::to(&self.reply_topic)
+                    .key(&key)
+                    .payload(&payload),
+                SEND_TIMEOUT,
+            )
+            .await
+            .map_err(|(err, _)| err)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::internal::infra::kafka::message::{KafkaChatReply, KafkaChatRequest};
+use crate::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::error::UseCaseError;
+
+const STREAM_BUFFER_SIZE: usize = 32;
+
+// KafkaRequestHandler runs the chat requests read from Kafka, apart from the consumer so it
+// can be exercised without a broker
+pub struct KafkaRequestHandler {
+    completion: Arc<ChatCompletionUseCase>,
+    stream: Arc<ChatCompletionStreamUseCase>,
+}
+
+impl KafkaRequestHandler {
+    pub fn new(
+        completion: Arc<ChatCompletionUseCase>,
+        stream: Arc<ChatCompletionStreamUseCase>,
+    ) -> Self {
+        Self { completion, stream }
+    }
+
+    // handle runs the request in the payload and sends its replies, a failed request gets a
+    // failed reply; payloads that are not a request are skipped since no retry can fix them
+    pub async fn handle(&self, payload: &[u8], replies: mpsc::Sender<KafkaChatReply>) {
+        let request = match serde_json::from_slice::<KafkaChatRequest>(payload) {
+            Ok(request) => request,
+            Err(err) => {
+                tracing::warn!(error = %err, "skipping malformed chat request");
+                return;
+            }
+        };
+
+        let result = match request.stream {
+            true => self.stream_reply(&request, &replies).await,
+            false => self.completion.execute(request.to_input()).await,
+        };
+        let reply = match result {
+            Ok(output) => KafkaChatReply::completed(&request, output),
+            Err(err) => {
+                tracing::warn!(error = %err, request_id = %request.request_id, "chat request failed");
+                KafkaChatReply::failed(&request, &err)
+            }
+        };
+        let _ = replies.send(reply).await;
+    }
+
+    // stream_reply forwards the deltas of the reply as chunks while the model is answering
+    async fn stream_reply(
+        &self,
+        request: &KafkaChatRequest,
+        replies: &mpsc::Sender<KafkaChatReply>,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(STREAM_BUFFER_SIZE);
+        let forward = async {
+            while let Some(output) = receiver.recv().await {
+                if replies
+                    .send(KafkaChatReply::chunk(request, output))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        };
+
+        let (result, _) = tokio::join!(self.stream.execute(request.to_input(), sender), forward);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, TrimmingPolicy};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    struct FakeGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for FakeGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                "Hi, how can I help?",
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            for delta in ["Hi, ", "how can I help?"] {
+                let _ = sender.send(delta.to_string()).await;
+            }
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    async fn handler(user_id: Uuid) -> KafkaRequestHandler {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let config = ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        };
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let users = Arc::new(InMemoryUserRepository::new());
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+
+        KafkaRequestHandler::new(
+            Arc::new(ChatCompletionUseCase::new(
+                Arc::new(FakeGateway),
+                repository.clone(),
+                users.clone(),
+                model.clone(),
+                config.clone(),
+            )),
+            Arc::new(ChatCompletionStreamUseCase::new(
+                Arc::new(FakeGateway),
+                repository,
+                users,
+                model,
+                config,
+            )),
+        )
+    }
+
+    async fn replies(
+        handler: &KafkaRequestHandler,
+        payload: serde_json::Value,
+    ) -> Vec<KafkaChatReply> {
+        let (sender, mut receiver) = mpsc::channel(STREAM_BUFFER_SIZE);
+        handler
+            .handle(&serde_json::to_vec(&payload).unwrap(), sender)
+            .await;
+
+        let mut replies = vec![];
+        while let Some(reply) = receiver.recv().await {
+            replies.push(reply);
+        }
+        replies
+    }
+
+    #[tokio::test]
+    async fn test_handle() {
+        let user_id = Uuid::new_v4();
+        let handler = handler(user_id).await;
+
+        let completed = replies(
+            &handler,
+            serde_json::json!({ "request_id": "req-1", "user_id": user_id, "user_message": "Hello!" }),
+        )
+        .await;
+        assert!(matches!(
+            completed.as_slice(),
+            [KafkaChatReply::Completed { request_id, content, .. }]
+                if request_id == "req-1" && content == "Hi, how can I help?"
+        ));
+
+        let streamed = replies(
+            &handler,
+            serde_json::json!({
+                "request_id": "req-2",
+                "user_id": user_id,
+                "user_message": "Hello!",
+                "stream": true,
+            }),
+        )
+        .await;
+        assert_eq!(streamed.len(), 3);
+        assert!(matches!(streamed[0], KafkaChatReply::Chunk { .. }));
+        assert!(matches!(streamed[2], KafkaChatReply::Completed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_handle_failures() {
+        let handler = handler(Uuid::new_v4()).await;
+
+        assert!(
+            replies(&handler, serde_json::json!({ "user_message": "Hello!" }))
+                .await
+                .is_empty()
+        );
+
+        let unknown = Uuid::new_v4();
+        let failed = replies(
+            &handler,
+            serde_json::json!({
+                "request_id": "req-1",
+                "tenant_id": DEFAULT_TENANT_ID,
+                "user_id": unknown,
+                "user_message": "Hello!",
+            }),
+        )
+        .await;
+        assert!(matches!(
+            failed.as_slice(),
+            [KafkaChatReply::Failed { request_id, chat_id: None, .. }] if request_id == "req-1"
+        ));
+    }
+}
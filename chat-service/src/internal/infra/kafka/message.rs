@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatCompletionOutputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::error::UseCaseError;
+
+// KafkaChatRequest is a chat completion request read from the request topic, the services
+// producing to it are trusted so the user and tenant are taken as sent
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct KafkaChatRequest {
+    // request_id is echoed in every reply so the caller can match them to the request
+    pub request_id: String,
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
+    pub user_id: Uuid,
+    // chat_id continues an existing chat, a new one is started when it is empty
+    #[serde(default)]
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    // stream sends the reply as chunks while the model is answering, then the full reply
+    #[serde(default)]
+    pub stream: bool,
+    // overrides set the model and sampling of this message only, e.g. "model": "gpt-4o"
+    #[serde(flatten)]
+    pub overrides: ChatOverridesInputDTO,
+}
+
+impl KafkaChatRequest {
+    // to_input keys the request by its id, so a redelivered request is answered from the
+    // stored response instead of running again
+    pub fn to_input(&self) -> ChatCompletionInputDTO {
+        ChatCompletionInputDTO {
+            tenant_id: self.tenant_id.unwrap_or(DEFAULT_TENANT_ID),
+            user_id: self.user_id,
+            chat_id: self.chat_id,
+            user_message: self.user_message.clone(),
+            attachments: self.attachments.clone(),
+            template: None,
+            assistant: None,
+            idempotency_key: Some(format!("kafka:{}", self.request_id)),
+            overrides: self.overrides.clone(),
+            labels: ChatLabelsInputDTO::default(),
+        }
+    }
+}
+
+// KafkaChatReply is written to the reply topic, keyed by chat so the replies of a chat stay
+// in order on one partition
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KafkaChatReply {
+    Chunk {
+        request_id: String,
+        chat_id: Uuid,
+        user_id: Uuid,
+        content: String,
+    },
+    Completed {
+        request_id: String,
+        chat_id: Uuid,
+        user_id: Uuid,
+        content: String,
+    },
+    Failed {
+        request_id: String,
+        chat_id: Option<Uuid>,
+        error: String,
+    },
+}
+
+impl KafkaChatReply {
+    pub fn chunk(request: &KafkaChatRequest, output: ChatCompletionOutputDTO) -> Self {
+        KafkaChatReply::Chunk {
+            request_id: request.request_id.clone(),
+            chat_id: output.chat_id,
+            user_id: output.user_id,
+            content: output.content,
+        }
+    }
+
+    pub fn completed(request: &KafkaChatRequest, output: ChatCompletionOutputDTO) -> Self {
+        KafkaChatReply::Completed {
+            request_id: request.request_id.clone(),
+            chat_id: output.chat_id,
+            user_id: output.user_id,
+            content: output.content,
+        }
+    }
+
+    pub fn failed(request: &KafkaChatRequest, err: &UseCaseError) -> Self {
+        KafkaChatReply::Failed {
+            request_id: request.request_id.clone(),
+            chat_id: request.chat_id,
+            error: err.to_string(),
+        }
+    }
+
+    // key is the chat of the reply, or the request id when a new chat failed to start
+    pub fn key(&self) -> String {
+        match self {
+            KafkaChatReply::Chunk { chat_id, .. } | KafkaChatReply::Completed { chat_id, .. } => {
+                chat_id.to_string()
+            }
+            KafkaChatReply::Failed {
+                chat_id: Some(chat_id),
+                ..
+            } => chat_id.to_string(),
+            KafkaChatReply::Failed { request_id, .. } => request_id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_to_input() {
+        let user_id = Uuid::new_v4();
+        let request: KafkaChatRequest = serde_json::from_value(serde_json::json!({
+            "request_id": "req-1",
+            "user_id": user_id,
+            "user_message": "Hello!",
+            "temperature": 0.2,
+        }))
+        .unwrap();
+
+        let input = request.to_input();
+
+        assert!(!request.stream);
+        assert_eq!(input.overrides.temperature, Some(0.2));
+        assert_eq!(input.overrides.model, None);
+        assert_eq!(input.tenant_id, DEFAULT_TENANT_ID);
+        assert_eq!(input.user_id, user_id);
+        assert_eq!(input.chat_id, None);
+        assert_eq!(input.idempotency_key.as_deref(), Some("kafka:req-1"));
+    }
+
+    #[test]
+    fn test_reply_key() {
+        let request = KafkaChatRequest {
+            request_id: "req-1".to_string(),
+            tenant_id: None,
+            user_id: Uuid::new_v4(),
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            stream: false,
+            overrides: ChatOverridesInputDTO::default(),
+        };
+        let output = ChatCompletionOutputDTO {
+            chat_id: Uuid::new_v4(),
+            user_id: request.user_id,
+            content: "Hi!".to_string(),
+            candidates: vec![],
+        };
+
+        let completed = KafkaChatReply::completed(&request, output.clone());
+        assert_eq!(completed.key(), output.chat_id.to_string());
+        assert_eq!(
+            serde_json::to_value(&completed).unwrap()["type"],
+            "completed"
+        );
+
+        let failed = KafkaChatReply::failed(&request, &UseCaseError::UserNotFound(request.user_id));
+        assert_eq!(failed.key(), "req-1");
+    }
+}
@@ -0,0 +1,4 @@
+#[cfg(feature = "kafka")]
+pub mod consumer;
+pub mod handler;
+pub mod message;
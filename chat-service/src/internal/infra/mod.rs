@@ -0,0 +1,21 @@
+pub mod anthropic;
+pub mod blob;
+pub mod cache;
+pub mod client;
+pub mod event;
+pub mod grpc;
+pub mod guard;
+pub mod health;
+pub mod http;
+pub mod job;
+pub mod jwt;
+pub mod kafka;
+pub mod ollama;
+pub mod openai;
+pub mod provider;
+pub mod redaction;
+pub mod repository;
+pub mod shutdown;
+pub mod telemetry;
+pub mod web;
+pub mod webhook;
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::ollama::types::{
+    parse_stream_line, OllamaChatRequest, OllamaChatResponse,
+};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+pub const BASE_URL_ENV: &str = "OLLAMA_BASE_URL";
+
+// OllamaGateway talks to a local Ollama server, no API key is involved
+pub struct OllamaGateway {
+    client: RetryClient,
+    base_url: String,
+}
+
+impl OllamaGateway {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // from_env reads OLLAMA_BASE_URL, falling back to the default local server
+    pub fn from_env() -> Self {
+        let base_url = std::env::var(BASE_URL_ENV).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Self::new(base_url)
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+
+    async fn send(&self, request: &OllamaChatRequest) -> Result<reqwest::Response, GatewayError> {
+        let request = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(request);
+
+        self.client.send(request).await
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for OllamaGateway {
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let request = OllamaChatRequest::from_chat(chat);
+        let response = self.send(&request).await?;
+
+        let completion: OllamaChatResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        if let Some(error) = completion.error {
+            return Err(GatewayError::Request(error));
+        }
+
+        let content = completion.content();
+        if content.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            content,
+            completion.eval_count.unwrap_or_default(),
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        ))
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let request = OllamaChatRequest::from_chat(chat).streaming();
+        let response = self.send(&request).await?;
+
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut content = String::new();
+        let mut eval_count = 0;
+
+        'stream: while let Some(bytes) = stream.next().await {
+            let bytes = bytes.map_err(|e| GatewayError::Request(e.to_string()))?;
+            buffer.extend_from_slice(&bytes);
+
+            while let Some(position) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=position).collect();
+                let line = String::from_utf8_lossy(&line);
+
+                let chunk = match parse_stream_line(&line) {
+                    Some(chunk) => chunk.map_err(|e| GatewayError::Request(e.to_string()))?,
+                    None => continue,
+                };
+
+                if let Some(error) = chunk.error {
+                    return Err(GatewayError::Request(error));
+                }
+
+                let delta = chunk.content();
+                if !delta.is_empty() {
+                    content.push_str(delta);
+                    let _ = sender.send(delta.to_string()).await;
+                }
+
+                if chunk.done {
+                    eval_count = chunk.eval_count.unwrap_or_default();
+                    break 'stream;
+                }
+            }
+        }
+
+        if content.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            &content,
+            eval_count,
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        ))
+    }
+}
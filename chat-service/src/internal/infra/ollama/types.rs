@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::response_format::ResponseFormat;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct OllamaMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl From<&Message> for OllamaMessage {
+    fn from(message: &Message) -> Self {
+        Self {
+            role: message.role,
+            content: message.content.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OllamaOptions {
+    pub temperature: f32,
+    pub top_p: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    pub presence_penalty: f32,
+    pub frequency_penalty: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaChatRequest {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    pub stream: bool,
+    pub options: OllamaOptions,
+    // format is "json" or a JSON schema the reply must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+}
+
+impl OllamaChatRequest {
+    // from_chat builds the /api/chat body, Ollama streams by default so stream is always explicit
+    pub fn from_chat(chat: &Chat) -> Self {
+        let messages = chat
+            .prompt_messages()
+            .into_iter()
+            .map(OllamaMessage::from)
+            .collect();
+
+        Self {
+            model: chat.config.model.api_name().to_string(),
+            messages,
+            stream: false,
+            options: OllamaOptions {
+                temperature: chat.config.temperature,
+                top_p: chat.config.top_p,
+                stop: chat.config.stop.clone(),
+                presence_penalty: chat.config.presence_penalty,
+                frequency_penalty: chat.config.frequency_penalty,
+            },
+            format: match &chat.config.response_format {
+                ResponseFormat::Text => None,
+                ResponseFormat::JsonObject => Some(serde_json::Value::from("json")),
+                ResponseFormat::JsonSchema { schema, .. } => Some(schema.clone()),
+            },
+        }
+    }
+
+    pub fn streaming(mut self) -> Self {
+        self.stream = true;
+        self
+    }
+}
+
+// OllamaChatResponse is both the full reply and a single line of the streamed reply
+#[derive(Debug, Deserialize)]
+pub struct OllamaChatResponse {
+    pub model: Option<String>,
+    pub message: Option<OllamaMessage>,
+    #[serde(default)]
+    pub done: bool,
+    pub prompt_eval_count: Option<usize>,
+    pub eval_count: Option<usize>,
+    pub error: Option<String>,
+}
+
+impl OllamaChatResponse {
+    pub fn content(&self) -> &str {
+        self.message
+            .as_ref()
+            .map(|message| message.content.as_str())
+            .unwrap_or_default()
+    }
+}
+
+// parse_stream_line decodes one line of the newline-delimited JSON stream
+pub fn parse_stream_line(line: &str) -> Option<Result<OllamaChatResponse, serde_json::Error>> {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return None;
+    }
+
+    Some(serde_json::from_str(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::testing::golden::{assert_golden, golden_chat};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_from_chat() {
+        let model = Model::new("ollama/llama3".to_string(), 8192);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model,
+            chrono::Utc::now(),
+        );
+        chat.messages.push(message);
+
+        let request = OllamaChatRequest::from_chat(&chat);
+
+        assert_eq!(request.model, "llama3");
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[1].role, Role::User);
+
+        let body = serde_json::to_value(request.streaming()).unwrap();
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert!(body["options"].get("stop").is_none());
+        assert!(body.get("format").is_none());
+
+        chat.config.response_format = ResponseFormat::JsonObject;
+        let body = serde_json::to_value(OllamaChatRequest::from_chat(&chat)).unwrap();
+        assert_eq!(body["format"], "json");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = r#"{
+            "model": "llama3",
+            "created_at": "2024-05-01T12:00:00.000Z",
+            "message": {"role": "assistant", "content": "Hi there!"},
+            "done": true,
+            "total_duration": 5191566416,
+            "prompt_eval_count": 26,
+            "eval_count": 12
+        }"#;
+
+        let response: OllamaChatResponse = serde_json::from_str(body).unwrap();
+
+        assert!(response.done);
+        assert_eq!(response.content(), "Hi there!");
+        assert_eq!(response.eval_count, Some(12));
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let line = r#"{"model":"llama3","created_at":"2024-05-01T12:00:00.000Z","message":{"role":"assistant","content":"Hello"},"done":false}"#;
+
+        let chunk = parse_stream_line(line).unwrap().unwrap();
+        assert!(!chunk.done);
+        assert_eq!(chunk.content(), "Hello");
+
+        let chunk = parse_stream_line(r#"{"error":"model 'llama9' not found"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.error.as_deref(), Some("model 'llama9' not found"));
+
+        assert!(parse_stream_line("").is_none());
+        assert!(matches!(parse_stream_line("{"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_request_golden() {
+        let chat = golden_chat(Model::new("ollama/llama3".to_string(), 8192));
+
+        assert_golden("ollama_chat_request", &OllamaChatRequest::from_chat(&chat));
+        assert_golden(
+            "ollama_chat_stream_request",
+            &OllamaChatRequest::from_chat(&chat).streaming(),
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::tool::ToolCall;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::openai::endpoint::{AzureConfig, Endpoint};
+use crate::internal::infra::openai::types::{
+    parse_stream_line, ChatCompletionContent, ChatCompletionRequest, ChatCompletionResponse,
+    ChatCompletionStreamEvent, ToolCallAccumulator,
+};
+
+pub struct OpenAIGateway {
+    client: RetryClient,
+    api_key: String,
+    endpoint: Endpoint,
+}
+
+impl OpenAIGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_endpoint(api_key, Endpoint::default())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self::with_endpoint(api_key, Endpoint::OpenAI { base_url })
+    }
+
+    // azure sends requests to an Azure OpenAI deployment, the model name in the body is ignored
+    pub fn azure(api_key: String, config: AzureConfig) -> Self {
+        Self::with_endpoint(api_key, Endpoint::Azure(config))
+    }
+
+    pub fn with_endpoint(api_key: String, endpoint: Endpoint) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            endpoint,
+        }
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+
+    async fn send(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<reqwest::Response, GatewayError> {
+        let (header, value) = self.endpoint.auth_header(&self.api_key);
+        let request = self
+            .client
+            .post(self.endpoint.chat_completions_url())
+            .header(header, value)
+            .json(request);
+
+        self.client.send(request).await
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for OpenAIGateway {
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let request = ChatCompletionRequest::from_chat(chat);
+        let response = self.send(&request).await?;
+
+        let completion: ChatCompletionResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        let (choice, candidates) = completion
+            .into_choice()
+            .ok_or(GatewayError::EmptyResponse)?;
+
+        let tool_calls: Vec<ToolCall> = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice
+            .message
+            .content
+            .map(ChatCompletionContent::into_text)
+            .unwrap_or_default();
+        if content.is_empty() && tool_calls.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            &content,
+            0,
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        )
+        .with_tool_calls(tool_calls)
+        .with_candidates(candidates))
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id, model = %chat.config.model.name))]
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let request = ChatCompletionRequest::from_chat(chat).streaming();
+        let response = self.send(&request).await?;
+
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut content = String::new();
+        let mut tool_calls = ToolCallAccumulator::default();
+        // complete tells whether the provider said the reply was over, with [DONE] or a finish
+        // reason; some compatible servers end the stream after the finish reason without [DONE]
+        let mut complete = false;
+
+        'stream: while let Some(bytes) = stream.next().await {
+            let bytes = bytes.map_err(|e| GatewayError::Request(e.to_string()))?;
+            buffer.extend_from_slice(&bytes);
+
+            while let Some(position) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=position).collect();
+                let line = String::from_utf8_lossy(&line);
+
+                let event = match parse_stream_line(&line) {
+                    Some(event) => event.map_err(|e| GatewayError::Request(e.to_string()))?,
+                    None => continue,
+                };
+
+                let chunk = match event {
+                    ChatCompletionStreamEvent::Chunk(chunk) => chunk,
+                    ChatCompletionStreamEvent::Done => {
+                        complete = true;
+                        break 'stream;
+                    }
+                };
+
+                let choice = match chunk.choices.into_iter().next() {
+                    Some(choice) => choice,
+                    None => continue,
+                };
+                complete |= choice.finish_reason.is_some();
+                let delta = choice.delta;
+                for tool_call in delta.tool_calls {
+                    tool_calls.push(tool_call);
+                }
+
+                if let Some(delta) = delta.content.filter(|delta| !delta.is_empty()) {
+                    content.push_str(&delta);
+                    let _ = sender.send(delta).await;
+                }
+            }
+        }
+
+        // a connection dropped before the reply was over leaves it cut short, it is not passed
+        // off as a complete one
+        if !complete {
+            return Err(GatewayError::Request(
+                "stream ended before the reply was complete".to_string(),
+            ));
+        }
+
+        let tool_calls = tool_calls.finish();
+        if content.is_empty() && tool_calls.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            &content,
+            0,
+            chat.initial_system_message().model.clone(),
+            chrono::Utc::now(),
+        )
+        .with_tool_calls(tool_calls))
+    }
+}
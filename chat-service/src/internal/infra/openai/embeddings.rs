@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::openai::endpoint::DEFAULT_BASE_URL;
+use crate::internal::infra::openai::types::{EmbeddingsRequest, EmbeddingsResponse};
+
+pub const DEFAULT_EMBEDDINGS_MODEL: &str = "text-embedding-3-small";
+// MAX_BATCH_SIZE is the number of inputs the endpoint accepts in one request
+pub const MAX_BATCH_SIZE: usize = 2048;
+
+// OpenAIEmbeddingsGateway embeds texts with the OpenAI embeddings endpoint
+pub struct OpenAIEmbeddingsGateway {
+    client: RetryClient,
+    api_key: String,
+    base_url: String,
+    model: String,
+    dimensions: Option<u32>,
+}
+
+impl OpenAIEmbeddingsGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            base_url,
+            model: DEFAULT_EMBEDDINGS_MODEL.to_string(),
+            dimensions: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    // with_dimensions shortens the vectors, the model's full size is used otherwise
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+
+    async fn embed_batch(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+        let request = EmbeddingsRequest {
+            model: self.model.clone(),
+            input: inputs.to_vec(),
+            dimensions: self.dimensions,
+        };
+
+        let request = self
+            .client
+            .post(format!(
+                "{}/embeddings",
+                self.base_url.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .json(&request);
+        let response = self.client.send(request).await?;
+
+        let embeddings: EmbeddingsResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        embeddings.into_vectors(inputs.len())
+    }
+}
+
+#[async_trait]
+impl EmbeddingsGateway for OpenAIEmbeddingsGateway {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    #[instrument(skip_all, fields(model = %self.model, inputs = inputs.len()))]
+    async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+        let mut vectors = Vec::with_capacity(inputs.len());
+        for batch in inputs.chunks(MAX_BATCH_SIZE) {
+            vectors.extend(self.embed_batch(batch).await?);
+        }
+
+        Ok(vectors)
+    }
+}
@@ -0,0 +1,96 @@
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-02-01";
+
+// AzureConfig points at a model deployment of an Azure OpenAI resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct AzureConfig {
+    // endpoint is the resource URL, e.g. https://my-resource.openai.azure.com
+    pub endpoint: String,
+    pub deployment: String,
+    pub api_version: String,
+}
+
+impl AzureConfig {
+    pub fn new(endpoint: String, deployment: String) -> Self {
+        Self {
+            endpoint,
+            deployment,
+            api_version: DEFAULT_AZURE_API_VERSION.to_string(),
+        }
+    }
+}
+
+// Endpoint is where chat completions are sent and how the API key is presented
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+    OpenAI { base_url: String },
+    Azure(AzureConfig),
+}
+
+impl Endpoint {
+    pub fn chat_completions_url(&self) -> String {
+        match self {
+            Endpoint::OpenAI { base_url } => {
+                format!("{}/chat/completions", base_url.trim_end_matches('/'))
+            }
+            Endpoint::Azure(azure) => format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                azure.endpoint.trim_end_matches('/'),
+                azure.deployment,
+                azure.api_version
+            ),
+        }
+    }
+
+    // auth_header returns the header name and value carrying the API key
+    pub fn auth_header(&self, api_key: &str) -> (&'static str, String) {
+        match self {
+            Endpoint::OpenAI { .. } => ("Authorization", format!("Bearer {}", api_key)),
+            Endpoint::Azure(_) => ("api-key", api_key.to_string()),
+        }
+    }
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Endpoint::OpenAI {
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_endpoint() {
+        let endpoint = Endpoint::default();
+
+        assert_eq!(
+            endpoint.chat_completions_url(),
+            "https://api.openai.com/v1/chat/completions"
+        );
+        assert_eq!(
+            endpoint.auth_header("sk-test"),
+            ("Authorization", "Bearer sk-test".to_string())
+        );
+    }
+
+    #[test]
+    fn test_azure_endpoint() {
+        let endpoint = Endpoint::Azure(AzureConfig::new(
+            "https://my-resource.openai.azure.com/".to_string(),
+            "gpt-4o-prod".to_string(),
+        ));
+
+        assert_eq!(
+            endpoint.chat_completions_url(),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-02-01"
+        );
+        assert_eq!(
+            endpoint.auth_header("azure-key"),
+            ("api-key", "azure-key".to_string())
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod chat_completion;
+pub mod embeddings;
+pub mod endpoint;
+pub mod moderation;
+pub mod speech;
+pub mod transcription;
+pub mod types;
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::moderation::{ModerationGateway, ModerationResult};
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::openai::endpoint::DEFAULT_BASE_URL;
+use crate::internal::infra::openai::types::{ModerationRequest, ModerationResponse};
+
+pub const DEFAULT_MODERATION_MODEL: &str = "omni-moderation-latest";
+
+// OpenAIModerationGateway classifies content with the OpenAI moderation endpoint
+pub struct OpenAIModerationGateway {
+    client: RetryClient,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAIModerationGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            base_url,
+            model: DEFAULT_MODERATION_MODEL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+}
+
+#[async_trait]
+impl ModerationGateway for OpenAIModerationGateway {
+    #[instrument(skip_all, fields(model = %self.model))]
+    async fn moderate(&self, content: &str) -> Result<ModerationResult, GatewayError> {
+        let request = ModerationRequest {
+            model: self.model.clone(),
+            input: content.to_string(),
+        };
+
+        let request = self
+            .client
+            .post(format!(
+                "{}/moderations",
+                self.base_url.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .json(&request);
+        let response = self.client.send(request).await?;
+
+        let moderation: ModerationResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        moderation
+            .results
+            .into_iter()
+            .next()
+            .map(ModerationResult::from)
+            .ok_or(GatewayError::EmptyResponse)
+    }
+}
@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::internal::domain::entity::speech::{Speech, Voice};
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::speech::SpeechGateway;
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::openai::endpoint::DEFAULT_BASE_URL;
+use crate::internal::infra::openai::types::SpeechRequest;
+
+pub const DEFAULT_SPEECH_MODEL: &str = "tts-1";
+// MAX_INPUT_CHARS is the longest text the endpoint reads in one request
+pub const MAX_INPUT_CHARS: usize = 4096;
+
+// OpenAISpeechGateway reads text aloud with the OpenAI speech endpoint
+pub struct OpenAISpeechGateway {
+    client: RetryClient,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAISpeechGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            base_url,
+            model: DEFAULT_SPEECH_MODEL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+}
+
+#[async_trait]
+impl SpeechGateway for OpenAISpeechGateway {
+    fn max_input_chars(&self) -> usize {
+        MAX_INPUT_CHARS
+    }
+
+    #[instrument(skip_all, fields(model = %self.model, voice = %voice, chars = text.chars().count()))]
+    async fn synthesize(&self, text: &str, voice: Voice) -> Result<Speech, GatewayError> {
+        let request = SpeechRequest {
+            model: self.model.clone(),
+            input: text.to_string(),
+            voice,
+            response_format: "mp3".to_string(),
+        };
+
+        let request = self
+            .client
+            .post(format!(
+                "{}/audio/speech",
+                self.base_url.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .json(&request);
+        let response = self.client.send(request).await?;
+
+        let content = response
+            .bytes()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+        if content.is_empty() {
+            return Err(GatewayError::EmptyResponse);
+        }
+
+        Ok(Speech::new(voice, content.to_vec()))
+    }
+}
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::audio::Audio;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::transcription::TranscriptionGateway;
+use crate::internal::infra::http::client::RetryClient;
+use crate::internal::infra::http::retry::RetryPolicy;
+use crate::internal::infra::openai::endpoint::DEFAULT_BASE_URL;
+use crate::internal::infra::openai::types::{multipart_body, TranscriptionResponse};
+
+pub const DEFAULT_TRANSCRIPTION_MODEL: &str = "whisper-1";
+
+// OpenAITranscriptionGateway transcribes recordings with the OpenAI Whisper endpoint
+pub struct OpenAITranscriptionGateway {
+    client: RetryClient,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl OpenAITranscriptionGateway {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        Self {
+            client: RetryClient::default(),
+            api_key,
+            base_url,
+            model: DEFAULT_TRANSCRIPTION_MODEL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    // with_retry_policy changes how failed requests are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.client = self.client.with_policy(policy);
+        self
+    }
+}
+
+#[async_trait]
+impl TranscriptionGateway for OpenAITranscriptionGateway {
+    // transcribe sends the form as bytes rather than a reqwest multipart stream, so the
+    // request can be cloned and retried
+    #[instrument(skip_all, fields(model = %self.model, format = %audio.format, bytes = audio.content.len()))]
+    async fn transcribe(&self, audio: &Audio) -> Result<String, GatewayError> {
+        let boundary = format!("chat-service-{}", Uuid::new_v4().simple());
+        let body = multipart_body(
+            &boundary,
+            &[("model", &self.model), ("response_format", "json")],
+            ("file", &audio.file_name(), audio.format.mime_type()),
+            &audio.content,
+        );
+
+        let request = self
+            .client
+            .post(format!(
+                "{}/audio/transcriptions",
+                self.base_url.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(body);
+        let response = self.client.send(request).await?;
+
+        let transcription: TranscriptionResponse = response
+            .json()
+            .await
+            .map_err(|e| GatewayError::Request(e.to_string()))?;
+
+        Ok(transcription.text)
+    }
+}
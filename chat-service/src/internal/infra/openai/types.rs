@@ -0,0 +1,959 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::speech::Voice;
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::moderation::ModerationResult;
+
+const FUNCTION_TYPE: &str = "function";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatCompletionImageUrl {
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatCompletionContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ChatCompletionImageUrl },
+}
+
+// ChatCompletionContent is plain text, or the text and images of a message sent to a vision model
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ChatCompletionContent {
+    Text(String),
+    Parts(Vec<ChatCompletionContentPart>),
+}
+
+impl ChatCompletionContent {
+    // into_text drops the images and joins the text parts
+    pub fn into_text(self) -> String {
+        match self {
+            ChatCompletionContent::Text(text) => text,
+            ChatCompletionContent::Parts(parts) => parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    ChatCompletionContentPart::Text { text } => Some(text),
+                    ChatCompletionContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatCompletionMessage {
+    pub role: Role,
+    // content is null on assistant messages that only call tools
+    pub content: Option<ChatCompletionContent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ChatCompletionToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatCompletionMessage {
+    // from_message sends the attachments as image parts when the model reads images, they
+    // are left out otherwise, e.g. after the chat moved to a text model
+    pub fn from_message(message: &Message, vision: bool) -> Self {
+        let content =
+            if message.content.is_empty() && !message.tool_calls.is_empty() {
+                None
+            } else if vision && !message.attachments.is_empty() {
+                let text = Some(message.content.clone())
+                    .filter(|content| !content.is_empty())
+                    .map(|text| ChatCompletionContentPart::Text { text });
+                let images = message.attachments.iter().map(|attachment| {
+                    ChatCompletionContentPart::ImageUrl {
+                        image_url: ChatCompletionImageUrl {
+                            url: attachment.data_url(),
+                        },
+                    }
+                });
+                Some(ChatCompletionContent::Parts(
+                    text.into_iter().chain(images).collect(),
+                ))
+            } else {
+                Some(ChatCompletionContent::Text(message.content.clone()))
+            };
+
+        Self {
+            role: message.role,
+            content,
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(ChatCompletionToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatCompletionFunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatCompletionToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ChatCompletionFunctionCall,
+}
+
+impl From<&ToolCall> for ChatCompletionToolCall {
+    fn from(call: &ToolCall) -> Self {
+        Self {
+            id: call.id.clone(),
+            kind: FUNCTION_TYPE.to_string(),
+            function: ChatCompletionFunctionCall {
+                name: call.name.clone(),
+                arguments: call.arguments.clone(),
+            },
+        }
+    }
+}
+
+impl From<ChatCompletionToolCall> for ToolCall {
+    fn from(call: ChatCompletionToolCall) -> Self {
+        Self {
+            id: call.id,
+            name: call.function.name,
+            arguments: call.function.arguments,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChatCompletionFunction {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChatCompletionTool {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ChatCompletionFunction,
+}
+
+impl From<&ToolDefinition> for ChatCompletionTool {
+    fn from(tool: &ToolDefinition) -> Self {
+        Self {
+            kind: FUNCTION_TYPE.to_string(),
+            function: ChatCompletionFunction {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.parameters.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChatCompletionJsonSchema {
+    pub name: String,
+    pub schema: serde_json::Value,
+    pub strict: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatCompletionResponseFormat {
+    JsonObject,
+    JsonSchema {
+        json_schema: ChatCompletionJsonSchema,
+    },
+}
+
+impl ChatCompletionResponseFormat {
+    // from_format returns None for text, which is what the API defaults to
+    pub fn from_format(format: &ResponseFormat) -> Option<Self> {
+        match format {
+            ResponseFormat::Text => None,
+            ResponseFormat::JsonObject => Some(Self::JsonObject),
+            ResponseFormat::JsonSchema {
+                name,
+                schema,
+                strict,
+            } => Some(Self::JsonSchema {
+                json_schema: ChatCompletionJsonSchema {
+                    name: name.clone(),
+                    schema: schema.clone(),
+                    strict: *strict,
+                },
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatCompletionMessage>,
+    pub temperature: f32,
+    pub top_p: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    pub presence_penalty: f32,
+    pub frequency_penalty: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ChatCompletionTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ChatCompletionResponseFormat>,
+}
+
+impl ChatCompletionRequest {
+    // from_chat builds the request body with the system message followed by the chat history
+    pub fn from_chat(chat: &Chat) -> Self {
+        let vision = chat.config.model.supports_vision();
+        let messages = chat
+            .prompt_messages()
+            .into_iter()
+            .map(|message| ChatCompletionMessage::from_message(message, vision))
+            .collect();
+
+        Self {
+            model: chat.config.model.api_name().to_string(),
+            messages,
+            temperature: chat.config.temperature,
+            top_p: chat.config.top_p,
+            n: Some(chat.config.n).filter(|n| *n > 0),
+            stop: chat.config.stop.clone(),
+            presence_penalty: chat.config.presence_penalty,
+            frequency_penalty: chat.config.frequency_penalty,
+            stream: None,
+            tools: chat
+                .config
+                .tools
+                .iter()
+                .map(ChatCompletionTool::from)
+                .collect(),
+            response_format: ChatCompletionResponseFormat::from_format(
+                &chat.config.response_format,
+            ),
+        }
+    }
+
+    // streaming switches the request to server-sent events, a stream carries a single candidate
+    pub fn streaming(mut self) -> Self {
+        self.stream = Some(true);
+        self.n = None;
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionChoice {
+    pub index: u32,
+    pub message: ChatCompletionMessage,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionUsage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionResponse {
+    pub id: String,
+    pub model: String,
+    pub choices: Vec<ChatCompletionChoice>,
+    pub usage: Option<ChatCompletionUsage>,
+}
+
+impl ChatCompletionResponse {
+    // into_choice returns the first choice and the text of the others, which are the extra
+    // candidates asked for with n > 1
+    pub fn into_choice(self) -> Option<(ChatCompletionChoice, Vec<String>)> {
+        let mut choices = self.choices.into_iter();
+        let choice = choices.next()?;
+        let candidates = choices
+            .filter_map(|choice| choice.message.content)
+            .map(ChatCompletionContent::into_text)
+            .filter(|candidate| !candidate.is_empty())
+            .collect();
+
+        Some((choice, candidates))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionFunctionCallDelta {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+// ChatCompletionToolCallDelta is a fragment of the tool call at index, id and name come first
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionToolCallDelta {
+    pub index: usize,
+    pub id: Option<String>,
+    pub function: Option<ChatCompletionFunctionCallDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionDelta {
+    pub role: Option<Role>,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ChatCompletionToolCallDelta>,
+}
+
+// ToolCallAccumulator assembles the tool calls streamed as fragments
+#[derive(Debug, Default)]
+pub struct ToolCallAccumulator {
+    calls: Vec<ToolCall>,
+}
+
+impl ToolCallAccumulator {
+    pub fn push(&mut self, delta: ChatCompletionToolCallDelta) {
+        while self.calls.len() <= delta.index {
+            self.calls.push(ToolCall {
+                id: String::new(),
+                name: String::new(),
+                arguments: String::new(),
+            });
+        }
+
+        let call = &mut self.calls[delta.index];
+        if let Some(id) = delta.id {
+            call.id = id;
+        }
+        if let Some(function) = delta.function {
+            if let Some(name) = function.name {
+                call.name.push_str(&name);
+            }
+            if let Some(arguments) = function.arguments {
+                call.arguments.push_str(&arguments);
+            }
+        }
+    }
+
+    pub fn finish(self) -> Vec<ToolCall> {
+        self.calls
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionChunkChoice {
+    pub index: u32,
+    pub delta: ChatCompletionDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionChunk {
+    pub id: String,
+    pub model: String,
+    pub choices: Vec<ChatCompletionChunkChoice>,
+}
+
+#[derive(Debug)]
+pub enum ChatCompletionStreamEvent {
+    Chunk(ChatCompletionChunk),
+    Done,
+}
+
+// parse_stream_line decodes a single server-sent event line, skipping anything that is not data
+pub fn parse_stream_line(
+    line: &str,
+) -> Option<Result<ChatCompletionStreamEvent, serde_json::Error>> {
+    let data = line.trim().strip_prefix("data:")?.trim();
+
+    if data == "[DONE]" {
+        return Some(Ok(ChatCompletionStreamEvent::Done));
+    }
+
+    Some(serde_json::from_str(data).map(ChatCompletionStreamEvent::Chunk))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModerationRequest {
+    pub model: String,
+    pub input: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationCategoryResult {
+    pub flagged: bool,
+    // categories maps every policy category to whether the input violates it
+    #[serde(default)]
+    pub categories: BTreeMap<String, bool>,
+}
+
+impl From<ModerationCategoryResult> for ModerationResult {
+    fn from(result: ModerationCategoryResult) -> Self {
+        Self {
+            flagged: result.flagged,
+            categories: result
+                .categories
+                .into_iter()
+                .filter(|(_, flagged)| *flagged)
+                .map(|(category, _)| category)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationCategoryResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    // dimensions shortens the vectors of the models that support it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingData {
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingsResponse {
+    pub model: String,
+    pub data: Vec<EmbeddingData>,
+}
+
+impl EmbeddingsResponse {
+    // into_vectors orders the vectors like the inputs, every input must have one
+    pub fn into_vectors(mut self, inputs: usize) -> Result<Vec<Vec<f32>>, GatewayError> {
+        self.data.sort_by_key(|data| data.index);
+        if self.data.len() != inputs
+            || self
+                .data
+                .iter()
+                .enumerate()
+                .any(|(i, data)| data.index != i)
+        {
+            return Err(GatewayError::Request(format!(
+                "expected {} embeddings, got {}",
+                inputs,
+                self.data.len()
+            )));
+        }
+
+        Ok(self.data.into_iter().map(|data| data.embedding).collect())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: Voice,
+    pub response_format: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+}
+
+// multipart_body encodes the fields and the file, given as its field name, file name and
+// content type, as a multipart/form-data body separated by the boundary
+pub fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, &str)],
+    file: (&str, &str, &str),
+    content: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(content.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+
+    let (name, file_name, content_type) = file;
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+             Content-Type: {}\r\n\r\n",
+            boundary, name, file_name, content_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::domain::entity::attachment::Attachment;
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus, TrimmingPolicy};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::testing::golden::{assert_golden, golden_chat};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_from_chat() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let config = ChatConfig {
+            model: Model::new("gpt-3.5-turbo".to_string(), 4096),
+            temperature: 0.7,
+            top_p: 1.0,
+            n: 0,
+            stop: vec![],
+            max_tokens: 5000,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        );
+        let message = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        chat.messages.push(message);
+
+        let request = ChatCompletionRequest::from_chat(&chat);
+
+        assert_eq!(request.model, "gpt-3.5-turbo");
+        assert_eq!(request.n, None);
+        assert_eq!(
+            request.messages,
+            vec![
+                ChatCompletionMessage {
+                    role: Role::System,
+                    content: Some(ChatCompletionContent::Text(
+                        "You are a helpful assistant.".to_string()
+                    )),
+                    tool_calls: vec![],
+                    tool_call_id: None,
+                },
+                ChatCompletionMessage {
+                    role: Role::User,
+                    content: Some(ChatCompletionContent::Text("Hello!".to_string())),
+                    tool_calls: vec![],
+                    tool_call_id: None,
+                },
+            ]
+        );
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert!(body.get("stop").is_none());
+        assert!(body.get("n").is_none());
+        assert!(body.get("tools").is_none());
+        assert!(body.get("response_format").is_none());
+
+        chat.config.n = 3;
+        chat.config.stop = vec!["\n\n".to_string()];
+        chat.config.presence_penalty = 0.5;
+        let request = ChatCompletionRequest::from_chat(&chat);
+        assert_eq!(request.n, Some(3));
+        assert_eq!(request.stop, vec!["\n\n".to_string()]);
+        assert_eq!(request.presence_penalty, 0.5);
+        assert_eq!(request.streaming().n, None);
+    }
+
+    #[test]
+    fn test_attachments() {
+        let message = |model: &Model| {
+            Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                "What is in this image?",
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+            .with_attachments(vec![Attachment::base64("image/png", "iVBORw0=")])
+        };
+        let chat = |model: Model| {
+            Chat::new(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                Message::new(
+                    Uuid::new_v4(),
+                    Role::System,
+                    "You are a helpful assistant.",
+                    0,
+                    model.clone(),
+                    chrono::Utc::now(),
+                ),
+                vec![message(&model)],
+                vec![],
+                ChatStatus::Active,
+                0,
+                ChatConfig::default_for(model),
+            )
+        };
+
+        let body = serde_json::to_value(ChatCompletionRequest::from_chat(&chat(Model::new(
+            "gpt-4o".to_string(),
+            128000,
+        ))))
+        .unwrap();
+        assert_eq!(
+            body["messages"][1]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0="}}
+            ])
+        );
+
+        let body = serde_json::to_value(ChatCompletionRequest::from_chat(&chat(Model::new(
+            "gpt-3.5-turbo".to_string(),
+            4096,
+        ))))
+        .unwrap();
+        assert_eq!(body["messages"][1]["content"], "What is in this image?");
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let body = multipart_body(
+            "b",
+            &[("model", "whisper-1")],
+            ("file", "audio.wav", "audio/wav"),
+            b"RIFF",
+        );
+
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\n\
+             Content-Type: audio/wav\r\n\r\nRIFF\r\n--b--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_response_format() {
+        let format = ResponseFormat::JsonSchema {
+            name: "weather".to_string(),
+            schema: serde_json::json!({"type": "object"}),
+            strict: true,
+        };
+
+        let body =
+            serde_json::to_value(ChatCompletionResponseFormat::from_format(&format)).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "weather", "schema": {"type": "object"}, "strict": true}
+            })
+        );
+
+        let body = serde_json::to_value(ChatCompletionResponseFormat::from_format(
+            &ResponseFormat::JsonObject,
+        ))
+        .unwrap();
+        assert_eq!(body, serde_json::json!({"type": "json_object"}));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = r#"{
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1677652288,
+            "model": "gpt-3.5-turbo-0613",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi there!"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 9, "completion_tokens": 12, "total_tokens": 21}
+        }"#;
+
+        let response: ChatCompletionResponse = serde_json::from_str(body).unwrap();
+
+        assert_eq!(response.choices.len(), 1);
+        assert_eq!(
+            response.choices[0].message.content,
+            Some(ChatCompletionContent::Text("Hi there!".to_string()))
+        );
+        assert_eq!(response.usage.unwrap().total_tokens, 21);
+    }
+
+    #[test]
+    fn test_into_choice() {
+        let body = r#"{
+            "id": "chatcmpl-123",
+            "model": "gpt-4o",
+            "choices": [
+                {"index": 0, "message": {"role": "assistant", "content": "Hi!"}, "finish_reason": "stop"},
+                {"index": 1, "message": {"role": "assistant", "content": "Hello!"}, "finish_reason": "stop"},
+                {"index": 2, "message": {"role": "assistant", "content": ""}, "finish_reason": "stop"}
+            ]
+        }"#;
+
+        let response: ChatCompletionResponse = serde_json::from_str(body).unwrap();
+        let (choice, candidates) = response.into_choice().unwrap();
+
+        assert_eq!(
+            choice.message.content,
+            Some(ChatCompletionContent::Text("Hi!".to_string()))
+        );
+        assert_eq!(candidates, vec!["Hello!".to_string()]);
+
+        let empty: ChatCompletionResponse =
+            serde_json::from_str(r#"{"id": "1", "model": "gpt-4o", "choices": []}"#).unwrap();
+        assert!(empty.into_choice().is_none());
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let line = r#"data: {"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268190,"model":"gpt-3.5-turbo-0613","choices":[{"index":0,"delta":{"content":"Hello"},"finish_reason":null}]}"#;
+
+        match parse_stream_line(line) {
+            Some(Ok(ChatCompletionStreamEvent::Chunk(chunk))) => {
+                assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("Hello"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert!(matches!(
+            parse_stream_line("data: [DONE]"),
+            Some(Ok(ChatCompletionStreamEvent::Done))
+        ));
+        assert!(parse_stream_line("").is_none());
+        assert!(parse_stream_line(": keep-alive").is_none());
+        assert!(matches!(parse_stream_line("data: {"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_tool_calls() {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let config = ChatConfig::builder(model.clone())
+            .tools(vec![ToolDefinition::new(
+                "get_weather",
+                "Current weather of a city",
+                serde_json::json!({"type": "object"}),
+            )])
+            .build()
+            .unwrap();
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "get_weather".to_string(),
+            arguments: r#"{"city":"Lisbon"}"#.to_string(),
+        };
+        let request = Message::new(
+            Uuid::new_v4(),
+            Role::Assistant,
+            "",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        )
+        .with_tool_calls(vec![call]);
+        let result = Message::new(
+            Uuid::new_v4(),
+            Role::Tool,
+            "18C and sunny",
+            0,
+            model,
+            chrono::Utc::now(),
+        )
+        .with_tool_call_id("call_1");
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![request, result],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        );
+
+        let body = serde_json::to_value(ChatCompletionRequest::from_chat(&chat)).unwrap();
+
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+        assert!(body["messages"][1]["content"].is_null());
+        assert_eq!(
+            body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+            r#"{"city":"Lisbon"}"#
+        );
+        assert_eq!(body["messages"][2]["role"], "tool");
+        assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+    }
+
+    #[test]
+    fn test_parse_tool_call_response() {
+        let body = r#"{
+            "id": "chatcmpl-123",
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Lisbon\"}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }"#;
+
+        let response: ChatCompletionResponse = serde_json::from_str(body).unwrap();
+        let message = response.choices.into_iter().next().unwrap().message;
+        let call = ToolCall::from(message.tool_calls.into_iter().next().unwrap());
+
+        assert_eq!(message.content, None);
+        assert_eq!(call.name, "get_weather");
+        assert_eq!(call.arguments, r#"{"city":"Lisbon"}"#);
+    }
+
+    #[test]
+    fn test_accumulate_tool_call_deltas() {
+        let lines = [
+            r#"data: {"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":null,"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":""}}]},"finish_reason":null}]}"#,
+            r#"data: {"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"city\":"}}]},"finish_reason":null}]}"#,
+            r#"data: {"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"Lisbon\"}"}}]},"finish_reason":null}]}"#,
+        ];
+        let mut accumulator = ToolCallAccumulator::default();
+
+        for line in lines {
+            match parse_stream_line(line) {
+                Some(Ok(ChatCompletionStreamEvent::Chunk(chunk))) => {
+                    for choice in chunk.choices {
+                        choice
+                            .delta
+                            .tool_calls
+                            .into_iter()
+                            .for_each(|delta| accumulator.push(delta));
+                    }
+                }
+                other => panic!("unexpected event: {:?}", other),
+            }
+        }
+
+        assert_eq!(
+            accumulator.finish(),
+            vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: r#"{"city":"Lisbon"}"#.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_moderation_response() {
+        let body = r#"{
+            "id": "modr-123",
+            "model": "omni-moderation-latest",
+            "results": [{
+                "flagged": true,
+                "categories": {"harassment": true, "self-harm": false, "violence": true},
+                "category_scores": {"harassment": 0.91, "self-harm": 0.01, "violence": 0.78}
+            }]
+        }"#;
+
+        let response: ModerationResponse = serde_json::from_str(body).unwrap();
+        let result = ModerationResult::from(response.results.into_iter().next().unwrap());
+
+        assert!(result.flagged);
+        assert_eq!(result.categories, vec!["harassment", "violence"]);
+    }
+
+    #[test]
+    fn test_parse_embeddings_response() {
+        let body = r#"{
+            "object": "list",
+            "model": "text-embedding-3-small",
+            "data": [
+                {"object": "embedding", "index": 1, "embedding": [0.3, 0.4]},
+                {"object": "embedding", "index": 0, "embedding": [0.1, 0.2]}
+            ],
+            "usage": {"prompt_tokens": 8, "total_tokens": 8}
+        }"#;
+
+        let response: EmbeddingsResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            response.into_vectors(2).unwrap(),
+            vec![vec![0.1, 0.2], vec![0.3, 0.4]]
+        );
+
+        let response: EmbeddingsResponse = serde_json::from_str(body).unwrap();
+        assert!(response.into_vectors(3).is_err());
+    }
+
+    #[test]
+    fn test_request_golden() {
+        let chat = golden_chat(Model::new("gpt-4o".to_string(), 128000));
+
+        assert_golden(
+            "openai_chat_request",
+            &ChatCompletionRequest::from_chat(&chat),
+        );
+        assert_golden(
+            "openai_chat_stream_request",
+            &ChatCompletionRequest::from_chat(&chat).streaming(),
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::audit::{current_request_id, AuditEntry};
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::domain::repository::audit::AuditRepository;
+
+// AuditedGateway appends every prompt to the audit log before it is sent and the response, or
+// the failure, once the provider is done; a prompt that cannot be logged is not sent
+pub struct AuditedGateway {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    repository: Arc<dyn AuditRepository>,
+}
+
+impl AuditedGateway {
+    pub fn new(
+        gateway: Arc<dyn ChatCompletionGateway>,
+        repository: Arc<dyn AuditRepository>,
+    ) -> Self {
+        Self {
+            gateway,
+            repository,
+        }
+    }
+
+    async fn audited(
+        &self,
+        chat: &Chat,
+        completion: impl Future<Output = Result<Message, GatewayError>>,
+    ) -> Result<Message, GatewayError> {
+        let request_id = current_request_id();
+        let prompt =
+            AuditEntry::prompt(request_id, chat).map_err(|e| GatewayError::Audit(e.to_string()))?;
+        self.repository
+            .append(&prompt)
+            .await
+            .map_err(|e| GatewayError::Audit(e.to_string()))?;
+
+        let started = Instant::now();
+        let result = completion.await;
+
+        // the reply is already paid for, a response that cannot be logged does not fail it
+        let response = AuditEntry::response(request_id, chat, result.as_ref(), started.elapsed());
+        if let Err(err) = self.repository.append(&response).await {
+            tracing::error!(%request_id, error = %err, "could not append the response to the audit log");
+        }
+
+        result
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for AuditedGateway {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        self.audited(chat, self.gateway.create_chat_completion(chat))
+            .await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        self.audited(
+            chat,
+            self.gateway.create_chat_completion_stream(chat, sender),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::audit::AuditDirection;
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::repository::audit::AuditQuery;
+    use crate::internal::domain::repository::chat::RepositoryError;
+    use crate::internal::domain::request_id;
+    use crate::internal::infra::repository::memory::audit::InMemoryAuditRepository;
+
+    // ScriptedGateway answers with the reply, or fails when there is none
+    struct ScriptedGateway {
+        reply: Option<&'static str>,
+        calls: AtomicUsize,
+    }
+
+    impl ScriptedGateway {
+        fn new(reply: Option<&'static str>) -> Self {
+            Self {
+                reply,
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for ScriptedGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let content = self.reply.ok_or(GatewayError::EmptyResponse)?;
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                content,
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    // ClosedLog refuses every entry like an unreachable database
+    struct ClosedLog;
+
+    #[async_trait]
+    impl AuditRepository for ClosedLog {
+        async fn append(&self, _entry: &AuditEntry) -> Result<(), RepositoryError> {
+            Err(RepositoryError::Database("connection refused".to_string()))
+        }
+
+        async fn list_entries(
+            &self,
+            _query: &AuditQuery,
+        ) -> Result<Vec<AuditEntry>, RepositoryError> {
+            Ok(vec![])
+        }
+    }
+
+    fn chat() -> Chat {
+        let model = Model::new("gpt-4o".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        )
+    }
+
+    fn everything() -> AuditQuery {
+        AuditQuery {
+            tenant_id: None,
+            user_id: None,
+            chat_id: None,
+            request_id: None,
+            from: None,
+            to: None,
+            after: None,
+            limit: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_prompt_and_response() {
+        let repository = Arc::new(InMemoryAuditRepository::new());
+        let gateway = AuditedGateway::new(
+            Arc::new(ScriptedGateway::new(Some("Hi, how can I help?"))),
+            repository.clone(),
+        );
+        let chat = chat();
+        let request_id = Uuid::new_v4();
+
+        request_id::with_request_id(
+            request_id.to_string(),
+            gateway.create_chat_completion(&chat),
+        )
+        .await
+        .unwrap();
+
+        let entries = repository.list_entries(&everything()).await.unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.direction)
+                .collect::<Vec<_>>(),
+            vec![AuditDirection::Prompt, AuditDirection::Response]
+        );
+        assert_eq!(entries[0].request_id, request_id);
+        assert_eq!(entries[1].request_id, request_id);
+        assert_eq!(entries[1].chat_id, chat.id);
+        assert_eq!(entries[1].content, "Hi, how can I help?");
+        assert!(entries[1].latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_logs_failures() {
+        let repository = Arc::new(InMemoryAuditRepository::new());
+        let gateway = AuditedGateway::new(Arc::new(ScriptedGateway::new(None)), repository.clone());
+
+        let result = gateway.create_chat_completion(&chat()).await;
+        assert!(matches!(result, Err(GatewayError::EmptyResponse)));
+
+        let entries = repository.list_entries(&everything()).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[1].error,
+            Some(GatewayError::EmptyResponse.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlogged_prompts_are_not_sent() {
+        let provider = Arc::new(ScriptedGateway::new(Some("Hi, how can I help?")));
+        let gateway = AuditedGateway::new(provider.clone(), Arc::new(ClosedLog));
+
+        let result = gateway.create_chat_completion(&chat()).await;
+        assert!(matches!(result, Err(GatewayError::Audit(_))));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,138 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::domain::quota::QuotaEnforcer;
+use crate::internal::domain::usage_tracker::UsageTracker;
+
+// BilledGateway holds the calls made on behalf of a user outside of a chat turn, titles and
+// classifications, to the quota of the tenant and user of the request and records their tokens
+// against them; the turns are billed by their use cases and must not go through it
+pub struct BilledGateway {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    usage_tracker: Arc<UsageTracker>,
+    quota: Option<Arc<QuotaEnforcer>>,
+}
+
+impl BilledGateway {
+    pub fn new(gateway: Arc<dyn ChatCompletionGateway>, usage_tracker: Arc<UsageTracker>) -> Self {
+        Self {
+            gateway,
+            usage_tracker,
+            quota: None,
+        }
+    }
+
+    // with_quota_enforcer refuses the calls of users or tenants out of tokens
+    pub fn with_quota_enforcer(mut self, quota: Arc<QuotaEnforcer>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    async fn billed(
+        &self,
+        chat: &Chat,
+        completion: impl Future<Output = Result<Message, GatewayError>>,
+    ) -> Result<Message, GatewayError> {
+        if let Some(quota) = &self.quota {
+            quota.check(chat.tenant_id, chat.user_id).await?;
+        }
+
+        let response = completion.await?;
+        // the answer is already paid for, usage that cannot be recorded does not fail it
+        if let Err(err) = self
+            .usage_tracker
+            .record(
+                chat.tenant_id,
+                chat.user_id,
+                chat.id,
+                &response.model,
+                chat.token_usage,
+                response.tokens,
+            )
+            .await
+        {
+            tracing::warn!(chat_id = %chat.id, error = %err, "could not record the usage of the call");
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for BilledGateway {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        self.billed(chat, self.gateway.create_chat_completion(chat))
+            .await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        self.billed(
+            chat,
+            self.gateway.create_chat_completion_stream(chat, sender),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::quota::{QuotaConfig, QuotaError};
+    use crate::internal::domain::repository::usage::UsageRepository;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+    use crate::internal::testing::builder::ChatBuilder;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+
+    #[tokio::test]
+    async fn test_calls_are_billed_to_the_user_of_the_request() {
+        let usage = Arc::new(InMemoryUsageRepository::new());
+        let inner = Arc::new(FakeCompletionGateway::new().reply("Lisbon trip"));
+        let gateway = BilledGateway::new(inner.clone(), Arc::new(UsageTracker::new(usage.clone())))
+            .with_quota_enforcer(Arc::new(QuotaEnforcer::new(
+                usage.clone(),
+                QuotaConfig {
+                    user_daily_tokens: 1,
+                    ..QuotaConfig::default()
+                },
+            )));
+        let mut chat = ChatBuilder::new().user_message("Hello").build();
+        chat.refresh_token_usage();
+
+        gateway.create_chat_completion(&chat).await.unwrap();
+
+        let billed = usage
+            .list_chat_usage(DEFAULT_TENANT_ID, &[chat.id])
+            .await
+            .unwrap();
+        assert_eq!(billed.len(), 1);
+
+        // the tokens of the first call used up the quota of the user
+        let refused = gateway.create_chat_completion(&chat).await;
+        assert!(matches!(
+            refused,
+            Err(GatewayError::Quota(QuotaError::Exceeded(_)))
+        ));
+        assert_eq!(inner.calls(), 1);
+
+        // another user still has tokens left
+        let other = ChatBuilder::new()
+            .user_id(Uuid::new_v4())
+            .user_message("Hello")
+            .build();
+        assert!(gateway.create_chat_completion(&other).await.is_ok());
+    }
+}
@@ -0,0 +1,280 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::internal::domain::cache_bypass;
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+use crate::internal::domain::request_id;
+use crate::internal::infra::cache::completion::CompletionCache;
+
+// CacheStats counts how the completions that could be cached were answered
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bypassed: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CacheStatsSnapshot {
+    pub hits: u64,
+    pub misses: u64,
+    pub bypassed: u64,
+    // hit_rate is the share of the lookups answered from the cache, 0 before the first one
+    pub hit_rate: f64,
+}
+
+impl CacheStats {
+    pub fn snapshot(&self) -> CacheStatsSnapshot {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        CacheStatsSnapshot {
+            hits,
+            misses,
+            bypassed: self.bypassed.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
+        }
+    }
+}
+
+// CachedGateway answers a deterministic request, one sent with a zero temperature for a single
+// reply, with the reply to the identical request when the cache still holds it; the cache is
+// only a shortcut, a cache failure sends the request to the provider
+pub struct CachedGateway {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    cache: Arc<dyn CompletionCache>,
+    stats: Arc<CacheStats>,
+}
+
+impl CachedGateway {
+    pub fn new(gateway: Arc<dyn ChatCompletionGateway>, cache: Arc<dyn CompletionCache>) -> Self {
+        Self {
+            gateway,
+            cache,
+            stats: Arc::new(CacheStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> Arc<CacheStats> {
+        self.stats.clone()
+    }
+
+    // lookup returns the cache key of the request and the reply cached under it, no key when
+    // the request is not to be cached
+    async fn lookup(&self, chat: &Chat) -> (Option<String>, Option<Message>) {
+        let Some(key) = cache_key(chat) else {
+            return (None, None);
+        };
+        if cache_bypass::is_bypassed() {
+            self.stats.bypassed.fetch_add(1, Ordering::Relaxed);
+            return (None, None);
+        }
+
+        match self.cache.get_completion(&key).await {
+            Ok(Some(cached)) => {
+                self.stats.hits.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!(key, "answered the completion from the cache");
+                (Some(key), Some(fresh(cached)))
+            }
+            Ok(None) => {
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                (Some(key), None)
+            }
+            Err(err) => {
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(error = %err, "could not read the completion cache");
+                (Some(key), None)
+            }
+        }
+    }
+
+    async fn store(&self, key: Option<String>, result: &Result<Message, GatewayError>) {
+        let (Some(key), Ok(message)) = (key, result) else {
+            return;
+        };
+        if message.interrupted {
+            return;
+        }
+        if let Err(err) = self.cache.set_completion(&key, message).await {
+            tracing::warn!(error = %err, "could not write the completion cache");
+        }
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for CachedGateway {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let (key, cached) = self.lookup(chat).await;
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+
+        let result = self.gateway.create_chat_completion(chat).await;
+        self.store(key, &result).await;
+
+        result
+    }
+
+    // create_chat_completion_stream sends a cached reply as a single delta
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let (key, cached) = self.lookup(chat).await;
+        if let Some(cached) = cached {
+            let _ = sender.send(cached.content.clone()).await;
+            return Ok(cached);
+        }
+
+        let result = self
+            .gateway
+            .create_chat_completion_stream(chat, sender)
+            .await;
+        self.store(key, &result).await;
+
+        result
+    }
+}
+
+// cache_key hashes what the provider is sent, the messages with their whitespace normalized and
+// the sampling config; sampled requests get None, the same prompt should not always get the same
+// reply. The tenant is part of the key so tenants never share replies
+fn cache_key(chat: &Chat) -> Option<String> {
+    let config = &chat.config;
+    if config.temperature != 0.0 || config.n != 1 {
+        return None;
+    }
+
+    let messages = chat
+        .prompt_messages()
+        .into_iter()
+        .map(|message| {
+            serde_json::json!({
+                "role": message.role,
+                "content": normalize(&message.content),
+                "tool_calls": message.tool_calls,
+                "tool_call_id": message.tool_call_id,
+                "attachments": message.attachments,
+            })
+        })
+        .collect::<Vec<_>>();
+    let request = serde_json::json!({
+        "tenant_id": chat.tenant_id,
+        "model": config.model.name,
+        "top_p": config.top_p,
+        "stop": config.stop,
+        "max_tokens": config.max_tokens,
+        "presence_penalty": config.presence_penalty,
+        "frequency_penalty": config.frequency_penalty,
+        "tools": config.tools,
+        "response_format": config.response_format,
+        "messages": messages,
+    });
+
+    Some(hex::encode(Sha256::digest(request.to_string())))
+}
+
+fn normalize(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// fresh makes a cached reply a new message of the request being served
+fn fresh(cached: Message) -> Message {
+    Message {
+        id: Uuid::new_v4(),
+        created_at: chrono::Utc::now(),
+        request_id: request_id::current(),
+        ..cached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::cache_bypass::with_cache_bypass;
+    use crate::internal::domain::entity::chat::ChatConfig;
+    use crate::internal::infra::cache::completion::InMemoryCompletionCache;
+    use crate::internal::testing::builder::{test_model, ChatBuilder};
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+
+    fn deterministic(user_message: &str) -> Chat {
+        ChatBuilder::new()
+            .config(
+                ChatConfig::builder(test_model())
+                    .temperature(0.0)
+                    .build()
+                    .unwrap(),
+            )
+            .user_message(user_message)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_answers_identical_requests_from_the_cache() {
+        let provider = Arc::new(FakeCompletionGateway::new().reply("Paris").reply("Madrid"));
+        let gateway = CachedGateway::new(
+            provider.clone(),
+            Arc::new(InMemoryCompletionCache::new(
+                std::time::Duration::from_secs(60),
+                10,
+            )),
+        );
+
+        let first = gateway
+            .create_chat_completion(&deterministic("Capital of France?"))
+            .await
+            .unwrap();
+        let cached = gateway
+            .create_chat_completion(&deterministic("  Capital of   France? "))
+            .await
+            .unwrap();
+        assert_eq!(cached.content, "Paris");
+        assert_ne!(cached.id, first.id);
+        assert_eq!(provider.calls(), 1);
+
+        let bypassed =
+            with_cache_bypass(gateway.create_chat_completion(&deterministic("Capital of France?")))
+                .await
+                .unwrap();
+        assert_eq!(bypassed.content, "Madrid");
+        assert_eq!(provider.calls(), 2);
+
+        let stats = gateway.stats().snapshot();
+        assert_eq!((stats.hits, stats.misses, stats.bypassed), (1, 1, 1));
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let chat = deterministic("Capital of France?");
+        assert_eq!(
+            cache_key(&chat),
+            cache_key(&deterministic("Capital of France?"))
+        );
+        assert_ne!(
+            cache_key(&chat),
+            cache_key(&deterministic("Capital of Spain?"))
+        );
+
+        let sampled = ChatBuilder::new()
+            .user_message("Capital of France?")
+            .build();
+        assert_eq!(sampled.config.temperature, 1.0);
+        assert_eq!(cache_key(&sampled), None);
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+
+// CircuitBreakerConfig sets how many consecutive failures open the circuit and for how long
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    Closed { failures: u32 },
+    // Open rejects every request until the deadline
+    Open { until: Instant },
+    // HalfOpen lets a single probe through, a probe that never reports back
+    // is replaced once open_duration has passed
+    HalfOpen { since: Instant },
+}
+
+// CircuitBreaker fails fast with ProviderUnavailable while the wrapped gateway keeps failing,
+// instead of letting every request wait for the provider to time out
+pub struct CircuitBreaker {
+    provider: String,
+    gateway: Arc<dyn ChatCompletionGateway>,
+    config: CircuitBreakerConfig,
+    state: Mutex<CircuitState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(
+        provider: &str,
+        gateway: Arc<dyn ChatCompletionGateway>,
+        config: CircuitBreakerConfig,
+    ) -> Self {
+        Self {
+            provider: provider.to_string(),
+            gateway,
+            config,
+            state: Mutex::new(CircuitState::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn acquire_at(&self, now: Instant) -> Result<(), GatewayError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match *state {
+            CircuitState::Closed { .. } => Ok(()),
+            CircuitState::Open { until } if now >= until => {
+                *state = CircuitState::HalfOpen { since: now };
+                Ok(())
+            }
+            CircuitState::HalfOpen { since }
+                if now.saturating_duration_since(since) >= self.config.open_duration =>
+            {
+                *state = CircuitState::HalfOpen { since: now };
+                Ok(())
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => {
+                Err(GatewayError::ProviderUnavailable(self.provider.clone()))
+            }
+        }
+    }
+
+    fn record_at<T>(&self, result: &Result<T, GatewayError>, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let failed = match result {
+            Ok(_) => false,
+            Err(err) => err.is_provider_failure(),
+        };
+        if !failed {
+            *state = CircuitState::Closed { failures: 0 };
+            return;
+        }
+
+        let failures = match *state {
+            CircuitState::Closed { failures } => failures + 1,
+            // a failed probe opens the circuit again right away
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => {
+                self.config.failure_threshold
+            }
+        };
+        *state = if failures >= self.config.failure_threshold {
+            tracing::warn!(
+                provider = %self.provider,
+                open_secs = self.config.open_duration.as_secs(),
+                "circuit opened"
+            );
+            CircuitState::Open {
+                until: now + self.config.open_duration,
+            }
+        } else {
+            CircuitState::Closed { failures }
+        };
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for CircuitBreaker {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        self.acquire_at(Instant::now())?;
+        let result = self.gateway.create_chat_completion(chat).await;
+        self.record_at(&result, Instant::now());
+
+        result
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        self.acquire_at(Instant::now())?;
+        let result = self
+            .gateway
+            .create_chat_completion_stream(chat, sender)
+            .await;
+        self.record_at(&result, Instant::now());
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct DownGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for DownGateway {
+        async fn create_chat_completion(&self, _chat: &Chat) -> Result<Message, GatewayError> {
+            Err(GatewayError::Request("connection refused".to_string()))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            "openai",
+            Arc::new(DownGateway),
+            CircuitBreakerConfig {
+                failure_threshold: 3,
+                open_duration: Duration::from_secs(30),
+            },
+        )
+    }
+
+    fn failure() -> Result<(), GatewayError> {
+        Err(GatewayError::Api {
+            status: 503,
+            body: "overloaded".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            breaker.acquire_at(now).unwrap();
+            breaker.record_at(&failure(), now);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 2 });
+
+        breaker.record_at(&Ok(()), now);
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+
+        for _ in 0..3 {
+            breaker.record_at(&failure(), now);
+        }
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        assert!(matches!(
+            breaker.acquire_at(now + Duration::from_secs(10)),
+            Err(GatewayError::ProviderUnavailable(p)) if p == "openai"
+        ));
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_at(&failure(), now);
+        }
+
+        let later = now + Duration::from_secs(30);
+        breaker.acquire_at(later).unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen { since: later });
+        assert!(breaker.acquire_at(later).is_err());
+
+        breaker.record_at(&failure(), later);
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+        let recovered = later + Duration::from_secs(30);
+        breaker.acquire_at(recovered).unwrap();
+        breaker.record_at(&Ok(()), recovered);
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[test]
+    fn test_client_errors_do_not_count() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            breaker.record_at::<()>(
+                &Err(GatewayError::Api {
+                    status: 400,
+                    body: "bad request".to_string(),
+                }),
+                now,
+            );
+        }
+
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+}
@@ -0,0 +1,281 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+
+const DELTA_BUFFER_SIZE: usize = 32;
+
+// FallbackGateway retries a completion on the next model of the chain when the provider of the
+// current one fails or times out; the reply carries the model that actually served it
+pub struct FallbackGateway {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    fallbacks: Vec<Model>,
+    timeout: Option<Duration>,
+}
+
+impl FallbackGateway {
+    // new tries the chat's own model first, then the fallbacks in order
+    pub fn new(gateway: Arc<dyn ChatCompletionGateway>, fallbacks: Vec<Model>) -> Self {
+        Self {
+            gateway,
+            fallbacks,
+            timeout: None,
+        }
+    }
+
+    // with_timeout gives up on a model that has not answered in time and moves to the next one
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn chain(&self, chat: &Chat) -> Vec<Model> {
+        let mut chain = vec![chat.config.model.clone()];
+        for model in &self.fallbacks {
+            if !chain.iter().any(|m| m.name == model.name) {
+                chain.push(model.clone());
+            }
+        }
+
+        chain
+    }
+
+    async fn timed(
+        &self,
+        completion: impl Future<Output = Result<Message, GatewayError>>,
+    ) -> Result<Message, GatewayError> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, completion)
+                .await
+                .map_err(|_| GatewayError::Timeout(timeout))?,
+            None => completion.await,
+        }
+    }
+
+    // stream_attempt forwards the deltas of one model and reports whether any reached the client
+    async fn stream_attempt(
+        &self,
+        chat: &Chat,
+        sender: &mpsc::Sender<String>,
+    ) -> (Result<Message, GatewayError>, bool) {
+        let (attempt_sender, mut receiver) = mpsc::channel::<String>(DELTA_BUFFER_SIZE);
+
+        let forward = async move {
+            let mut forwarded = false;
+            while let Some(delta) = receiver.recv().await {
+                forwarded = true;
+                let _ = sender.send(delta).await;
+            }
+            forwarded
+        };
+
+        tokio::join!(
+            self.timed(
+                self.gateway
+                    .create_chat_completion_stream(chat, attempt_sender)
+            ),
+            forward
+        )
+    }
+}
+
+// with_model is the chat as the given model would receive it
+fn with_model(chat: &Chat, model: &Model) -> Chat {
+    let mut chat = chat.clone();
+    chat.config.model = model.clone();
+    chat
+}
+
+#[async_trait]
+impl ChatCompletionGateway for FallbackGateway {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let mut last_error = None;
+
+        for model in self.chain(chat) {
+            let attempt = with_model(chat, &model);
+            match self
+                .timed(self.gateway.create_chat_completion(&attempt))
+                .await
+            {
+                Ok(mut message) => {
+                    message.model = model;
+                    return Ok(message);
+                }
+                Err(err) if err.is_provider_failure() => {
+                    tracing::warn!(model = %model.name, error = %err, "model failed, falling back");
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.unwrap_or(GatewayError::EmptyResponse))
+    }
+
+    // create_chat_completion_stream only falls back while nothing was streamed yet,
+    // otherwise the client would receive the start of two different replies
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let mut last_error = None;
+
+        for model in self.chain(chat) {
+            let attempt = with_model(chat, &model);
+            match self.stream_attempt(&attempt, &sender).await {
+                (Ok(mut message), _) => {
+                    message.model = model;
+                    return Ok(message);
+                }
+                (Err(err), false) if err.is_provider_failure() => {
+                    tracing::warn!(model = %model.name, error = %err, "model failed, falling back");
+                    last_error = Some(err);
+                }
+                (Err(err), _) => return Err(err),
+            }
+        }
+
+        Err(last_error.unwrap_or(GatewayError::EmptyResponse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::ChatConfig;
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::testing::builder::ChatBuilder;
+
+    // ScriptedGateway fails for the listed models and answers with the model name otherwise
+    #[derive(Default)]
+    struct ScriptedGateway {
+        failing: Vec<&'static str>,
+        slow: Vec<&'static str>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for ScriptedGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let name = chat.config.model.name.clone();
+            self.calls.lock().unwrap().push(name.clone());
+
+            if self.slow.contains(&name.as_str()) {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            if self.failing.contains(&name.as_str()) {
+                return Err(GatewayError::Api {
+                    status: 503,
+                    body: "overloaded".to_string(),
+                });
+            }
+
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                &name,
+                0,
+                chat.initial_system_message().model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            let _ = sender.send("partial".to_string()).await;
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    fn chat() -> Chat {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+
+        ChatBuilder::new()
+            .config(ChatConfig::default_for(model))
+            .build()
+    }
+
+    fn fallbacks() -> Vec<Model> {
+        vec![
+            Model::new("gpt-4o-mini".to_string(), 128000),
+            Model::new("anthropic/claude-3-5-sonnet".to_string(), 200000),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_in_order() {
+        let gateway = Arc::new(ScriptedGateway {
+            failing: vec!["gpt-4o", "gpt-4o-mini"],
+            ..Default::default()
+        });
+        let fallback = FallbackGateway::new(gateway.clone(), fallbacks());
+
+        let reply = fallback.create_chat_completion(&chat()).await.unwrap();
+
+        assert_eq!(reply.content, "anthropic/claude-3-5-sonnet");
+        assert_eq!(reply.model.name, "anthropic/claude-3-5-sonnet");
+        assert_eq!(
+            *gateway.calls.lock().unwrap(),
+            vec!["gpt-4o", "gpt-4o-mini", "anthropic/claude-3-5-sonnet"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_fail() {
+        let gateway = Arc::new(ScriptedGateway {
+            failing: vec!["gpt-4o", "gpt-4o-mini", "anthropic/claude-3-5-sonnet"],
+            ..Default::default()
+        });
+        let fallback = FallbackGateway::new(gateway, fallbacks());
+
+        let result = fallback.create_chat_completion(&chat()).await;
+
+        assert!(matches!(result, Err(GatewayError::Api { status: 503, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_timeout() {
+        let gateway = Arc::new(ScriptedGateway {
+            slow: vec!["gpt-4o"],
+            ..Default::default()
+        });
+        let fallback =
+            FallbackGateway::new(gateway, fallbacks()).with_timeout(Duration::from_millis(20));
+
+        let reply = fallback.create_chat_completion(&chat()).await.unwrap();
+
+        assert_eq!(reply.model.name, "gpt-4o-mini");
+    }
+
+    #[tokio::test]
+    async fn test_stream_does_not_fall_back_after_deltas() {
+        let gateway = Arc::new(ScriptedGateway {
+            failing: vec!["gpt-4o"],
+            ..Default::default()
+        });
+        let fallback = FallbackGateway::new(gateway.clone(), fallbacks());
+        let (sender, mut receiver) = mpsc::channel(8);
+
+        let result = fallback
+            .create_chat_completion_stream(&chat(), sender)
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(receiver.recv().await.as_deref(), Some("partial"));
+        assert_eq!(*gateway.calls.lock().unwrap(), vec!["gpt-4o"]);
+    }
+}
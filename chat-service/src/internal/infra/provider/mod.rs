@@ -0,0 +1,6 @@
+pub mod audit;
+pub mod billing;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod fallback;
+pub mod router;
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+
+// ProviderRouter picks the gateway from the provider prefix of the chat's model,
+// e.g. anthropic/claude-3-5-sonnet goes to the gateway registered as anthropic
+pub struct ProviderRouter {
+    gateways: HashMap<String, Arc<dyn ChatCompletionGateway>>,
+}
+
+impl ProviderRouter {
+    pub fn new() -> Self {
+        Self {
+            gateways: HashMap::new(),
+        }
+    }
+
+    pub fn with_provider(
+        mut self,
+        provider: &str,
+        gateway: Arc<dyn ChatCompletionGateway>,
+    ) -> Self {
+        self.gateways.insert(provider.to_string(), gateway);
+        self
+    }
+
+    fn gateway(&self, chat: &Chat) -> Result<&Arc<dyn ChatCompletionGateway>, GatewayError> {
+        let provider = chat.config.model.provider();
+
+        self.gateways
+            .get(provider)
+            .ok_or_else(|| GatewayError::UnknownProvider(provider.to_string()))
+    }
+}
+
+impl Default for ProviderRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for ProviderRouter {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        self.gateway(chat)?.create_chat_completion(chat).await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        self.gateway(chat)?
+            .create_chat_completion_stream(chat, sender)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::model::Model;
+
+    struct NamedGateway(&'static str);
+
+    #[async_trait]
+    impl ChatCompletionGateway for NamedGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                self.0,
+                0,
+                chat.initial_system_message().model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    fn chat_for(model_name: &str) -> Chat {
+        let model = Model::new(model_name.to_string(), 4096);
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_provider() {
+        let router = ProviderRouter::new()
+            .with_provider("openai", Arc::new(NamedGateway("openai")))
+            .with_provider("anthropic", Arc::new(NamedGateway("anthropic")));
+
+        let reply = router
+            .create_chat_completion(&chat_for("gpt-3.5-turbo"))
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "openai");
+
+        let reply = router
+            .create_chat_completion(&chat_for("anthropic/claude-3-5-sonnet"))
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "anthropic");
+
+        let result = router
+            .create_chat_completion(&chat_for("mistral/mistral-large"))
+            .await;
+        assert!(matches!(result, Err(GatewayError::UnknownProvider(p)) if p == "mistral"));
+    }
+}
@@ -0,0 +1,102 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::internal::domain::redactor::{Cipher, CipherError};
+
+// KEY_LEN is the length of an AES-256 key in bytes
+pub const KEY_LEN: usize = 32;
+
+// AesGcmCipher encrypts with AES-256-GCM under a random nonce, which is kept in front of the
+// ciphertext
+pub struct AesGcmCipher {
+    key: LessSafeKey,
+    random: SystemRandom,
+}
+
+impl AesGcmCipher {
+    pub fn new(key: &[u8]) -> Result<Self, CipherError> {
+        if key.len() != KEY_LEN {
+            return Err(CipherError(format!("key must be {} bytes", KEY_LEN)));
+        }
+        let key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| CipherError("key is invalid".to_string()))?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            random: SystemRandom::new(),
+        })
+    }
+
+    // from_base64 reads the key the way it is configured
+    pub fn from_base64(key: &str) -> Result<Self, CipherError> {
+        let key = STANDARD
+            .decode(key.trim())
+            .map_err(|e| CipherError(format!("key is not base64: {}", e)))?;
+
+        Self::new(&key)
+    }
+}
+
+impl Cipher for AesGcmCipher {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random
+            .fill(&mut nonce)
+            .map_err(|_| CipherError("no randomness for the nonce".to_string()))?;
+
+        let mut sealed = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .map_err(|_| CipherError("encryption failed".to_string()))?;
+
+        Ok([nonce.as_slice(), &sealed].concat())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        if ciphertext.len() < NONCE_LEN {
+            return Err(CipherError("ciphertext is truncated".to_string()));
+        }
+        let (nonce, sealed) = ciphertext.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| CipherError("nonce is invalid".to_string()))?;
+
+        let mut opened = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut opened)
+            .map_err(|_| CipherError("decryption failed".to_string()))?;
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = AesGcmCipher::from_base64(&STANDARD.encode([7u8; KEY_LEN])).unwrap();
+
+        let first = cipher.encrypt(b"ada@example.com").unwrap();
+        let second = cipher.encrypt(b"ada@example.com").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(cipher.decrypt(&first).unwrap(), b"ada@example.com");
+
+        let other = AesGcmCipher::new(&[8u8; KEY_LEN]).unwrap();
+        assert!(other.decrypt(&first).is_err());
+        assert!(cipher.decrypt(&first[..NONCE_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        assert!(AesGcmCipher::new(&[7u8; 16]).is_err());
+        assert!(AesGcmCipher::from_base64("not base64!").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::internal::domain::redactor::PiiDetector;
+
+// BUILTIN_DETECTORS are the detectors a configuration can name
+pub const BUILTIN_DETECTORS: [&str; 3] = ["email", "phone", "credit_card"];
+
+const EMAIL_PATTERN: &str = r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b";
+// PHONE_PATTERN wants three groups of digits, so dates and short numbers are left alone
+const PHONE_PATTERN: &str = r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{2,4}\)?[\s.-]?\d{3,4}[\s.-]?\d{4}\b";
+const CREDIT_CARD_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+// RegexDetector finds the matches of a pattern, a check can reject the ones that only look
+// like the data
+pub struct RegexDetector {
+    kind: String,
+    pattern: Regex,
+    check: Option<fn(&str) -> bool>,
+}
+
+impl RegexDetector {
+    pub fn new(kind: &str, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            kind: kind.to_string(),
+            pattern: Regex::new(pattern)?,
+            check: None,
+        })
+    }
+
+    pub fn email() -> Self {
+        Self::builtin("email", EMAIL_PATTERN, None)
+    }
+
+    pub fn phone() -> Self {
+        Self::builtin("phone", PHONE_PATTERN, None)
+    }
+
+    // credit_card only masks numbers that pass the Luhn checksum
+    pub fn credit_card() -> Self {
+        Self::builtin("credit_card", CREDIT_CARD_PATTERN, Some(luhn))
+    }
+
+    // named returns the built-in detector of the name, one of BUILTIN_DETECTORS
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "email" => Some(Self::email()),
+            "phone" => Some(Self::phone()),
+            "credit_card" => Some(Self::credit_card()),
+            _ => None,
+        }
+    }
+
+    fn builtin(kind: &str, pattern: &str, check: Option<fn(&str) -> bool>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            pattern: Regex::new(pattern).expect("built-in patterns are valid"),
+            check,
+        }
+    }
+}
+
+impl PiiDetector for RegexDetector {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn find(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern
+            .find_iter(text)
+            .filter(|found| self.check.is_none_or(|check| check(found.as_str())))
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+// luhn validates the checksum digit of a card number, separators are ignored
+fn luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found<'a>(detector: &RegexDetector, text: &'a str) -> Vec<&'a str> {
+        detector
+            .find(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(
+            found(
+                &RegexDetector::email(),
+                "Write to Ada.Lovelace+chat@mail.example.co.uk or ada@example"
+            ),
+            vec!["Ada.Lovelace+chat@mail.example.co.uk"]
+        );
+    }
+
+    #[test]
+    fn test_phone() {
+        assert_eq!(
+            found(
+                &RegexDetector::phone(),
+                "Call +1 (555) 123-4567 or +44 20 7946 0958, not on 2024-01-15 at 10:30"
+            ),
+            vec!["+1 (555) 123-4567", "+44 20 7946 0958"]
+        );
+    }
+
+    #[test]
+    fn test_credit_card() {
+        assert_eq!(
+            found(
+                &RegexDetector::credit_card(),
+                "Charge 4111 1111 1111 1111 or 5500-0000-0000-0004, not 4111 1111 1111 1112"
+            ),
+            vec!["4111 1111 1111 1111", "5500-0000-0000-0004"]
+        );
+    }
+
+    #[test]
+    fn test_named() {
+        for name in BUILTIN_DETECTORS {
+            assert_eq!(RegexDetector::named(name).unwrap().kind(), name);
+        }
+        assert!(RegexDetector::named("passport").is_none());
+        assert!(RegexDetector::new("ticket", "[").is_err());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cipher;
+pub mod detector;
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::internal::domain::error::ConfigError;
+
+// DatabaseDriver picks the database the repositories are backed by, every driver but memory
+// needs its cargo feature compiled in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseDriver {
+    #[default]
+    Postgres,
+    Mysql,
+    Sqlite,
+    // Memory keeps everything in the process, it is lost on restart
+    Memory,
+}
+
+impl fmt::Display for DatabaseDriver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DatabaseDriver::Postgres => "postgres",
+            DatabaseDriver::Mysql => "mysql",
+            DatabaseDriver::Sqlite => "sqlite",
+            DatabaseDriver::Memory => "memory",
+        })
+    }
+}
+
+impl FromStr for DatabaseDriver {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(DatabaseDriver::Postgres),
+            "mysql" => Ok(DatabaseDriver::Mysql),
+            "sqlite" => Ok(DatabaseDriver::Sqlite),
+            "memory" => Ok(DatabaseDriver::Memory),
+            _ => Err(ConfigError::UnknownDatabaseDriver(s.to_string())),
+        }
+    }
+}
+
+impl DatabaseDriver {
+    // is_compiled tells whether the binary was built with the feature of the driver
+    pub fn is_compiled(&self) -> bool {
+        match self {
+            DatabaseDriver::Postgres => cfg!(feature = "postgres"),
+            DatabaseDriver::Mysql => cfg!(feature = "mysql"),
+            DatabaseDriver::Sqlite => cfg!(feature = "sqlite"),
+            DatabaseDriver::Memory => true,
+        }
+    }
+
+    // first_compiled is the first database driver the tests were built with, memory when they
+    // were built without any
+    #[cfg(test)]
+    pub(crate) fn first_compiled() -> Self {
+        [
+            DatabaseDriver::Postgres,
+            DatabaseDriver::Mysql,
+            DatabaseDriver::Sqlite,
+        ]
+        .into_iter()
+        .find(DatabaseDriver::is_compiled)
+        .unwrap_or(DatabaseDriver::Memory)
+    }
+
+    // has_vector_store tells whether embeddings can be stored, retrieval needs them
+    pub fn has_vector_store(&self) -> bool {
+        matches!(self, DatabaseDriver::Postgres | DatabaseDriver::Memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("sqlite".parse(), Ok(DatabaseDriver::Sqlite));
+        assert_eq!(DatabaseDriver::Mysql.to_string(), "mysql");
+        assert!(matches!(
+            "oracle".parse::<DatabaseDriver>(),
+            Err(ConfigError::UnknownDatabaseDriver(_))
+        ));
+        assert!(DatabaseDriver::Memory.is_compiled());
+    }
+
+    #[test]
+    fn test_has_vector_store() {
+        assert!(DatabaseDriver::Postgres.has_vector_store());
+        assert!(DatabaseDriver::Memory.has_vector_store());
+        // the sql drivers keep documents and vectors in memory only, retrieval is refused
+        assert!(!DatabaseDriver::Sqlite.has_vector_store());
+        assert!(!DatabaseDriver::Mysql.has_vector_store());
+    }
+}
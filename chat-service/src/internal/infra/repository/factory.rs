@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::gateway::health::HealthCheck;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::audit::AuditRepository;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+use crate::internal::domain::repository::document::DocumentRepository;
+use crate::internal::domain::repository::idempotency::IdempotencyRepository;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::domain::repository::moderation::ModerationRepository;
+use crate::internal::domain::repository::outbox::OutboxRepository;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::domain::repository::redaction::RedactionRepository;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::domain::repository::tenant::TenantRepository;
+use crate::internal::domain::repository::unit_of_work::UnitOfWork;
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+use crate::internal::domain::repository::vector_store::VectorStore;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::infra::repository::driver::DatabaseDriver;
+use crate::internal::infra::repository::memory::api_key::InMemoryApiKeyRepository;
+use crate::internal::infra::repository::memory::assistant::InMemoryAssistantRepository;
+use crate::internal::infra::repository::memory::audit::InMemoryAuditRepository;
+use crate::internal::infra::repository::memory::batch::InMemoryBatchRepository;
+use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+use crate::internal::infra::repository::memory::document::InMemoryDocumentRepository;
+use crate::internal::infra::repository::memory::idempotency::InMemoryIdempotencyRepository;
+use crate::internal::infra::repository::memory::job::InMemoryJobRepository;
+use crate::internal::infra::repository::memory::moderation::InMemoryModerationRepository;
+use crate::internal::infra::repository::memory::prompt_template::InMemoryPromptTemplateRepository;
+use crate::internal::infra::repository::memory::redaction::InMemoryRedactionRepository;
+use crate::internal::infra::repository::memory::scheduled_message::InMemoryScheduledMessageRepository;
+use crate::internal::infra::repository::memory::tenant::InMemoryTenantRepository;
+use crate::internal::infra::repository::memory::unit_of_work::InMemoryUnitOfWork;
+use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+use crate::internal::infra::repository::memory::user_memory::InMemoryMemoryRepository;
+use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+use crate::internal::infra::repository::migration::SchemaVersion;
+
+// Pool is the connection pool behind the repositories, kept to close it on shutdown
+enum Pool {
+    #[cfg(feature = "postgres")]
+    Postgres(sqlx::PgPool),
+    #[cfg(any(feature = "mysql", feature = "sqlite"))]
+    Sql(sqlx::AnyPool),
+    Memory,
+}
+
+// Repositories are every repository of the service backed by the configured driver
+pub struct Repositories {
+    pub chats: Arc<dyn ChatRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub usage: Arc<dyn UsageRepository>,
+    pub moderation: Arc<dyn ModerationRepository>,
+    pub redactions: Arc<dyn RedactionRepository>,
+    pub tenants: Arc<dyn TenantRepository>,
+    pub templates: Arc<dyn PromptTemplateRepository>,
+    pub assistants: Arc<dyn AssistantRepository>,
+    pub idempotency: Arc<dyn IdempotencyRepository>,
+    // documents and vectors are kept in memory by the drivers without a vector store,
+    // retrieval is refused with those at startup
+    pub documents: Arc<dyn DocumentRepository>,
+    pub vectors: Arc<dyn VectorStore>,
+    pub outbox: Arc<dyn OutboxRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub memories: Arc<dyn MemoryRepository>,
+    pub scheduled: Arc<dyn ScheduledMessageRepository>,
+    pub webhooks: Arc<dyn WebhookRepository>,
+    pub batches: Arc<dyn BatchRepository>,
+    pub jobs: Arc<dyn JobRepository>,
+    // unit_of_work writes chats and usage in one transaction of the same database
+    pub unit_of_work: Arc<dyn UnitOfWork>,
+    // health is None for the memory driver, there is nothing to probe
+    pub health: Option<Arc<dyn HealthCheck>>,
+    pool: Pool,
+}
+
+impl Repositories {
+    // connect opens a pool to the database of the driver; messages are read back as produced
+    // by the given model
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(unused_variables)
+    )]
+    pub async fn connect(
+        driver: DatabaseDriver,
+        url: &str,
+        model: Model,
+    ) -> Result<Self, RepositoryError> {
+        match driver {
+            #[cfg(feature = "postgres")]
+            DatabaseDriver::Postgres => Self::postgres(url, model).await,
+            #[cfg(feature = "mysql")]
+            DatabaseDriver::Mysql => Self::sql(driver, url, model).await,
+            #[cfg(feature = "sqlite")]
+            DatabaseDriver::Sqlite => Self::sql(driver, url, model).await,
+            DatabaseDriver::Memory => Ok(Self::memory()),
+            #[allow(unreachable_patterns)]
+            driver => Err(RepositoryError::Database(format!(
+                "database driver {} is not compiled in",
+                driver
+            ))),
+        }
+    }
+
+    // memory keeps everything in the process, the chat repository doubles as the outbox
+    pub fn memory() -> Self {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let usage = Arc::new(InMemoryUsageRepository::new());
+
+        Self {
+            chats: chats.clone(),
+            users: Arc::new(InMemoryUserRepository::new()),
+            api_keys: Arc::new(InMemoryApiKeyRepository::new()),
+            usage: usage.clone(),
+            moderation: Arc::new(InMemoryModerationRepository::new()),
+            redactions: Arc::new(InMemoryRedactionRepository::new()),
+            tenants: Arc::new(InMemoryTenantRepository::new()),
+            templates: Arc::new(InMemoryPromptTemplateRepository::new()),
+            assistants: Arc::new(InMemoryAssistantRepository::new()),
+            idempotency: Arc::new(InMemoryIdempotencyRepository::new()),
+            documents: Arc::new(InMemoryDocumentRepository::new()),
+            vectors: Arc::new(InMemoryVectorStore::new()),
+            outbox: chats.clone(),
+            audit: Arc::new(InMemoryAuditRepository::new()),
+            memories: Arc::new(InMemoryMemoryRepository::new()),
+            scheduled: Arc::new(InMemoryScheduledMessageRepository::new()),
+            webhooks: Arc::new(InMemoryWebhookRepository::new()),
+            batches: Arc::new(InMemoryBatchRepository::new()),
+            jobs: Arc::new(InMemoryJobRepository::new()),
+            unit_of_work: Arc::new(InMemoryUnitOfWork::new(chats, usage)),
+            health: None,
+            pool: Pool::Memory,
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    async fn postgres(url: &str, model: Model) -> Result<Self, RepositoryError> {
+        use crate::internal::infra::health::postgres::PostgresHealthCheck;
+        use crate::internal::infra::repository::postgres::api_key::PostgresApiKeyRepository;
+        use crate::internal::infra::repository::postgres::assistant::PostgresAssistantRepository;
+        use crate::internal::infra::repository::postgres::audit::PostgresAuditRepository;
+        use crate::internal::infra::repository::postgres::batch::PostgresBatchRepository;
+        use crate::internal::infra::repository::postgres::chat::PostgresChatRepository;
+        use crate::internal::infra::repository::postgres::document::PostgresDocumentRepository;
+        use crate::internal::infra::repository::postgres::idempotency::PostgresIdempotencyRepository;
+        use crate::internal::infra::repository::postgres::job::PostgresJobRepository;
+        use crate::internal::infra::repository::postgres::moderation::PostgresModerationRepository;
+        use crate::internal::infra::repository::postgres::outbox::PostgresOutboxRepository;
+        use crate::internal::infra::repository::postgres::prompt_template::PostgresPromptTemplateRepository;
+        use crate::internal::infra::repository::postgres::redaction::PostgresRedactionRepository;
+        use crate::internal::infra::repository::postgres::scheduled_message::PostgresScheduledMessageRepository;
+        use crate::internal::infra::repository::postgres::tenant::PostgresTenantRepository;
+        use crate::internal::infra::repository::postgres::unit_of_work::PostgresUnitOfWork;
+        use crate::internal::infra::repository::postgres::usage::PostgresUsageRepository;
+        use crate::internal::infra::repository::postgres::user::PostgresUserRepository;
+        use crate::internal::infra::repository::postgres::user_memory::PostgresMemoryRepository;
+        use crate::internal::infra::repository::postgres::vector_store::PgVectorStore;
+        use crate::internal::infra::repository::postgres::webhook::PostgresWebhookRepository;
+
+        let pool = sqlx::PgPool::connect(url)
+            .await
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(Self {
+            chats: Arc::new(PostgresChatRepository::new(pool.clone(), model)),
+            users: Arc::new(PostgresUserRepository::new(pool.clone())),
+            api_keys: Arc::new(PostgresApiKeyRepository::new(pool.clone())),
+            usage: Arc::new(PostgresUsageRepository::new(pool.clone())),
+            moderation: Arc::new(PostgresModerationRepository::new(pool.clone())),
+            redactions: Arc::new(PostgresRedactionRepository::new(pool.clone())),
+            tenants: Arc::new(PostgresTenantRepository::new(pool.clone())),
+            templates: Arc::new(PostgresPromptTemplateRepository::new(pool.clone())),
+            assistants: Arc::new(PostgresAssistantRepository::new(pool.clone())),
+            idempotency: Arc::new(PostgresIdempotencyRepository::new(pool.clone())),
+            documents: Arc::new(PostgresDocumentRepository::new(pool.clone())),
+            vectors: Arc::new(PgVectorStore::new(pool.clone())),
+            outbox: Arc::new(PostgresOutboxRepository::new(pool.clone())),
+            audit: Arc::new(PostgresAuditRepository::new(pool.clone())),
+            memories: Arc::new(PostgresMemoryRepository::new(pool.clone())),
+            scheduled: Arc::new(PostgresScheduledMessageRepository::new(pool.clone())),
+            webhooks: Arc::new(PostgresWebhookRepository::new(pool.clone())),
+            batches: Arc::new(PostgresBatchRepository::new(pool.clone())),
+            jobs: Arc::new(PostgresJobRepository::new(pool.clone())),
+            unit_of_work: Arc::new(PostgresUnitOfWork::new(pool.clone())),
+            health: Some(Arc::new(PostgresHealthCheck::new(pool.clone()))),
+            pool: Pool::Postgres(pool),
+        })
+    }
+
+    #[cfg(any(feature = "mysql", feature = "sqlite"))]
+    async fn sql(driver: DatabaseDriver, url: &str, model: Model) -> Result<Self, RepositoryError> {
+        use crate::internal::infra::health::sql::SqlHealthCheck;
+        use crate::internal::infra::repository::sql::api_key::SqlApiKeyRepository;
+        use crate::internal::infra::repository::sql::assistant::SqlAssistantRepository;
+        use crate::internal::infra::repository::sql::audit::SqlAuditRepository;
+        use crate::internal::infra::repository::sql::batch::SqlBatchRepository;
+        use crate::internal::infra::repository::sql::chat::SqlChatRepository;
+        use crate::internal::infra::repository::sql::dialect::Dialect;
+        use crate::internal::infra::repository::sql::idempotency::SqlIdempotencyRepository;
+        use crate::internal::infra::repository::sql::job::SqlJobRepository;
+        use crate::internal::infra::repository::sql::moderation::SqlModerationRepository;
+        use crate::internal::infra::repository::sql::outbox::SqlOutboxRepository;
+        use crate::internal::infra::repository::sql::prompt_template::SqlPromptTemplateRepository;
+        use crate::internal::infra::repository::sql::redaction::SqlRedactionRepository;
+        use crate::internal::infra::repository::sql::scheduled_message::SqlScheduledMessageRepository;
+        use crate::internal::infra::repository::sql::tenant::SqlTenantRepository;
+        use crate::internal::infra::repository::sql::unit_of_work::SqlUnitOfWork;
+        use crate::internal::infra::repository::sql::usage::SqlUsageRepository;
+        use crate::internal::infra::repository::sql::user::SqlUserRepository;
+        use crate::internal::infra::repository::sql::user_memory::SqlMemoryRepository;
+        use crate::internal::infra::repository::sql::webhook::SqlWebhookRepository;
+
+        let dialect = Dialect::of(driver)
+            .ok_or_else(|| RepositoryError::Database(format!("{} is not a sql driver", driver)))?;
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(url)
+            .await
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(Self {
+            chats: Arc::new(SqlChatRepository::new(pool.clone(), dialect, model)),
+            users: Arc::new(SqlUserRepository::new(pool.clone())),
+            api_keys: Arc::new(SqlApiKeyRepository::new(pool.clone())),
+            usage: Arc::new(SqlUsageRepository::new(pool.clone(), dialect)),
+            moderation: Arc::new(SqlModerationRepository::new(pool.clone())),
+            redactions: Arc::new(SqlRedactionRepository::new(pool.clone())),
+            tenants: Arc::new(SqlTenantRepository::new(pool.clone(), dialect)),
+            templates: Arc::new(SqlPromptTemplateRepository::new(pool.clone())),
+            assistants: Arc::new(SqlAssistantRepository::new(pool.clone())),
+            idempotency: Arc::new(SqlIdempotencyRepository::new(pool.clone(), dialect)),
+            documents: Arc::new(InMemoryDocumentRepository::new()),
+            vectors: Arc::new(InMemoryVectorStore::new()),
+            outbox: Arc::new(SqlOutboxRepository::new(pool.clone())),
+            audit: Arc::new(SqlAuditRepository::new(pool.clone())),
+            memories: Arc::new(SqlMemoryRepository::new(pool.clone())),
+            scheduled: Arc::new(SqlScheduledMessageRepository::new(pool.clone())),
+            webhooks: Arc::new(SqlWebhookRepository::new(pool.clone())),
+            batches: Arc::new(SqlBatchRepository::new(pool.clone())),
+            jobs: Arc::new(SqlJobRepository::new(pool.clone())),
+            unit_of_work: Arc::new(SqlUnitOfWork::new(pool.clone(), dialect)),
+            health: Some(Arc::new(SqlHealthCheck::new(
+                &driver.to_string(),
+                pool.clone(),
+            ))),
+            pool: Pool::Sql(pool),
+        })
+    }
+
+    // schema_version reports how far the database schema is from the one the binary expects,
+    // the memory driver has no schema and is always up to date
+    pub async fn schema_version(&self) -> Result<SchemaVersion, RepositoryError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                use crate::internal::infra::repository::migration::{
+                    schema_version, POSTGRES_MIGRATOR,
+                };
+
+                let mut conn = pool
+                    .acquire()
+                    .await
+                    .map_err(|e| RepositoryError::Database(e.to_string()))?;
+                schema_version(&POSTGRES_MIGRATOR, &mut *conn).await
+            }
+            #[cfg(any(feature = "mysql", feature = "sqlite"))]
+            Pool::Sql(pool) => {
+                use crate::internal::infra::repository::migration::{schema_version, SQL_MIGRATOR};
+
+                let mut conn = pool
+                    .acquire()
+                    .await
+                    .map_err(|e| RepositoryError::Database(e.to_string()))?;
+                schema_version(&SQL_MIGRATOR, &mut *conn).await
+            }
+            Pool::Memory => Ok(SchemaVersion::default()),
+        }
+    }
+
+    // migrate applies the pending migrations, migrations applied before are checked to be
+    // unchanged
+    pub async fn migrate(&self) -> Result<SchemaVersion, RepositoryError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                use crate::internal::infra::repository::migration::{
+                    migrate_error, POSTGRES_MIGRATOR,
+                };
+
+                POSTGRES_MIGRATOR.run(pool).await.map_err(migrate_error)?;
+            }
+            #[cfg(any(feature = "mysql", feature = "sqlite"))]
+            Pool::Sql(pool) => {
+                use crate::internal::infra::repository::migration::{migrate_error, SQL_MIGRATOR};
+
+                SQL_MIGRATOR.run(pool).await.map_err(migrate_error)?;
+            }
+            Pool::Memory => {}
+        }
+
+        self.schema_version().await
+    }
+
+    // close waits for the connections in use to be returned and closes the pool
+    pub async fn close(&self) {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => pool.close().await,
+            #[cfg(any(feature = "mysql", feature = "sqlite"))]
+            Pool::Sql(pool) => pool.close().await,
+            Pool::Memory => {}
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Default)]
+pub struct InMemoryApiKeyRepository {
+    // keys are indexed by hash, the only way they are ever looked up
+    api_keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    async fn create_api_key(&self, api_key: &ApiKey) -> Result<(), RepositoryError> {
+        let mut api_keys = self
+            .api_keys
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        api_keys.insert(api_key.key_hash.clone(), api_key.clone());
+
+        Ok(())
+    }
+
+    async fn find_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        let api_keys = self
+            .api_keys
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(api_keys.get(key_hash).cloned())
+    }
+
+    async fn revoke_api_keys(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        keep: Uuid,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, RepositoryError> {
+        let mut api_keys = self
+            .api_keys
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut revoked = 0;
+        for api_key in api_keys.values_mut().filter(|api_key| {
+            api_key.tenant_id == tenant_id
+                && api_key.user_id == user_id
+                && api_key.id != keep
+                && api_key.is_active()
+        }) {
+            api_key.revoked_at = Some(revoked_at);
+            revoked += 1;
+        }
+
+        Ok(revoked)
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Default)]
+pub struct InMemoryAssistantRepository {
+    assistants: RwLock<HashMap<Uuid, Assistant>>,
+}
+
+impl InMemoryAssistantRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// check_name mirrors the unique constraint on assistants (tenant_id, name)
+fn check_name(
+    assistants: &HashMap<Uuid, Assistant>,
+    assistant: &Assistant,
+) -> Result<(), RepositoryError> {
+    let taken = assistants.values().any(|other| {
+        other.id != assistant.id
+            && other.tenant_id == assistant.tenant_id
+            && other.name == assistant.name
+    });
+    if taken {
+        return Err(RepositoryError::Database(format!(
+            "assistant {} already exists",
+            assistant.name
+        )));
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl AssistantRepository for InMemoryAssistantRepository {
+    async fn create_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        let mut assistants = self
+            .assistants
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        check_name(&assistants, assistant)?;
+        assistants.insert(assistant.id, assistant.clone());
+
+        Ok(())
+    }
+
+    async fn find_assistant_by_id(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<Option<Assistant>, RepositoryError> {
+        let assistants = self
+            .assistants
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(assistants
+            .get(&assistant_id)
+            .filter(|assistant| assistant.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_assistants(&self, tenant_id: Uuid) -> Result<Vec<Assistant>, RepositoryError> {
+        let assistants = self
+            .assistants
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<Assistant> = assistants
+            .values()
+            .filter(|assistant| assistant.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(found)
+    }
+
+    async fn update_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        let mut assistants = self
+            .assistants
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        check_name(&assistants, assistant)?;
+        if let Some(stored) = assistants
+            .get_mut(&assistant.id)
+            .filter(|stored| stored.tenant_id == assistant.tenant_id)
+        {
+            *stored = Assistant {
+                created_at: stored.created_at,
+                ..assistant.clone()
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn delete_assistant(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut assistants = self
+            .assistants
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        if assistants
+            .get(&assistant_id)
+            .is_some_and(|assistant| assistant.tenant_id == tenant_id)
+        {
+            assistants.remove(&assistant_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_assistants() {
+        let repository = InMemoryAssistantRepository::new();
+        let tenant_id = Uuid::new_v4();
+        let support = Assistant::new(
+            Uuid::new_v4(),
+            tenant_id,
+            "support",
+            "You help customers.",
+            chrono::Utc::now(),
+        );
+        let sales = Assistant::new(
+            Uuid::new_v4(),
+            tenant_id,
+            "sales",
+            "You sell.",
+            chrono::Utc::now(),
+        );
+        repository.create_assistant(&support).await.unwrap();
+        repository.create_assistant(&sales).await.unwrap();
+        assert!(repository
+            .create_assistant(&Assistant {
+                id: Uuid::new_v4(),
+                ..support.clone()
+            })
+            .await
+            .is_err());
+
+        let names: Vec<String> = repository
+            .list_assistants(tenant_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|assistant| assistant.name)
+            .collect();
+        assert_eq!(names, vec!["sales", "support"]);
+        assert!(repository
+            .find_assistant_by_id(Uuid::new_v4(), support.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let updated = support.clone().with_tools(vec!["get_invoice".to_string()]);
+        repository.update_assistant(&updated).await.unwrap();
+        assert_eq!(
+            repository
+                .find_assistant_by_id(tenant_id, support.id)
+                .await
+                .unwrap(),
+            Some(updated)
+        );
+        assert!(repository
+            .update_assistant(&Assistant {
+                name: "sales".to_string(),
+                ..support.clone()
+            })
+            .await
+            .is_err());
+
+        repository
+            .delete_assistant(tenant_id, support.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.list_assistants(tenant_id).await.unwrap().len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::audit::AuditEntry;
+use crate::internal::domain::repository::audit::{AuditQuery, AuditRepository};
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Default)]
+pub struct InMemoryAuditRepository {
+    entries: RwLock<Vec<AuditEntry>>,
+}
+
+impl InMemoryAuditRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn append(&self, entry: &AuditEntry) -> Result<(), RepositoryError> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        entries.push(entry.clone());
+
+        Ok(())
+    }
+
+    async fn list_entries(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let entries = self
+            .entries
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut matching: Vec<AuditEntry> = entries
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|entry| (entry.created_at, entry.id));
+        matching.truncate(query.limit);
+
+        Ok(matching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::audit::AuditDirection;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::repository::audit::AuditCursor;
+
+    fn entry(user_id: Uuid, direction: AuditDirection, seconds: i64) -> AuditEntry {
+        AuditEntry {
+            id: Uuid::new_v4(),
+            request_id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: Uuid::new_v4(),
+            direction,
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            content: "Hello!".to_string(),
+            error: None,
+            latency_ms: None,
+            prompt_tokens: 10,
+            completion_tokens: 0,
+            created_at: chrono::Utc::now() + chrono::Duration::seconds(seconds),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_entries() {
+        let repository = InMemoryAuditRepository::new();
+        let user_id = Uuid::new_v4();
+        let entries = [
+            entry(user_id, AuditDirection::Response, 2),
+            entry(user_id, AuditDirection::Prompt, 1),
+            entry(Uuid::new_v4(), AuditDirection::Prompt, 0),
+        ];
+        for entry in &entries {
+            repository.append(entry).await.unwrap();
+        }
+        let query = AuditQuery {
+            tenant_id: Some(DEFAULT_TENANT_ID),
+            user_id: Some(user_id),
+            chat_id: None,
+            request_id: None,
+            from: None,
+            to: None,
+            after: None,
+            limit: 1,
+        };
+
+        let page = repository.list_entries(&query).await.unwrap();
+        assert_eq!(page, vec![entries[1].clone()]);
+
+        let page = repository
+            .list_entries(&AuditQuery {
+                after: Some(AuditCursor::of(&page[0])),
+                limit: 10,
+                ..query.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page, vec![entries[0].clone()]);
+
+        let page = repository
+            .list_entries(&AuditQuery {
+                tenant_id: Some(Uuid::new_v4()),
+                ..query
+            })
+            .await
+            .unwrap();
+        assert!(page.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::Batch;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Default)]
+pub struct InMemoryBatchRepository {
+    batches: RwLock<HashMap<Uuid, Batch>>,
+}
+
+impl InMemoryBatchRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BatchRepository for InMemoryBatchRepository {
+    async fn create_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        let mut batches = self
+            .batches
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        batches.insert(batch.id, batch.clone());
+
+        Ok(())
+    }
+
+    async fn find_batch_by_id(
+        &self,
+        tenant_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<Option<Batch>, RepositoryError> {
+        let batches = self
+            .batches
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(batches
+            .get(&batch_id)
+            .filter(|batch| batch.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn complete_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        let mut batches = self
+            .batches
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        if let Some(stored) = batches
+            .get_mut(&batch.id)
+            .filter(|stored| stored.tenant_id == batch.tenant_id)
+        {
+            stored.status = batch.status;
+            stored.items = batch.items.clone();
+            stored.completed_at = batch.completed_at;
+        }
+
+        Ok(())
+    }
+}
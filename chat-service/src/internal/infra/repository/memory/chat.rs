@@ -0,0 +1,808 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatStatus, ChatSummary};
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::repository::chat::{
+    ChatCursor, ChatRepository, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+};
+use crate::internal::domain::repository::outbox::OutboxRepository;
+use crate::internal::domain::text_search::{match_text, search_terms};
+
+#[derive(Default)]
+struct Store {
+    // every write gets a later timestamp than the previous one so listings are ordered
+    // by last update even when writes land on the same clock tick
+    last_write: Option<DateTime<Utc>>,
+    chats: HashMap<Uuid, StoredChat>,
+    // summaries is the projection listings read, written with every chat like the postgres
+    // chat_summaries table
+    summaries: HashMap<Uuid, ChatSummary>,
+    // outbox keeps the events written with the chats in the order they were recorded
+    outbox: Vec<StoredEvent>,
+}
+
+struct StoredEvent {
+    event: OutboxEvent,
+    published_at: Option<DateTime<Utc>>,
+}
+
+struct StoredChat {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    chat: Chat,
+}
+
+impl Store {
+    // append_events does not store twice the events already in the outbox from an earlier save
+    // of the same chat
+    fn append_events(&mut self, events: Vec<OutboxEvent>) {
+        for event in events {
+            if !self.outbox.iter().any(|stored| stored.event.id == event.id) {
+                self.outbox.push(StoredEvent {
+                    event,
+                    published_at: None,
+                });
+            }
+        }
+    }
+
+    fn find(&self, tenant_id: Uuid, id: Uuid) -> Option<&StoredChat> {
+        self.chats
+            .get(&id)
+            .filter(|stored| stored.chat.tenant_id == tenant_id)
+    }
+}
+
+impl StoredChat {
+    fn is_listed(&self, tenant_id: Uuid, user_id: Uuid) -> bool {
+        self.chat.tenant_id == tenant_id && self.chat.user_id == user_id && !self.chat.is_deleted()
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryChatRepository {
+    store: RwLock<Store>,
+}
+
+impl InMemoryChatRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // write stores the chat and tells whether it did; a save has to be made on top of the
+    // stored version and bumps it
+    fn write(&self, chat: &Chat, save: bool) -> Result<bool, RepositoryError> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let now = match store.last_write {
+            Some(last) if last >= Utc::now() => last + chrono::Duration::microseconds(1),
+            _ => Utc::now(),
+        };
+        let previous = store.chats.get(&chat.id);
+        // mirrors the tenant condition of the postgres update
+        if matches!(previous, Some(stored) if stored.chat.tenant_id != chat.tenant_id) {
+            return Ok(false);
+        }
+        if save && matches!(previous, Some(stored) if stored.chat.version != chat.version) {
+            return Err(RepositoryError::ConcurrentModification(chat.id));
+        }
+        let created_at = previous.map_or(now, |stored| stored.created_at);
+        // deleted chats keep the instant they were first deleted at so the retention holds
+        let deleted_at = match chat.is_deleted() {
+            true => previous.and_then(|stored| stored.deleted_at).or(Some(now)),
+            false => None,
+        };
+        // a title set meanwhile survives the save of a copy loaded before it
+        let mut chat = chat.clone();
+        let events = chat.outbox_events();
+        chat.events.clear();
+        if chat.title.is_none() {
+            chat.title = previous.and_then(|stored| stored.chat.title.clone());
+        }
+        if save {
+            chat.version += 1;
+        }
+        store.last_write = Some(now);
+        store
+            .summaries
+            .insert(chat.id, ChatSummary::of(&chat, created_at, now));
+        store.chats.insert(
+            chat.id,
+            StoredChat {
+                created_at,
+                updated_at: now,
+                deleted_at,
+                chat,
+            },
+        );
+        store.append_events(events);
+
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl ChatRepository for InMemoryChatRepository {
+    async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+        self.write(chat, false)?;
+
+        Ok(())
+    }
+
+    async fn find_chat_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Chat>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(store.find(tenant_id, id).map(|stored| stored.chat.clone()))
+    }
+
+    async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        if self.write(chat, true)? {
+            chat.version += 1;
+        }
+
+        Ok(())
+    }
+
+    async fn update_chat_title(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        title: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        if let Some(stored) = store
+            .chats
+            .get_mut(&id)
+            .filter(|stored| stored.chat.tenant_id == tenant_id)
+        {
+            stored.chat.title = Some(title.to_string());
+        }
+        if let Some(summary) = store
+            .summaries
+            .get_mut(&id)
+            .filter(|summary| summary.tenant_id == tenant_id)
+        {
+            summary.title = Some(title.to_string());
+        }
+
+        Ok(())
+    }
+
+    async fn list_chats_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut chats: Vec<&StoredChat> = store
+            .chats
+            .values()
+            .filter(|stored| stored.is_listed(tenant_id, user_id))
+            .collect();
+        chats.sort_by_key(|stored| Reverse(stored.updated_at));
+
+        Ok(chats
+            .into_iter()
+            .map(|stored| stored.chat.clone())
+            .collect())
+    }
+
+    async fn list_chat_summaries(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        tag: Option<&str>,
+        after: Option<ChatCursor>,
+        limit: usize,
+    ) -> Result<Vec<ChatSummary>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut summaries: Vec<&ChatSummary> = store
+            .summaries
+            .values()
+            .filter(|summary| {
+                summary.tenant_id == tenant_id
+                    && summary.user_id == user_id
+                    && summary.status != ChatStatus::Deleted
+            })
+            .filter(|summary| match tag {
+                Some(tag) => summary.tags.iter().any(|t| t == tag),
+                None => true,
+            })
+            .filter(|summary| match after {
+                Some(after) => {
+                    (summary.last_activity_at, summary.id) < (after.last_activity_at, after.id)
+                }
+                None => true,
+            })
+            .collect();
+        summaries.sort_by_key(|summary| Reverse((summary.last_activity_at, summary.id)));
+
+        Ok(summaries.into_iter().take(limit).cloned().collect())
+    }
+
+    async fn find_chat_summary(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ChatSummary>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(store
+            .summaries
+            .get(&id)
+            .filter(|summary| summary.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_messages(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        let Some(stored) = store.find(tenant_id, chat_id) else {
+            return Ok(vec![]);
+        };
+
+        Ok(query.page(&stored.chat.messages))
+    }
+
+    // search_transcripts matches the words of the query as text_search does, there is no
+    // stemming or stop words
+    async fn search_transcripts(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        let terms = search_terms(query);
+
+        let mut matches: Vec<TranscriptMatch> = store
+            .chats
+            .values()
+            .filter(|stored| stored.is_listed(tenant_id, user_id))
+            .flat_map(|stored| {
+                stored
+                    .chat
+                    .messages
+                    .iter()
+                    .filter(|message| matches!(message.role, Role::User | Role::Assistant))
+                    .filter_map(|message| {
+                        let found = match_text(&message.content, &terms)?;
+                        Some(TranscriptMatch {
+                            chat_id: stored.chat.id,
+                            chat_title: stored.chat.title.clone(),
+                            message_id: message.id,
+                            role: message.role,
+                            highlight: found.highlight,
+                            rank: found.rank,
+                            created_at: message.created_at,
+                        })
+                    })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.rank
+                .total_cmp(&a.rank)
+                .then(b.created_at.cmp(&a.created_at))
+        });
+        matches.truncate(limit);
+
+        Ok(matches)
+    }
+
+    async fn list_inactive_chats(
+        &self,
+        inactive_before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut chats: Vec<&StoredChat> = store
+            .chats
+            .values()
+            .filter(|stored| {
+                !stored.chat.is_deleted()
+                    && !stored.chat.is_cold()
+                    && stored.updated_at < inactive_before
+            })
+            .collect();
+        chats.sort_by_key(|stored| (stored.updated_at, stored.chat.id));
+
+        Ok(chats
+            .into_iter()
+            .take(limit)
+            .map(|stored| stored.chat.clone())
+            .collect())
+    }
+
+    async fn archive_chat_history(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let Some(stored) = store
+            .chats
+            .get_mut(&chat.id)
+            .filter(|stored| stored.chat.tenant_id == chat.tenant_id)
+        else {
+            return Ok(());
+        };
+        if stored.chat.version != chat.version {
+            return Err(RepositoryError::ConcurrentModification(chat.id));
+        }
+
+        let title = stored.chat.title.clone();
+        stored.chat = chat.clone();
+        stored.chat.events.clear();
+        stored.chat.title = stored.chat.title.take().or(title);
+        stored.chat.version += 1;
+        let summary = ChatSummary::of(&stored.chat, stored.created_at, stored.updated_at);
+        store.summaries.insert(chat.id, summary);
+        store.append_events(chat.outbox_events());
+        chat.version += 1;
+
+        Ok(())
+    }
+
+    async fn purge_deleted_chats(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<Vec<PurgedChat>, RepositoryError> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let purged: Vec<PurgedChat> = store
+            .chats
+            .values()
+            .filter(|stored| matches!(stored.deleted_at, Some(at) if at < deleted_before))
+            .map(|stored| PurgedChat {
+                id: stored.chat.id,
+                history_key: stored
+                    .chat
+                    .history_archive
+                    .as_ref()
+                    .map(|archive| archive.key.clone()),
+            })
+            .collect();
+        for chat in &purged {
+            store.chats.remove(&chat.id);
+            store.summaries.remove(&chat.id);
+        }
+
+        Ok(purged)
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for InMemoryChatRepository {
+    async fn unpublished_events(&self, limit: usize) -> Result<Vec<OutboxEvent>, RepositoryError> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(store
+            .outbox
+            .iter()
+            .filter(|stored| stored.published_at.is_none())
+            .take(limit)
+            .map(|stored| stored.event.clone())
+            .collect())
+    }
+
+    async fn mark_published(
+        &self,
+        ids: &[Uuid],
+        published_at: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        for stored in store.outbox.iter_mut() {
+            if ids.contains(&stored.event.id) {
+                stored.published_at.get_or_insert(published_at);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus, TrimmingPolicy};
+    use crate::internal::domain::entity::event::ChatEvent;
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+    fn new_chat(user_id: Uuid, model: &Model) -> Chat {
+        let initial_system_message = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let config = ChatConfig {
+            model: model.clone(),
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            tools: vec![],
+            response_format: ResponseFormat::default(),
+            response_language: None,
+        };
+
+        Chat::new(
+            Uuid::new_v4(),
+            user_id,
+            initial_system_message,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            config,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_chat() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let chat = new_chat(Uuid::new_v4(), &model);
+
+        repository.create_chat(&chat).await.unwrap();
+
+        let found = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, chat.id);
+        assert_eq!(found.user_id, chat.user_id);
+        assert!(repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_save_chat() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let mut chat = new_chat(Uuid::new_v4(), &model);
+        repository.create_chat(&chat).await.unwrap();
+
+        chat.messages.push(Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        ));
+        chat.end().unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+
+        let found = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.count_messages(), 1);
+        assert_eq!(found.status, ChatStatus::Ended);
+        assert_eq!(found.version, 1);
+        assert_eq!(chat.version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_chat_rejects_stale_copy() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let mut chat = new_chat(Uuid::new_v4(), &model);
+        repository.create_chat(&chat).await.unwrap();
+        let mut stale = chat.clone();
+
+        chat.end().unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+
+        stale.archive().unwrap();
+        assert!(matches!(
+            repository.save_chat(&mut stale).await,
+            Err(RepositoryError::ConcurrentModification(id)) if id == chat.id
+        ));
+        assert_eq!(stale.version, 0);
+        let found = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.status, ChatStatus::Ended);
+    }
+
+    #[tokio::test]
+    async fn test_save_chat_keeps_title() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let mut chat = new_chat(Uuid::new_v4(), &model);
+        repository.create_chat(&chat).await.unwrap();
+
+        repository
+            .update_chat_title(DEFAULT_TENANT_ID, chat.id, "Greetings")
+            .await
+            .unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+
+        let found = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.title.as_deref(), Some("Greetings"));
+    }
+
+    #[tokio::test]
+    async fn test_list_chats_by_user() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let user_id = Uuid::new_v4();
+        let mut first = new_chat(user_id, &model);
+        let second = new_chat(user_id, &model);
+        let other = new_chat(Uuid::new_v4(), &model);
+
+        repository.create_chat(&first).await.unwrap();
+        repository.create_chat(&second).await.unwrap();
+        repository.create_chat(&other).await.unwrap();
+        repository.save_chat(&mut first).await.unwrap();
+
+        let chats = repository
+            .list_chats_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = chats.iter().map(|chat| chat.id).collect();
+
+        assert_eq!(ids, vec![first.id, second.id]);
+    }
+
+    #[tokio::test]
+    async fn test_list_chat_summaries() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let user_id = Uuid::new_v4();
+        let mut chats: Vec<Chat> = (0..3).map(|_| new_chat(user_id, &model)).collect();
+        for chat in &chats {
+            repository.create_chat(chat).await.unwrap();
+        }
+        repository
+            .create_chat(&new_chat(Uuid::new_v4(), &model))
+            .await
+            .unwrap();
+        repository.save_chat(&mut chats[0]).await.unwrap();
+
+        let page = repository
+            .list_chat_summaries(DEFAULT_TENANT_ID, user_id, None, None, 2)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = page.iter().map(|summary| summary.id).collect();
+        assert_eq!(ids, vec![chats[0].id, chats[2].id]);
+        assert!(page[0].last_activity_at > page[0].created_at);
+        assert_eq!(page[0].model, "gpt-3.5-turbo");
+
+        let after = ChatCursor::of(&page[1]);
+        let page = repository
+            .list_chat_summaries(DEFAULT_TENANT_ID, user_id, None, Some(after), 2)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = page.iter().map(|summary| summary.id).collect();
+        assert_eq!(ids, vec![chats[1].id]);
+
+        chats[1].set_tags(&["support".to_string()]).unwrap();
+        repository.save_chat(&mut chats[1]).await.unwrap();
+        let page = repository
+            .list_chat_summaries(DEFAULT_TENANT_ID, user_id, Some("support"), None, 10)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = page.iter().map(|summary| summary.id).collect();
+        assert_eq!(ids, vec![chats[1].id]);
+        assert_eq!(page[0].tags, vec!["support"]);
+
+        // titling reaches the projection without a save
+        repository
+            .update_chat_title(DEFAULT_TENANT_ID, chats[1].id, "Support")
+            .await
+            .unwrap();
+        let summary = repository
+            .find_chat_summary(DEFAULT_TENANT_ID, chats[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.title.as_deref(), Some("Support"));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_chats_are_hidden_and_purged() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let user_id = Uuid::new_v4();
+        let kept = new_chat(user_id, &model);
+        let mut deleted = new_chat(user_id, &model);
+        repository.create_chat(&kept).await.unwrap();
+        repository.create_chat(&deleted).await.unwrap();
+
+        deleted.delete().unwrap();
+        repository.save_chat(&mut deleted).await.unwrap();
+
+        let chats = repository
+            .list_chats_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap();
+        assert_eq!(chats.len(), 1);
+        let page = repository
+            .list_chat_summaries(DEFAULT_TENANT_ID, user_id, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, kept.id);
+
+        let purged = repository
+            .purge_deleted_chats(Utc::now() - chrono::Duration::seconds(60))
+            .await
+            .unwrap();
+        assert!(purged.is_empty());
+
+        let purged = repository
+            .purge_deleted_chats(Utc::now() + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            purged,
+            vec![PurgedChat {
+                id: deleted.id,
+                history_key: None
+            }]
+        );
+        assert!(repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, deleted.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, kept.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_chats_are_scoped_by_tenant() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let tenant_id = Uuid::new_v4();
+        let chat = new_chat(Uuid::new_v4(), &model).with_tenant(tenant_id);
+        repository.create_chat(&chat).await.unwrap();
+
+        assert!(repository
+            .find_chat_by_id(tenant_id, chat.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .list_chats_by_user(DEFAULT_TENANT_ID, chat.user_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // a copy claiming another tenant cannot overwrite the chat
+        let mut hijacked = chat.clone().with_tenant(DEFAULT_TENANT_ID);
+        hijacked.end().unwrap();
+        repository.save_chat(&mut hijacked).await.unwrap();
+        let found = repository
+            .find_chat_by_id(tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.status, ChatStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_outbox_events() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = InMemoryChatRepository::new();
+        let mut chat = new_chat(Uuid::new_v4(), &model);
+        chat.record(ChatEvent::ChatCreated {
+            model: model.name.clone(),
+        });
+        repository.create_chat(&chat).await.unwrap();
+
+        chat.end().unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+
+        let events = repository.unpublished_events(10).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event, ChatEvent::ChatEnded);
+        assert!(repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .events
+            .is_empty());
+
+        repository
+            .mark_published(&[events[0].id], Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.unpublished_events(10).await.unwrap(),
+            vec![events[1].clone()]
+        );
+        assert_eq!(repository.unpublished_events(0).await.unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::document::Document;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::document::DocumentRepository;
+
+#[derive(Default)]
+pub struct InMemoryDocumentRepository {
+    documents: RwLock<HashMap<Uuid, Document>>,
+}
+
+impl InMemoryDocumentRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DocumentRepository for InMemoryDocumentRepository {
+    async fn create_document(&self, document: &Document) -> Result<(), RepositoryError> {
+        let mut documents = self
+            .documents
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        documents.insert(document.id, document.clone());
+
+        Ok(())
+    }
+
+    async fn find_document_by_id(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<Option<Document>, RepositoryError> {
+        let documents = self
+            .documents
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(documents
+            .get(&document_id)
+            .filter(|document| document.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_documents_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Document>, RepositoryError> {
+        let documents = self
+            .documents
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<Document> = documents
+            .values()
+            .filter(|document| document.tenant_id == tenant_id && document.user_id == user_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+
+        Ok(found)
+    }
+
+    async fn delete_document(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut documents = self
+            .documents
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        documents.retain(|id, document| !(*id == document_id && document.tenant_id == tenant_id));
+
+        Ok(())
+    }
+}
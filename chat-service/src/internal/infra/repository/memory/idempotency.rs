@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::idempotency::IdempotencyRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::idempotency::IdempotencyRepository;
+
+type RecordKey = (Uuid, Uuid, String);
+
+#[derive(Default)]
+pub struct InMemoryIdempotencyRepository {
+    records: RwLock<HashMap<RecordKey, IdempotencyRecord>>,
+}
+
+impl InMemoryIdempotencyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for InMemoryIdempotencyRepository {
+    async fn reserve(
+        &self,
+        record: &IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError> {
+        let mut records = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        records.retain(|_, existing| !existing.is_expired(record.created_at));
+
+        let key = (record.tenant_id, record.user_id, record.key.clone());
+        if let Some(existing) = records.get(&key) {
+            return Ok(Some(existing.clone()));
+        }
+
+        records.insert(key, record.clone());
+
+        Ok(None)
+    }
+
+    async fn complete(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), RepositoryError> {
+        let mut records = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        if let Some(record) = records.get_mut(&(tenant_id, user_id, key.to_string())) {
+            record.response = Some(response.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn release(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut records = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        records.remove(&(tenant_id, user_id, key.to_string()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(user_id: Uuid, now: chrono::DateTime<chrono::Utc>) -> IdempotencyRecord {
+        IdempotencyRecord::new(
+            Uuid::nil(),
+            user_id,
+            "retry-1",
+            "fingerprint",
+            now,
+            chrono::Duration::seconds(60),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_reserve_complete_and_release() {
+        let repository = InMemoryIdempotencyRepository::new();
+        let user_id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+
+        assert!(repository
+            .reserve(&record(user_id, now))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .reserve(&record(Uuid::new_v4(), now))
+            .await
+            .unwrap()
+            .is_none());
+
+        repository
+            .complete(
+                Uuid::nil(),
+                user_id,
+                "retry-1",
+                &json!({ "content": "Hi!" }),
+            )
+            .await
+            .unwrap();
+        let existing = repository
+            .reserve(&record(user_id, now))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(existing.response, Some(json!({ "content": "Hi!" })));
+
+        repository
+            .release(Uuid::nil(), user_id, "retry-1")
+            .await
+            .unwrap();
+        assert!(repository
+            .reserve(&record(user_id, now))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reserve_expired_key() {
+        let repository = InMemoryIdempotencyRepository::new();
+        let user_id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+
+        repository.reserve(&record(user_id, now)).await.unwrap();
+
+        let later = now + chrono::Duration::seconds(61);
+        assert!(repository
+            .reserve(&record(user_id, later))
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::job::{Job, JobStatus};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::job::JobRepository;
+
+#[derive(Default)]
+pub struct InMemoryJobRepository {
+    jobs: RwLock<HashMap<Uuid, Job>>,
+}
+
+impl InMemoryJobRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// is_claimable tells whether a job is queued or was left running by a worker whose claim is over
+fn is_claimable(job: &Job, now: DateTime<Utc>) -> bool {
+    match job.status {
+        JobStatus::Queued => true,
+        JobStatus::Running => job.claimed_until.is_none_or(|until| until < now),
+        JobStatus::Succeeded | JobStatus::Failed => false,
+    }
+}
+
+#[async_trait]
+impl JobRepository for InMemoryJobRepository {
+    async fn create_job(&self, job: &Job) -> Result<(), RepositoryError> {
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        jobs.insert(job.id, job.clone());
+
+        Ok(())
+    }
+
+    async fn find_job_by_id(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<Job>, RepositoryError> {
+        let jobs = self
+            .jobs
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(jobs
+            .get(&job_id)
+            .filter(|job| job.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_claimable(
+        &self,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<Job>, RepositoryError> {
+        let jobs = self
+            .jobs
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut claimable: Vec<Job> = jobs
+            .values()
+            .filter(|job| is_claimable(job, now))
+            .cloned()
+            .collect();
+        claimable.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        claimable.truncate(limit);
+
+        Ok(claimable)
+    }
+
+    async fn claim_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        now: DateTime<Utc>,
+        claimed_until: DateTime<Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        match jobs.get_mut(&job_id) {
+            Some(job) if job.tenant_id == tenant_id && is_claimable(job, now) => {
+                job.status = JobStatus::Running;
+                job.claimed_until = Some(claimed_until);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn complete_job(
+        &self,
+        job: &Job,
+        claimed_until: DateTime<Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        match jobs.get_mut(&job.id) {
+            Some(stored)
+                if stored.tenant_id == job.tenant_id
+                    && stored.status == JobStatus::Running
+                    && stored.claimed_until == Some(claimed_until) =>
+            {
+                stored.status = job.status;
+                stored.chat_id = job.chat_id;
+                stored.content = job.content.clone();
+                stored.error = job.error.clone();
+                stored.completed_at = job.completed_at;
+                stored.claimed_until = None;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
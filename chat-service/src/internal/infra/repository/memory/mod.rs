@@ -0,0 +1,19 @@
+pub mod api_key;
+pub mod assistant;
+pub mod audit;
+pub mod batch;
+pub mod chat;
+pub mod document;
+pub mod idempotency;
+pub mod job;
+pub mod moderation;
+pub mod prompt_template;
+pub mod redaction;
+pub mod scheduled_message;
+pub mod tenant;
+pub mod unit_of_work;
+pub mod usage;
+pub mod user;
+pub mod user_memory;
+pub mod vector_store;
+pub mod webhook;
@@ -0,0 +1,50 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::moderation::ModerationFlag;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::moderation::ModerationRepository;
+
+#[derive(Default)]
+pub struct InMemoryModerationRepository {
+    // flags are kept in insertion order, which is oldest first
+    flags: RwLock<Vec<ModerationFlag>>,
+}
+
+impl InMemoryModerationRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ModerationRepository for InMemoryModerationRepository {
+    async fn record_flag(&self, flag: &ModerationFlag) -> Result<(), RepositoryError> {
+        let mut flags = self
+            .flags
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        flags.push(flag.clone());
+
+        Ok(())
+    }
+
+    async fn list_flags_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ModerationFlag>, RepositoryError> {
+        let flags = self
+            .flags
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(flags
+            .iter()
+            .filter(|flag| flag.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+
+#[derive(Default)]
+pub struct InMemoryPromptTemplateRepository {
+    templates: RwLock<HashMap<String, PromptTemplate>>,
+}
+
+impl InMemoryPromptTemplateRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PromptTemplateRepository for InMemoryPromptTemplateRepository {
+    async fn create_template(&self, template: &PromptTemplate) -> Result<(), RepositoryError> {
+        let mut templates = self
+            .templates
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        // mirrors the unique constraint on prompt_templates.name
+        if templates.contains_key(&template.name) {
+            return Err(RepositoryError::Database(format!(
+                "prompt template {} already exists",
+                template.name
+            )));
+        }
+
+        templates.insert(template.name.clone(), template.clone());
+
+        Ok(())
+    }
+
+    async fn find_template_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<PromptTemplate>, RepositoryError> {
+        let templates = self
+            .templates
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(templates.get(name).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_create_and_find_template() {
+        let repository = InMemoryPromptTemplateRepository::new();
+        let template = PromptTemplate::new(
+            Uuid::new_v4(),
+            "support",
+            "You help {{user_name}}.",
+            chrono::Utc::now(),
+        );
+
+        repository.create_template(&template).await.unwrap();
+
+        assert_eq!(
+            repository.find_template_by_name("support").await.unwrap(),
+            Some(template.clone())
+        );
+        assert!(repository
+            .find_template_by_name("sales")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository.create_template(&template).await.is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::redaction::RedactionRepository;
+
+#[derive(Default)]
+pub struct InMemoryRedactionRepository {
+    records: RwLock<Vec<RedactionRecord>>,
+}
+
+impl InMemoryRedactionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RedactionRepository for InMemoryRedactionRepository {
+    async fn record_redaction(&self, record: &RedactionRecord) -> Result<(), RepositoryError> {
+        let mut records = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        records.push(record.clone());
+
+        Ok(())
+    }
+
+    async fn find_redaction(
+        &self,
+        tenant_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<Option<RedactionRecord>, RepositoryError> {
+        let records = self
+            .records
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(records
+            .iter()
+            .find(|record| record.tenant_id == tenant_id && record.message_id == message_id)
+            .cloned())
+    }
+}
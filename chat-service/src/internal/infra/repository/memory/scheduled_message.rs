@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+
+#[derive(Default)]
+pub struct InMemoryScheduledMessageRepository {
+    scheduled: RwLock<HashMap<Uuid, ScheduledMessage>>,
+}
+
+impl InMemoryScheduledMessageRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScheduledMessageRepository for InMemoryScheduledMessageRepository {
+    async fn create_scheduled_message(
+        &self,
+        scheduled: &ScheduledMessage,
+    ) -> Result<(), RepositoryError> {
+        let mut stored = self
+            .scheduled
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        stored.insert(scheduled.id, scheduled.clone());
+
+        Ok(())
+    }
+
+    async fn find_scheduled_message_by_id(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+    ) -> Result<Option<ScheduledMessage>, RepositoryError> {
+        let scheduled = self
+            .scheduled
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(scheduled
+            .get(&scheduled_id)
+            .filter(|scheduled| scheduled.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_pending_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let scheduled = self
+            .scheduled
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<ScheduledMessage> = scheduled
+            .values()
+            .filter(|scheduled| {
+                scheduled.tenant_id == tenant_id
+                    && scheduled.user_id == user_id
+                    && scheduled.status == ScheduledStatus::Pending
+            })
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.send_at.cmp(&b.send_at).then(a.id.cmp(&b.id)));
+
+        Ok(found)
+    }
+
+    async fn list_due(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let scheduled = self
+            .scheduled
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut due: Vec<ScheduledMessage> = scheduled
+            .values()
+            .filter(|scheduled| scheduled.is_due(now))
+            .cloned()
+            .collect();
+        due.sort_by(|a, b| a.send_at.cmp(&b.send_at).then(a.id.cmp(&b.id)));
+        due.truncate(limit);
+
+        Ok(due)
+    }
+
+    async fn claim_due(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let mut scheduled = self
+            .scheduled
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        match scheduled.get_mut(&scheduled_id) {
+            Some(scheduled) if scheduled.tenant_id == tenant_id && scheduled.is_due(now) => {
+                scheduled.status = ScheduledStatus::Sending;
+                scheduled.claimed_until = Some(claimed_until);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn transition(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        from: ScheduledStatus,
+        to: ScheduledStatus,
+        error: Option<&str>,
+    ) -> Result<bool, RepositoryError> {
+        let mut scheduled = self
+            .scheduled
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        match scheduled.get_mut(&scheduled_id) {
+            Some(scheduled) if scheduled.tenant_id == tenant_id && scheduled.status == from => {
+                scheduled.status = to;
+                scheduled.error = error.map(str::to_string);
+                scheduled.claimed_until = None;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
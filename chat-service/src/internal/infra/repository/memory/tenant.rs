@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::tenant::Tenant;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::tenant::TenantRepository;
+
+#[derive(Default)]
+pub struct InMemoryTenantRepository {
+    tenants: RwLock<HashMap<Uuid, Tenant>>,
+}
+
+impl InMemoryTenantRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TenantRepository for InMemoryTenantRepository {
+    async fn save_tenant(&self, tenant: &Tenant) -> Result<(), RepositoryError> {
+        let mut tenants = self
+            .tenants
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        tenants.insert(tenant.id, tenant.clone());
+
+        Ok(())
+    }
+
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, RepositoryError> {
+        let tenants = self
+            .tenants
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut tenants: Vec<Tenant> = tenants.values().cloned().collect();
+        tenants.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+
+        Ok(tenants)
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+use crate::internal::domain::repository::unit_of_work::{ChatTransaction, UnitOfWork};
+use crate::internal::domain::repository::usage::UsageRepository;
+
+// InMemoryUnitOfWork keeps the writes of a transaction aside and hands them to the
+// repositories on commit, the in memory repositories cannot fail halfway through
+pub struct InMemoryUnitOfWork {
+    chats: Arc<dyn ChatRepository>,
+    usage: Arc<dyn UsageRepository>,
+}
+
+impl InMemoryUnitOfWork {
+    pub fn new(chats: Arc<dyn ChatRepository>, usage: Arc<dyn UsageRepository>) -> Self {
+        Self { chats, usage }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn ChatTransaction>, RepositoryError> {
+        Ok(Box::new(InMemoryChatTransaction {
+            chats: self.chats.clone(),
+            usage: self.usage.clone(),
+            writes: vec![],
+        }))
+    }
+}
+
+enum Write {
+    CreateChat(Chat),
+    SaveChat(Chat),
+    RecordUsage(UsageRecord),
+}
+
+struct InMemoryChatTransaction {
+    chats: Arc<dyn ChatRepository>,
+    usage: Arc<dyn UsageRepository>,
+    writes: Vec<Write>,
+}
+
+#[async_trait]
+impl ChatTransaction for InMemoryChatTransaction {
+    async fn create_chat(&mut self, chat: &Chat) -> Result<(), RepositoryError> {
+        self.writes.push(Write::CreateChat(chat.clone()));
+        Ok(())
+    }
+
+    // save_chat bumps the version as the commit will, a stale chat is only reported there
+    async fn save_chat(&mut self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        self.writes.push(Write::SaveChat(chat.clone()));
+        chat.version += 1;
+        Ok(())
+    }
+
+    async fn record_usage(&mut self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        self.writes.push(Write::RecordUsage(record.clone()));
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepositoryError> {
+        for write in self.writes {
+            match write {
+                Write::CreateChat(chat) => self.chats.create_chat(&chat).await?,
+                Write::SaveChat(mut chat) => self.chats.save_chat(&mut chat).await?,
+                Write::RecordUsage(record) => self.usage.record_usage(&record).await?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn new_chat() -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::builder(model).build().unwrap(),
+        )
+    }
+
+    fn usage(chat: &Chat) -> UsageRecord {
+        UsageRecord {
+            tenant_id: chat.tenant_id,
+            user_id: chat.user_id,
+            chat_id: chat.id,
+            model: chat.config.model.name.clone(),
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            cost: 0.0,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_and_rollback() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let usage_repository = Arc::new(InMemoryUsageRepository::new());
+        let unit_of_work = InMemoryUnitOfWork::new(chats.clone(), usage_repository.clone());
+
+        let dropped = new_chat();
+        let mut tx = unit_of_work.begin().await.unwrap();
+        tx.create_chat(&dropped).await.unwrap();
+        tx.record_usage(&usage(&dropped)).await.unwrap();
+        drop(tx);
+
+        let mut committed = new_chat();
+        let mut tx = unit_of_work.begin().await.unwrap();
+        tx.create_chat(&committed).await.unwrap();
+        tx.save_chat(&mut committed).await.unwrap();
+        tx.record_usage(&usage(&committed)).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert!(chats
+            .find_chat_by_id(DEFAULT_TENANT_ID, dropped.id)
+            .await
+            .unwrap()
+            .is_none());
+        let stored = chats
+            .find_chat_by_id(DEFAULT_TENANT_ID, committed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, committed.version);
+        let chat_usage = usage_repository
+            .list_chat_usage(DEFAULT_TENANT_ID, &[dropped.id, committed.id])
+            .await
+            .unwrap();
+        assert_eq!(chat_usage.len(), 1);
+        assert_eq!(chat_usage[0].chat_id, committed.id);
+    }
+}
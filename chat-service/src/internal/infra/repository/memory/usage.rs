@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::{
+    ChatUsage, DailyUsage, TenantUsage, UsageRecord, UserUsage,
+};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::usage::UsageRepository;
+
+type UsageKey = (Uuid, Uuid, chrono::NaiveDate, String);
+
+#[derive(Default)]
+pub struct InMemoryUsageRepository {
+    // ordered by tenant, user then date so range queries come out oldest first
+    usage: RwLock<BTreeMap<UsageKey, DailyUsage>>,
+    chats: RwLock<HashMap<(Uuid, Uuid), ChatUsage>>,
+}
+
+impl InMemoryUsageRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageRepository for InMemoryUsageRepository {
+    async fn record_usage(&self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        let mut usage = self
+            .usage
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        usage
+            .entry((
+                record.tenant_id,
+                record.user_id,
+                record.date(),
+                record.model.clone(),
+            ))
+            .or_insert_with(|| DailyUsage::from_record(record))
+            .add(record);
+
+        self.chats
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?
+            .entry((record.tenant_id, record.chat_id))
+            .or_insert_with(|| ChatUsage::new(record.chat_id))
+            .add(record);
+
+        Ok(())
+    }
+
+    async fn list_daily_usage(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<DailyUsage>, RepositoryError> {
+        let usage = self
+            .usage
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(usage
+            .iter()
+            .filter(|((tenant, user, date, _), _)| {
+                *tenant == tenant_id && *user == user_id && *date >= from && *date <= to
+            })
+            .map(|(_, daily)| daily.clone())
+            .collect())
+    }
+
+    async fn list_chat_usage(
+        &self,
+        tenant_id: Uuid,
+        chat_ids: &[Uuid],
+    ) -> Result<Vec<ChatUsage>, RepositoryError> {
+        let chats = self
+            .chats
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(chat_ids
+            .iter()
+            .filter_map(|id| chats.get(&(tenant_id, *id)).cloned())
+            .collect())
+    }
+
+    async fn summarize_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<TenantUsage>, RepositoryError> {
+        let usage = self
+            .usage
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut summary: BTreeMap<(Uuid, String), (TenantUsage, HashSet<Uuid>)> = BTreeMap::new();
+        for ((tenant, user, date, model), daily) in usage.iter() {
+            if tenant_id.is_some_and(|id| id != *tenant) || *date < from || *date > to {
+                continue;
+            }
+
+            let (total, users) = summary.entry((*tenant, model.clone())).or_insert_with(|| {
+                (
+                    TenantUsage {
+                        tenant_id: *tenant,
+                        model: model.clone(),
+                        users: 0,
+                        requests: 0,
+                        prompt_tokens: 0,
+                        completion_tokens: 0,
+                        cost: 0.0,
+                    },
+                    HashSet::new(),
+                )
+            });
+            users.insert(*user);
+            total.users = users.len() as u64;
+            total.requests += daily.requests;
+            total.prompt_tokens += daily.prompt_tokens;
+            total.completion_tokens += daily.completion_tokens;
+            total.cost += daily.cost;
+        }
+
+        Ok(summary.into_values().map(|(total, _)| total).collect())
+    }
+
+    async fn summarize_user_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<UserUsage>, RepositoryError> {
+        let usage = self
+            .usage
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut summary: BTreeMap<(Uuid, Uuid, String), UserUsage> = BTreeMap::new();
+        for ((tenant, user, date, model), daily) in usage.iter() {
+            if tenant_id.is_some_and(|id| id != *tenant) || *date < from || *date > to {
+                continue;
+            }
+
+            let total = summary
+                .entry((*tenant, *user, model.clone()))
+                .or_insert_with(|| UserUsage {
+                    tenant_id: *tenant,
+                    user_id: *user,
+                    model: model.clone(),
+                    requests: 0,
+                    prompt_tokens: 0,
+                    completion_tokens: 0,
+                    cost: 0.0,
+                });
+            total.requests += daily.requests;
+            total.prompt_tokens += daily.prompt_tokens;
+            total.completion_tokens += daily.completion_tokens;
+            total.cost += daily.cost;
+        }
+
+        Ok(summary.into_values().collect())
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user::UserRepository;
+
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: RwLock<HashMap<Uuid, User>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create_user(&self, user: &User) -> Result<(), RepositoryError> {
+        let mut users = self
+            .users
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        // mirrors the unique constraint on users (tenant_id, external_id)
+        if users.values().any(|existing| {
+            existing.tenant_id == user.tenant_id
+                && existing.external_id == user.external_id
+                && existing.id != user.id
+        }) {
+            return Err(RepositoryError::Database(format!(
+                "user with external_id {} already exists",
+                user.external_id
+            )));
+        }
+
+        users.insert(user.id, user.clone());
+
+        Ok(())
+    }
+
+    async fn find_user_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<User>, RepositoryError> {
+        let users = self
+            .users
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(users
+            .get(&id)
+            .filter(|user| user.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: Uuid,
+        external_id: &str,
+    ) -> Result<Option<User>, RepositoryError> {
+        let users = self
+            .users
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(users
+            .values()
+            .find(|user| user.tenant_id == tenant_id && user.external_id == external_id)
+            .cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_create_and_find_user() {
+        let repository = InMemoryUserRepository::new();
+        let user = User::new(Uuid::new_v4(), "auth0|42", "Ada", chrono::Utc::now());
+
+        repository.create_user(&user).await.unwrap();
+
+        assert_eq!(
+            repository
+                .find_user_by_id(DEFAULT_TENANT_ID, user.id)
+                .await
+                .unwrap(),
+            Some(user.clone())
+        );
+        assert_eq!(
+            repository
+                .find_user_by_external_id(DEFAULT_TENANT_ID, "auth0|42")
+                .await
+                .unwrap(),
+            Some(user.clone())
+        );
+        assert!(repository
+            .find_user_by_id(DEFAULT_TENANT_ID, Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .find_user_by_id(Uuid::new_v4(), user.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_external_id() {
+        let repository = InMemoryUserRepository::new();
+        let now = chrono::Utc::now();
+
+        repository
+            .create_user(&User::new(Uuid::new_v4(), "auth0|42", "Ada", now))
+            .await
+            .unwrap();
+
+        let result = repository
+            .create_user(&User::new(Uuid::new_v4(), "auth0|42", "Grace", now))
+            .await;
+        assert!(result.is_err());
+
+        // external ids are only unique within a tenant
+        repository
+            .create_user(
+                &User::new(Uuid::new_v4(), "auth0|42", "Grace", now).with_tenant(Uuid::new_v4()),
+            )
+            .await
+            .unwrap();
+    }
+}
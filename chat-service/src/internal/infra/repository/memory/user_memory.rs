@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user_memory::Memory;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+
+#[derive(Default)]
+pub struct InMemoryMemoryRepository {
+    memories: RwLock<HashMap<Uuid, Memory>>,
+}
+
+impl InMemoryMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MemoryRepository for InMemoryMemoryRepository {
+    async fn create_memories(
+        &self,
+        memories: &[Memory],
+        max_memories: usize,
+    ) -> Result<usize, RepositoryError> {
+        let mut stored = self
+            .memories
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut created = 0;
+        for memory in memories {
+            let count = stored
+                .values()
+                .filter(|m| m.tenant_id == memory.tenant_id && m.user_id == memory.user_id)
+                .count();
+            if count < max_memories {
+                stored.insert(memory.id, memory.clone());
+                created += 1;
+            }
+        }
+
+        Ok(created)
+    }
+
+    async fn find_memory_by_id(
+        &self,
+        tenant_id: Uuid,
+        memory_id: Uuid,
+    ) -> Result<Option<Memory>, RepositoryError> {
+        let memories = self
+            .memories
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(memories
+            .get(&memory_id)
+            .filter(|memory| memory.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_memories_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Memory>, RepositoryError> {
+        let memories = self
+            .memories
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<Memory> = memories
+            .values()
+            .filter(|memory| memory.tenant_id == tenant_id && memory.user_id == user_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        Ok(found)
+    }
+
+    async fn delete_memory(&self, tenant_id: Uuid, memory_id: Uuid) -> Result<(), RepositoryError> {
+        let mut memories = self
+            .memories
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        memories.retain(|id, memory| !(*id == memory_id && memory.tenant_id == tenant_id));
+
+        Ok(())
+    }
+}
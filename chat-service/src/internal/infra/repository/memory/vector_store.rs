@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::embedding::{cosine_similarity, VectorMatch, VectorRecord};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::vector_store::{VectorQuery, VectorStore};
+
+// InMemoryVectorStore compares the query with every record of the user
+#[derive(Default)]
+pub struct InMemoryVectorStore {
+    records: RwLock<HashMap<Uuid, VectorRecord>>,
+}
+
+impl InMemoryVectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn upsert(&self, records: &[VectorRecord]) -> Result<(), RepositoryError> {
+        let mut stored = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        for record in records {
+            stored.insert(record.id, record.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn search(&self, query: &VectorQuery) -> Result<Vec<VectorMatch>, RepositoryError> {
+        let records = self
+            .records
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut matches: Vec<VectorMatch> = records
+            .values()
+            .filter(|record| {
+                record.tenant_id == query.tenant_id
+                    && record.user_id == query.user_id
+                    && record.model == query.model
+                    && (query.kinds.is_empty() || query.kinds.contains(&record.kind))
+            })
+            .map(|record| record.to_match(cosine_similarity(&record.embedding, &query.embedding)))
+            .filter(|found| found.score >= query.min_score)
+            .collect();
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        matches.truncate(query.limit);
+
+        Ok(matches)
+    }
+
+    async fn delete_by_source(
+        &self,
+        tenant_id: Uuid,
+        source_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut records = self
+            .records
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        records
+            .retain(|_, record| !(record.tenant_id == tenant_id && record.source_id == source_id));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::embedding::VectorKind;
+
+    fn record(
+        user_id: Uuid,
+        kind: VectorKind,
+        source_id: Uuid,
+        embedding: Vec<f32>,
+    ) -> VectorRecord {
+        VectorRecord {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            user_id,
+            kind,
+            source_id,
+            content: format!("{:?}", embedding),
+            model: "text-embedding-3-small".to_string(),
+            embedding,
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn query(user_id: Uuid, embedding: Vec<f32>) -> VectorQuery {
+        VectorQuery {
+            tenant_id: Uuid::nil(),
+            user_id,
+            model: "text-embedding-3-small".to_string(),
+            embedding,
+            kinds: vec![],
+            limit: 10,
+            min_score: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let store = InMemoryVectorStore::new();
+        let user_id = Uuid::new_v4();
+        let document_id = Uuid::new_v4();
+        let close = record(
+            user_id,
+            VectorKind::DocumentChunk,
+            document_id,
+            vec![1.0, 0.1],
+        );
+        let far = record(user_id, VectorKind::Message, Uuid::new_v4(), vec![0.2, 1.0]);
+        let opposite = record(
+            user_id,
+            VectorKind::Message,
+            Uuid::new_v4(),
+            vec![-1.0, 0.0],
+        );
+        let other_user = record(
+            Uuid::new_v4(),
+            VectorKind::Message,
+            Uuid::new_v4(),
+            vec![1.0, 0.0],
+        );
+        let mut other_model = record(user_id, VectorKind::Message, Uuid::new_v4(), vec![1.0, 0.0]);
+        other_model.model = "text-embedding-3-large".to_string();
+        store
+            .upsert(&[
+                close.clone(),
+                far.clone(),
+                opposite,
+                other_user,
+                other_model,
+            ])
+            .await
+            .unwrap();
+
+        let matches = store.search(&query(user_id, vec![1.0, 0.0])).await.unwrap();
+        assert_eq!(
+            matches.iter().map(|found| found.id).collect::<Vec<_>>(),
+            vec![close.id, far.id]
+        );
+        assert!(matches[0].score > matches[1].score);
+
+        let messages = store
+            .search(&VectorQuery {
+                kinds: vec![VectorKind::Message],
+                limit: 1,
+                ..query(user_id, vec![1.0, 0.0])
+            })
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id, far.id);
+
+        store
+            .delete_by_source(Uuid::nil(), document_id)
+            .await
+            .unwrap();
+        let matches = store.search(&query(user_id, vec![1.0, 0.0])).await.unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, Webhook, WebhookDelivery};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+
+#[derive(Default)]
+pub struct InMemoryWebhookRepository {
+    webhooks: RwLock<HashMap<Uuid, Webhook>>,
+    deliveries: RwLock<HashMap<Uuid, WebhookDelivery>>,
+    dead_letters: RwLock<Vec<DeadLetter>>,
+}
+
+impl InMemoryWebhookRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for InMemoryWebhookRepository {
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<(), RepositoryError> {
+        let mut webhooks = self
+            .webhooks
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        webhooks.insert(webhook.id, webhook.clone());
+
+        Ok(())
+    }
+
+    async fn find_webhook_by_id(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<Option<Webhook>, RepositoryError> {
+        let webhooks = self
+            .webhooks
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(webhooks
+            .get(&webhook_id)
+            .filter(|webhook| webhook.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_webhooks(&self, tenant_id: Uuid) -> Result<Vec<Webhook>, RepositoryError> {
+        let webhooks = self
+            .webhooks
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<Webhook> = webhooks
+            .values()
+            .filter(|webhook| webhook.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        Ok(found)
+    }
+
+    async fn delete_webhook(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut webhooks = self
+            .webhooks
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        if webhooks
+            .get(&webhook_id)
+            .is_some_and(|webhook| webhook.tenant_id == tenant_id)
+        {
+            webhooks.remove(&webhook_id);
+            let mut deliveries = self
+                .deliveries
+                .write()
+                .map_err(|e| RepositoryError::Database(e.to_string()))?;
+            deliveries.retain(|_, delivery| delivery.webhook_id != webhook_id);
+        }
+
+        Ok(())
+    }
+
+    async fn enqueue_deliveries(
+        &self,
+        deliveries: &[WebhookDelivery],
+    ) -> Result<(), RepositoryError> {
+        let mut stored = self
+            .deliveries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        for delivery in deliveries {
+            stored.insert(delivery.id, delivery.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn due_deliveries(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let deliveries = self
+            .deliveries
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut due: Vec<WebhookDelivery> = deliveries
+            .values()
+            .filter(|delivery| delivery.next_attempt_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by(|a, b| {
+            a.next_attempt_at
+                .cmp(&b.next_attempt_at)
+                .then(a.id.cmp(&b.id))
+        });
+        due.truncate(limit);
+
+        Ok(due)
+    }
+
+    async fn claim_delivery(
+        &self,
+        delivery_id: Uuid,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        lease_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        match deliveries.get_mut(&delivery_id) {
+            Some(delivery) if delivery.next_attempt_at == next_attempt_at => {
+                delivery.next_attempt_at = lease_until;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn reschedule_delivery(&self, delivery: &WebhookDelivery) -> Result<(), RepositoryError> {
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        if let Some(stored) = deliveries.get_mut(&delivery.id) {
+            stored.attempts = delivery.attempts;
+            stored.next_attempt_at = delivery.next_attempt_at;
+            stored.last_error = delivery.last_error.clone();
+        }
+
+        Ok(())
+    }
+
+    async fn complete_delivery(&self, delivery_id: Uuid) -> Result<(), RepositoryError> {
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        deliveries.remove(&delivery_id);
+
+        Ok(())
+    }
+
+    async fn dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), RepositoryError> {
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        let mut dead_letters = self
+            .dead_letters
+            .write()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        deliveries.remove(&dead_letter.delivery.id);
+        dead_letters.push(dead_letter.clone());
+
+        Ok(())
+    }
+
+    async fn list_dead_letters(
+        &self,
+        tenant_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let dead_letters = self
+            .dead_letters
+            .read()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        let mut found: Vec<DeadLetter> = dead_letters
+            .iter()
+            .filter(|dead_letter| dead_letter.delivery.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| {
+            b.failed_at
+                .cmp(&a.failed_at)
+                .then(b.delivery.id.cmp(&a.delivery.id))
+        });
+        found.truncate(limit);
+
+        Ok(found)
+    }
+}
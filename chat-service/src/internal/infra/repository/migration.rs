@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::fmt;
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use sqlx::migrate::{Migrate, Migrator};
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// The migrations are compiled into the binary, so a deployment carries the schema it expects
+#[cfg(feature = "postgres")]
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+pub static SQL_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sql");
+
+// SchemaVersion compares the migrations applied to the database with the ones the binary carries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaVersion {
+    // current is the latest applied migration, None on an empty database
+    pub current: Option<i64>,
+    // latest is the latest migration of the binary, None when it has none, e.g. the memory driver
+    pub latest: Option<i64>,
+    pub pending: Vec<i64>,
+}
+
+impl SchemaVersion {
+    pub fn of(available: &[i64], applied: &[i64]) -> Self {
+        let applied_set: HashSet<i64> = applied.iter().copied().collect();
+
+        Self {
+            current: applied.iter().copied().max(),
+            latest: available.iter().copied().max(),
+            pending: available
+                .iter()
+                .copied()
+                .filter(|version| !applied_set.contains(version))
+                .collect(),
+        }
+    }
+
+    // is_behind tells whether migrations of the binary have not been applied yet
+    pub fn is_behind(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |version: Option<i64>| {
+            version.map_or_else(|| "none".to_string(), |version| version.to_string())
+        };
+        write!(
+            f,
+            "schema version {}, latest {}, {} pending",
+            version(self.current),
+            version(self.latest),
+            self.pending.len()
+        )
+    }
+}
+
+// schema_version reads the applied migrations, creating the table that tracks them if missing
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) async fn schema_version<C: Migrate>(
+    migrator: &Migrator,
+    conn: &mut C,
+) -> Result<SchemaVersion, RepositoryError> {
+    conn.ensure_migrations_table()
+        .await
+        .map_err(migrate_error)?;
+    if let Some(version) = conn.dirty_version().await.map_err(migrate_error)? {
+        return Err(RepositoryError::Database(format!(
+            "migration {} was left partially applied, fix the schema by hand",
+            version
+        )));
+    }
+
+    let applied: Vec<i64> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(migrate_error)?
+        .iter()
+        .map(|migration| migration.version)
+        .collect();
+    let available: Vec<i64> = migrator
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .map(|migration| migration.version)
+        .collect();
+
+    Ok(SchemaVersion::of(&available, &applied))
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) fn migrate_error(err: sqlx::migrate::MigrateError) -> RepositoryError {
+    RepositoryError::Database(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_version() {
+        let version = SchemaVersion::of(&[1, 2, 3], &[1, 2]);
+        assert_eq!(version.current, Some(2));
+        assert_eq!(version.latest, Some(3));
+        assert_eq!(version.pending, vec![3]);
+        assert!(version.is_behind());
+        assert_eq!(version.to_string(), "schema version 2, latest 3, 1 pending");
+
+        assert!(!SchemaVersion::of(&[1, 2], &[1, 2]).is_behind());
+        assert_eq!(
+            SchemaVersion::of(&[1], &[]).to_string(),
+            "schema version none, latest 1, 1 pending"
+        );
+        assert!(!SchemaVersion::default().is_behind());
+    }
+}
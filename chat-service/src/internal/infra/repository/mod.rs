@@ -0,0 +1,8 @@
+pub mod driver;
+pub mod factory;
+pub mod memory;
+pub mod migration;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(any(feature = "mysql", feature = "sqlite"))]
+pub mod sql;
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresApiKeyRepository {
+    pool: PgPool,
+}
+
+impl PostgresApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PostgresApiKeyRepository {
+    #[instrument(skip_all, fields(user_id = %api_key.user_id))]
+    async fn create_api_key(&self, api_key: &ApiKey) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO api_keys (id, tenant_id, user_id, prefix, key_hash, created_at, \
+             revoked_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(api_key.id)
+        .bind(api_key.tenant_id)
+        .bind(api_key.user_id)
+        .bind(&api_key.prefix)
+        .bind(&api_key.key_hash)
+        .bind(api_key.created_at)
+        .bind(api_key.revoked_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn find_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, prefix, key_hash, created_at, revoked_at \
+             FROM api_keys WHERE key_hash = $1",
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        Ok(Some(ApiKey {
+            id: row.try_get("id").map_err(db_error)?,
+            tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+            user_id: row.try_get("user_id").map_err(db_error)?,
+            prefix: row.try_get("prefix").map_err(db_error)?,
+            key_hash: row.try_get("key_hash").map_err(db_error)?,
+            created_at: row.try_get("created_at").map_err(db_error)?,
+            revoked_at: row.try_get("revoked_at").map_err(db_error)?,
+        }))
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn revoke_api_keys(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        keep: Uuid,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = $1 \
+             WHERE tenant_id = $2 AND user_id = $3 AND id <> $4 AND revoked_at IS NULL",
+        )
+        .bind(revoked_at)
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(keep)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() as usize)
+    }
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+const ASSISTANT_COLUMNS: &str =
+    "id, tenant_id, name, instructions, model, temperature, tools, created_at, updated_at";
+
+pub struct PostgresAssistantRepository {
+    pool: PgPool,
+}
+
+impl PostgresAssistantRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AssistantRepository for PostgresAssistantRepository {
+    #[instrument(skip_all, fields(assistant_id = %assistant.id))]
+    async fn create_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        sqlx::query(&format!(
+            "INSERT INTO assistants ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(assistant.id)
+        .bind(assistant.tenant_id)
+        .bind(&assistant.name)
+        .bind(&assistant.instructions)
+        .bind(&assistant.model)
+        .bind(assistant.temperature)
+        .bind(Json(&assistant.tools))
+        .bind(assistant.created_at)
+        .bind(assistant.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant_id))]
+    async fn find_assistant_by_id(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<Option<Assistant>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM assistants WHERE tenant_id = $1 AND id = $2",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(assistant_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| assistant_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_assistants(&self, tenant_id: Uuid) -> Result<Vec<Assistant>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM assistants WHERE tenant_id = $1 ORDER BY name",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(assistant_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant.id))]
+    async fn update_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE assistants SET name = $3, instructions = $4, model = $5, temperature = $6, \
+             tools = $7, updated_at = $8 WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(assistant.tenant_id)
+        .bind(assistant.id)
+        .bind(&assistant.name)
+        .bind(&assistant.instructions)
+        .bind(&assistant.model)
+        .bind(assistant.temperature)
+        .bind(Json(&assistant.tools))
+        .bind(assistant.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant_id))]
+    async fn delete_assistant(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM assistants WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(assistant_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn assistant_from_row(row: &PgRow) -> Result<Assistant, RepositoryError> {
+    let tools: Json<Vec<String>> = row.try_get("tools").map_err(db_error)?;
+
+    Ok(Assistant {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        name: row.try_get("name").map_err(db_error)?,
+        instructions: row.try_get("instructions").map_err(db_error)?,
+        model: row.try_get("model").map_err(db_error)?,
+        temperature: row.try_get("temperature").map_err(db_error)?,
+        tools: tools.0,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        updated_at: row.try_get("updated_at").map_err(db_error)?,
+    })
+}
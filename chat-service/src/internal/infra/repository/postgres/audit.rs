@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+
+use crate::internal::domain::entity::audit::{AuditDirection, AuditEntry};
+use crate::internal::domain::repository::audit::{AuditQuery, AuditRepository};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresAuditRepository {
+    pool: PgPool,
+}
+
+impl PostgresAuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PostgresAuditRepository {
+    #[instrument(skip_all, fields(request_id = %entry.request_id, direction = %entry.direction))]
+    async fn append(&self, entry: &AuditEntry) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO audit_log (id, request_id, tenant_id, user_id, chat_id, direction, \
+             provider, model, content, error, latency_ms, prompt_tokens, completion_tokens, \
+             created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+        )
+        .bind(entry.id)
+        .bind(entry.request_id)
+        .bind(entry.tenant_id)
+        .bind(entry.user_id)
+        .bind(entry.chat_id)
+        .bind(entry.direction.to_string())
+        .bind(&entry.provider)
+        .bind(&entry.model)
+        .bind(&entry.content)
+        .bind(&entry.error)
+        .bind(entry.latency_ms.map(|latency| latency as i64))
+        .bind(entry.prompt_tokens as i32)
+        .bind(entry.completion_tokens as i32)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn list_entries(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, request_id, tenant_id, user_id, chat_id, direction, provider, model, \
+             content, error, latency_ms, prompt_tokens, completion_tokens, created_at \
+             FROM audit_log \
+             WHERE ($1::UUID IS NULL OR tenant_id = $1) \
+             AND ($2::UUID IS NULL OR user_id = $2) \
+             AND ($3::UUID IS NULL OR chat_id = $3) \
+             AND ($4::UUID IS NULL OR request_id = $4) \
+             AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5) \
+             AND ($6::TIMESTAMPTZ IS NULL OR created_at < $6) \
+             AND ($7::TIMESTAMPTZ IS NULL OR (created_at, id) > ($7, $8)) \
+             ORDER BY created_at, id LIMIT $9",
+        )
+        .bind(query.tenant_id)
+        .bind(query.user_id)
+        .bind(query.chat_id)
+        .bind(query.request_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.after.map(|after| after.created_at))
+        .bind(query.after.map(|after| after.id))
+        .bind(query.limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(entry_from_row).collect()
+    }
+}
+
+fn entry_from_row(row: &PgRow) -> Result<AuditEntry, RepositoryError> {
+    let direction: String = row.try_get("direction").map_err(db_error)?;
+    let direction: AuditDirection = direction.parse().map_err(RepositoryError::Database)?;
+    let latency_ms: Option<i64> = row.try_get("latency_ms").map_err(db_error)?;
+    let prompt_tokens: i32 = row.try_get("prompt_tokens").map_err(db_error)?;
+    let completion_tokens: i32 = row.try_get("completion_tokens").map_err(db_error)?;
+
+    Ok(AuditEntry {
+        id: row.try_get("id").map_err(db_error)?,
+        request_id: row.try_get("request_id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        chat_id: row.try_get("chat_id").map_err(db_error)?,
+        direction,
+        provider: row.try_get("provider").map_err(db_error)?,
+        model: row.try_get("model").map_err(db_error)?,
+        content: row.try_get("content").map_err(db_error)?,
+        error: row.try_get("error").map_err(db_error)?,
+        latency_ms: latency_ms.map(|latency| latency as u64),
+        prompt_tokens: prompt_tokens as u32,
+        completion_tokens: completion_tokens as u32,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::{Batch, BatchItem};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresBatchRepository {
+    pool: PgPool,
+}
+
+impl PostgresBatchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BatchRepository for PostgresBatchRepository {
+    #[instrument(skip_all, fields(batch_id = %batch.id))]
+    async fn create_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO batches \
+             (id, tenant_id, user_id, status, items, created_at, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(batch.id)
+        .bind(batch.tenant_id)
+        .bind(batch.user_id)
+        .bind(batch.status.to_string())
+        .bind(Json(&batch.items))
+        .bind(batch.created_at)
+        .bind(batch.completed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(batch_id = %batch_id))]
+    async fn find_batch_by_id(
+        &self,
+        tenant_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<Option<Batch>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, status, items, created_at, completed_at \
+             FROM batches WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(batch_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| batch_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(batch_id = %batch.id))]
+    async fn complete_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE batches SET status = $1, items = $2, completed_at = $3 \
+             WHERE tenant_id = $4 AND id = $5",
+        )
+        .bind(batch.status.to_string())
+        .bind(Json(&batch.items))
+        .bind(batch.completed_at)
+        .bind(batch.tenant_id)
+        .bind(batch.id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn batch_from_row(row: &PgRow) -> Result<Batch, RepositoryError> {
+    let status: String = row.try_get("status").map_err(db_error)?;
+    let items: Json<Vec<BatchItem>> = row.try_get("items").map_err(db_error)?;
+
+    Ok(Batch {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        status: status
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        items: items.0,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        completed_at: row.try_get("completed_at").map_err(db_error)?,
+    })
+}
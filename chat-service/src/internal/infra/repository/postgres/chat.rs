@@ -0,0 +1,754 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::{Postgres, Row, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::deadline;
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::{
+    Chat, ChatConfig, ChatStatus, ChatSummary, HistoryArchive, TrimmingPolicy,
+};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::{
+    ChatCursor, ChatRepository, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+};
+use crate::internal::domain::text_search::{
+    render_highlight, HIGHLIGHT_WORDS, MATCH_END, MATCH_START,
+};
+
+const SELECT_CHAT: &str =
+    "SELECT id, tenant_id, user_id, system_message_id, status, token_usage, model, \
+     model_max_tokens, temperature, top_p, n, stop, max_tokens, presence_penalty, \
+     frequency_penalty, trimming_policy, tools, response_format, title, tags, metadata, version, \
+     history_archive, assistant_id, response_language FROM chats";
+
+const SELECT_SUMMARY: &str =
+    "SELECT chat_id, tenant_id, user_id, status, model, title, tags, metadata, token_usage, \
+     message_count, last_message_preview, created_at, updated_at FROM chat_summaries";
+
+pub struct PostgresChatRepository {
+    pool: PgPool,
+    model: Model,
+}
+
+impl PostgresChatRepository {
+    // new expects every message stored by this service to have been produced with the given model
+    pub fn new(pool: PgPool, model: Model) -> Self {
+        Self { pool, model }
+    }
+
+    async fn load_chat(&self, row: PgRow) -> Result<Chat, RepositoryError> {
+        let id: Uuid = row.try_get("id").map_err(db_error)?;
+        let system_message_id: Uuid = row.try_get("system_message_id").map_err(db_error)?;
+
+        let rows = sqlx::query(
+            "SELECT id, role, content, tokens, erased, created_at, tool_calls, tool_call_id, \
+             revision_of, attachments, candidates, interrupted, request_id, speaker, language \
+             FROM messages WHERE chat_id = $1 ORDER BY position",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut initial_system_message = None;
+        let mut messages = vec![];
+        let mut erased_messages = vec![];
+
+        for row in rows {
+            let erased: bool = row.try_get("erased").map_err(db_error)?;
+            let message = self.message_from_row(&row)?;
+
+            if message.id == system_message_id {
+                initial_system_message = Some(message);
+            } else if erased {
+                erased_messages.push(message);
+            } else {
+                messages.push(message);
+            }
+        }
+
+        let initial_system_message = initial_system_message.ok_or_else(|| {
+            RepositoryError::Database(format!("chat {} has no system message", id))
+        })?;
+
+        let model_max_tokens: i32 = row.try_get("model_max_tokens").map_err(db_error)?;
+        let n: i32 = row.try_get("n").map_err(db_error)?;
+        let max_tokens: i64 = row.try_get("max_tokens").map_err(db_error)?;
+        let token_usage: i64 = row.try_get("token_usage").map_err(db_error)?;
+        let version: i64 = row.try_get("version").map_err(db_error)?;
+        let status: String = row.try_get("status").map_err(db_error)?;
+        let status: ChatStatus = status
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+        let trimming_policy: String = row.try_get("trimming_policy").map_err(db_error)?;
+        let trimming_policy: TrimmingPolicy = trimming_policy
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+        let tools: Json<Vec<ToolDefinition>> = row.try_get("tools").map_err(db_error)?;
+        let response_format: Json<ResponseFormat> =
+            row.try_get("response_format").map_err(db_error)?;
+        let tags: Json<Vec<String>> = row.try_get("tags").map_err(db_error)?;
+        let metadata: Json<BTreeMap<String, String>> = row.try_get("metadata").map_err(db_error)?;
+        let history_archive: Option<Json<HistoryArchive>> =
+            row.try_get("history_archive").map_err(db_error)?;
+
+        let config = ChatConfig {
+            model: Model::new(
+                row.try_get("model").map_err(db_error)?,
+                model_max_tokens as u32,
+            ),
+            temperature: row.try_get("temperature").map_err(db_error)?,
+            top_p: row.try_get("top_p").map_err(db_error)?,
+            n: n as u32,
+            stop: row.try_get("stop").map_err(db_error)?,
+            max_tokens: max_tokens as usize,
+            presence_penalty: row.try_get("presence_penalty").map_err(db_error)?,
+            frequency_penalty: row.try_get("frequency_penalty").map_err(db_error)?,
+            trimming_policy,
+            tools: tools.0,
+            response_format: response_format.0,
+            response_language: row.try_get("response_language").map_err(db_error)?,
+        };
+
+        Ok(Chat::new(
+            id,
+            row.try_get("user_id").map_err(db_error)?,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            token_usage as usize,
+            config,
+        )
+        .with_tenant(row.try_get("tenant_id").map_err(db_error)?)
+        .with_title(row.try_get("title").map_err(db_error)?)
+        .with_tags(tags.0)
+        .with_metadata(metadata.0)
+        .with_history_archive(history_archive.map(|archive| archive.0))
+        .with_assistant(row.try_get("assistant_id").map_err(db_error)?)
+        .with_version(version as u64))
+    }
+
+    fn message_from_row(&self, row: &PgRow) -> Result<Message, RepositoryError> {
+        let tokens: i64 = row.try_get("tokens").map_err(db_error)?;
+        let role: String = row.try_get("role").map_err(db_error)?;
+        let role: Role = role
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+        let tool_calls: Json<Vec<ToolCall>> = row.try_get("tool_calls").map_err(db_error)?;
+        let attachments: Json<Vec<Attachment>> = row.try_get("attachments").map_err(db_error)?;
+        let candidates: Json<Vec<String>> = row.try_get("candidates").map_err(db_error)?;
+
+        Ok(Message {
+            id: row.try_get("id").map_err(db_error)?,
+            role,
+            content: row.try_get("content").map_err(db_error)?,
+            tokens: tokens as usize,
+            model: self.model.clone(),
+            created_at: row.try_get("created_at").map_err(db_error)?,
+            tool_calls: tool_calls.0,
+            tool_call_id: row.try_get("tool_call_id").map_err(db_error)?,
+            revision_of: row.try_get("revision_of").map_err(db_error)?,
+            attachments: attachments.0,
+            candidates: candidates.0,
+            interrupted: row.try_get("interrupted").map_err(db_error)?,
+            request_id: row.try_get("request_id").map_err(db_error)?,
+            speaker: row.try_get("speaker").map_err(db_error)?,
+            language: row.try_get("language").map_err(db_error)?,
+            redaction: None,
+        })
+    }
+}
+
+#[async_trait]
+impl ChatRepository for PostgresChatRepository {
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+        insert_chat(&mut tx, chat).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn find_chat_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Chat>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = $1 AND id = $2", SELECT_CHAT))
+            .bind(tenant_id)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_chat(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+        update_chat(&mut tx, chat).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    // update_chat_title leaves updated_at alone, titling is not activity of the user
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn update_chat_title(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        title: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+        sqlx::query("UPDATE chats SET title = $3 WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .bind(title)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query("UPDATE chat_summaries SET title = $3 WHERE tenant_id = $1 AND chat_id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .bind(title)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_chats_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND user_id = $2 AND status <> 'deleted' \
+             ORDER BY updated_at DESC",
+            SELECT_CHAT
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut chats = Vec::with_capacity(rows.len());
+        for row in rows {
+            chats.push(self.load_chat(row).await?);
+        }
+
+        Ok(chats)
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_chat_summaries(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        tag: Option<&str>,
+        after: Option<ChatCursor>,
+        limit: usize,
+    ) -> Result<Vec<ChatSummary>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND user_id = $2 AND status <> 'deleted' \
+             AND ($3::TIMESTAMPTZ IS NULL OR (updated_at, chat_id) < ($3, $4)) \
+             AND ($6::TEXT IS NULL OR tags ? $6) \
+             ORDER BY updated_at DESC, chat_id DESC LIMIT $5",
+            SELECT_SUMMARY
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(after.map(|after| after.last_activity_at))
+        .bind(after.map(|after| after.id))
+        .bind(limit as i64)
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(summary_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn find_chat_summary(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ChatSummary>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND chat_id = $2",
+            SELECT_SUMMARY
+        ))
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.as_ref().map(summary_from_row).transpose()
+    }
+
+    // list_messages skips the system message by its -1 position and pages by the position
+    // of the cursor message, which stays valid while later turns are appended
+    #[instrument(skip_all, fields(chat_id = %chat_id))]
+    async fn list_messages(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        let roles: Vec<String> = query.roles.iter().map(|role| role.to_string()).collect();
+        let rows = sqlx::query(
+            "SELECT id, role, content, tokens, erased, created_at, tool_calls, tool_call_id, \
+             revision_of, attachments, candidates, interrupted, request_id, speaker, language \
+             FROM messages WHERE chat_id = $1 AND NOT erased AND position >= 0 \
+             AND (cardinality($2::TEXT[]) = 0 OR role = ANY($2)) \
+             AND ($3::TIMESTAMPTZ IS NULL OR created_at >= $3) \
+             AND ($4::TIMESTAMPTZ IS NULL OR created_at < $4) \
+             AND ($5::UUID IS NULL OR position > (SELECT position FROM messages \
+             WHERE chat_id = $1 AND id = $5 AND NOT erased)) \
+             AND EXISTS (SELECT 1 FROM chats WHERE id = $1 AND tenant_id = $7) \
+             ORDER BY position LIMIT $6",
+        )
+        .bind(chat_id)
+        .bind(&roles)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(query.after)
+        .bind(query.limit as i64)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(|row| self.message_from_row(row)).collect()
+    }
+
+    // search_transcripts matches the query against the english search vector of the messages,
+    // so words are stemmed and the web search syntax of quotes, or and - is understood
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn search_transcripts(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+        // the matched words are marked with control characters, taken out of the content first,
+        // so the headline can be escaped
+        let options = format!(
+            "StartSel={}, StopSel={}, MaxWords={}, MinWords=10",
+            MATCH_START, MATCH_END, HIGHLIGHT_WORDS
+        );
+        let rows = sqlx::query(
+            "SELECT m.id, m.chat_id, m.role, m.created_at, c.title, \
+             ts_headline('english', translate(m.content, E'\\x02\\x03', ''), q, $5) AS highlight, \
+             ts_rank(m.search_vector, q) AS rank \
+             FROM messages m JOIN chats c ON c.id = m.chat_id, \
+             websearch_to_tsquery('english', $3) q \
+             WHERE c.tenant_id = $1 AND c.user_id = $2 AND c.status <> 'deleted' \
+             AND NOT m.erased AND m.role IN ('user', 'assistant') AND m.search_vector @@ q \
+             ORDER BY rank DESC, m.created_at DESC LIMIT $4",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(query)
+        .bind(limit as i64)
+        .bind(options)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let role: String = row.try_get("role").map_err(db_error)?;
+                let highlight: String = row.try_get("highlight").map_err(db_error)?;
+                Ok(TranscriptMatch {
+                    chat_id: row.try_get("chat_id").map_err(db_error)?,
+                    chat_title: row.try_get("title").map_err(db_error)?,
+                    message_id: row.try_get("id").map_err(db_error)?,
+                    role: role
+                        .parse()
+                        .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+                    highlight: render_highlight(&highlight),
+                    rank: row.try_get("rank").map_err(db_error)?,
+                    created_at: row.try_get("created_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    async fn list_inactive_chats(
+        &self,
+        inactive_before: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE status <> 'deleted' AND history_archive IS NULL AND updated_at < $1 \
+             ORDER BY updated_at, id LIMIT $2",
+            SELECT_CHAT
+        ))
+        .bind(inactive_before)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut chats = Vec::with_capacity(rows.len());
+        for row in rows {
+            chats.push(self.load_chat(row).await?);
+        }
+
+        Ok(chats)
+    }
+
+    // archive_chat_history keeps the system message, it stays with the chat
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn archive_chat_history(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+        let updated = sqlx::query(
+            "UPDATE chats SET status = $3, history_archive = $4, version = version + 1 \
+             WHERE id = $1 AND tenant_id = $2 AND version = $5",
+        )
+        .bind(chat.id)
+        .bind(chat.tenant_id)
+        .bind(chat.status.to_string())
+        .bind(chat.history_archive.as_ref().map(Json))
+        .bind(chat.version as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if updated.rows_affected() == 0 {
+            return match is_stored(&mut tx, chat).await? {
+                true => Err(RepositoryError::ConcurrentModification(chat.id)),
+                false => Ok(()),
+            };
+        }
+
+        sqlx::query("DELETE FROM messages WHERE chat_id = $1 AND position >= 0")
+            .bind(chat.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        write_summary(&mut tx, chat).await?;
+        insert_events(&mut tx, chat).await?;
+        tx.commit().await.map_err(db_error)?;
+        chat.version += 1;
+
+        Ok(())
+    }
+
+    // purge_deleted_chats relies on the foreign keys to remove the messages and usage of the chats
+    #[instrument(skip_all)]
+    async fn purge_deleted_chats(
+        &self,
+        deleted_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PurgedChat>, RepositoryError> {
+        let rows = sqlx::query(
+            "DELETE FROM chats WHERE status = 'deleted' AND deleted_at < $1 \
+             RETURNING id, history_archive",
+        )
+        .bind(deleted_before)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let history_archive: Option<Json<HistoryArchive>> =
+                    row.try_get("history_archive").map_err(db_error)?;
+
+                Ok(PurgedChat {
+                    id: row.try_get("id").map_err(db_error)?,
+                    history_key: history_archive.map(|archive| archive.0.key),
+                })
+            })
+            .collect()
+    }
+}
+
+fn summary_from_row(row: &PgRow) -> Result<ChatSummary, RepositoryError> {
+    let status: String = row.try_get("status").map_err(db_error)?;
+    let status: ChatStatus = status
+        .parse()
+        .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+    let token_usage: i64 = row.try_get("token_usage").map_err(db_error)?;
+    let message_count: i64 = row.try_get("message_count").map_err(db_error)?;
+    let tags: Json<Vec<String>> = row.try_get("tags").map_err(db_error)?;
+    let metadata: Json<BTreeMap<String, String>> = row.try_get("metadata").map_err(db_error)?;
+
+    Ok(ChatSummary {
+        id: row.try_get("chat_id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        status,
+        model: row.try_get("model").map_err(db_error)?,
+        title: row.try_get("title").map_err(db_error)?,
+        tags: tags.0,
+        metadata: metadata.0,
+        token_usage: token_usage as usize,
+        message_count: message_count as usize,
+        last_message_preview: row.try_get("last_message_preview").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        last_activity_at: row.try_get("updated_at").map_err(db_error)?,
+    })
+}
+
+// insert_chat stores a new chat with its system message in the transaction
+pub(super) async fn insert_chat(
+    tx: &mut Transaction<'_, Postgres>,
+    chat: &Chat,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "INSERT INTO chats (id, user_id, system_message_id, status, token_usage, model, \
+         model_max_tokens, temperature, top_p, n, stop, max_tokens, presence_penalty, \
+         frequency_penalty, trimming_policy, tools, response_format, title, tenant_id, version, \
+         tags, metadata, history_archive, assistant_id, response_language) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+         $18, $19, $20, $21, $22, $23, $24, $25)",
+    )
+    .bind(chat.id)
+    .bind(chat.user_id)
+    .bind(chat.initial_system_message().id)
+    .bind(chat.status.to_string())
+    .bind(chat.token_usage as i64)
+    .bind(&chat.config.model.name)
+    .bind(chat.config.model.max_tokens as i32)
+    .bind(chat.config.temperature)
+    .bind(chat.config.top_p)
+    .bind(chat.config.n as i32)
+    .bind(&chat.config.stop)
+    .bind(chat.config.max_tokens as i64)
+    .bind(chat.config.presence_penalty)
+    .bind(chat.config.frequency_penalty)
+    .bind(chat.config.trimming_policy.to_string())
+    .bind(Json(&chat.config.tools))
+    .bind(Json(&chat.config.response_format))
+    .bind(&chat.title)
+    .bind(chat.tenant_id)
+    .bind(chat.version as i64)
+    .bind(Json(&chat.tags))
+    .bind(Json(&chat.metadata))
+    .bind(chat.history_archive.as_ref().map(Json))
+    .bind(chat.assistant_id)
+    .bind(&chat.config.response_language)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    insert_message(tx, chat.id, chat.initial_system_message(), false, -1).await?;
+    write_summary(tx, chat).await?;
+    insert_events(tx, chat).await?;
+
+    Ok(())
+}
+
+// update_chat leaves the messages alone when the chat is not stored under its tenant; the
+// system message is written again as it may have been replaced
+pub(super) async fn update_chat(
+    tx: &mut Transaction<'_, Postgres>,
+    chat: &mut Chat,
+) -> Result<(), RepositoryError> {
+    let updated = sqlx::query(
+        "UPDATE chats SET status = $3, token_usage = $4, tools = $5, updated_at = NOW(), \
+         deleted_at = CASE WHEN $3 = 'deleted' THEN COALESCE(deleted_at, NOW()) END, \
+         title = COALESCE($6, title), model = $7, model_max_tokens = $8, temperature = $9, \
+         max_tokens = $10, tags = $12, metadata = $13, history_archive = $14, \
+         response_language = $15, version = version + 1 \
+         WHERE id = $1 AND tenant_id = $2 AND version = $11",
+    )
+    .bind(chat.id)
+    .bind(chat.tenant_id)
+    .bind(chat.status.to_string())
+    .bind(chat.token_usage as i64)
+    .bind(Json(&chat.config.tools))
+    .bind(&chat.title)
+    .bind(&chat.config.model.name)
+    .bind(chat.config.model.max_tokens as i32)
+    .bind(chat.config.temperature)
+    .bind(chat.config.max_tokens as i64)
+    .bind(chat.version as i64)
+    .bind(Json(&chat.tags))
+    .bind(Json(&chat.metadata))
+    .bind(chat.history_archive.as_ref().map(Json))
+    .bind(&chat.config.response_language)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if updated.rows_affected() == 0 {
+        return match is_stored(tx, chat).await? {
+            true => Err(RepositoryError::ConcurrentModification(chat.id)),
+            false => Ok(()),
+        };
+    }
+
+    sqlx::query("DELETE FROM messages WHERE chat_id = $1")
+        .bind(chat.id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    insert_message(tx, chat.id, chat.initial_system_message(), false, -1).await?;
+    for (position, message) in chat.messages.iter().enumerate() {
+        insert_message(tx, chat.id, message, false, position as i32).await?;
+    }
+
+    let offset = chat.messages.len();
+    for (position, message) in chat.erased_messages.iter().enumerate() {
+        insert_message(tx, chat.id, message, true, (offset + position) as i32).await?;
+    }
+
+    write_summary(tx, chat).await?;
+    insert_events(tx, chat).await?;
+    chat.version += 1;
+
+    Ok(())
+}
+
+// is_stored tells whether the chat is stored under its tenant, which is the case when a
+// versioned update missed it because another save bumped its version first
+async fn is_stored(
+    tx: &mut Transaction<'_, Postgres>,
+    chat: &Chat,
+) -> Result<bool, RepositoryError> {
+    let stored = sqlx::query("SELECT id FROM chats WHERE id = $1 AND tenant_id = $2")
+        .bind(chat.id)
+        .bind(chat.tenant_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    Ok(stored.is_some())
+}
+
+async fn insert_message(
+    tx: &mut Transaction<'_, Postgres>,
+    chat_id: Uuid,
+    message: &Message,
+    erased: bool,
+    position: i32,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "INSERT INTO messages (id, chat_id, role, content, tokens, model, erased, position, \
+         created_at, tool_calls, tool_call_id, revision_of, attachments, candidates, \
+         interrupted, request_id, speaker, language) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+         $18)",
+    )
+    .bind(message.id)
+    .bind(chat_id)
+    .bind(message.role.to_string())
+    .bind(&message.content)
+    .bind(message.tokens as i64)
+    .bind(&message.model.name)
+    .bind(erased)
+    .bind(position)
+    .bind(message.created_at)
+    .bind(Json(&message.tool_calls))
+    .bind(&message.tool_call_id)
+    .bind(message.revision_of)
+    .bind(Json(&message.attachments))
+    .bind(Json(&message.candidates))
+    .bind(message.interrupted)
+    .bind(&message.request_id)
+    .bind(&message.speaker)
+    .bind(&message.language)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+// write_summary projects the chat to chat_summaries next to its events; the rest is copied from
+// the chats row just written, so the summary has its timestamps and the title a save kept
+async fn write_summary(
+    tx: &mut Transaction<'_, Postgres>,
+    chat: &Chat,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "INSERT INTO chat_summaries (chat_id, tenant_id, user_id, status, model, title, tags, \
+         metadata, token_usage, message_count, last_message_preview, created_at, updated_at) \
+         SELECT id, tenant_id, user_id, status, model, title, tags, metadata, token_usage, $2, \
+         $3, created_at, updated_at FROM chats WHERE id = $1 \
+         ON CONFLICT (chat_id) DO UPDATE SET status = EXCLUDED.status, model = EXCLUDED.model, \
+         title = EXCLUDED.title, tags = EXCLUDED.tags, metadata = EXCLUDED.metadata, \
+         token_usage = EXCLUDED.token_usage, message_count = EXCLUDED.message_count, \
+         last_message_preview = EXCLUDED.last_message_preview, \
+         updated_at = EXCLUDED.updated_at",
+    )
+    .bind(chat.id)
+    .bind(chat.count_messages() as i64)
+    .bind(chat.last_message_preview())
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+// insert_events writes the events recorded by the chat to the outbox in the transaction that
+// saves it, events already there from an earlier save of the same chat are skipped
+async fn insert_events(
+    tx: &mut Transaction<'_, Postgres>,
+    chat: &Chat,
+) -> Result<(), RepositoryError> {
+    for event in chat.outbox_events() {
+        sqlx::query(
+            "INSERT INTO outbox_events (id, tenant_id, user_id, chat_id, event_type, payload, \
+             occurred_at) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(event.id)
+        .bind(event.tenant_id)
+        .bind(event.user_id)
+        .bind(event.chat_id)
+        .bind(event.event.name())
+        .bind(Json(&event.event))
+        .bind(event.occurred_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn db_error(err: sqlx::Error) -> RepositoryError {
+    RepositoryError::Database(err.to_string())
+}
+
+// begin starts a transaction whose statements time out at the deadline of the request, so the
+// server stops working on writes nobody waits for anymore
+pub(super) async fn begin(
+    pool: &PgPool,
+) -> Result<Transaction<'static, Postgres>, RepositoryError> {
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    if let Some(remaining) = deadline::remaining() {
+        // a zero statement_timeout disables it
+        let millis = remaining.as_millis().max(1);
+        sqlx::query(&format!("SET LOCAL statement_timeout = {}", millis))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+
+    Ok(tx)
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::document::{Document, DocumentFormat};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::document::DocumentRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresDocumentRepository {
+    pool: PgPool,
+}
+
+impl PostgresDocumentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DocumentRepository for PostgresDocumentRepository {
+    #[instrument(skip_all, fields(document_id = %document.id))]
+    async fn create_document(&self, document: &Document) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO documents (id, tenant_id, user_id, name, format, size_bytes, \
+             chunk_count, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(document.id)
+        .bind(document.tenant_id)
+        .bind(document.user_id)
+        .bind(&document.name)
+        .bind(document.format.to_string())
+        .bind(document.size_bytes as i64)
+        .bind(document.chunk_count as i32)
+        .bind(document.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(document_id = %document_id))]
+    async fn find_document_by_id(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<Option<Document>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, name, format, size_bytes, chunk_count, created_at \
+             FROM documents WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(document_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| document_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_documents_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Document>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, user_id, name, format, size_bytes, chunk_count, created_at \
+             FROM documents WHERE tenant_id = $1 AND user_id = $2 \
+             ORDER BY created_at DESC, id DESC",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(document_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(document_id = %document_id))]
+    async fn delete_document(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM documents WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(document_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn document_from_row(row: &PgRow) -> Result<Document, RepositoryError> {
+    let format: String = row.try_get("format").map_err(db_error)?;
+    let size_bytes: i64 = row.try_get("size_bytes").map_err(db_error)?;
+    let chunk_count: i32 = row.try_get("chunk_count").map_err(db_error)?;
+
+    Ok(Document {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        name: row.try_get("name").map_err(db_error)?,
+        format: format
+            .parse::<DocumentFormat>()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?,
+        size_bytes: size_bytes as usize,
+        chunk_count: chunk_count as usize,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
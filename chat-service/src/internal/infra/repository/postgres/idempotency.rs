@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::idempotency::IdempotencyRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::idempotency::IdempotencyRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresIdempotencyRepository {
+    pool: PgPool,
+}
+
+impl PostgresIdempotencyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for PostgresIdempotencyRepository {
+    #[instrument(skip_all, fields(user_id = %record.user_id))]
+    async fn reserve(
+        &self,
+        record: &IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys \
+             WHERE tenant_id = $1 AND user_id = $2 AND expires_at <= $3",
+        )
+        .bind(record.tenant_id)
+        .bind(record.user_id)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys \
+             (tenant_id, user_id, key, fingerprint, created_at, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (tenant_id, user_id, key) DO NOTHING",
+        )
+        .bind(record.tenant_id)
+        .bind(record.user_id)
+        .bind(&record.key)
+        .bind(&record.fingerprint)
+        .bind(record.created_at)
+        .bind(record.expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        if inserted.rows_affected() > 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            "SELECT tenant_id, user_id, key, fingerprint, response, created_at, expires_at \
+             FROM idempotency_keys WHERE tenant_id = $1 AND user_id = $2 AND key = $3",
+        )
+        .bind(record.tenant_id)
+        .bind(record.user_id)
+        .bind(&record.key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| record_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn complete(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET response = $4 \
+             WHERE tenant_id = $1 AND user_id = $2 AND key = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(key)
+        .bind(response)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn release(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE tenant_id = $1 AND user_id = $2 AND key = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn record_from_row(row: &PgRow) -> Result<IdempotencyRecord, RepositoryError> {
+    Ok(IdempotencyRecord {
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        key: row.try_get("key").map_err(db_error)?,
+        fingerprint: row.try_get("fingerprint").map_err(db_error)?,
+        response: row.try_get("response").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        expires_at: row.try_get("expires_at").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+const SELECT_JOBS: &str = "SELECT id, tenant_id, user_id, chat_id, user_message, attachments, \
+                           overrides, status, content, error, created_at, completed_at, \
+                           claimed_until FROM completion_jobs";
+
+// CLAIMABLE selects the queued jobs and the running ones whose claim was over before $1
+const CLAIMABLE: &str = "(status = 'queued' OR (status = 'running' \
+                         AND (claimed_until IS NULL OR claimed_until < $1)))";
+
+pub struct PostgresJobRepository {
+    pool: PgPool,
+}
+
+impl PostgresJobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JobRepository for PostgresJobRepository {
+    #[instrument(skip_all, fields(job_id = %job.id))]
+    async fn create_job(&self, job: &Job) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO completion_jobs \
+             (id, tenant_id, user_id, chat_id, user_message, attachments, overrides, status, \
+              content, error, created_at, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(job.id)
+        .bind(job.tenant_id)
+        .bind(job.user_id)
+        .bind(job.chat_id)
+        .bind(&job.user_message)
+        .bind(Json(&job.attachments))
+        .bind(Json(&job.overrides))
+        .bind(job.status.to_string())
+        .bind(&job.content)
+        .bind(&job.error)
+        .bind(job.created_at)
+        .bind(job.completed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn find_job_by_id(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<Job>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = $1 AND id = $2", SELECT_JOBS))
+            .bind(tenant_id)
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| job_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all)]
+    async fn list_claimable(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Job>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE {} ORDER BY created_at, id LIMIT $2",
+            SELECT_JOBS, CLAIMABLE
+        ))
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(job_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn claim_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(&format!(
+            "UPDATE completion_jobs SET status = 'running', claimed_until = $2 \
+             WHERE tenant_id = $3 AND id = $4 AND {}",
+            CLAIMABLE
+        ))
+        .bind(now)
+        .bind(claimed_until)
+        .bind(tenant_id)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(job_id = %job.id))]
+    async fn complete_job(
+        &self,
+        job: &Job,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE completion_jobs \
+             SET status = $1, chat_id = $2, content = $3, error = $4, completed_at = $5, \
+             claimed_until = NULL WHERE tenant_id = $6 AND id = $7 \
+             AND status = 'running' AND claimed_until = $8",
+        )
+        .bind(job.status.to_string())
+        .bind(job.chat_id)
+        .bind(&job.content)
+        .bind(&job.error)
+        .bind(job.completed_at)
+        .bind(job.tenant_id)
+        .bind(job.id)
+        .bind(claimed_until)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+fn job_from_row(row: &PgRow) -> Result<Job, RepositoryError> {
+    let status: String = row.try_get("status").map_err(db_error)?;
+    let attachments: Json<Vec<Attachment>> = row.try_get("attachments").map_err(db_error)?;
+    let overrides: Json<serde_json::Value> = row.try_get("overrides").map_err(db_error)?;
+
+    Ok(Job {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        chat_id: row.try_get("chat_id").map_err(db_error)?,
+        user_message: row.try_get("user_message").map_err(db_error)?,
+        attachments: attachments.0,
+        overrides: overrides.0,
+        status: status
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        content: row.try_get("content").map_err(db_error)?,
+        error: row.try_get("error").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        completed_at: row.try_get("completed_at").map_err(db_error)?,
+        claimed_until: row.try_get("claimed_until").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::moderation::ModerationFlag;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::moderation::ModerationRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresModerationRepository {
+    pool: PgPool,
+}
+
+impl PostgresModerationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ModerationRepository for PostgresModerationRepository {
+    #[instrument(skip_all, fields(user_id = %flag.user_id))]
+    async fn record_flag(&self, flag: &ModerationFlag) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO moderation_flags (id, user_id, chat_id, content, categories, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(flag.id)
+        .bind(flag.user_id)
+        .bind(flag.chat_id)
+        .bind(&flag.content)
+        .bind(&flag.categories)
+        .bind(flag.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_flags_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ModerationFlag>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, chat_id, content, categories, created_at FROM moderation_flags \
+             WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ModerationFlag {
+                    id: row.try_get("id").map_err(db_error)?,
+                    user_id: row.try_get("user_id").map_err(db_error)?,
+                    chat_id: row.try_get("chat_id").map_err(db_error)?,
+                    content: row.try_get("content").map_err(db_error)?,
+                    categories: row.try_get("categories").map_err(db_error)?,
+                    created_at: row.try_get("created_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::outbox::OutboxRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresOutboxRepository {
+    pool: PgPool,
+}
+
+impl PostgresOutboxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for PostgresOutboxRepository {
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn unpublished_events(&self, limit: usize) -> Result<Vec<OutboxEvent>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, payload, occurred_at FROM outbox_events \
+             WHERE published_at IS NULL ORDER BY occurred_at, id LIMIT $1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(event_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(events = ids.len()))]
+    async fn mark_published(
+        &self,
+        ids: &[Uuid],
+        published_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE outbox_events SET published_at = $2 \
+             WHERE id = ANY($1) AND published_at IS NULL",
+        )
+        .bind(ids)
+        .bind(published_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn event_from_row(row: &PgRow) -> Result<OutboxEvent, RepositoryError> {
+    let event: Json<ChatEvent> = row.try_get("payload").map_err(db_error)?;
+
+    Ok(OutboxEvent {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        chat_id: row.try_get("chat_id").map_err(db_error)?,
+        event: event.0,
+        occurred_at: row.try_get("occurred_at").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresPromptTemplateRepository {
+    pool: PgPool,
+}
+
+impl PostgresPromptTemplateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PromptTemplateRepository for PostgresPromptTemplateRepository {
+    #[instrument(skip_all, fields(template = %template.name))]
+    async fn create_template(&self, template: &PromptTemplate) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO prompt_templates (id, name, content, created_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(template.id)
+        .bind(&template.name)
+        .bind(&template.content)
+        .bind(template.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(template = %name))]
+    async fn find_template_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<PromptTemplate>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, name, content, created_at FROM prompt_templates WHERE name = $1",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| template_from_row(&row)).transpose()
+    }
+}
+
+fn template_from_row(row: &PgRow) -> Result<PromptTemplate, RepositoryError> {
+    Ok(PromptTemplate {
+        id: row.try_get("id").map_err(db_error)?,
+        name: row.try_get("name").map_err(db_error)?,
+        content: row.try_get("content").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
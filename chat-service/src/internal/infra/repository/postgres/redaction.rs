@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::redaction::RedactionRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresRedactionRepository {
+    pool: PgPool,
+}
+
+impl PostgresRedactionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RedactionRepository for PostgresRedactionRepository {
+    #[instrument(skip_all, fields(message_id = %record.message_id))]
+    async fn record_redaction(&self, record: &RedactionRecord) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO redactions \
+             (id, tenant_id, user_id, chat_id, message_id, findings, ciphertext, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(record.id)
+        .bind(record.tenant_id)
+        .bind(record.user_id)
+        .bind(record.chat_id)
+        .bind(record.message_id)
+        .bind(&record.findings)
+        .bind(&record.ciphertext)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(message_id = %message_id))]
+    async fn find_redaction(
+        &self,
+        tenant_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<Option<RedactionRecord>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, message_id, findings, ciphertext, created_at \
+             FROM redactions WHERE tenant_id = $1 AND message_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| {
+            Ok(RedactionRecord {
+                id: row.try_get("id").map_err(db_error)?,
+                tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+                user_id: row.try_get("user_id").map_err(db_error)?,
+                chat_id: row.try_get("chat_id").map_err(db_error)?,
+                message_id: row.try_get("message_id").map_err(db_error)?,
+                findings: row.try_get("findings").map_err(db_error)?,
+                ciphertext: row.try_get("ciphertext").map_err(db_error)?,
+                created_at: row.try_get("created_at").map_err(db_error)?,
+            })
+        })
+        .transpose()
+    }
+}
@@ -0,0 +1,185 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+const SELECT_SCHEDULED: &str =
+    "SELECT id, tenant_id, user_id, chat_id, content, send_at, status, error, created_at, \
+     claimed_until FROM scheduled_messages";
+
+// DUE selects the pending messages whose send_at is not after $1 and the sending ones whose
+// claim was over before it
+const DUE: &str = "((status = 'pending' AND send_at <= $1) OR (status = 'sending' \
+                   AND (claimed_until IS NULL OR claimed_until < $1)))";
+
+pub struct PostgresScheduledMessageRepository {
+    pool: PgPool,
+}
+
+impl PostgresScheduledMessageRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduledMessageRepository for PostgresScheduledMessageRepository {
+    #[instrument(skip_all, fields(scheduled_id = %scheduled.id))]
+    async fn create_scheduled_message(
+        &self,
+        scheduled: &ScheduledMessage,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO scheduled_messages \
+             (id, tenant_id, user_id, chat_id, content, send_at, status, error, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(scheduled.id)
+        .bind(scheduled.tenant_id)
+        .bind(scheduled.user_id)
+        .bind(scheduled.chat_id)
+        .bind(&scheduled.content)
+        .bind(scheduled.send_at)
+        .bind(scheduled.status.to_string())
+        .bind(&scheduled.error)
+        .bind(scheduled.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id))]
+    async fn find_scheduled_message_by_id(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+    ) -> Result<Option<ScheduledMessage>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND id = $2",
+            SELECT_SCHEDULED
+        ))
+        .bind(tenant_id)
+        .bind(scheduled_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| scheduled_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_pending_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND user_id = $2 AND status = 'pending' \
+             ORDER BY send_at, id",
+            SELECT_SCHEDULED
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(scheduled_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn list_due(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE {} ORDER BY send_at, id LIMIT $2",
+            SELECT_SCHEDULED, DUE
+        ))
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(scheduled_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id))]
+    async fn claim_due(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(&format!(
+            "UPDATE scheduled_messages SET status = 'sending', claimed_until = $2 \
+             WHERE tenant_id = $3 AND id = $4 AND {}",
+            DUE
+        ))
+        .bind(now)
+        .bind(claimed_until)
+        .bind(tenant_id)
+        .bind(scheduled_id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id, from = %from, to = %to))]
+    async fn transition(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        from: ScheduledStatus,
+        to: ScheduledStatus,
+        error: Option<&str>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE scheduled_messages SET status = $1, error = $2, claimed_until = NULL \
+             WHERE tenant_id = $3 AND id = $4 AND status = $5",
+        )
+        .bind(to.to_string())
+        .bind(error)
+        .bind(tenant_id)
+        .bind(scheduled_id)
+        .bind(from.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+fn scheduled_from_row(row: &PgRow) -> Result<ScheduledMessage, RepositoryError> {
+    let status: String = row.try_get("status").map_err(db_error)?;
+
+    Ok(ScheduledMessage {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        chat_id: row.try_get("chat_id").map_err(db_error)?,
+        content: row.try_get("content").map_err(db_error)?,
+        send_at: row.try_get("send_at").map_err(db_error)?,
+        status: status
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        error: row.try_get("error").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+        claimed_until: row.try_get("claimed_until").map_err(db_error)?,
+    })
+}
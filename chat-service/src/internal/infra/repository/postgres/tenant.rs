@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::tenant::{Tenant, TenantConfig};
+use crate::internal::domain::output_filter::OutputFilterConfig;
+use crate::internal::domain::quota::QuotaConfig;
+use crate::internal::domain::rate_limiter::RateLimitConfig;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::tenant::TenantRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresTenantRepository {
+    pool: PgPool,
+}
+
+impl PostgresTenantRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantRepository for PostgresTenantRepository {
+    #[instrument(skip_all, fields(tenant_id = %tenant.id))]
+    async fn save_tenant(&self, tenant: &Tenant) -> Result<(), RepositoryError> {
+        let config = &tenant.config;
+        sqlx::query(
+            "INSERT INTO tenants (id, name, model, model_max_tokens, requests_per_minute, \
+             tokens_per_minute, allowed_models, user_daily_tokens, user_monthly_tokens, \
+             tenant_daily_tokens, tenant_monthly_tokens, output_filter) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, model = EXCLUDED.model, \
+             model_max_tokens = EXCLUDED.model_max_tokens, \
+             requests_per_minute = EXCLUDED.requests_per_minute, \
+             tokens_per_minute = EXCLUDED.tokens_per_minute, \
+             allowed_models = EXCLUDED.allowed_models, \
+             user_daily_tokens = EXCLUDED.user_daily_tokens, \
+             user_monthly_tokens = EXCLUDED.user_monthly_tokens, \
+             tenant_daily_tokens = EXCLUDED.tenant_daily_tokens, \
+             tenant_monthly_tokens = EXCLUDED.tenant_monthly_tokens, \
+             output_filter = EXCLUDED.output_filter",
+        )
+        .bind(tenant.id)
+        .bind(&tenant.name)
+        .bind(config.model.as_ref().map(|model| model.name.clone()))
+        .bind(config.model.as_ref().map(|model| model.max_tokens as i32))
+        .bind(
+            config
+                .rate_limit
+                .map(|limit| limit.requests_per_minute as i32),
+        )
+        .bind(
+            config
+                .rate_limit
+                .map(|limit| limit.tokens_per_minute as i32),
+        )
+        .bind(&config.allowed_models)
+        .bind(config.quota.map(|quota| quota.user_daily_tokens as i64))
+        .bind(config.quota.map(|quota| quota.user_monthly_tokens as i64))
+        .bind(config.quota.map(|quota| quota.tenant_daily_tokens as i64))
+        .bind(config.quota.map(|quota| quota.tenant_monthly_tokens as i64))
+        .bind(config.output_filter.as_ref().map(Json))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, name, model, model_max_tokens, requests_per_minute, tokens_per_minute, \
+             allowed_models, user_daily_tokens, user_monthly_tokens, tenant_daily_tokens, \
+             tenant_monthly_tokens, output_filter FROM tenants ORDER BY name, id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(tenant_from_row).collect()
+    }
+}
+
+fn tenant_from_row(row: &PgRow) -> Result<Tenant, RepositoryError> {
+    let model: Option<String> = row.try_get("model").map_err(db_error)?;
+    let max_tokens: Option<i32> = row.try_get("model_max_tokens").map_err(db_error)?;
+    let requests_per_minute: Option<i32> = row.try_get("requests_per_minute").map_err(db_error)?;
+    let tokens_per_minute: Option<i32> = row.try_get("tokens_per_minute").map_err(db_error)?;
+    let name: String = row.try_get("name").map_err(db_error)?;
+    let output_filter: Option<Json<OutputFilterConfig>> =
+        row.try_get("output_filter").map_err(db_error)?;
+    // the quota columns are written together, all null when the defaults apply
+    let quota: [Option<i64>; 4] = [
+        row.try_get("user_daily_tokens").map_err(db_error)?,
+        row.try_get("user_monthly_tokens").map_err(db_error)?,
+        row.try_get("tenant_daily_tokens").map_err(db_error)?,
+        row.try_get("tenant_monthly_tokens").map_err(db_error)?,
+    ];
+
+    Ok(Tenant::new(
+        row.try_get("id").map_err(db_error)?,
+        &name,
+        TenantConfig {
+            model: model
+                .zip(max_tokens)
+                .map(|(name, max_tokens)| Model::new(name, max_tokens as u32)),
+            rate_limit: requests_per_minute.zip(tokens_per_minute).map(
+                |(requests_per_minute, tokens_per_minute)| RateLimitConfig {
+                    requests_per_minute: requests_per_minute as u32,
+                    tokens_per_minute: tokens_per_minute as u32,
+                },
+            ),
+            allowed_models: row.try_get("allowed_models").map_err(db_error)?,
+            quota: quota_from_columns(quota),
+            output_filter: output_filter.map(|filter| filter.0),
+        },
+    ))
+}
+
+fn quota_from_columns(columns: [Option<i64>; 4]) -> Option<QuotaConfig> {
+    match columns {
+        [Some(user_daily), Some(user_monthly), Some(tenant_daily), Some(tenant_monthly)] => {
+            Some(QuotaConfig {
+                user_daily_tokens: user_daily as u64,
+                user_monthly_tokens: user_monthly as u64,
+                tenant_daily_tokens: tenant_daily as u64,
+                tenant_monthly_tokens: tenant_monthly as u64,
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Transaction};
+use tracing::instrument;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::unit_of_work::{ChatTransaction, UnitOfWork};
+use crate::internal::infra::repository::postgres::chat::{
+    begin, db_error, insert_chat, update_chat,
+};
+use crate::internal::infra::repository::postgres::usage::add_usage;
+
+pub struct PostgresUnitOfWork {
+    pool: PgPool,
+}
+
+impl PostgresUnitOfWork {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PostgresUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn ChatTransaction>, RepositoryError> {
+        let tx = begin(&self.pool).await?;
+
+        Ok(Box::new(PostgresChatTransaction { tx }))
+    }
+}
+
+// PostgresChatTransaction holds its connection until commit, sqlx rolls the transaction back
+// when it is dropped
+struct PostgresChatTransaction {
+    tx: Transaction<'static, Postgres>,
+}
+
+#[async_trait]
+impl ChatTransaction for PostgresChatTransaction {
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn create_chat(&mut self, chat: &Chat) -> Result<(), RepositoryError> {
+        insert_chat(&mut self.tx, chat).await
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn save_chat(&mut self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        update_chat(&mut self.tx, chat).await
+    }
+
+    #[instrument(skip_all, fields(user_id = %record.user_id, chat_id = %record.chat_id))]
+    async fn record_usage(&mut self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        add_usage(&mut self.tx, record).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepositoryError> {
+        self.tx.commit().await.map_err(db_error)
+    }
+}
@@ -0,0 +1,235 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Row, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::{
+    ChatUsage, DailyUsage, TenantUsage, UsageRecord, UserUsage,
+};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::infra::repository::postgres::chat::{begin, db_error};
+
+pub struct PostgresUsageRepository {
+    pool: PgPool,
+}
+
+impl PostgresUsageRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UsageRepository for PostgresUsageRepository {
+    #[instrument(skip_all, fields(user_id = %record.user_id, chat_id = %record.chat_id))]
+    async fn record_usage(&self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+        add_usage(&mut tx, record).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_daily_usage(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<DailyUsage>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT user_id, date, model, requests, prompt_tokens, completion_tokens, cost \
+             FROM usage_daily WHERE tenant_id = $1 AND user_id = $2 AND date BETWEEN $3 AND $4 \
+             ORDER BY date, model",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let requests: i64 = row.try_get("requests").map_err(db_error)?;
+                let prompt_tokens: i64 = row.try_get("prompt_tokens").map_err(db_error)?;
+                let completion_tokens: i64 = row.try_get("completion_tokens").map_err(db_error)?;
+
+                Ok(DailyUsage {
+                    user_id: row.try_get("user_id").map_err(db_error)?,
+                    date: row.try_get("date").map_err(db_error)?,
+                    model: row.try_get("model").map_err(db_error)?,
+                    requests: requests as u64,
+                    prompt_tokens: prompt_tokens as u64,
+                    completion_tokens: completion_tokens as u64,
+                    cost: row.try_get("cost").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(chats = chat_ids.len()))]
+    async fn list_chat_usage(
+        &self,
+        tenant_id: Uuid,
+        chat_ids: &[Uuid],
+    ) -> Result<Vec<ChatUsage>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT chat_id, requests, prompt_tokens, completion_tokens, cost \
+             FROM chat_usage WHERE tenant_id = $1 AND chat_id = ANY($2)",
+        )
+        .bind(tenant_id)
+        .bind(chat_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let requests: i64 = row.try_get("requests").map_err(db_error)?;
+                let prompt_tokens: i64 = row.try_get("prompt_tokens").map_err(db_error)?;
+                let completion_tokens: i64 = row.try_get("completion_tokens").map_err(db_error)?;
+
+                Ok(ChatUsage {
+                    chat_id: row.try_get("chat_id").map_err(db_error)?,
+                    requests: requests as u64,
+                    prompt_tokens: prompt_tokens as u64,
+                    completion_tokens: completion_tokens as u64,
+                    cost: row.try_get("cost").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    async fn summarize_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<TenantUsage>, RepositoryError> {
+        // the sums of BIGINT columns are NUMERIC, they are cast back to fit an i64
+        let rows = sqlx::query(
+            "SELECT tenant_id, model, COUNT(DISTINCT user_id) AS users, \
+             SUM(requests)::BIGINT AS requests, SUM(prompt_tokens)::BIGINT AS prompt_tokens, \
+             SUM(completion_tokens)::BIGINT AS completion_tokens, SUM(cost) AS cost \
+             FROM usage_daily WHERE date BETWEEN $1 AND $2 \
+             AND ($3::UUID IS NULL OR tenant_id = $3) \
+             GROUP BY tenant_id, model ORDER BY tenant_id, model",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let users: i64 = row.try_get("users").map_err(db_error)?;
+                let requests: i64 = row.try_get("requests").map_err(db_error)?;
+                let prompt_tokens: i64 = row.try_get("prompt_tokens").map_err(db_error)?;
+                let completion_tokens: i64 = row.try_get("completion_tokens").map_err(db_error)?;
+
+                Ok(TenantUsage {
+                    tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+                    model: row.try_get("model").map_err(db_error)?,
+                    users: users as u64,
+                    requests: requests as u64,
+                    prompt_tokens: prompt_tokens as u64,
+                    completion_tokens: completion_tokens as u64,
+                    cost: row.try_get("cost").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    async fn summarize_user_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<UserUsage>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT tenant_id, user_id, model, SUM(requests)::BIGINT AS requests, \
+             SUM(prompt_tokens)::BIGINT AS prompt_tokens, \
+             SUM(completion_tokens)::BIGINT AS completion_tokens, SUM(cost) AS cost \
+             FROM usage_daily WHERE date BETWEEN $1 AND $2 \
+             AND ($3::UUID IS NULL OR tenant_id = $3) \
+             GROUP BY tenant_id, user_id, model ORDER BY tenant_id, user_id, model",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let requests: i64 = row.try_get("requests").map_err(db_error)?;
+                let prompt_tokens: i64 = row.try_get("prompt_tokens").map_err(db_error)?;
+                let completion_tokens: i64 = row.try_get("completion_tokens").map_err(db_error)?;
+
+                Ok(UserUsage {
+                    tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+                    user_id: row.try_get("user_id").map_err(db_error)?,
+                    model: row.try_get("model").map_err(db_error)?,
+                    requests: requests as u64,
+                    prompt_tokens: prompt_tokens as u64,
+                    completion_tokens: completion_tokens as u64,
+                    cost: row.try_get("cost").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+// add_usage adds the record to the aggregates of its day and chat in the transaction
+pub(super) async fn add_usage(
+    tx: &mut Transaction<'_, Postgres>,
+    record: &UsageRecord,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "INSERT INTO usage_daily (tenant_id, user_id, date, model, requests, prompt_tokens, \
+         completion_tokens, cost) VALUES ($1, $2, $3, $4, 1, $5, $6, $7) \
+         ON CONFLICT (user_id, date, model) DO UPDATE SET \
+         requests = usage_daily.requests + 1, \
+         prompt_tokens = usage_daily.prompt_tokens + EXCLUDED.prompt_tokens, \
+         completion_tokens = usage_daily.completion_tokens + EXCLUDED.completion_tokens, \
+         cost = usage_daily.cost + EXCLUDED.cost",
+    )
+    .bind(record.tenant_id)
+    .bind(record.user_id)
+    .bind(record.date())
+    .bind(&record.model)
+    .bind(record.prompt_tokens as i64)
+    .bind(record.completion_tokens as i64)
+    .bind(record.cost)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    sqlx::query(
+        "INSERT INTO chat_usage (tenant_id, chat_id, requests, prompt_tokens, \
+         completion_tokens, cost) VALUES ($1, $2, 1, $3, $4, $5) \
+         ON CONFLICT (chat_id) DO UPDATE SET \
+         requests = chat_usage.requests + 1, \
+         prompt_tokens = chat_usage.prompt_tokens + EXCLUDED.prompt_tokens, \
+         completion_tokens = chat_usage.completion_tokens + EXCLUDED.completion_tokens, \
+         cost = chat_usage.cost + EXCLUDED.cost",
+    )
+    .bind(record.tenant_id)
+    .bind(record.chat_id)
+    .bind(record.prompt_tokens as i64)
+    .bind(record.completion_tokens as i64)
+    .bind(record.cost)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+const SELECT_USER: &str = "SELECT id, tenant_id, external_id, display_name, created_at FROM users";
+
+pub struct PostgresUserRepository {
+    pool: PgPool,
+}
+
+impl PostgresUserRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PostgresUserRepository {
+    #[instrument(skip_all, fields(user_id = %user.id))]
+    async fn create_user(&self, user: &User) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO users (id, tenant_id, external_id, display_name, created_at) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(user.id)
+        .bind(user.tenant_id)
+        .bind(&user.external_id)
+        .bind(&user.display_name)
+        .bind(user.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %id))]
+    async fn find_user_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<User>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = $1 AND id = $2", SELECT_USER))
+            .bind(tenant_id)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| user_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all)]
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: Uuid,
+        external_id: &str,
+    ) -> Result<Option<User>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 AND external_id = $2",
+            SELECT_USER
+        ))
+        .bind(tenant_id)
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| user_from_row(&row)).transpose()
+    }
+}
+
+fn user_from_row(row: &PgRow) -> Result<User, RepositoryError> {
+    Ok(User {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        external_id: row.try_get("external_id").map_err(db_error)?,
+        display_name: row.try_get("display_name").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user_memory::Memory;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+pub struct PostgresMemoryRepository {
+    pool: PgPool,
+}
+
+impl PostgresMemoryRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MemoryRepository for PostgresMemoryRepository {
+    #[instrument(skip_all, fields(count = memories.len()))]
+    async fn create_memories(
+        &self,
+        memories: &[Memory],
+        max_memories: usize,
+    ) -> Result<usize, RepositoryError> {
+        let max_memories = i64::try_from(max_memories).unwrap_or(i64::MAX);
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let mut created = 0;
+        for memory in memories {
+            // the lock keeps two extractions of the same user from both taking the last room
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+                .bind(memory.user_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            let result = sqlx::query(
+                "INSERT INTO memories (id, tenant_id, user_id, chat_id, content, created_at) \
+                 SELECT $1, $2, $3, $4, $5, $6 \
+                 WHERE (SELECT COUNT(*) FROM memories WHERE tenant_id = $2 AND user_id = $3) < $7",
+            )
+            .bind(memory.id)
+            .bind(memory.tenant_id)
+            .bind(memory.user_id)
+            .bind(memory.chat_id)
+            .bind(&memory.content)
+            .bind(memory.created_at)
+            .bind(max_memories)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+            created += result.rows_affected() as usize;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(created)
+    }
+
+    #[instrument(skip_all, fields(memory_id = %memory_id))]
+    async fn find_memory_by_id(
+        &self,
+        tenant_id: Uuid,
+        memory_id: Uuid,
+    ) -> Result<Option<Memory>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, content, created_at \
+             FROM memories WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(memory_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| memory_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_memories_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Memory>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, content, created_at \
+             FROM memories WHERE tenant_id = $1 AND user_id = $2 \
+             ORDER BY created_at, id",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(memory_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(memory_id = %memory_id))]
+    async fn delete_memory(&self, tenant_id: Uuid, memory_id: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM memories WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(memory_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn memory_from_row(row: &PgRow) -> Result<Memory, RepositoryError> {
+    Ok(Memory {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        user_id: row.try_get("user_id").map_err(db_error)?,
+        chat_id: row.try_get("chat_id").map_err(db_error)?,
+        content: row.try_get("content").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::embedding::{VectorKind, VectorMatch, VectorRecord};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::vector_store::{VectorQuery, VectorStore};
+use crate::internal::infra::repository::postgres::chat::{begin, db_error};
+
+// PgVectorStore keeps the embeddings in a pgvector column and ranks them by cosine distance;
+// searches are scoped to one user, so they scan the user's rows instead of an ANN index
+pub struct PgVectorStore {
+    pool: PgPool,
+}
+
+impl PgVectorStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    #[instrument(skip_all, fields(records = records.len()))]
+    async fn upsert(&self, records: &[VectorRecord]) -> Result<(), RepositoryError> {
+        let mut tx = begin(&self.pool).await?;
+
+        for record in records {
+            sqlx::query(
+                "INSERT INTO embeddings (id, tenant_id, user_id, kind, source_id, content, model, \
+                 embedding, metadata, created_at) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8::vector, $9, $10) \
+                 ON CONFLICT (id) DO UPDATE SET content = EXCLUDED.content, \
+                 model = EXCLUDED.model, embedding = EXCLUDED.embedding, \
+                 metadata = EXCLUDED.metadata",
+            )
+            .bind(record.id)
+            .bind(record.tenant_id)
+            .bind(record.user_id)
+            .bind(record.kind.to_string())
+            .bind(record.source_id)
+            .bind(&record.content)
+            .bind(&record.model)
+            .bind(vector_literal(&record.embedding))
+            .bind(Json(&record.metadata))
+            .bind(record.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(user_id = %query.user_id, limit = query.limit))]
+    async fn search(&self, query: &VectorQuery) -> Result<Vec<VectorMatch>, RepositoryError> {
+        let kinds: Vec<String> = query.kinds.iter().map(|kind| kind.to_string()).collect();
+
+        let rows = sqlx::query(
+            "SELECT id, kind, source_id, content, metadata, \
+             1 - (embedding <=> $4::vector) AS score FROM embeddings \
+             WHERE tenant_id = $1 AND user_id = $2 AND model = $3 \
+             AND (cardinality($5::text[]) = 0 OR kind = ANY($5)) \
+             AND 1 - (embedding <=> $4::vector) >= $6 \
+             ORDER BY embedding <=> $4::vector, id LIMIT $7",
+        )
+        .bind(query.tenant_id)
+        .bind(query.user_id)
+        .bind(&query.model)
+        .bind(vector_literal(&query.embedding))
+        .bind(&kinds)
+        .bind(query.min_score as f64)
+        .bind(query.limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(match_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(source_id = %source_id))]
+    async fn delete_by_source(
+        &self,
+        tenant_id: Uuid,
+        source_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM embeddings WHERE tenant_id = $1 AND source_id = $2")
+            .bind(tenant_id)
+            .bind(source_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+// vector_literal writes the embedding the way pgvector parses it, e.g. [0.1,-0.2]
+fn vector_literal(embedding: &[f32]) -> String {
+    let values: Vec<String> = embedding.iter().map(|value| value.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+fn match_from_row(row: &PgRow) -> Result<VectorMatch, RepositoryError> {
+    let kind: String = row.try_get("kind").map_err(db_error)?;
+    let metadata: Json<serde_json::Value> = row.try_get("metadata").map_err(db_error)?;
+    let score: f64 = row.try_get("score").map_err(db_error)?;
+
+    Ok(VectorMatch {
+        id: row.try_get("id").map_err(db_error)?,
+        kind: kind
+            .parse::<VectorKind>()
+            .map_err(|e| RepositoryError::Database(e.to_string()))?,
+        source_id: row.try_get("source_id").map_err(db_error)?,
+        content: row.try_get("content").map_err(db_error)?,
+        metadata: metadata.0,
+        score: score as f32,
+    })
+}
@@ -0,0 +1,299 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgRow};
+use sqlx::types::Json;
+use sqlx::Row;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{
+    DeadLetter, Webhook, WebhookDelivery, WebhookEvent,
+};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::infra::repository::postgres::chat::db_error;
+
+const DELIVERY_COLUMNS: &str = "id, tenant_id, webhook_id, event_id, event, payload, attempts, \
+                                next_attempt_at, last_error, created_at";
+
+pub struct PostgresWebhookRepository {
+    pool: PgPool,
+}
+
+impl PostgresWebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for PostgresWebhookRepository {
+    #[instrument(skip_all, fields(webhook_id = %webhook.id))]
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO webhooks (id, tenant_id, url, secret, events, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(webhook.id)
+        .bind(webhook.tenant_id)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(Json(&webhook.events))
+        .bind(webhook.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(webhook_id = %webhook_id))]
+    async fn find_webhook_by_id(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<Option<Webhook>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, url, secret, events, created_at \
+             FROM webhooks WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(webhook_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| webhook_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_webhooks(&self, tenant_id: Uuid) -> Result<Vec<Webhook>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, url, secret, events, created_at \
+             FROM webhooks WHERE tenant_id = $1 ORDER BY created_at, id",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(webhook_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(webhook_id = %webhook_id))]
+    async fn delete_webhook(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE tenant_id = $1 AND webhook_id = $2")
+            .bind(tenant_id)
+            .bind(webhook_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query("DELETE FROM webhooks WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(webhook_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(count = deliveries.len()))]
+    async fn enqueue_deliveries(
+        &self,
+        deliveries: &[WebhookDelivery],
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for delivery in deliveries {
+            sqlx::query(&format!(
+                "INSERT INTO webhook_deliveries ({}) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                DELIVERY_COLUMNS
+            ))
+            .bind(delivery.id)
+            .bind(delivery.tenant_id)
+            .bind(delivery.webhook_id)
+            .bind(delivery.event_id)
+            .bind(delivery.event.to_string())
+            .bind(&delivery.payload)
+            .bind(delivery.attempts as i32)
+            .bind(delivery.next_attempt_at)
+            .bind(&delivery.last_error)
+            .bind(delivery.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn due_deliveries(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE next_attempt_at <= $1 \
+             ORDER BY next_attempt_at, id LIMIT $2",
+            DELIVERY_COLUMNS
+        ))
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery_id))]
+    async fn claim_delivery(
+        &self,
+        delivery_id: Uuid,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        lease_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE webhook_deliveries SET next_attempt_at = $1 \
+             WHERE id = $2 AND next_attempt_at = $3",
+        )
+        .bind(lease_until)
+        .bind(delivery_id)
+        .bind(next_attempt_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery.id))]
+    async fn reschedule_delivery(&self, delivery: &WebhookDelivery) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE webhook_deliveries SET attempts = $1, next_attempt_at = $2, last_error = $3 \
+             WHERE id = $4",
+        )
+        .bind(delivery.attempts as i32)
+        .bind(delivery.next_attempt_at)
+        .bind(&delivery.last_error)
+        .bind(delivery.id)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery_id))]
+    async fn complete_delivery(&self, delivery_id: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = $1")
+            .bind(delivery_id)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %dead_letter.delivery.id))]
+    async fn dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), RepositoryError> {
+        let delivery = &dead_letter.delivery;
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query(&format!(
+            "INSERT INTO webhook_dead_letters ({}, failed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery.id)
+        .bind(delivery.tenant_id)
+        .bind(delivery.webhook_id)
+        .bind(delivery.event_id)
+        .bind(delivery.event.to_string())
+        .bind(&delivery.payload)
+        .bind(delivery.attempts as i32)
+        .bind(delivery.next_attempt_at)
+        .bind(&delivery.last_error)
+        .bind(delivery.created_at)
+        .bind(dead_letter.failed_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = $1")
+            .bind(delivery.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_dead_letters(
+        &self,
+        tenant_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, failed_at FROM webhook_dead_letters WHERE tenant_id = $1 \
+             ORDER BY failed_at DESC, id DESC LIMIT $2",
+            DELIVERY_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DeadLetter {
+                    delivery: delivery_from_row(row)?,
+                    failed_at: row.try_get("failed_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn webhook_from_row(row: &PgRow) -> Result<Webhook, RepositoryError> {
+    let events: Json<Vec<WebhookEvent>> = row.try_get("events").map_err(db_error)?;
+
+    Ok(Webhook {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        url: row.try_get("url").map_err(db_error)?,
+        secret: row.try_get("secret").map_err(db_error)?,
+        events: events.0,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
+
+fn delivery_from_row(row: &PgRow) -> Result<WebhookDelivery, RepositoryError> {
+    let event: String = row.try_get("event").map_err(db_error)?;
+    let attempts: i32 = row.try_get("attempts").map_err(db_error)?;
+
+    Ok(WebhookDelivery {
+        id: row.try_get("id").map_err(db_error)?,
+        tenant_id: row.try_get("tenant_id").map_err(db_error)?,
+        webhook_id: row.try_get("webhook_id").map_err(db_error)?,
+        event_id: row.try_get("event_id").map_err(db_error)?,
+        event: event
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        payload: row.try_get("payload").map_err(db_error)?,
+        attempts: attempts as u32,
+        next_attempt_at: row.try_get("next_attempt_at").map_err(db_error)?,
+        last_error: row.try_get("last_error").map_err(db_error)?,
+        created_at: row.try_get("created_at").map_err(db_error)?,
+    })
+}
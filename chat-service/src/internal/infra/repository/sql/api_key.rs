@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_optional_timestamp, get_text, get_timestamp, get_uuid, timestamp,
+};
+
+pub struct SqlApiKeyRepository {
+    pool: AnyPool,
+}
+
+impl SqlApiKeyRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for SqlApiKeyRepository {
+    #[instrument(skip_all, fields(user_id = %api_key.user_id))]
+    async fn create_api_key(&self, api_key: &ApiKey) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO api_keys (id, tenant_id, user_id, prefix, key_hash, created_at, \
+             revoked_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(api_key.id.to_string())
+        .bind(api_key.tenant_id.to_string())
+        .bind(api_key.user_id.to_string())
+        .bind(&api_key.prefix)
+        .bind(&api_key.key_hash)
+        .bind(timestamp(api_key.created_at))
+        .bind(api_key.revoked_at.map(timestamp))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn find_api_key_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, prefix, key_hash, created_at, revoked_at \
+             FROM api_keys WHERE key_hash = ?",
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let row = match row {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        Ok(Some(ApiKey {
+            id: get_uuid(&row, "id")?,
+            tenant_id: get_uuid(&row, "tenant_id")?,
+            user_id: get_uuid(&row, "user_id")?,
+            prefix: get_text(&row, "prefix")?,
+            key_hash: get_text(&row, "key_hash")?,
+            created_at: get_timestamp(&row, "created_at")?,
+            revoked_at: get_optional_timestamp(&row, "revoked_at")?,
+        }))
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn revoke_api_keys(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        keep: Uuid,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = ? \
+             WHERE tenant_id = ? AND user_id = ? AND id <> ? AND revoked_at IS NULL",
+        )
+        .bind(timestamp(revoked_at))
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .bind(keep.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() as usize)
+    }
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_optional_float, get_optional_text, get_text, get_timestamp, get_uuid,
+    json, timestamp,
+};
+
+const ASSISTANT_COLUMNS: &str =
+    "id, tenant_id, name, instructions, model, temperature, tools, created_at, updated_at";
+
+pub struct SqlAssistantRepository {
+    pool: AnyPool,
+}
+
+impl SqlAssistantRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AssistantRepository for SqlAssistantRepository {
+    #[instrument(skip_all, fields(assistant_id = %assistant.id))]
+    async fn create_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        sqlx::query(&format!(
+            "INSERT INTO assistants ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(assistant.id.to_string())
+        .bind(assistant.tenant_id.to_string())
+        .bind(&assistant.name)
+        .bind(&assistant.instructions)
+        .bind(assistant.model.clone())
+        .bind(assistant.temperature.map(f64::from))
+        .bind(json(&assistant.tools)?)
+        .bind(timestamp(assistant.created_at))
+        .bind(timestamp(assistant.updated_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant_id))]
+    async fn find_assistant_by_id(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<Option<Assistant>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM assistants WHERE tenant_id = ? AND id = ?",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(tenant_id.to_string())
+        .bind(assistant_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| assistant_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_assistants(&self, tenant_id: Uuid) -> Result<Vec<Assistant>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM assistants WHERE tenant_id = ? ORDER BY name",
+            ASSISTANT_COLUMNS
+        ))
+        .bind(tenant_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(assistant_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant.id))]
+    async fn update_assistant(&self, assistant: &Assistant) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE assistants SET name = ?, instructions = ?, model = ?, temperature = ?, \
+             tools = ?, updated_at = ? WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(&assistant.name)
+        .bind(&assistant.instructions)
+        .bind(assistant.model.clone())
+        .bind(assistant.temperature.map(f64::from))
+        .bind(json(&assistant.tools)?)
+        .bind(timestamp(assistant.updated_at))
+        .bind(assistant.tenant_id.to_string())
+        .bind(assistant.id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(assistant_id = %assistant_id))]
+    async fn delete_assistant(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM assistants WHERE tenant_id = ? AND id = ?")
+            .bind(tenant_id.to_string())
+            .bind(assistant_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn assistant_from_row(row: &AnyRow) -> Result<Assistant, RepositoryError> {
+    Ok(Assistant {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        name: get_text(row, "name")?,
+        instructions: get_text(row, "instructions")?,
+        model: get_optional_text(row, "model")?,
+        temperature: get_optional_float(row, "temperature")?.map(|t| t as f32),
+        tools: get_json(row, "tools")?,
+        created_at: get_timestamp(row, "created_at")?,
+        updated_at: get_timestamp(row, "updated_at")?,
+    })
+}
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+
+use crate::internal::domain::entity::audit::{AuditDirection, AuditEntry};
+use crate::internal::domain::repository::audit::{AuditQuery, AuditRepository};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_integer, get_optional_integer, get_optional_text, get_text, get_timestamp,
+    get_uuid, timestamp,
+};
+
+// SqlAuditRepository only ever inserts into the audit log; unlike postgres the table has no
+// trigger refusing updates and deletes, sqlite and mysql share the migrations and have no
+// trigger syntax in common, so the database itself does not keep it append-only
+pub struct SqlAuditRepository {
+    pool: AnyPool,
+}
+
+impl SqlAuditRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqlAuditRepository {
+    #[instrument(skip_all, fields(request_id = %entry.request_id, direction = %entry.direction))]
+    async fn append(&self, entry: &AuditEntry) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO audit_log (id, request_id, tenant_id, user_id, chat_id, direction, \
+             provider, model, content, error, latency_ms, prompt_tokens, completion_tokens, \
+             created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.id.to_string())
+        .bind(entry.request_id.to_string())
+        .bind(entry.tenant_id.to_string())
+        .bind(entry.user_id.to_string())
+        .bind(entry.chat_id.to_string())
+        .bind(entry.direction.to_string())
+        .bind(&entry.provider)
+        .bind(&entry.model)
+        .bind(&entry.content)
+        .bind(&entry.error)
+        .bind(entry.latency_ms.map(|latency| latency as i64))
+        .bind(entry.prompt_tokens as i64)
+        .bind(entry.completion_tokens as i64)
+        .bind(timestamp(entry.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    // list_entries only adds the conditions of the filters that are set
+    #[instrument(skip_all)]
+    async fn list_entries(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, RepositoryError> {
+        let mut conditions = vec!["1 = 1"];
+        let mut values = vec![];
+        for (column, id) in [
+            ("tenant_id = ?", query.tenant_id),
+            ("user_id = ?", query.user_id),
+            ("chat_id = ?", query.chat_id),
+            ("request_id = ?", query.request_id),
+        ] {
+            if let Some(id) = id {
+                conditions.push(column);
+                values.push(id.to_string());
+            }
+        }
+        if let Some(from) = query.from {
+            conditions.push("created_at >= ?");
+            values.push(timestamp(from));
+        }
+        if let Some(to) = query.to {
+            conditions.push("created_at < ?");
+            values.push(timestamp(to));
+        }
+        if let Some(after) = query.after {
+            conditions.push("(created_at > ? OR (created_at = ? AND id > ?))");
+            values.push(timestamp(after.created_at));
+            values.push(timestamp(after.created_at));
+            values.push(after.id.to_string());
+        }
+        let sql = format!(
+            "SELECT id, request_id, tenant_id, user_id, chat_id, direction, provider, model, \
+             content, error, latency_ms, prompt_tokens, completion_tokens, created_at \
+             FROM audit_log WHERE {} ORDER BY created_at, id LIMIT ?",
+            conditions.join(" AND ")
+        );
+
+        let mut sql_query = sqlx::query(&sql);
+        for value in values {
+            sql_query = sql_query.bind(value);
+        }
+        let rows = sql_query
+            .bind(query.limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(entry_from_row).collect()
+    }
+}
+
+fn entry_from_row(row: &AnyRow) -> Result<AuditEntry, RepositoryError> {
+    let direction: AuditDirection = get_text(row, "direction")?
+        .parse()
+        .map_err(RepositoryError::Database)?;
+
+    Ok(AuditEntry {
+        id: get_uuid(row, "id")?,
+        request_id: get_uuid(row, "request_id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        chat_id: get_uuid(row, "chat_id")?,
+        direction,
+        provider: get_text(row, "provider")?,
+        model: get_text(row, "model")?,
+        content: get_text(row, "content")?,
+        error: get_optional_text(row, "error")?,
+        latency_ms: get_optional_integer(row, "latency_ms")?.map(|latency| latency as u64),
+        prompt_tokens: get_integer(row, "prompt_tokens")? as u32,
+        completion_tokens: get_integer(row, "completion_tokens")? as u32,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::Batch;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_optional_timestamp, get_text, get_timestamp, get_uuid, json, timestamp,
+};
+
+pub struct SqlBatchRepository {
+    pool: AnyPool,
+}
+
+impl SqlBatchRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BatchRepository for SqlBatchRepository {
+    #[instrument(skip_all, fields(batch_id = %batch.id))]
+    async fn create_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO batches \
+             (id, tenant_id, user_id, status, items, created_at, completed_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(batch.id.to_string())
+        .bind(batch.tenant_id.to_string())
+        .bind(batch.user_id.to_string())
+        .bind(batch.status.to_string())
+        .bind(json(&batch.items)?)
+        .bind(timestamp(batch.created_at))
+        .bind(batch.completed_at.map(timestamp))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(batch_id = %batch_id))]
+    async fn find_batch_by_id(
+        &self,
+        tenant_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<Option<Batch>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, status, items, created_at, completed_at \
+             FROM batches WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id.to_string())
+        .bind(batch_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| batch_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(batch_id = %batch.id))]
+    async fn complete_batch(&self, batch: &Batch) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE batches SET status = ?, items = ?, completed_at = ? \
+             WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(batch.status.to_string())
+        .bind(json(&batch.items)?)
+        .bind(batch.completed_at.map(timestamp))
+        .bind(batch.tenant_id.to_string())
+        .bind(batch.id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn batch_from_row(row: &AnyRow) -> Result<Batch, RepositoryError> {
+    Ok(Batch {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        status: get_text(row, "status")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        items: get_json(row, "items")?,
+        created_at: get_timestamp(row, "created_at")?,
+        completed_at: get_optional_timestamp(row, "completed_at")?,
+    })
+}
@@ -0,0 +1,797 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use sqlx::{Any, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{
+    Chat, ChatConfig, ChatStatus, ChatSummary, HistoryArchive, TrimmingPolicy,
+};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::{
+    ChatCursor, ChatRepository, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+};
+use crate::internal::domain::text_search::{match_text, search_terms};
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_float, get_integer, get_json, get_optional_json, get_optional_text,
+    get_optional_uuid, get_text, get_timestamp, get_uuid, json, placeholders, timestamp,
+};
+use crate::internal::infra::repository::sql::dialect::Dialect;
+
+const SELECT_CHAT: &str =
+    "SELECT id, tenant_id, user_id, system_message_id, status, token_usage, model, \
+     model_max_tokens, temperature, top_p, n, stop, max_tokens, presence_penalty, \
+     frequency_penalty, trimming_policy, tools, response_format, title, tags, metadata, version, \
+     history_archive, assistant_id, response_language FROM chats";
+
+const SELECT_SUMMARY: &str =
+    "SELECT chat_id, tenant_id, user_id, status, model, title, tags, metadata, token_usage, \
+     message_count, last_message_preview, created_at, updated_at FROM chat_summaries";
+
+// SEARCH_SCAN bounds the messages a search ranks, the most recent ones containing the words
+const SEARCH_SCAN: usize = 1000;
+
+const SELECT_MESSAGE: &str =
+    "SELECT id, role, content, tokens, erased, created_at, tool_calls, tool_call_id, \
+     revision_of, attachments, candidates, interrupted, request_id, speaker, language \
+     FROM messages";
+
+// SqlChatRepository stores chats in mysql or sqlite, it also writes the events the chats
+// record to the outbox
+pub struct SqlChatRepository {
+    pool: AnyPool,
+    dialect: Dialect,
+    model: Model,
+}
+
+impl SqlChatRepository {
+    // new expects every message stored by this service to have been produced with the given model
+    pub fn new(pool: AnyPool, dialect: Dialect, model: Model) -> Self {
+        Self {
+            pool,
+            dialect,
+            model,
+        }
+    }
+
+    async fn load_chat(&self, row: AnyRow) -> Result<Chat, RepositoryError> {
+        let id = get_uuid(&row, "id")?;
+        let system_message_id = get_uuid(&row, "system_message_id")?;
+
+        let rows = sqlx::query(&format!(
+            "{} WHERE chat_id = ? ORDER BY position",
+            SELECT_MESSAGE
+        ))
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut initial_system_message = None;
+        let mut messages = vec![];
+        let mut erased_messages = vec![];
+
+        for row in rows {
+            let erased = get_integer(&row, "erased")? != 0;
+            let message = self.message_from_row(&row)?;
+
+            if message.id == system_message_id {
+                initial_system_message = Some(message);
+            } else if erased {
+                erased_messages.push(message);
+            } else {
+                messages.push(message);
+            }
+        }
+
+        let initial_system_message = initial_system_message.ok_or_else(|| {
+            RepositoryError::Database(format!("chat {} has no system message", id))
+        })?;
+
+        let status: ChatStatus = get_text(&row, "status")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+        let trimming_policy: TrimmingPolicy = get_text(&row, "trimming_policy")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+
+        let config = ChatConfig {
+            model: Model::new(
+                get_text(&row, "model")?,
+                get_integer(&row, "model_max_tokens")? as u32,
+            ),
+            temperature: get_float(&row, "temperature")? as f32,
+            top_p: get_float(&row, "top_p")? as f32,
+            n: get_integer(&row, "n")? as u32,
+            stop: get_json(&row, "stop")?,
+            max_tokens: get_integer(&row, "max_tokens")? as usize,
+            presence_penalty: get_float(&row, "presence_penalty")? as f32,
+            frequency_penalty: get_float(&row, "frequency_penalty")? as f32,
+            trimming_policy,
+            tools: get_json(&row, "tools")?,
+            response_format: get_json(&row, "response_format")?,
+            response_language: get_optional_text(&row, "response_language")?,
+        };
+
+        Ok(Chat::new(
+            id,
+            get_uuid(&row, "user_id")?,
+            initial_system_message,
+            messages,
+            erased_messages,
+            status,
+            get_integer(&row, "token_usage")? as usize,
+            config,
+        )
+        .with_tenant(get_uuid(&row, "tenant_id")?)
+        .with_title(get_optional_text(&row, "title")?)
+        .with_tags(get_optional_json(&row, "tags")?.unwrap_or_default())
+        .with_metadata(get_optional_json(&row, "metadata")?.unwrap_or_default())
+        .with_history_archive(get_optional_json(&row, "history_archive")?)
+        .with_assistant(get_optional_uuid(&row, "assistant_id")?)
+        .with_version(get_integer(&row, "version")? as u64))
+    }
+
+    fn message_from_row(&self, row: &AnyRow) -> Result<Message, RepositoryError> {
+        let role: Role = get_text(row, "role")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+
+        Ok(Message {
+            id: get_uuid(row, "id")?,
+            role,
+            content: get_text(row, "content")?,
+            tokens: get_integer(row, "tokens")? as usize,
+            model: self.model.clone(),
+            created_at: get_timestamp(row, "created_at")?,
+            tool_calls: get_json(row, "tool_calls")?,
+            tool_call_id: get_optional_text(row, "tool_call_id")?,
+            revision_of: get_optional_uuid(row, "revision_of")?,
+            attachments: get_json(row, "attachments")?,
+            // candidates is null for the messages stored before it was added
+            candidates: get_optional_json(row, "candidates")?.unwrap_or_default(),
+            interrupted: get_integer(row, "interrupted")? != 0,
+            request_id: get_optional_text(row, "request_id")?,
+            speaker: get_optional_text(row, "speaker")?,
+            language: get_optional_text(row, "language")?,
+            redaction: None,
+        })
+    }
+}
+
+#[async_trait]
+impl ChatRepository for SqlChatRepository {
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        insert_chat(&mut tx, self.dialect, chat).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn find_chat_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<Chat>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = ? AND id = ?", SELECT_CHAT))
+            .bind(tenant_id.to_string())
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_chat(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        update_chat(&mut tx, self.dialect, chat).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    // update_chat_title leaves updated_at alone, titling is not activity of the user
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn update_chat_title(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        title: &str,
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("UPDATE chats SET title = ? WHERE tenant_id = ? AND id = ?")
+            .bind(title)
+            .bind(tenant_id.to_string())
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query("UPDATE chat_summaries SET title = ? WHERE tenant_id = ? AND chat_id = ?")
+            .bind(title)
+            .bind(tenant_id.to_string())
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_chats_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = ? AND user_id = ? AND status <> 'deleted' \
+             ORDER BY updated_at DESC",
+            SELECT_CHAT
+        ))
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut chats = Vec::with_capacity(rows.len());
+        for row in rows {
+            chats.push(self.load_chat(row).await?);
+        }
+
+        Ok(chats)
+    }
+
+    // list_chat_summaries spells the cursor comparison out, mysql and sqlite do not agree on
+    // comparing rows; the tag is looked up as a quoted string in the json array, normalized tags
+    // hold no quotes
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_chat_summaries(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        tag: Option<&str>,
+        after: Option<ChatCursor>,
+        limit: usize,
+    ) -> Result<Vec<ChatSummary>, RepositoryError> {
+        let tagged = match tag {
+            Some(_) => "AND INSTR(tags, ?) > 0",
+            None => "",
+        };
+        let cursor = match after {
+            Some(_) => "AND (updated_at < ? OR (updated_at = ? AND chat_id < ?))",
+            None => "",
+        };
+        let sql = format!(
+            "{} WHERE tenant_id = ? AND user_id = ? AND status <> 'deleted' {} {} \
+             ORDER BY updated_at DESC, chat_id DESC LIMIT ?",
+            SELECT_SUMMARY, tagged, cursor
+        );
+
+        let mut query = sqlx::query(&sql)
+            .bind(tenant_id.to_string())
+            .bind(user_id.to_string());
+        if let Some(tag) = tag {
+            query = query.bind(json(&tag)?);
+        }
+        if let Some(after) = after {
+            query = query
+                .bind(timestamp(after.last_activity_at))
+                .bind(timestamp(after.last_activity_at))
+                .bind(after.id.to_string());
+        }
+        let rows = query
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(summary_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(chat_id = %id))]
+    async fn find_chat_summary(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ChatSummary>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = ? AND chat_id = ?",
+            SELECT_SUMMARY
+        ))
+        .bind(tenant_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.as_ref().map(summary_from_row).transpose()
+    }
+
+    // list_messages skips the system message by its -1 position and pages by the position
+    // of the cursor message, which stays valid while later turns are appended
+    #[instrument(skip_all, fields(chat_id = %chat_id))]
+    async fn list_messages(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        query: &MessageQuery,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        let mut sql = format!(
+            "{} WHERE chat_id = ? AND erased = 0 AND position >= 0 \
+             AND EXISTS (SELECT 1 FROM chats WHERE id = ? AND tenant_id = ?)",
+            SELECT_MESSAGE
+        );
+        if !query.roles.is_empty() {
+            sql.push_str(&format!(
+                " AND role IN ({})",
+                placeholders(query.roles.len())
+            ));
+        }
+        if query.from.is_some() {
+            sql.push_str(" AND created_at >= ?");
+        }
+        if query.to.is_some() {
+            sql.push_str(" AND created_at < ?");
+        }
+        if query.after.is_some() {
+            sql.push_str(
+                " AND position > (SELECT position FROM messages \
+                 WHERE chat_id = ? AND id = ? AND erased = 0)",
+            );
+        }
+        sql.push_str(" ORDER BY position LIMIT ?");
+
+        let mut statement = sqlx::query(&sql)
+            .bind(chat_id.to_string())
+            .bind(chat_id.to_string())
+            .bind(tenant_id.to_string());
+        for role in &query.roles {
+            statement = statement.bind(role.to_string());
+        }
+        if let Some(from) = query.from {
+            statement = statement.bind(timestamp(from));
+        }
+        if let Some(to) = query.to {
+            statement = statement.bind(timestamp(to));
+        }
+        if let Some(after) = query.after {
+            statement = statement.bind(chat_id.to_string()).bind(after.to_string());
+        }
+        let rows = statement
+            .bind(query.limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(|row| self.message_from_row(row)).collect()
+    }
+
+    // search_transcripts narrows the messages down with LIKE on every word of the query and
+    // matches them as text_search does, mysql and sqlite share no full-text syntax
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn search_transcripts(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+        let terms = search_terms(query);
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut sql = "SELECT m.id, m.chat_id, m.role, m.content, m.created_at, c.title \
+                       FROM messages m JOIN chats c ON c.id = m.chat_id \
+                       WHERE c.tenant_id = ? AND c.user_id = ? AND c.status <> 'deleted' \
+                       AND m.erased = 0 AND m.role IN ('user', 'assistant')"
+            .to_string();
+        for _ in &terms {
+            sql.push_str(" AND LOWER(m.content) LIKE ?");
+        }
+        sql.push_str(" ORDER BY m.created_at DESC LIMIT ?");
+
+        let mut statement = sqlx::query(&sql)
+            .bind(tenant_id.to_string())
+            .bind(user_id.to_string());
+        for term in &terms {
+            statement = statement.bind(format!("%{}%", term));
+        }
+        let rows = statement
+            .bind(SEARCH_SCAN as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut matches = vec![];
+        for row in &rows {
+            let Some(found) = match_text(&get_text(row, "content")?, &terms) else {
+                continue;
+            };
+            matches.push(TranscriptMatch {
+                chat_id: get_uuid(row, "chat_id")?,
+                chat_title: get_optional_text(row, "title")?,
+                message_id: get_uuid(row, "id")?,
+                role: get_text(row, "role")?
+                    .parse()
+                    .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+                highlight: found.highlight,
+                rank: found.rank,
+                created_at: get_timestamp(row, "created_at")?,
+            });
+        }
+        matches.sort_by(|a, b| {
+            b.rank
+                .total_cmp(&a.rank)
+                .then(b.created_at.cmp(&a.created_at))
+        });
+        matches.truncate(limit);
+
+        Ok(matches)
+    }
+
+    #[instrument(skip_all)]
+    async fn list_inactive_chats(
+        &self,
+        inactive_before: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Chat>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE status <> 'deleted' AND history_archive IS NULL AND updated_at < ? \
+             ORDER BY updated_at, id LIMIT ?",
+            SELECT_CHAT
+        ))
+        .bind(timestamp(inactive_before))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut chats = Vec::with_capacity(rows.len());
+        for row in rows {
+            chats.push(self.load_chat(row).await?);
+        }
+
+        Ok(chats)
+    }
+
+    // archive_chat_history keeps the system message, it stays with the chat
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn archive_chat_history(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let updated = sqlx::query(
+            "UPDATE chats SET status = ?, history_archive = ?, version = version + 1 \
+             WHERE id = ? AND tenant_id = ? AND version = ?",
+        )
+        .bind(chat.status.to_string())
+        .bind(chat.history_archive.as_ref().map(json).transpose()?)
+        .bind(chat.id.to_string())
+        .bind(chat.tenant_id.to_string())
+        .bind(chat.version as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        if updated.rows_affected() == 0 {
+            return match is_stored(&mut tx, chat).await? {
+                true => Err(RepositoryError::ConcurrentModification(chat.id)),
+                false => Ok(()),
+            };
+        }
+
+        sqlx::query("DELETE FROM messages WHERE chat_id = ? AND position >= 0")
+            .bind(chat.id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        write_summary(&mut tx, chat).await?;
+        insert_events(&mut tx, self.dialect, chat).await?;
+        tx.commit().await.map_err(db_error)?;
+        chat.version += 1;
+
+        Ok(())
+    }
+
+    // purge_deleted_chats reads the ids before deleting, mysql has no RETURNING; the foreign
+    // keys remove the messages and usage of the chats
+    #[instrument(skip_all)]
+    async fn purge_deleted_chats(
+        &self,
+        deleted_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<PurgedChat>, RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let rows = sqlx::query(
+            "SELECT id, history_archive FROM chats WHERE status = 'deleted' AND deleted_at < ?",
+        )
+        .bind(timestamp(deleted_before))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        let purged = rows
+            .iter()
+            .map(|row| {
+                let history_archive: Option<HistoryArchive> =
+                    get_optional_json(row, "history_archive")?;
+
+                Ok(PurgedChat {
+                    id: get_uuid(row, "id")?,
+                    history_key: history_archive.map(|archive| archive.key),
+                })
+            })
+            .collect::<Result<Vec<_>, RepositoryError>>()?;
+        if purged.is_empty() {
+            return Ok(purged);
+        }
+
+        let sql = format!(
+            "DELETE FROM chats WHERE id IN ({})",
+            placeholders(purged.len())
+        );
+        let mut statement = sqlx::query(&sql);
+        for chat in &purged {
+            statement = statement.bind(chat.id.to_string());
+        }
+        statement.execute(&mut *tx).await.map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(purged)
+    }
+}
+
+fn summary_from_row(row: &AnyRow) -> Result<ChatSummary, RepositoryError> {
+    let status: ChatStatus = get_text(row, "status")?
+        .parse()
+        .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?;
+
+    Ok(ChatSummary {
+        id: get_uuid(row, "chat_id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        status,
+        model: get_text(row, "model")?,
+        title: get_optional_text(row, "title")?,
+        tags: get_optional_json(row, "tags")?.unwrap_or_default(),
+        metadata: get_optional_json(row, "metadata")?.unwrap_or_default(),
+        token_usage: get_integer(row, "token_usage")? as usize,
+        message_count: get_integer(row, "message_count")? as usize,
+        last_message_preview: get_optional_text(row, "last_message_preview")?,
+        created_at: get_timestamp(row, "created_at")?,
+        last_activity_at: get_timestamp(row, "updated_at")?,
+    })
+}
+
+// insert_chat stores a new chat with its system message in the transaction
+pub(super) async fn insert_chat(
+    tx: &mut Transaction<'_, Any>,
+    dialect: Dialect,
+    chat: &Chat,
+) -> Result<(), RepositoryError> {
+    let now = timestamp(chrono::Utc::now());
+
+    sqlx::query(
+        "INSERT INTO chats (id, user_id, system_message_id, status, token_usage, model, \
+         model_max_tokens, temperature, top_p, n, stop, max_tokens, presence_penalty, \
+         frequency_penalty, trimming_policy, tools, response_format, title, tenant_id, \
+         created_at, updated_at, version, tags, metadata, history_archive, assistant_id, \
+         response_language) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(chat.id.to_string())
+    .bind(chat.user_id.to_string())
+    .bind(chat.initial_system_message().id.to_string())
+    .bind(chat.status.to_string())
+    .bind(chat.token_usage as i64)
+    .bind(&chat.config.model.name)
+    .bind(chat.config.model.max_tokens as i64)
+    .bind(chat.config.temperature as f64)
+    .bind(chat.config.top_p as f64)
+    .bind(chat.config.n as i64)
+    .bind(json(&chat.config.stop)?)
+    .bind(chat.config.max_tokens as i64)
+    .bind(chat.config.presence_penalty as f64)
+    .bind(chat.config.frequency_penalty as f64)
+    .bind(chat.config.trimming_policy.to_string())
+    .bind(json(&chat.config.tools)?)
+    .bind(json(&chat.config.response_format)?)
+    .bind(&chat.title)
+    .bind(chat.tenant_id.to_string())
+    .bind(&now)
+    .bind(&now)
+    .bind(chat.version as i64)
+    .bind(json(&chat.tags)?)
+    .bind(json(&chat.metadata)?)
+    .bind(chat.history_archive.as_ref().map(json).transpose()?)
+    .bind(chat.assistant_id.map(|id| id.to_string()))
+    .bind(&chat.config.response_language)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    insert_message(tx, chat.id, chat.initial_system_message(), false, -1).await?;
+    write_summary(tx, chat).await?;
+    insert_events(tx, dialect, chat).await?;
+
+    Ok(())
+}
+
+// update_chat leaves the messages alone when the chat is not stored under its tenant; the
+// system message is written again as it may have been replaced
+pub(super) async fn update_chat(
+    tx: &mut Transaction<'_, Any>,
+    dialect: Dialect,
+    chat: &mut Chat,
+) -> Result<(), RepositoryError> {
+    let now = timestamp(chrono::Utc::now());
+    let deleted = chat.status == ChatStatus::Deleted;
+
+    let updated = sqlx::query(
+        "UPDATE chats SET status = ?, token_usage = ?, tools = ?, updated_at = ?, \
+         deleted_at = CASE WHEN ? THEN COALESCE(deleted_at, ?) END, \
+         title = COALESCE(?, title), model = ?, model_max_tokens = ?, temperature = ?, \
+         max_tokens = ?, tags = ?, metadata = ?, history_archive = ?, response_language = ?, \
+         version = version + 1 \
+         WHERE id = ? AND tenant_id = ? AND version = ?",
+    )
+    .bind(chat.status.to_string())
+    .bind(chat.token_usage as i64)
+    .bind(json(&chat.config.tools)?)
+    .bind(&now)
+    .bind(deleted as i64)
+    .bind(&now)
+    .bind(&chat.title)
+    .bind(&chat.config.model.name)
+    .bind(chat.config.model.max_tokens as i64)
+    .bind(chat.config.temperature as f64)
+    .bind(chat.config.max_tokens as i64)
+    .bind(json(&chat.tags)?)
+    .bind(json(&chat.metadata)?)
+    .bind(chat.history_archive.as_ref().map(json).transpose()?)
+    .bind(&chat.config.response_language)
+    .bind(chat.id.to_string())
+    .bind(chat.tenant_id.to_string())
+    .bind(chat.version as i64)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+    if updated.rows_affected() == 0 {
+        return match is_stored(tx, chat).await? {
+            true => Err(RepositoryError::ConcurrentModification(chat.id)),
+            false => Ok(()),
+        };
+    }
+
+    sqlx::query("DELETE FROM messages WHERE chat_id = ?")
+        .bind(chat.id.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    insert_message(tx, chat.id, chat.initial_system_message(), false, -1).await?;
+    for (position, message) in chat.messages.iter().enumerate() {
+        insert_message(tx, chat.id, message, false, position as i64).await?;
+    }
+
+    let offset = chat.messages.len();
+    for (position, message) in chat.erased_messages.iter().enumerate() {
+        insert_message(tx, chat.id, message, true, (offset + position) as i64).await?;
+    }
+
+    write_summary(tx, chat).await?;
+    insert_events(tx, dialect, chat).await?;
+    chat.version += 1;
+
+    Ok(())
+}
+
+// is_stored tells whether the chat is stored under its tenant, which is the case when a
+// versioned update missed it because another save bumped its version first
+async fn is_stored(tx: &mut Transaction<'_, Any>, chat: &Chat) -> Result<bool, RepositoryError> {
+    let stored = sqlx::query("SELECT id FROM chats WHERE id = ? AND tenant_id = ?")
+        .bind(chat.id.to_string())
+        .bind(chat.tenant_id.to_string())
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    Ok(stored.is_some())
+}
+
+async fn insert_message(
+    tx: &mut Transaction<'_, Any>,
+    chat_id: Uuid,
+    message: &Message,
+    erased: bool,
+    position: i64,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        "INSERT INTO messages (id, chat_id, role, content, tokens, model, erased, position, \
+         created_at, tool_calls, tool_call_id, revision_of, attachments, candidates, \
+         interrupted, request_id, speaker, language) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(message.id.to_string())
+    .bind(chat_id.to_string())
+    .bind(message.role.to_string())
+    .bind(&message.content)
+    .bind(message.tokens as i64)
+    .bind(&message.model.name)
+    .bind(erased as i64)
+    .bind(position)
+    .bind(timestamp(message.created_at))
+    .bind(json(&message.tool_calls)?)
+    .bind(&message.tool_call_id)
+    .bind(message.revision_of.map(|id| id.to_string()))
+    .bind(json(&message.attachments)?)
+    .bind(json(&message.candidates)?)
+    .bind(message.interrupted as i64)
+    .bind(&message.request_id)
+    .bind(&message.speaker)
+    .bind(&message.language)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+// write_summary projects the chat to chat_summaries next to its events; the rest is copied from
+// the chats row just written, so the summary has its timestamps and the title a save kept, and
+// the row is replaced as mysql and sqlite spell upserts differently
+async fn write_summary(tx: &mut Transaction<'_, Any>, chat: &Chat) -> Result<(), RepositoryError> {
+    sqlx::query("DELETE FROM chat_summaries WHERE chat_id = ?")
+        .bind(chat.id.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query(
+        "INSERT INTO chat_summaries (chat_id, tenant_id, user_id, status, model, title, tags, \
+         metadata, token_usage, message_count, last_message_preview, created_at, updated_at) \
+         SELECT id, tenant_id, user_id, status, model, title, tags, metadata, token_usage, ?, ?, \
+         created_at, updated_at FROM chats WHERE id = ?",
+    )
+    .bind(chat.count_messages() as i64)
+    .bind(chat.last_message_preview())
+    .bind(chat.id.to_string())
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+// insert_events writes the events recorded by the chat to the outbox in the transaction
+// that saves it, events already there from an earlier save of the same chat are skipped
+async fn insert_events(
+    tx: &mut Transaction<'_, Any>,
+    dialect: Dialect,
+    chat: &Chat,
+) -> Result<(), RepositoryError> {
+    for event in chat.outbox_events() {
+        sqlx::query(&format!(
+            "{} outbox_events (id, tenant_id, user_id, chat_id, event_type, payload, \
+             occurred_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            dialect.insert_ignore()
+        ))
+        .bind(event.id.to_string())
+        .bind(event.tenant_id.to_string())
+        .bind(event.user_id.to_string())
+        .bind(event.chat_id.to_string())
+        .bind(event.event.name())
+        .bind(json(&event.event)?)
+        .bind(timestamp(event.occurred_at))
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+    }
+
+    Ok(())
+}
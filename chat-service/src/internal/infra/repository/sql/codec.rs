@@ -0,0 +1,172 @@
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::any::AnyRow;
+use sqlx::{Row, TypeInfo, ValueRef};
+use uuid::Uuid;
+
+use crate::internal::domain::repository::chat::RepositoryError;
+
+// The sql repositories go through the any driver, which only carries integers, floats, text
+// and bytes; these helpers store everything else as text and read it back
+
+pub(super) fn db_error(err: sqlx::Error) -> RepositoryError {
+    RepositoryError::Database(err.to_string())
+}
+
+fn decode_error(column: &str, err: impl std::fmt::Display) -> RepositoryError {
+    RepositoryError::Database(format!("column {}: {}", column, err))
+}
+
+// placeholders returns n comma separated parameters, for IN lists
+pub(super) fn placeholders(n: usize) -> String {
+    vec!["?"; n].join(", ")
+}
+
+// timestamp formats the instant with a fixed width so the text sorts in time order
+pub(super) fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+pub(super) fn date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+pub(super) fn json<T: Serialize + ?Sized>(value: &T) -> Result<String, RepositoryError> {
+    serde_json::to_string(value).map_err(|e| RepositoryError::Database(e.to_string()))
+}
+
+pub(super) fn get_integer(row: &AnyRow, column: &str) -> Result<i64, RepositoryError> {
+    row.try_get(column).map_err(db_error)
+}
+
+pub(super) fn get_optional_integer(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<i64>, RepositoryError> {
+    if is_null(row, column)? {
+        return Ok(None);
+    }
+
+    get_integer(row, column).map(Some)
+}
+
+pub(super) fn get_bytes(row: &AnyRow, column: &str) -> Result<Vec<u8>, RepositoryError> {
+    row.try_get(column).map_err(db_error)
+}
+
+pub(super) fn get_float(row: &AnyRow, column: &str) -> Result<f64, RepositoryError> {
+    row.try_get(column).map_err(db_error)
+}
+
+pub(super) fn get_optional_float(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<f64>, RepositoryError> {
+    if is_null(row, column)? {
+        return Ok(None);
+    }
+
+    get_float(row, column).map(Some)
+}
+
+// is_null tells nulls by the name of their type, the any driver reports neither the values nor
+// their types as null
+fn is_null(row: &AnyRow, column: &str) -> Result<bool, RepositoryError> {
+    Ok(row
+        .try_get_raw(column)
+        .map_err(db_error)?
+        .type_info()
+        .name()
+        == "NULL")
+}
+
+// get_optional_text also reads mysql TEXT columns, the any driver hands them over as bytes
+pub(super) fn get_optional_text(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<String>, RepositoryError> {
+    if is_null(row, column)? {
+        return Ok(None);
+    }
+
+    match row.try_get::<String, _>(column) {
+        Ok(text) => Ok(Some(text)),
+        Err(_) => {
+            let bytes: Vec<u8> = row.try_get(column).map_err(db_error)?;
+            String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|e| decode_error(column, e))
+        }
+    }
+}
+
+pub(super) fn get_text(row: &AnyRow, column: &str) -> Result<String, RepositoryError> {
+    get_optional_text(row, column)?.ok_or_else(|| decode_error(column, "unexpected null"))
+}
+
+pub(super) fn get_optional_uuid(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<Uuid>, RepositoryError> {
+    get_optional_text(row, column)?
+        .map(|id| Uuid::parse_str(&id).map_err(|e| decode_error(column, e)))
+        .transpose()
+}
+
+pub(super) fn get_uuid(row: &AnyRow, column: &str) -> Result<Uuid, RepositoryError> {
+    get_optional_uuid(row, column)?.ok_or_else(|| decode_error(column, "unexpected null"))
+}
+
+pub(super) fn get_optional_timestamp(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+    get_optional_text(row, column)?
+        .map(|at| {
+            DateTime::parse_from_rfc3339(&at)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(|e| decode_error(column, e))
+        })
+        .transpose()
+}
+
+pub(super) fn get_timestamp(row: &AnyRow, column: &str) -> Result<DateTime<Utc>, RepositoryError> {
+    get_optional_timestamp(row, column)?.ok_or_else(|| decode_error(column, "unexpected null"))
+}
+
+pub(super) fn get_date(row: &AnyRow, column: &str) -> Result<NaiveDate, RepositoryError> {
+    NaiveDate::parse_from_str(&get_text(row, column)?, "%Y-%m-%d")
+        .map_err(|e| decode_error(column, e))
+}
+
+pub(super) fn get_optional_json<T: DeserializeOwned>(
+    row: &AnyRow,
+    column: &str,
+) -> Result<Option<T>, RepositoryError> {
+    get_optional_text(row, column)?
+        .map(|value| serde_json::from_str(&value).map_err(|e| decode_error(column, e)))
+        .transpose()
+}
+
+pub(super) fn get_json<T: DeserializeOwned>(
+    row: &AnyRow,
+    column: &str,
+) -> Result<T, RepositoryError> {
+    get_optional_json(row, column)?.ok_or_else(|| decode_error(column, "unexpected null"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp() {
+        let at = DateTime::parse_from_rfc3339("2024-01-29T09:30:00.5+01:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(timestamp(at), "2024-01-29T08:30:00.500000Z");
+        assert_eq!(timestamp(at).len(), 27);
+        assert_eq!(placeholders(3), "?, ?, ?");
+    }
+}
@@ -0,0 +1,114 @@
+use crate::internal::infra::repository::driver::DatabaseDriver;
+
+// Dialect covers the statements mysql and sqlite spell differently, everything else the
+// sql repositories run is written once for both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Mysql,
+    Sqlite,
+}
+
+impl Dialect {
+    pub fn of(driver: DatabaseDriver) -> Option<Self> {
+        match driver {
+            DatabaseDriver::Mysql => Some(Dialect::Mysql),
+            DatabaseDriver::Sqlite => Some(Dialect::Sqlite),
+            DatabaseDriver::Postgres | DatabaseDriver::Memory => None,
+        }
+    }
+
+    // insert_ignore starts an insert that skips the rows conflicting with a stored one
+    pub fn insert_ignore(&self) -> &'static str {
+        match self {
+            Dialect::Mysql => "INSERT IGNORE INTO",
+            Dialect::Sqlite => "INSERT OR IGNORE INTO",
+        }
+    }
+
+    // add_on_conflict ends an insert so a row conflicting on the key adds the inserted values
+    // of the columns to the stored ones
+    pub fn add_on_conflict(&self, table: &str, key: &[&str], columns: &[&str]) -> String {
+        match self {
+            Dialect::Mysql => format!(
+                "ON DUPLICATE KEY UPDATE {}",
+                columns
+                    .iter()
+                    .map(|column| format!("{0} = {0} + VALUES({0})", column))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Dialect::Sqlite => format!(
+                "ON CONFLICT ({}) DO UPDATE SET {}",
+                key.join(", "),
+                columns
+                    .iter()
+                    .map(|column| format!("{0} = {1}.{0} + excluded.{0}", column, table))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    // integer casts the expression to a 64-bit integer, mysql sums integers as decimals
+    pub fn integer(&self, expression: &str) -> String {
+        match self {
+            Dialect::Mysql => format!("CAST({} AS SIGNED)", expression),
+            Dialect::Sqlite => format!("CAST({} AS INTEGER)", expression),
+        }
+    }
+
+    // replace_on_conflict ends an insert so a row conflicting on the key takes the inserted
+    // values of the columns
+    pub fn replace_on_conflict(&self, key: &[&str], columns: &[&str]) -> String {
+        match self {
+            Dialect::Mysql => format!(
+                "ON DUPLICATE KEY UPDATE {}",
+                columns
+                    .iter()
+                    .map(|column| format!("{0} = VALUES({0})", column))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Dialect::Sqlite => format!(
+                "ON CONFLICT ({}) DO UPDATE SET {}",
+                key.join(", "),
+                columns
+                    .iter()
+                    .map(|column| format!("{0} = excluded.{0}", column))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_on_conflict() {
+        assert_eq!(
+            Dialect::Mysql.add_on_conflict("usage", &["id"], &["requests", "cost"]),
+            "ON DUPLICATE KEY UPDATE requests = requests + VALUES(requests), \
+             cost = cost + VALUES(cost)"
+        );
+        assert_eq!(
+            Dialect::Sqlite.add_on_conflict("usage", &["id"], &["requests", "cost"]),
+            "ON CONFLICT (id) DO UPDATE SET requests = usage.requests + excluded.requests, \
+             cost = usage.cost + excluded.cost"
+        );
+    }
+
+    #[test]
+    fn test_replace_on_conflict() {
+        assert_eq!(
+            Dialect::Mysql.replace_on_conflict(&["id"], &["name", "model"]),
+            "ON DUPLICATE KEY UPDATE name = VALUES(name), model = VALUES(model)"
+        );
+        assert_eq!(
+            Dialect::Sqlite.replace_on_conflict(&["id"], &["name", "model"]),
+            "ON CONFLICT (id) DO UPDATE SET name = excluded.name, model = excluded.model"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::idempotency::IdempotencyRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::idempotency::IdempotencyRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_optional_json, get_text, get_timestamp, get_uuid, json, timestamp,
+};
+use crate::internal::infra::repository::sql::dialect::Dialect;
+
+pub struct SqlIdempotencyRepository {
+    pool: AnyPool,
+    dialect: Dialect,
+}
+
+impl SqlIdempotencyRepository {
+    pub fn new(pool: AnyPool, dialect: Dialect) -> Self {
+        Self { pool, dialect }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for SqlIdempotencyRepository {
+    #[instrument(skip_all, fields(user_id = %record.user_id))]
+    async fn reserve(
+        &self,
+        record: &IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys \
+             WHERE tenant_id = ? AND user_id = ? AND expires_at <= ?",
+        )
+        .bind(record.tenant_id.to_string())
+        .bind(record.user_id.to_string())
+        .bind(timestamp(record.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let inserted = sqlx::query(&format!(
+            "{} idempotency_keys \
+             (tenant_id, user_id, `key`, fingerprint, created_at, expires_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+            self.dialect.insert_ignore()
+        ))
+        .bind(record.tenant_id.to_string())
+        .bind(record.user_id.to_string())
+        .bind(&record.key)
+        .bind(&record.fingerprint)
+        .bind(timestamp(record.created_at))
+        .bind(timestamp(record.expires_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        if inserted.rows_affected() > 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            "SELECT tenant_id, user_id, `key`, fingerprint, response, created_at, expires_at \
+             FROM idempotency_keys WHERE tenant_id = ? AND user_id = ? AND `key` = ?",
+        )
+        .bind(record.tenant_id.to_string())
+        .bind(record.user_id.to_string())
+        .bind(&record.key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| record_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn complete(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET response = ? \
+             WHERE tenant_id = ? AND user_id = ? AND `key` = ?",
+        )
+        .bind(json(response)?)
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn release(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: &str,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE tenant_id = ? AND user_id = ? AND `key` = ?",
+        )
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn record_from_row(row: &AnyRow) -> Result<IdempotencyRecord, RepositoryError> {
+    Ok(IdempotencyRecord {
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        key: get_text(row, "key")?,
+        fingerprint: get_text(row, "fingerprint")?,
+        response: get_optional_json(row, "response")?,
+        created_at: get_timestamp(row, "created_at")?,
+        expires_at: get_timestamp(row, "expires_at")?,
+    })
+}
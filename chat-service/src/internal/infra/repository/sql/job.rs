@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_optional_text, get_optional_timestamp, get_optional_uuid, get_text,
+    get_timestamp, get_uuid, json, timestamp,
+};
+
+const SELECT_JOBS: &str = "SELECT id, tenant_id, user_id, chat_id, user_message, attachments, \
+                           overrides, status, content, error, created_at, completed_at, \
+                           claimed_until FROM completion_jobs";
+
+// CLAIMABLE selects the queued jobs and the running ones whose claim was over before the
+// bound instant
+const CLAIMABLE: &str = "(status = 'queued' OR (status = 'running' \
+                         AND (claimed_until IS NULL OR claimed_until < ?)))";
+
+pub struct SqlJobRepository {
+    pool: AnyPool,
+}
+
+impl SqlJobRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JobRepository for SqlJobRepository {
+    #[instrument(skip_all, fields(job_id = %job.id))]
+    async fn create_job(&self, job: &Job) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO completion_jobs \
+             (id, tenant_id, user_id, chat_id, user_message, attachments, overrides, status, \
+              content, error, created_at, completed_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(job.id.to_string())
+        .bind(job.tenant_id.to_string())
+        .bind(job.user_id.to_string())
+        .bind(job.chat_id.map(|id| id.to_string()))
+        .bind(&job.user_message)
+        .bind(json(&job.attachments)?)
+        .bind(json(&job.overrides)?)
+        .bind(job.status.to_string())
+        .bind(&job.content)
+        .bind(&job.error)
+        .bind(timestamp(job.created_at))
+        .bind(job.completed_at.map(timestamp))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn find_job_by_id(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<Job>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = ? AND id = ?", SELECT_JOBS))
+            .bind(tenant_id.to_string())
+            .bind(job_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| job_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all)]
+    async fn list_claimable(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<Job>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE {} ORDER BY created_at, id LIMIT ?",
+            SELECT_JOBS, CLAIMABLE
+        ))
+        .bind(timestamp(now))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(job_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(job_id = %job_id))]
+    async fn claim_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(&format!(
+            "UPDATE completion_jobs SET status = 'running', claimed_until = ? \
+             WHERE tenant_id = ? AND id = ? AND {}",
+            CLAIMABLE
+        ))
+        .bind(timestamp(claimed_until))
+        .bind(tenant_id.to_string())
+        .bind(job_id.to_string())
+        .bind(timestamp(now))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(job_id = %job.id))]
+    async fn complete_job(
+        &self,
+        job: &Job,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE completion_jobs \
+             SET status = ?, chat_id = ?, content = ?, error = ?, completed_at = ?, \
+             claimed_until = NULL WHERE tenant_id = ? AND id = ? \
+             AND status = 'running' AND claimed_until = ?",
+        )
+        .bind(job.status.to_string())
+        .bind(job.chat_id.map(|id| id.to_string()))
+        .bind(&job.content)
+        .bind(&job.error)
+        .bind(job.completed_at.map(timestamp))
+        .bind(job.tenant_id.to_string())
+        .bind(job.id.to_string())
+        .bind(timestamp(claimed_until))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+fn job_from_row(row: &AnyRow) -> Result<Job, RepositoryError> {
+    Ok(Job {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        chat_id: get_optional_uuid(row, "chat_id")?,
+        user_message: get_text(row, "user_message")?,
+        attachments: get_json(row, "attachments")?,
+        overrides: get_json(row, "overrides")?,
+        status: get_text(row, "status")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        content: get_optional_text(row, "content")?,
+        error: get_optional_text(row, "error")?,
+        created_at: get_timestamp(row, "created_at")?,
+        completed_at: get_optional_timestamp(row, "completed_at")?,
+        claimed_until: get_optional_timestamp(row, "claimed_until")?,
+    })
+}
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::moderation::ModerationFlag;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::moderation::ModerationRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_optional_uuid, get_text, get_timestamp, get_uuid, json, timestamp,
+};
+
+pub struct SqlModerationRepository {
+    pool: AnyPool,
+}
+
+impl SqlModerationRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ModerationRepository for SqlModerationRepository {
+    #[instrument(skip_all, fields(user_id = %flag.user_id))]
+    async fn record_flag(&self, flag: &ModerationFlag) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO moderation_flags (id, user_id, chat_id, content, categories, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(flag.id.to_string())
+        .bind(flag.user_id.to_string())
+        .bind(flag.chat_id.map(|id| id.to_string()))
+        .bind(&flag.content)
+        .bind(json(&flag.categories)?)
+        .bind(timestamp(flag.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_flags_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ModerationFlag>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, chat_id, content, categories, created_at FROM moderation_flags \
+             WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ModerationFlag {
+                    id: get_uuid(row, "id")?,
+                    user_id: get_uuid(row, "user_id")?,
+                    chat_id: get_optional_uuid(row, "chat_id")?,
+                    content: get_text(row, "content")?,
+                    categories: get_json(row, "categories")?,
+                    created_at: get_timestamp(row, "created_at")?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::event::OutboxEvent;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::outbox::OutboxRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_timestamp, get_uuid, placeholders, timestamp,
+};
+
+pub struct SqlOutboxRepository {
+    pool: AnyPool,
+}
+
+impl SqlOutboxRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for SqlOutboxRepository {
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn unpublished_events(&self, limit: usize) -> Result<Vec<OutboxEvent>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, payload, occurred_at FROM outbox_events \
+             WHERE published_at IS NULL ORDER BY occurred_at, id LIMIT ?",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(event_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(events = ids.len()))]
+    async fn mark_published(
+        &self,
+        ids: &[Uuid],
+        published_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), RepositoryError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let sql = format!(
+            "UPDATE outbox_events SET published_at = ? \
+             WHERE id IN ({}) AND published_at IS NULL",
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query(&sql).bind(timestamp(published_at));
+        for id in ids {
+            query = query.bind(id.to_string());
+        }
+        query.execute(&self.pool).await.map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn event_from_row(row: &AnyRow) -> Result<OutboxEvent, RepositoryError> {
+    Ok(OutboxEvent {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        chat_id: get_uuid(row, "chat_id")?,
+        event: get_json(row, "payload")?,
+        occurred_at: get_timestamp(row, "occurred_at")?,
+    })
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_text, get_timestamp, get_uuid, timestamp,
+};
+
+pub struct SqlPromptTemplateRepository {
+    pool: AnyPool,
+}
+
+impl SqlPromptTemplateRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PromptTemplateRepository for SqlPromptTemplateRepository {
+    #[instrument(skip_all, fields(template = %template.name))]
+    async fn create_template(&self, template: &PromptTemplate) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO prompt_templates (id, name, content, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(template.id.to_string())
+        .bind(&template.name)
+        .bind(&template.content)
+        .bind(timestamp(template.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(template = %name))]
+    async fn find_template_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<PromptTemplate>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, name, content, created_at FROM prompt_templates WHERE name = ?",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| template_from_row(&row)).transpose()
+    }
+}
+
+fn template_from_row(row: &AnyRow) -> Result<PromptTemplate, RepositoryError> {
+    Ok(PromptTemplate {
+        id: get_uuid(row, "id")?,
+        name: get_text(row, "name")?,
+        content: get_text(row, "content")?,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::redaction::RedactionRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::redaction::RedactionRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_bytes, get_json, get_optional_uuid, get_timestamp, get_uuid, json, timestamp,
+};
+
+pub struct SqlRedactionRepository {
+    pool: AnyPool,
+}
+
+impl SqlRedactionRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RedactionRepository for SqlRedactionRepository {
+    #[instrument(skip_all, fields(message_id = %record.message_id))]
+    async fn record_redaction(&self, record: &RedactionRecord) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO redactions \
+             (id, tenant_id, user_id, chat_id, message_id, findings, ciphertext, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(record.id.to_string())
+        .bind(record.tenant_id.to_string())
+        .bind(record.user_id.to_string())
+        .bind(record.chat_id.map(|id| id.to_string()))
+        .bind(record.message_id.to_string())
+        .bind(json(&record.findings)?)
+        .bind(record.ciphertext.clone())
+        .bind(timestamp(record.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(message_id = %message_id))]
+    async fn find_redaction(
+        &self,
+        tenant_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<Option<RedactionRecord>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, message_id, findings, ciphertext, created_at \
+             FROM redactions WHERE tenant_id = ? AND message_id = ?",
+        )
+        .bind(tenant_id.to_string())
+        .bind(message_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| {
+            Ok(RedactionRecord {
+                id: get_uuid(&row, "id")?,
+                tenant_id: get_uuid(&row, "tenant_id")?,
+                user_id: get_uuid(&row, "user_id")?,
+                chat_id: get_optional_uuid(&row, "chat_id")?,
+                message_id: get_uuid(&row, "message_id")?,
+                findings: get_json(&row, "findings")?,
+                ciphertext: get_bytes(&row, "ciphertext")?,
+                created_at: get_timestamp(&row, "created_at")?,
+            })
+        })
+        .transpose()
+    }
+}
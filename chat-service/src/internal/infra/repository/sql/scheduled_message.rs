@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_optional_text, get_optional_timestamp, get_text, get_timestamp, get_uuid,
+    timestamp,
+};
+
+const SELECT_SCHEDULED: &str =
+    "SELECT id, tenant_id, user_id, chat_id, content, send_at, status, error, created_at, \
+     claimed_until FROM scheduled_messages";
+
+// DUE selects the pending messages whose send_at is not after the bound instant and the sending ones whose
+// claim was over before it
+const DUE: &str = "((status = 'pending' AND send_at <= ?) OR (status = 'sending' \
+                   AND (claimed_until IS NULL OR claimed_until < ?)))";
+
+pub struct SqlScheduledMessageRepository {
+    pool: AnyPool,
+}
+
+impl SqlScheduledMessageRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduledMessageRepository for SqlScheduledMessageRepository {
+    #[instrument(skip_all, fields(scheduled_id = %scheduled.id))]
+    async fn create_scheduled_message(
+        &self,
+        scheduled: &ScheduledMessage,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO scheduled_messages \
+             (id, tenant_id, user_id, chat_id, content, send_at, status, error, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(scheduled.id.to_string())
+        .bind(scheduled.tenant_id.to_string())
+        .bind(scheduled.user_id.to_string())
+        .bind(scheduled.chat_id.to_string())
+        .bind(&scheduled.content)
+        .bind(timestamp(scheduled.send_at))
+        .bind(scheduled.status.to_string())
+        .bind(scheduled.error.clone())
+        .bind(timestamp(scheduled.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id))]
+    async fn find_scheduled_message_by_id(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+    ) -> Result<Option<ScheduledMessage>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = ? AND id = ?",
+            SELECT_SCHEDULED
+        ))
+        .bind(tenant_id.to_string())
+        .bind(scheduled_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| scheduled_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_pending_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = ? AND user_id = ? AND status = 'pending' \
+             ORDER BY send_at, id",
+            SELECT_SCHEDULED
+        ))
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(scheduled_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn list_due(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<ScheduledMessage>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE {} ORDER BY send_at, id LIMIT ?",
+            SELECT_SCHEDULED, DUE
+        ))
+        .bind(timestamp(now))
+        .bind(timestamp(now))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(scheduled_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id))]
+    async fn claim_due(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(&format!(
+            "UPDATE scheduled_messages SET status = 'sending', claimed_until = ? \
+             WHERE tenant_id = ? AND id = ? AND {}",
+            DUE
+        ))
+        .bind(timestamp(claimed_until))
+        .bind(tenant_id.to_string())
+        .bind(scheduled_id.to_string())
+        .bind(timestamp(now))
+        .bind(timestamp(now))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(scheduled_id = %scheduled_id, from = %from, to = %to))]
+    async fn transition(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        from: ScheduledStatus,
+        to: ScheduledStatus,
+        error: Option<&str>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE scheduled_messages SET status = ?, error = ?, claimed_until = NULL \
+             WHERE tenant_id = ? AND id = ? AND status = ?",
+        )
+        .bind(to.to_string())
+        .bind(error.map(str::to_string))
+        .bind(tenant_id.to_string())
+        .bind(scheduled_id.to_string())
+        .bind(from.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+fn scheduled_from_row(row: &AnyRow) -> Result<ScheduledMessage, RepositoryError> {
+    Ok(ScheduledMessage {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        chat_id: get_uuid(row, "chat_id")?,
+        content: get_text(row, "content")?,
+        send_at: get_timestamp(row, "send_at")?,
+        status: get_text(row, "status")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        error: get_optional_text(row, "error")?,
+        created_at: get_timestamp(row, "created_at")?,
+        claimed_until: get_optional_timestamp(row, "claimed_until")?,
+    })
+}
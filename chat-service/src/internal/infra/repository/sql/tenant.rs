@@ -0,0 +1,140 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::tenant::{Tenant, TenantConfig};
+use crate::internal::domain::quota::QuotaConfig;
+use crate::internal::domain::rate_limiter::RateLimitConfig;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::tenant::TenantRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_json, get_optional_integer, get_optional_json, get_optional_text, get_text,
+    get_uuid, json, placeholders,
+};
+use crate::internal::infra::repository::sql::dialect::Dialect;
+
+const COLUMNS: [&str; 11] = [
+    "name",
+    "model",
+    "model_max_tokens",
+    "requests_per_minute",
+    "tokens_per_minute",
+    "allowed_models",
+    "user_daily_tokens",
+    "user_monthly_tokens",
+    "tenant_daily_tokens",
+    "tenant_monthly_tokens",
+    "output_filter",
+];
+
+pub struct SqlTenantRepository {
+    pool: AnyPool,
+    dialect: Dialect,
+}
+
+impl SqlTenantRepository {
+    pub fn new(pool: AnyPool, dialect: Dialect) -> Self {
+        Self { pool, dialect }
+    }
+}
+
+#[async_trait]
+impl TenantRepository for SqlTenantRepository {
+    #[instrument(skip_all, fields(tenant_id = %tenant.id))]
+    async fn save_tenant(&self, tenant: &Tenant) -> Result<(), RepositoryError> {
+        let config = &tenant.config;
+        let sql = format!(
+            "INSERT INTO tenants (id, {}) VALUES (?, {}) {}",
+            COLUMNS.join(", "),
+            placeholders(COLUMNS.len()),
+            self.dialect.replace_on_conflict(&["id"], &COLUMNS)
+        );
+        sqlx::query(&sql)
+            .bind(tenant.id.to_string())
+            .bind(&tenant.name)
+            .bind(config.model.as_ref().map(|model| model.name.clone()))
+            .bind(config.model.as_ref().map(|model| model.max_tokens as i64))
+            .bind(
+                config
+                    .rate_limit
+                    .map(|limit| limit.requests_per_minute as i64),
+            )
+            .bind(
+                config
+                    .rate_limit
+                    .map(|limit| limit.tokens_per_minute as i64),
+            )
+            .bind(json(&config.allowed_models)?)
+            .bind(config.quota.map(|quota| quota.user_daily_tokens as i64))
+            .bind(config.quota.map(|quota| quota.user_monthly_tokens as i64))
+            .bind(config.quota.map(|quota| quota.tenant_daily_tokens as i64))
+            .bind(config.quota.map(|quota| quota.tenant_monthly_tokens as i64))
+            .bind(config.output_filter.as_ref().map(json).transpose()?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn list_tenants(&self) -> Result<Vec<Tenant>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, name, model, model_max_tokens, requests_per_minute, tokens_per_minute, \
+             allowed_models, user_daily_tokens, user_monthly_tokens, tenant_daily_tokens, \
+             tenant_monthly_tokens, output_filter FROM tenants ORDER BY name, id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(tenant_from_row).collect()
+    }
+}
+
+fn tenant_from_row(row: &AnyRow) -> Result<Tenant, RepositoryError> {
+    let model =
+        get_optional_text(row, "model")?.zip(get_optional_integer(row, "model_max_tokens")?);
+    let rate_limit = get_optional_integer(row, "requests_per_minute")?
+        .zip(get_optional_integer(row, "tokens_per_minute")?);
+    // the quota columns are written together, all null when the defaults apply
+    let quota = [
+        get_optional_integer(row, "user_daily_tokens")?,
+        get_optional_integer(row, "user_monthly_tokens")?,
+        get_optional_integer(row, "tenant_daily_tokens")?,
+        get_optional_integer(row, "tenant_monthly_tokens")?,
+    ];
+
+    Ok(Tenant::new(
+        get_uuid(row, "id")?,
+        &get_text(row, "name")?,
+        TenantConfig {
+            model: model.map(|(name, max_tokens)| Model::new(name, max_tokens as u32)),
+            rate_limit: rate_limit.map(|(requests_per_minute, tokens_per_minute)| {
+                RateLimitConfig {
+                    requests_per_minute: requests_per_minute as u32,
+                    tokens_per_minute: tokens_per_minute as u32,
+                }
+            }),
+            allowed_models: get_json(row, "allowed_models")?,
+            quota: quota_from_columns(quota),
+            output_filter: get_optional_json(row, "output_filter")?,
+        },
+    ))
+}
+
+fn quota_from_columns(columns: [Option<i64>; 4]) -> Option<QuotaConfig> {
+    match columns {
+        [Some(user_daily), Some(user_monthly), Some(tenant_daily), Some(tenant_monthly)] => {
+            Some(QuotaConfig {
+                user_daily_tokens: user_daily as u64,
+                user_monthly_tokens: user_monthly as u64,
+                tenant_daily_tokens: tenant_daily as u64,
+                tenant_monthly_tokens: tenant_monthly as u64,
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use sqlx::{Any, AnyPool, Transaction};
+use tracing::instrument;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::unit_of_work::{ChatTransaction, UnitOfWork};
+use crate::internal::infra::repository::sql::chat::{insert_chat, update_chat};
+use crate::internal::infra::repository::sql::codec::db_error;
+use crate::internal::infra::repository::sql::dialect::Dialect;
+use crate::internal::infra::repository::sql::usage::add_usage;
+
+pub struct SqlUnitOfWork {
+    pool: AnyPool,
+    dialect: Dialect,
+}
+
+impl SqlUnitOfWork {
+    pub fn new(pool: AnyPool, dialect: Dialect) -> Self {
+        Self { pool, dialect }
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for SqlUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn ChatTransaction>, RepositoryError> {
+        let tx = self.pool.begin().await.map_err(db_error)?;
+
+        Ok(Box::new(SqlChatTransaction {
+            tx,
+            dialect: self.dialect,
+        }))
+    }
+}
+
+// SqlChatTransaction holds its connection until commit, sqlx rolls the transaction back
+// when it is dropped
+struct SqlChatTransaction {
+    tx: Transaction<'static, Any>,
+    dialect: Dialect,
+}
+
+#[async_trait]
+impl ChatTransaction for SqlChatTransaction {
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn create_chat(&mut self, chat: &Chat) -> Result<(), RepositoryError> {
+        insert_chat(&mut self.tx, self.dialect, chat).await
+    }
+
+    #[instrument(skip_all, fields(chat_id = %chat.id))]
+    async fn save_chat(&mut self, chat: &mut Chat) -> Result<(), RepositoryError> {
+        update_chat(&mut self.tx, self.dialect, chat).await
+    }
+
+    #[instrument(skip_all, fields(user_id = %record.user_id, chat_id = %record.chat_id))]
+    async fn record_usage(&mut self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        add_usage(&mut self.tx, self.dialect, record).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RepositoryError> {
+        self.tx.commit().await.map_err(db_error)
+    }
+}
@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use sqlx::{Any, AnyPool, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::{
+    ChatUsage, DailyUsage, TenantUsage, UsageRecord, UserUsage,
+};
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::infra::repository::sql::codec::{
+    date, db_error, get_date, get_float, get_integer, get_text, get_uuid, placeholders,
+};
+use crate::internal::infra::repository::sql::dialect::Dialect;
+
+const COUNTERS: [&str; 4] = ["requests", "prompt_tokens", "completion_tokens", "cost"];
+
+pub struct SqlUsageRepository {
+    pool: AnyPool,
+    dialect: Dialect,
+}
+
+impl SqlUsageRepository {
+    pub fn new(pool: AnyPool, dialect: Dialect) -> Self {
+        Self { pool, dialect }
+    }
+
+    // sums selects the totals of the counters, cast back to integers where the database sums
+    // them as decimals
+    fn sums(&self) -> String {
+        ["requests", "prompt_tokens", "completion_tokens"]
+            .iter()
+            .map(|column| {
+                format!(
+                    "{} AS {}",
+                    self.dialect.integer(&format!("SUM({})", column)),
+                    column
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[async_trait]
+impl UsageRepository for SqlUsageRepository {
+    #[instrument(skip_all, fields(user_id = %record.user_id, chat_id = %record.chat_id))]
+    async fn record_usage(&self, record: &UsageRecord) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        add_usage(&mut tx, self.dialect, record).await?;
+        tx.commit().await.map_err(db_error)
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_daily_usage(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<DailyUsage>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT user_id, date, model, requests, prompt_tokens, completion_tokens, cost \
+             FROM usage_daily WHERE tenant_id = ? AND user_id = ? AND date BETWEEN ? AND ? \
+             ORDER BY date, model",
+        )
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .bind(date(from))
+        .bind(date(to))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DailyUsage {
+                    user_id: get_uuid(row, "user_id")?,
+                    date: get_date(row, "date")?,
+                    model: get_text(row, "model")?,
+                    requests: get_integer(row, "requests")? as u64,
+                    prompt_tokens: get_integer(row, "prompt_tokens")? as u64,
+                    completion_tokens: get_integer(row, "completion_tokens")? as u64,
+                    cost: get_float(row, "cost")?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(chats = chat_ids.len()))]
+    async fn list_chat_usage(
+        &self,
+        tenant_id: Uuid,
+        chat_ids: &[Uuid],
+    ) -> Result<Vec<ChatUsage>, RepositoryError> {
+        if chat_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let sql = format!(
+            "SELECT chat_id, requests, prompt_tokens, completion_tokens, cost \
+             FROM chat_usage WHERE tenant_id = ? AND chat_id IN ({})",
+            placeholders(chat_ids.len())
+        );
+        let mut query = sqlx::query(&sql).bind(tenant_id.to_string());
+        for chat_id in chat_ids {
+            query = query.bind(chat_id.to_string());
+        }
+        let rows = query.fetch_all(&self.pool).await.map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ChatUsage {
+                    chat_id: get_uuid(row, "chat_id")?,
+                    requests: get_integer(row, "requests")? as u64,
+                    prompt_tokens: get_integer(row, "prompt_tokens")? as u64,
+                    completion_tokens: get_integer(row, "completion_tokens")? as u64,
+                    cost: get_float(row, "cost")?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    async fn summarize_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<TenantUsage>, RepositoryError> {
+        let sums = self.sums();
+        let sql = format!(
+            "SELECT tenant_id, model, COUNT(DISTINCT user_id) AS users, {}, SUM(cost) AS cost \
+             FROM usage_daily WHERE date BETWEEN ? AND ? {} \
+             GROUP BY tenant_id, model ORDER BY tenant_id, model",
+            sums,
+            if tenant_id.is_some() {
+                "AND tenant_id = ?"
+            } else {
+                ""
+            }
+        );
+        let mut query = sqlx::query(&sql).bind(date(from)).bind(date(to));
+        if let Some(tenant_id) = tenant_id {
+            query = query.bind(tenant_id.to_string());
+        }
+        let rows = query.fetch_all(&self.pool).await.map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(TenantUsage {
+                    tenant_id: get_uuid(row, "tenant_id")?,
+                    model: get_text(row, "model")?,
+                    users: get_integer(row, "users")? as u64,
+                    requests: get_integer(row, "requests")? as u64,
+                    prompt_tokens: get_integer(row, "prompt_tokens")? as u64,
+                    completion_tokens: get_integer(row, "completion_tokens")? as u64,
+                    cost: get_float(row, "cost")?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    async fn summarize_user_usage(
+        &self,
+        tenant_id: Option<Uuid>,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+    ) -> Result<Vec<UserUsage>, RepositoryError> {
+        let sql = format!(
+            "SELECT tenant_id, user_id, model, {}, SUM(cost) AS cost \
+             FROM usage_daily WHERE date BETWEEN ? AND ? {} \
+             GROUP BY tenant_id, user_id, model ORDER BY tenant_id, user_id, model",
+            self.sums(),
+            if tenant_id.is_some() {
+                "AND tenant_id = ?"
+            } else {
+                ""
+            }
+        );
+        let mut query = sqlx::query(&sql).bind(date(from)).bind(date(to));
+        if let Some(tenant_id) = tenant_id {
+            query = query.bind(tenant_id.to_string());
+        }
+        let rows = query.fetch_all(&self.pool).await.map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(UserUsage {
+                    tenant_id: get_uuid(row, "tenant_id")?,
+                    user_id: get_uuid(row, "user_id")?,
+                    model: get_text(row, "model")?,
+                    requests: get_integer(row, "requests")? as u64,
+                    prompt_tokens: get_integer(row, "prompt_tokens")? as u64,
+                    completion_tokens: get_integer(row, "completion_tokens")? as u64,
+                    cost: get_float(row, "cost")?,
+                })
+            })
+            .collect()
+    }
+}
+
+// add_usage adds the record to the aggregates of its day and chat in the transaction
+pub(super) async fn add_usage(
+    tx: &mut Transaction<'_, Any>,
+    dialect: Dialect,
+    record: &UsageRecord,
+) -> Result<(), RepositoryError> {
+    sqlx::query(&format!(
+        "INSERT INTO usage_daily (tenant_id, user_id, date, model, requests, prompt_tokens, \
+         completion_tokens, cost) VALUES (?, ?, ?, ?, 1, ?, ?, ?) {}",
+        dialect.add_on_conflict("usage_daily", &["user_id", "date", "model"], &COUNTERS)
+    ))
+    .bind(record.tenant_id.to_string())
+    .bind(record.user_id.to_string())
+    .bind(date(record.date()))
+    .bind(&record.model)
+    .bind(record.prompt_tokens as i64)
+    .bind(record.completion_tokens as i64)
+    .bind(record.cost)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    sqlx::query(&format!(
+        "INSERT INTO chat_usage (tenant_id, chat_id, requests, prompt_tokens, \
+         completion_tokens, cost) VALUES (?, ?, 1, ?, ?, ?) {}",
+        dialect.add_on_conflict("chat_usage", &["chat_id"], &COUNTERS)
+    ))
+    .bind(record.tenant_id.to_string())
+    .bind(record.chat_id.to_string())
+    .bind(record.prompt_tokens as i64)
+    .bind(record.completion_tokens as i64)
+    .bind(record.cost)
+    .execute(&mut **tx)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
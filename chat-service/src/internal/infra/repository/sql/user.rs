@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_text, get_timestamp, get_uuid, timestamp,
+};
+
+const SELECT_USER: &str = "SELECT id, tenant_id, external_id, display_name, created_at FROM users";
+
+pub struct SqlUserRepository {
+    pool: AnyPool,
+}
+
+impl SqlUserRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqlUserRepository {
+    #[instrument(skip_all, fields(user_id = %user.id))]
+    async fn create_user(&self, user: &User) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO users (id, tenant_id, external_id, display_name, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(user.id.to_string())
+        .bind(user.tenant_id.to_string())
+        .bind(&user.external_id)
+        .bind(&user.display_name)
+        .bind(timestamp(user.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(user_id = %id))]
+    async fn find_user_by_id(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<User>, RepositoryError> {
+        let row = sqlx::query(&format!("{} WHERE tenant_id = ? AND id = ?", SELECT_USER))
+            .bind(tenant_id.to_string())
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| user_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all)]
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: Uuid,
+        external_id: &str,
+    ) -> Result<Option<User>, RepositoryError> {
+        let row = sqlx::query(&format!(
+            "{} WHERE tenant_id = ? AND external_id = ?",
+            SELECT_USER
+        ))
+        .bind(tenant_id.to_string())
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| user_from_row(&row)).transpose()
+    }
+}
+
+fn user_from_row(row: &AnyRow) -> Result<User, RepositoryError> {
+    Ok(User {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        external_id: get_text(row, "external_id")?,
+        display_name: get_text(row, "display_name")?,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
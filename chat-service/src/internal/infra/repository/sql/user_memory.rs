@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user_memory::Memory;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_text, get_timestamp, get_uuid, timestamp,
+};
+
+pub struct SqlMemoryRepository {
+    pool: AnyPool,
+}
+
+impl SqlMemoryRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MemoryRepository for SqlMemoryRepository {
+    #[instrument(skip_all, fields(count = memories.len()))]
+    async fn create_memories(
+        &self,
+        memories: &[Memory],
+        max_memories: usize,
+    ) -> Result<usize, RepositoryError> {
+        let max_memories = i64::try_from(max_memories).unwrap_or(i64::MAX);
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let mut created = 0;
+        for memory in memories {
+            // the count is read from a derived table, mysql does not let the subquery of an
+            // insert read its own table otherwise
+            let result = sqlx::query(
+                "INSERT INTO memories (id, tenant_id, user_id, chat_id, content, created_at) \
+                 SELECT ?, ?, ?, ?, ?, ? FROM \
+                 (SELECT COUNT(*) AS stored FROM memories WHERE tenant_id = ? AND user_id = ?) known \
+                 WHERE known.stored < ?",
+            )
+            .bind(memory.id.to_string())
+            .bind(memory.tenant_id.to_string())
+            .bind(memory.user_id.to_string())
+            .bind(memory.chat_id.to_string())
+            .bind(&memory.content)
+            .bind(timestamp(memory.created_at))
+            .bind(memory.tenant_id.to_string())
+            .bind(memory.user_id.to_string())
+            .bind(max_memories)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+            created += result.rows_affected() as usize;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(created)
+    }
+
+    #[instrument(skip_all, fields(memory_id = %memory_id))]
+    async fn find_memory_by_id(
+        &self,
+        tenant_id: Uuid,
+        memory_id: Uuid,
+    ) -> Result<Option<Memory>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, content, created_at \
+             FROM memories WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id.to_string())
+        .bind(memory_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| memory_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(user_id = %user_id))]
+    async fn list_memories_by_user(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<Memory>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, user_id, chat_id, content, created_at \
+             FROM memories WHERE tenant_id = ? AND user_id = ? \
+             ORDER BY created_at, id",
+        )
+        .bind(tenant_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(memory_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(memory_id = %memory_id))]
+    async fn delete_memory(&self, tenant_id: Uuid, memory_id: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM memories WHERE tenant_id = ? AND id = ?")
+            .bind(tenant_id.to_string())
+            .bind(memory_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+fn memory_from_row(row: &AnyRow) -> Result<Memory, RepositoryError> {
+    Ok(Memory {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        user_id: get_uuid(row, "user_id")?,
+        chat_id: get_uuid(row, "chat_id")?,
+        content: get_text(row, "content")?,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
@@ -0,0 +1,293 @@
+use async_trait::async_trait;
+use sqlx::any::AnyRow;
+use sqlx::AnyPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, Webhook, WebhookDelivery};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::infra::repository::sql::codec::{
+    db_error, get_integer, get_json, get_optional_text, get_text, get_timestamp, get_uuid, json,
+    timestamp,
+};
+
+const DELIVERY_COLUMNS: &str = "id, tenant_id, webhook_id, event_id, event, payload, attempts, \
+                                next_attempt_at, last_error, created_at";
+
+pub struct SqlWebhookRepository {
+    pool: AnyPool,
+}
+
+impl SqlWebhookRepository {
+    pub fn new(pool: AnyPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqlWebhookRepository {
+    #[instrument(skip_all, fields(webhook_id = %webhook.id))]
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO webhooks (id, tenant_id, url, secret, events, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(webhook.id.to_string())
+        .bind(webhook.tenant_id.to_string())
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(json(&webhook.events)?)
+        .bind(timestamp(webhook.created_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(webhook_id = %webhook_id))]
+    async fn find_webhook_by_id(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<Option<Webhook>, RepositoryError> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, url, secret, events, created_at \
+             FROM webhooks WHERE tenant_id = ? AND id = ?",
+        )
+        .bind(tenant_id.to_string())
+        .bind(webhook_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| webhook_from_row(&row)).transpose()
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_webhooks(&self, tenant_id: Uuid) -> Result<Vec<Webhook>, RepositoryError> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, url, secret, events, created_at \
+             FROM webhooks WHERE tenant_id = ? ORDER BY created_at, id",
+        )
+        .bind(tenant_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(webhook_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(webhook_id = %webhook_id))]
+    async fn delete_webhook(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE tenant_id = ? AND webhook_id = ?")
+            .bind(tenant_id.to_string())
+            .bind(webhook_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query("DELETE FROM webhooks WHERE tenant_id = ? AND id = ?")
+            .bind(tenant_id.to_string())
+            .bind(webhook_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(count = deliveries.len()))]
+    async fn enqueue_deliveries(
+        &self,
+        deliveries: &[WebhookDelivery],
+    ) -> Result<(), RepositoryError> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for delivery in deliveries {
+            sqlx::query(&format!(
+                "INSERT INTO webhook_deliveries ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                DELIVERY_COLUMNS
+            ))
+            .bind(delivery.id.to_string())
+            .bind(delivery.tenant_id.to_string())
+            .bind(delivery.webhook_id.to_string())
+            .bind(delivery.event_id.to_string())
+            .bind(delivery.event.to_string())
+            .bind(&delivery.payload)
+            .bind(delivery.attempts as i64)
+            .bind(timestamp(delivery.next_attempt_at))
+            .bind(delivery.last_error.clone())
+            .bind(timestamp(delivery.created_at))
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(limit = limit))]
+    async fn due_deliveries(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE next_attempt_at <= ? \
+             ORDER BY next_attempt_at, id LIMIT ?",
+            DELIVERY_COLUMNS
+        ))
+        .bind(timestamp(now))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery_id))]
+    async fn claim_delivery(
+        &self,
+        delivery_id: Uuid,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        lease_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query(
+            "UPDATE webhook_deliveries SET next_attempt_at = ? \
+             WHERE id = ? AND next_attempt_at = ?",
+        )
+        .bind(timestamp(lease_until))
+        .bind(delivery_id.to_string())
+        .bind(timestamp(next_attempt_at))
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery.id))]
+    async fn reschedule_delivery(&self, delivery: &WebhookDelivery) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE webhook_deliveries SET attempts = ?, next_attempt_at = ?, last_error = ? \
+             WHERE id = ?",
+        )
+        .bind(delivery.attempts as i64)
+        .bind(timestamp(delivery.next_attempt_at))
+        .bind(delivery.last_error.clone())
+        .bind(delivery.id.to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %delivery_id))]
+    async fn complete_delivery(&self, delivery_id: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = ?")
+            .bind(delivery_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(delivery_id = %dead_letter.delivery.id))]
+    async fn dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), RepositoryError> {
+        let delivery = &dead_letter.delivery;
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query(&format!(
+            "INSERT INTO webhook_dead_letters ({}, failed_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery.id.to_string())
+        .bind(delivery.tenant_id.to_string())
+        .bind(delivery.webhook_id.to_string())
+        .bind(delivery.event_id.to_string())
+        .bind(delivery.event.to_string())
+        .bind(&delivery.payload)
+        .bind(delivery.attempts as i64)
+        .bind(timestamp(delivery.next_attempt_at))
+        .bind(delivery.last_error.clone())
+        .bind(timestamp(delivery.created_at))
+        .bind(timestamp(dead_letter.failed_at))
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = ?")
+            .bind(delivery.id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(tenant_id = %tenant_id))]
+    async fn list_dead_letters(
+        &self,
+        tenant_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, failed_at FROM webhook_dead_letters WHERE tenant_id = ? \
+             ORDER BY failed_at DESC, id DESC LIMIT ?",
+            DELIVERY_COLUMNS
+        ))
+        .bind(tenant_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DeadLetter {
+                    delivery: delivery_from_row(row)?,
+                    failed_at: get_timestamp(row, "failed_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn webhook_from_row(row: &AnyRow) -> Result<Webhook, RepositoryError> {
+    Ok(Webhook {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        url: get_text(row, "url")?,
+        secret: get_text(row, "secret")?,
+        events: get_json(row, "events")?,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
+
+fn delivery_from_row(row: &AnyRow) -> Result<WebhookDelivery, RepositoryError> {
+    Ok(WebhookDelivery {
+        id: get_uuid(row, "id")?,
+        tenant_id: get_uuid(row, "tenant_id")?,
+        webhook_id: get_uuid(row, "webhook_id")?,
+        event_id: get_uuid(row, "event_id")?,
+        event: get_text(row, "event")?
+            .parse()
+            .map_err(|e: ChatError| RepositoryError::Database(e.to_string()))?,
+        payload: get_text(row, "payload")?,
+        attempts: get_integer(row, "attempts")? as u32,
+        next_attempt_at: get_timestamp(row, "next_attempt_at")?,
+        last_error: get_optional_text(row, "last_error")?,
+        created_at: get_timestamp(row, "created_at")?,
+    })
+}
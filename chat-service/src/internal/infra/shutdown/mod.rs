@@ -0,0 +1,120 @@
+pub mod signal;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+// Shutdown coordinates a graceful stop: servers stop accepting requests once it is triggered,
+// and work already running holds an InFlight guard the process waits for before exiting
+#[derive(Clone)]
+pub struct Shutdown {
+    triggered: Arc<watch::Sender<bool>>,
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+// InFlight marks a request or stream as running until it is dropped
+#[derive(Debug)]
+pub struct InFlight {
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            triggered: Arc::new(watch::Sender::new(false)),
+            in_flight: Arc::new(watch::Sender::new(0)),
+        }
+    }
+
+    pub fn trigger(&self) {
+        self.triggered.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.triggered.borrow()
+    }
+
+    // triggered resolves once the shutdown has started, servers use it to stop listening
+    pub async fn triggered(&self) {
+        let mut triggered = self.triggered.subscribe();
+        let _ = triggered.wait_for(|triggered| *triggered).await;
+    }
+
+    // begin tracks new work, None once the shutdown has started so it can be turned away
+    pub fn begin(&self) -> Option<InFlight> {
+        if self.is_triggered() {
+            return None;
+        }
+
+        self.in_flight.send_modify(|count| *count += 1);
+        Some(InFlight {
+            in_flight: self.in_flight.clone(),
+        })
+    }
+
+    pub fn in_flight(&self) -> usize {
+        *self.in_flight.borrow()
+    }
+
+    // drain waits until no work is in flight, false when the timeout elapsed first
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let mut in_flight = self.in_flight.subscribe();
+        // the receiver moves into the wait so the borrow of its count ends with it
+        let idle = async move { in_flight.wait_for(|count| *count == 0).await.map(drop) };
+
+        tokio::time::timeout(timeout, idle).await.is_ok()
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.in_flight.send_modify(|count| *count -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_begin_is_refused_once_triggered() {
+        let shutdown = Shutdown::new();
+
+        let in_flight = shutdown.begin();
+        assert!(in_flight.is_some());
+        assert_eq!(shutdown.in_flight(), 1);
+
+        shutdown.trigger();
+        shutdown.triggered().await;
+
+        assert!(shutdown.is_triggered());
+        assert!(shutdown.begin().is_none());
+        assert_eq!(shutdown.in_flight(), 1);
+
+        drop(in_flight);
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_work() {
+        let shutdown = Shutdown::new();
+        let in_flight = shutdown.begin().unwrap();
+        shutdown.trigger();
+
+        assert!(!shutdown.drain(Duration::from_millis(10)).await);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(in_flight);
+        });
+
+        assert!(shutdown.drain(Duration::from_secs(5)).await);
+    }
+}
@@ -0,0 +1,32 @@
+use tokio::signal;
+
+// terminated resolves on SIGINT, or SIGTERM as sent by Kubernetes and most process managers
+pub async fn terminated() {
+    let interrupt = async {
+        if let Err(err) = signal::ctrl_c().await {
+            tracing::error!(error = %err, "could not listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "could not listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => tracing::info!("received SIGINT, shutting down"),
+        _ = terminate => tracing::info!("received SIGTERM, shutting down"),
+    }
+}
@@ -0,0 +1,77 @@
+pub mod propagation;
+
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryError {
+    #[error("invalid log filter: {0}")]
+    Filter(String),
+    #[error("could not start the OTLP exporter: {0}")]
+    Exporter(String),
+    #[error("could not install the tracing subscriber: {0}")]
+    Subscriber(String),
+}
+
+// TelemetryConfig sets how logs are written and where spans are exported
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+    pub service_name: String,
+    // otlp_endpoint enables the OTLP gRPC exporter, e.g. http://localhost:4317
+    pub otlp_endpoint: Option<String>,
+    // log_filter uses the RUST_LOG syntax, e.g. info,chat_service=debug
+    pub log_filter: String,
+    pub json_logs: bool,
+}
+
+// init installs the global subscriber, logging to stdout and exporting spans when configured;
+// W3C trace context is used to continue traces started by callers
+pub fn init(config: &TelemetryConfig) -> Result<(), TelemetryError> {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter = EnvFilter::try_new(&config.log_filter)
+        .map_err(|e| TelemetryError::Filter(e.to_string()))?;
+
+    let logs = if config.json_logs {
+        tracing_subscriber::fmt::layer().json().boxed()
+    } else {
+        tracing_subscriber::fmt::layer().boxed()
+    };
+
+    let spans = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", config.service_name.clone()),
+                ])))
+                .install_batch(runtime::Tokio)
+                .map_err(|e| TelemetryError::Exporter(e.to_string()))?;
+
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(logs)
+        .with(filter)
+        .with(spans)
+        .try_init()
+        .map_err(|e| TelemetryError::Subscriber(e.to_string()))
+}
+
+// shutdown flushes the spans the exporter still buffers
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
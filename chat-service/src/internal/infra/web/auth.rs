@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, Request, Uri};
+use axum::middleware::Next;
+use axum::response::Response;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::usecase::error::UseCaseError;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_QUERY_PARAM: &str = "api_key";
+
+// AuthenticatedUser is the user resolved from the request credentials, every request is
+// scoped to its tenant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+}
+
+// require_auth rejects requests without valid credentials and attaches the user to the request
+pub async fn require_auth<B>(
+    State(state): State<AppState>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError> {
+    let credential =
+        credentials(request.headers(), request.uri()).ok_or(UseCaseError::Unauthenticated)?;
+    let authenticated = state.authenticate.execute(&credential).await?;
+
+    request.extensions_mut().insert(AuthenticatedUser {
+        user_id: authenticated.user_id,
+        tenant_id: authenticated.tenant_id,
+    });
+
+    Ok(next.run(request).await)
+}
+
+// require_admin rejects requests that do not carry the admin token as their bearer token
+pub async fn require_admin<B>(
+    State(state): State<AppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, ApiError> {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (bearer, state.admin_token.as_deref()) {
+        (Some(bearer), Some(token)) if same_token(bearer.trim(), token) => {
+            Ok(next.run(request).await)
+        }
+        _ => Err(UseCaseError::Unauthenticated.into()),
+    }
+}
+
+// same_token compares digests so the time taken does not tell how much of the token matched
+fn same_token(candidate: &str, token: &str) -> bool {
+    Sha256::digest(candidate)
+        .iter()
+        .zip(Sha256::digest(token).iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+// credentials reads an API key or access token from the Authorization bearer token or the
+// X-API-Key header, the api_key query parameter is accepted for EventSource and WebSocket
+// clients that cannot set headers
+pub fn credentials(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let key = bearer
+        .or_else(|| {
+            headers
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::to_string)
+        .or_else(|| {
+            Query::<HashMap<String, String>>::try_from_uri(uri)
+                .ok()
+                .and_then(|Query(mut params)| params.remove(API_KEY_QUERY_PARAM))
+        })?;
+
+    let key = key.trim();
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials() {
+        let uri: Uri = "/chats".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(credentials(&headers, &uri), None);
+
+        let query: Uri = "/chats/1/stream?user_message=hi&api_key=chs_query"
+            .parse()
+            .unwrap();
+        assert_eq!(credentials(&headers, &query), Some("chs_query".to_string()));
+
+        headers.insert(API_KEY_HEADER, "chs_header".parse().unwrap());
+        assert_eq!(
+            credentials(&headers, &query),
+            Some("chs_header".to_string())
+        );
+
+        headers.insert(header::AUTHORIZATION, "Bearer chs_bearer".parse().unwrap());
+        assert_eq!(credentials(&headers, &uri), Some("chs_bearer".to_string()));
+
+        headers.insert(header::AUTHORIZATION, "Bearer  ".parse().unwrap());
+        assert_eq!(credentials(&headers, &uri), None);
+    }
+
+    #[test]
+    fn test_same_token() {
+        let token = "a".repeat(32);
+        assert!(same_token(&token, &token));
+        assert!(!same_token(&"a".repeat(31), &token));
+        assert!(!same_token("", &token));
+    }
+}
@@ -0,0 +1,55 @@
+use axum::http::{header, HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::internal::domain::cache_bypass::with_cache_bypass;
+
+// CACHE_BYPASS_HEADER lets a client ask for a fresh completion even when the response cache
+// holds one, like Cache-Control: no-cache does
+pub const CACHE_BYPASS_HEADER: &str = "x-cache-bypass";
+
+// bypass_cache runs the request without the response cache when the client asked for it
+pub async fn bypass_cache<B>(request: Request<B>, next: Next<B>) -> Response {
+    if bypassed(request.headers()) {
+        return with_cache_bypass(next.run(request)).await;
+    }
+
+    next.run(request).await
+}
+
+fn bypassed(headers: &HeaderMap) -> bool {
+    let flag = headers
+        .get(CACHE_BYPASS_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| matches!(value.trim(), "1" | "true"));
+    let no_cache = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| matches!(directive.trim(), "no-cache" | "no-store"));
+
+    flag || no_cache
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bypassed() {
+        let mut headers = HeaderMap::new();
+        assert!(!bypassed(&headers));
+
+        headers.insert(CACHE_BYPASS_HEADER, "true".parse().unwrap());
+        assert!(bypassed(&headers));
+        headers.insert(CACHE_BYPASS_HEADER, "no".parse().unwrap());
+        assert!(!bypassed(&headers));
+
+        headers.insert(
+            header::CACHE_CONTROL,
+            "max-age=0, no-cache".parse().unwrap(),
+        );
+        assert!(bypassed(&headers));
+    }
+}
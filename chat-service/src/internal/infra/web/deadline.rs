@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::internal::domain::deadline::{request_timeout, with_deadline};
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::usecase::error::UseCaseError;
+
+// REQUEST_TIMEOUT_HEADER lets a client ask for a shorter deadline than the server's, in
+// milliseconds
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout-ms";
+
+// apply_deadline answers with a 504 once the request outlives its deadline, dropping the handler
+// cancels the provider call and queries it was waiting on; SSE and WebSocket handlers hand their
+// reply to a task of its own, so streamed replies are not bounded by it
+pub async fn apply_deadline<B>(
+    State(state): State<AppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let requested = match requested_timeout(request.headers()) {
+        Ok(requested) => requested,
+        Err(err) => return ApiError(err).into_response(),
+    };
+    let Some(timeout) = request_timeout(state.request_timeout, requested) else {
+        return next.run(request).await;
+    };
+
+    match with_deadline(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(err) => ApiError(err.into()).into_response(),
+    }
+}
+
+// requested_timeout reads the deadline the client asked for, None when it did not ask
+fn requested_timeout(headers: &HeaderMap) -> Result<Option<Duration>, UseCaseError> {
+    let Some(value) = headers.get(REQUEST_TIMEOUT_HEADER) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|millis| *millis > 0)
+        .map(|millis| Some(Duration::from_millis(millis)))
+        .ok_or_else(|| {
+            UseCaseError::InvalidInput(format!(
+                "{} must be a positive number of milliseconds",
+                REQUEST_TIMEOUT_HEADER
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_timeout() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(requested_timeout(&headers).unwrap(), None);
+
+        headers.insert(REQUEST_TIMEOUT_HEADER, "2500".parse().unwrap());
+        assert_eq!(
+            requested_timeout(&headers).unwrap(),
+            Some(Duration::from_millis(2500))
+        );
+
+        for invalid in ["0", "-1", "soon"] {
+            headers.insert(REQUEST_TIMEOUT_HEADER, invalid.parse().unwrap());
+            assert!(matches!(
+                requested_timeout(&headers),
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+    }
+}
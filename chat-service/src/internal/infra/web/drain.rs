@@ -0,0 +1,27 @@
+use axum::extract::State;
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+
+use crate::internal::infra::web::handler::AppState;
+
+// track_request keeps the request in flight until the response is ready, requests that arrive
+// once the shutdown started are turned away so clients retry on another instance
+pub async fn track_request<B>(
+    State(state): State<AppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(_in_flight) = state.shutdown.begin() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::CONNECTION, "close")],
+            Json(json!({ "error": "server is shutting down" })),
+        )
+            .into_response();
+    };
+
+    next.run(request).await
+}
@@ -0,0 +1,254 @@
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::request_id;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct ApiError(pub UseCaseError);
+
+impl From<UseCaseError> for ApiError {
+    fn from(err: UseCaseError) -> Self {
+        Self(err)
+    }
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match &self.0 {
+            UseCaseError::Unauthenticated => StatusCode::UNAUTHORIZED,
+            UseCaseError::ChatNotFound(_)
+            | UseCaseError::MessageNotFound(_)
+            | UseCaseError::TemplateNotFound(_)
+            | UseCaseError::AssistantNotFound(_)
+            | UseCaseError::DocumentNotFound(_)
+            | UseCaseError::MemoryNotFound(_)
+            | UseCaseError::ScheduledMessageNotFound(_)
+            | UseCaseError::WebhookNotFound(_)
+            | UseCaseError::BatchNotFound(_)
+            | UseCaseError::JobNotFound(_)
+            | UseCaseError::UserNotFound(_)
+            | UseCaseError::TenantNotFound(_) => StatusCode::NOT_FOUND,
+            UseCaseError::UserAlreadyExists(_)
+            | UseCaseError::TenantAlreadyExists(_)
+            | UseCaseError::TemplateAlreadyExists(_)
+            | UseCaseError::AssistantAlreadyExists(_)
+            | UseCaseError::IdempotencyKeyInProgress(_) => StatusCode::CONFLICT,
+            UseCaseError::IdempotencyKeyReused(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            UseCaseError::Forbidden(_) => StatusCode::FORBIDDEN,
+            UseCaseError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            UseCaseError::RateLimited { .. } | UseCaseError::QuotaExceeded(_) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            // nobody reads it as the client is gone, 499 is how proxies log a closed request
+            UseCaseError::StreamCancelled(_) => {
+                StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST)
+            }
+            UseCaseError::Domain(err) => match err {
+                ChatError::InvalidMessage(_)
+                | ChatError::InvalidUser(_)
+                | ChatError::InvalidModel(_)
+                | ChatError::InvalidTemplate(_)
+                | ChatError::MissingTemplateVariables(_)
+                | ChatError::InvalidDocument(_)
+                | ChatError::InvalidAttachment(_)
+                | ChatError::AttachmentsNotSupported(_)
+                | ChatError::InvalidAudio(_)
+                | ChatError::InvalidWebhook(_)
+                | ChatError::InvalidAssistant(_)
+                | ChatError::InvalidTag(_)
+                | ChatError::InvalidMetadata(_)
+                | ChatError::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+                ChatError::ModelNotAllowed(_) => StatusCode::FORBIDDEN,
+                ChatError::InvalidStatus(_)
+                | ChatError::ChatEnded
+                | ChatError::ChatArchived
+                | ChatError::ChatDeleted
+                | ChatError::InvalidTransition { .. } => StatusCode::CONFLICT,
+                ChatError::TokenLimitExceeded { .. }
+                | ChatError::ContentFlagged(_)
+                | ChatError::PromptInjection(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                ChatError::ResponseFormatMismatch(_) => StatusCode::BAD_GATEWAY,
+            },
+            UseCaseError::Gateway(GatewayError::ProviderUnavailable(_)) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            UseCaseError::Gateway(GatewayError::Timeout(_)) | UseCaseError::DeadlineExceeded(_) => {
+                StatusCode::GATEWAY_TIMEOUT
+            }
+            UseCaseError::Gateway(GatewayError::Audit(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+            UseCaseError::Gateway(_)
+            | UseCaseError::ToolRoundsExceeded(_)
+            | UseCaseError::Publish(_)
+            | UseCaseError::Blob(_) => StatusCode::BAD_GATEWAY,
+            // the chat kept changing under the request, sending it again may succeed
+            UseCaseError::Repository(RepositoryError::ConcurrentModification(_)) => {
+                StatusCode::CONFLICT
+            }
+            UseCaseError::Repository(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status_code().is_server_error() {
+            tracing::error!(error = %self.0, "request failed");
+        }
+
+        let mut body = json!({ "error": self.0.to_string() });
+        if let Some(request_id) = request_id::current() {
+            body["request_id"] = request_id.into();
+        }
+        let body = Json(body);
+        let mut response = (self.status_code(), body).into_response();
+
+        match &self.0 {
+            UseCaseError::Unauthenticated => {
+                response
+                    .headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+            }
+            UseCaseError::RateLimited { retry_after } => {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_after.as_secs().into());
+            }
+            UseCaseError::QuotaExceeded(err) => {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, err.retry_after().as_secs().into());
+            }
+            _ => {}
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::internal::domain::quota::{QuotaExceeded, QuotaPeriod, QuotaScope};
+
+    #[test]
+    fn test_status_code() {
+        let chat_id = Uuid::new_v4();
+
+        assert_eq!(
+            ApiError(UseCaseError::Unauthenticated).status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            ApiError(UseCaseError::ChatNotFound(chat_id)).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError(UseCaseError::UserNotFound(chat_id)).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Forbidden(chat_id)).status_code(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Domain(ChatError::InvalidMessage(
+                "content is empty".to_string()
+            )))
+            .status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Domain(ChatError::ChatEnded)).status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError(UseCaseError::IdempotencyKeyInProgress(
+                "retry-1".to_string()
+            ))
+            .status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Repository(
+                RepositoryError::ConcurrentModification(chat_id)
+            ))
+            .status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError(UseCaseError::IdempotencyKeyReused("retry-1".to_string())).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Domain(ChatError::ContentFlagged(vec![
+                "violence".to_string()
+            ])))
+            .status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[test]
+    fn test_gateway_status_code() {
+        assert_eq!(
+            ApiError(UseCaseError::Gateway(GatewayError::EmptyResponse)).status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            ApiError(UseCaseError::Gateway(GatewayError::ProviderUnavailable(
+                "openai".to_string()
+            )))
+            .status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_rate_limited_response() {
+        let response = ApiError(UseCaseError::RateLimited {
+            retry_after: std::time::Duration::from_secs(12),
+        })
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "12");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_has_request_id() {
+        let response = request_id::with_request_id("req-1".to_string(), async {
+            ApiError(UseCaseError::Unauthenticated).into_response()
+        })
+        .await;
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[test]
+    fn test_quota_exceeded_response() {
+        let response = ApiError(UseCaseError::QuotaExceeded(QuotaExceeded {
+            scope: QuotaScope::User,
+            period: QuotaPeriod::Daily,
+            limit: 1000,
+            resets_at: chrono::Utc::now() + chrono::Duration::hours(2),
+        }))
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after > 7000 && retry_after <= 7200);
+    }
+}
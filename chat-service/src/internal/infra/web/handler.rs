@@ -0,0 +1,1517 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::message::Role;
+use crate::internal::domain::entity::speech::Voice;
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+use crate::internal::domain::output_filter::{OutputFilterStats, OutputFilterStatsSnapshot};
+use crate::internal::infra::provider::cache::{CacheStats, CacheStatsSnapshot};
+use crate::internal::infra::shutdown::Shutdown;
+use crate::internal::infra::web::auth::AuthenticatedUser;
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::resume::ReplyStreams;
+use crate::internal::usecase::authenticate::usecase::AuthenticateUseCase;
+use crate::internal::usecase::batch_completion::dto::{BatchCompletionInputDTO, BatchOutputDTO};
+use crate::internal::usecase::batch_completion::usecase::BatchCompletionUseCase;
+use crate::internal::usecase::cancel_scheduled_message::usecase::CancelScheduledMessageUseCase;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatAssistantInputDTO, ChatCompletionInputDTO, ChatCompletionOutputDTO, ChatLabelsInputDTO,
+    ChatOverridesInputDTO, PromptTemplateInputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::check_readiness::dto::ReadinessOutputDTO;
+use crate::internal::usecase::check_readiness::usecase::CheckReadinessUseCase;
+use crate::internal::usecase::create_api_key::dto::ApiKeyOutputDTO;
+use crate::internal::usecase::create_api_key::usecase::CreateApiKeyUseCase;
+use crate::internal::usecase::create_assistant::dto::{AssistantInputDTO, AssistantOutputDTO};
+use crate::internal::usecase::create_assistant::usecase::CreateAssistantUseCase;
+use crate::internal::usecase::create_prompt_template::dto::{
+    CreatePromptTemplateInputDTO, PromptTemplateOutputDTO,
+};
+use crate::internal::usecase::create_prompt_template::usecase::CreatePromptTemplateUseCase;
+use crate::internal::usecase::create_tenant::dto::{
+    CreateTenantInputDTO, TenantInputDTO, TenantOutputDTO,
+};
+use crate::internal::usecase::create_tenant::usecase::CreateTenantUseCase;
+use crate::internal::usecase::create_user::dto::{CreateUserInputDTO, UserOutputDTO};
+use crate::internal::usecase::create_user::usecase::CreateUserUseCase;
+use crate::internal::usecase::create_webhook::dto::{
+    CreateWebhookInputDTO, CreatedWebhookOutputDTO,
+};
+use crate::internal::usecase::create_webhook::usecase::CreateWebhookUseCase;
+use crate::internal::usecase::delete_assistant::usecase::DeleteAssistantUseCase;
+use crate::internal::usecase::delete_chat::usecase::DeleteChatUseCase;
+use crate::internal::usecase::delete_document::usecase::DeleteDocumentUseCase;
+use crate::internal::usecase::delete_memory::usecase::DeleteMemoryUseCase;
+use crate::internal::usecase::delete_webhook::usecase::DeleteWebhookUseCase;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::export_chat::dto::{ExportChatInputDTO, ExportFormat};
+use crate::internal::usecase::export_chat::usecase::ExportChatUseCase;
+use crate::internal::usecase::fork_chat::dto::ForkChatInputDTO;
+use crate::internal::usecase::fork_chat::usecase::ForkChatUseCase;
+use crate::internal::usecase::get_batch::usecase::GetBatchUseCase;
+use crate::internal::usecase::get_billing_report::dto::{GetBillingReportInputDTO, ReportFormat};
+use crate::internal::usecase::get_billing_report::usecase::GetBillingReportUseCase;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+use crate::internal::usecase::get_chat::usecase::GetChatUseCase;
+use crate::internal::usecase::get_job::usecase::GetJobUseCase;
+use crate::internal::usecase::get_quota::dto::{GetQuotaInputDTO, QuotasOutputDTO};
+use crate::internal::usecase::get_quota::usecase::GetQuotaUseCase;
+use crate::internal::usecase::get_usage::dto::{GetUsageInputDTO, UsageOutputDTO};
+use crate::internal::usecase::get_usage::usecase::GetUsageUseCase;
+use crate::internal::usecase::get_usage_summary::dto::{
+    GetUsageSummaryInputDTO, UsageSummaryOutputDTO,
+};
+use crate::internal::usecase::get_usage_summary::usecase::GetUsageSummaryUseCase;
+use crate::internal::usecase::import_chat::dto::{ImportChatInputDTO, ImportSourceDTO};
+use crate::internal::usecase::import_chat::usecase::ImportChatUseCase;
+use crate::internal::usecase::ingest_document::dto::{DocumentOutputDTO, IngestDocumentInputDTO};
+use crate::internal::usecase::ingest_document::usecase::IngestDocumentUseCase;
+use crate::internal::usecase::list_assistants::dto::AssistantListOutputDTO;
+use crate::internal::usecase::list_assistants::usecase::ListAssistantsUseCase;
+use crate::internal::usecase::list_audit_entries::dto::{
+    AuditEntryListOutputDTO, ListAuditEntriesInputDTO,
+};
+use crate::internal::usecase::list_audit_entries::usecase::ListAuditEntriesUseCase;
+use crate::internal::usecase::list_chat_messages::dto::{
+    ListChatMessagesInputDTO, MessageListOutputDTO, MessageOutputDTO,
+};
+use crate::internal::usecase::list_chat_messages::usecase::ListChatMessagesUseCase;
+use crate::internal::usecase::list_chats::dto::{ChatListOutputDTO, ListChatsInputDTO};
+use crate::internal::usecase::list_chats::usecase::ListChatsUseCase;
+use crate::internal::usecase::list_dead_letters::dto::DeadLetterListOutputDTO;
+use crate::internal::usecase::list_dead_letters::usecase::ListDeadLettersUseCase;
+use crate::internal::usecase::list_documents::dto::DocumentListOutputDTO;
+use crate::internal::usecase::list_documents::usecase::ListDocumentsUseCase;
+use crate::internal::usecase::list_memories::dto::MemoryListOutputDTO;
+use crate::internal::usecase::list_memories::usecase::ListMemoriesUseCase;
+use crate::internal::usecase::list_scheduled_messages::dto::ScheduledMessageListOutputDTO;
+use crate::internal::usecase::list_scheduled_messages::usecase::ListScheduledMessagesUseCase;
+use crate::internal::usecase::list_tenants::dto::TenantListOutputDTO;
+use crate::internal::usecase::list_tenants::usecase::ListTenantsUseCase;
+use crate::internal::usecase::list_webhooks::dto::WebhookListOutputDTO;
+use crate::internal::usecase::list_webhooks::usecase::ListWebhooksUseCase;
+use crate::internal::usecase::openai_chat_completion::usecase::OpenAIChatCompletionUseCase;
+use crate::internal::usecase::orchestrate_chat::usecase::OrchestrateChatUseCase;
+use crate::internal::usecase::rag_chat_completion::dto::RagChatCompletionOutputDTO;
+use crate::internal::usecase::rag_chat_completion::usecase::RagChatCompletionUseCase;
+use crate::internal::usecase::regenerate_message::dto::RegenerateMessageInputDTO;
+use crate::internal::usecase::regenerate_message::usecase::RegenerateMessageUseCase;
+use crate::internal::usecase::rotate_api_key::dto::RotatedApiKeyOutputDTO;
+use crate::internal::usecase::rotate_api_key::usecase::RotateApiKeyUseCase;
+use crate::internal::usecase::schedule_message::dto::{
+    ScheduleMessageInputDTO, ScheduledMessageOutputDTO,
+};
+use crate::internal::usecase::schedule_message::usecase::ScheduleMessageUseCase;
+use crate::internal::usecase::search_messages::dto::SearchMessagesInputDTO;
+use crate::internal::usecase::search_messages::usecase::SearchMessagesUseCase;
+use crate::internal::usecase::search_transcripts::dto::SearchTranscriptsInputDTO;
+use crate::internal::usecase::search_transcripts::usecase::SearchTranscriptsUseCase;
+use crate::internal::usecase::select_candidate::dto::SelectCandidateInputDTO;
+use crate::internal::usecase::select_candidate::usecase::SelectCandidateUseCase;
+use crate::internal::usecase::submit_job::dto::{JobOutputDTO, SubmitJobInputDTO};
+use crate::internal::usecase::submit_job::usecase::SubmitJobUseCase;
+use crate::internal::usecase::synthesize_speech::dto::{
+    SpeechOutputDTO, SpokenOutputDTO, SynthesizeSpeechInputDTO,
+};
+use crate::internal::usecase::synthesize_speech::usecase::SynthesizeSpeechUseCase;
+use crate::internal::usecase::transcribe_message::dto::{
+    TranscribeMessageInputDTO, TranscribedMessageOutputDTO,
+};
+use crate::internal::usecase::transcribe_message::usecase::TranscribeMessageUseCase;
+use crate::internal::usecase::update_assistant::usecase::UpdateAssistantUseCase;
+use crate::internal::usecase::update_chat::dto::UpdateChatInputDTO;
+use crate::internal::usecase::update_chat::usecase::UpdateChatUseCase;
+use crate::internal::usecase::update_system_prompt::dto::UpdateSystemPromptInputDTO;
+use crate::internal::usecase::update_system_prompt::usecase::UpdateSystemPromptUseCase;
+use crate::internal::usecase::update_tenant::usecase::UpdateTenantUseCase;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+#[derive(Clone)]
+pub struct AppState {
+    pub chat_completion: Arc<ChatCompletionUseCase>,
+    pub chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+    // streams buffers the replies streamed over SSE and WebSocket so clients can resume them
+    pub streams: Arc<ReplyStreams>,
+    // request_timeout is the deadline of every request, clients can ask for a shorter one
+    pub request_timeout: Option<Duration>,
+    pub regenerate_message: Arc<RegenerateMessageUseCase>,
+    pub select_candidate: Arc<SelectCandidateUseCase>,
+    // rag_chat_completion answers from the user's documents, its routes are only served when
+    // it is set
+    pub rag_chat_completion: Option<Arc<RagChatCompletionUseCase>>,
+    // ingest_document embeds uploaded documents, the document routes are only served when it
+    // is set
+    pub ingest_document: Option<Arc<IngestDocumentUseCase>>,
+    pub list_documents: Arc<ListDocumentsUseCase>,
+    // search_messages finds past messages by meaning, the semantic search is refused when it
+    // is not set
+    pub search_messages: Option<Arc<SearchMessagesUseCase>>,
+    pub search_transcripts: Arc<SearchTranscriptsUseCase>,
+    // transcribe_message answers recorded messages, its route is only served when it is set
+    pub transcribe_message: Option<Arc<TranscribeMessageUseCase>>,
+    // synthesize_speech reads replies aloud for the requests that ask for a voice
+    pub synthesize_speech: Option<Arc<SynthesizeSpeechUseCase>>,
+    pub delete_document: Arc<DeleteDocumentUseCase>,
+    pub list_memories: Arc<ListMemoriesUseCase>,
+    pub delete_memory: Arc<DeleteMemoryUseCase>,
+    pub batch_completion: Arc<BatchCompletionUseCase>,
+    pub get_batch: Arc<GetBatchUseCase>,
+    pub submit_job: Arc<SubmitJobUseCase>,
+    pub get_job: Arc<GetJobUseCase>,
+    pub schedule_message: Arc<ScheduleMessageUseCase>,
+    pub list_scheduled_messages: Arc<ListScheduledMessagesUseCase>,
+    pub cancel_scheduled_message: Arc<CancelScheduledMessageUseCase>,
+    pub get_chat: Arc<GetChatUseCase>,
+    pub update_chat: Arc<UpdateChatUseCase>,
+    pub update_system_prompt: Arc<UpdateSystemPromptUseCase>,
+    pub delete_chat: Arc<DeleteChatUseCase>,
+    pub fork_chat: Arc<ForkChatUseCase>,
+    pub export_chat: Arc<ExportChatUseCase>,
+    pub import_chat: Arc<ImportChatUseCase>,
+    pub list_chat_messages: Arc<ListChatMessagesUseCase>,
+    pub list_chats: Arc<ListChatsUseCase>,
+    // openai_chat_completion serves the OpenAI compatible chat completions route
+    pub openai_chat_completion: Arc<OpenAIChatCompletionUseCase>,
+    pub get_usage: Arc<GetUsageUseCase>,
+    pub get_quota: Arc<GetQuotaUseCase>,
+    pub create_user: Arc<CreateUserUseCase>,
+    pub create_api_key: Arc<CreateApiKeyUseCase>,
+    pub create_prompt_template: Arc<CreatePromptTemplateUseCase>,
+    pub list_audit_entries: Arc<ListAuditEntriesUseCase>,
+    pub list_tenants: Arc<ListTenantsUseCase>,
+    pub create_tenant: Arc<CreateTenantUseCase>,
+    pub update_tenant: Arc<UpdateTenantUseCase>,
+    pub rotate_api_key: Arc<RotateApiKeyUseCase>,
+    pub create_webhook: Arc<CreateWebhookUseCase>,
+    pub list_webhooks: Arc<ListWebhooksUseCase>,
+    pub delete_webhook: Arc<DeleteWebhookUseCase>,
+    pub list_dead_letters: Arc<ListDeadLettersUseCase>,
+    pub create_assistant: Arc<CreateAssistantUseCase>,
+    pub list_assistants: Arc<ListAssistantsUseCase>,
+    pub update_assistant: Arc<UpdateAssistantUseCase>,
+    pub delete_assistant: Arc<DeleteAssistantUseCase>,
+    // orchestrate_chat has assistants take turns in a chat
+    pub orchestrate_chat: Arc<OrchestrateChatUseCase>,
+    pub get_usage_summary: Arc<GetUsageSummaryUseCase>,
+    pub get_billing_report: Arc<GetBillingReportUseCase>,
+    // response_cache counts the hits of the response cache, its route is only served when it is
+    // set
+    pub response_cache: Option<Arc<CacheStats>>,
+    // output_filter counts the checks the output filter triggered, its route is only served
+    // when it is set
+    pub output_filter: Option<Arc<OutputFilterStats>>,
+    // admin_token is the bearer token of the admin routes, they are only served when it is set
+    pub admin_token: Option<String>,
+    pub authenticate: Arc<AuthenticateUseCase>,
+    pub check_readiness: Arc<CheckReadinessUseCase>,
+    pub shutdown: Shutdown,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageRequest {
+    pub user_message: String,
+    // attachments are images the model reads with the message, e.g. {"url": "https://..."}
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    // voice asks for the reply to be read aloud too
+    pub voice: Option<Voice>,
+    // overrides set the model and sampling of this message only, e.g. "temperature": 0.2
+    #[serde(flatten)]
+    pub overrides: ChatOverridesInputDTO,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateChatRequest {
+    pub user_message: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    // template names the prompt template the system message is rendered from
+    pub template: Option<String>,
+    // assistant_id names the assistant the chat is started with, instead of a template
+    pub assistant_id: Option<Uuid>,
+    // variables fill the template or the instructions of the assistant
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub voice: Option<Voice>,
+    // overrides set the model and sampling of the first message only, the chat is created
+    // with the tenant's model
+    #[serde(flatten)]
+    pub overrides: ChatOverridesInputDTO,
+    // labels are the tags and metadata the chat is created with
+    #[serde(flatten)]
+    pub labels: ChatLabelsInputDTO,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RegenerateRequest {
+    // user_message edits the message, it is sent again unchanged when omitted
+    pub user_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectCandidateRequest {
+    // candidate is the position of the chosen reply in the candidates of the message
+    pub candidate: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateChatRequest {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    // response_language is the code of the language to answer in, empty to follow the user
+    pub response_language: Option<String>,
+    pub status: Option<String>,
+    // tags and metadata replace the ones of the chat when set
+    pub tags: Option<Vec<String>>,
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSystemPromptRequest {
+    pub system_message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMessageRequest {
+    pub content: String,
+    pub send_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCompletionRequest {
+    pub prompts: Vec<String>,
+    // background queues the prompts for the job worker and responds with the batch id to poll
+    #[serde(default)]
+    pub background: bool,
+    // overrides set the model and sampling of every prompt, e.g. "temperature": 0.2
+    #[serde(flatten)]
+    pub overrides: ChatOverridesInputDTO,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitJobRequest {
+    // chat_id continues a chat of the user, a new chat is created when omitted
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(flatten)]
+    pub overrides: ChatOverridesInputDTO,
+}
+
+// CreateUserRequest registers a user, the tenant comes from the route and never from the body
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    pub external_id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    // events are the names of the events called back, every event when empty
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ForkRequest {
+    // message_id is the last message copied, the whole chat is forked when omitted
+    pub message_id: Option<Uuid>,
+}
+
+// SearchMode is how past messages are searched: by the words of the query, or by meaning
+// through the embeddings of the retrieval setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Text,
+    Semantic,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub mode: SearchMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AudioParams {
+    // name is the file name of the recording, read when the content type is missing
+    pub name: Option<String>,
+    pub voice: Option<Voice>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadParams {
+    // name is the file name, its extension tells the format when the content type does not
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BillingReportParams {
+    pub tenant_id: Option<Uuid>,
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+    // format defaults to json
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    // format defaults to json
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    // model names the model the chat is imported under, the one in the transcript or the
+    // tenant's model otherwise
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    // tag keeps the chats labelled with it
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageParams {
+    // role is a comma separated list, e.g. user,assistant
+    pub role: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageParams {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+// healthz is the liveness probe, it only tells that the process is serving requests
+pub async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+// readyz is the readiness probe, it answers 503 while a dependency is unavailable
+// or once the shutdown started, so no new traffic is routed to the instance
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadinessOutputDTO>) {
+    if state.shutdown.is_triggered() {
+        let output = ReadinessOutputDTO {
+            ready: false,
+            checks: vec![],
+        };
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(output));
+    }
+
+    let output = state.check_readiness.execute().await;
+    let status = if output.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(output))
+}
+
+// create_user registers the user chats will be created for, sign-ups always join the default
+// tenant as the route is public
+pub async fn create_user(
+    State(state): State<AppState>,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserOutputDTO>), ApiError> {
+    let output = state
+        .create_user
+        .execute(CreateUserInputDTO {
+            external_id: request.external_id,
+            display_name: request.display_name,
+            tenant_id: DEFAULT_TENANT_ID,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// create_api_key issues an additional API key for the authenticated user
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<(StatusCode, Json<ApiKeyOutputDTO>), ApiError> {
+    let output = state
+        .create_api_key
+        .execute(user.tenant_id, user.user_id)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// create_prompt_template stores a system prompt template chats can be created from
+pub async fn create_prompt_template(
+    State(state): State<AppState>,
+    Json(request): Json<CreatePromptTemplateInputDTO>,
+) -> Result<(StatusCode, Json<PromptTemplateOutputDTO>), ApiError> {
+    let output = state.create_prompt_template.execute(request).await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// chat_starter hands the variables to the template or the assistant a new chat starts with,
+// the use case refuses a request naming both
+fn chat_starter(
+    template: Option<String>,
+    assistant_id: Option<Uuid>,
+    variables: HashMap<String, String>,
+) -> (
+    Option<PromptTemplateInputDTO>,
+    Option<ChatAssistantInputDTO>,
+) {
+    let assistant = assistant_id.map(|id| ChatAssistantInputDTO {
+        id,
+        variables: variables.clone(),
+    });
+    let template = template.map(|name| PromptTemplateInputDTO { name, variables });
+
+    (template, assistant)
+}
+
+// create_chat starts a new chat with the first user message and returns the assistant reply
+pub async fn create_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    headers: HeaderMap,
+    Json(request): Json<CreateChatRequest>,
+) -> Result<(StatusCode, Json<SpokenOutputDTO<ChatCompletionOutputDTO>>), ApiError> {
+    let (template, assistant) =
+        chat_starter(request.template, request.assistant_id, request.variables);
+    let speaker = speaker(&state, request.voice)?;
+    let output = state
+        .chat_completion
+        .execute(ChatCompletionInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id: None,
+            user_message: request.user_message,
+            attachments: request.attachments,
+            template,
+            assistant,
+            idempotency_key: idempotency_key(&headers),
+            overrides: request.overrides,
+            labels: request.labels,
+        })
+        .await?;
+
+    let (audio, audio_error) = speak(speaker, &output.content).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SpokenOutputDTO {
+            reply: output,
+            audio,
+            audio_error,
+        }),
+    ))
+}
+
+// send_message appends a user message to an existing chat and returns the assistant reply
+pub async fn send_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<MessageRequest>,
+) -> Result<Json<SpokenOutputDTO<ChatCompletionOutputDTO>>, ApiError> {
+    let speaker = speaker(&state, request.voice)?;
+    let output = state
+        .chat_completion
+        .execute(ChatCompletionInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id: Some(chat_id),
+            user_message: request.user_message,
+            attachments: request.attachments,
+            template: None,
+            assistant: None,
+            idempotency_key: idempotency_key(&headers),
+            overrides: request.overrides,
+            labels: ChatLabelsInputDTO::default(),
+        })
+        .await?;
+
+    let (audio, audio_error) = speak(speaker, &output.content).await;
+
+    Ok(Json(SpokenOutputDTO {
+        reply: output,
+        audio,
+        audio_error,
+    }))
+}
+
+// create_rag_chat starts a new chat answered from the user's documents, the reply cites the
+// chunks the model was given
+pub async fn create_rag_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    headers: HeaderMap,
+    Json(request): Json<CreateChatRequest>,
+) -> Result<
+    (
+        StatusCode,
+        Json<SpokenOutputDTO<RagChatCompletionOutputDTO>>,
+    ),
+    ApiError,
+> {
+    let (template, assistant) =
+        chat_starter(request.template, request.assistant_id, request.variables);
+    let speaker = speaker(&state, request.voice)?;
+    let output = enabled(&state.rag_chat_completion, "retrieval")?
+        .execute(ChatCompletionInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id: None,
+            user_message: request.user_message,
+            attachments: request.attachments,
+            template,
+            assistant,
+            idempotency_key: idempotency_key(&headers),
+            overrides: request.overrides,
+            labels: request.labels,
+        })
+        .await?;
+
+    let (audio, audio_error) = speak(speaker, &output.content).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SpokenOutputDTO {
+            reply: output,
+            audio,
+            audio_error,
+        }),
+    ))
+}
+
+// send_rag_message appends a user message to an existing chat and answers it from the
+// user's documents
+pub async fn send_rag_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<MessageRequest>,
+) -> Result<Json<SpokenOutputDTO<RagChatCompletionOutputDTO>>, ApiError> {
+    let speaker = speaker(&state, request.voice)?;
+    let output = enabled(&state.rag_chat_completion, "retrieval")?
+        .execute(ChatCompletionInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id: Some(chat_id),
+            user_message: request.user_message,
+            attachments: request.attachments,
+            template: None,
+            assistant: None,
+            idempotency_key: idempotency_key(&headers),
+            overrides: request.overrides,
+            labels: ChatLabelsInputDTO::default(),
+        })
+        .await?;
+
+    let (audio, audio_error) = speak(speaker, &output.content).await;
+
+    Ok(Json(SpokenOutputDTO {
+        reply: output,
+        audio,
+        audio_error,
+    }))
+}
+
+// enabled returns a use case of an optional feature, the router leaves its routes out when
+// the feature is disabled
+fn enabled<'a, T>(usecase: &'a Option<Arc<T>>, feature: &str) -> Result<&'a T, ApiError> {
+    usecase.as_deref().ok_or_else(|| {
+        ApiError(UseCaseError::InvalidInput(format!(
+            "{} is not enabled",
+            feature
+        )))
+    })
+}
+
+// speaker returns the use case that reads the reply aloud when the request asked for a voice,
+// it fails before the message is sent when speech is disabled
+fn speaker(
+    state: &AppState,
+    voice: Option<Voice>,
+) -> Result<Option<(&SynthesizeSpeechUseCase, Voice)>, ApiError> {
+    match voice {
+        Some(voice) => Ok(Some((enabled(&state.synthesize_speech, "speech")?, voice))),
+        None => Ok(None),
+    }
+}
+
+// speak reads the content aloud with the speaker, if any; a failure is returned as the error to
+// show next to the reply, which is already saved
+async fn speak(
+    speaker: Option<(&SynthesizeSpeechUseCase, Voice)>,
+    content: &str,
+) -> (Option<SpeechOutputDTO>, Option<String>) {
+    let Some((usecase, voice)) = speaker else {
+        return (None, None);
+    };
+    let result = usecase
+        .execute(SynthesizeSpeechInputDTO {
+            text: content.to_string(),
+            voice,
+        })
+        .await;
+
+    match result {
+        Ok(output) => (Some(output), None),
+        Err(err) => {
+            tracing::warn!(error = %err, "could not read the reply aloud");
+            (None, Some(err.to_string()))
+        }
+    }
+}
+
+// send_audio_message transcribes the recording in the request body and sends the transcript
+// to the chat, the response holds the transcript and the reply
+pub async fn send_audio_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Query(params): Query<AudioParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<SpokenOutputDTO<TranscribedMessageOutputDTO>>, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let speaker = speaker(&state, params.voice)?;
+    let output = enabled(&state.transcribe_message, "transcription")?
+        .execute(TranscribeMessageInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id,
+            name: params.name,
+            content_type,
+            audio: body.to_vec(),
+            idempotency_key: idempotency_key(&headers),
+        })
+        .await?;
+
+    let (audio, audio_error) = speak(speaker, &output.content).await;
+
+    Ok(Json(SpokenOutputDTO {
+        reply: output,
+        audio,
+        audio_error,
+    }))
+}
+
+// regenerate_message edits a previous user message and replies to the new revision, the
+// messages that followed it are dropped from the chat
+pub async fn regenerate_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path((chat_id, message_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    request: Option<Json<RegenerateRequest>>,
+) -> Result<Json<ChatCompletionOutputDTO>, ApiError> {
+    let Json(request) = request.unwrap_or_default();
+    let output = state
+        .regenerate_message
+        .execute(RegenerateMessageInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            message_id,
+            user_message: request.user_message,
+            idempotency_key: idempotency_key(&headers),
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// select_candidate makes one of the candidates of the last reply the one the chat continues
+// from, the others are discarded
+pub async fn select_candidate(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path((chat_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<SelectCandidateRequest>,
+) -> Result<Json<MessageOutputDTO>, ApiError> {
+    let output = state
+        .select_candidate
+        .execute(SelectCandidateInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            message_id,
+            candidate: request.candidate,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// list_audit_entries pages through the audit log of provider requests for the admin
+pub async fn list_audit_entries(
+    State(state): State<AppState>,
+    Query(params): Query<ListAuditEntriesInputDTO>,
+) -> Result<Json<AuditEntryListOutputDTO>, ApiError> {
+    let output = state.list_audit_entries.execute(params).await?;
+
+    Ok(Json(output))
+}
+
+// list_tenants returns the tenants served by this instance for the admin
+pub async fn list_tenants(State(state): State<AppState>) -> Json<TenantListOutputDTO> {
+    Json(state.list_tenants.execute())
+}
+
+// create_tenant stores a new tenant for the admin, its users can be created right away
+pub async fn create_tenant(
+    State(state): State<AppState>,
+    Json(request): Json<CreateTenantInputDTO>,
+) -> Result<(StatusCode, Json<TenantOutputDTO>), ApiError> {
+    let output = state.create_tenant.execute(request).await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// update_tenant replaces the name, model, rate limit and allowed models of a tenant
+pub async fn update_tenant(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<TenantInputDTO>,
+) -> Result<Json<TenantOutputDTO>, ApiError> {
+    let output = state.update_tenant.execute(tenant_id, request).await?;
+
+    Ok(Json(output))
+}
+
+// create_tenant_user registers a user of the tenant for the admin
+pub async fn create_tenant_user(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserOutputDTO>), ApiError> {
+    let output = state
+        .create_user
+        .execute(CreateUserInputDTO {
+            external_id: request.external_id,
+            display_name: request.display_name,
+            tenant_id,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// rotate_api_key issues a new API key for a user on the admin's behalf and revokes the others
+pub async fn rotate_api_key(
+    State(state): State<AppState>,
+    Path((tenant_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, Json<RotatedApiKeyOutputDTO>), ApiError> {
+    let output = state.rotate_api_key.execute(tenant_id, user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// create_webhook registers a webhook of the tenant for the admin, the secret its calls are
+// signed with is only returned here
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreatedWebhookOutputDTO>), ApiError> {
+    let output = state
+        .create_webhook
+        .execute(CreateWebhookInputDTO {
+            tenant_id,
+            url: request.url,
+            secret: request.secret,
+            events: request.events,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// list_webhooks returns the webhooks of the tenant for the admin
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<WebhookListOutputDTO>, ApiError> {
+    let output = state.list_webhooks.execute(tenant_id).await?;
+
+    Ok(Json(output))
+}
+
+// delete_webhook stops calling a webhook of the tenant back
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path((tenant_id, webhook_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    state.delete_webhook.execute(tenant_id, webhook_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// create_assistant stores an assistant of the tenant for the admin
+pub async fn create_assistant(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<AssistantInputDTO>,
+) -> Result<(StatusCode, Json<AssistantOutputDTO>), ApiError> {
+    let output = state.create_assistant.execute(tenant_id, request).await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// list_tenant_assistants returns the assistants of the tenant for the admin
+pub async fn list_tenant_assistants(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<AssistantListOutputDTO>, ApiError> {
+    let output = state.list_assistants.execute(tenant_id).await?;
+
+    Ok(Json(output))
+}
+
+// update_assistant replaces the configuration of an assistant of the tenant
+pub async fn update_assistant(
+    State(state): State<AppState>,
+    Path((tenant_id, assistant_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<AssistantInputDTO>,
+) -> Result<Json<AssistantOutputDTO>, ApiError> {
+    let output = state
+        .update_assistant
+        .execute(tenant_id, assistant_id, request)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// delete_assistant removes an assistant of the tenant
+pub async fn delete_assistant(
+    State(state): State<AppState>,
+    Path((tenant_id, assistant_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .delete_assistant
+        .execute(tenant_id, assistant_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// list_assistants returns the assistants chats of the authenticated user can be started with
+pub async fn list_assistants(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<AssistantListOutputDTO>, ApiError> {
+    let output = state.list_assistants.execute(user.tenant_id).await?;
+
+    Ok(Json(output))
+}
+
+// list_dead_letters returns the webhook deliveries of the tenant that failed every attempt
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<DeadLetterListOutputDTO>, ApiError> {
+    let output = state.list_dead_letters.execute(tenant_id).await?;
+
+    Ok(Json(output))
+}
+
+// get_billing_report returns the cost per tenant, user and model for the admin, as a CSV
+// attachment when asked for one
+pub async fn get_billing_report(
+    State(state): State<AppState>,
+    Query(params): Query<BillingReportParams>,
+) -> Result<Response, ApiError> {
+    let output = state
+        .get_billing_report
+        .execute(GetBillingReportInputDTO {
+            tenant_id: params.tenant_id,
+            from: params.from,
+            to: params.to,
+        })
+        .await?;
+
+    match params.format {
+        ReportFormat::Json => Ok(Json(output).into_response()),
+        ReportFormat::Csv => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", output.filename()),
+                ),
+            ],
+            output.to_csv(),
+        )
+            .into_response()),
+    }
+}
+
+// get_cache_stats returns how many completions the response cache answered for the admin,
+// counted since the instance started
+pub async fn get_cache_stats(
+    State(state): State<AppState>,
+) -> Result<Json<CacheStatsSnapshot>, ApiError> {
+    let stats = enabled(&state.response_cache, "response cache")?;
+
+    Ok(Json(stats.snapshot()))
+}
+
+// get_output_filter_stats returns how many assistant responses the output filter changed and
+// which checks it triggered for the admin, counted since the instance started
+pub async fn get_output_filter_stats(
+    State(state): State<AppState>,
+) -> Result<Json<OutputFilterStatsSnapshot>, ApiError> {
+    let stats = enabled(&state.output_filter, "output filter")?;
+
+    Ok(Json(stats.snapshot()))
+}
+
+// get_usage_summary aggregates the usage per tenant and model for the admin
+pub async fn get_usage_summary(
+    State(state): State<AppState>,
+    Query(params): Query<GetUsageSummaryInputDTO>,
+) -> Result<Json<UsageSummaryOutputDTO>, ApiError> {
+    let output = state.get_usage_summary.execute(params).await?;
+
+    Ok(Json(output))
+}
+
+// list_chats pages through the chats of the authenticated user by last activity
+pub async fn list_chats(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<ChatListOutputDTO>, ApiError> {
+    list_user_chats(
+        State(state),
+        Extension(user),
+        Path(user.user_id),
+        Query(params),
+    )
+    .await
+}
+
+// list_user_chats pages through the chats of the authenticated user by last activity
+pub async fn list_user_chats(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<PageParams>,
+) -> Result<Json<ChatListOutputDTO>, ApiError> {
+    let output = state
+        .list_chats
+        .execute(ListChatsInputDTO {
+            tenant_id: user.tenant_id,
+            user_id,
+            requester_id: user.user_id,
+            limit: params.limit,
+            cursor: params.cursor,
+            tag: params.tag,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+pub async fn get_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+) -> Result<Json<ChatOutputDTO>, ApiError> {
+    let output = state
+        .get_chat
+        .execute(user.tenant_id, chat_id, user.user_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// update_chat changes the model, temperature, response language, status, tags or metadata of the
+// chat, the fields left out of the request keep their value
+pub async fn update_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Json(request): Json<UpdateChatRequest>,
+) -> Result<Json<ChatOutputDTO>, ApiError> {
+    let output = state
+        .update_chat
+        .execute(UpdateChatInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            model: request.model,
+            temperature: request.temperature,
+            response_language: request.response_language,
+            status: request.status,
+            tags: request.tags,
+            metadata: request.metadata,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// update_system_prompt replaces the system message of the chat, the change is audited
+pub async fn update_system_prompt(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Json(request): Json<UpdateSystemPromptRequest>,
+) -> Result<Json<ChatOutputDTO>, ApiError> {
+    let output = state
+        .update_system_prompt
+        .execute(UpdateSystemPromptInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            system_message: request.system_message,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// fork_chat copies the chat up to a message into a new chat owned by the same user
+pub async fn fork_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    request: Option<Json<ForkRequest>>,
+) -> Result<(StatusCode, Json<ChatOutputDTO>), ApiError> {
+    let Json(request) = request.unwrap_or_default();
+    let output = state
+        .fork_chat
+        .execute(ForkChatInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            message_id: request.message_id,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// export_chat downloads the transcript of the chat as JSON, Markdown or JSON lines
+pub async fn export_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Query(params): Query<ExportParams>,
+) -> Result<([(header::HeaderName, String); 2], String), ApiError> {
+    let output = state
+        .export_chat
+        .execute(ExportChatInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            format: params.format,
+        })
+        .await?;
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                output.format.content_type().to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", output.filename),
+            ),
+        ],
+        output.content,
+    ))
+}
+
+// import_chat recreates an exported transcript or an OpenAI messages array as a new chat of
+// the user, the tokens are counted again under the model it is imported under
+pub async fn import_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<ImportParams>,
+    Json(source): Json<ImportSourceDTO>,
+) -> Result<(StatusCode, Json<ChatOutputDTO>), ApiError> {
+    let output = state
+        .import_chat
+        .execute(ImportChatInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            model: params.model,
+            source,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// delete_chat soft deletes the chat, it is purged once the retention has passed
+pub async fn delete_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .delete_chat
+        .execute(user.tenant_id, chat_id, user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// search_chats returns the chats of the authenticated user with the messages containing the
+// words of q highlighted, or in semantic mode the past messages closest in meaning to q
+pub async fn search_chats(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<SearchParams>,
+) -> Result<Response, ApiError> {
+    match params.mode {
+        SearchMode::Text => {
+            let output = state
+                .search_transcripts
+                .execute(SearchTranscriptsInputDTO {
+                    tenant_id: user.tenant_id,
+                    user_id: user.user_id,
+                    query: params.q,
+                    limit: params.limit,
+                })
+                .await?;
+
+            Ok(Json(output).into_response())
+        }
+        SearchMode::Semantic => {
+            let output = enabled(&state.search_messages, "retrieval")?
+                .execute(SearchMessagesInputDTO {
+                    tenant_id: user.tenant_id,
+                    user_id: user.user_id,
+                    query: params.q,
+                    limit: params.limit,
+                })
+                .await?;
+
+            Ok(Json(output).into_response())
+        }
+    }
+}
+
+// upload_document stores the text of the request body as a document of the authenticated
+// user, chunked and embedded for the RAG chats
+pub async fn upload_document(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<UploadParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<DocumentOutputDTO>), ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let output = enabled(&state.ingest_document, "retrieval")?
+        .execute(IngestDocumentInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            name: params.name,
+            content_type,
+            content: body.to_vec(),
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// list_documents returns the documents of the authenticated user, newest first
+pub async fn list_documents(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<DocumentListOutputDTO>, ApiError> {
+    let output = state
+        .list_documents
+        .execute(user.tenant_id, user.user_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// delete_document removes a document and its chunks
+pub async fn delete_document(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(document_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .delete_document
+        .execute(user.tenant_id, document_id, user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// list_memories returns what is remembered about the authenticated user, oldest first
+pub async fn list_memories(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<MemoryListOutputDTO>, ApiError> {
+    let output = state
+        .list_memories
+        .execute(user.tenant_id, user.user_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// delete_memory forgets a memory of the authenticated user
+pub async fn delete_memory(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(memory_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .delete_memory
+        .execute(user.tenant_id, memory_id, user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// schedule_message sends a message into the chat at send_at, the assistant answers it then
+pub async fn schedule_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Json(request): Json<ScheduleMessageRequest>,
+) -> Result<(StatusCode, Json<ScheduledMessageOutputDTO>), ApiError> {
+    let output = state
+        .schedule_message
+        .execute(ScheduleMessageInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id,
+            content: request.content,
+            send_at: request.send_at,
+        })
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(output)))
+}
+
+// batch_completions answers independent prompts of the authenticated user, each in a new chat;
+// the prompts of a background batch are queued for the job worker and polled with get_batch
+pub async fn batch_completions(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<BatchCompletionRequest>,
+) -> Result<(StatusCode, Json<BatchOutputDTO>), ApiError> {
+    let input = BatchCompletionInputDTO {
+        tenant_id: user.tenant_id,
+        user_id: user.user_id,
+        prompts: request.prompts,
+        overrides: request.overrides,
+    };
+    if !request.background {
+        let output = state.batch_completion.execute(input).await?;
+        return Ok((StatusCode::OK, Json(output)));
+    }
+
+    let batch = state.batch_completion.queue(input).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(BatchOutputDTO::from(&batch))))
+}
+
+// get_batch returns a batch of the authenticated user, with the answers once it is completed
+pub async fn get_batch(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<BatchOutputDTO>, ApiError> {
+    let output = state
+        .get_batch
+        .execute(user.tenant_id, user.user_id, batch_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// submit_job queues a completion of the authenticated user and responds with the job to poll
+// with get_job, the reply is written by the job worker
+pub async fn submit_job(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<SubmitJobRequest>,
+) -> Result<(StatusCode, Json<JobOutputDTO>), ApiError> {
+    let output = state
+        .submit_job
+        .execute(SubmitJobInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            chat_id: request.chat_id,
+            user_message: request.user_message,
+            attachments: request.attachments,
+            overrides: request.overrides,
+        })
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(output)))
+}
+
+// get_job returns a job of the authenticated user, with the reply or the error once it is done
+pub async fn get_job(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<JobOutputDTO>, ApiError> {
+    let output = state
+        .get_job
+        .execute(user.tenant_id, user.user_id, job_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// list_scheduled_messages returns the pending messages of the authenticated user, soonest first
+pub async fn list_scheduled_messages(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<ScheduledMessageListOutputDTO>, ApiError> {
+    let output = state
+        .list_scheduled_messages
+        .execute(user.tenant_id, user.user_id)
+        .await?;
+
+    Ok(Json(output))
+}
+
+// cancel_scheduled_message cancels a pending message of the authenticated user
+pub async fn cancel_scheduled_message(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(scheduled_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .cancel_scheduled_message
+        .execute(user.tenant_id, scheduled_id, user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// list_chat_messages pages through the chat history, optionally filtered by role and time range
+pub async fn list_chat_messages(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Query(params): Query<MessageParams>,
+) -> Result<Json<MessageListOutputDTO>, ApiError> {
+    let roles = params
+        .role
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|role| !role.is_empty())
+        .map(|role| {
+            role.parse::<Role>()
+                .map_err(|_| UseCaseError::InvalidInput(format!("unknown role {}", role)))
+        })
+        .collect::<Result<Vec<_>, UseCaseError>>()?;
+
+    let output = state
+        .list_chat_messages
+        .execute(ListChatMessagesInputDTO {
+            tenant_id: user.tenant_id,
+            chat_id,
+            user_id: user.user_id,
+            roles,
+            from: params.from,
+            to: params.to,
+            limit: params.limit,
+            cursor: params.cursor,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// get_usage reports the tokens and estimated cost of the authenticated user per day
+pub async fn get_usage(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<UsageParams>,
+) -> Result<Json<UsageOutputDTO>, ApiError> {
+    let output = state
+        .get_usage
+        .execute(GetUsageInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+            from: params.from,
+            to: params.to,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// get_quota reports the tokens the authenticated user and its tenant have left
+pub async fn get_quota(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<Json<QuotasOutputDTO>, ApiError> {
+    let output = state
+        .get_quota
+        .execute(GetQuotaInputDTO {
+            tenant_id: user.tenant_id,
+            user_id: user.user_id,
+        })
+        .await?;
+
+    Ok(Json(output))
+}
+
+// idempotency_key reads the Idempotency-Key header clients send to retry a request safely
+fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
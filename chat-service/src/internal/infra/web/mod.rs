@@ -0,0 +1,13 @@
+pub mod auth;
+pub mod cache;
+pub mod deadline;
+pub mod drain;
+pub mod error;
+pub mod handler;
+pub mod openai;
+pub mod request_id;
+pub mod resume;
+pub mod server;
+pub mod sse;
+pub mod trace;
+pub mod websocket;
@@ -0,0 +1,154 @@
+use std::convert::Infallible;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+use crate::internal::domain::request_id;
+use crate::internal::infra::web::auth::AuthenticatedUser;
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::infra::web::sse::DONE_EVENT_DATA;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::openai_chat_completion::dto::{
+    OpenAIChatCompletionChunkDTO, OpenAIChatCompletionInputDTO, OpenAIChatCompletionRequestDTO,
+};
+
+const STREAM_BUFFER_SIZE: usize = 32;
+
+// OpenAIError answers with the status of the API error and the error body OpenAI clients
+// parse, {"error": {"message", "type", "code"}}
+pub struct OpenAIError(pub ApiError);
+
+impl From<UseCaseError> for OpenAIError {
+    fn from(err: UseCaseError) -> Self {
+        Self(ApiError(err))
+    }
+}
+
+impl IntoResponse for OpenAIError {
+    fn into_response(self) -> Response {
+        let status = self.0.status_code();
+        let body = error_body(status, &self.0 .0.to_string());
+        // the headers of the API error, Retry-After and WWW-Authenticate, are kept
+        let (parts, _) = self.0.into_response().into_parts();
+
+        (parts, Json(body)).into_response()
+    }
+}
+
+// error_body names the error the way OpenAI types them by status
+fn error_body(status: StatusCode, message: &str) -> Value {
+    let kind = match status {
+        StatusCode::UNAUTHORIZED => "authentication_error",
+        StatusCode::FORBIDDEN => "permission_error",
+        StatusCode::NOT_FOUND => "not_found_error",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limit_error",
+        status if status.is_server_error() => "api_error",
+        _ => "invalid_request_error",
+    };
+
+    json!({ "error": { "message": message, "type": kind, "code": status.as_u16() } })
+}
+
+// chat_completions answers an OpenAI chat completion request, so OpenAI SDKs can use the
+// service as their base URL; with stream set the reply is sent as chunk events ending with
+// [DONE]. A request refused before the first chunk gets an error status, a reply failing after
+// it ends the stream with an error event instead
+pub async fn chat_completions(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    request: Result<Json<OpenAIChatCompletionRequestDTO>, JsonRejection>,
+) -> Result<Response, OpenAIError> {
+    let Json(request) =
+        request.map_err(|rejection| UseCaseError::InvalidInput(rejection.body_text()))?;
+    let input = OpenAIChatCompletionInputDTO {
+        tenant_id: user.tenant_id,
+        user_id: user.user_id,
+        request,
+    };
+    if !input.request.stream {
+        let output = state.openai_chat_completion.execute(input).await?;
+        return Ok(Json(output).into_response());
+    }
+
+    let (chunks, mut chunk_receiver) =
+        mpsc::channel::<OpenAIChatCompletionChunkDTO>(STREAM_BUFFER_SIZE);
+    let usecase = state.openai_chat_completion.clone();
+    let in_flight = state.shutdown.begin();
+    let reply = tokio::spawn(request_id::inherit(async move {
+        let _in_flight = in_flight;
+        usecase.execute_stream(input, chunks).await
+    }));
+    let Some(first) = chunk_receiver.recv().await else {
+        // the use case is done without a chunk, so the request was refused
+        return match reply.await {
+            Ok(Err(err)) => Err(err.into()),
+            _ => Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        };
+    };
+
+    let (sender, receiver) = mpsc::channel::<Event>(STREAM_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let mut next = Some(first);
+        while let Some(chunk) = next {
+            let Ok(event) = Event::default().json_data(&chunk) else {
+                break;
+            };
+            if sender.send(event).await.is_err() {
+                break;
+            }
+            next = chunk_receiver.recv().await;
+        }
+        // dropping the receiver tells the use case the client is gone
+        drop(chunk_receiver);
+
+        let event = match reply.await {
+            Ok(Ok(_)) => Ok(Event::default().data(DONE_EVENT_DATA)),
+            Ok(Err(err)) => {
+                let err = ApiError(err);
+                Event::default().json_data(error_body(err.status_code(), &err.0.to_string()))
+            }
+            Err(_) => return,
+        };
+        if let Ok(event) = event {
+            let _ = sender.send(event).await;
+        }
+    });
+
+    let events = ReceiverStream::new(receiver).map(Ok::<_, Infallible>);
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use axum::http::header;
+
+    #[tokio::test]
+    async fn test_openai_error() {
+        let response = OpenAIError::from(UseCaseError::RateLimited {
+            retry_after: Duration::from_secs(3),
+        })
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["type"], "rate_limit_error");
+        assert_eq!(body["error"]["code"], 429);
+    }
+}
@@ -0,0 +1,27 @@
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::internal::domain::request_id::{self, with_request_id};
+
+// REQUEST_ID_HEADER carries the id correlating a request with its log lines, error responses and
+// the messages it created
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// assign_request_id runs every request under the id the client sent, or a new one when it sent
+// none or an invalid one; the id is echoed in the response so it can be quoted to support
+pub async fn assign_request_id<B>(request: Request<B>, next: Next<B>) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(request_id::parse)
+        .unwrap_or_else(request_id::generate);
+
+    let mut response = with_request_id(id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
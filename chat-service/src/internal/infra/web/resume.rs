@@ -0,0 +1,94 @@
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::internal::domain::stream_buffer::{StreamBuffer, StreamWriter};
+use crate::internal::infra::shutdown::InFlight;
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatCompletionOutputDTO,
+};
+
+const STREAM_BUFFER_SIZE: usize = 32;
+
+// StreamItem is one chunk of a streamed reply as the SSE and WebSocket clients receive it,
+// a stream ends with either Done or Error
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamItem {
+    Delta(ChatCompletionOutputDTO),
+    Done(ChatCompletionOutputDTO),
+    Error { code: u16, error: String },
+}
+
+// ReplyStreams buffers the streamed replies so clients can resume them
+pub type ReplyStreams = StreamBuffer<StreamItem>;
+
+// produce runs the completion into the stream, detached from the connection that asked for it so
+// a client that reconnects in time gets the rest; it is cancelled once the stream is abandoned
+pub async fn produce(
+    state: AppState,
+    input: ChatCompletionInputDTO,
+    writer: StreamWriter<StreamItem>,
+    in_flight: Option<InFlight>,
+) {
+    let _in_flight = in_flight;
+    let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(STREAM_BUFFER_SIZE);
+
+    let forward = async {
+        let abandoned = writer.abandoned();
+        tokio::pin!(abandoned);
+        loop {
+            let output = tokio::select! {
+                output = receiver.recv() => output,
+                _ = &mut abandoned => None,
+            };
+            let Some(output) = output else { break };
+            writer.push(StreamItem::Delta(output));
+        }
+        // dropping the receiver tells the use case the client is gone
+        drop(receiver);
+    };
+
+    let (result, _) = tokio::join!(state.chat_completion_stream.execute(input, sender), forward);
+
+    writer.push(match result {
+        Ok(output) => StreamItem::Done(output),
+        Err(err) => {
+            let err = ApiError(err);
+            StreamItem::Error {
+                code: err.status_code().as_u16(),
+                error: err.0.to_string(),
+            }
+        }
+    });
+}
+
+// event_id is the resume token of a chunk, the stream id and the chunk's sequence number
+pub fn event_id(stream_id: Uuid, seq: u64) -> String {
+    format!("{}:{}", stream_id, seq)
+}
+
+// parse_event_id reads a resume token back, None when it is not one
+pub fn parse_event_id(event_id: &str) -> Option<(Uuid, u64)> {
+    let (stream_id, seq) = event_id.trim().split_once(':')?;
+
+    Some((stream_id.parse().ok()?, seq.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_id() {
+        let stream_id = Uuid::new_v4();
+
+        assert_eq!(
+            parse_event_id(&event_id(stream_id, 12)),
+            Some((stream_id, 12))
+        );
+        assert_eq!(parse_event_id("12"), None);
+        assert_eq!(parse_event_id("not-a-uuid:12"), None);
+        assert_eq!(parse_event_id(&format!("{}:last", stream_id)), None);
+    }
+}
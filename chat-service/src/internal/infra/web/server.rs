@@ -0,0 +1,191 @@
+use std::net::SocketAddr;
+
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+
+use crate::internal::infra::web::auth::{require_admin, require_auth};
+use crate::internal::infra::web::cache::bypass_cache;
+use crate::internal::infra::web::deadline::apply_deadline;
+use crate::internal::infra::web::drain::track_request;
+use crate::internal::infra::web::handler::{
+    batch_completions, cancel_scheduled_message, create_api_key, create_assistant, create_chat,
+    create_prompt_template, create_rag_chat, create_tenant, create_tenant_user, create_user,
+    create_webhook, delete_assistant, delete_chat, delete_document, delete_memory, delete_webhook,
+    export_chat, fork_chat, get_batch, get_billing_report, get_cache_stats, get_chat, get_job,
+    get_output_filter_stats, get_quota, get_usage, get_usage_summary, healthz, import_chat,
+    list_assistants, list_audit_entries, list_chat_messages, list_chats, list_dead_letters,
+    list_documents, list_memories, list_scheduled_messages, list_tenant_assistants, list_tenants,
+    list_user_chats, list_webhooks, readyz, regenerate_message, rotate_api_key, schedule_message,
+    search_chats, select_candidate, send_audio_message, send_message, send_rag_message, submit_job,
+    update_assistant, update_chat, update_system_prompt, update_tenant, upload_document, AppState,
+};
+use crate::internal::infra::web::openai::chat_completions;
+use crate::internal::infra::web::request_id::assign_request_id;
+use crate::internal::infra::web::sse::{chat_sse, orchestrate_chat};
+use crate::internal::infra::web::trace::trace_request;
+use crate::internal::infra::web::websocket::chat_ws;
+
+pub struct WebServer {
+    pub state: AppState,
+    pub port: u16,
+}
+
+impl WebServer {
+    pub fn new(state: AppState, port: u16) -> Self {
+        Self { state, port }
+    }
+
+    // router exposes user sign-up and the probes publicly, the admin routes require the admin
+    // token and every other route user credentials; every request but the probes gets a request
+    // id, is traced and drained on shutdown, and may ask to bypass the response cache
+    pub fn router(&self) -> Router {
+        let mut authenticated = Router::new()
+            .route("/api-keys", post(create_api_key))
+            .route("/assistants", get(list_assistants))
+            .route("/batch/completions", post(batch_completions))
+            .route("/batch/:id", get(get_batch))
+            .route("/chats", get(list_chats).post(create_chat))
+            .route(
+                "/chats/:id",
+                get(get_chat).patch(update_chat).delete(delete_chat),
+            )
+            .route("/chats/:id/export", get(export_chat))
+            .route("/chats/:id/fork", post(fork_chat))
+            .route("/chats/:id/system-prompt", put(update_system_prompt))
+            .route("/chats/import", post(import_chat))
+            .route("/chats/orchestrate", post(orchestrate_chat))
+            .route("/chats/search", get(search_chats))
+            .route(
+                "/chats/:id/messages",
+                get(list_chat_messages).post(send_message),
+            )
+            .route(
+                "/chats/:id/messages/:message_id/regenerate",
+                post(regenerate_message),
+            )
+            .route(
+                "/chats/:id/messages/:message_id/select",
+                post(select_candidate),
+            )
+            .route("/chats/:id/scheduled-messages", post(schedule_message))
+            .route("/chats/:id/stream", get(chat_sse))
+            .route("/jobs", post(submit_job))
+            .route("/jobs/:id", get(get_job))
+            .route("/memories", get(list_memories))
+            .route("/memories/:id", delete(delete_memory))
+            .route("/prompt-templates", post(create_prompt_template))
+            .route("/quota", get(get_quota))
+            .route("/scheduled-messages", get(list_scheduled_messages))
+            .route("/scheduled-messages/:id", delete(cancel_scheduled_message))
+            .route("/ws/chats/:id", get(chat_ws))
+            .route("/usage", get(get_usage))
+            .route("/users/:id/chats", get(list_user_chats))
+            .route("/v1/chat/completions", post(chat_completions));
+        if let Some(transcribe_message) = &self.state.transcribe_message {
+            authenticated = authenticated.route(
+                "/chats/:id/audio",
+                post(send_audio_message)
+                    .layer(DefaultBodyLimit::max(transcribe_message.max_size_bytes())),
+            );
+        }
+        if self.state.rag_chat_completion.is_some() {
+            authenticated = authenticated
+                .route("/rag/chats", post(create_rag_chat))
+                .route("/rag/chats/:id/messages", post(send_rag_message));
+        }
+        if let Some(ingest_document) = &self.state.ingest_document {
+            authenticated = authenticated
+                .route(
+                    "/documents",
+                    get(list_documents)
+                        .post(upload_document)
+                        .layer(DefaultBodyLimit::max(ingest_document.max_size_bytes())),
+                )
+                .route("/documents/:id", delete(delete_document));
+        }
+        let authenticated = authenticated.route_layer(middleware::from_fn_with_state(
+            self.state.clone(),
+            require_auth,
+        ));
+
+        let mut router = Router::new()
+            .route("/users", post(create_user))
+            .merge(authenticated);
+        if self.state.admin_token.is_some() {
+            let mut admin = Router::new();
+            if self.state.response_cache.is_some() {
+                admin = admin.route("/admin/response-cache", get(get_cache_stats));
+            }
+            if self.state.output_filter.is_some() {
+                admin = admin.route("/admin/output-filter", get(get_output_filter_stats));
+            }
+            router = router.merge(
+                admin
+                    .route("/admin/audit-log", get(list_audit_entries))
+                    .route("/billing/report", get(get_billing_report))
+                    .route("/admin/tenants", get(list_tenants).post(create_tenant))
+                    .route("/admin/tenants/:tenant_id", put(update_tenant))
+                    .route("/admin/tenants/:tenant_id/users", post(create_tenant_user))
+                    .route(
+                        "/admin/tenants/:tenant_id/users/:user_id/api-keys/rotate",
+                        post(rotate_api_key),
+                    )
+                    .route(
+                        "/admin/tenants/:tenant_id/assistants",
+                        get(list_tenant_assistants).post(create_assistant),
+                    )
+                    .route(
+                        "/admin/tenants/:tenant_id/assistants/:assistant_id",
+                        put(update_assistant).delete(delete_assistant),
+                    )
+                    .route(
+                        "/admin/tenants/:tenant_id/webhooks",
+                        get(list_webhooks).post(create_webhook),
+                    )
+                    .route(
+                        "/admin/tenants/:tenant_id/webhooks/dead-letters",
+                        get(list_dead_letters),
+                    )
+                    .route(
+                        "/admin/tenants/:tenant_id/webhooks/:webhook_id",
+                        delete(delete_webhook),
+                    )
+                    .route("/admin/usage", get(get_usage_summary))
+                    .route_layer(middleware::from_fn_with_state(
+                        self.state.clone(),
+                        require_admin,
+                    )),
+            );
+        }
+
+        router
+            .layer(middleware::from_fn(bypass_cache))
+            .layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                apply_deadline,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                track_request,
+            ))
+            .layer(middleware::from_fn(trace_request))
+            .layer(middleware::from_fn(assign_request_id))
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .with_state(self.state.clone())
+    }
+
+    // start serves the HTTP API until the shutdown is triggered, then stops accepting
+    // connections and lets the open ones finish
+    pub async fn start(self) -> Result<(), hyper::Error> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
+        let shutdown = self.state.shutdown.clone();
+
+        axum::Server::bind(&addr)
+            .serve(self.router().into_make_service())
+            .with_graceful_shutdown(async move { shutdown.triggered().await })
+            .await
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures_util::Stream;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use crate::internal::domain::request_id;
+use crate::internal::domain::stream_buffer::StreamSubscription;
+use crate::internal::infra::web::auth::AuthenticatedUser;
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::infra::web::resume::{event_id, parse_event_id, produce, StreamItem};
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::orchestrate_chat::dto::{
+    AgentTurnOutputDTO, OrchestrateChatInputDTO,
+};
+
+const STREAM_BUFFER_SIZE: usize = 32;
+pub const DONE_EVENT_DATA: &str = "[DONE]";
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+#[derive(Debug, Deserialize)]
+pub struct SseParams {
+    pub user_message: String,
+    // the parameters below override the chat's model and sampling for this message only, stop
+    // is a comma separated list
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Option<String>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrchestrateChatRequest {
+    // chat_id continues a chat of the user, a new one is started without it
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    // assistants take their turns in the order they are listed
+    pub assistants: Vec<Uuid>,
+    // variables fill the instructions of the assistants
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub max_rounds: Option<usize>,
+}
+
+// chat_sse streams the assistant reply as server-sent events, one event per delta,
+// followed by a terminal [DONE] event; the reply stays in flight until it is done.
+// Every event carries an id, a client that reconnects with the Last-Event-ID header gets the
+// events it missed instead of a new reply
+pub async fn chat_sse(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Query(params): Query<SseParams>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (sender, receiver) = mpsc::channel::<Event>(STREAM_BUFFER_SIZE);
+
+    let last_event_id = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let subscription = match last_event_id {
+        Some(last_event_id) => parse_event_id(last_event_id)
+            .and_then(|(stream_id, seq)| state.streams.resume(stream_id, user.user_id, seq)),
+        None => {
+            let input = ChatCompletionInputDTO {
+                tenant_id: user.tenant_id,
+                user_id: user.user_id,
+                chat_id: Some(chat_id),
+                user_message: params.user_message,
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO {
+                    model: params.model,
+                    temperature: params.temperature,
+                    top_p: params.top_p,
+                    stop: stop_sequences(params.stop.as_deref()),
+                    presence_penalty: params.presence_penalty,
+                    frequency_penalty: params.frequency_penalty,
+                },
+                labels: ChatLabelsInputDTO::default(),
+            };
+            let (writer, subscription) = state.streams.open(user.user_id);
+            let in_flight = state.shutdown.begin();
+            tokio::spawn(request_id::inherit(produce(
+                state.clone(),
+                input,
+                writer,
+                in_flight,
+            )));
+            Some(subscription)
+        }
+    };
+
+    tokio::spawn(async move {
+        match subscription {
+            Some(subscription) => follow(subscription, &sender).await,
+            None => {
+                let body = json!({
+                    "code": StatusCode::NOT_FOUND.as_u16(),
+                    "error": "the stream expired or never existed",
+                });
+                if let Ok(event) = Event::default().event("error").json_data(body) {
+                    let _ = sender.send(event).await;
+                }
+                let _ = sender.send(Event::default().data(DONE_EVENT_DATA)).await;
+            }
+        }
+    });
+
+    Sse::new(ReceiverStream::new(receiver).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+// orchestrate_chat has assistants take turns answering the user message and streams their
+// turns as server-sent events, one event per delta labelled with the round and the speaker,
+// followed by a terminal [DONE] event. A request refused before the first delta gets an error
+// status, a turn failing after it ends the stream with an error event instead
+pub async fn orchestrate_chat(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<OrchestrateChatRequest>,
+) -> Result<Response, ApiError> {
+    let input = OrchestrateChatInputDTO {
+        tenant_id: user.tenant_id,
+        user_id: user.user_id,
+        chat_id: request.chat_id,
+        user_message: request.user_message,
+        assistants: request.assistants,
+        variables: request.variables,
+        max_rounds: request.max_rounds,
+    };
+
+    let (deltas, mut delta_receiver) = mpsc::channel::<AgentTurnOutputDTO>(STREAM_BUFFER_SIZE);
+    let usecase = state.orchestrate_chat.clone();
+    let in_flight = state.shutdown.begin();
+    let orchestration = tokio::spawn(request_id::inherit(async move {
+        let _in_flight = in_flight;
+        usecase.execute(input, deltas).await
+    }));
+    let Some(first) = delta_receiver.recv().await else {
+        // the use case is done without a delta, so the request was refused
+        return match orchestration.await {
+            Ok(Err(err)) => Err(err.into()),
+            _ => Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        };
+    };
+
+    let (sender, receiver) = mpsc::channel::<Event>(STREAM_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let mut next = Some(first);
+        while let Some(delta) = next {
+            let Ok(event) = Event::default().json_data(&delta) else {
+                break;
+            };
+            if sender.send(event).await.is_err() {
+                break;
+            }
+            next = delta_receiver.recv().await;
+        }
+        // dropping the receiver tells the use case the client is gone
+        drop(delta_receiver);
+
+        let mut events = vec![];
+        match orchestration.await {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => {
+                let err = ApiError(err);
+                let body =
+                    json!({ "code": err.status_code().as_u16(), "error": err.0.to_string() });
+                events.extend(Event::default().event("error").json_data(body));
+            }
+            Err(_) => return,
+        }
+        events.push(Event::default().data(DONE_EVENT_DATA));
+        for event in events {
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(
+        Sse::new(ReceiverStream::new(receiver).map(Ok::<_, Infallible>))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+    )
+}
+
+// stop_sequences reads the comma separated stop sequences of a query, none when it lists none
+pub(crate) fn stop_sequences(stop: Option<&str>) -> Option<Vec<String>> {
+    let sequences: Vec<String> = stop?
+        .split(',')
+        .filter(|sequence| !sequence.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    (!sequences.is_empty()).then_some(sequences)
+}
+
+// follow sends the chunks of the stream as events until it ends or the client is gone
+async fn follow(mut subscription: StreamSubscription<StreamItem>, sender: &mpsc::Sender<Event>) {
+    loop {
+        let chunk = tokio::select! {
+            chunk = subscription.next() => chunk,
+            _ = sender.closed() => None,
+        };
+        let Some((seq, item)) = chunk else { break };
+        let id = event_id(subscription.id(), seq);
+
+        let events = match item {
+            StreamItem::Delta(output) => vec![Event::default().json_data(&output)],
+            StreamItem::Done(_) => vec![Ok(Event::default().data(DONE_EVENT_DATA))],
+            StreamItem::Error { code, error } => vec![
+                Event::default()
+                    .event("error")
+                    .json_data(json!({ "code": code, "error": error })),
+                Ok(Event::default().data(DONE_EVENT_DATA)),
+            ],
+        };
+        for event in events.into_iter().flatten() {
+            if sender.send(event.id(id.clone())).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_sequences() {
+        assert_eq!(
+            stop_sequences(Some("END,\n\n")),
+            Some(vec!["END".to_string(), "\n\n".to_string()])
+        );
+        assert_eq!(stop_sequences(Some("")), None);
+        assert_eq!(stop_sequences(None), None);
+    }
+}
@@ -0,0 +1,35 @@
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Instrument;
+
+use crate::internal::domain::request_id;
+use crate::internal::infra::telemetry::propagation::{continue_trace, HeaderExtractor};
+
+// trace_request runs every request in a span that continues the caller's trace, the span
+// carries the request id so every log line of the request has it; the route template is used
+// instead of the raw path to keep span names bounded
+pub async fn trace_request<B>(request: Request<B>, next: Next<B>) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let span = tracing::info_span!(
+        "http_request",
+        method = %request.method(),
+        route = %route,
+        request_id = tracing::field::Empty,
+        status = tracing::field::Empty,
+    );
+    if let Some(request_id) = request_id::current() {
+        span.record("request_id", request_id.as_str());
+    }
+    continue_trace(&span, &HeaderExtractor(request.headers()));
+
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("status", response.status().as_u16());
+
+    response
+}
@@ -0,0 +1,329 @@
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{
+    close_code, CloseFrame, Message as WsMessage, WebSocket, WebSocketUpgrade,
+};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::request_id::{self, with_request_id};
+use crate::internal::domain::stream_buffer::StreamSubscription;
+use crate::internal::infra::shutdown::InFlight;
+use crate::internal::infra::web::auth::AuthenticatedUser;
+use crate::internal::infra::web::error::ApiError;
+use crate::internal::infra::web::handler::AppState;
+use crate::internal::infra::web::resume::{event_id, parse_event_id, produce, StreamItem};
+use crate::internal::infra::web::sse::stop_sequences;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const PONG_TIMEOUT: Duration = Duration::from_secs(60);
+
+// ServerEvent is a frame sent to the client, the id of deltas and replies is the token to
+// resume the stream with
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    Delta {
+        id: String,
+        chat_id: Uuid,
+        content: String,
+    },
+    Done {
+        id: String,
+        chat_id: Uuid,
+        content: String,
+    },
+    Error {
+        code: u16,
+        error: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WsParams {
+    // last_event_id is the id of the last event received before the connection was lost
+    pub last_event_id: Option<String>,
+    // the parameters below override the chat's model and sampling for every message sent over
+    // the connection, stop is a comma separated list
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Option<String>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+}
+
+// chat_ws upgrades the connection, every text frame sent by the client is a user message;
+// a client reconnecting with last_event_id first gets what it missed of the reply it was following
+pub async fn chat_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(chat_id): Path<Uuid>,
+    Query(params): Query<WsParams>,
+) -> Response {
+    // the connection outlives the upgrade request, its turns keep the id of that request
+    let request_id = request_id::current().unwrap_or_else(request_id::generate);
+    let overrides = ChatOverridesInputDTO {
+        model: params.model,
+        temperature: params.temperature,
+        top_p: params.top_p,
+        stop: stop_sequences(params.stop.as_deref()),
+        presence_penalty: params.presence_penalty,
+        frequency_penalty: params.frequency_penalty,
+    };
+    ws.on_upgrade(move |socket| {
+        with_request_id(
+            request_id,
+            handle_socket(
+                socket,
+                state,
+                chat_id,
+                user,
+                params.last_event_id,
+                overrides,
+            ),
+        )
+    })
+}
+
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    chat_id: Uuid,
+    user: AuthenticatedUser,
+    last_event_id: Option<String>,
+    overrides: ChatOverridesInputDTO,
+) {
+    let (mut sink, mut stream) = socket.split();
+
+    match state
+        .get_chat
+        .execute(user.tenant_id, chat_id, user.user_id)
+        .await
+    {
+        Ok(chat) if chat.status != "active" => {
+            close(&mut sink, close_code::NORMAL, "chat is no longer active").await;
+            return;
+        }
+        Ok(_) => {}
+        Err(err) => {
+            let err = ApiError(err);
+            let event = ServerEvent::Error {
+                code: err.status_code().as_u16(),
+                error: err.0.to_string(),
+            };
+            let _ = send_event(&mut sink, &event).await;
+            close(&mut sink, close_code::POLICY, "chat unavailable").await;
+            return;
+        }
+    }
+
+    if let Some(last_event_id) = last_event_id {
+        if !resume(&state, &mut sink, user, &last_event_id).await {
+            return;
+        }
+    }
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    let mut pending_ping: Option<Instant> = None;
+
+    // the shutdown closes the socket between turns, a reply being streamed is finished first
+    loop {
+        tokio::select! {
+            biased;
+
+            _ = state.shutdown.triggered() => {
+                close(&mut sink, close_code::AWAY, "server is shutting down").await;
+                break;
+            }
+            message = stream.next() => match message {
+                Some(Ok(WsMessage::Text(text))) => {
+                    let Some(in_flight) = state.shutdown.begin() else {
+                        close(&mut sink, close_code::AWAY, "server is shutting down").await;
+                        break;
+                    };
+                    let turn = run_turn(
+                        &state,
+                        &mut sink,
+                        chat_id,
+                        user,
+                        text,
+                        overrides.clone(),
+                        in_flight,
+                    );
+                    if !turn.await {
+                        break;
+                    }
+
+                    if !chat_active(&state, chat_id, user).await {
+                        close(&mut sink, close_code::NORMAL, "chat is no longer active").await;
+                        break;
+                    }
+                }
+                Some(Ok(WsMessage::Pong(_))) => pending_ping = None,
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = ping.tick() => {
+                if pending_ping.is_some_and(|sent| sent.elapsed() > PONG_TIMEOUT) {
+                    close(&mut sink, close_code::AWAY, "keepalive timeout").await;
+                    break;
+                }
+
+                if sink.send(WsMessage::Ping(vec![])).await.is_err() {
+                    break;
+                }
+                pending_ping.get_or_insert_with(Instant::now);
+            }
+        }
+    }
+}
+
+// run_turn streams the assistant reply for one user message, returns false once the socket is gone;
+// the reply goes on without the socket so a client reconnecting in time can resume it
+async fn run_turn(
+    state: &AppState,
+    sink: &mut SplitSink<WebSocket, WsMessage>,
+    chat_id: Uuid,
+    user: AuthenticatedUser,
+    text: String,
+    overrides: ChatOverridesInputDTO,
+    in_flight: InFlight,
+) -> bool {
+    let input = ChatCompletionInputDTO {
+        tenant_id: user.tenant_id,
+        user_id: user.user_id,
+        chat_id: Some(chat_id),
+        user_message: text,
+        attachments: vec![],
+        template: None,
+        assistant: None,
+        idempotency_key: None,
+        overrides,
+        labels: ChatLabelsInputDTO::default(),
+    };
+    let (writer, subscription) = state.streams.open(user.user_id);
+    tokio::spawn(request_id::inherit(produce(
+        state.clone(),
+        input,
+        writer,
+        Some(in_flight),
+    )));
+
+    forward_stream(sink, subscription).await
+}
+
+// resume sends what the client missed of the stream it was following, and the rest of it while
+// it is still streamed; returns false once the socket is gone
+async fn resume(
+    state: &AppState,
+    sink: &mut SplitSink<WebSocket, WsMessage>,
+    user: AuthenticatedUser,
+    last_event_id: &str,
+) -> bool {
+    let subscription = parse_event_id(last_event_id)
+        .and_then(|(stream_id, seq)| state.streams.resume(stream_id, user.user_id, seq));
+
+    match subscription {
+        Some(subscription) => forward_stream(sink, subscription).await,
+        None => {
+            let event = ServerEvent::Error {
+                code: StatusCode::NOT_FOUND.as_u16(),
+                error: "the stream expired or never existed".to_string(),
+            };
+            send_event(sink, &event).await.is_ok()
+        }
+    }
+}
+
+// forward_stream sends the chunks of the stream until it ends, returns false once the socket is
+// gone
+async fn forward_stream(
+    sink: &mut SplitSink<WebSocket, WsMessage>,
+    mut subscription: StreamSubscription<StreamItem>,
+) -> bool {
+    while let Some((seq, item)) = subscription.next().await {
+        let id = event_id(subscription.id(), seq);
+        let event = match item {
+            StreamItem::Delta(output) => ServerEvent::Delta {
+                id,
+                chat_id: output.chat_id,
+                content: output.content,
+            },
+            StreamItem::Done(output) => ServerEvent::Done {
+                id,
+                chat_id: output.chat_id,
+                content: output.content,
+            },
+            StreamItem::Error { code, error } => ServerEvent::Error { code, error },
+        };
+        if send_event(sink, &event).await.is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+async fn chat_active(state: &AppState, chat_id: Uuid, user: AuthenticatedUser) -> bool {
+    matches!(
+        state.get_chat.execute(user.tenant_id, chat_id, user.user_id).await,
+        Ok(chat) if chat.status == "active"
+    )
+}
+
+async fn send_event(
+    sink: &mut SplitSink<WebSocket, WsMessage>,
+    event: &ServerEvent,
+) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).unwrap_or_default();
+    sink.send(WsMessage::Text(text)).await
+}
+
+async fn close(sink: &mut SplitSink<WebSocket, WsMessage>, code: u16, reason: &'static str) {
+    let frame = CloseFrame {
+        code,
+        reason: reason.into(),
+    };
+    let _ = sink.send(WsMessage::Close(Some(frame))).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_event_serialization() {
+        let chat_id = Uuid::new_v4();
+        let event = ServerEvent::Delta {
+            id: "stream:1".to_string(),
+            chat_id,
+            content: "Hi".to_string(),
+        };
+
+        let value = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(value["type"], "delta");
+        assert_eq!(value["id"], "stream:1");
+        assert_eq!(value["chat_id"], chat_id.to_string());
+        assert_eq!(value["content"], "Hi");
+
+        let value = serde_json::to_value(ServerEvent::Error {
+            code: 404,
+            error: "chat not found".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(value["type"], "error");
+        assert_eq!(value["code"], 404);
+    }
+}
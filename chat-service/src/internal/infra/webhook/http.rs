@@ -0,0 +1,124 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Url;
+
+use crate::internal::domain::entity::webhook::{is_public_ip, Webhook, WebhookDelivery};
+use crate::internal::domain::gateway::webhook::{WebhookError, WebhookSender};
+use crate::internal::infra::webhook::signature::{sign, EVENT_HEADER, ID_HEADER, SIGNATURE_HEADER};
+
+// HttpWebhookSender POSTs the payload as JSON, a call that takes longer than the timeout fails;
+// redirects are not followed and only public addresses are called, whatever the host resolves
+// to by the time of the call
+pub struct HttpWebhookSender {
+    client: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(timeout: Duration) -> Result<Self, WebhookError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .map_err(|e| WebhookError(e.to_string()))?;
+
+        Ok(Self { client })
+    }
+}
+
+// PublicResolver resolves the hosts of webhooks without the addresses that are not public, so a
+// name pointed at an internal service after it was registered is not called
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+// public_url parses the URL of a webhook, the addresses written in it are never resolved so
+// they are checked here
+fn public_url(url: &str) -> Result<Url, WebhookError> {
+    let url = Url::parse(url).map_err(|e| WebhookError(format!("{}: {}", url, e)))?;
+    if url.scheme() != "https" {
+        return Err(WebhookError(format!("{} is not an https URL", url)));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| WebhookError(format!("{} has no host", url)))?;
+    let ip = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>();
+    if matches!(ip, Ok(ip) if !is_public_ip(ip)) {
+        return Err(WebhookError(format!("{} is not a public host", url)));
+    }
+
+    Ok(url)
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(
+        &self,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery,
+    ) -> Result<(), WebhookError> {
+        let signature = sign(
+            &webhook.secret,
+            chrono::Utc::now().timestamp(),
+            &delivery.payload,
+        );
+        let url = public_url(&webhook.url)?;
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(ID_HEADER, delivery.event_id.to_string())
+            .header(EVENT_HEADER, delivery.event.to_string())
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| WebhookError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(WebhookError(format!(
+                "{} answered {}",
+                webhook.url,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn test_resolver_refuses_private_addresses() {
+        for host in ["localhost", "127.0.0.1", "10.0.0.1", "192.168.1.1"] {
+            let resolved = PublicResolver.resolve(Name::from_str(host).unwrap()).await;
+            assert!(resolved.is_err(), "{} was resolved", host);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use ring::hmac;
+
+// SIGNATURE_HEADER carries the signature of a webhook call as t=<unix seconds>,v1=<hex>, where
+// v1 is the HMAC-SHA256 of "<t>.<body>" under the secret of the webhook; receivers recompute it
+// and reject calls whose t is too old to stop replays
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+// ID_HEADER carries the id of the chat event, receivers dedupe on it
+pub const ID_HEADER: &str = "X-Webhook-Id";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+
+// sign returns the value of the signature header for the body sent at timestamp
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{}.{}", timestamp, body).as_bytes());
+
+    format!("t={},v1={}", timestamp, hex::encode(tag.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        let signature = sign("whsec_secret", 1_700_000_000, r#"{"id":"1"}"#);
+
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature.len(), "t=1700000000,v1=".len() + 64);
+        assert_eq!(
+            signature,
+            sign("whsec_secret", 1_700_000_000, r#"{"id":"1"}"#)
+        );
+        assert_ne!(
+            signature,
+            sign("whsec_other", 1_700_000_000, r#"{"id":"1"}"#)
+        );
+        assert_ne!(
+            signature,
+            sign("whsec_secret", 1_700_000_001, r#"{"id":"1"}"#)
+        );
+    }
+}
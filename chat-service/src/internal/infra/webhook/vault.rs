@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, Webhook, WebhookDelivery};
+use crate::internal::domain::redactor::Cipher;
+use crate::internal::domain::repository::chat::RepositoryError;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+
+// ENCRYPTED_SECRET_PREFIX marks the stored secrets that are encrypted, the ones stored before
+// encryption was configured are read as they are
+const ENCRYPTED_SECRET_PREFIX: &str = "enc:";
+
+// EncryptedWebhookRepository keeps the secrets of the webhooks encrypted in the repository, they
+// are only decrypted when the webhooks are read back to be called
+pub struct EncryptedWebhookRepository {
+    repository: Arc<dyn WebhookRepository>,
+    cipher: Arc<dyn Cipher>,
+}
+
+impl EncryptedWebhookRepository {
+    pub fn new(repository: Arc<dyn WebhookRepository>, cipher: Arc<dyn Cipher>) -> Self {
+        Self { repository, cipher }
+    }
+
+    fn seal(&self, webhook: &Webhook) -> Result<Webhook, RepositoryError> {
+        let ciphertext = self
+            .cipher
+            .encrypt(webhook.secret.as_bytes())
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(Webhook {
+            secret: format!("{}{}", ENCRYPTED_SECRET_PREFIX, STANDARD.encode(ciphertext)),
+            ..webhook.clone()
+        })
+    }
+
+    fn open(&self, mut webhook: Webhook) -> Result<Webhook, RepositoryError> {
+        let Some(sealed) = webhook.secret.strip_prefix(ENCRYPTED_SECRET_PREFIX) else {
+            return Ok(webhook);
+        };
+        let ciphertext = STANDARD
+            .decode(sealed)
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        let plaintext = self
+            .cipher
+            .decrypt(&ciphertext)
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        webhook.secret =
+            String::from_utf8(plaintext).map_err(|e| RepositoryError::Database(e.to_string()))?;
+
+        Ok(webhook)
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for EncryptedWebhookRepository {
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<(), RepositoryError> {
+        self.repository.create_webhook(&self.seal(webhook)?).await
+    }
+
+    async fn find_webhook_by_id(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<Option<Webhook>, RepositoryError> {
+        self.repository
+            .find_webhook_by_id(tenant_id, webhook_id)
+            .await?
+            .map(|webhook| self.open(webhook))
+            .transpose()
+    }
+
+    async fn list_webhooks(&self, tenant_id: Uuid) -> Result<Vec<Webhook>, RepositoryError> {
+        self.repository
+            .list_webhooks(tenant_id)
+            .await?
+            .into_iter()
+            .map(|webhook| self.open(webhook))
+            .collect()
+    }
+
+    async fn delete_webhook(
+        &self,
+        tenant_id: Uuid,
+        webhook_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        self.repository.delete_webhook(tenant_id, webhook_id).await
+    }
+
+    async fn enqueue_deliveries(
+        &self,
+        deliveries: &[WebhookDelivery],
+    ) -> Result<(), RepositoryError> {
+        self.repository.enqueue_deliveries(deliveries).await
+    }
+
+    async fn due_deliveries(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, RepositoryError> {
+        self.repository.due_deliveries(now, limit).await
+    }
+
+    async fn claim_delivery(
+        &self,
+        delivery_id: Uuid,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        lease_until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, RepositoryError> {
+        self.repository
+            .claim_delivery(delivery_id, next_attempt_at, lease_until)
+            .await
+    }
+
+    async fn reschedule_delivery(&self, delivery: &WebhookDelivery) -> Result<(), RepositoryError> {
+        self.repository.reschedule_delivery(delivery).await
+    }
+
+    async fn complete_delivery(&self, delivery_id: Uuid) -> Result<(), RepositoryError> {
+        self.repository.complete_delivery(delivery_id).await
+    }
+
+    async fn dead_letter(&self, dead_letter: &DeadLetter) -> Result<(), RepositoryError> {
+        self.repository.dead_letter(dead_letter).await
+    }
+
+    async fn list_dead_letters(
+        &self,
+        tenant_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>, RepositoryError> {
+        self.repository.list_dead_letters(tenant_id, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::redaction::cipher::AesGcmCipher;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+
+    #[tokio::test]
+    async fn test_secrets_are_stored_encrypted() {
+        let inner = Arc::new(InMemoryWebhookRepository::new());
+        let vault = EncryptedWebhookRepository::new(
+            inner.clone(),
+            Arc::new(AesGcmCipher::new(&[7u8; 32]).unwrap()),
+        );
+        let webhook =
+            Webhook::new(DEFAULT_TENANT_ID, "https://example.com/hooks", None, &[]).unwrap();
+        vault.create_webhook(&webhook).await.unwrap();
+
+        let stored = inner
+            .find_webhook_by_id(DEFAULT_TENANT_ID, webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.secret.starts_with(ENCRYPTED_SECRET_PREFIX));
+        assert!(!stored.secret.contains(&webhook.secret));
+
+        let found = vault
+            .find_webhook_by_id(DEFAULT_TENANT_ID, webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found, webhook);
+        assert_eq!(
+            vault.list_webhooks(DEFAULT_TENANT_ID).await.unwrap(),
+            vec![webhook]
+        );
+
+        // a secret stored before encryption was configured is read as it is
+        let legacy = Webhook::new(DEFAULT_TENANT_ID, "https://example.com/old", None, &[]).unwrap();
+        inner.create_webhook(&legacy).await.unwrap();
+        let found = vault
+            .find_webhook_by_id(DEFAULT_TENANT_ID, legacy.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.secret, legacy.secret);
+    }
+}
@@ -1 +1,7 @@
+pub mod app;
+pub mod config;
 pub mod domain;
+pub mod infra;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod usecase;
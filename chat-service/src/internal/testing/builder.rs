@@ -0,0 +1,213 @@
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+
+pub const SYSTEM_MESSAGE: &str = "You are a helpful assistant.";
+
+// test_model is the model the builders use unless told otherwise
+pub fn test_model() -> Model {
+    Model::new("gpt-3.5-turbo".to_string(), 4096)
+}
+
+// ChatBuilder starts from an empty active chat of the default tenant on the test model, tests
+// only set what they are about; the messages are taken as they are, without trimming or events
+#[derive(Debug, Clone)]
+pub struct ChatBuilder {
+    id: Uuid,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    system_message: String,
+    messages: Vec<Message>,
+    status: ChatStatus,
+    config: ChatConfig,
+    title: Option<String>,
+}
+
+impl Default for ChatBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatBuilder {
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: Uuid::new_v4(),
+            system_message: SYSTEM_MESSAGE.to_string(),
+            messages: vec![],
+            status: ChatStatus::Active,
+            config: ChatConfig::default_for(test_model()),
+            title: None,
+        }
+    }
+
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn tenant_id(mut self, tenant_id: Uuid) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn user_id(mut self, user_id: Uuid) -> Self {
+        self.user_id = user_id;
+        self
+    }
+
+    // model keeps the rest of the config, use config to change the sampling
+    pub fn model(mut self, model: Model) -> Self {
+        self.config.model = model;
+        self
+    }
+
+    pub fn config(mut self, config: ChatConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn system_message(mut self, content: &str) -> Self {
+        self.system_message = content.to_string();
+        self
+    }
+
+    pub fn message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    pub fn user_message(self, content: &str) -> Self {
+        let message = MessageBuilder::user(content)
+            .model(self.config.model.clone())
+            .build();
+        self.message(message)
+    }
+
+    pub fn assistant_message(self, content: &str) -> Self {
+        let message = MessageBuilder::assistant(content)
+            .model(self.config.model.clone())
+            .build();
+        self.message(message)
+    }
+
+    pub fn status(mut self, status: ChatStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn build(self) -> Chat {
+        let system = MessageBuilder::new(Role::System, &self.system_message)
+            .model(self.config.model.clone())
+            .build();
+        let mut chat = Chat::new(
+            self.id,
+            self.user_id,
+            system,
+            self.messages,
+            vec![],
+            self.status,
+            0,
+            self.config,
+        )
+        .with_tenant(self.tenant_id)
+        .with_title(self.title);
+        chat.refresh_token_usage();
+
+        chat
+    }
+}
+
+// MessageBuilder makes a message of the test model created now, its tokens are counted with
+// the model's encoding
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    id: Uuid,
+    role: Role,
+    content: String,
+    model: Model,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl MessageBuilder {
+    pub fn new(role: Role, content: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            role,
+            content: content.to_string(),
+            model: test_model(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn user(content: &str) -> Self {
+        Self::new(Role::User, content)
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        Self::new(Role::Assistant, content)
+    }
+
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn model(mut self, model: Model) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: chrono::DateTime<chrono::Utc>) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    pub fn build(self) -> Message {
+        Message::new(
+            self.id,
+            self.role,
+            &self.content,
+            0,
+            self.model,
+            self.created_at,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_builder() {
+        let user_id = Uuid::new_v4();
+        let chat = ChatBuilder::new()
+            .user_id(user_id)
+            .user_message("Hello!")
+            .assistant_message("Hi, how can I help?")
+            .title("Greetings")
+            .build();
+
+        assert_eq!(chat.user_id, user_id);
+        assert_eq!(chat.tenant_id, DEFAULT_TENANT_ID);
+        assert_eq!(chat.status, ChatStatus::Active);
+        assert_eq!(chat.initial_system_message().content, SYSTEM_MESSAGE);
+        assert_eq!(chat.messages.len(), 2);
+        assert_eq!(chat.messages[0].role, Role::User);
+        assert_eq!(chat.messages[1].content, "Hi, how can I help?");
+        assert_eq!(chat.title.as_deref(), Some("Greetings"));
+        assert!(chat.token_usage > 0);
+        assert!(chat.events.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+
+// DEFAULT_REPLY is what the fake answers once its script is played
+pub const DEFAULT_REPLY: &str = "Hi, how can I help?";
+
+enum Scripted {
+    Reply(Vec<String>),
+    Fail(GatewayError),
+    Hang,
+}
+
+// FakeCompletionGateway answers every completion from a script, in order, and with
+// DEFAULT_REPLY once the script is played; a streamed reply is sent chunk by chunk. The chats
+// it received are kept so tests can check what was sent to the model
+#[derive(Default)]
+pub struct FakeCompletionGateway {
+    script: Mutex<VecDeque<Scripted>>,
+    received: Mutex<Vec<Chat>>,
+}
+
+impl FakeCompletionGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // reply queues a reply, streamed as a single chunk
+    pub fn reply(self, content: &str) -> Self {
+        self.stream(&[content])
+    }
+
+    // stream queues a reply that is streamed in the given chunks
+    pub fn stream(self, chunks: &[&str]) -> Self {
+        self.push(Scripted::Reply(
+            chunks.iter().map(|chunk| chunk.to_string()).collect(),
+        ))
+    }
+
+    // fail queues a failed completion
+    pub fn fail(self, err: GatewayError) -> Self {
+        self.push(Scripted::Fail(err))
+    }
+
+    // hang queues a completion that is never answered, like a provider that stopped responding
+    pub fn hang(self) -> Self {
+        self.push(Scripted::Hang)
+    }
+
+    // received returns the chats sent to the model so far, oldest first
+    pub fn received(&self) -> Vec<Chat> {
+        self.received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn calls(&self) -> usize {
+        self.received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    fn push(self, scripted: Scripted) -> Self {
+        self.script
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push_back(scripted);
+        self
+    }
+
+    async fn next(&self, chat: &Chat) -> Result<Vec<String>, GatewayError> {
+        self.received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(chat.clone());
+
+        let scripted = self
+            .script
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front();
+        match scripted {
+            Some(Scripted::Reply(chunks)) => Ok(chunks),
+            Some(Scripted::Fail(err)) => Err(err),
+            Some(Scripted::Hang) => std::future::pending().await,
+            None => Ok(vec![DEFAULT_REPLY.to_string()]),
+        }
+    }
+}
+
+#[async_trait]
+impl ChatCompletionGateway for FakeCompletionGateway {
+    async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+        let chunks = self.next(chat).await?;
+
+        Ok(reply(chat, &chunks.concat()))
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        chat: &Chat,
+        sender: mpsc::Sender<String>,
+    ) -> Result<Message, GatewayError> {
+        let chunks = self.next(chat).await?;
+        for chunk in &chunks {
+            // like a provider, the reply is finished even when nobody listens anymore
+            let _ = sender.send(chunk.clone()).await;
+        }
+
+        Ok(reply(chat, &chunks.concat()))
+    }
+}
+
+fn reply(chat: &Chat, content: &str) -> Message {
+    Message::new(
+        Uuid::new_v4(),
+        Role::Assistant,
+        content,
+        0,
+        chat.config.model.clone(),
+        chrono::Utc::now(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::testing::builder::ChatBuilder;
+
+    #[tokio::test]
+    async fn test_script() {
+        let gateway = FakeCompletionGateway::new()
+            .reply("first")
+            .fail(GatewayError::EmptyResponse)
+            .stream(&["str", "eamed"]);
+        let chat = ChatBuilder::new().user_message("Hello!").build();
+
+        let reply = gateway.create_chat_completion(&chat).await.unwrap();
+        assert_eq!(reply.content, "first");
+        assert_eq!(reply.role, Role::Assistant);
+
+        let failed = gateway.create_chat_completion(&chat).await;
+        assert!(matches!(failed, Err(GatewayError::EmptyResponse)));
+
+        let (sender, mut receiver) = mpsc::channel(8);
+        let reply = gateway
+            .create_chat_completion_stream(&chat, sender)
+            .await
+            .unwrap();
+        assert_eq!(reply.content, "streamed");
+        assert_eq!(receiver.recv().await.as_deref(), Some("str"));
+        assert_eq!(receiver.recv().await.as_deref(), Some("eamed"));
+
+        let reply = gateway.create_chat_completion(&chat).await.unwrap();
+        assert_eq!(reply.content, DEFAULT_REPLY);
+
+        assert_eq!(gateway.calls(), 4);
+        assert_eq!(gateway.received()[0].id, chat.id);
+    }
+}
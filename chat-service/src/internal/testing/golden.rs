@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::{Chat, ChatConfig};
+use crate::internal::domain::entity::message::Role;
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+use crate::internal::testing::builder::{ChatBuilder, MessageBuilder};
+
+// UPDATE_GOLDEN_ENV makes assert_golden write the golden files instead of comparing against
+// them, e.g. UPDATE_GOLDEN=1 cargo test golden
+pub const UPDATE_GOLDEN_ENV: &str = "UPDATE_GOLDEN";
+
+// golden_path is where the golden file of the given name is committed
+pub fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.json", name))
+}
+
+// assert_golden compares the value, as pretty-printed JSON, with its committed golden file; a
+// missing golden file fails as well, so every new snapshot is reviewed before it is committed
+#[track_caller]
+pub fn assert_golden(name: &str, value: &impl Serialize) {
+    let actual = serde_json::to_string_pretty(value).expect("golden value serializes") + "\n";
+    let path = golden_path(name);
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("golden directory is writable");
+        }
+        std::fs::write(&path, actual).expect("golden file is writable");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "golden file {} cannot be read ({}), run with {}=1 to create it",
+            path.display(),
+            e,
+            UPDATE_GOLDEN_ENV
+        )
+    });
+    assert!(
+        actual == expected,
+        "{} does not match {}, run with {}=1 if the change is intended\n--- expected\n{}--- actual\n{}",
+        name,
+        path.display(),
+        UPDATE_GOLDEN_ENV,
+        expected,
+        actual
+    );
+}
+
+// golden_chat is the chat the provider payloads are snapshotted from, it sets every field a
+// provider maps: sampling, stop sequences, tools, a response schema, a summary, a tool round
+// trip and an image
+pub fn golden_chat(model: Model) -> Chat {
+    let config = ChatConfig::builder(model.clone())
+        .temperature(0.7)
+        .top_p(0.9)
+        .n(2)
+        .stop(vec!["\n\nUser:".to_string()])
+        .presence_penalty(0.5)
+        .frequency_penalty(0.25)
+        .tools(vec![ToolDefinition::new(
+            "get_weather",
+            "Current weather of a city",
+            serde_json::json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            }),
+        )])
+        .response_format(ResponseFormat::JsonSchema {
+            name: "forecast".to_string(),
+            schema: serde_json::json!({
+                "type": "object",
+                "properties": {"summary": {"type": "string"}},
+                "required": ["summary"]
+            }),
+            strict: true,
+        })
+        .build()
+        .expect("golden config is valid");
+    let message =
+        |role: Role, content: &str| MessageBuilder::new(role, content).model(model.clone());
+
+    ChatBuilder::new()
+        .config(config)
+        .system_message("You are a helpful weather assistant.")
+        .message(
+            message(
+                Role::System,
+                "Summary: the user lives in Lisbon and prefers Celsius.",
+            )
+            .build(),
+        )
+        .message(message(Role::User, "Will it rain today?").build())
+        .message(
+            message(Role::Assistant, "")
+                .build()
+                .with_tool_calls(vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: r#"{"city":"Lisbon"}"#.to_string(),
+                }]),
+        )
+        .message(
+            message(Role::Tool, r#"{"rain":0.8,"celsius":17}"#)
+                .build()
+                .with_tool_call_id("call_1"),
+        )
+        .message(message(Role::Assistant, r#"{"summary":"Likely rain, 17°C."}"#).build())
+        .message(
+            message(Role::User, "And what about this sky?")
+                .build()
+                .with_attachments(vec![Attachment::base64("image/png", "iVBORw0=")]),
+        )
+        .build()
+}
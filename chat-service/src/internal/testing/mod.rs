@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod gateway;
+pub mod golden;
+
+// the in-memory repositories double as fakes, InMemoryChatRepository keeps chats as saved
+pub use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
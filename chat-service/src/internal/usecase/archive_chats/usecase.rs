@@ -0,0 +1,251 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::chat::Chat;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::blob_store::{BlobError, BlobStore};
+use crate::internal::domain::history_codec::{decode_history, encode_history};
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct ArchiveChatsUseCase {
+    repository: Arc<dyn ChatRepository>,
+    blobs: Arc<dyn BlobStore>,
+    inactivity: Duration,
+    batch_size: usize,
+}
+
+impl ArchiveChatsUseCase {
+    // new archives the histories of the chats inactive for longer than the inactivity, loading
+    // batch_size chats at a time
+    pub fn new(
+        repository: Arc<dyn ChatRepository>,
+        blobs: Arc<dyn BlobStore>,
+        inactivity: Duration,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            repository,
+            blobs,
+            inactivity,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    // execute moves the histories of the inactive chats to the blob store and returns how many
+    // chats went cold; a chat saved while its history was uploaded is active again, it is
+    // skipped and its blob removed
+    #[instrument(name = "archive_chats", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let inactivity = chrono::Duration::from_std(self.inactivity)
+            .map_err(|_| UseCaseError::InvalidInput("inactivity is out of range".to_string()))?;
+        let inactive_before = chrono::Utc::now() - inactivity;
+
+        let mut archived = 0;
+        loop {
+            let chats = self
+                .repository
+                .list_inactive_chats(inactive_before, self.batch_size)
+                .await?;
+            let count = chats.len();
+            for mut chat in chats {
+                if self.archive(&mut chat).await? {
+                    archived += 1;
+                }
+            }
+            if count < self.batch_size {
+                return Ok(archived);
+            }
+        }
+    }
+
+    // archive uploads the history before the messages are removed from the database, so a
+    // failure in between leaves an orphaned blob rather than a lost history
+    async fn archive(&self, chat: &mut Chat) -> Result<bool, UseCaseError> {
+        let key = history_key(chat);
+        let content = encode_history(chat).map_err(|e| BlobError(e.to_string()))?;
+        self.blobs.put(&key, content).await?;
+        chat.archive_history(key.clone())?;
+
+        match self.repository.archive_chat_history(chat).await {
+            Ok(()) => Ok(true),
+            Err(RepositoryError::ConcurrentModification(_)) => {
+                if let Err(err) = self.blobs.delete(&key).await {
+                    tracing::warn!(error = %err, key, "could not remove the history of an active chat");
+                }
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+// history_key is unique per version so archiving a chat again never overwrites a blob that
+// is still to be removed
+fn history_key(chat: &Chat) -> String {
+    format!("{}/{}/{}.jsonl", chat.tenant_id, chat.id, chat.version)
+}
+
+// restore_history reads the archived history of a cold chat back into it and returns the key
+// of the blob, to be removed once the chat is saved with its messages
+pub async fn restore_history(
+    blobs: &dyn BlobStore,
+    chat: &mut Chat,
+) -> Result<Option<String>, UseCaseError> {
+    let Some(archive) = chat.history_archive.clone() else {
+        return Ok(None);
+    };
+    let content = blobs
+        .get(&archive.key)
+        .await?
+        .ok_or_else(|| BlobError(format!("history {} is missing", archive.key)))?;
+    let (messages, erased_messages) =
+        decode_history(&content).map_err(|e| BlobError(e.to_string()))?;
+    chat.restore_history(messages, erased_messages);
+
+    Ok(Some(archive.key))
+}
+
+// read_history reads the archived history of a cold chat back into it for a request that only
+// reads it, the chat stays cold in the repository; without a blob store the history cannot be
+// read and the chat is reported archived
+pub async fn read_history(
+    blobs: Option<&dyn BlobStore>,
+    chat: &mut Chat,
+) -> Result<(), UseCaseError> {
+    if !chat.is_cold() {
+        return Ok(());
+    }
+    let blobs = blobs.ok_or(ChatError::ChatArchived)?;
+    restore_history(blobs, chat).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    fn chat(status: ChatStatus) -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi, how can I help?"),
+            ],
+            vec![],
+            status,
+            0,
+            ChatConfig::default_for(model.clone()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let blobs = Arc::new(InMemoryBlobStore::new());
+        let mut deleted = chat(ChatStatus::Active);
+        deleted.delete().unwrap();
+        let chats = [
+            chat(ChatStatus::Active),
+            chat(ChatStatus::Ended),
+            chat(ChatStatus::Archived),
+            deleted,
+        ];
+        for chat in &chats {
+            repository.create_chat(chat).await.unwrap();
+        }
+
+        let recent = ArchiveChatsUseCase::new(
+            repository.clone(),
+            blobs.clone(),
+            Duration::from_secs(3600),
+            2,
+        );
+        assert_eq!(recent.execute().await.unwrap(), 0);
+
+        let usecase =
+            ArchiveChatsUseCase::new(repository.clone(), blobs.clone(), Duration::ZERO, 2);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(usecase.execute().await.unwrap(), 3);
+        assert_eq!(blobs.keys().len(), 3);
+        assert_eq!(usecase.execute().await.unwrap(), 0);
+
+        let mut cold = repository
+            .find_chat_by_id(chats[0].tenant_id, chats[0].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(cold.is_cold());
+        assert!(cold.messages.is_empty());
+        assert_eq!(cold.status, ChatStatus::Archived);
+        assert_eq!(cold.count_messages(), 2);
+
+        let archived_key = cold
+            .history_archive
+            .as_ref()
+            .map(|archive| archive.key.clone());
+        let key = restore_history(blobs.as_ref(), &mut cold).await.unwrap();
+        assert_eq!(key, archived_key);
+        assert!(!cold.is_cold());
+        assert_eq!(cold.messages, chats[0].messages);
+        assert_eq!(
+            restore_history(blobs.as_ref(), &mut cold).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_history() {
+        let blobs = InMemoryBlobStore::new();
+        let hot = chat(ChatStatus::Active);
+        let mut cold = hot.clone();
+        let content = encode_history(&cold).unwrap();
+        blobs.put("cold.jsonl", content).await.unwrap();
+        cold.archive_history("cold.jsonl".to_string()).unwrap();
+
+        assert!(matches!(
+            read_history(None, &mut cold.clone()).await,
+            Err(UseCaseError::Domain(ChatError::ChatArchived))
+        ));
+        read_history(Some(&blobs), &mut cold).await.unwrap();
+        assert_eq!(cold.messages, hot.messages);
+        assert_eq!(blobs.keys().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_restore_history_fails_without_blob() {
+        let mut chat = chat(ChatStatus::Active);
+        chat.archive_history("missing.jsonl".to_string()).unwrap();
+
+        assert!(matches!(
+            restore_history(&InMemoryBlobStore::new(), &mut chat).await,
+            Err(UseCaseError::Blob(_))
+        ));
+        assert!(chat.is_cold());
+    }
+}
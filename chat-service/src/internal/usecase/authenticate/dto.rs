@@ -0,0 +1,9 @@
+use uuid::Uuid;
+
+// AuthenticationOutputDTO is the user behind a credential and the tenant every request of
+// the user is scoped to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuthenticationOutputDTO {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+}
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::{hash_key, API_KEY_PREFIX};
+use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::gateway::token_verifier::{TokenVerifier, VerifiedToken};
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::authenticate::dto::AuthenticationOutputDTO;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct AuthenticateUseCase {
+    api_keys: Arc<dyn ApiKeyRepository>,
+    users: Arc<dyn UserRepository>,
+    token_verifier: Option<Arc<dyn TokenVerifier>>,
+    tenants: Arc<TenantRegistry>,
+}
+
+impl AuthenticateUseCase {
+    pub fn new(api_keys: Arc<dyn ApiKeyRepository>, users: Arc<dyn UserRepository>) -> Self {
+        Self {
+            api_keys,
+            users,
+            token_verifier: None,
+            tenants: Arc::new(TenantRegistry::new()),
+        }
+    }
+
+    // with_tenants accepts access tokens of the configured tenants
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = tenants;
+        self
+    }
+
+    // with_token_verifier accepts access tokens from an identity provider next to API keys
+    pub fn with_token_verifier(mut self, token_verifier: Arc<dyn TokenVerifier>) -> Self {
+        self.token_verifier = Some(token_verifier);
+        self
+    }
+
+    // execute resolves the user behind an API key or an access token along with its tenant
+    #[instrument(name = "authenticate", skip_all)]
+    pub async fn execute(&self, credential: &str) -> Result<AuthenticationOutputDTO, UseCaseError> {
+        if credential.starts_with(API_KEY_PREFIX) {
+            return self.authenticate_api_key(credential).await;
+        }
+
+        match &self.token_verifier {
+            Some(token_verifier) => self.authenticate_token(token_verifier, credential).await,
+            None => Err(UseCaseError::Unauthenticated),
+        }
+    }
+
+    // authenticate_api_key rejects unknown and revoked keys alike, the key belongs to the
+    // tenant of its user
+    async fn authenticate_api_key(
+        &self,
+        key: &str,
+    ) -> Result<AuthenticationOutputDTO, UseCaseError> {
+        let api_key = self
+            .api_keys
+            .find_api_key_by_hash(&hash_key(key))
+            .await?
+            .filter(|api_key| api_key.is_active())
+            .ok_or(UseCaseError::Unauthenticated)?;
+
+        self.users
+            .find_user_by_id(api_key.tenant_id, api_key.user_id)
+            .await?
+            .ok_or(UseCaseError::Unauthenticated)?;
+
+        Ok(AuthenticationOutputDTO {
+            user_id: api_key.user_id,
+            tenant_id: api_key.tenant_id,
+        })
+    }
+
+    // authenticate_token maps the token subject to a user, users seen for the first time are
+    // provisioned so the identity provider stays the source of truth
+    async fn authenticate_token(
+        &self,
+        token_verifier: &Arc<dyn TokenVerifier>,
+        token: &str,
+    ) -> Result<AuthenticationOutputDTO, UseCaseError> {
+        let verified = token_verifier
+            .verify(token)
+            .await
+            .map_err(|_| UseCaseError::Unauthenticated)?;
+        let tenant_id = self.tenant_of(&verified)?;
+        let authenticated = |user_id| AuthenticationOutputDTO { user_id, tenant_id };
+
+        if let Some(user) = self
+            .users
+            .find_user_by_external_id(tenant_id, &verified.subject)
+            .await?
+        {
+            return Ok(authenticated(user.id));
+        }
+
+        let user = User::new(
+            Uuid::new_v4(),
+            &verified.subject,
+            verified.name.as_deref().unwrap_or(&verified.subject),
+            chrono::Utc::now(),
+        )
+        .with_tenant(tenant_id);
+        user.validate().map_err(|_| UseCaseError::Unauthenticated)?;
+
+        if let Err(err) = self.users.create_user(&user).await {
+            // a concurrent request may have provisioned the same user first
+            return match self
+                .users
+                .find_user_by_external_id(tenant_id, &user.external_id)
+                .await?
+            {
+                Some(existing) => Ok(authenticated(existing.id)),
+                None => Err(err.into()),
+            };
+        }
+
+        Ok(authenticated(user.id))
+    }
+
+    // tenant_of returns the registered tenant the token claims, tokens without one belong to
+    // the default tenant
+    fn tenant_of(&self, verified: &VerifiedToken) -> Result<Uuid, UseCaseError> {
+        let tenant_id = match &verified.tenant {
+            Some(tenant) => Uuid::parse_str(tenant).map_err(|_| UseCaseError::Unauthenticated)?,
+            None => DEFAULT_TENANT_ID,
+        };
+
+        if !self.tenants.contains(tenant_id) {
+            return Err(UseCaseError::Unauthenticated);
+        }
+
+        Ok(tenant_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+
+    use crate::internal::domain::entity::api_key::ApiKey;
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig};
+    use crate::internal::domain::gateway::token_verifier::TokenError;
+    use crate::internal::infra::repository::memory::api_key::InMemoryApiKeyRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+
+    struct FakeTokenVerifier;
+
+    #[async_trait]
+    impl TokenVerifier for FakeTokenVerifier {
+        async fn verify(&self, token: &str) -> Result<VerifiedToken, TokenError> {
+            // tokens look like valid.<subject> or valid.<subject>@<tenant>
+            match token.strip_prefix("valid.") {
+                Some(claims) => {
+                    let (subject, tenant) = match claims.split_once('@') {
+                        Some((subject, tenant)) => (subject, Some(tenant.to_string())),
+                        None => (claims, None),
+                    };
+
+                    Ok(VerifiedToken {
+                        subject: subject.to_string(),
+                        name: Some("Ada".to_string()),
+                        tenant,
+                    })
+                }
+                None => Err(TokenError::Invalid("bad signature".to_string())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let api_keys = Arc::new(InMemoryApiKeyRepository::new());
+        let user = User::new(Uuid::new_v4(), "auth0|42", "Ada", chrono::Utc::now());
+        users.create_user(&user).await.unwrap();
+
+        let (api_key, key) = ApiKey::generate(user.tenant_id, user.id);
+        api_keys.create_api_key(&api_key).await.unwrap();
+        let (mut revoked, revoked_key) = ApiKey::generate(user.tenant_id, user.id);
+        revoked.revoke();
+        api_keys.create_api_key(&revoked).await.unwrap();
+
+        let usecase = AuthenticateUseCase::new(api_keys, users);
+
+        assert_eq!(
+            usecase.execute(&key).await.unwrap(),
+            AuthenticationOutputDTO {
+                user_id: user.id,
+                tenant_id: DEFAULT_TENANT_ID,
+            }
+        );
+        for key in ["", "chs_unknown", revoked_key.as_str(), "valid.auth0|42"] {
+            assert!(matches!(
+                usecase.execute(key).await,
+                Err(UseCaseError::Unauthenticated)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_token() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let existing = User::new(Uuid::new_v4(), "auth0|42", "Ada", chrono::Utc::now());
+        users.create_user(&existing).await.unwrap();
+
+        let usecase =
+            AuthenticateUseCase::new(Arc::new(InMemoryApiKeyRepository::new()), users.clone())
+                .with_token_verifier(Arc::new(FakeTokenVerifier));
+
+        assert_eq!(
+            usecase.execute("valid.auth0|42").await.unwrap().user_id,
+            existing.id
+        );
+
+        let provisioned = usecase.execute("valid.auth0|7").await.unwrap();
+        assert_eq!(usecase.execute("valid.auth0|7").await.unwrap(), provisioned);
+        let user = users
+            .find_user_by_id(DEFAULT_TENANT_ID, provisioned.user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.external_id, "auth0|7");
+        assert_eq!(user.display_name, "Ada");
+
+        assert!(matches!(
+            usecase.execute("forged.auth0|42").await,
+            Err(UseCaseError::Unauthenticated)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_token_tenant() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let acme = Uuid::new_v4();
+        let tenants = TenantRegistry::new()
+            .with_tenant(Tenant::new(acme, "Acme", TenantConfig::default()))
+            .unwrap();
+        let usecase =
+            AuthenticateUseCase::new(Arc::new(InMemoryApiKeyRepository::new()), users.clone())
+                .with_token_verifier(Arc::new(FakeTokenVerifier))
+                .with_tenants(Arc::new(tenants));
+
+        let default = usecase.execute("valid.auth0|42").await.unwrap();
+        let tenant = usecase
+            .execute(&format!("valid.auth0|42@{}", acme))
+            .await
+            .unwrap();
+
+        assert_eq!(tenant.tenant_id, acme);
+        assert_ne!(tenant.user_id, default.user_id);
+        for token in [
+            format!("valid.auth0|42@{}", Uuid::new_v4()),
+            "valid.auth0|42@acme".to_string(),
+        ] {
+            assert!(matches!(
+                usecase.execute(&token).await,
+                Err(UseCaseError::Unauthenticated)
+            ));
+        }
+    }
+}
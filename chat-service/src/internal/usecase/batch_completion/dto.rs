@@ -0,0 +1,60 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::{Batch, BatchItem, BatchStatus};
+use crate::internal::usecase::chat_completion::dto::ChatOverridesInputDTO;
+
+// BatchCompletionInputDTO sends independent prompts of a user at once, each answered in a new
+// chat with the same overrides
+#[derive(Debug, Clone, Default)]
+pub struct BatchCompletionInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub prompts: Vec<String>,
+    pub overrides: ChatOverridesInputDTO,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchItemOutputDTO {
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&BatchItem> for BatchItemOutputDTO {
+    fn from(item: &BatchItem) -> Self {
+        Self {
+            prompt: item.prompt.clone(),
+            chat_id: item.chat_id,
+            content: item.content.clone(),
+            error: item.error.clone(),
+        }
+    }
+}
+
+// BatchOutputDTO lists the prompts in the order they were sent, with their answers once the
+// batch is completed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchOutputDTO {
+    pub id: Uuid,
+    pub status: BatchStatus,
+    pub items: Vec<BatchItemOutputDTO>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<&Batch> for BatchOutputDTO {
+    fn from(batch: &Batch) -> Self {
+        Self {
+            id: batch.id,
+            status: batch.status,
+            items: batch.items.iter().map(BatchItemOutputDTO::from).collect(),
+            created_at: batch.created_at,
+            completed_at: batch.completed_at,
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::sync::Arc;
+
+use futures_util::stream::{self, StreamExt};
+use tracing::instrument;
+
+use crate::internal::domain::entity::batch::{Batch, BatchItem};
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::usecase::batch_completion::dto::{BatchCompletionInputDTO, BatchOutputDTO};
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::error::UseCaseError;
+
+// BatchCompletionUseCase answers the prompts of a batch through the chat completion use case,
+// so each of them is limited, moderated and charged like any other message; at most
+// concurrency prompts are sent to the model at a time
+pub struct BatchCompletionUseCase {
+    batches: Arc<dyn BatchRepository>,
+    jobs: Arc<dyn JobRepository>,
+    chat_completion: Arc<ChatCompletionUseCase>,
+    max_prompts: usize,
+    concurrency: usize,
+}
+
+impl BatchCompletionUseCase {
+    pub fn new(
+        batches: Arc<dyn BatchRepository>,
+        jobs: Arc<dyn JobRepository>,
+        chat_completion: Arc<ChatCompletionUseCase>,
+        max_prompts: usize,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            batches,
+            jobs,
+            chat_completion,
+            max_prompts,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    // execute answers every prompt of the batch and returns the completed batch
+    #[instrument(name = "batch_completion", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: BatchCompletionInputDTO,
+    ) -> Result<BatchOutputDTO, UseCaseError> {
+        let batch = self.check(&input)?;
+        self.batches.create_batch(&batch).await?;
+
+        self.run(batch, &input.overrides).await
+    }
+
+    // queue stores the batch and queues a completion job per prompt, the job worker answers
+    // them so that a batch outlives the instance it was sent to; get_batch completes the batch
+    // once every job is done
+    #[instrument(name = "batch_completion_queue", skip_all, fields(user_id = %input.user_id))]
+    pub async fn queue(&self, input: BatchCompletionInputDTO) -> Result<Batch, UseCaseError> {
+        let mut batch = self.check(&input)?;
+        let overrides = serde_json::to_value(&input.overrides)
+            .map_err(|e| UseCaseError::InvalidInput(e.to_string()))?;
+        let jobs = batch
+            .items
+            .iter_mut()
+            .map(|item| {
+                let job = Job::new(
+                    input.tenant_id,
+                    input.user_id,
+                    None,
+                    &item.prompt,
+                    vec![],
+                    overrides.clone(),
+                )?;
+                item.job_id = Some(job.id);
+                Ok(job)
+            })
+            .collect::<Result<Vec<_>, UseCaseError>>()?;
+
+        self.batches.create_batch(&batch).await?;
+        for job in &jobs {
+            self.jobs.create_job(job).await?;
+        }
+
+        Ok(batch)
+    }
+
+    // check builds the batch once its size and overrides are checked
+    fn check(&self, input: &BatchCompletionInputDTO) -> Result<Batch, UseCaseError> {
+        if input.prompts.len() > self.max_prompts {
+            return Err(UseCaseError::InvalidInput(format!(
+                "a batch has at most {} prompts",
+                self.max_prompts
+            )));
+        }
+        // the overrides are the same for every prompt, a bad one would fail them all
+        self.chat_completion
+            .overrides_for(&prompt_input(input, String::new()))?;
+
+        Ok(Batch::new(
+            input.tenant_id,
+            input.user_id,
+            input.prompts.clone(),
+        )?)
+    }
+
+    // run answers the prompts of a stored batch in a new chat each; a prompt that fails keeps
+    // its error and does not stop the others
+    #[instrument(name = "batch_completion_run", skip_all, fields(batch_id = %batch.id))]
+    async fn run(
+        &self,
+        mut batch: Batch,
+        overrides: &ChatOverridesInputDTO,
+    ) -> Result<BatchOutputDTO, UseCaseError> {
+        let input = BatchCompletionInputDTO {
+            tenant_id: batch.tenant_id,
+            user_id: batch.user_id,
+            prompts: vec![],
+            overrides: overrides.clone(),
+        };
+        let items = stream::iter(batch.items.clone())
+            .map(|item| self.answer(&input, item))
+            .buffered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        batch.complete(items);
+        self.batches.complete_batch(&batch).await?;
+
+        Ok(BatchOutputDTO::from(&batch))
+    }
+
+    async fn answer(&self, input: &BatchCompletionInputDTO, item: BatchItem) -> BatchItem {
+        match self
+            .chat_completion
+            .execute(prompt_input(input, item.prompt.clone()))
+            .await
+        {
+            Ok(output) => BatchItem {
+                chat_id: Some(output.chat_id),
+                content: Some(output.content),
+                ..item
+            },
+            Err(err) => BatchItem {
+                error: Some(err.to_string()),
+                ..item
+            },
+        }
+    }
+}
+
+// prompt_input is the completion request of a prompt of the batch, in a new chat
+fn prompt_input(input: &BatchCompletionInputDTO, prompt: String) -> ChatCompletionInputDTO {
+    ChatCompletionInputDTO {
+        tenant_id: input.tenant_id,
+        user_id: input.user_id,
+        chat_id: None,
+        user_message: prompt,
+        attachments: vec![],
+        template: None,
+        assistant: None,
+        idempotency_key: None,
+        overrides: input.overrides.clone(),
+        labels: ChatLabelsInputDTO::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::batch::BatchStatus;
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::job::JobStatus;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::GatewayError;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::batch::InMemoryBatchRepository;
+    use crate::internal::infra::repository::memory::job::InMemoryJobRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    async fn setup(
+        gateway: FakeCompletionGateway,
+        concurrency: usize,
+    ) -> (
+        BatchCompletionUseCase,
+        Arc<InMemoryBatchRepository>,
+        Arc<InMemoryJobRepository>,
+        Uuid,
+    ) {
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let chat_completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(gateway),
+            Arc::new(InMemoryChatRepository::new()),
+            Arc::new(users),
+            test_model(),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+        let batches = Arc::new(InMemoryBatchRepository::new());
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let usecase = BatchCompletionUseCase::new(
+            batches.clone(),
+            jobs.clone(),
+            chat_completion,
+            3,
+            concurrency,
+        );
+
+        (usecase, batches, jobs, user_id)
+    }
+
+    fn input(user_id: Uuid, prompts: &[&str]) -> BatchCompletionInputDTO {
+        BatchCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            prompts: prompts.iter().map(|prompt| prompt.to_string()).collect(),
+            overrides: ChatOverridesInputDTO::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_every_prompt() {
+        let gateway = FakeCompletionGateway::new()
+            .reply("Paris")
+            .fail(GatewayError::EmptyResponse)
+            .reply("Rome");
+        let (usecase, batches, _, user_id) = setup(gateway, 1).await;
+
+        let output = usecase
+            .execute(input(user_id, &["France?", "Spain?", "Italy?"]))
+            .await
+            .unwrap();
+
+        assert_eq!(output.status, BatchStatus::Completed);
+        assert_eq!(output.items[0].content.as_deref(), Some("Paris"));
+        assert!(output.items[1].content.is_none());
+        assert!(output.items[1].error.is_some());
+        assert_eq!(output.items[2].prompt, "Italy?");
+        assert_eq!(output.items[2].content.as_deref(), Some("Rome"));
+        assert_ne!(output.items[0].chat_id, output.items[2].chat_id);
+
+        let stored = batches
+            .find_batch_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(BatchOutputDTO::from(&stored), output);
+    }
+
+    #[tokio::test]
+    async fn test_queue_queues_a_job_per_prompt() {
+        let (usecase, batches, jobs, user_id) = setup(FakeCompletionGateway::new(), 2).await;
+
+        assert!(matches!(
+            usecase.queue(input(user_id, &["1", "2", "3", "4"])).await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase.queue(input(user_id, &[])).await,
+            Err(UseCaseError::Domain(_))
+        ));
+
+        let batch = usecase.queue(input(user_id, &["1", "2"])).await.unwrap();
+        let stored = batches
+            .find_batch_by_id(DEFAULT_TENANT_ID, batch.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, BatchStatus::Running);
+        for item in &stored.items {
+            let job = jobs
+                .find_job_by_id(DEFAULT_TENANT_ID, item.job_id.unwrap())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(job.status, JobStatus::Queued);
+            assert_eq!(job.user_message, item.prompt);
+            assert_eq!(job.chat_id, None);
+        }
+    }
+}
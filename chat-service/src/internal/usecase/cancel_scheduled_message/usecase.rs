@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::ScheduledStatus;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct CancelScheduledMessageUseCase {
+    scheduled: Arc<dyn ScheduledMessageRepository>,
+}
+
+impl CancelScheduledMessageUseCase {
+    pub fn new(scheduled: Arc<dyn ScheduledMessageRepository>) -> Self {
+        Self { scheduled }
+    }
+
+    // execute cancels a message of the user that is still pending, a message already being
+    // sent or sent cannot be called back
+    #[instrument(name = "cancel_scheduled_message", skip_all, fields(scheduled_id = %scheduled_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        scheduled_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UseCaseError> {
+        let scheduled = self
+            .scheduled
+            .find_scheduled_message_by_id(tenant_id, scheduled_id)
+            .await?
+            .ok_or(UseCaseError::ScheduledMessageNotFound(scheduled_id))?;
+
+        if scheduled.user_id != user_id {
+            return Err(UseCaseError::Forbidden(scheduled_id));
+        }
+
+        let cancelled = self
+            .scheduled
+            .transition(
+                tenant_id,
+                scheduled_id,
+                ScheduledStatus::Pending,
+                ScheduledStatus::Cancelled,
+                None,
+            )
+            .await?;
+        if !cancelled {
+            return Err(UseCaseError::InvalidInput(format!(
+                "scheduled message {} is no longer pending",
+                scheduled_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::scheduled_message::ScheduledMessage;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::scheduled_message::InMemoryScheduledMessageRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryScheduledMessageRepository::new());
+        let user_id = Uuid::new_v4();
+        let scheduled = ScheduledMessage::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            Uuid::new_v4(),
+            "Remind me to stretch",
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        repository
+            .create_scheduled_message(&scheduled)
+            .await
+            .unwrap();
+        let usecase = CancelScheduledMessageUseCase::new(repository.clone());
+
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, scheduled.id, Uuid::new_v4())
+                .await,
+            Err(UseCaseError::Forbidden(_))
+        ));
+
+        usecase
+            .execute(DEFAULT_TENANT_ID, scheduled.id, user_id)
+            .await
+            .unwrap();
+        assert!(repository
+            .list_pending_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, scheduled.id, user_id)
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, Uuid::new_v4(), user_id)
+                .await,
+            Err(UseCaseError::ScheduledMessageNotFound(_))
+        ));
+    }
+}
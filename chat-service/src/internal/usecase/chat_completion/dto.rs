@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::TrimmingPolicy;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+
+#[derive(Debug, Clone)]
+pub struct ChatCompletionConfigInputDTO {
+    pub temperature: f32,
+    pub top_p: f32,
+    pub n: u32,
+    pub stop: Vec<String>,
+    pub max_tokens: usize,
+    pub presence_penalty: f32,
+    pub frequency_penalty: f32,
+    pub trimming_policy: TrimmingPolicy,
+    pub initial_system_message: String,
+    pub response_format: ResponseFormat,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatCompletionInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    // attachments are images sent with the user message, the chat model must read images
+    pub attachments: Vec<Attachment>,
+    // template builds the system message of a new chat instead of the configured one
+    pub template: Option<PromptTemplateInputDTO>,
+    // assistant starts a new chat with the instructions, model and tools of an assistant
+    pub assistant: Option<ChatAssistantInputDTO>,
+    // idempotency_key makes retries of the request return the first response
+    pub idempotency_key: Option<String>,
+    // overrides change the model and sampling of this turn only, the chat keeps its config
+    pub overrides: ChatOverridesInputDTO,
+    // labels tag a new chat and attach metadata to it, a stored chat is labelled by an update
+    pub labels: ChatLabelsInputDTO,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatLabelsInputDTO {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ChatLabelsInputDTO {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.metadata.is_empty()
+    }
+}
+
+// ChatOverridesInputDTO names a registry model and sampling parameters for a single turn,
+// unset fields keep the chat's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatOverridesInputDTO {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplateInputDTO {
+    pub name: String,
+    pub variables: HashMap<String, String>,
+}
+
+// ChatAssistantInputDTO names the assistant of a new chat, its instructions are filled with the
+// variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatAssistantInputDTO {
+    pub id: Uuid,
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionOutputDTO {
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    pub content: String,
+    // candidates are the other replies offered when the chat asks for n > 1, the chat continues
+    // from content unless one of them is selected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+}
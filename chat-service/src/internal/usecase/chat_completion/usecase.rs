@@ -0,0 +1,2842 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus, ConfigOverrides};
+use crate::internal::domain::entity::event::ChatEvent;
+use crate::internal::domain::entity::idempotency::{
+    fingerprint, validate_idempotency_key, IdempotencyRecord,
+};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::entity::tool::ToolDefinition;
+use crate::internal::domain::entity::usage::UsageRecord;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+use crate::internal::domain::language::{detect_language, response_instruction};
+use crate::internal::domain::memory_extractor::MemoryExtractor;
+use crate::internal::domain::message_indexer::MessageIndexer;
+use crate::internal::domain::moderator::Moderator;
+use crate::internal::domain::output_filter::OutputFilter;
+use crate::internal::domain::prompt_guard::PromptGuardPolicy;
+use crate::internal::domain::quota::QuotaEnforcer;
+use crate::internal::domain::rate_limiter::RateLimiter;
+use crate::internal::domain::redactor::Redactor;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+use crate::internal::domain::repository::idempotency::IdempotencyRepository;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::domain::repository::unit_of_work::UnitOfWork;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::summarizer::Summarizer;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::domain::title_generator::TitleGenerator;
+use crate::internal::domain::tool_registry::{ToolError, ToolRegistry};
+use crate::internal::domain::usage_tracker::UsageTracker;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatAssistantInputDTO, ChatCompletionConfigInputDTO, ChatCompletionInputDTO,
+    ChatCompletionOutputDTO, ChatOverridesInputDTO, PromptTemplateInputDTO,
+};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+// MAX_TOOL_ROUNDS bounds how many times the model may call tools before it has to answer
+pub const MAX_TOOL_ROUNDS: usize = 5;
+// MAX_SAVE_ATTEMPTS bounds how many times a reply is saved again on a fresh load of a chat
+// another request saved meanwhile
+pub const MAX_SAVE_ATTEMPTS: usize = 3;
+
+pub struct ChatCompletionUseCase {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    repository: Arc<dyn ChatRepository>,
+    users: Arc<dyn UserRepository>,
+    model: Model,
+    config: ChatCompletionConfigInputDTO,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    quota: Option<Arc<QuotaEnforcer>>,
+    usage_tracker: Option<Arc<UsageTracker>>,
+    summarizer: Option<Arc<Summarizer>>,
+    title_generator: Option<Arc<TitleGenerator>>,
+    message_indexer: Option<Arc<MessageIndexer>>,
+    memory_extractor: Option<Arc<MemoryExtractor>>,
+    tools: Option<Arc<ToolRegistry>>,
+    moderator: Option<Arc<Moderator>>,
+    prompt_guard: Option<Arc<PromptGuardPolicy>>,
+    redactor: Option<Arc<Redactor>>,
+    output_filter: Option<Arc<OutputFilter>>,
+    templates: Option<Arc<dyn PromptTemplateRepository>>,
+    assistants: Option<Arc<dyn AssistantRepository>>,
+    tenants: Option<Arc<TenantRegistry>>,
+    idempotency: Option<Arc<dyn IdempotencyRepository>>,
+    idempotency_ttl: Duration,
+    unit_of_work: Option<Arc<dyn UnitOfWork>>,
+}
+
+// LoadedChat is the chat a request replies in; a chat the request starts is only stored along
+// with its first reply, so a failed reply leaves no empty chat behind
+pub(crate) struct LoadedChat {
+    pub chat: Chat,
+    pub is_new: bool,
+}
+
+impl LoadedChat {
+    pub fn stored(chat: Chat) -> Self {
+        Self {
+            chat,
+            is_new: false,
+        }
+    }
+}
+
+// ChatDefaults are the model and config a new chat starts with, the assistant's when the input
+// names one
+pub(crate) struct ChatDefaults {
+    pub model: Model,
+    pub config: ChatCompletionConfigInputDTO,
+    pub assistant_id: Option<Uuid>,
+}
+
+// Exchange is what a reply adds to its chat, replayed on a fresh load when the chat was saved
+// by another request since it was loaded
+pub(crate) struct Exchange {
+    pub messages: Vec<Message>,
+    pub consumed: ChatEvent,
+    pub usage: Option<UsageRecord>,
+}
+
+impl ChatCompletionUseCase {
+    pub fn new(
+        gateway: Arc<dyn ChatCompletionGateway>,
+        repository: Arc<dyn ChatRepository>,
+        users: Arc<dyn UserRepository>,
+        model: Model,
+        config: ChatCompletionConfigInputDTO,
+    ) -> Self {
+        Self {
+            gateway,
+            repository,
+            users,
+            model,
+            config,
+            rate_limiter: None,
+            quota: None,
+            usage_tracker: None,
+            summarizer: None,
+            title_generator: None,
+            message_indexer: None,
+            memory_extractor: None,
+            tools: None,
+            moderator: None,
+            prompt_guard: None,
+            redactor: None,
+            output_filter: None,
+            templates: None,
+            assistants: None,
+            tenants: None,
+            idempotency: None,
+            idempotency_ttl: Duration::ZERO,
+            unit_of_work: None,
+        }
+    }
+
+    // with_rate_limiter enforces the per-user request and token budgets
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    // with_quota_enforcer refuses messages once a user or tenant used up its daily or monthly
+    // tokens
+    pub fn with_quota_enforcer(mut self, quota: Arc<QuotaEnforcer>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    // with_usage_tracker records the tokens and estimated cost of every completion
+    pub fn with_usage_tracker(mut self, usage_tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(usage_tracker);
+        self
+    }
+
+    // with_summarizer compresses the history of summarize_and_trim chats close to their budget
+    pub fn with_summarizer(mut self, summarizer: Arc<Summarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    // with_title_generator names new chats in the background once their first reply is saved
+    pub fn with_title_generator(mut self, title_generator: Arc<TitleGenerator>) -> Self {
+        self.title_generator = Some(title_generator);
+        self
+    }
+
+    // with_message_indexer embeds every saved exchange in the background for the chat search
+    pub fn with_message_indexer(mut self, message_indexer: Arc<MessageIndexer>) -> Self {
+        self.message_indexer = Some(message_indexer);
+        self
+    }
+
+    // with_memory_extractor remembers what users tell about themselves after every saved
+    // exchange and tells their new chats about it
+    pub fn with_memory_extractor(mut self, memory_extractor: Arc<MemoryExtractor>) -> Self {
+        self.memory_extractor = Some(memory_extractor);
+        self
+    }
+
+    // with_tools offers the registered tools to the model and runs the calls it makes
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    // with_moderator rejects user messages flagged by moderation before they reach the model
+    pub fn with_moderator(mut self, moderator: Arc<Moderator>) -> Self {
+        self.moderator = Some(moderator);
+        self
+    }
+
+    // with_prompt_guard screens user messages for prompt injection after moderation, they are
+    // blocked, flagged or annotated as the policy says
+    pub fn with_prompt_guard(mut self, prompt_guard: Arc<PromptGuardPolicy>) -> Self {
+        self.prompt_guard = Some(prompt_guard);
+        self
+    }
+
+    // with_redactor masks personal data in user messages before they reach any provider
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    // with_output_filter filters the assistant responses before they are saved or returned
+    pub fn with_output_filter(mut self, output_filter: Arc<OutputFilter>) -> Self {
+        self.output_filter = Some(output_filter);
+        self
+    }
+
+    // with_templates lets new chats build their system message from a prompt template
+    pub fn with_templates(mut self, templates: Arc<dyn PromptTemplateRepository>) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    // with_assistants lets new chats be started with an assistant, which then limits the tools
+    // offered in them
+    pub fn with_assistants(mut self, assistants: Arc<dyn AssistantRepository>) -> Self {
+        self.assistants = Some(assistants);
+        self
+    }
+
+    // with_tenants applies the default model configured for each tenant
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    // with_idempotency stores the responses of requests sent with an idempotency key for ttl,
+    // so their retries are answered without another completion
+    pub fn with_idempotency(
+        mut self,
+        idempotency: Arc<dyn IdempotencyRepository>,
+        ttl: Duration,
+    ) -> Self {
+        self.idempotency = Some(idempotency);
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    // with_unit_of_work commits the chat and the usage of a reply in one transaction,
+    // nothing is written when any of them fails
+    pub fn with_unit_of_work(mut self, unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        self.unit_of_work = Some(unit_of_work);
+        self
+    }
+
+    // model_for returns the model of the tenant, or the service one when it has no override
+    pub(crate) fn model_for(&self, tenant_id: Uuid) -> Model {
+        self.tenants
+            .as_ref()
+            .and_then(|tenants| tenants.model(tenant_id))
+            .unwrap_or_else(|| self.model.clone())
+    }
+
+    // check_model refuses models the tenant does not allow, chats keep the model they were
+    // created with so the restriction also applies to older chats
+    pub(crate) fn check_model(&self, tenant_id: Uuid, model: &Model) -> Result<(), UseCaseError> {
+        if let Some(tenants) = &self.tenants {
+            tenants.check_model(tenant_id, model)?;
+        }
+
+        Ok(())
+    }
+
+    // execute appends the user message to the chat, asks the model for a reply and persists both
+    #[instrument(name = "chat_completion", skip_all, fields(user_id = %input.user_id, chat_id))]
+    pub async fn execute(
+        &self,
+        input: ChatCompletionInputDTO,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let chat_id = input.chat_id.map(|id| id.to_string()).unwrap_or_default();
+        let template = input
+            .template
+            .as_ref()
+            .map(template_fingerprint)
+            .unwrap_or_default();
+        let assistant = input
+            .assistant
+            .as_ref()
+            .map(assistant_fingerprint)
+            .unwrap_or_default();
+        let attachments = attachments_fingerprint(&input.attachments);
+        let overrides = overrides_fingerprint(&input.overrides);
+        let fingerprint = fingerprint(&[
+            "send",
+            &chat_id,
+            &input.user_message,
+            &attachments,
+            &template,
+            &assistant,
+            &overrides,
+        ]);
+
+        self.idempotent(
+            input.tenant_id,
+            input.user_id,
+            input.idempotency_key.as_deref(),
+            &fingerprint,
+            self.complete(&input),
+        )
+        .await
+    }
+
+    // complete runs a completion request once its idempotency key, if any, is reserved
+    pub(crate) async fn complete(
+        &self,
+        input: &ChatCompletionInputDTO,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let overrides = self.overrides_for(input)?;
+        let user_message = new_user_message(
+            &self.model_for(input.tenant_id),
+            &input.user_message,
+            input.attachments.clone(),
+        )?;
+        let user_message = self
+            .admit(input.tenant_id, input.user_id, input.chat_id, user_message)
+            .await?;
+
+        let chat = self.load_or_create_chat(input).await?;
+        tracing::Span::current().record("chat_id", tracing::field::display(chat.chat.id));
+
+        self.reply_with_context(chat, user_message, None, &overrides)
+            .await
+    }
+
+    // overrides_for resolves the overrides of the input, an overriding model has to be one the
+    // tenant allows
+    pub(crate) fn overrides_for(
+        &self,
+        input: &ChatCompletionInputDTO,
+    ) -> Result<ConfigOverrides, UseCaseError> {
+        let overrides = resolve_overrides(&input.overrides)?;
+        if let Some(model) = &overrides.model {
+            self.check_model(input.tenant_id, model)?;
+        }
+
+        Ok(overrides)
+    }
+
+    // load_or_create_chat returns the chat the input continues, or a new one for the tenant model
+    // or the one of its assistant
+    pub(crate) async fn load_or_create_chat(
+        &self,
+        input: &ChatCompletionInputDTO,
+    ) -> Result<LoadedChat, UseCaseError> {
+        let defaults = chat_defaults(
+            self.assistants.as_deref(),
+            self.model_for(input.tenant_id),
+            &self.config,
+            input,
+        )
+        .await?;
+        // a new chat is checked before it is created, so a refused one leaves nothing behind
+        if input.chat_id.is_none() {
+            self.check_model(input.tenant_id, &defaults.model)?;
+        }
+
+        let mut loaded = load_or_create_chat(
+            self.repository.as_ref(),
+            self.users.as_ref(),
+            self.templates.as_deref(),
+            self.memory_extractor.as_deref(),
+            &defaults.model,
+            &defaults.config,
+            input,
+        )
+        .await?;
+        if loaded.is_new {
+            loaded.chat.assistant_id = defaults.assistant_id;
+        }
+
+        Ok(loaded)
+    }
+
+    // idempotent runs the request once per idempotency key; a retry with the same key gets the
+    // stored response back, and a failed request releases its key so it can be sent again
+    pub(crate) async fn idempotent<F, T>(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        key: Option<&str>,
+        fingerprint: &str,
+        request: F,
+    ) -> Result<T, UseCaseError>
+    where
+        F: Future<Output = Result<T, UseCaseError>>,
+        T: Serialize + DeserializeOwned,
+    {
+        let (Some(idempotency), Some(key)) = (&self.idempotency, key) else {
+            return request.await;
+        };
+        validate_idempotency_key(key).map_err(UseCaseError::InvalidInput)?;
+        let ttl = chrono::Duration::from_std(self.idempotency_ttl).map_err(|_| {
+            UseCaseError::InvalidInput("idempotency ttl is out of range".to_string())
+        })?;
+
+        let record = IdempotencyRecord::new(
+            tenant_id,
+            user_id,
+            key,
+            fingerprint,
+            chrono::Utc::now(),
+            ttl,
+        );
+        if let Some(existing) = idempotency.reserve(&record).await? {
+            if existing.fingerprint != fingerprint {
+                return Err(UseCaseError::IdempotencyKeyReused(key.to_string()));
+            }
+
+            let response = existing
+                .response
+                .ok_or_else(|| UseCaseError::IdempotencyKeyInProgress(key.to_string()))?;
+            return serde_json::from_value(response)
+                .map_err(|e| RepositoryError::Database(e.to_string()).into());
+        }
+
+        let output = match request.await {
+            Ok(output) => output,
+            Err(err) => {
+                if let Err(release_err) = idempotency.release(tenant_id, user_id, key).await {
+                    tracing::warn!(error = %release_err, "failed to release idempotency key");
+                }
+                return Err(err);
+            }
+        };
+
+        // the reply is already saved, a failure to store it only costs the replay
+        let stored = match serde_json::to_value(&output) {
+            Ok(response) => {
+                idempotency
+                    .complete(tenant_id, user_id, key, &response)
+                    .await
+            }
+            Err(e) => Err(RepositoryError::Database(e.to_string())),
+        };
+        if let Err(err) = stored {
+            tracing::warn!(error = %err, "failed to store idempotent response");
+        }
+
+        Ok(output)
+    }
+
+    // admit applies token quotas, the rate limit, redaction, moderation and the prompt guard to
+    // a user message before any work is done; the message to go on with has its personal data
+    // masked, moderation included, and the guard's annotation; the quota goes first so a
+    // request it refuses does not use up the rate limit
+    pub(crate) async fn admit(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        message: Message,
+    ) -> Result<Message, UseCaseError> {
+        if let Some(quota) = &self.quota {
+            quota.check(tenant_id, user_id).await?;
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(tenant_id, user_id)?;
+        }
+
+        self.screen(tenant_id, user_id, chat_id, message).await
+    }
+
+    // screen applies redaction, moderation and the prompt guard to a user message, for the
+    // messages a request brings along with the one it is admitted for
+    pub(crate) async fn screen(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        message: Message,
+    ) -> Result<Message, UseCaseError> {
+        let message = match &self.redactor {
+            Some(redactor) => redactor.redact(tenant_id, user_id, chat_id, message)?,
+            None => message,
+        };
+
+        if let Some(moderator) = &self.moderator {
+            moderator.check(user_id, chat_id, &message.content).await?;
+        }
+        let message = match &self.prompt_guard {
+            Some(prompt_guard) => {
+                prompt_guard
+                    .screen(tenant_id, user_id, chat_id, message)
+                    .await?
+            }
+            None => message,
+        };
+
+        Ok(message)
+    }
+
+    // record_redactions stores the originals of the masked messages of a chat saved elsewhere
+    pub(crate) async fn record_redactions(&self, chat: &mut Chat) {
+        record_redactions(self.redactor.as_deref(), chat).await;
+    }
+
+    // reply adds the user message to the chat, asks the model for a reply and persists both
+    pub(crate) async fn reply(
+        &self,
+        chat: LoadedChat,
+        user_message: Message,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        self.reply_with_context(chat, user_message, None, &ConfigOverrides::default())
+            .await
+    }
+
+    // reply_with_context replies like reply, the model also reads the context at the end of the
+    // system message and is prompted with the overrides; neither is saved with the chat
+    pub(crate) async fn reply_with_context(
+        &self,
+        chat: LoadedChat,
+        user_message: Message,
+        context: Option<&str>,
+        overrides: &ConfigOverrides,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let LoadedChat { mut chat, is_new } = chat;
+        // only the model that answers the turn has to be allowed
+        let model = overrides.model.as_ref().unwrap_or(&chat.config.model);
+        self.check_model(chat.tenant_id, model)?;
+        if let Some(tools) = &self.tools {
+            chat.config.tools = tools_for(tools, self.assistants.as_deref(), &chat).await?;
+        }
+
+        let mut added = vec![user_message.clone()];
+        chat.add_message(user_message)?;
+
+        if let Some(summarizer) = &self.summarizer {
+            summarizer.summarize_if_needed(&mut chat).await?;
+        }
+
+        let prompt = prompt_for(&chat, context, overrides)?;
+        let mut prompt_tokens = prompt.token_usage;
+        let mut response = self.gateway.create_chat_completion(&prompt).await?;
+        let mut completion_tokens = response.tokens;
+
+        for _ in 0..MAX_TOOL_ROUNDS {
+            if response.tool_calls.is_empty() {
+                break;
+            }
+
+            added.extend(answer_tool_calls(self.tools.as_deref(), &mut chat, response).await?);
+            let prompt = prompt_for(&chat, context, overrides)?;
+            prompt_tokens += prompt.token_usage;
+            response = self.gateway.create_chat_completion(&prompt).await?;
+            completion_tokens += response.tokens;
+        }
+        if !response.tool_calls.is_empty() {
+            return Err(UseCaseError::ToolRoundsExceeded(MAX_TOOL_ROUNDS));
+        }
+        chat.config
+            .response_format
+            .check_response(&response.content)?;
+        if let Some(output_filter) = &self.output_filter {
+            response = output_filter
+                .apply(chat.tenant_id, chat.user_id, response)
+                .await;
+        }
+
+        // the model that served the reply differs from the chat's one after a fallback
+        let served_model = response.model.clone();
+        let content = response.content.clone();
+        let candidates = response.candidates.clone();
+        added.push(response.clone());
+        chat.add_message(response)?;
+        let consumed = ChatEvent::TokensConsumed {
+            model: served_model.name.clone(),
+            prompt_tokens,
+            completion_tokens,
+        };
+        chat.record(consumed.clone());
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.consume_tokens(chat.tenant_id, chat.user_id, chat.token_usage);
+        }
+
+        let usage = self.usage_tracker.as_ref().map(|usage_tracker| {
+            usage_tracker.price(
+                chat.tenant_id,
+                chat.user_id,
+                chat.id,
+                &served_model,
+                prompt_tokens,
+                completion_tokens,
+            )
+        });
+        let exchange = Exchange {
+            messages: added,
+            consumed,
+            usage,
+        };
+        save_exchange(
+            self.repository.as_ref(),
+            self.unit_of_work.as_deref(),
+            self.usage_tracker.as_deref(),
+            &mut chat,
+            is_new,
+            &exchange,
+        )
+        .await?;
+        record_redactions(self.redactor.as_deref(), &mut chat).await;
+
+        if let Some(title_generator) = &self.title_generator {
+            title_generator.spawn(&chat);
+        }
+
+        if let Some(message_indexer) = &self.message_indexer {
+            message_indexer.spawn(&chat);
+        }
+
+        if let Some(memory_extractor) = &self.memory_extractor {
+            memory_extractor.spawn(&chat);
+        }
+
+        Ok(ChatCompletionOutputDTO {
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            content,
+            candidates,
+        })
+    }
+}
+
+// with_context returns the chat the way the model should see it, with the context appended to
+// its system message; the context is left out when the chat has no room left for it
+fn with_context<'a>(chat: &'a Chat, context: Option<&str>) -> Cow<'a, Chat> {
+    let Some(context) = context else {
+        return Cow::Borrowed(chat);
+    };
+
+    match chat.with_context(context) {
+        Ok(prompt) => Cow::Owned(prompt),
+        Err(err) => {
+            tracing::warn!(error = %err, "leaving the context out of the prompt");
+            Cow::Borrowed(chat)
+        }
+    }
+}
+
+// prompt_for returns the chat the way the model should see it for one turn, with the overrides
+// applied before the context so the context is left out when the overriding model has no room;
+// the instruction to answer in the response language of the chat follows the context
+pub(crate) fn prompt_for<'a>(
+    chat: &'a Chat,
+    context: Option<&str>,
+    overrides: &ConfigOverrides,
+) -> Result<Cow<'a, Chat>, ChatError> {
+    let context = match (context, chat.config.response_language.as_deref()) {
+        (Some(context), Some(language)) => Some(Cow::Owned(format!(
+            "{}\n\n{}",
+            context,
+            response_instruction(language)
+        ))),
+        (None, Some(language)) => Some(Cow::Owned(response_instruction(language))),
+        (context, None) => context.map(Cow::Borrowed),
+    };
+    let context = context.as_deref();
+
+    if overrides.is_empty() {
+        return Ok(with_context(chat, context));
+    }
+
+    let prompt = chat.with_overrides(overrides)?;
+    let prompt = with_context(&prompt, context).into_owned();
+
+    Ok(Cow::Owned(prompt))
+}
+
+// resolve_overrides looks the overriding model up in the registry, the sampling parameters are
+// checked once they are applied to the chat
+pub(crate) fn resolve_overrides(
+    overrides: &ChatOverridesInputDTO,
+) -> Result<ConfigOverrides, UseCaseError> {
+    Ok(ConfigOverrides {
+        model: overrides.model.as_deref().map(resolve_model).transpose()?,
+        temperature: overrides.temperature,
+        top_p: overrides.top_p,
+        stop: overrides.stop.clone(),
+        presence_penalty: overrides.presence_penalty,
+        frequency_penalty: overrides.frequency_penalty,
+    })
+}
+
+// overrides_fingerprint lists the overrides of a request so a retry with other ones is told
+// apart from the original
+pub(crate) fn overrides_fingerprint(overrides: &ChatOverridesInputDTO) -> String {
+    fingerprint(&[&format!("{:?}", overrides)])
+}
+
+// template_fingerprint renders a template input with its variables sorted, so equal inputs
+// always fingerprint the same
+pub(crate) fn template_fingerprint(template: &PromptTemplateInputDTO) -> String {
+    variables_fingerprint(&template.name, &template.variables)
+}
+
+// assistant_fingerprint renders an assistant input like template_fingerprint does
+pub(crate) fn assistant_fingerprint(assistant: &ChatAssistantInputDTO) -> String {
+    variables_fingerprint(&assistant.id.to_string(), &assistant.variables)
+}
+
+fn variables_fingerprint(name: &str, variables: &HashMap<String, String>) -> String {
+    let mut variables: Vec<_> = variables.iter().collect();
+    variables.sort();
+
+    fingerprint(
+        &std::iter::once(name)
+            .chain(
+                variables
+                    .into_iter()
+                    .flat_map(|(name, value)| [name.as_str(), value.as_str()]),
+            )
+            .collect::<Vec<_>>(),
+    )
+}
+
+// attachments_fingerprint lists the images of a request so a retry with other images is told
+// apart from the original
+pub(crate) fn attachments_fingerprint(attachments: &[Attachment]) -> String {
+    let urls: Vec<String> = attachments.iter().map(Attachment::data_url).collect();
+    fingerprint(&urls.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+// chat_defaults returns the model and config a new chat of the input starts with; an assistant
+// replaces the configured system message by its instructions and may change the model and the
+// temperature
+pub(crate) async fn chat_defaults(
+    assistants: Option<&dyn AssistantRepository>,
+    model: Model,
+    config: &ChatCompletionConfigInputDTO,
+    input: &ChatCompletionInputDTO,
+) -> Result<ChatDefaults, UseCaseError> {
+    let Some(requested) = &input.assistant else {
+        return Ok(ChatDefaults {
+            model,
+            config: config.clone(),
+            assistant_id: None,
+        });
+    };
+    if input.chat_id.is_some() {
+        return Err(UseCaseError::InvalidInput(
+            "an assistant only applies to new chats".to_string(),
+        ));
+    }
+    if input.template.is_some() {
+        return Err(UseCaseError::InvalidInput(
+            "a chat starts with either a template or an assistant".to_string(),
+        ));
+    }
+
+    let assistant = match assistants {
+        Some(assistants) => {
+            assistants
+                .find_assistant_by_id(input.tenant_id, requested.id)
+                .await?
+        }
+        None => None,
+    }
+    .ok_or(UseCaseError::AssistantNotFound(requested.id))?;
+
+    let mut config = config.clone();
+    config.initial_system_message = assistant.render_instructions(&requested.variables)?;
+    if let Some(temperature) = assistant.temperature {
+        config.temperature = temperature;
+    }
+    let model = match &assistant.model {
+        Some(name) => resolve_model(name)?,
+        None => model,
+    };
+
+    Ok(ChatDefaults {
+        model,
+        config,
+        assistant_id: Some(assistant.id),
+    })
+}
+
+// tools_for returns the tools offered in the chat; a chat started with an assistant is only
+// offered the ones it lists, and none once the assistant is deleted
+pub(crate) async fn tools_for(
+    tools: &ToolRegistry,
+    assistants: Option<&dyn AssistantRepository>,
+    chat: &Chat,
+) -> Result<Vec<ToolDefinition>, UseCaseError> {
+    let Some(assistant_id) = chat.assistant_id else {
+        return Ok(tools.definitions());
+    };
+    let assistant = match assistants {
+        Some(assistants) => {
+            assistants
+                .find_assistant_by_id(chat.tenant_id, assistant_id)
+                .await?
+        }
+        None => None,
+    };
+
+    Ok(tools
+        .definitions()
+        .into_iter()
+        .filter(|tool| {
+            assistant
+                .as_ref()
+                .is_some_and(|assistant| assistant.offers(&tool.name))
+        })
+        .collect())
+}
+
+// load_or_create_chat returns the chat referenced by the input or starts a new one for an
+// existing user, told about the memories of the user; the new chat is stored by save_exchange
+pub(crate) async fn load_or_create_chat(
+    repository: &dyn ChatRepository,
+    users: &dyn UserRepository,
+    templates: Option<&dyn PromptTemplateRepository>,
+    memories: Option<&MemoryExtractor>,
+    model: &Model,
+    config: &ChatCompletionConfigInputDTO,
+    input: &ChatCompletionInputDTO,
+) -> Result<LoadedChat, UseCaseError> {
+    if let Some(chat_id) = input.chat_id {
+        if input.template.is_some() {
+            return Err(UseCaseError::InvalidInput(
+                "a template only applies to new chats".to_string(),
+            ));
+        }
+        if !input.labels.is_empty() {
+            return Err(UseCaseError::InvalidInput(
+                "tags and metadata of a stored chat are changed by updating it".to_string(),
+            ));
+        }
+
+        let chat = repository
+            .find_chat_by_id(input.tenant_id, chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(chat_id));
+        }
+
+        return Ok(LoadedChat::stored(chat));
+    }
+
+    if users
+        .find_user_by_id(input.tenant_id, input.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(UseCaseError::UserNotFound(input.user_id));
+    }
+
+    if !input.attachments.is_empty() && !model.supports_vision() {
+        return Err(ChatError::AttachmentsNotSupported(model.name.clone()).into());
+    }
+
+    let system_message = match &input.template {
+        Some(template) => render_template(templates, template).await?,
+        None => config.initial_system_message.clone(),
+    };
+    let system_message = match memories {
+        Some(memories) => remember(memories, input, system_message).await,
+        None => system_message,
+    };
+    let mut chat =
+        new_chat(input.user_id, model, config, &system_message)?.with_tenant(input.tenant_id);
+    chat.set_tags(&input.labels.tags)?;
+    chat.set_metadata(input.labels.metadata.clone())?;
+    chat.validate()?;
+
+    Ok(LoadedChat { chat, is_new: true })
+}
+
+// remember adds the memories of the user to the system message of a new chat, the chat starts
+// without them when they cannot be read
+async fn remember(
+    memories: &MemoryExtractor,
+    input: &ChatCompletionInputDTO,
+    system_message: String,
+) -> String {
+    match memories
+        .remember(input.tenant_id, input.user_id, &system_message)
+        .await
+    {
+        Ok(system_message) => system_message,
+        Err(err) => {
+            tracing::warn!(error = %err, "starting the chat without the memories of the user");
+            system_message
+        }
+    }
+}
+
+// save_exchange stores the chat, created when it is new, and the usage of its reply; when
+// another request saved the chat meanwhile the exchange is replayed on a fresh load of it and
+// saved again, so neither reply is lost
+pub(crate) async fn save_exchange(
+    repository: &dyn ChatRepository,
+    unit_of_work: Option<&dyn UnitOfWork>,
+    usage_tracker: Option<&UsageTracker>,
+    chat: &mut Chat,
+    is_new: bool,
+    exchange: &Exchange,
+) -> Result<(), UseCaseError> {
+    let mut attempt = 1;
+    loop {
+        let saved = write_exchange(
+            repository,
+            unit_of_work,
+            usage_tracker,
+            chat,
+            is_new,
+            exchange.usage.as_ref(),
+        )
+        .await;
+        match saved {
+            Err(UseCaseError::Repository(RepositoryError::ConcurrentModification(_)))
+                if !is_new && attempt < MAX_SAVE_ATTEMPTS =>
+            {
+                tracing::debug!(chat_id = %chat.id, attempt, "chat was saved meanwhile, rebasing the reply");
+                *chat = rebase(repository, chat, exchange).await?;
+                attempt += 1;
+            }
+            saved => return saved,
+        }
+    }
+}
+
+// record_redactions stores the originals of the masked messages once their chat is saved, the
+// saved reply stands when they cannot be stored
+pub(crate) async fn record_redactions(redactor: Option<&Redactor>, chat: &mut Chat) {
+    let Some(redactor) = redactor else {
+        return;
+    };
+    if let Err(err) = redactor.record(chat).await {
+        tracing::error!(chat_id = %chat.id, error = %err, "could not record the originals of the masked messages");
+    }
+}
+
+// rebase loads the chat as it is stored now and adds the exchange to it again
+async fn rebase(
+    repository: &dyn ChatRepository,
+    stale: &Chat,
+    exchange: &Exchange,
+) -> Result<Chat, UseCaseError> {
+    let mut chat = repository
+        .find_chat_by_id(stale.tenant_id, stale.id)
+        .await?
+        .filter(|chat| !chat.is_deleted())
+        .ok_or(UseCaseError::ChatNotFound(stale.id))?;
+    chat.config.tools = stale.config.tools.clone();
+
+    // a revision is replayed from its original, which another edit may have erased meanwhile
+    if let Some(original) = exchange.messages.first().and_then(|m| m.revision_of) {
+        chat.rewind_to(original)
+            .map_err(|_| RepositoryError::ConcurrentModification(stale.id))?;
+    }
+    for message in &exchange.messages {
+        chat.add_message(message.clone())?;
+    }
+    chat.record(exchange.consumed.clone());
+
+    Ok(chat)
+}
+
+// write_exchange writes the chat and the usage once; with a unit of work they commit together,
+// otherwise each write goes straight to its repository
+async fn write_exchange(
+    repository: &dyn ChatRepository,
+    unit_of_work: Option<&dyn UnitOfWork>,
+    usage_tracker: Option<&UsageTracker>,
+    chat: &mut Chat,
+    is_new: bool,
+    usage: Option<&UsageRecord>,
+) -> Result<(), UseCaseError> {
+    let Some(unit_of_work) = unit_of_work else {
+        if is_new {
+            repository.create_chat(chat).await?;
+        }
+        repository.save_chat(chat).await?;
+        if let (Some(usage_tracker), Some(record)) = (usage_tracker, usage) {
+            usage_tracker.save(record).await?;
+        }
+
+        return Ok(());
+    };
+
+    // an early return drops the transaction, which rolls back what it wrote so far
+    let mut tx = unit_of_work.begin().await?;
+    if is_new {
+        tx.create_chat(chat).await?;
+    }
+    tx.save_chat(chat).await?;
+    if let Some(record) = usage {
+        tx.record_usage(record).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// render_template builds a system message from the named template and the given variables
+async fn render_template(
+    templates: Option<&dyn PromptTemplateRepository>,
+    input: &PromptTemplateInputDTO,
+) -> Result<String, UseCaseError> {
+    let template = match templates {
+        Some(templates) => templates.find_template_by_name(&input.name).await?,
+        None => None,
+    }
+    .ok_or_else(|| UseCaseError::TemplateNotFound(input.name.clone()))?;
+
+    Ok(template.render(&input.variables)?)
+}
+
+// answer_tool_calls adds the assistant tool request and the result of every call to the chat,
+// failed calls are reported to the model as their result so it can recover; only the tools
+// offered in the chat are run, the results are labelled with the speaker of the request; it
+// returns the messages it added
+pub(crate) async fn answer_tool_calls(
+    tools: Option<&ToolRegistry>,
+    chat: &mut Chat,
+    request: Message,
+) -> Result<Vec<Message>, UseCaseError> {
+    let calls = request.tool_calls.clone();
+    let speaker = request.speaker.clone();
+    let mut added = vec![request.clone()];
+    chat.add_message(request)?;
+
+    for call in &calls {
+        let offered = chat.config.tools.iter().any(|tool| tool.name == call.name);
+        let result = match tools {
+            Some(tools) if offered => tools.execute(call).await,
+            _ => Err(ToolError::Unknown(call.name.clone())),
+        };
+        let content = result.unwrap_or_else(|e| format!("error: {}", e));
+
+        let mut message = Message::new(
+            Uuid::new_v4(),
+            Role::Tool,
+            &content,
+            0,
+            chat.config.model.clone(),
+            chrono::Utc::now(),
+        )
+        .with_tool_call_id(&call.id);
+        if let Some(speaker) = &speaker {
+            message = message.with_speaker(speaker);
+        }
+        added.push(message.clone());
+        chat.add_message(message)?;
+    }
+
+    Ok(added)
+}
+
+// new_user_message builds and validates the message typed by the user with the images attached
+pub(crate) fn new_user_message(
+    model: &Model,
+    content: &str,
+    attachments: Vec<Attachment>,
+) -> Result<Message, UseCaseError> {
+    let message = Message::new(
+        Uuid::new_v4(),
+        Role::User,
+        content,
+        0,
+        model.clone(),
+        chrono::Utc::now(),
+    )
+    .with_attachments(attachments);
+    message.validate()?;
+
+    Ok(match detect_language(content) {
+        Some(language) => message.with_language(language),
+        None => message,
+    })
+}
+
+// new_chat builds an active chat seeded with the given system message
+pub(crate) fn new_chat(
+    user_id: Uuid,
+    model: &Model,
+    config: &ChatCompletionConfigInputDTO,
+    system_message: &str,
+) -> Result<Chat, UseCaseError> {
+    let chat_config = ChatConfig::builder(model.clone())
+        .temperature(config.temperature)
+        .top_p(config.top_p)
+        .n(config.n)
+        .stop(config.stop.clone())
+        // a tenant model may have a smaller context than the one the budget was sized for
+        .max_tokens(config.max_tokens.min(model.max_tokens as usize))
+        .presence_penalty(config.presence_penalty)
+        .frequency_penalty(config.frequency_penalty)
+        .trimming_policy(config.trimming_policy)
+        .response_format(config.response_format.clone())
+        .build()
+        .map_err(ChatError::from)?;
+
+    let initial_system_message = Message::new(
+        Uuid::new_v4(),
+        Role::System,
+        system_message,
+        0,
+        model.clone(),
+        chrono::Utc::now(),
+    );
+
+    let mut chat = Chat::new(
+        Uuid::new_v4(),
+        user_id,
+        initial_system_message,
+        vec![],
+        vec![],
+        ChatStatus::Active,
+        0,
+        chat_config,
+    );
+    chat.record(ChatEvent::ChatCreated {
+        model: model.name.clone(),
+    });
+
+    Ok(chat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use crate::internal::domain::entity::assistant::Assistant;
+    use crate::internal::domain::entity::chat::{ChatSummary, TrimmingPolicy};
+    use crate::internal::domain::entity::prompt_template::PromptTemplate;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+    use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::entity::user_memory::Memory;
+    use crate::internal::domain::gateway::chat_completion::GatewayError;
+    use crate::internal::domain::gateway::moderation::{ModerationGateway, ModerationResult};
+    use crate::internal::domain::output_filter::{OutputFilterConfig, OutputRule};
+    use crate::internal::domain::quota::{QuotaConfig, QuotaPeriod, QuotaScope};
+    use crate::internal::domain::rate_limiter::RateLimitConfig;
+    use crate::internal::domain::repository::chat::{
+        ChatCursor, MessageQuery, PurgedChat, TranscriptMatch,
+    };
+    use crate::internal::domain::repository::moderation::ModerationRepository;
+    use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+    use crate::internal::domain::repository::redaction::RedactionRepository;
+    use crate::internal::domain::repository::usage::UsageRepository;
+    use crate::internal::domain::repository::user_memory::MemoryRepository;
+    use crate::internal::infra::redaction::cipher::AesGcmCipher;
+    use crate::internal::infra::redaction::detector::RegexDetector;
+    use crate::internal::infra::repository::memory::assistant::InMemoryAssistantRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::idempotency::InMemoryIdempotencyRepository;
+    use crate::internal::infra::repository::memory::moderation::InMemoryModerationRepository;
+    use crate::internal::infra::repository::memory::prompt_template::InMemoryPromptTemplateRepository;
+    use crate::internal::infra::repository::memory::redaction::InMemoryRedactionRepository;
+    use crate::internal::infra::repository::memory::unit_of_work::InMemoryUnitOfWork;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::infra::repository::memory::user_memory::InMemoryMemoryRepository;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::usecase::chat_completion::dto::ChatLabelsInputDTO;
+
+    #[derive(Default)]
+    struct FakeRepository {
+        created: Mutex<Vec<Uuid>>,
+        saved: Mutex<Vec<(Uuid, usize)>>,
+    }
+
+    #[async_trait]
+    impl ChatRepository for FakeRepository {
+        async fn create_chat(&self, chat: &Chat) -> Result<(), RepositoryError> {
+            self.created.lock().unwrap().push(chat.id);
+            Ok(())
+        }
+
+        async fn find_chat_by_id(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<Chat>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn save_chat(&self, chat: &mut Chat) -> Result<(), RepositoryError> {
+            self.saved
+                .lock()
+                .unwrap()
+                .push((chat.id, chat.count_messages() + chat.erased_messages.len()));
+            Ok(())
+        }
+
+        async fn update_chat_title(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+            _title: &str,
+        ) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn list_chats_by_user(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_chat_summaries(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _tag: Option<&str>,
+            _after: Option<ChatCursor>,
+            _limit: usize,
+        ) -> Result<Vec<ChatSummary>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn find_chat_summary(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<ChatSummary>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn list_messages(
+            &self,
+            _tenant_id: Uuid,
+            _chat_id: Uuid,
+            _query: &MessageQuery,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn search_transcripts(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_inactive_chats(
+            &self,
+            _inactive_before: chrono::DateTime<chrono::Utc>,
+            _limit: usize,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn archive_chat_history(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn purge_deleted_chats(
+            &self,
+            _deleted_before: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<PurgedChat>, RepositoryError> {
+            Ok(vec![])
+        }
+    }
+
+    fn config() -> ChatCompletionConfigInputDTO {
+        ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        }
+    }
+
+    async fn users_with(user_id: Uuid) -> Arc<InMemoryUserRepository> {
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(
+                user_id,
+                &user_id.to_string(),
+                "Ada",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+
+        Arc::new(users)
+    }
+
+    #[tokio::test]
+    async fn test_execute_creates_chat() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        );
+
+        let output = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.user_id, user_id);
+        assert_eq!(output.content, "Hi, how can I help?");
+        assert_eq!(*repository.created.lock().unwrap(), vec![output.chat_id]);
+        assert_eq!(*repository.saved.lock().unwrap(), vec![(output.chat_id, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_labels_new_chats() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        );
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO {
+                tags: vec!["Support".to_string()],
+                metadata: [("ticket".to_string(), "T-42".to_string())].into(),
+            },
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.tags, vec!["support"]);
+        assert_eq!(chat.metadata["ticket"], "T-42");
+
+        assert!(matches!(
+            usecase
+                .execute(ChatCompletionInputDTO {
+                    chat_id: Some(output.chat_id),
+                    ..input.clone()
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ChatCompletionInputDTO {
+                    labels: ChatLabelsInputDTO {
+                        tags: vec!["not a tag".to_string()],
+                        ..Default::default()
+                    },
+                    ..input
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidTag(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tells_new_chats_the_memories() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let gateway = Arc::new(FakeCompletionGateway::new());
+        let memories = Arc::new(InMemoryMemoryRepository::new());
+        let user_id = Uuid::new_v4();
+        memories
+            .create_memories(
+                &[Memory::new(
+                    DEFAULT_TENANT_ID,
+                    user_id,
+                    Uuid::new_v4(),
+                    "The user is called Ana.",
+                )],
+                10,
+            )
+            .await
+            .unwrap();
+        let usecase = ChatCompletionUseCase::new(
+            gateway.clone(),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_memory_extractor(Arc::new(MemoryExtractor::new(
+            gateway.clone(),
+            memories,
+            10,
+        )));
+
+        usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        let system_message = gateway.received()[0]
+            .initial_system_message()
+            .content
+            .clone();
+        assert!(system_message.starts_with("You are a helpful assistant."));
+        assert!(system_message.ends_with("- The user is called Ana."));
+    }
+
+    #[tokio::test]
+    async fn test_execute_replays_idempotent_request() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_idempotency(
+            Arc::new(InMemoryIdempotencyRepository::new()),
+            Duration::from_secs(60),
+        );
+        let input = |user_message: &str| ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: user_message.to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: Some("retry-1".to_string()),
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase.execute(input("Hello!")).await.unwrap();
+        let replayed = usecase.execute(input("Hello!")).await.unwrap();
+
+        assert_eq!(replayed, output);
+        assert_eq!(*repository.created.lock().unwrap(), vec![output.chat_id]);
+
+        let result = usecase.execute(input("Bye!")).await;
+        assert!(matches!(
+            result,
+            Err(UseCaseError::IdempotencyKeyReused(key)) if key == "retry-1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_chat_not_found() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            Arc::new(FakeRepository::default()),
+            Arc::new(InMemoryUserRepository::new()),
+            model.clone(),
+            config(),
+        );
+        let chat_id = Uuid::new_v4();
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id: Uuid::new_v4(),
+                chat_id: Some(chat_id),
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(result, Err(UseCaseError::ChatNotFound(id)) if id == chat_id));
+    }
+
+    #[tokio::test]
+    async fn test_execute_empty_message() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            model.clone(),
+            config(),
+        );
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::InvalidMessage(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_invalid_config() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            ChatCompletionConfigInputDTO {
+                temperature: 3.0,
+                ..config()
+            },
+        );
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::InvalidConfig(_)))
+        ));
+        assert!(repository.created.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_user_not_found() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            Arc::new(InMemoryUserRepository::new()),
+            model,
+            config(),
+        );
+        let user_id = Uuid::new_v4();
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(result, Err(UseCaseError::UserNotFound(id)) if id == user_id));
+        assert!(repository.created.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_rate_limited() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_rate_limiter(Arc::new(RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 1,
+            tokens_per_minute: 0,
+        })));
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        assert!(usecase.execute(input.clone()).await.is_ok());
+        assert!(matches!(
+            usecase.execute(input).await,
+            Err(UseCaseError::RateLimited { retry_after }) if retry_after.as_secs() == 60
+        ));
+        assert_eq!(repository.created.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_records_usage() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let usage = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_usage_tracker(Arc::new(UsageTracker::new(usage.clone())));
+
+        usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        let today = chrono::Utc::now().date_naive();
+        let daily = usage
+            .list_daily_usage(DEFAULT_TENANT_ID, user_id, today, today)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].model, "gpt-3.5-turbo");
+        assert_eq!(daily[0].requests, 1);
+        assert!(daily[0].prompt_tokens > 0);
+        assert!(daily[0].completion_tokens > 0);
+        assert!(daily[0].cost > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_quota_exceeded() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let usage = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 2,
+            tokens_per_minute: 0,
+        }));
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_rate_limiter(rate_limiter.clone())
+        .with_usage_tracker(Arc::new(UsageTracker::new(usage.clone())))
+        .with_quota_enforcer(Arc::new(QuotaEnforcer::new(
+            usage,
+            QuotaConfig {
+                user_daily_tokens: 1,
+                ..QuotaConfig::default()
+            },
+        )));
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        assert!(usecase.execute(input.clone()).await.is_ok());
+        assert!(matches!(
+            usecase.execute(input).await,
+            Err(UseCaseError::QuotaExceeded(err))
+                if err.scope == QuotaScope::User && err.period == QuotaPeriod::Daily
+        ));
+        assert_eq!(repository.created.lock().unwrap().len(), 1);
+        // the refused request left the rate limit as it was
+        assert!(rate_limiter.acquire(DEFAULT_TENANT_ID, user_id).is_ok());
+    }
+
+    // ToolCallingGateway asks for the weather, and answers once a tool result is in the chat
+    // unless it is stubborn
+    struct ToolCallingGateway {
+        stubborn: bool,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for ToolCallingGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let model = chat.config.model.clone();
+            let result = chat
+                .messages
+                .iter()
+                .find(|message| message.role == Role::Tool)
+                .filter(|_| !self.stubborn);
+
+            let message = match result {
+                Some(result) => Message::new(
+                    Uuid::new_v4(),
+                    Role::Assistant,
+                    &format!("It is {}.", result.content),
+                    0,
+                    model,
+                    chrono::Utc::now(),
+                ),
+                None => Message::new(
+                    Uuid::new_v4(),
+                    Role::Assistant,
+                    "",
+                    0,
+                    model,
+                    chrono::Utc::now(),
+                )
+                .with_tool_calls(vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: r#"{"city":"Lisbon"}"#.to_string(),
+                }]),
+            };
+
+            Ok(message)
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_calls_tools() {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let tools = ToolRegistry::new()
+            .with_tool(
+                ToolDefinition::new(
+                    "get_weather",
+                    "Current weather of a city",
+                    serde_json::json!({"type": "object"}),
+                ),
+                |arguments: serde_json::Value| async move {
+                    Ok(format!(
+                        "sunny in {}",
+                        arguments["city"].as_str().unwrap_or("?")
+                    ))
+                },
+            )
+            .unwrap();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(ToolCallingGateway { stubborn: false }),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_tools(Arc::new(tools));
+
+        let output = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "What's the weather in Lisbon?".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.content, "It is sunny in Lisbon.");
+        // user message, tool request, tool result and the final answer
+        assert_eq!(*repository.saved.lock().unwrap(), vec![(output.chat_id, 4)]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_rounds_exceeded() {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(ToolCallingGateway { stubborn: true }),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            model,
+            config(),
+        );
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "What's the weather in Lisbon?".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(UseCaseError::ToolRoundsExceeded(MAX_TOOL_ROUNDS))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_response_format_mismatch() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            ChatCompletionConfigInputDTO {
+                response_format: ResponseFormat::JsonObject,
+                ..config()
+            },
+        );
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::ResponseFormatMismatch(_)))
+        ));
+        assert!(repository.saved.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_attachments() {
+        let user_id = Uuid::new_v4();
+        let users = users_with(user_id).await;
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "What is in this image?".to_string(),
+            attachments: vec![Attachment::url("https://example.com/cat.png")],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let repository = Arc::new(FakeRepository::default());
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users.clone(),
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        );
+        let result = usecase.execute(input.clone()).await;
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::AttachmentsNotSupported(name))) if name == "gpt-3.5-turbo"
+        ));
+        assert!(repository.created.lock().unwrap().is_empty());
+
+        let repository = Arc::new(FakeRepository::default());
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users,
+            Model::new("gpt-4o".to_string(), 128000),
+            config(),
+        );
+        let output = usecase.execute(input).await.unwrap();
+        assert_eq!(*repository.saved.lock().unwrap(), vec![(output.chat_id, 2)]);
+    }
+
+    struct FlagEverything;
+
+    #[async_trait]
+    impl ModerationGateway for FlagEverything {
+        async fn moderate(&self, _content: &str) -> Result<ModerationResult, GatewayError> {
+            Ok(ModerationResult {
+                flagged: true,
+                categories: vec!["harassment".to_string()],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_content_flagged() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(FakeRepository::default());
+        let flags = Arc::new(InMemoryModerationRepository::new());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_moderator(Arc::new(Moderator::new(
+            Arc::new(FlagEverything),
+            flags.clone(),
+        )));
+
+        let result = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "You are useless".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::ContentFlagged(_)))
+        ));
+        assert!(repository.created.lock().unwrap().is_empty());
+
+        let recorded = flags.list_flags_by_user(user_id).await.unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].chat_id, None);
+    }
+
+    // RecordingGateway keeps the last message of every chat it is sent
+    #[derive(Default)]
+    struct RecordingGateway {
+        sent: Mutex<Vec<Message>>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for RecordingGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            self.sent
+                .lock()
+                .unwrap()
+                .extend(chat.messages.last().cloned());
+            FakeCompletionGateway::new()
+                .create_chat_completion(chat)
+                .await
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_redacts_personal_data() {
+        let gateway = Arc::new(RecordingGateway::default());
+        let redactions = Arc::new(InMemoryRedactionRepository::new());
+        let redactor = Arc::new(Redactor::new(
+            vec![Arc::new(RegexDetector::email())],
+            Arc::new(AesGcmCipher::new(&[7u8; 32]).unwrap()),
+            redactions.clone(),
+        ));
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            gateway.clone(),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        )
+        .with_redactor(redactor.clone());
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Reply to ada@example.com".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+
+        let sent = gateway.sent.lock().unwrap().pop().unwrap();
+        assert_eq!(sent.content, "Reply to [EMAIL]");
+
+        let record = redactions
+            .find_redaction(DEFAULT_TENANT_ID, sent.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.chat_id, Some(output.chat_id));
+        assert_eq!(record.findings, vec!["email"]);
+        assert_eq!(
+            redactor.original(&record).unwrap(),
+            "Reply to ada@example.com"
+        );
+
+        // a turn that fails before its chat is saved leaves no original behind
+        let failing = Arc::new(FakeCompletionGateway::new().fail(GatewayError::EmptyResponse));
+        let usecase = ChatCompletionUseCase::new(
+            failing.clone(),
+            Arc::new(FakeRepository::default()),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        )
+        .with_redactor(redactor.clone());
+        assert!(usecase.execute(input).await.is_err());
+        let sent = &failing.received()[0];
+        let unsent = sent.messages.last().unwrap();
+        assert_eq!(unsent.content, "Reply to [EMAIL]");
+        assert!(redactions
+            .find_redaction(DEFAULT_TENANT_ID, unsent.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    // ConfigGateway keeps the config of every chat it is sent
+    #[derive(Default)]
+    struct ConfigGateway {
+        sent: Mutex<Vec<ChatConfig>>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for ConfigGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            self.sent.lock().unwrap().push(chat.config.clone());
+            FakeCompletionGateway::new()
+                .create_chat_completion(chat)
+                .await
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_overrides() {
+        let gateway = Arc::new(ConfigGateway::default());
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let acme = Tenant::new(
+            Uuid::new_v4(),
+            "Acme",
+            TenantConfig {
+                allowed_models: vec!["gpt-3.5-turbo".to_string()],
+                ..Default::default()
+            },
+        );
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        )
+        .with_tenants(Arc::new(
+            TenantRegistry::new().with_tenant(acme.clone()).unwrap(),
+        ));
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO {
+                model: Some("gpt-4o-mini".to_string()),
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+
+        let sent = gateway.sent.lock().unwrap().pop().unwrap();
+        assert_eq!(sent.model.name, "gpt-4o-mini");
+        assert_eq!(sent.temperature, 0.2);
+        assert_eq!(sent.top_p, config().top_p);
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.config.model.name, "gpt-3.5-turbo");
+        assert_eq!(chat.config.temperature, config().temperature);
+        assert_eq!(chat.messages[1].model.name, "gpt-4o-mini");
+
+        let unknown = ChatCompletionInputDTO {
+            overrides: ChatOverridesInputDTO {
+                model: Some("gpt-unknown".to_string()),
+                ..Default::default()
+            },
+            ..input.clone()
+        };
+        assert!(matches!(
+            usecase.execute(unknown).await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        let out_of_range = ChatCompletionInputDTO {
+            chat_id: Some(output.chat_id),
+            overrides: ChatOverridesInputDTO {
+                temperature: Some(3.0),
+                ..Default::default()
+            },
+            ..input.clone()
+        };
+        assert!(matches!(
+            usecase.execute(out_of_range).await,
+            Err(UseCaseError::Domain(ChatError::InvalidConfig(_)))
+        ));
+        let disallowed = ChatCompletionInputDTO {
+            tenant_id: acme.id,
+            ..input
+        };
+        assert!(matches!(
+            usecase.execute(disallowed).await,
+            Err(UseCaseError::Domain(ChatError::ModelNotAllowed(name))) if name == "gpt-4o-mini"
+        ));
+    }
+
+    // CandidatesGateway offers as many replies as the chat asks for
+    struct CandidatesGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for CandidatesGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let candidates = (1..chat.config.n)
+                .map(|n| format!("candidate {}", n))
+                .collect();
+
+            Ok(FakeCompletionGateway::new()
+                .create_chat_completion(chat)
+                .await?
+                .with_candidates(candidates))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_candidates() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(CandidatesGateway),
+            repository.clone(),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            ChatCompletionConfigInputDTO { n: 3, ..config() },
+        );
+
+        let output = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.content, "Hi, how can I help?");
+        assert_eq!(output.candidates, vec!["candidate 1", "candidate 2"]);
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.messages.len(), 2);
+        assert_eq!(chat.messages[1].candidates, output.candidates);
+    }
+
+    #[tokio::test]
+    async fn test_execute_filters_output() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let output_filter = Arc::new(OutputFilter::new(OutputFilterConfig {
+            rules: vec![OutputRule {
+                name: "candidate".to_string(),
+                pattern: r"candidate (\d)".to_string(),
+                replacement: Some("option $1".to_string()),
+            }],
+            max_length: 10,
+            ..Default::default()
+        }));
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(CandidatesGateway),
+            repository.clone(),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            ChatCompletionConfigInputDTO { n: 2, ..config() },
+        )
+        .with_output_filter(output_filter.clone());
+
+        let output = usecase
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.content, "Hi, how ca");
+        assert_eq!(output.candidates, vec!["option 1"]);
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.messages[1].content, output.content);
+        assert_eq!(chat.messages[1].candidates, output.candidates);
+        let stats = output_filter.stats().snapshot();
+        assert_eq!(stats.filtered, 1);
+        assert_eq!(stats.triggered["rule:candidate"], 1);
+        assert_eq!(stats.triggered["max_length"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_in_response_language() {
+        let gateway = Arc::new(FakeCompletionGateway::new());
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users_with(user_id).await,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            config(),
+        );
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Comment est-ce que je peux changer mon mot de passe ?".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+
+        let mut chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.messages[0].language.as_deref(), Some("fr"));
+        assert_eq!(chat.messages[1].language, None);
+        assert_eq!(
+            gateway.received()[0].initial_system_message().content,
+            "You are a helpful assistant."
+        );
+
+        // the instruction reaches the prompt, the chat keeps its system message
+        chat.set_response_language(Some("de".to_string())).unwrap();
+        repository.save_chat(&mut chat).await.unwrap();
+        usecase
+            .execute(ChatCompletionInputDTO {
+                chat_id: Some(output.chat_id),
+                ..input
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            gateway.received()[1].initial_system_message().content,
+            format!(
+                "You are a helpful assistant.\n\n{}",
+                response_instruction("de")
+            )
+        );
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You are a helpful assistant."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_template() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let templates = Arc::new(InMemoryPromptTemplateRepository::new());
+        templates
+            .create_template(&PromptTemplate::new(
+                Uuid::new_v4(),
+                "support",
+                "You help {{user_name}} with {{product}}.",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_templates(templates);
+        let input = |name: &str, variables: &[(&str, &str)]| ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: Some(PromptTemplateInputDTO {
+                name: name.to_string(),
+                variables: variables
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            }),
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase
+            .execute(input(
+                "support",
+                &[("user_name", "Ada"), ("product", "the billing API")],
+            ))
+            .await
+            .unwrap();
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You help Ada with the billing API."
+        );
+
+        assert!(matches!(
+            usecase
+                .execute(input("support", &[("user_name", "Ada")]))
+                .await,
+            Err(UseCaseError::Domain(ChatError::MissingTemplateVariables(missing)))
+                if missing == vec!["product".to_string()]
+        ));
+        assert!(matches!(
+            usecase.execute(input("sales", &[])).await,
+            Err(UseCaseError::TemplateNotFound(name)) if name == "sales"
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ChatCompletionInputDTO {
+                    chat_id: Some(chat.id),
+                    ..input("support", &[])
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_assistant() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let assistants = Arc::new(InMemoryAssistantRepository::new());
+        let billing = Assistant::new(
+            Uuid::new_v4(),
+            DEFAULT_TENANT_ID,
+            "billing",
+            "You answer {{user_name}} about invoices.",
+            chrono::Utc::now(),
+        )
+        .with_model(Some("gpt-4o-mini".to_string()))
+        .with_temperature(Some(0.2))
+        .with_tools(vec!["get_invoice".to_string()]);
+        assistants.create_assistant(&billing).await.unwrap();
+        let tool =
+            |name: &str| ToolDefinition::new(name, "", serde_json::json!({"type": "object"}));
+        let tools = Arc::new(
+            ToolRegistry::new()
+                .with_tool(tool("get_invoice"), |_: serde_json::Value| async {
+                    Ok(String::new())
+                })
+                .unwrap()
+                .with_tool(tool("get_weather"), |_: serde_json::Value| async {
+                    Ok(String::new())
+                })
+                .unwrap(),
+        );
+        let user_id = Uuid::new_v4();
+        let usecase = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users_with(user_id).await,
+            model,
+            config(),
+        )
+        .with_tools(tools.clone())
+        .with_assistants(assistants.clone());
+        let input = |id: Uuid| ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: Some(ChatAssistantInputDTO {
+                id,
+                variables: HashMap::from([("user_name".to_string(), "Ada".to_string())]),
+            }),
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+
+        let output = usecase.execute(input(billing.id)).await.unwrap();
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.assistant_id, Some(billing.id));
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You answer Ada about invoices."
+        );
+        assert_eq!(chat.config.model.name, "gpt-4o-mini");
+        assert_eq!(chat.config.temperature, 0.2);
+        let offered: Vec<String> = chat.config.tools.into_iter().map(|t| t.name).collect();
+        assert_eq!(offered, vec!["get_invoice"]);
+
+        assert!(matches!(
+            usecase.execute(input(Uuid::new_v4())).await,
+            Err(UseCaseError::AssistantNotFound(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ChatCompletionInputDTO {
+                    chat_id: Some(output.chat_id),
+                    ..input(billing.id)
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ChatCompletionInputDTO {
+                    template: Some(PromptTemplateInputDTO {
+                        name: "support".to_string(),
+                        variables: HashMap::new(),
+                    }),
+                    ..input(billing.id)
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+
+        // a chat whose assistant is gone is offered no tools at all
+        let mut chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assistants
+            .delete_assistant(DEFAULT_TENANT_ID, billing.id)
+            .await
+            .unwrap();
+        assert!(tools_for(&tools, Some(assistants.as_ref()), &chat)
+            .await
+            .unwrap()
+            .is_empty());
+        chat.assistant_id = None;
+        assert_eq!(
+            tools_for(&tools, Some(assistants.as_ref()), &chat)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    // UnavailableGateway fails every completion like a provider that is down
+    struct UnavailableGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for UnavailableGateway {
+        async fn create_chat_completion(&self, _chat: &Chat) -> Result<Message, GatewayError> {
+            Err(GatewayError::ProviderUnavailable("openai".to_string()))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_commits_unit_of_work() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let usage = Arc::new(InMemoryUsageRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWork::new(repository.clone(), usage.clone()));
+        let user_id = Uuid::new_v4();
+        let users = users_with(user_id).await;
+        let usecase = |gateway: Arc<dyn ChatCompletionGateway>| {
+            ChatCompletionUseCase::new(
+                gateway,
+                repository.clone(),
+                users.clone(),
+                model.clone(),
+                config(),
+            )
+            .with_usage_tracker(Arc::new(UsageTracker::new(usage.clone())))
+            .with_unit_of_work(unit_of_work.clone())
+        };
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+        let today = chrono::Utc::now().date_naive();
+
+        let result = usecase(Arc::new(UnavailableGateway))
+            .execute(input.clone())
+            .await;
+        assert!(matches!(result, Err(UseCaseError::Gateway(_))));
+        assert!(repository
+            .list_chats_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let output = usecase(Arc::new(FakeCompletionGateway::new()))
+            .execute(input.clone())
+            .await
+            .unwrap();
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.count_messages(), 2);
+
+        let result = usecase(Arc::new(UnavailableGateway))
+            .execute(ChatCompletionInputDTO {
+                chat_id: Some(chat.id),
+                user_message: "Are you there?".to_string(),
+                ..input
+            })
+            .await;
+        assert!(matches!(result, Err(UseCaseError::Gateway(_))));
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.count_messages(), 2);
+        let daily = usage
+            .list_daily_usage(DEFAULT_TENANT_ID, user_id, today, today)
+            .await
+            .unwrap();
+        assert_eq!(daily[0].requests, 1);
+    }
+
+    // InterleavedGateway saves a message to the chat while the reply is generated, like a
+    // request sent to the same chat at the same time
+    struct InterleavedGateway {
+        repository: Arc<InMemoryChatRepository>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for InterleavedGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let mut stored = self
+                .repository
+                .find_chat_by_id(chat.tenant_id, chat.id)
+                .await
+                .unwrap()
+                .unwrap();
+            stored
+                .add_message(Message::new(
+                    Uuid::new_v4(),
+                    Role::User,
+                    "Are you there?",
+                    0,
+                    chat.config.model.clone(),
+                    chrono::Utc::now(),
+                ))
+                .unwrap();
+            self.repository.save_chat(&mut stored).await.unwrap();
+
+            FakeCompletionGateway::new()
+                .create_chat_completion(chat)
+                .await
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_rebases_on_concurrent_save() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let user_id = Uuid::new_v4();
+        let users = users_with(user_id).await;
+        let input = ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: "Hello!".to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        };
+        let output = ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            repository.clone(),
+            users.clone(),
+            model.clone(),
+            config(),
+        )
+        .execute(input.clone())
+        .await
+        .unwrap();
+
+        let gateway = InterleavedGateway {
+            repository: repository.clone(),
+        };
+        ChatCompletionUseCase::new(
+            Arc::new(gateway),
+            repository.clone(),
+            users,
+            model,
+            config(),
+        )
+        .execute(ChatCompletionInputDTO {
+            chat_id: Some(output.chat_id),
+            user_message: "What time is it?".to_string(),
+            ..input
+        })
+        .await
+        .unwrap();
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.messages
+                .iter()
+                .map(|message| message.content.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Hello!",
+                "Hi, how can I help?",
+                "Are you there?",
+                "What time is it?",
+                "Hi, how can I help?",
+            ]
+        );
+        assert_eq!(chat.version, 3);
+    }
+}
@@ -0,0 +1,1071 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::entity::chat::{Chat, ConfigOverrides};
+use crate::internal::domain::entity::event::ChatEvent;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+use crate::internal::domain::memory_extractor::MemoryExtractor;
+use crate::internal::domain::message_indexer::MessageIndexer;
+use crate::internal::domain::moderator::Moderator;
+use crate::internal::domain::output_filter::OutputFilter;
+use crate::internal::domain::prompt_guard::PromptGuardPolicy;
+use crate::internal::domain::quota::QuotaEnforcer;
+use crate::internal::domain::rate_limiter::RateLimiter;
+use crate::internal::domain::redactor::Redactor;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::domain::repository::unit_of_work::UnitOfWork;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::summarizer::Summarizer;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::domain::title_generator::TitleGenerator;
+use crate::internal::domain::tool_registry::ToolRegistry;
+use crate::internal::domain::usage_tracker::UsageTracker;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionConfigInputDTO, ChatCompletionInputDTO, ChatCompletionOutputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::{
+    answer_tool_calls, chat_defaults, load_or_create_chat, new_user_message, prompt_for,
+    record_redactions, resolve_overrides, save_exchange, tools_for, Exchange, LoadedChat,
+    MAX_TOOL_ROUNDS,
+};
+use crate::internal::usecase::error::UseCaseError;
+
+const DELTA_BUFFER_SIZE: usize = 32;
+
+pub struct ChatCompletionStreamUseCase {
+    gateway: Arc<dyn ChatCompletionGateway>,
+    repository: Arc<dyn ChatRepository>,
+    users: Arc<dyn UserRepository>,
+    model: Model,
+    config: ChatCompletionConfigInputDTO,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    quota: Option<Arc<QuotaEnforcer>>,
+    usage_tracker: Option<Arc<UsageTracker>>,
+    summarizer: Option<Arc<Summarizer>>,
+    title_generator: Option<Arc<TitleGenerator>>,
+    message_indexer: Option<Arc<MessageIndexer>>,
+    memory_extractor: Option<Arc<MemoryExtractor>>,
+    tools: Option<Arc<ToolRegistry>>,
+    moderator: Option<Arc<Moderator>>,
+    prompt_guard: Option<Arc<PromptGuardPolicy>>,
+    redactor: Option<Arc<Redactor>>,
+    output_filter: Option<Arc<OutputFilter>>,
+    templates: Option<Arc<dyn PromptTemplateRepository>>,
+    assistants: Option<Arc<dyn AssistantRepository>>,
+    tenants: Option<Arc<TenantRegistry>>,
+    unit_of_work: Option<Arc<dyn UnitOfWork>>,
+}
+
+impl ChatCompletionStreamUseCase {
+    pub fn new(
+        gateway: Arc<dyn ChatCompletionGateway>,
+        repository: Arc<dyn ChatRepository>,
+        users: Arc<dyn UserRepository>,
+        model: Model,
+        config: ChatCompletionConfigInputDTO,
+    ) -> Self {
+        Self {
+            gateway,
+            repository,
+            users,
+            model,
+            config,
+            rate_limiter: None,
+            quota: None,
+            usage_tracker: None,
+            summarizer: None,
+            title_generator: None,
+            message_indexer: None,
+            memory_extractor: None,
+            tools: None,
+            moderator: None,
+            prompt_guard: None,
+            redactor: None,
+            output_filter: None,
+            templates: None,
+            assistants: None,
+            tenants: None,
+            unit_of_work: None,
+        }
+    }
+
+    // with_rate_limiter enforces the per-user request and token budgets
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    // with_quota_enforcer refuses messages once a user or tenant used up its daily or monthly
+    // tokens
+    pub fn with_quota_enforcer(mut self, quota: Arc<QuotaEnforcer>) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    // with_usage_tracker records the tokens and estimated cost of every completion
+    pub fn with_usage_tracker(mut self, usage_tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(usage_tracker);
+        self
+    }
+
+    // with_summarizer compresses the history of summarize_and_trim chats close to their budget
+    pub fn with_summarizer(mut self, summarizer: Arc<Summarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    // with_title_generator names new chats in the background once their first reply is saved
+    pub fn with_title_generator(mut self, title_generator: Arc<TitleGenerator>) -> Self {
+        self.title_generator = Some(title_generator);
+        self
+    }
+
+    // with_message_indexer embeds every saved exchange in the background for the chat search
+    pub fn with_message_indexer(mut self, message_indexer: Arc<MessageIndexer>) -> Self {
+        self.message_indexer = Some(message_indexer);
+        self
+    }
+
+    // with_memory_extractor remembers what users tell about themselves after every saved
+    // exchange and tells their new chats about it
+    pub fn with_memory_extractor(mut self, memory_extractor: Arc<MemoryExtractor>) -> Self {
+        self.memory_extractor = Some(memory_extractor);
+        self
+    }
+
+    // with_tools offers the registered tools to the model and runs the calls it makes
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    // with_moderator rejects user messages flagged by moderation before they reach the model
+    pub fn with_moderator(mut self, moderator: Arc<Moderator>) -> Self {
+        self.moderator = Some(moderator);
+        self
+    }
+
+    // with_prompt_guard screens user messages for prompt injection after moderation, they are
+    // blocked, flagged or annotated as the policy says
+    pub fn with_prompt_guard(mut self, prompt_guard: Arc<PromptGuardPolicy>) -> Self {
+        self.prompt_guard = Some(prompt_guard);
+        self
+    }
+
+    // with_redactor masks personal data in user messages before they reach any provider
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    // with_output_filter filters the assistant responses before they are saved or returned; the
+    // responses the filter applies to are sent as a single delta once filtered
+    pub fn with_output_filter(mut self, output_filter: Arc<OutputFilter>) -> Self {
+        self.output_filter = Some(output_filter);
+        self
+    }
+
+    // with_templates lets new chats build their system message from a prompt template
+    pub fn with_templates(mut self, templates: Arc<dyn PromptTemplateRepository>) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    // with_assistants lets new chats be started with an assistant, which then limits the tools
+    // offered in them
+    pub fn with_assistants(mut self, assistants: Arc<dyn AssistantRepository>) -> Self {
+        self.assistants = Some(assistants);
+        self
+    }
+
+    // with_tenants applies the default model configured for each tenant
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    // with_unit_of_work commits the chat and the usage of a reply in one transaction,
+    // nothing is written when any of them fails
+    pub fn with_unit_of_work(mut self, unit_of_work: Arc<dyn UnitOfWork>) -> Self {
+        self.unit_of_work = Some(unit_of_work);
+        self
+    }
+
+    // model_for returns the model of the tenant, or the service one when it has no override
+    fn model_for(&self, tenant_id: Uuid) -> Model {
+        self.tenants
+            .as_ref()
+            .and_then(|tenants| tenants.model(tenant_id))
+            .unwrap_or_else(|| self.model.clone())
+    }
+
+    // check_model refuses models the tenant does not allow
+    fn check_model(&self, tenant_id: Uuid, model: &Model) -> Result<(), UseCaseError> {
+        if let Some(tenants) = &self.tenants {
+            tenants.check_model(tenant_id, model)?;
+        }
+
+        Ok(())
+    }
+
+    // execute forwards every assistant delta to the stream while the model is answering,
+    // then persists the chat and returns the full reply
+    #[instrument(name = "chat_completion_stream", skip_all, fields(user_id = %input.user_id, chat_id))]
+    pub async fn execute(
+        &self,
+        input: ChatCompletionInputDTO,
+        stream: mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let overrides = resolve_overrides(&input.overrides)?;
+        if let Some(model) = &overrides.model {
+            self.check_model(input.tenant_id, model)?;
+        }
+        let (chat, user_message) = self.prepare(&input).await?;
+
+        self.reply(chat, user_message, &overrides, stream).await
+    }
+
+    // prepare admits the user message of the input and loads the chat it is sent to, a new one
+    // starts with the defaults of the input
+    pub(crate) async fn prepare(
+        &self,
+        input: &ChatCompletionInputDTO,
+    ) -> Result<(LoadedChat, Message), UseCaseError> {
+        let defaults = chat_defaults(
+            self.assistants.as_deref(),
+            self.model_for(input.tenant_id),
+            &self.config,
+            input,
+        )
+        .await?;
+        if input.chat_id.is_none() {
+            self.check_model(input.tenant_id, &defaults.model)?;
+        }
+        let user_message = new_user_message(
+            &defaults.model,
+            &input.user_message,
+            input.attachments.clone(),
+        )?;
+        let user_message = self
+            .admit(input.tenant_id, input.user_id, input.chat_id, user_message)
+            .await?;
+
+        let mut chat = load_or_create_chat(
+            self.repository.as_ref(),
+            self.users.as_ref(),
+            self.templates.as_deref(),
+            self.memory_extractor.as_deref(),
+            &defaults.model,
+            &defaults.config,
+            input,
+        )
+        .await?;
+        if chat.is_new {
+            chat.chat.assistant_id = defaults.assistant_id;
+        }
+        tracing::Span::current().record("chat_id", tracing::field::display(chat.chat.id));
+
+        Ok((chat, user_message))
+    }
+
+    // admit applies token quotas, the rate limit, redaction, moderation and the prompt guard to
+    // a user message before any work is done, the message to go on with has its personal data
+    // masked and the guard's annotation; a request the quota refuses keeps its rate limit
+    pub(crate) async fn admit(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        message: Message,
+    ) -> Result<Message, UseCaseError> {
+        if let Some(quota) = &self.quota {
+            quota.check(tenant_id, user_id).await?;
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(tenant_id, user_id)?;
+        }
+
+        self.screen(tenant_id, user_id, chat_id, message).await
+    }
+
+    // screen applies redaction, moderation and the prompt guard to a user message like
+    // ChatCompletionUseCase::screen does
+    pub(crate) async fn screen(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        chat_id: Option<Uuid>,
+        mut message: Message,
+    ) -> Result<Message, UseCaseError> {
+        if let Some(redactor) = &self.redactor {
+            message = redactor.redact(tenant_id, user_id, chat_id, message)?;
+        }
+
+        if let Some(moderator) = &self.moderator {
+            moderator.check(user_id, chat_id, &message.content).await?;
+        }
+        if let Some(prompt_guard) = &self.prompt_guard {
+            message = prompt_guard
+                .screen(tenant_id, user_id, chat_id, message)
+                .await?;
+        }
+
+        Ok(message)
+    }
+
+    // reply adds the admitted user message to the chat and streams the model's reply, prompted
+    // with the overrides, then persists the chat and returns the full reply
+    pub(crate) async fn reply(
+        &self,
+        chat: LoadedChat,
+        user_message: Message,
+        overrides: &ConfigOverrides,
+        stream: mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let LoadedChat { mut chat, is_new } = chat;
+        // only the model that answers the turn has to be allowed
+        self.check_model(
+            chat.tenant_id,
+            overrides.model.as_ref().unwrap_or(&chat.config.model),
+        )?;
+
+        if let Some(tools) = &self.tools {
+            chat.config.tools = tools_for(tools, self.assistants.as_deref(), &chat).await?;
+        }
+
+        let added = vec![user_message.clone()];
+        chat.add_message(user_message)?;
+        let response = self
+            .respond(&mut chat, is_new, added, None, overrides, &stream)
+            .await?;
+
+        Ok(ChatCompletionOutputDTO {
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            content: response.content,
+            candidates: vec![],
+        })
+    }
+
+    // take_turn has the speaker answer the next turn of an orchestrated chat and streams its
+    // reply; the speaker is prompted with the chat as seen_by it, with its overrides, and only
+    // offered its tools. The chat is saved with the added messages and the reply, which is
+    // returned
+    pub(crate) async fn take_turn(
+        &self,
+        chat: &mut Chat,
+        is_new: bool,
+        added: Vec<Message>,
+        speaker: &Speaker,
+        stream: &mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<Message, UseCaseError> {
+        self.check_model(
+            chat.tenant_id,
+            speaker
+                .overrides
+                .model
+                .as_ref()
+                .unwrap_or(&chat.config.model),
+        )?;
+
+        if let Some(tools) = &self.tools {
+            chat.config.tools = tools
+                .definitions()
+                .into_iter()
+                .filter(|tool| speaker.assistant.offers(&tool.name))
+                .collect();
+        }
+
+        self.respond(
+            chat,
+            is_new,
+            added,
+            Some(speaker),
+            &speaker.overrides,
+            stream,
+        )
+        .await
+    }
+
+    // respond streams the model's reply to the messages added to the chat, running the tool
+    // calls it makes, then persists the chat and returns the reply; the messages of a speaker's
+    // turn are labelled with its name
+    async fn respond(
+        &self,
+        chat: &mut Chat,
+        is_new: bool,
+        mut added: Vec<Message>,
+        speaker: Option<&Speaker>,
+        overrides: &ConfigOverrides,
+        stream: &mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<Message, UseCaseError> {
+        if let Some(summarizer) = &self.summarizer {
+            summarizer.summarize_if_needed(chat).await?;
+        }
+
+        let chat_id = chat.id;
+        // a response the filter may change is only sent once filtered
+        let buffered = self
+            .output_filter
+            .as_ref()
+            .is_some_and(|output_filter| output_filter.applies_to(chat.tenant_id));
+        let label = |message: Message| match speaker {
+            Some(speaker) => message.with_speaker(&speaker.assistant.name),
+            None => message,
+        };
+
+        let mut prompt_tokens = 0;
+        let mut completion_tokens = 0;
+        let mut rounds = 0;
+        let response = loop {
+            let seen = speaker
+                .map(|speaker| chat.seen_by(&speaker.assistant.name, &speaker.instructions))
+                .transpose()?;
+            let prompt = prompt_for(seen.as_ref().unwrap_or(chat), None, overrides)?;
+            prompt_tokens += prompt.token_usage;
+            let response = match self.stream_completion(&prompt, buffered, stream).await? {
+                Streamed::Complete(response) => response,
+                Streamed::Interrupted(partial) => {
+                    // the tokens of the cut reply are charged, not the request it took
+                    if let Some(rate_limiter) = &self.rate_limiter {
+                        rate_limiter.release(chat.tenant_id, chat.user_id);
+                    }
+                    let served_model = prompt.config.model.clone();
+                    if let Some(partial) = partial.map(label) {
+                        completion_tokens += partial.tokens;
+                        // the cut reply is saved filtered like a complete one
+                        let partial = match &self.output_filter {
+                            Some(output_filter) => {
+                                output_filter
+                                    .apply(chat.tenant_id, chat.user_id, partial)
+                                    .await
+                            }
+                            None => partial,
+                        };
+                        added.push(partial.clone());
+                        chat.add_message(partial)?;
+                    }
+                    let turn = Turn {
+                        is_new,
+                        added,
+                        served_model,
+                        prompt_tokens,
+                        completion_tokens,
+                    };
+                    self.finish(chat, turn).await?;
+
+                    return Err(UseCaseError::StreamCancelled(chat_id));
+                }
+            };
+            let response = label(response);
+            completion_tokens += response.tokens;
+
+            if response.tool_calls.is_empty() {
+                break response;
+            }
+            if rounds == MAX_TOOL_ROUNDS {
+                return Err(UseCaseError::ToolRoundsExceeded(MAX_TOOL_ROUNDS));
+            }
+            rounds += 1;
+            added.extend(answer_tool_calls(self.tools.as_deref(), chat, response).await?);
+        };
+        chat.config
+            .response_format
+            .check_response(&response.content)?;
+        let response = match &self.output_filter {
+            Some(output_filter) => {
+                output_filter
+                    .apply(chat.tenant_id, chat.user_id, response)
+                    .await
+            }
+            None => response,
+        };
+        if buffered {
+            // the reply is saved even when the client went away meanwhile, as a streamed one
+            // that reached its end
+            let _ = forward(chat, stream, &mut String::new(), response.content.clone()).await;
+        }
+
+        // the model that served the reply differs from the chat's one after a fallback
+        let served_model = response.model.clone();
+        added.push(response.clone());
+        chat.add_message(response.clone())?;
+        let turn = Turn {
+            is_new,
+            added,
+            served_model,
+            prompt_tokens,
+            completion_tokens,
+        };
+        self.finish(chat, turn).await?;
+
+        Ok(response)
+    }
+
+    // finish charges the tokens of the turn and saves its messages along with its usage
+    async fn finish(&self, chat: &mut Chat, turn: Turn) -> Result<(), UseCaseError> {
+        let Turn {
+            is_new,
+            added,
+            served_model,
+            prompt_tokens,
+            completion_tokens,
+        } = turn;
+        let consumed = ChatEvent::TokensConsumed {
+            model: served_model.name.clone(),
+            prompt_tokens,
+            completion_tokens,
+        };
+        chat.record(consumed.clone());
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.consume_tokens(chat.tenant_id, chat.user_id, chat.token_usage);
+        }
+
+        let usage = self.usage_tracker.as_ref().map(|usage_tracker| {
+            usage_tracker.price(
+                chat.tenant_id,
+                chat.user_id,
+                chat.id,
+                &served_model,
+                prompt_tokens,
+                completion_tokens,
+            )
+        });
+        let exchange = Exchange {
+            messages: added,
+            consumed,
+            usage,
+        };
+        save_exchange(
+            self.repository.as_ref(),
+            self.unit_of_work.as_deref(),
+            self.usage_tracker.as_deref(),
+            chat,
+            is_new,
+            &exchange,
+        )
+        .await?;
+        record_redactions(self.redactor.as_deref(), chat).await;
+
+        if let Some(title_generator) = &self.title_generator {
+            title_generator.spawn(chat);
+        }
+
+        if let Some(message_indexer) = &self.message_indexer {
+            message_indexer.spawn(chat);
+        }
+
+        if let Some(memory_extractor) = &self.memory_extractor {
+            memory_extractor.spawn(chat);
+        }
+
+        Ok(())
+    }
+
+    // stream_completion asks the model for the next message, forwarding its deltas to the stream
+    // unless they are buffered; the deltas wait while the stream is full, and the provider
+    // request is dropped as soon as the stream is closed by the client going away
+    async fn stream_completion(
+        &self,
+        chat: &Chat,
+        buffered: bool,
+        stream: &mpsc::Sender<ChatCompletionOutputDTO>,
+    ) -> Result<Streamed, UseCaseError> {
+        let (sender, mut receiver) = mpsc::channel::<String>(DELTA_BUFFER_SIZE);
+        let completion = self.gateway.create_chat_completion_stream(chat, sender);
+        tokio::pin!(completion);
+        let mut received = String::new();
+
+        let response = loop {
+            tokio::select! {
+                response = &mut completion => break response,
+                Some(delta) = receiver.recv() => {
+                    if buffered {
+                        continue;
+                    }
+                    if forward(chat, stream, &mut received, delta).await.is_err() {
+                        return Ok(Streamed::interrupted(chat, &received));
+                    }
+                }
+                _ = stream.closed() => return Ok(Streamed::interrupted(chat, &received)),
+            }
+        };
+
+        if buffered {
+            return Ok(Streamed::Complete(response?));
+        }
+
+        // the deltas sent right before the provider was done are still buffered
+        while let Ok(delta) = receiver.try_recv() {
+            if forward(chat, stream, &mut received, delta).await.is_err() {
+                return Ok(Streamed::interrupted(chat, &received));
+            }
+        }
+
+        Ok(Streamed::Complete(response?))
+    }
+}
+
+// Speaker is an assistant taking turns in an orchestrated chat, with its instructions rendered
+// and its model and temperature as the overrides of its turns
+pub(crate) struct Speaker {
+    pub assistant: Assistant,
+    pub instructions: String,
+    pub overrides: ConfigOverrides,
+}
+
+// Turn is what a streamed turn added to the chat and the tokens it took
+struct Turn {
+    is_new: bool,
+    added: Vec<Message>,
+    served_model: Model,
+    prompt_tokens: usize,
+    completion_tokens: usize,
+}
+
+// Streamed is the outcome of a streamed completion; an interrupted one keeps what the client
+// received, nothing when it went away before the first delta
+enum Streamed {
+    Complete(Message),
+    Interrupted(Option<Message>),
+}
+
+impl Streamed {
+    fn interrupted(chat: &Chat, received: &str) -> Self {
+        let partial = (!received.is_empty()).then(|| {
+            Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                received,
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            )
+            .with_interrupted()
+        });
+
+        Streamed::Interrupted(partial)
+    }
+}
+
+// forward sends a delta to the stream and keeps it, it fails once the stream is closed
+async fn forward(
+    chat: &Chat,
+    stream: &mpsc::Sender<ChatCompletionOutputDTO>,
+    received: &mut String,
+    delta: String,
+) -> Result<(), mpsc::error::SendError<ChatCompletionOutputDTO>> {
+    received.push_str(&delta);
+    stream
+        .send(ChatCompletionOutputDTO {
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            content: delta,
+            candidates: vec![],
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatSummary, TrimmingPolicy};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::GatewayError;
+    use crate::internal::domain::output_filter::OutputFilterConfig;
+    use crate::internal::domain::rate_limiter::RateLimitConfig;
+    use crate::internal::domain::repository::chat::{
+        ChatCursor, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+    };
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::usecase::chat_completion::dto::{
+        ChatLabelsInputDTO, ChatOverridesInputDTO,
+    };
+
+    struct FakeStreamGateway {
+        deltas: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for FakeStreamGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                &self.deltas.concat(),
+                0,
+                chat.initial_system_message().model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            for delta in &self.deltas {
+                sender.send(delta.to_string()).await.unwrap();
+            }
+
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    struct NoopRepository;
+
+    #[async_trait]
+    impl ChatRepository for NoopRepository {
+        async fn create_chat(&self, _chat: &Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn find_chat_by_id(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<Chat>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn save_chat(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn update_chat_title(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+            _title: &str,
+        ) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn list_chats_by_user(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_chat_summaries(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _tag: Option<&str>,
+            _after: Option<ChatCursor>,
+            _limit: usize,
+        ) -> Result<Vec<ChatSummary>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn find_chat_summary(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<ChatSummary>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn list_messages(
+            &self,
+            _tenant_id: Uuid,
+            _chat_id: Uuid,
+            _query: &MessageQuery,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn search_transcripts(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_inactive_chats(
+            &self,
+            _inactive_before: chrono::DateTime<chrono::Utc>,
+            _limit: usize,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn archive_chat_history(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn purge_deleted_chats(
+            &self,
+            _deleted_before: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<PurgedChat>, RepositoryError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_streams_deltas() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let gateway = FakeStreamGateway {
+            deltas: vec!["Hi", ", how", " can I help?"],
+        };
+        let config = ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        };
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "auth0|42", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let usecase = ChatCompletionStreamUseCase::new(
+            Arc::new(gateway),
+            Arc::new(NoopRepository),
+            Arc::new(users),
+            model,
+            config,
+        );
+        let (sender, mut receiver) = mpsc::channel(8);
+
+        let output = usecase
+            .execute(
+                ChatCompletionInputDTO {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id,
+                    chat_id: None,
+                    user_message: "Hello!".to_string(),
+                    attachments: vec![],
+                    template: None,
+                    assistant: None,
+                    idempotency_key: None,
+                    overrides: ChatOverridesInputDTO::default(),
+                    labels: ChatLabelsInputDTO::default(),
+                },
+                sender,
+            )
+            .await
+            .unwrap();
+
+        let mut deltas = vec![];
+        while let Some(chunk) = receiver.recv().await {
+            assert_eq!(chunk.chat_id, output.chat_id);
+            deltas.push(chunk.content);
+        }
+
+        assert_eq!(deltas, vec!["Hi", ", how", " can I help?"]);
+        assert_eq!(output.content, "Hi, how can I help?");
+    }
+
+    #[tokio::test]
+    async fn test_execute_filters_output() {
+        let gateway = FakeStreamGateway {
+            deltas: vec!["Well", ", shit", " happens"],
+        };
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "auth0|42", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let usecase = ChatCompletionStreamUseCase::new(
+            Arc::new(gateway),
+            Arc::new(InMemoryChatRepository::new()),
+            Arc::new(users),
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        )
+        .with_output_filter(Arc::new(OutputFilter::new(OutputFilterConfig {
+            mask_profanity: true,
+            ..Default::default()
+        })));
+        let (sender, mut receiver) = mpsc::channel(8);
+
+        let output = usecase
+            .execute(
+                ChatCompletionInputDTO {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id,
+                    chat_id: None,
+                    user_message: "Hello!".to_string(),
+                    attachments: vec![],
+                    template: None,
+                    assistant: None,
+                    idempotency_key: None,
+                    overrides: ChatOverridesInputDTO::default(),
+                    labels: ChatLabelsInputDTO::default(),
+                },
+                sender,
+            )
+            .await
+            .unwrap();
+
+        // the filtered reply is sent in one delta, the model's own deltas never are
+        let mut deltas = vec![];
+        while let Some(chunk) = receiver.recv().await {
+            deltas.push(chunk.content);
+        }
+        assert_eq!(deltas, vec!["Well, s*** happens"]);
+        assert_eq!(output.content, "Well, s*** happens");
+    }
+
+    // StallingGateway sends its deltas and then never finishes, it notes when it is dropped
+    struct StallingGateway {
+        deltas: Vec<&'static str>,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    // CancelGuard flags the request as cancelled when the gateway future is dropped
+    struct CancelGuard(Arc<AtomicBool>);
+
+    impl Drop for CancelGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl ChatCompletionGateway for StallingGateway {
+        async fn create_chat_completion(&self, _chat: &Chat) -> Result<Message, GatewayError> {
+            std::future::pending().await
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            let _guard = CancelGuard(self.cancelled.clone());
+            for delta in &self.deltas {
+                sender.send(delta.to_string()).await.unwrap();
+            }
+
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_client_gone() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let gateway = StallingGateway {
+            deltas: vec!["Once upon", " a time"],
+            cancelled: cancelled.clone(),
+        };
+        let config = ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        };
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "auth0|42", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 1,
+            tokens_per_minute: 0,
+        }));
+        let usecase = ChatCompletionStreamUseCase::new(
+            Arc::new(gateway),
+            repository.clone(),
+            Arc::new(users),
+            model,
+            config,
+        )
+        .with_rate_limiter(rate_limiter.clone());
+        let (sender, mut receiver) = mpsc::channel(8);
+
+        // the client reads both deltas and goes away
+        let client = async move {
+            for _ in 0..2 {
+                receiver.recv().await.unwrap();
+            }
+        };
+        let (result, _) = tokio::join!(
+            usecase.execute(
+                ChatCompletionInputDTO {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id,
+                    chat_id: None,
+                    user_message: "Tell me a story".to_string(),
+                    attachments: vec![],
+                    template: None,
+                    assistant: None,
+                    idempotency_key: None,
+                    overrides: ChatOverridesInputDTO::default(),
+                    labels: ChatLabelsInputDTO::default(),
+                },
+                sender,
+            ),
+            client
+        );
+
+        let Err(UseCaseError::StreamCancelled(chat_id)) = result else {
+            panic!("expected the stream to be cancelled, got {:?}", result);
+        };
+        assert!(cancelled.load(Ordering::SeqCst));
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let reply = chat.messages.last().unwrap();
+        assert_eq!(reply.role, Role::Assistant);
+        assert_eq!(reply.content, "Once upon a time");
+        assert!(reply.interrupted);
+        assert!(rate_limiter.acquire(DEFAULT_TENANT_ID, user_id).is_ok());
+    }
+}
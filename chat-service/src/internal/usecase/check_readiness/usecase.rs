@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use tracing::instrument;
+
+use crate::internal::domain::gateway::health::{HealthCheck, HealthError};
+use crate::internal::usecase::check_readiness::dto::{CheckOutputDTO, ReadinessOutputDTO};
+
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct CheckReadinessUseCase {
+    checks: Vec<Arc<dyn HealthCheck>>,
+    timeout: Duration,
+}
+
+impl CheckReadinessUseCase {
+    pub fn new(checks: Vec<Arc<dyn HealthCheck>>) -> Self {
+        Self {
+            checks,
+            timeout: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+
+    // with_timeout bounds every check, a dependency that hangs counts as unhealthy
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // execute runs the checks concurrently, the service is ready when all of them pass
+    #[instrument(name = "check_readiness", skip_all)]
+    pub async fn execute(&self) -> ReadinessOutputDTO {
+        let checks = join_all(self.checks.iter().map(|check| self.run(check.as_ref()))).await;
+        let ready = checks.iter().all(|check| check.healthy);
+        if !ready {
+            for check in checks.iter().filter(|check| !check.healthy) {
+                tracing::warn!(check = %check.name, error = ?check.error, "dependency is not ready");
+            }
+        }
+
+        ReadinessOutputDTO { ready, checks }
+    }
+
+    async fn run(&self, check: &dyn HealthCheck) -> CheckOutputDTO {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, check.check()).await {
+            Ok(result) => result,
+            Err(_) => Err(HealthError(format!(
+                "timed out after {}ms",
+                self.timeout.as_millis()
+            ))),
+        };
+
+        CheckOutputDTO {
+            name: check.name().to_string(),
+            healthy: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+            latency_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct StaticCheck {
+        name: &'static str,
+        result: Result<(), HealthError>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl HealthCheck for StaticCheck {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn check(&self) -> Result<(), HealthError> {
+            tokio::time::sleep(self.delay).await;
+            self.result.clone()
+        }
+    }
+
+    fn check(name: &'static str, result: Result<(), HealthError>) -> Arc<dyn HealthCheck> {
+        Arc::new(StaticCheck {
+            name,
+            result,
+            delay: Duration::ZERO,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_ready_when_every_check_passes() {
+        let usecase =
+            CheckReadinessUseCase::new(vec![check("postgres", Ok(())), check("redis", Ok(()))]);
+
+        let output = usecase.execute().await;
+
+        assert!(output.ready);
+        assert_eq!(output.checks.len(), 2);
+        assert_eq!(output.checks[0].name, "postgres");
+        assert!(output.checks.iter().all(|check| check.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_not_ready_when_a_check_fails() {
+        let usecase = CheckReadinessUseCase::new(vec![
+            check("postgres", Ok(())),
+            check("redis", Err(HealthError("connection refused".to_string()))),
+        ]);
+
+        let output = usecase.execute().await;
+
+        assert!(!output.ready);
+        assert!(output.checks[0].healthy);
+        assert!(!output.checks[1].healthy);
+        assert_eq!(
+            output.checks[1].error.as_deref(),
+            Some("connection refused")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hanging_check_times_out() {
+        let usecase = CheckReadinessUseCase::new(vec![Arc::new(StaticCheck {
+            name: "provider:openai",
+            result: Ok(()),
+            delay: Duration::from_secs(5),
+        })])
+        .with_timeout(Duration::from_millis(10));
+
+        let output = usecase.execute().await;
+
+        assert!(!output.ready);
+        assert_eq!(
+            output.checks[0].error.as_deref(),
+            Some("timed out after 10ms")
+        );
+    }
+}
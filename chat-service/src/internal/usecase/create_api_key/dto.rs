@@ -0,0 +1,12 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+// ApiKeyOutputDTO carries the plaintext key, it is only ever returned once
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiKeyOutputDTO {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub key: String,
+    pub prefix: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::usecase::create_api_key::dto::ApiKeyOutputDTO;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct CreateApiKeyUseCase {
+    api_keys: Arc<dyn ApiKeyRepository>,
+    users: Arc<dyn UserRepository>,
+}
+
+impl CreateApiKeyUseCase {
+    pub fn new(api_keys: Arc<dyn ApiKeyRepository>, users: Arc<dyn UserRepository>) -> Self {
+        Self { api_keys, users }
+    }
+
+    // execute issues a new API key for an existing user
+    #[instrument(name = "create_api_key", skip_all, fields(user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ApiKeyOutputDTO, UseCaseError> {
+        if self
+            .users
+            .find_user_by_id(tenant_id, user_id)
+            .await?
+            .is_none()
+        {
+            return Err(UseCaseError::UserNotFound(user_id));
+        }
+
+        let (api_key, key) = ApiKey::generate(tenant_id, user_id);
+        self.api_keys.create_api_key(&api_key).await?;
+
+        Ok(ApiKeyOutputDTO {
+            id: api_key.id,
+            user_id,
+            key,
+            prefix: api_key.prefix,
+            created_at: api_key.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::api_key::hash_key;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::infra::repository::memory::api_key::InMemoryApiKeyRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let api_keys = Arc::new(InMemoryApiKeyRepository::new());
+        let user = User::new(Uuid::new_v4(), "auth0|42", "Ada", chrono::Utc::now());
+        users.create_user(&user).await.unwrap();
+        let usecase = CreateApiKeyUseCase::new(api_keys.clone(), users);
+
+        let output = usecase.execute(user.tenant_id, user.id).await.unwrap();
+
+        let stored = api_keys
+            .find_api_key_by_hash(&hash_key(&output.key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, output.id);
+        assert_eq!(stored.user_id, user.id);
+        assert_eq!(stored.tenant_id, user.tenant_id);
+
+        let missing = Uuid::new_v4();
+        assert!(matches!(
+            usecase.execute(user.tenant_id, missing).await,
+            Err(UseCaseError::UserNotFound(id)) if id == missing
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+
+// AssistantInputDTO configures an assistant of the tenant; its chats use the tenant model and
+// the configured temperature when model and temperature are not set, tools name registered
+// tools
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AssistantInputDTO {
+    pub name: String,
+    pub instructions: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssistantOutputDTO {
+    pub id: Uuid,
+    pub name: String,
+    pub instructions: String,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub tools: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Assistant> for AssistantOutputDTO {
+    fn from(assistant: &Assistant) -> Self {
+        Self {
+            id: assistant.id,
+            name: assistant.name.clone(),
+            instructions: assistant.instructions.clone(),
+            model: assistant.model.clone(),
+            temperature: assistant.temperature,
+            tools: assistant.tools.clone(),
+            created_at: assistant.created_at,
+            updated_at: assistant.updated_at,
+        }
+    }
+}
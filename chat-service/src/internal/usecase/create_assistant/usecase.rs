@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::domain::tool_registry::ToolRegistry;
+use crate::internal::usecase::create_assistant::dto::{AssistantInputDTO, AssistantOutputDTO};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+pub struct CreateAssistantUseCase {
+    assistants: Arc<dyn AssistantRepository>,
+    tenants: Arc<TenantRegistry>,
+    tools: Option<Arc<ToolRegistry>>,
+}
+
+impl CreateAssistantUseCase {
+    pub fn new(assistants: Arc<dyn AssistantRepository>, tenants: Arc<TenantRegistry>) -> Self {
+        Self {
+            assistants,
+            tenants,
+            tools: None,
+        }
+    }
+
+    // with_tools lets assistants list the registered tools, without it they cannot list any
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    // execute stores a new assistant of the tenant, chats can be started with it from then on
+    #[instrument(name = "create_assistant", skip_all, fields(tenant_id = %tenant_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        input: AssistantInputDTO,
+    ) -> Result<AssistantOutputDTO, UseCaseError> {
+        if !self.tenants.contains(tenant_id) {
+            return Err(UseCaseError::TenantNotFound(tenant_id));
+        }
+
+        let assistant = Assistant::new(
+            Uuid::new_v4(),
+            tenant_id,
+            &input.name,
+            &input.instructions,
+            chrono::Utc::now(),
+        )
+        .with_model(input.model)
+        .with_temperature(input.temperature)
+        .with_tools(input.tools);
+        check_assistant(
+            self.assistants.as_ref(),
+            &self.tenants,
+            self.tools.as_deref(),
+            &assistant,
+        )
+        .await?;
+        self.assistants.create_assistant(&assistant).await?;
+
+        Ok(AssistantOutputDTO::from(&assistant))
+    }
+}
+
+// check_assistant validates the assistant against the service before it is stored: its model
+// has to be a registry one the tenant allows, its tools have to be registered and its name must
+// not be taken by another assistant of the tenant
+pub(crate) async fn check_assistant(
+    assistants: &dyn AssistantRepository,
+    tenants: &TenantRegistry,
+    tools: Option<&ToolRegistry>,
+    assistant: &Assistant,
+) -> Result<(), UseCaseError> {
+    assistant.validate()?;
+
+    if let Some(name) = &assistant.model {
+        tenants.check_model(assistant.tenant_id, &resolve_model(name)?)?;
+    }
+    if let Some(tool) = assistant
+        .tools
+        .iter()
+        .find(|tool| !tools.is_some_and(|tools| tools.contains(tool)))
+    {
+        return Err(UseCaseError::InvalidInput(format!(
+            "tool {} is not registered",
+            tool
+        )));
+    }
+
+    let taken = assistants
+        .list_assistants(assistant.tenant_id)
+        .await?
+        .iter()
+        .any(|other| other.id != assistant.id && other.name == assistant.name);
+    if taken {
+        return Err(UseCaseError::AssistantAlreadyExists(assistant.name.clone()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::tool::ToolDefinition;
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::infra::repository::memory::assistant::InMemoryAssistantRepository;
+
+    fn usecase(assistants: Arc<InMemoryAssistantRepository>) -> CreateAssistantUseCase {
+        let tools = ToolRegistry::new()
+            .with_tool(
+                ToolDefinition::new("get_invoice", "", json!({"type": "object"})),
+                |_: serde_json::Value| async { Ok(String::new()) },
+            )
+            .unwrap();
+
+        CreateAssistantUseCase::new(assistants, Arc::new(TenantRegistry::new()))
+            .with_tools(Arc::new(tools))
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let assistants = Arc::new(InMemoryAssistantRepository::new());
+        let usecase = usecase(assistants.clone());
+        let input = AssistantInputDTO {
+            name: "billing".to_string(),
+            instructions: "You answer {{user_name}} about invoices.".to_string(),
+            model: Some("gpt-4o".to_string()),
+            temperature: Some(0.2),
+            tools: vec!["get_invoice".to_string()],
+        };
+
+        let output = usecase
+            .execute(DEFAULT_TENANT_ID, input.clone())
+            .await
+            .unwrap();
+        let stored = assistants
+            .find_assistant_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(AssistantOutputDTO::from(&stored), output);
+        assert!(matches!(
+            usecase.execute(DEFAULT_TENANT_ID, input.clone()).await,
+            Err(UseCaseError::AssistantAlreadyExists(name)) if name == "billing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_assistants() {
+        let usecase = usecase(Arc::new(InMemoryAssistantRepository::new()));
+        let input = AssistantInputDTO {
+            name: "billing".to_string(),
+            instructions: "You answer about invoices.".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            usecase.execute(Uuid::new_v4(), input.clone()).await,
+            Err(UseCaseError::TenantNotFound(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(
+                    DEFAULT_TENANT_ID,
+                    AssistantInputDTO {
+                        model: Some("gpt-0".to_string()),
+                        ..input.clone()
+                    }
+                )
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(
+                    DEFAULT_TENANT_ID,
+                    AssistantInputDTO {
+                        tools: vec!["get_weather".to_string()],
+                        ..input.clone()
+                    }
+                )
+                .await,
+            Err(UseCaseError::InvalidInput(message)) if message.contains("get_weather")
+        ));
+        assert!(matches!(
+            usecase
+                .execute(
+                    DEFAULT_TENANT_ID,
+                    AssistantInputDTO {
+                        name: " ".to_string(),
+                        ..input
+                    }
+                )
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidAssistant(_)))
+        ));
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatePromptTemplateInputDTO {
+    pub name: String,
+    // content is the system message, {{variable}} placeholders are filled at chat creation
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromptTemplateOutputDTO {
+    pub id: Uuid,
+    pub name: String,
+    pub content: String,
+    // variables are the names a chat created from the template has to provide
+    pub variables: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::prompt_template::PromptTemplate;
+use crate::internal::domain::repository::prompt_template::PromptTemplateRepository;
+use crate::internal::usecase::create_prompt_template::dto::{
+    CreatePromptTemplateInputDTO, PromptTemplateOutputDTO,
+};
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct CreatePromptTemplateUseCase {
+    templates: Arc<dyn PromptTemplateRepository>,
+}
+
+impl CreatePromptTemplateUseCase {
+    pub fn new(templates: Arc<dyn PromptTemplateRepository>) -> Self {
+        Self { templates }
+    }
+
+    // execute stores a new template once its placeholders parse, template names are unique
+    #[instrument(name = "create_prompt_template", skip_all)]
+    pub async fn execute(
+        &self,
+        input: CreatePromptTemplateInputDTO,
+    ) -> Result<PromptTemplateOutputDTO, UseCaseError> {
+        let template = PromptTemplate::new(
+            Uuid::new_v4(),
+            &input.name,
+            &input.content,
+            chrono::Utc::now(),
+        );
+        template.validate()?;
+
+        if self
+            .templates
+            .find_template_by_name(&template.name)
+            .await?
+            .is_some()
+        {
+            return Err(UseCaseError::TemplateAlreadyExists(template.name));
+        }
+
+        self.templates.create_template(&template).await?;
+
+        Ok(PromptTemplateOutputDTO {
+            id: template.id,
+            variables: template
+                .variables()?
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            name: template.name,
+            content: template.content,
+            created_at: template.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::infra::repository::memory::prompt_template::InMemoryPromptTemplateRepository;
+
+    fn input(content: &str) -> CreatePromptTemplateInputDTO {
+        CreatePromptTemplateInputDTO {
+            name: "support".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let usecase =
+            CreatePromptTemplateUseCase::new(Arc::new(InMemoryPromptTemplateRepository::new()));
+
+        let output = usecase
+            .execute(input("You help {{user_name}} with {{product}}."))
+            .await
+            .unwrap();
+        assert_eq!(output.name, "support");
+        assert_eq!(output.variables, vec!["user_name", "product"]);
+
+        assert!(matches!(
+            usecase.execute(input("You help everyone.")).await,
+            Err(UseCaseError::TemplateAlreadyExists(name)) if name == "support"
+        ));
+        assert!(matches!(
+            usecase
+                .execute(CreatePromptTemplateInputDTO {
+                    name: "broken".to_string(),
+                    content: "You help {{user_name".to_string(),
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidTemplate(_)))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::tenant::Tenant;
+use crate::internal::domain::output_filter::OutputFilterConfig;
+use crate::internal::domain::quota::QuotaConfig;
+use crate::internal::domain::rate_limiter::RateLimitConfig;
+
+// RateLimitDTO is a per-user budget, a zero limit disables that budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitDTO {
+    #[serde(default)]
+    pub requests_per_minute: u32,
+    #[serde(default)]
+    pub tokens_per_minute: u32,
+}
+
+impl From<RateLimitDTO> for RateLimitConfig {
+    fn from(limit: RateLimitDTO) -> Self {
+        RateLimitConfig {
+            requests_per_minute: limit.requests_per_minute,
+            tokens_per_minute: limit.tokens_per_minute,
+        }
+    }
+}
+
+// QuotaDTO caps the tokens spent per UTC day and month by each user of the tenant and by the
+// tenant as a whole, a zero cap is disabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaDTO {
+    #[serde(default)]
+    pub user_daily_tokens: u64,
+    #[serde(default)]
+    pub user_monthly_tokens: u64,
+    #[serde(default)]
+    pub tenant_daily_tokens: u64,
+    #[serde(default)]
+    pub tenant_monthly_tokens: u64,
+}
+
+impl From<QuotaDTO> for QuotaConfig {
+    fn from(quota: QuotaDTO) -> Self {
+        QuotaConfig {
+            user_daily_tokens: quota.user_daily_tokens,
+            user_monthly_tokens: quota.user_monthly_tokens,
+            tenant_daily_tokens: quota.tenant_daily_tokens,
+            tenant_monthly_tokens: quota.tenant_monthly_tokens,
+        }
+    }
+}
+
+// TenantInputDTO sets the name and overrides of a tenant, omitted overrides keep the service
+// defaults
+#[derive(Debug, Clone, Deserialize)]
+pub struct TenantInputDTO {
+    pub name: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitDTO>,
+    // allowed_models restricts the tenant's chats to these models, every model when empty
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    #[serde(default)]
+    pub quota: Option<QuotaDTO>,
+    // output_filter replaces the default filter of the tenant's assistant responses
+    #[serde(default)]
+    pub output_filter: Option<OutputFilterConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTenantInputDTO {
+    // id lets a tenant keep the id it has elsewhere, a new one is generated when omitted
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    #[serde(flatten)]
+    pub tenant: TenantInputDTO,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TenantOutputDTO {
+    pub id: Uuid,
+    pub name: String,
+    pub model: Option<String>,
+    pub rate_limit: Option<RateLimitDTO>,
+    pub allowed_models: Vec<String>,
+    pub quota: Option<QuotaDTO>,
+    pub output_filter: Option<OutputFilterConfig>,
+}
+
+impl From<&Tenant> for TenantOutputDTO {
+    fn from(tenant: &Tenant) -> Self {
+        Self {
+            id: tenant.id,
+            name: tenant.name.clone(),
+            model: tenant.config.model.as_ref().map(|model| model.name.clone()),
+            rate_limit: tenant.config.rate_limit.map(|limit| RateLimitDTO {
+                requests_per_minute: limit.requests_per_minute,
+                tokens_per_minute: limit.tokens_per_minute,
+            }),
+            allowed_models: tenant.config.allowed_models.clone(),
+            quota: tenant.config.quota.map(|quota| QuotaDTO {
+                user_daily_tokens: quota.user_daily_tokens,
+                user_monthly_tokens: quota.user_monthly_tokens,
+                tenant_daily_tokens: quota.tenant_daily_tokens,
+                tenant_monthly_tokens: quota.tenant_monthly_tokens,
+            }),
+            output_filter: tenant.config.output_filter.clone(),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::rate_limiter::RateLimiter;
+use crate::internal::domain::repository::tenant::TenantRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::create_tenant::dto::{
+    CreateTenantInputDTO, TenantInputDTO, TenantOutputDTO,
+};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+pub struct CreateTenantUseCase {
+    repository: Arc<dyn TenantRepository>,
+    tenants: Arc<TenantRegistry>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl CreateTenantUseCase {
+    // new shares the registry and rate limiter with the chat use cases so a new tenant's users
+    // are accepted and limited from the next request
+    pub fn new(
+        repository: Arc<dyn TenantRepository>,
+        tenants: Arc<TenantRegistry>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            repository,
+            tenants,
+            rate_limiter,
+        }
+    }
+
+    // execute stores a new tenant and registers it with this instance, other instances pick it
+    // up when they start
+    #[instrument(name = "create_tenant", skip_all)]
+    pub async fn execute(
+        &self,
+        input: CreateTenantInputDTO,
+    ) -> Result<TenantOutputDTO, UseCaseError> {
+        let id = input.id.unwrap_or_else(Uuid::new_v4);
+        if self.tenants.contains(id) {
+            return Err(UseCaseError::TenantAlreadyExists(id));
+        }
+
+        let tenant = new_tenant(id, input.tenant)?;
+        self.repository.save_tenant(&tenant).await?;
+        apply_tenant(&self.tenants, &self.rate_limiter, &tenant)?;
+
+        Ok(TenantOutputDTO::from(&tenant))
+    }
+}
+
+// new_tenant builds and validates a tenant from its input, the default tenant cannot be
+// overridden since it uses the service settings
+pub(crate) fn new_tenant(id: Uuid, input: TenantInputDTO) -> Result<Tenant, UseCaseError> {
+    if id == DEFAULT_TENANT_ID {
+        return Err(UseCaseError::InvalidInput(
+            "the default tenant uses the service settings".to_string(),
+        ));
+    }
+    if let Some(name) = input
+        .allowed_models
+        .iter()
+        .find(|name| name.trim().is_empty())
+    {
+        return Err(UseCaseError::InvalidInput(format!(
+            "allowed model {:?} is empty",
+            name
+        )));
+    }
+
+    let tenant = Tenant::new(
+        id,
+        &input.name,
+        TenantConfig {
+            model: input.model.as_deref().map(resolve_model).transpose()?,
+            rate_limit: input.rate_limit.map(Into::into),
+            allowed_models: input.allowed_models,
+            quota: input.quota.map(Into::into),
+            output_filter: input.output_filter,
+        },
+    );
+    tenant.validate().map_err(ChatError::from)?;
+
+    Ok(tenant)
+}
+
+// apply_tenant makes a stored tenant visible to the chat use cases of this instance
+pub(crate) fn apply_tenant(
+    tenants: &TenantRegistry,
+    rate_limiter: &RateLimiter,
+    tenant: &Tenant,
+) -> Result<(), UseCaseError> {
+    tenants.register(tenant.clone()).map_err(ChatError::from)?;
+    rate_limiter.set_tenant_config(tenant.id, tenant.config.rate_limit);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::output_filter::{OutputFilterConfig, OutputRule};
+    use crate::internal::domain::rate_limiter::RateLimitConfig;
+    use crate::internal::infra::repository::memory::tenant::InMemoryTenantRepository;
+    use crate::internal::usecase::create_tenant::dto::{QuotaDTO, RateLimitDTO};
+
+    fn input(name: &str) -> TenantInputDTO {
+        TenantInputDTO {
+            name: name.to_string(),
+            model: Some("gpt-4o".to_string()),
+            rate_limit: Some(RateLimitDTO {
+                requests_per_minute: 10,
+                tokens_per_minute: 0,
+            }),
+            allowed_models: vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()],
+            quota: Some(QuotaDTO {
+                user_daily_tokens: 1000,
+                ..Default::default()
+            }),
+            output_filter: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryTenantRepository::new());
+        let tenants = Arc::new(TenantRegistry::new());
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+        let usecase =
+            CreateTenantUseCase::new(repository.clone(), tenants.clone(), rate_limiter.clone());
+
+        let output = usecase
+            .execute(CreateTenantInputDTO {
+                id: None,
+                tenant: input(" Acme "),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.name, "Acme");
+        assert_eq!(output.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(repository.list_tenants().await.unwrap().len(), 1);
+        assert_eq!(tenants.model(output.id).unwrap().max_tokens, 128000);
+        assert_eq!(rate_limiter.config_for(output.id).requests_per_minute, 10);
+        assert_eq!(
+            tenants
+                .find(output.id)
+                .unwrap()
+                .config
+                .quota
+                .unwrap()
+                .user_daily_tokens,
+            1000
+        );
+
+        assert!(matches!(
+            usecase
+                .execute(CreateTenantInputDTO {
+                    id: Some(output.id),
+                    tenant: input("Acme"),
+                })
+                .await,
+            Err(UseCaseError::TenantAlreadyExists(id)) if id == output.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_tenants() {
+        let usecase = CreateTenantUseCase::new(
+            Arc::new(InMemoryTenantRepository::new()),
+            Arc::new(TenantRegistry::new()),
+            Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        );
+
+        assert!(matches!(
+            usecase
+                .execute(CreateTenantInputDTO {
+                    id: Some(DEFAULT_TENANT_ID),
+                    tenant: input("Acme"),
+                })
+                .await,
+            Err(UseCaseError::TenantAlreadyExists(_))
+        ));
+        for tenant in [
+            input(" "),
+            TenantInputDTO {
+                model: Some("gpt-unknown".to_string()),
+                ..input("Acme")
+            },
+            TenantInputDTO {
+                model: Some("gpt-3.5-turbo".to_string()),
+                ..input("Acme")
+            },
+            TenantInputDTO {
+                output_filter: Some(OutputFilterConfig {
+                    rules: vec![OutputRule {
+                        name: "unbalanced".to_string(),
+                        pattern: "(".to_string(),
+                        replacement: None,
+                    }],
+                    ..Default::default()
+                }),
+                ..input("Acme")
+            },
+        ] {
+            let result = usecase
+                .execute(CreateTenantInputDTO { id: None, tenant })
+                .await;
+            assert!(
+                matches!(
+                    result,
+                    Err(UseCaseError::InvalidInput(_))
+                        | Err(UseCaseError::Domain(ChatError::InvalidConfig(_)))
+                ),
+                "{:?}",
+                result
+            );
+        }
+    }
+}
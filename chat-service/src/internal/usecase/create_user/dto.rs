@@ -0,0 +1,21 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct CreateUserInputDTO {
+    pub external_id: String,
+    pub display_name: String,
+    // tenant_id is the organization of the user, chosen by the server and never by the client
+    pub tenant_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UserOutputDTO {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub external_id: String,
+    pub display_name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // api_key is the first key of the user in plaintext, it cannot be retrieved again
+    pub api_key: String,
+}
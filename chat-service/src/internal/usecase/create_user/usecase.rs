@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::api_key::ApiKey;
+use crate::internal::domain::entity::user::User;
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::create_user::dto::{CreateUserInputDTO, UserOutputDTO};
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct CreateUserUseCase {
+    users: Arc<dyn UserRepository>,
+    api_keys: Arc<dyn ApiKeyRepository>,
+    tenants: Arc<TenantRegistry>,
+}
+
+impl CreateUserUseCase {
+    pub fn new(users: Arc<dyn UserRepository>, api_keys: Arc<dyn ApiKeyRepository>) -> Self {
+        Self {
+            users,
+            api_keys,
+            tenants: Arc::new(TenantRegistry::new()),
+        }
+    }
+
+    // with_tenants lets users be registered in the configured tenants
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = tenants;
+        self
+    }
+
+    // execute registers a user and issues its first API key, external ids are unique
+    // within a tenant
+    #[instrument(name = "create_user", skip_all)]
+    pub async fn execute(&self, input: CreateUserInputDTO) -> Result<UserOutputDTO, UseCaseError> {
+        let tenant_id = input.tenant_id;
+        if !self.tenants.contains(tenant_id) {
+            return Err(UseCaseError::TenantNotFound(tenant_id));
+        }
+
+        let user = User::new(
+            Uuid::new_v4(),
+            &input.external_id,
+            &input.display_name,
+            chrono::Utc::now(),
+        )
+        .with_tenant(tenant_id);
+        user.validate()?;
+
+        if self
+            .users
+            .find_user_by_external_id(tenant_id, &user.external_id)
+            .await?
+            .is_some()
+        {
+            return Err(UseCaseError::UserAlreadyExists(user.external_id));
+        }
+
+        self.users.create_user(&user).await?;
+
+        let (api_key, key) = ApiKey::generate(tenant_id, user.id);
+        self.api_keys.create_api_key(&api_key).await?;
+
+        Ok(UserOutputDTO {
+            id: user.id,
+            tenant_id,
+            external_id: user.external_id,
+            display_name: user.display_name,
+            created_at: user.created_at,
+            api_key: key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::api_key::hash_key;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig};
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::infra::repository::memory::api_key::InMemoryApiKeyRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+
+    fn input(external_id: &str) -> CreateUserInputDTO {
+        CreateUserInputDTO {
+            external_id: external_id.to_string(),
+            display_name: "Ada".to_string(),
+            tenant_id: DEFAULT_TENANT_ID,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let api_keys = Arc::new(InMemoryApiKeyRepository::new());
+        let usecase = CreateUserUseCase::new(users.clone(), api_keys.clone());
+
+        let output = usecase.execute(input("auth0|42")).await.unwrap();
+
+        assert_eq!(output.external_id, "auth0|42");
+        assert_eq!(output.display_name, "Ada");
+        assert!(users
+            .find_user_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        let api_key = api_keys
+            .find_api_key_by_hash(&hash_key(&output.api_key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(api_key.user_id, output.id);
+    }
+
+    #[tokio::test]
+    async fn test_execute_duplicate() {
+        let usecase = CreateUserUseCase::new(
+            Arc::new(InMemoryUserRepository::new()),
+            Arc::new(InMemoryApiKeyRepository::new()),
+        );
+        usecase.execute(input("auth0|42")).await.unwrap();
+
+        let result = usecase.execute(input("auth0|42")).await;
+        assert!(matches!(result, Err(UseCaseError::UserAlreadyExists(id)) if id == "auth0|42"));
+
+        let result = usecase.execute(input(" ")).await;
+        assert!(matches!(
+            result,
+            Err(UseCaseError::Domain(ChatError::InvalidUser(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_in_tenant() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let acme = Uuid::new_v4();
+        let tenants = TenantRegistry::new()
+            .with_tenant(Tenant::new(acme, "Acme", TenantConfig::default()))
+            .unwrap();
+        let usecase =
+            CreateUserUseCase::new(users.clone(), Arc::new(InMemoryApiKeyRepository::new()))
+                .with_tenants(Arc::new(tenants));
+        usecase.execute(input("auth0|42")).await.unwrap();
+
+        let output = usecase
+            .execute(CreateUserInputDTO {
+                tenant_id: acme,
+                ..input("auth0|42")
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.tenant_id, acme);
+        assert!(users
+            .find_user_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let unknown = Uuid::new_v4();
+        let result = usecase
+            .execute(CreateUserInputDTO {
+                tenant_id: unknown,
+                ..input("auth0|7")
+            })
+            .await;
+        assert!(matches!(result, Err(UseCaseError::TenantNotFound(id)) if id == unknown));
+    }
+}
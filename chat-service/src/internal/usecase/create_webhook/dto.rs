@@ -0,0 +1,42 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{Webhook, WebhookEvent};
+
+// CreateWebhookInputDTO registers a URL of the tenant for the named events, every event when
+// none is named; the secret is generated when none is given
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreateWebhookInputDTO {
+    pub tenant_id: Uuid,
+    pub url: String,
+    pub secret: Option<String>,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookOutputDTO {
+    pub id: Uuid,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Webhook> for WebhookOutputDTO {
+    fn from(webhook: &Webhook) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url.clone(),
+            events: webhook.events.clone(),
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+// CreatedWebhookOutputDTO carries the secret the calls are signed with, it is only returned
+// when the webhook is created
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CreatedWebhookOutputDTO {
+    #[serde(flatten)]
+    pub webhook: WebhookOutputDTO,
+    pub secret: String,
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::webhook::{Webhook, WebhookEvent};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::create_webhook::dto::{
+    CreateWebhookInputDTO, CreatedWebhookOutputDTO, WebhookOutputDTO,
+};
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct CreateWebhookUseCase {
+    webhooks: Arc<dyn WebhookRepository>,
+    tenants: Arc<TenantRegistry>,
+}
+
+impl CreateWebhookUseCase {
+    pub fn new(webhooks: Arc<dyn WebhookRepository>, tenants: Arc<TenantRegistry>) -> Self {
+        Self { webhooks, tenants }
+    }
+
+    // execute registers the webhook, it is called back on the events of the tenant's chats
+    // from then on
+    #[instrument(name = "create_webhook", skip_all, fields(tenant_id = %input.tenant_id))]
+    pub async fn execute(
+        &self,
+        input: CreateWebhookInputDTO,
+    ) -> Result<CreatedWebhookOutputDTO, UseCaseError> {
+        if !self.tenants.contains(input.tenant_id) {
+            return Err(UseCaseError::TenantNotFound(input.tenant_id));
+        }
+
+        let events = input
+            .events
+            .iter()
+            .map(|event| event.parse())
+            .collect::<Result<Vec<WebhookEvent>, ChatError>>()?;
+        let webhook = Webhook::new(
+            input.tenant_id,
+            &input.url,
+            input.secret.as_deref(),
+            &events,
+        )?;
+        self.webhooks.create_webhook(&webhook).await?;
+
+        Ok(CreatedWebhookOutputDTO {
+            webhook: WebhookOutputDTO::from(&webhook),
+            secret: webhook.secret,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let webhooks = Arc::new(InMemoryWebhookRepository::new());
+        let usecase = CreateWebhookUseCase::new(webhooks.clone(), Arc::new(TenantRegistry::new()));
+        let input = CreateWebhookInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            url: "https://example.com/hooks".to_string(),
+            secret: None,
+            events: vec!["chat.created".to_string()],
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+        assert_eq!(output.webhook.events, vec![WebhookEvent::ChatCreated]);
+        let stored = webhooks
+            .find_webhook_by_id(DEFAULT_TENANT_ID, output.webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.secret, output.secret);
+
+        assert!(matches!(
+            usecase
+                .execute(CreateWebhookInputDTO {
+                    events: vec!["chat.deleted".to_string()],
+                    ..input.clone()
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidWebhook(_)))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(CreateWebhookInputDTO {
+                    tenant_id: Uuid::new_v4(),
+                    ..input
+                })
+                .await,
+            Err(UseCaseError::TenantNotFound(_))
+        ));
+    }
+}
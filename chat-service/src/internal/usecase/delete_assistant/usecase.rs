@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct DeleteAssistantUseCase {
+    assistants: Arc<dyn AssistantRepository>,
+}
+
+impl DeleteAssistantUseCase {
+    pub fn new(assistants: Arc<dyn AssistantRepository>) -> Self {
+        Self { assistants }
+    }
+
+    // execute removes the assistant, the chats started with it go on without tools
+    #[instrument(name = "delete_assistant", skip_all, fields(tenant_id = %tenant_id, assistant_id = %assistant_id))]
+    pub async fn execute(&self, tenant_id: Uuid, assistant_id: Uuid) -> Result<(), UseCaseError> {
+        self.assistants
+            .find_assistant_by_id(tenant_id, assistant_id)
+            .await?
+            .ok_or(UseCaseError::AssistantNotFound(assistant_id))?;
+
+        self.assistants
+            .delete_assistant(tenant_id, assistant_id)
+            .await?;
+
+        Ok(())
+    }
+}
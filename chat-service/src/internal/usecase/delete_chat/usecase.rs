@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::repository::vector_store::VectorStore;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct DeleteChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+    vectors: Option<Arc<dyn VectorStore>>,
+}
+
+impl DeleteChatUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self {
+            repository,
+            vectors: None,
+        }
+    }
+
+    // with_vector_store drops the embedded messages of the chat so it leaves the search results
+    pub fn with_vector_store(mut self, vectors: Arc<dyn VectorStore>) -> Self {
+        self.vectors = Some(vectors);
+        self
+    }
+
+    // execute soft deletes the chat, it disappears from reads and listings right away and is
+    // purged with its messages once the retention has passed; chats can only be deleted by
+    // their owner
+    #[instrument(name = "delete_chat", skip_all, fields(chat_id = %chat_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(tenant_id, chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(chat_id))?;
+
+        if chat.user_id != user_id {
+            return Err(UseCaseError::Forbidden(chat_id));
+        }
+
+        chat.delete()?;
+        self.repository.save_chat(&mut chat).await?;
+
+        if let Some(vectors) = &self.vectors {
+            vectors.delete_by_source(tenant_id, chat_id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    fn chat() -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Ended,
+            0,
+            ChatConfig::default_for(model),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let usecase = DeleteChatUseCase::new(repository.clone());
+        let chat = chat();
+        repository.create_chat(&chat).await.unwrap();
+
+        assert!(matches!(
+            usecase.execute(chat.tenant_id, chat.id, Uuid::new_v4()).await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+
+        usecase
+            .execute(chat.tenant_id, chat.id, chat.user_id)
+            .await
+            .unwrap();
+
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, ChatStatus::Deleted);
+        assert!(repository
+            .list_chats_by_user(chat.tenant_id, chat.user_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            usecase.execute(chat.tenant_id, chat.id, chat.user_id).await,
+            Err(UseCaseError::ChatNotFound(id)) if id == chat.id
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::document::DocumentRepository;
+use crate::internal::domain::repository::vector_store::VectorStore;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct DeleteDocumentUseCase {
+    documents: Arc<dyn DocumentRepository>,
+    store: Arc<dyn VectorStore>,
+}
+
+impl DeleteDocumentUseCase {
+    pub fn new(documents: Arc<dyn DocumentRepository>, store: Arc<dyn VectorStore>) -> Self {
+        Self { documents, store }
+    }
+
+    // execute removes the document and its chunks, chats stop retrieving them right away;
+    // documents can only be deleted by their owner
+    #[instrument(name = "delete_document", skip_all, fields(document_id = %document_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        document_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UseCaseError> {
+        let document = self
+            .documents
+            .find_document_by_id(tenant_id, document_id)
+            .await?
+            .ok_or(UseCaseError::DocumentNotFound(document_id))?;
+
+        if document.user_id != user_id {
+            return Err(UseCaseError::Forbidden(document_id));
+        }
+
+        // the chunks go first so a failure leaves a listed document to delete again
+        self.store.delete_by_source(tenant_id, document_id).await?;
+        self.documents
+            .delete_document(tenant_id, document_id)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::document::{Document, DocumentFormat};
+    use crate::internal::domain::entity::embedding::{VectorKind, VectorRecord};
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::repository::vector_store::VectorQuery;
+    use crate::internal::infra::repository::memory::document::InMemoryDocumentRepository;
+    use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let documents = Arc::new(InMemoryDocumentRepository::new());
+        let store = Arc::new(InMemoryVectorStore::new());
+        let user_id = Uuid::new_v4();
+        let document = Document::new(
+            Uuid::new_v4(),
+            DEFAULT_TENANT_ID,
+            user_id,
+            "notes.txt",
+            DocumentFormat::Text,
+            5,
+            chrono::Utc::now(),
+        );
+        documents.create_document(&document).await.unwrap();
+        store
+            .upsert(&[VectorRecord {
+                id: Uuid::new_v4(),
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                kind: VectorKind::DocumentChunk,
+                source_id: document.id,
+                content: "notes".to_string(),
+                model: "test".to_string(),
+                embedding: vec![1.0],
+                metadata: serde_json::json!({}),
+                created_at: document.created_at,
+            }])
+            .await
+            .unwrap();
+        let usecase = DeleteDocumentUseCase::new(documents.clone(), store.clone());
+
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, document.id, Uuid::new_v4())
+                .await,
+            Err(UseCaseError::Forbidden(_))
+        ));
+
+        usecase
+            .execute(DEFAULT_TENANT_ID, document.id, user_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            documents
+                .find_document_by_id(DEFAULT_TENANT_ID, document.id)
+                .await
+                .unwrap(),
+            None
+        );
+        let chunks = store
+            .search(&VectorQuery {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                model: "test".to_string(),
+                embedding: vec![1.0],
+                kinds: vec![],
+                limit: 10,
+                min_score: 0.0,
+            })
+            .await
+            .unwrap();
+        assert!(chunks.is_empty());
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, document.id, user_id)
+                .await,
+            Err(UseCaseError::DocumentNotFound(_))
+        ));
+    }
+}
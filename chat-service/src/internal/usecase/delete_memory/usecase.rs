@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct DeleteMemoryUseCase {
+    memories: Arc<dyn MemoryRepository>,
+}
+
+impl DeleteMemoryUseCase {
+    pub fn new(memories: Arc<dyn MemoryRepository>) -> Self {
+        Self { memories }
+    }
+
+    // execute forgets the memory, new chats are no longer told about it; memories can only be
+    // deleted by the user they are about
+    #[instrument(name = "delete_memory", skip_all, fields(memory_id = %memory_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        memory_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), UseCaseError> {
+        let memory = self
+            .memories
+            .find_memory_by_id(tenant_id, memory_id)
+            .await?
+            .ok_or(UseCaseError::MemoryNotFound(memory_id))?;
+
+        if memory.user_id != user_id {
+            return Err(UseCaseError::Forbidden(memory_id));
+        }
+
+        self.memories.delete_memory(tenant_id, memory_id).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user_memory::Memory;
+    use crate::internal::infra::repository::memory::user_memory::InMemoryMemoryRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let memories = Arc::new(InMemoryMemoryRepository::new());
+        let user_id = Uuid::new_v4();
+        let memory = Memory::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            Uuid::new_v4(),
+            "The user is called Ana.",
+        );
+        memories
+            .create_memories(std::slice::from_ref(&memory), 10)
+            .await
+            .unwrap();
+        let usecase = DeleteMemoryUseCase::new(memories.clone());
+
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, memory.id, Uuid::new_v4())
+                .await,
+            Err(UseCaseError::Forbidden(_))
+        ));
+
+        usecase
+            .execute(DEFAULT_TENANT_ID, memory.id, user_id)
+            .await
+            .unwrap();
+        assert!(memories
+            .list_memories_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            usecase.execute(DEFAULT_TENANT_ID, memory.id, user_id).await,
+            Err(UseCaseError::MemoryNotFound(_))
+        ));
+    }
+}
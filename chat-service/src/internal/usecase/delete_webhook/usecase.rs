@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct DeleteWebhookUseCase {
+    webhooks: Arc<dyn WebhookRepository>,
+}
+
+impl DeleteWebhookUseCase {
+    pub fn new(webhooks: Arc<dyn WebhookRepository>) -> Self {
+        Self { webhooks }
+    }
+
+    // execute stops calling the webhook back and drops its pending deliveries
+    #[instrument(name = "delete_webhook", skip_all, fields(tenant_id = %tenant_id, webhook_id = %webhook_id))]
+    pub async fn execute(&self, tenant_id: Uuid, webhook_id: Uuid) -> Result<(), UseCaseError> {
+        self.webhooks
+            .find_webhook_by_id(tenant_id, webhook_id)
+            .await?
+            .ok_or(UseCaseError::WebhookNotFound(webhook_id))?;
+
+        self.webhooks.delete_webhook(tenant_id, webhook_id).await?;
+
+        Ok(())
+    }
+}
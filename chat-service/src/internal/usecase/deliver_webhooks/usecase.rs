@@ -0,0 +1,216 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, Webhook, WebhookDelivery};
+use crate::internal::domain::gateway::webhook::WebhookSender;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+// DELIVERY_LEASE is how long a claimed delivery is left to its worker, one that dies mid call
+// gets it sent again once the lease is over
+const DELIVERY_LEASE: Duration = Duration::from_secs(300);
+// MAX_BACKOFF_DOUBLINGS stops the backoff from growing past 1024 times the first one
+const MAX_BACKOFF_DOUBLINGS: u32 = 10;
+
+pub struct DeliverWebhooksUseCase {
+    repository: Arc<dyn WebhookRepository>,
+    sender: Arc<dyn WebhookSender>,
+    batch_size: usize,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+impl DeliverWebhooksUseCase {
+    // new makes up to max_attempts calls per delivery, waiting backoff after the first failed
+    // one and twice as long after each next one
+    pub fn new(
+        repository: Arc<dyn WebhookRepository>,
+        sender: Arc<dyn WebhookSender>,
+        batch_size: usize,
+        max_attempts: u32,
+        backoff: Duration,
+    ) -> Self {
+        Self {
+            repository,
+            sender,
+            batch_size: batch_size.max(1),
+            max_attempts: max_attempts.max(1),
+            backoff,
+        }
+    }
+
+    // execute calls the webhooks of the due deliveries and returns how many succeeded; a
+    // delivery is claimed before the call so that several instances do not make it twice, and
+    // moves to the dead letters once its last attempt fails
+    #[instrument(name = "deliver_webhooks", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let now = chrono::Utc::now();
+        let due = self.repository.due_deliveries(now, self.batch_size).await?;
+
+        let mut webhooks: HashMap<Uuid, Option<Webhook>> = HashMap::new();
+        let mut delivered = 0;
+        for mut delivery in due {
+            let lease_until = now + chrono::Duration::from_std(DELIVERY_LEASE).unwrap_or_default();
+            if !self
+                .repository
+                .claim_delivery(delivery.id, delivery.next_attempt_at, lease_until)
+                .await?
+            {
+                continue;
+            }
+
+            let webhook = match webhooks.entry(delivery.webhook_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    self.repository
+                        .find_webhook_by_id(delivery.tenant_id, delivery.webhook_id)
+                        .await?,
+                ),
+            };
+            let Some(webhook) = webhook else {
+                // the webhook was deleted since, nobody is listening anymore
+                self.repository.complete_delivery(delivery.id).await?;
+                continue;
+            };
+
+            match self.sender.send(webhook, &delivery).await {
+                Ok(()) => {
+                    self.repository.complete_delivery(delivery.id).await?;
+                    delivered += 1;
+                }
+                Err(err) => {
+                    warn!(delivery_id = %delivery.id, attempts = delivery.attempts + 1, error = %err, "webhook call failed");
+                    self.fail(&mut delivery, err.to_string()).await?;
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    async fn fail(
+        &self,
+        delivery: &mut WebhookDelivery,
+        error: String,
+    ) -> Result<(), UseCaseError> {
+        let now = chrono::Utc::now();
+        delivery.attempts += 1;
+        delivery.last_error = Some(error);
+
+        if delivery.attempts >= self.max_attempts {
+            self.repository
+                .dead_letter(&DeadLetter {
+                    delivery: delivery.clone(),
+                    failed_at: now,
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let doublings = (delivery.attempts - 1).min(MAX_BACKOFF_DOUBLINGS);
+        let backoff = self.backoff * 2u32.pow(doublings);
+        delivery.next_attempt_at = now + chrono::Duration::from_std(backoff).unwrap_or_default();
+        self.repository.reschedule_delivery(delivery).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::webhook::{WebhookEvent, WebhookPayload};
+    use crate::internal::domain::gateway::webhook::WebhookError;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+
+    // FlakySender fails the calls of the webhooks whose URL contains "down"
+    #[derive(Default)]
+    struct FlakySender {
+        calls: Mutex<Vec<Uuid>>,
+    }
+
+    #[async_trait]
+    impl WebhookSender for FlakySender {
+        async fn send(
+            &self,
+            webhook: &Webhook,
+            delivery: &WebhookDelivery,
+        ) -> Result<(), WebhookError> {
+            self.calls.lock().unwrap().push(delivery.id);
+            if webhook.url.contains("down") {
+                return Err(WebhookError("503 Service Unavailable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    fn delivery(webhook: &Webhook) -> WebhookDelivery {
+        let outbox = OutboxEvent {
+            id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            event: ChatEvent::ChatEnded,
+            occurred_at: chrono::Utc::now(),
+        };
+        WebhookDelivery::new(
+            webhook,
+            &WebhookPayload::new(WebhookEvent::ChatEnded, &outbox),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_execute_retries_then_dead_letters() {
+        let repository = Arc::new(InMemoryWebhookRepository::new());
+        let up = Webhook::new(DEFAULT_TENANT_ID, "https://example.com/up", None, &[]).unwrap();
+        let down = Webhook::new(DEFAULT_TENANT_ID, "https://example.com/down", None, &[]).unwrap();
+        repository.create_webhook(&up).await.unwrap();
+        repository.create_webhook(&down).await.unwrap();
+        let failing = delivery(&down);
+        repository
+            .enqueue_deliveries(&[delivery(&up), failing.clone()])
+            .await
+            .unwrap();
+        let sender = Arc::new(FlakySender::default());
+        let usecase =
+            DeliverWebhooksUseCase::new(repository.clone(), sender.clone(), 10, 2, Duration::ZERO);
+
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+        let due = repository
+            .due_deliveries(chrono::Utc::now(), 10)
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, failing.id);
+        assert_eq!(due[0].attempts, 1);
+        assert!(due[0].last_error.as_deref().unwrap().contains("503"));
+
+        assert_eq!(usecase.execute().await.unwrap(), 0);
+        assert!(repository
+            .due_deliveries(chrono::Utc::now(), 10)
+            .await
+            .unwrap()
+            .is_empty());
+        let dead_letters = repository
+            .list_dead_letters(DEFAULT_TENANT_ID, 10)
+            .await
+            .unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].delivery.id, failing.id);
+        assert_eq!(dead_letters[0].delivery.attempts, 2);
+        assert_eq!(sender.calls.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::internal::domain::deadline::DeadlineExceeded;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::blob_store::BlobError;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::event_publisher::PublishError;
+use crate::internal::domain::moderator::ModerationError;
+use crate::internal::domain::prompt_guard::GuardError;
+use crate::internal::domain::quota::{QuotaError, QuotaExceeded};
+use crate::internal::domain::rate_limiter::RateLimitExceeded;
+use crate::internal::domain::redactor::RedactionError;
+use crate::internal::domain::repository::chat::RepositoryError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UseCaseError {
+    #[error("missing or invalid credentials")]
+    Unauthenticated,
+    #[error("chat {0} not found")]
+    ChatNotFound(Uuid),
+    #[error("message {0} not found")]
+    MessageNotFound(Uuid),
+    #[error("prompt template {0} not found")]
+    TemplateNotFound(String),
+    #[error("prompt template {0} already exists")]
+    TemplateAlreadyExists(String),
+    #[error("assistant {0} not found")]
+    AssistantNotFound(Uuid),
+    #[error("assistant {0} already exists")]
+    AssistantAlreadyExists(String),
+    #[error("document {0} not found")]
+    DocumentNotFound(Uuid),
+    #[error("memory {0} not found")]
+    MemoryNotFound(Uuid),
+    #[error("scheduled message {0} not found")]
+    ScheduledMessageNotFound(Uuid),
+    #[error("webhook {0} not found")]
+    WebhookNotFound(Uuid),
+    #[error("batch {0} not found")]
+    BatchNotFound(Uuid),
+    #[error("job {0} not found")]
+    JobNotFound(Uuid),
+    #[error("user {0} not found")]
+    UserNotFound(Uuid),
+    #[error("user with external id {0} already exists")]
+    UserAlreadyExists(String),
+    #[error("tenant {0} not found")]
+    TenantNotFound(Uuid),
+    #[error("tenant {0} already exists")]
+    TenantAlreadyExists(Uuid),
+    #[error("chat {0} does not belong to the user")]
+    Forbidden(Uuid),
+    #[error("idempotency key {0} was used with a different request")]
+    IdempotencyKeyReused(String),
+    #[error("a request with idempotency key {0} is still in progress")]
+    IdempotencyKeyInProgress(String),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("rate limit exceeded, retry after {}s", retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+    #[error(transparent)]
+    QuotaExceeded(#[from] QuotaExceeded),
+    #[error("the client went away while chat {0} was streamed")]
+    StreamCancelled(Uuid),
+    #[error(transparent)]
+    DeadlineExceeded(#[from] DeadlineExceeded),
+    #[error("model kept calling tools after {0} rounds")]
+    ToolRoundsExceeded(usize),
+    #[error(transparent)]
+    Domain(#[from] ChatError),
+    #[error(transparent)]
+    Gateway(#[from] GatewayError),
+    #[error(transparent)]
+    Repository(#[from] RepositoryError),
+    #[error(transparent)]
+    Publish(#[from] PublishError),
+    #[error(transparent)]
+    Blob(#[from] BlobError),
+}
+
+impl From<RateLimitExceeded> for UseCaseError {
+    fn from(err: RateLimitExceeded) -> Self {
+        UseCaseError::RateLimited {
+            retry_after: err.retry_after,
+        }
+    }
+}
+
+impl From<QuotaError> for UseCaseError {
+    fn from(err: QuotaError) -> Self {
+        match err {
+            QuotaError::Exceeded(err) => UseCaseError::QuotaExceeded(err),
+            QuotaError::Repository(err) => UseCaseError::Repository(err),
+        }
+    }
+}
+
+impl From<ModerationError> for UseCaseError {
+    fn from(err: ModerationError) -> Self {
+        match err {
+            ModerationError::Flagged(err) => UseCaseError::Domain(err),
+            ModerationError::Gateway(err) => UseCaseError::Gateway(err),
+            ModerationError::Repository(err) => UseCaseError::Repository(err),
+        }
+    }
+}
+
+impl From<GuardError> for UseCaseError {
+    fn from(err: GuardError) -> Self {
+        match err {
+            GuardError::Blocked(err) => UseCaseError::Domain(err),
+            GuardError::Gateway(err) => UseCaseError::Gateway(err),
+            GuardError::Repository(err) => UseCaseError::Repository(err),
+        }
+    }
+}
+
+// a message whose original cannot be kept is not sent, it fails like a failed write
+impl From<RedactionError> for UseCaseError {
+    fn from(err: RedactionError) -> Self {
+        match err {
+            RedactionError::Cipher(err) => {
+                UseCaseError::Repository(RepositoryError::Database(err.to_string()))
+            }
+            RedactionError::Repository(err) => UseCaseError::Repository(err),
+        }
+    }
+}
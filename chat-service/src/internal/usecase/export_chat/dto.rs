@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, TrimmingPolicy};
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+use crate::internal::usecase::list_chat_messages::dto::MessageOutputDTO;
+
+// ExportFormat is how the transcript is written: one JSON document, Markdown to read or share,
+// or JSON lines with the chat first and then a message per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Markdown,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportChatInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    pub format: ExportFormat,
+}
+
+// TranscriptChatDTO is what the transcript tells about the chat besides its messages, its
+// settings are the ones an import restores
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptChatDTO {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: Option<String>,
+    pub status: String,
+    pub model: String,
+    pub temperature: f32,
+    pub top_p: f32,
+    pub n: u32,
+    pub stop: Vec<String>,
+    pub max_tokens: usize,
+    pub presence_penalty: f32,
+    pub frequency_penalty: f32,
+    pub trimming_policy: TrimmingPolicy,
+    pub tools: Vec<ToolDefinition>,
+    pub response_format: ResponseFormat,
+    pub token_usage: usize,
+    pub message_count: usize,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TranscriptChatDTO {
+    pub fn new(chat: &Chat, exported_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            id: chat.id,
+            user_id: chat.user_id,
+            title: chat.title.clone(),
+            status: chat.status.to_string(),
+            model: chat.config.model.name.clone(),
+            temperature: chat.config.temperature,
+            top_p: chat.config.top_p,
+            n: chat.config.n,
+            stop: chat.config.stop.clone(),
+            max_tokens: chat.config.max_tokens,
+            presence_penalty: chat.config.presence_penalty,
+            frequency_penalty: chat.config.frequency_penalty,
+            trimming_policy: chat.config.trimming_policy,
+            tools: chat.config.tools.clone(),
+            response_format: chat.config.response_format.clone(),
+            token_usage: chat.token_usage,
+            message_count: chat.count_messages(),
+            exported_at,
+        }
+    }
+}
+
+// TranscriptMessageDTO is a message as listed, along with the tool calls it made or answered
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptMessageDTO {
+    #[serde(flatten)]
+    pub message: MessageOutputDTO,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl From<&Message> for TranscriptMessageDTO {
+    fn from(message: &Message) -> Self {
+        Self {
+            message: MessageOutputDTO::from(message),
+            tool_calls: message.tool_calls.clone(),
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
+}
+
+// TranscriptDTO is the whole chat as it is exported in JSON, the messages trimmed or summarized
+// out of the context included
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptDTO {
+    pub chat: TranscriptChatDTO,
+    pub system_message: TranscriptMessageDTO,
+    pub erased_messages: Vec<TranscriptMessageDTO>,
+    pub messages: Vec<TranscriptMessageDTO>,
+}
+
+impl TranscriptDTO {
+    pub fn new(chat: &Chat, exported_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            chat: TranscriptChatDTO::new(chat, exported_at),
+            system_message: TranscriptMessageDTO::from(chat.initial_system_message()),
+            erased_messages: chat
+                .erased_messages
+                .iter()
+                .map(TranscriptMessageDTO::from)
+                .collect(),
+            messages: chat
+                .messages
+                .iter()
+                .map(TranscriptMessageDTO::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOutputDTO {
+    pub format: ExportFormat,
+    // filename is what the transcript is offered to be saved as
+    pub filename: String,
+    pub content: String,
+}
@@ -0,0 +1,295 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::internal::domain::entity::attachment::AttachmentSource;
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::archive_chats::usecase::read_history;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::export_chat::dto::{
+    ExportChatInputDTO, ExportFormat, ExportOutputDTO, TranscriptDTO,
+};
+
+pub struct ExportChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+    blobs: Option<Arc<dyn BlobStore>>,
+}
+
+impl ExportChatUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self {
+            repository,
+            blobs: None,
+        }
+    }
+
+    // with_blob_store exports cold chats, their history is read back from the store
+    pub fn with_blob_store(mut self, blobs: Arc<dyn BlobStore>) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    // execute writes the transcript of the chat in the format asked for: its settings, the
+    // system message, the messages erased from the context and the ones the chat continues
+    // from; chats can only be exported by their owner
+    #[instrument(name = "export_chat", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: ExportChatInputDTO,
+    ) -> Result<ExportOutputDTO, UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+        read_history(self.blobs.as_deref(), &mut chat).await?;
+
+        let transcript = TranscriptDTO::new(&chat, chrono::Utc::now());
+        // the transcript only holds strings, numbers and string keyed maps, it always serializes
+        let content = match input.format {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&transcript).expect("transcript serializes")
+            }
+            ExportFormat::Markdown => to_markdown(&transcript),
+            ExportFormat::Jsonl => to_jsonl(&transcript),
+        };
+
+        Ok(ExportOutputDTO {
+            format: input.format,
+            filename: format!("chat-{}.{}", chat.id, input.format.extension()),
+            content,
+        })
+    }
+}
+
+// to_markdown writes the transcript to be read: the settings as a list, then a section per
+// message with its tool calls and attachments noted below the content, the erased messages
+// noted as such
+fn to_markdown(transcript: &TranscriptDTO) -> String {
+    let chat = &transcript.chat;
+    let title = match &chat.title {
+        Some(title) => title.clone(),
+        None => format!("Chat {}", chat.id),
+    };
+
+    let mut markdown = format!("# {}\n\n", title);
+    markdown.push_str(&format!("- Chat: {}\n", chat.id));
+    markdown.push_str(&format!("- Status: {}\n", chat.status));
+    markdown.push_str(&format!("- Model: {}\n", chat.model));
+    markdown.push_str(&format!("- Temperature: {}\n", chat.temperature));
+    markdown.push_str(&format!("- Top p: {}\n", chat.top_p));
+    markdown.push_str(&format!(
+        "- Tokens: {} of {}\n",
+        chat.token_usage, chat.max_tokens
+    ));
+    markdown.push_str(&format!("- Messages: {}\n", chat.message_count));
+    markdown.push_str(&format!("- Exported: {}\n", timestamp(chat.exported_at)));
+
+    let messages = std::iter::once((&transcript.system_message, false))
+        .chain(
+            transcript
+                .erased_messages
+                .iter()
+                .map(|message| (message, true)),
+        )
+        .chain(transcript.messages.iter().map(|message| (message, false)));
+    for (message, erased) in messages {
+        let output = &message.message;
+        markdown.push_str(&format!(
+            "\n## {} · {}\n\n",
+            capitalize(&output.role.to_string()),
+            timestamp(output.created_at)
+        ));
+        if !output.content.is_empty() {
+            markdown.push_str(&format!("{}\n", output.content));
+        }
+
+        let mut notes = vec![];
+        if let Some(tool_call_id) = &message.tool_call_id {
+            notes.push(format!("Result of {}", tool_call_id));
+        }
+        for tool_call in &message.tool_calls {
+            notes.push(format!(
+                "Called {} ({}) with `{}`",
+                tool_call.name, tool_call.id, tool_call.arguments
+            ));
+        }
+        for attachment in &output.attachments {
+            let mime_type = attachment.mime_type.as_deref().unwrap_or("image");
+            notes.push(match &attachment.source {
+                AttachmentSource::Url(url) => format!("Attachment: {} at {}", mime_type, url),
+                AttachmentSource::Base64(_) => format!("Attachment: {}, inline", mime_type),
+            });
+        }
+        if output.interrupted {
+            notes.push("The reply was interrupted".to_string());
+        }
+        if erased {
+            notes.push("Erased from the context".to_string());
+        }
+        for note in notes {
+            markdown.push_str(&format!("\n_{}_\n", note));
+        }
+    }
+
+    markdown
+}
+
+// to_jsonl writes the chat on the first line, then the system message, the erased messages and
+// every message on a line of their own
+fn to_jsonl(transcript: &TranscriptDTO) -> String {
+    let mut jsonl = line("chat", &transcript.chat);
+    jsonl.push_str(&line("message", &transcript.system_message));
+    for message in &transcript.erased_messages {
+        jsonl.push_str(&line("erased_message", message));
+    }
+    for message in &transcript.messages {
+        jsonl.push_str(&line("message", message));
+    }
+
+    jsonl
+}
+
+// line writes the value as a JSON line, type tells the chat line from the message lines
+fn line(kind: &str, value: &impl Serialize) -> String {
+    let mut value = serde_json::to_value(value).expect("transcript serializes");
+    if let Some(object) = value.as_object_mut() {
+        object.insert("type".to_string(), kind.into());
+    }
+
+    value.to_string() + "\n"
+}
+
+fn timestamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::Chat;
+    use crate::internal::domain::entity::tool::ToolCall;
+    use crate::internal::testing::builder::{ChatBuilder, MessageBuilder};
+    use crate::internal::testing::InMemoryChatRepository;
+
+    async fn setup() -> (ExportChatUseCase, Chat) {
+        let mut chat = ChatBuilder::new()
+            .title("Weather")
+            .user_message("Will it rain?")
+            .message(
+                MessageBuilder::assistant("")
+                    .build()
+                    .with_tool_calls(vec![ToolCall {
+                        id: "call_1".to_string(),
+                        name: "get_weather".to_string(),
+                        arguments: r#"{"city":"Lisbon"}"#.to_string(),
+                    }]),
+            )
+            .assistant_message("Likely, take an umbrella.")
+            .build();
+        chat.erased_messages
+            .push(MessageBuilder::user("Good morning").build());
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        (ExportChatUseCase::new(repository), chat)
+    }
+
+    fn input(chat: &Chat, format: ExportFormat) -> ExportChatInputDTO {
+        ExportChatInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            format,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, chat) = setup().await;
+
+        let output = usecase
+            .execute(input(&chat, ExportFormat::Json))
+            .await
+            .unwrap();
+        assert_eq!(output.filename, format!("chat-{}.json", chat.id));
+        let json: serde_json::Value = serde_json::from_str(&output.content).unwrap();
+        assert_eq!(json["chat"]["id"], chat.id.to_string());
+        assert_eq!(json["chat"]["title"], "Weather");
+        assert_eq!(
+            json["system_message"]["content"],
+            chat.initial_system_message().content
+        );
+        assert_eq!(json["chat"]["trimming_policy"], "trim_oldest");
+        assert_eq!(json["erased_messages"][0]["content"], "Good morning");
+        assert_eq!(json["messages"].as_array().unwrap().len(), 3);
+        assert_eq!(json["messages"][1]["tool_calls"][0]["name"], "get_weather");
+
+        let output = usecase
+            .execute(input(&chat, ExportFormat::Markdown))
+            .await
+            .unwrap();
+        assert_eq!(output.filename, format!("chat-{}.md", chat.id));
+        assert!(output.content.starts_with("# Weather\n\n- Chat: "));
+        assert!(output.content.contains("\n## System · "));
+        assert!(output.content.contains("Will it rain?\n"));
+        assert!(output
+            .content
+            .contains("Good morning\n\n_Erased from the context_\n"));
+        assert!(output
+            .content
+            .contains(r#"_Called get_weather (call_1) with `{"city":"Lisbon"}`_"#));
+
+        let output = usecase
+            .execute(input(&chat, ExportFormat::Jsonl))
+            .await
+            .unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0]["type"], "chat");
+        assert_eq!(lines[1]["type"], "message");
+        assert_eq!(lines[1]["role"], "system");
+        assert_eq!(lines[2]["type"], "erased_message");
+        assert_eq!(lines[5]["content"], "Likely, take an umbrella.");
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_other_users() {
+        let (usecase, chat) = setup().await;
+
+        let mut other = input(&chat, ExportFormat::Json);
+        other.user_id = Uuid::new_v4();
+        assert!(matches!(
+            usecase.execute(other).await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+
+        let mut missing = input(&chat, ExportFormat::Json);
+        missing.chat_id = Uuid::new_v4();
+        assert!(matches!(
+            usecase.execute(missing).await,
+            Err(UseCaseError::ChatNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,10 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForkChatInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // message_id is the last message copied into the fork, the whole chat when omitted
+    pub message_id: Option<Uuid>,
+}
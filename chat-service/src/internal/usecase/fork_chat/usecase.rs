@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::archive_chats::usecase::read_history;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::fork_chat::dto::ForkChatInputDTO;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+
+pub struct ForkChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+    blobs: Option<Arc<dyn BlobStore>>,
+}
+
+impl ForkChatUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self {
+            repository,
+            blobs: None,
+        }
+    }
+
+    // with_blob_store forks cold chats, their history is read back from the store
+    pub fn with_blob_store(mut self, blobs: Arc<dyn BlobStore>) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    // execute copies the chat up to the given message into a new chat the user can continue
+    // differently, the original chat is left untouched; chats can only be forked by their owner
+    #[instrument(name = "fork_chat", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(&self, input: ForkChatInputDTO) -> Result<ChatOutputDTO, UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+        read_history(self.blobs.as_deref(), &mut chat).await?;
+
+        if let Some(message_id) = input.message_id {
+            if !chat.messages.iter().any(|message| message.id == message_id) {
+                return Err(UseCaseError::MessageNotFound(message_id));
+            }
+        }
+
+        let fork = chat.fork(Uuid::new_v4(), input.message_id)?;
+        self.repository.create_chat(&fork).await?;
+
+        Ok(ChatOutputDTO::from(&fork))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::domain::history_codec::encode_history;
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    async fn setup() -> (ForkChatUseCase, Arc<InMemoryChatRepository>, Chat) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi, how can I help?"),
+                message(Role::User, "Tell me a joke"),
+                message(Role::Assistant, "Why did the chicken cross the road?"),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        (ForkChatUseCase::new(repository.clone()), repository, chat)
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, repository, chat) = setup().await;
+
+        let output = usecase
+            .execute(ForkChatInputDTO {
+                tenant_id: chat.tenant_id,
+                chat_id: chat.id,
+                user_id: chat.user_id,
+                message_id: Some(chat.messages[1].id),
+            })
+            .await
+            .unwrap();
+
+        assert_ne!(output.id, chat.id);
+        assert_eq!(output.user_id, chat.user_id);
+        assert_eq!(output.message_count, 2);
+        let fork = repository
+            .find_chat_by_id(chat.tenant_id, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fork.messages[0].content, "Hello!");
+        let original = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(original, chat);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_other_users_and_messages() {
+        let (usecase, _, chat) = setup().await;
+        let missing = Uuid::new_v4();
+
+        assert!(matches!(
+            usecase
+                .execute(ForkChatInputDTO {
+                    tenant_id: chat.tenant_id,
+                    chat_id: chat.id,
+                    user_id: Uuid::new_v4(),
+                    message_id: None,
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ForkChatInputDTO {
+                    tenant_id: chat.tenant_id,
+                    chat_id: chat.id,
+                    user_id: chat.user_id,
+                    message_id: Some(missing),
+                })
+                .await,
+            Err(UseCaseError::MessageNotFound(id)) if id == missing
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_forks_cold_chats() {
+        let (_, repository, hot) = setup().await;
+        let blobs = Arc::new(InMemoryBlobStore::new());
+        blobs
+            .put("cold.jsonl", encode_history(&hot).unwrap())
+            .await
+            .unwrap();
+        let mut cold = hot.clone();
+        cold.archive_history("cold.jsonl".to_string()).unwrap();
+        repository.archive_chat_history(&mut cold).await.unwrap();
+        let input = ForkChatInputDTO {
+            tenant_id: cold.tenant_id,
+            chat_id: cold.id,
+            user_id: cold.user_id,
+            message_id: Some(hot.messages[1].id),
+        };
+
+        assert!(matches!(
+            ForkChatUseCase::new(repository.clone())
+                .execute(input.clone())
+                .await,
+            Err(UseCaseError::Domain(ChatError::ChatArchived))
+        ));
+
+        let usecase = ForkChatUseCase::new(repository.clone()).with_blob_store(blobs);
+        let output = usecase.execute(input).await.unwrap();
+        assert_eq!(output.message_count, 2);
+        let original = repository
+            .find_chat_by_id(cold.tenant_id, cold.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(original.is_cold());
+    }
+}
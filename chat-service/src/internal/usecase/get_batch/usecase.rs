@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::batch::{Batch, BatchItem, BatchStatus};
+use crate::internal::domain::entity::job::JobStatus;
+use crate::internal::domain::repository::batch::BatchRepository;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::usecase::batch_completion::dto::BatchOutputDTO;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct GetBatchUseCase {
+    batches: Arc<dyn BatchRepository>,
+    jobs: Arc<dyn JobRepository>,
+}
+
+impl GetBatchUseCase {
+    pub fn new(batches: Arc<dyn BatchRepository>, jobs: Arc<dyn JobRepository>) -> Self {
+        Self { batches, jobs }
+    }
+
+    // execute returns a batch of the user, still running or completed; the batches of other
+    // users are not found
+    #[instrument(name = "get_batch", skip_all, fields(batch_id = %batch_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<BatchOutputDTO, UseCaseError> {
+        let mut batch = self
+            .batches
+            .find_batch_by_id(tenant_id, batch_id)
+            .await?
+            .filter(|batch| batch.user_id == user_id)
+            .ok_or(UseCaseError::BatchNotFound(batch_id))?;
+
+        if batch.status == BatchStatus::Running {
+            self.collect(&mut batch).await?;
+        }
+
+        Ok(BatchOutputDTO::from(&batch))
+    }
+
+    // collect fills in the answers of the jobs of a background batch done so far, and completes
+    // the batch once every one of them is done
+    async fn collect(&self, batch: &mut Batch) -> Result<(), UseCaseError> {
+        let mut items = Vec::with_capacity(batch.items.len());
+        let mut done = true;
+        for item in &batch.items {
+            let Some(job_id) = item.job_id else {
+                // a batch answered in the request has no jobs, it is completed by its request
+                return Ok(());
+            };
+            let Some(job) = self.jobs.find_job_by_id(batch.tenant_id, job_id).await? else {
+                items.push(BatchItem {
+                    error: Some("the prompt was not queued".to_string()),
+                    ..item.clone()
+                });
+                continue;
+            };
+            items.push(BatchItem {
+                chat_id: job.chat_id,
+                content: job.content,
+                error: job.error,
+                ..item.clone()
+            });
+            done &= matches!(job.status, JobStatus::Succeeded | JobStatus::Failed);
+        }
+
+        if done {
+            batch.complete(items);
+            self.batches.complete_batch(batch).await?;
+        } else {
+            batch.items = items;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::job::Job;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::batch::InMemoryBatchRepository;
+    use crate::internal::infra::repository::memory::job::InMemoryJobRepository;
+
+    #[tokio::test]
+    async fn test_execute_completes_the_batch_once_its_jobs_are_done() {
+        let user_id = Uuid::new_v4();
+        let batches = Arc::new(InMemoryBatchRepository::new());
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let mut batch = Batch::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            vec!["France?".to_string(), "Spain?".to_string()],
+        )
+        .unwrap();
+        let now = chrono::Utc::now();
+        let claimed_until = now + chrono::Duration::seconds(60);
+        let mut queued = Vec::new();
+        for item in batch.items.iter_mut() {
+            let job = Job::new(
+                DEFAULT_TENANT_ID,
+                user_id,
+                None,
+                &item.prompt,
+                vec![],
+                serde_json::json!({}),
+            )
+            .unwrap();
+            item.job_id = Some(job.id);
+            jobs.create_job(&job).await.unwrap();
+            jobs.claim_job(DEFAULT_TENANT_ID, job.id, now, claimed_until)
+                .await
+                .unwrap();
+            queued.push(job);
+        }
+        batches.create_batch(&batch).await.unwrap();
+        let usecase = GetBatchUseCase::new(batches.clone(), jobs.clone());
+
+        let chat_id = Uuid::new_v4();
+        queued[0].succeed(chat_id, "Paris".to_string());
+        jobs.complete_job(&queued[0], claimed_until).await.unwrap();
+        let running = usecase
+            .execute(DEFAULT_TENANT_ID, user_id, batch.id)
+            .await
+            .unwrap();
+        assert_eq!(running.status, BatchStatus::Running);
+        assert_eq!(running.items[0].content.as_deref(), Some("Paris"));
+        assert_eq!(running.items[1].content, None);
+
+        queued[1].fail("model is unavailable".to_string());
+        jobs.complete_job(&queued[1], claimed_until).await.unwrap();
+        let completed = usecase
+            .execute(DEFAULT_TENANT_ID, user_id, batch.id)
+            .await
+            .unwrap();
+        assert_eq!(completed.status, BatchStatus::Completed);
+        assert_eq!(completed.items[0].chat_id, Some(chat_id));
+        assert_eq!(
+            completed.items[1].error.as_deref(),
+            Some("model is unavailable")
+        );
+        let stored = batches
+            .find_batch_by_id(DEFAULT_TENANT_ID, batch.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, BatchStatus::Completed);
+
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, Uuid::new_v4(), batch.id)
+                .await,
+            Err(UseCaseError::BatchNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::UserUsage;
+
+// ReportFormat is how the billing report is written: one JSON document, or CSV with a line
+// per user and model to load in a spreadsheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct GetBillingReportInputDTO {
+    // tenant_id narrows the report to one tenant, every tenant when omitted
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
+    // from and to are inclusive UTC days, the last 30 days when omitted
+    #[serde(default)]
+    pub from: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BillingLineOutputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl From<UserUsage> for BillingLineOutputDTO {
+    fn from(usage: UserUsage) -> Self {
+        Self {
+            tenant_id: usage.tenant_id,
+            user_id: usage.user_id,
+            model: usage.model,
+            requests: usage.requests,
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            cost: usage.cost,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BillingReportOutputDTO {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+    pub lines: Vec<BillingLineOutputDTO>,
+}
+
+impl BillingReportOutputDTO {
+    // filename names the CSV export after the range it covers
+    pub fn filename(&self) -> String {
+        format!("billing-{}-{}.csv", self.from, self.to)
+    }
+
+    // to_csv writes a header and a line per user and model, costs in USD
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("tenant_id,user_id,model,requests,prompt_tokens,completion_tokens,cost\n");
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.6}\n",
+                line.tenant_id,
+                line.user_id,
+                csv_field(&line.model),
+                line.requests,
+                line.prompt_tokens,
+                line.completion_tokens,
+                line.cost
+            ));
+        }
+
+        csv
+    }
+}
+
+// csv_field quotes a field holding a separator or a quote, doubling its quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
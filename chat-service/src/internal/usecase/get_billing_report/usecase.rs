@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_billing_report::dto::{
+    BillingLineOutputDTO, BillingReportOutputDTO, GetBillingReportInputDTO,
+};
+use crate::internal::usecase::get_usage::usecase::usage_range;
+
+pub struct GetBillingReportUseCase {
+    repository: Arc<dyn UsageRepository>,
+}
+
+impl GetBillingReportUseCase {
+    pub fn new(repository: Arc<dyn UsageRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute aggregates the cost per tenant, user and model in the range along with its
+    // totals; costs are the ones recorded with the usage, at the prices of the time
+    #[instrument(name = "get_billing_report", skip_all)]
+    pub async fn execute(
+        &self,
+        input: GetBillingReportInputDTO,
+    ) -> Result<BillingReportOutputDTO, UseCaseError> {
+        let (from, to) = usage_range(input.from, input.to)?;
+        let usage = self
+            .repository
+            .summarize_user_usage(input.tenant_id, from, to)
+            .await?;
+
+        let mut output = BillingReportOutputDTO {
+            from,
+            to,
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+            lines: Vec::with_capacity(usage.len()),
+        };
+        for usage in usage {
+            output.requests += usage.requests;
+            output.prompt_tokens += usage.prompt_tokens;
+            output.completion_tokens += usage.completion_tokens;
+            output.cost += usage.cost;
+            output.lines.push(BillingLineOutputDTO::from(usage));
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn record(tenant_id: Uuid, user_id: Uuid, model: &str, cost: f64) -> UsageRecord {
+        UsageRecord {
+            tenant_id,
+            user_id,
+            chat_id: Uuid::new_v4(),
+            model: model.to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            cost,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_bills_users_per_model() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let (ada, grace) = (Uuid::new_v4(), Uuid::new_v4());
+        for record in [
+            record(acme, ada, "gpt-4o", 0.25),
+            record(acme, ada, "gpt-4o", 0.25),
+            record(acme, ada, "gpt-4o-mini", 0.01),
+            record(globex, grace, "gpt-4o", 0.5),
+        ] {
+            repository.record_usage(&record).await.unwrap();
+        }
+        let usecase = GetBillingReportUseCase::new(repository);
+
+        let report = usecase
+            .execute(GetBillingReportInputDTO {
+                tenant_id: Some(acme),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.lines.len(), 2);
+        assert_eq!(report.lines[0].model, "gpt-4o");
+        assert_eq!(report.lines[0].requests, 2);
+        assert!((report.cost - 0.51).abs() < 1e-9);
+
+        let csv = report.to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "tenant_id,user_id,model,requests,prompt_tokens,completion_tokens,cost"
+        );
+        assert_eq!(
+            lines[1],
+            format!("{},{},gpt-4o,2,200,100,0.500000", acme, ada)
+        );
+
+        let everyone = usecase
+            .execute(GetBillingReportInputDTO::default())
+            .await
+            .unwrap();
+        assert_eq!(everyone.lines.len(), 3);
+        assert!((everyone.cost - 1.01).abs() < 1e-9);
+    }
+}
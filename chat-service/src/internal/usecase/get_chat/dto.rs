@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::Chat;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatOutputDTO {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub model: String,
+    pub temperature: f32,
+    // response_language is the language the chat is answered in, unset when it follows the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_language: Option<String>,
+    pub title: Option<String>,
+    // assistant_id is the assistant the chat was started with
+    pub assistant_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
+    pub token_usage: usize,
+    pub message_count: usize,
+}
+
+impl From<&Chat> for ChatOutputDTO {
+    fn from(chat: &Chat) -> Self {
+        Self {
+            id: chat.id,
+            user_id: chat.user_id,
+            status: chat.status.to_string(),
+            model: chat.config.model.name.clone(),
+            temperature: chat.config.temperature,
+            response_language: chat.config.response_language.clone(),
+            title: chat.title.clone(),
+            assistant_id: chat.assistant_id,
+            tags: chat.tags.clone(),
+            metadata: chat.metadata.clone(),
+            token_usage: chat.token_usage,
+            message_count: chat.count_messages(),
+        }
+    }
+}
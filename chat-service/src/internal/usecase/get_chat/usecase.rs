@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+
+pub struct GetChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+}
+
+impl GetChatUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute returns the chat summary, chats can only be read by their owner
+    #[instrument(name = "get_chat", skip_all, fields(chat_id = %chat_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        chat_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ChatOutputDTO, UseCaseError> {
+        let chat = self
+            .repository
+            .find_chat_by_id(tenant_id, chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(chat_id))?;
+
+        if chat.user_id != user_id {
+            return Err(UseCaseError::Forbidden(chat_id));
+        }
+
+        Ok(ChatOutputDTO::from(&chat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+
+    use crate::internal::domain::entity::chat::{
+        Chat, ChatConfig, ChatStatus, ChatSummary, TrimmingPolicy,
+    };
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::repository::chat::{
+        ChatCursor, MessageQuery, PurgedChat, RepositoryError, TranscriptMatch,
+    };
+
+    struct SingleChatRepository {
+        chat_id: Uuid,
+        model: Model,
+    }
+
+    #[async_trait]
+    impl ChatRepository for SingleChatRepository {
+        async fn create_chat(&self, _chat: &Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn find_chat_by_id(
+            &self,
+            _tenant_id: Uuid,
+            id: Uuid,
+        ) -> Result<Option<Chat>, RepositoryError> {
+            if id != self.chat_id {
+                return Ok(None);
+            }
+
+            let system = Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                "You are a helpful assistant.",
+                0,
+                self.model.clone(),
+                chrono::Utc::now(),
+            );
+            let config = ChatConfig {
+                model: self.model.clone(),
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                tools: vec![],
+                response_format: ResponseFormat::default(),
+                response_language: None,
+            };
+
+            Ok(Some(Chat::new(
+                id,
+                Uuid::nil(),
+                system,
+                vec![],
+                vec![],
+                ChatStatus::Active,
+                0,
+                config,
+            )))
+        }
+
+        async fn save_chat(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn update_chat_title(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+            _title: &str,
+        ) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn list_chats_by_user(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_chat_summaries(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _tag: Option<&str>,
+            _after: Option<ChatCursor>,
+            _limit: usize,
+        ) -> Result<Vec<ChatSummary>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn find_chat_summary(
+            &self,
+            _tenant_id: Uuid,
+            _id: Uuid,
+        ) -> Result<Option<ChatSummary>, RepositoryError> {
+            Ok(None)
+        }
+
+        async fn list_messages(
+            &self,
+            _tenant_id: Uuid,
+            _chat_id: Uuid,
+            _query: &MessageQuery,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn search_transcripts(
+            &self,
+            _tenant_id: Uuid,
+            _user_id: Uuid,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<TranscriptMatch>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn list_inactive_chats(
+            &self,
+            _inactive_before: chrono::DateTime<chrono::Utc>,
+            _limit: usize,
+        ) -> Result<Vec<Chat>, RepositoryError> {
+            Ok(vec![])
+        }
+
+        async fn archive_chat_history(&self, _chat: &mut Chat) -> Result<(), RepositoryError> {
+            Ok(())
+        }
+
+        async fn purge_deleted_chats(
+            &self,
+            _deleted_before: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<PurgedChat>, RepositoryError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let chat_id = Uuid::new_v4();
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = SingleChatRepository { chat_id, model };
+        let usecase = GetChatUseCase::new(Arc::new(repository));
+
+        let output = usecase
+            .execute(DEFAULT_TENANT_ID, chat_id, Uuid::nil())
+            .await
+            .unwrap();
+
+        assert_eq!(output.id, chat_id);
+        assert_eq!(output.status, "active");
+        assert_eq!(output.model, "gpt-3.5-turbo");
+        assert_eq!(output.message_count, 0);
+
+        let missing = Uuid::new_v4();
+        assert!(matches!(
+            usecase.execute(DEFAULT_TENANT_ID, missing, Uuid::nil()).await,
+            Err(UseCaseError::ChatNotFound(id)) if id == missing
+        ));
+        assert!(matches!(
+            usecase.execute(DEFAULT_TENANT_ID, chat_id, Uuid::new_v4()).await,
+            Err(UseCaseError::Forbidden(id)) if id == chat_id
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::submit_job::dto::JobOutputDTO;
+
+pub struct GetJobUseCase {
+    jobs: Arc<dyn JobRepository>,
+}
+
+impl GetJobUseCase {
+    pub fn new(jobs: Arc<dyn JobRepository>) -> Self {
+        Self { jobs }
+    }
+
+    // execute returns a job of the user with its answer once it is done; the jobs of other
+    // users are not found
+    #[instrument(name = "get_job", skip_all, fields(job_id = %job_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<JobOutputDTO, UseCaseError> {
+        let job = self
+            .jobs
+            .find_job_by_id(tenant_id, job_id)
+            .await?
+            .filter(|job| job.user_id == user_id)
+            .ok_or(UseCaseError::JobNotFound(job_id))?;
+
+        Ok(JobOutputDTO::from(&job))
+    }
+}
@@ -0,0 +1,26 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetQuotaInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaOutputDTO {
+    // scope is user or tenant and period daily or monthly
+    pub scope: String,
+    pub period: String,
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    pub resets_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotasOutputDTO {
+    pub user_id: Uuid,
+    // quotas is empty when no quota applies to the user
+    pub quotas: Vec<QuotaOutputDTO>,
+}
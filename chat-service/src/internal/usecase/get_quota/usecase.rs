@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::quota::QuotaEnforcer;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_quota::dto::{GetQuotaInputDTO, QuotaOutputDTO, QuotasOutputDTO};
+
+pub struct GetQuotaUseCase {
+    quota: Arc<QuotaEnforcer>,
+}
+
+impl GetQuotaUseCase {
+    pub fn new(quota: Arc<QuotaEnforcer>) -> Self {
+        Self { quota }
+    }
+
+    // execute returns how many tokens the user and its tenant have left in every enabled quota
+    #[instrument(name = "get_quota", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(&self, input: GetQuotaInputDTO) -> Result<QuotasOutputDTO, UseCaseError> {
+        let quotas = self.quota.quotas(input.tenant_id, input.user_id).await?;
+
+        Ok(QuotasOutputDTO {
+            user_id: input.user_id,
+            quotas: quotas
+                .into_iter()
+                .map(|quota| QuotaOutputDTO {
+                    scope: quota.scope.to_string(),
+                    period: quota.period.to_string(),
+                    limit: quota.limit,
+                    used: quota.used,
+                    remaining: quota.remaining(),
+                    resets_at: quota.resets_at,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::domain::quota::QuotaConfig;
+    use crate::internal::domain::repository::usage::UsageRepository;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        for user_id in [user_id, Uuid::new_v4()] {
+            repository
+                .record_usage(&UsageRecord {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id,
+                    chat_id: Uuid::new_v4(),
+                    model: "gpt-4o".to_string(),
+                    prompt_tokens: 100,
+                    completion_tokens: 50,
+                    cost: 0.5,
+                    created_at: chrono::Utc::now(),
+                })
+                .await
+                .unwrap();
+        }
+        let usecase = GetQuotaUseCase::new(Arc::new(QuotaEnforcer::new(
+            repository,
+            QuotaConfig {
+                user_daily_tokens: 1000,
+                tenant_monthly_tokens: 200,
+                ..QuotaConfig::default()
+            },
+        )));
+
+        let output = usecase
+            .execute(GetQuotaInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.quotas.len(), 2);
+        assert_eq!(output.quotas[0].scope, "user");
+        assert_eq!(output.quotas[0].period, "daily");
+        assert_eq!(output.quotas[0].used, 150);
+        assert_eq!(output.quotas[0].remaining, 850);
+        assert_eq!(output.quotas[1].scope, "tenant");
+        assert_eq!(output.quotas[1].period, "monthly");
+        assert_eq!(output.quotas[1].used, 300);
+        assert_eq!(output.quotas[1].remaining, 0);
+    }
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetUsageInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // from and to are inclusive UTC days, the last 30 days when omitted
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyUsageOutputDTO {
+    pub date: chrono::NaiveDate,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageOutputDTO {
+    pub user_id: Uuid,
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+    pub days: Vec<DailyUsageOutputDTO>,
+}
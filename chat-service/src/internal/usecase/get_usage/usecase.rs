@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_usage::dto::{
+    DailyUsageOutputDTO, GetUsageInputDTO, UsageOutputDTO,
+};
+
+const DEFAULT_RANGE_DAYS: i64 = 30;
+const MAX_RANGE_DAYS: i64 = 366;
+
+pub struct GetUsageUseCase {
+    repository: Arc<dyn UsageRepository>,
+}
+
+impl GetUsageUseCase {
+    pub fn new(repository: Arc<dyn UsageRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute returns the daily usage of the user in the range along with its totals
+    #[instrument(name = "get_usage", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(&self, input: GetUsageInputDTO) -> Result<UsageOutputDTO, UseCaseError> {
+        let (from, to) = usage_range(input.from, input.to)?;
+        let daily = self
+            .repository
+            .list_daily_usage(input.tenant_id, input.user_id, from, to)
+            .await?;
+
+        let mut output = UsageOutputDTO {
+            user_id: input.user_id,
+            from,
+            to,
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+            days: Vec::with_capacity(daily.len()),
+        };
+        for usage in daily {
+            output.requests += usage.requests;
+            output.prompt_tokens += usage.prompt_tokens;
+            output.completion_tokens += usage.completion_tokens;
+            output.cost += usage.cost;
+            output.days.push(DailyUsageOutputDTO {
+                date: usage.date,
+                model: usage.model,
+                requests: usage.requests,
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                cost: usage.cost,
+            });
+        }
+
+        Ok(output)
+    }
+}
+
+// usage_range fills in the last 30 days for omitted bounds and rejects inverted or overly
+// long ranges
+pub(crate) fn usage_range(
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+) -> Result<(chrono::NaiveDate, chrono::NaiveDate), UseCaseError> {
+    let to = to.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let from = from.unwrap_or(to - chrono::Duration::days(DEFAULT_RANGE_DAYS - 1));
+
+    if from > to {
+        return Err(UseCaseError::InvalidInput(format!(
+            "from {} is after to {}",
+            from, to
+        )));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(UseCaseError::InvalidInput(format!(
+            "range cannot exceed {} days",
+            MAX_RANGE_DAYS
+        )));
+    }
+
+    Ok((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn record(user_id: Uuid, model: &str, days_ago: i64) -> UsageRecord {
+        UsageRecord {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: Uuid::new_v4(),
+            model: model.to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            cost: 0.5,
+            created_at: chrono::Utc::now() - chrono::Duration::days(days_ago),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        for record in [
+            record(user_id, "gpt-4o", 0),
+            record(user_id, "gpt-4o", 0),
+            record(user_id, "gpt-3.5-turbo", 1),
+            record(user_id, "gpt-4o", 45),
+            record(Uuid::new_v4(), "gpt-4o", 0),
+        ] {
+            repository.record_usage(&record).await.unwrap();
+        }
+        let usecase = GetUsageUseCase::new(repository);
+
+        let output = usecase
+            .execute(GetUsageInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                from: None,
+                to: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.days.len(), 2);
+        assert_eq!(output.days[0].model, "gpt-3.5-turbo");
+        assert_eq!(output.days[1].requests, 2);
+        assert_eq!(output.requests, 3);
+        assert_eq!(output.prompt_tokens, 300);
+        assert_eq!(output.completion_tokens, 150);
+        assert!((output.cost - 1.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_execute_invalid_range() {
+        let usecase = GetUsageUseCase::new(Arc::new(InMemoryUsageRepository::new()));
+        let today = chrono::Utc::now().date_naive();
+
+        let reversed = usecase
+            .execute(GetUsageInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id: Uuid::new_v4(),
+                from: Some(today),
+                to: Some(today - chrono::Duration::days(1)),
+            })
+            .await;
+        assert!(matches!(reversed, Err(UseCaseError::InvalidInput(_))));
+
+        let too_long = usecase
+            .execute(GetUsageInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id: Uuid::new_v4(),
+                from: Some(today - chrono::Duration::days(MAX_RANGE_DAYS)),
+                to: Some(today),
+            })
+            .await;
+        assert!(matches!(too_long, Err(UseCaseError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::usage::TenantUsage;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct GetUsageSummaryInputDTO {
+    // tenant_id narrows the summary to one tenant, every tenant when omitted
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
+    // from and to are inclusive UTC days, the last 30 days when omitted
+    #[serde(default)]
+    pub from: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TenantUsageOutputDTO {
+    pub tenant_id: Uuid,
+    pub model: String,
+    pub users: u64,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+}
+
+impl From<TenantUsage> for TenantUsageOutputDTO {
+    fn from(usage: TenantUsage) -> Self {
+        Self {
+            tenant_id: usage.tenant_id,
+            model: usage.model,
+            users: usage.users,
+            requests: usage.requests,
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            cost: usage.cost,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageSummaryOutputDTO {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+    pub tenants: Vec<TenantUsageOutputDTO>,
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_usage::usecase::usage_range;
+use crate::internal::usecase::get_usage_summary::dto::{
+    GetUsageSummaryInputDTO, TenantUsageOutputDTO, UsageSummaryOutputDTO,
+};
+
+pub struct GetUsageSummaryUseCase {
+    repository: Arc<dyn UsageRepository>,
+}
+
+impl GetUsageSummaryUseCase {
+    pub fn new(repository: Arc<dyn UsageRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute aggregates the usage per tenant and model in the range along with its totals
+    #[instrument(name = "get_usage_summary", skip_all)]
+    pub async fn execute(
+        &self,
+        input: GetUsageSummaryInputDTO,
+    ) -> Result<UsageSummaryOutputDTO, UseCaseError> {
+        let (from, to) = usage_range(input.from, input.to)?;
+        let usage = self
+            .repository
+            .summarize_usage(input.tenant_id, from, to)
+            .await?;
+
+        let mut output = UsageSummaryOutputDTO {
+            from,
+            to,
+            requests: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+            tenants: Vec::with_capacity(usage.len()),
+        };
+        for usage in usage {
+            output.requests += usage.requests;
+            output.prompt_tokens += usage.prompt_tokens;
+            output.completion_tokens += usage.completion_tokens;
+            output.cost += usage.cost;
+            output.tenants.push(TenantUsageOutputDTO::from(usage));
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn record(tenant_id: Uuid, model: &str, days_ago: i64) -> UsageRecord {
+        UsageRecord {
+            tenant_id,
+            user_id: Uuid::new_v4(),
+            chat_id: Uuid::new_v4(),
+            model: model.to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            cost: 0.5,
+            created_at: chrono::Utc::now() - chrono::Duration::days(days_ago),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryUsageRepository::new());
+        let acme = Uuid::new_v4();
+        let globex = Uuid::new_v4();
+        for record in [
+            record(acme, "gpt-4o", 0),
+            record(acme, "gpt-4o", 1),
+            record(acme, "gpt-4o-mini", 0),
+            record(acme, "gpt-4o", 45),
+            record(globex, "gpt-4o", 0),
+        ] {
+            repository.record_usage(&record).await.unwrap();
+        }
+        let usecase = GetUsageSummaryUseCase::new(repository);
+
+        let output = usecase
+            .execute(GetUsageSummaryInputDTO::default())
+            .await
+            .unwrap();
+        assert_eq!(output.requests, 4);
+        assert_eq!(output.tenants.len(), 3);
+
+        let output = usecase
+            .execute(GetUsageSummaryInputDTO {
+                tenant_id: Some(acme),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.requests, 3);
+        assert_eq!(output.prompt_tokens, 300);
+        let gpt4o = &output.tenants[0];
+        assert_eq!(
+            (gpt4o.model.as_str(), gpt4o.users, gpt4o.requests),
+            ("gpt-4o", 2, 2)
+        );
+
+        let today = chrono::Utc::now().date_naive();
+        assert!(matches!(
+            usecase
+                .execute(GetUsageSummaryInputDTO {
+                    tenant_id: None,
+                    from: Some(today),
+                    to: Some(today - chrono::Duration::days(1)),
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+}
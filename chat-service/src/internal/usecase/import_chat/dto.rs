@@ -0,0 +1,124 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::TrimmingPolicy;
+use crate::internal::domain::entity::message::Role;
+use crate::internal::domain::entity::response_format::ResponseFormat;
+use crate::internal::domain::entity::tool::{ToolCall, ToolDefinition};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportChatInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    // model names a model of the registry, it wins over the one named in the source
+    pub model: Option<String>,
+    pub source: ImportSourceDTO,
+}
+
+// ImportSourceDTO is what a chat is imported from: a transcript exported as JSON, an OpenAI
+// chat completion request or the bare messages array of one
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ImportSourceDTO {
+    Transcript {
+        chat: Box<ImportedChatDTO>,
+        system_message: ImportedMessageDTO,
+        #[serde(default)]
+        erased_messages: Vec<ImportedMessageDTO>,
+        messages: Vec<ImportedMessageDTO>,
+    },
+    Request {
+        #[serde(default)]
+        model: Option<String>,
+        messages: Vec<ImportedMessageDTO>,
+    },
+    Messages(Vec<ImportedMessageDTO>),
+}
+
+// ImportedChatDTO is what is kept of the exported chat, its id, owner and usage are not; the
+// settings left out keep the configured ones
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ImportedChatDTO {
+    pub title: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub n: Option<u32>,
+    pub stop: Option<Vec<String>>,
+    pub max_tokens: Option<usize>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub trimming_policy: Option<TrimmingPolicy>,
+    pub tools: Option<Vec<ToolDefinition>>,
+    pub response_format: Option<ResponseFormat>,
+}
+
+// ImportedMessageDTO reads a message both as exported and as the OpenAI API takes it; the
+// tokens are counted again with the model the chat is imported under
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportedMessageDTO {
+    pub role: Role,
+    // content is null on OpenAI assistant messages that only call tools
+    #[serde(default)]
+    pub content: Option<ImportedContentDTO>,
+    #[serde(default)]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub tool_calls: Vec<ImportedToolCallDTO>,
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+}
+
+// ImportedContentDTO is plain text, or the text and image parts of an OpenAI message
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ImportedContentDTO {
+    Text(String),
+    Parts(Vec<ImportedContentPartDTO>),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImportedContentPartDTO {
+    Text { text: String },
+    ImageUrl { image_url: ImportedImageUrlDTO },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportedImageUrlDTO {
+    pub url: String,
+}
+
+// ImportedToolCallDTO is a tool call as exported, or as OpenAI nests it under function
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ImportedToolCallDTO {
+    Exported(ToolCall),
+    OpenAI {
+        id: String,
+        function: ImportedFunctionCallDTO,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportedFunctionCallDTO {
+    pub name: String,
+    pub arguments: String,
+}
+
+impl From<ImportedToolCallDTO> for ToolCall {
+    fn from(tool_call: ImportedToolCallDTO) -> Self {
+        match tool_call {
+            ImportedToolCallDTO::Exported(tool_call) => tool_call,
+            ImportedToolCallDTO::OpenAI { id, function } => ToolCall {
+                id,
+                name: function.name,
+                arguments: function.arguments,
+            },
+        }
+    }
+}
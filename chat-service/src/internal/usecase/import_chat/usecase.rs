@@ -0,0 +1,520 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::chat::ChatConfig;
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+use crate::internal::usecase::chat_completion::usecase::{new_chat, ChatCompletionUseCase};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+use crate::internal::usecase::import_chat::dto::{
+    ImportChatInputDTO, ImportSourceDTO, ImportedChatDTO, ImportedContentDTO,
+    ImportedContentPartDTO, ImportedMessageDTO,
+};
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+// MAX_IMPORTED_MESSAGES bounds the history a single import recreates
+pub const MAX_IMPORTED_MESSAGES: usize = 1000;
+
+pub struct ImportChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+    users: Arc<dyn UserRepository>,
+    model: Model,
+    config: ChatCompletionConfigInputDTO,
+    tenants: Option<Arc<TenantRegistry>>,
+    chat_completion: Option<Arc<ChatCompletionUseCase>>,
+}
+
+impl ImportChatUseCase {
+    pub fn new(
+        repository: Arc<dyn ChatRepository>,
+        users: Arc<dyn UserRepository>,
+        model: Model,
+        config: ChatCompletionConfigInputDTO,
+    ) -> Self {
+        Self {
+            repository,
+            users,
+            model,
+            config,
+            tenants: None,
+            chat_completion: None,
+        }
+    }
+
+    // with_tenants imports under the default model of the tenant and refuses the models it
+    // does not allow
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    // with_chat_completion screens the imported user messages like the messages sent to a chat,
+    // with its redaction, moderation and prompt guard
+    pub fn with_chat_completion(mut self, chat_completion: Arc<ChatCompletionUseCase>) -> Self {
+        self.chat_completion = Some(chat_completion);
+        self
+    }
+
+    // execute recreates the chat as a new chat of the user with the settings of the transcript:
+    // the messages are added one by one under the chosen model, so their tokens are counted
+    // again and the trimming policy applies to a history larger than the budget, the erased
+    // ones are kept out of the context again; the user messages are screened before they are
+    // kept
+    #[instrument(name = "import_chat", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(&self, input: ImportChatInputDTO) -> Result<ChatOutputDTO, UseCaseError> {
+        if self
+            .users
+            .find_user_by_id(input.tenant_id, input.user_id)
+            .await?
+            .is_none()
+        {
+            return Err(UseCaseError::UserNotFound(input.user_id));
+        }
+
+        let (settings, system_message, erased_messages, mut messages) = match input.source {
+            ImportSourceDTO::Transcript {
+                chat,
+                system_message,
+                erased_messages,
+                messages,
+            } => (*chat, Some(system_message), erased_messages, messages),
+            ImportSourceDTO::Request { model, messages } => (
+                ImportedChatDTO {
+                    model,
+                    ..ImportedChatDTO::default()
+                },
+                None,
+                vec![],
+                messages,
+            ),
+            ImportSourceDTO::Messages(messages) => {
+                (ImportedChatDTO::default(), None, vec![], messages)
+            }
+        };
+        // an OpenAI conversation starts with its system message
+        let system_message = match system_message {
+            Some(system_message) => Some(system_message),
+            None if messages.first().map(|message| message.role) == Some(Role::System) => {
+                Some(messages.remove(0))
+            }
+            None => None,
+        };
+        if messages.is_empty() || messages.len() > MAX_IMPORTED_MESSAGES {
+            return Err(UseCaseError::InvalidInput(format!(
+                "an import takes between 1 and {} messages",
+                MAX_IMPORTED_MESSAGES
+            )));
+        }
+
+        let model = match input.model.or(settings.model.clone()) {
+            Some(name) => resolve_model(&name)?,
+            None => self.model_for(input.tenant_id),
+        };
+        if let Some(tenants) = &self.tenants {
+            tenants.check_model(input.tenant_id, &model)?;
+        }
+
+        let system_content = match system_message {
+            Some(system_message) => system_text(&system_message)?,
+            None => self.config.initial_system_message.clone(),
+        };
+        let mut chat = new_chat(input.user_id, &model, &self.config, &system_content)?
+            .with_tenant(input.tenant_id)
+            .with_title(settings.title.clone());
+        chat.config = imported_config(&chat.config, settings)?;
+        for message in erased_messages {
+            let message = self
+                .imported_message(input.tenant_id, input.user_id, message, &model)
+                .await?;
+            chat.erased_messages.push(message);
+        }
+        for message in messages {
+            let message = self
+                .imported_message(input.tenant_id, input.user_id, message, &model)
+                .await?;
+            chat.add_message(message)?;
+        }
+        chat.validate()?;
+
+        self.repository.create_chat(&chat).await?;
+        if let Some(chat_completion) = &self.chat_completion {
+            chat_completion.record_redactions(&mut chat).await;
+        }
+
+        Ok(ChatOutputDTO::from(&chat))
+    }
+
+    // imported_message builds the message under the model, a user message is screened
+    async fn imported_message(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        imported: ImportedMessageDTO,
+        model: &Model,
+    ) -> Result<Message, UseCaseError> {
+        let mut message = to_message(imported, model)?;
+        message.validate()?;
+        if let (Role::User, Some(chat_completion)) = (message.role, &self.chat_completion) {
+            message = chat_completion
+                .screen(tenant_id, user_id, None, message)
+                .await?;
+        }
+
+        Ok(message)
+    }
+
+    fn model_for(&self, tenant_id: Uuid) -> Model {
+        self.tenants
+            .as_ref()
+            .and_then(|tenants| tenants.model(tenant_id))
+            .unwrap_or_else(|| self.model.clone())
+    }
+}
+
+// imported_config is the configured chat config with the settings of the transcript, the token
+// budget bounded by the context of the model imported under
+fn imported_config(
+    config: &ChatConfig,
+    settings: ImportedChatDTO,
+) -> Result<ChatConfig, UseCaseError> {
+    let mut config = config.clone();
+    config.temperature = settings.temperature.unwrap_or(config.temperature);
+    config.top_p = settings.top_p.unwrap_or(config.top_p);
+    config.n = settings.n.unwrap_or(config.n);
+    config.stop = settings.stop.unwrap_or(config.stop);
+    if let Some(max_tokens) = settings.max_tokens {
+        config.max_tokens = max_tokens.min(config.model.max_tokens as usize);
+    }
+    config.presence_penalty = settings.presence_penalty.unwrap_or(config.presence_penalty);
+    config.frequency_penalty = settings
+        .frequency_penalty
+        .unwrap_or(config.frequency_penalty);
+    config.trimming_policy = settings.trimming_policy.unwrap_or(config.trimming_policy);
+    config.tools = settings.tools.unwrap_or(config.tools);
+    config.response_format = settings.response_format.unwrap_or(config.response_format);
+    config.validate().map_err(ChatError::from)?;
+
+    Ok(config)
+}
+
+// to_message builds the message under the model, the text parts of OpenAI content are joined
+// and its image parts become attachments
+pub(crate) fn to_message(
+    imported: ImportedMessageDTO,
+    model: &Model,
+) -> Result<Message, UseCaseError> {
+    let mut content = String::new();
+    let mut attachments = imported.attachments;
+    match imported.content {
+        Some(ImportedContentDTO::Text(text)) => content = text,
+        Some(ImportedContentDTO::Parts(parts)) => {
+            let mut texts = vec![];
+            for part in parts {
+                match part {
+                    ImportedContentPartDTO::Text { text } => texts.push(text),
+                    ImportedContentPartDTO::ImageUrl { image_url } => {
+                        attachments.push(attachment(&image_url.url))
+                    }
+                }
+            }
+            content = texts.join("\n");
+        }
+        None => {}
+    }
+
+    let mut message = Message::new(
+        Uuid::new_v4(),
+        imported.role,
+        &content,
+        0,
+        model.clone(),
+        imported.created_at.unwrap_or_else(chrono::Utc::now),
+    )
+    .with_attachments(attachments)
+    .with_tool_calls(imported.tool_calls.into_iter().map(Into::into).collect());
+    if let Some(tool_call_id) = &imported.tool_call_id {
+        message = message.with_tool_call_id(tool_call_id);
+    }
+
+    Ok(message)
+}
+
+// attachment reads an OpenAI image URL, a data URL carries the image itself
+fn attachment(url: &str) -> Attachment {
+    let inline = url
+        .strip_prefix("data:")
+        .and_then(|data| data.split_once(";base64,"));
+    match inline {
+        Some((mime_type, data)) => Attachment::base64(mime_type, data),
+        None => Attachment::url(url),
+    }
+}
+
+// system_text is the content of a system message, which takes no images
+pub(crate) fn system_text(message: &ImportedMessageDTO) -> Result<String, UseCaseError> {
+    match &message.content {
+        Some(ImportedContentDTO::Text(text)) => Ok(text.clone()),
+        Some(ImportedContentDTO::Parts(parts)) => parts
+            .iter()
+            .map(|part| match part {
+                ImportedContentPartDTO::Text { text } => Ok(text.as_str()),
+                ImportedContentPartDTO::ImageUrl { .. } => Err(UseCaseError::InvalidInput(
+                    "the system message cannot hold images".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|texts| texts.join("\n")),
+        None => Err(UseCaseError::InvalidInput(
+            "the system message is empty".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::attachment::AttachmentSource;
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::tool::ToolDefinition;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::redactor::Redactor;
+    use crate::internal::infra::redaction::cipher::AesGcmCipher;
+    use crate::internal::infra::redaction::detector::RegexDetector;
+    use crate::internal::infra::repository::memory::redaction::InMemoryRedactionRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::{test_model, ChatBuilder, MessageBuilder};
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::export_chat::dto::TranscriptDTO;
+
+    fn config() -> ChatCompletionConfigInputDTO {
+        ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        }
+    }
+
+    async fn setup() -> (ImportChatUseCase, Arc<InMemoryChatRepository>, Uuid) {
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let usecase =
+            ImportChatUseCase::new(repository.clone(), Arc::new(users), test_model(), config());
+
+        (usecase, repository, user_id)
+    }
+
+    fn input(user_id: Uuid, model: Option<&str>, source: serde_json::Value) -> ImportChatInputDTO {
+        ImportChatInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            model: model.map(str::to_string),
+            source: serde_json::from_value(source).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_imports_transcript() {
+        let (usecase, repository, user_id) = setup().await;
+        let config = ChatConfig::builder(test_model())
+            .temperature(0.4)
+            .n(2)
+            .stop(vec!["END".to_string()])
+            .max_tokens(2048)
+            .presence_penalty(0.5)
+            .frequency_penalty(0.25)
+            .trimming_policy(TrimmingPolicy::RejectNew)
+            .tools(vec![ToolDefinition::new(
+                "get_weather",
+                "Gets the weather",
+                serde_json::json!({"type": "object"}),
+            )])
+            .response_format(ResponseFormat::JsonObject)
+            .build()
+            .unwrap();
+        let mut exported = ChatBuilder::new()
+            .title("Weather")
+            .config(config)
+            .system_message("You answer in one sentence.")
+            .user_message("Will it rain?")
+            .assistant_message("Likely, take an umbrella.")
+            .build();
+        exported
+            .erased_messages
+            .push(MessageBuilder::user("Good morning").build());
+        let transcript = TranscriptDTO::new(&exported, chrono::Utc::now());
+
+        let output = usecase
+            .execute(input(
+                user_id,
+                None,
+                serde_json::to_value(transcript).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_ne!(output.id, exported.id);
+        assert_eq!(output.user_id, user_id);
+        assert_eq!(output.title.as_deref(), Some("Weather"));
+        assert_eq!(output.model, "gpt-3.5-turbo");
+        assert_eq!(output.message_count, 2);
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You answer in one sentence."
+        );
+        assert_eq!(chat.messages[1].content, "Likely, take an umbrella.");
+        assert_eq!(chat.messages[1].created_at, exported.messages[1].created_at);
+        assert_eq!(chat.token_usage, output.token_usage);
+        assert!(chat.token_usage > 0);
+        assert_eq!(chat.erased_messages[0].content, "Good morning");
+        // the model is resolved from the registry, the other settings are the exported ones
+        assert_eq!(
+            ChatConfig {
+                model: exported.config.model.clone(),
+                ..chat.config
+            },
+            exported.config
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_imports_openai_messages() {
+        let (usecase, repository, user_id) = setup().await;
+        let source = serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "You read images."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "What is this?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+                ]},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "lookup", "arguments": "{}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "A lighthouse"},
+                {"role": "assistant", "content": "A lighthouse."}
+            ]
+        });
+
+        let output = usecase.execute(input(user_id, None, source)).await.unwrap();
+        assert_eq!(output.model, "gpt-4o");
+        assert_eq!(output.message_count, 4);
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.initial_system_message().content, "You read images.");
+        assert_eq!(chat.messages[0].content, "What is this?");
+        assert_eq!(
+            chat.messages[0].attachments[0].source,
+            AttachmentSource::Base64("iVBORw0KGgo=".to_string())
+        );
+        assert_eq!(chat.messages[1].tool_calls[0].name, "lookup");
+        assert_eq!(chat.messages[2].tool_call_id.as_deref(), Some("call_1"));
+
+        // the model asked for wins, the bare array takes the configured system message
+        let source = serde_json::json!([{"role": "user", "content": "Hello!"}]);
+        let output = usecase
+            .execute(input(user_id, Some("gpt-4"), source))
+            .await
+            .unwrap();
+        assert_eq!(output.model, "gpt-4");
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_imports() {
+        let (usecase, _, user_id) = setup().await;
+        let hello = serde_json::json!([{"role": "user", "content": "Hello!"}]);
+
+        assert!(matches!(
+            usecase
+                .execute(input(Uuid::new_v4(), None, hello.clone()))
+                .await,
+            Err(UseCaseError::UserNotFound(_))
+        ));
+        assert!(matches!(
+            usecase.execute(input(user_id, Some("gpt-0"), hello)).await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(input(
+                    user_id,
+                    None,
+                    serde_json::json!([{"role": "system", "content": "Be brief."}])
+                ))
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_screens_user_messages() {
+        let (usecase, repository, user_id) = setup().await;
+        let users = Arc::new(InMemoryUserRepository::new());
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let redactor = Redactor::new(
+            vec![Arc::new(RegexDetector::email())],
+            Arc::new(AesGcmCipher::new(&[7u8; 32]).unwrap()),
+            Arc::new(InMemoryRedactionRepository::new()),
+        );
+        let usecase = usecase.with_chat_completion(Arc::new(
+            ChatCompletionUseCase::new(
+                Arc::new(FakeCompletionGateway::new()),
+                repository.clone(),
+                users,
+                test_model(),
+                config(),
+            )
+            .with_redactor(Arc::new(redactor)),
+        ));
+        let source = serde_json::json!([
+            {"role": "user", "content": "Write to ada@example.com"},
+            {"role": "assistant", "content": "I wrote to ada@example.com."}
+        ]);
+
+        let output = usecase.execute(input(user_id, None, source)).await.unwrap();
+
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.messages[0].content, "Write to [EMAIL]");
+        assert_eq!(chat.messages[1].content, "I wrote to ada@example.com.");
+    }
+}
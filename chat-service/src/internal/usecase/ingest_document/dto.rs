@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::document::{Document, DocumentFormat};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestDocumentInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    // content_type is the MIME type of the upload, the format is read from the name without it
+    pub content_type: Option<String>,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentOutputDTO {
+    pub id: Uuid,
+    pub name: String,
+    pub format: DocumentFormat,
+    pub size_bytes: usize,
+    pub chunk_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Document> for DocumentOutputDTO {
+    fn from(document: &Document) -> Self {
+        Self {
+            id: document.id,
+            name: document.name.clone(),
+            format: document.format,
+            size_bytes: document.size_bytes,
+            chunk_count: document.chunk_count,
+            created_at: document.created_at,
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::chunker::{chunk_text, ChunkingConfig};
+use crate::internal::domain::entity::document::{Document, DocumentFormat};
+use crate::internal::domain::entity::embedding::{VectorKind, VectorRecord};
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::repository::document::DocumentRepository;
+use crate::internal::domain::repository::vector_store::VectorStore;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::ingest_document::dto::{DocumentOutputDTO, IngestDocumentInputDTO};
+
+// DEFAULT_MAX_SIZE_BYTES bounds the size of an upload
+pub const DEFAULT_MAX_SIZE_BYTES: usize = 10 * 1024 * 1024;
+
+pub struct IngestDocumentUseCase {
+    documents: Arc<dyn DocumentRepository>,
+    embeddings: Arc<dyn EmbeddingsGateway>,
+    store: Arc<dyn VectorStore>,
+    chunking: ChunkingConfig,
+    max_size_bytes: usize,
+}
+
+impl IngestDocumentUseCase {
+    pub fn new(
+        documents: Arc<dyn DocumentRepository>,
+        embeddings: Arc<dyn EmbeddingsGateway>,
+        store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            documents,
+            embeddings,
+            store,
+            chunking: ChunkingConfig::default(),
+            max_size_bytes: DEFAULT_MAX_SIZE_BYTES,
+        }
+    }
+
+    pub fn with_chunking(mut self, chunking: ChunkingConfig) -> Self {
+        self.chunking = chunking;
+        self
+    }
+
+    pub fn with_max_size_bytes(mut self, max_size_bytes: usize) -> Self {
+        self.max_size_bytes = max_size_bytes;
+        self
+    }
+
+    pub fn max_size_bytes(&self) -> usize {
+        self.max_size_bytes
+    }
+
+    // execute extracts the text of the document, splits it into chunks and stores them embedded
+    // for the user, so the RAG chats of the user can retrieve them
+    #[instrument(name = "ingest_document", skip_all, fields(user_id = %input.user_id, document_id))]
+    pub async fn execute(
+        &self,
+        input: IngestDocumentInputDTO,
+    ) -> Result<DocumentOutputDTO, UseCaseError> {
+        if input.content.len() > self.max_size_bytes {
+            return Err(UseCaseError::InvalidInput(format!(
+                "document is larger than {} bytes",
+                self.max_size_bytes
+            )));
+        }
+
+        let format = DocumentFormat::detect(&input.name, input.content_type.as_deref())?;
+        let mut document = Document::new(
+            Uuid::new_v4(),
+            input.tenant_id,
+            input.user_id,
+            &input.name,
+            format,
+            input.content.len(),
+            chrono::Utc::now(),
+        );
+        document.validate()?;
+        tracing::Span::current().record("document_id", tracing::field::display(document.id));
+
+        let text = format.extract_text(&input.content)?;
+        let chunks = chunk_text(&text, &self.chunking);
+        if chunks.is_empty() {
+            return Err(ChatError::InvalidDocument("document has no text".to_string()).into());
+        }
+
+        let contents: Vec<String> = chunks.iter().map(|chunk| chunk.content.clone()).collect();
+        let vectors = self.embeddings.embed(&contents).await?;
+        let records: Vec<VectorRecord> = chunks
+            .into_iter()
+            .zip(vectors)
+            .map(|(chunk, embedding)| VectorRecord {
+                id: Uuid::new_v4(),
+                tenant_id: document.tenant_id,
+                user_id: document.user_id,
+                kind: VectorKind::DocumentChunk,
+                source_id: document.id,
+                content: chunk.content,
+                model: self.embeddings.model().to_string(),
+                embedding,
+                metadata: serde_json::json!({
+                    "document": document.name,
+                    "position": chunk.position,
+                    "start": chunk.start,
+                }),
+                created_at: document.created_at,
+            })
+            .collect();
+        document.chunk_count = records.len();
+
+        // the chunks go first so a listed document is always searchable, they are removed
+        // again when the document cannot be saved
+        self.store.upsert(&records).await?;
+        if let Err(err) = self.documents.create_document(&document).await {
+            if let Err(cleanup_err) = self
+                .store
+                .delete_by_source(document.tenant_id, document.id)
+                .await
+            {
+                tracing::warn!(error = %cleanup_err, "failed to remove the chunks of a document");
+            }
+            return Err(err.into());
+        }
+
+        Ok(DocumentOutputDTO::from(&document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::gateway::chat_completion::GatewayError;
+    use crate::internal::domain::repository::vector_store::VectorQuery;
+    use crate::internal::infra::repository::memory::document::InMemoryDocumentRepository;
+    use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+
+    // LengthEmbeddings embeds a text as its length, every chunk matches a query
+    struct LengthEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingsGateway for LengthEmbeddings {
+        fn model(&self) -> &str {
+            "length"
+        }
+
+        async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+            Ok(inputs
+                .iter()
+                .map(|input| vec![input.len() as f32, 1.0])
+                .collect())
+        }
+    }
+
+    fn setup() -> (
+        IngestDocumentUseCase,
+        Arc<InMemoryDocumentRepository>,
+        Arc<InMemoryVectorStore>,
+    ) {
+        let documents = Arc::new(InMemoryDocumentRepository::new());
+        let store = Arc::new(InMemoryVectorStore::new());
+        let usecase = IngestDocumentUseCase::new(
+            documents.clone(),
+            Arc::new(LengthEmbeddings),
+            store.clone(),
+        )
+        .with_chunking(ChunkingConfig {
+            size: 30,
+            overlap: 5,
+        })
+        .with_max_size_bytes(1024);
+
+        (usecase, documents, store)
+    }
+
+    fn input(user_id: Uuid, name: &str, content: &str) -> IngestDocumentInputDTO {
+        IngestDocumentInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            name: name.to_string(),
+            content_type: None,
+            content: content.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_stores_chunks() {
+        let (usecase, documents, store) = setup();
+        let user_id = Uuid::new_v4();
+
+        let output = usecase
+            .execute(input(
+                user_id,
+                "guide.md",
+                "# Guide\n\nInstall the service first.\n\nThen create a user and an API key.",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(output.format, DocumentFormat::Markdown);
+        assert!(output.chunk_count > 1);
+        let saved = documents
+            .find_document_by_id(DEFAULT_TENANT_ID, output.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.chunk_count, output.chunk_count);
+
+        let chunks = store
+            .search(&VectorQuery {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                model: "length".to_string(),
+                embedding: vec![1.0, 1.0],
+                kinds: vec![VectorKind::DocumentChunk],
+                limit: 10,
+                min_score: 0.0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(chunks.len(), output.chunk_count);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.source_id == output.id && chunk.metadata["document"] == "guide.md"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_documents() {
+        let (usecase, documents, _) = setup();
+        let user_id = Uuid::new_v4();
+
+        assert!(matches!(
+            usecase.execute(input(user_id, "photo.png", "...")).await,
+            Err(UseCaseError::Domain(ChatError::InvalidDocument(_)))
+        ));
+        assert!(matches!(
+            usecase.execute(input(user_id, "empty.txt", " \n\n ")).await,
+            Err(UseCaseError::Domain(ChatError::InvalidDocument(_)))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(input(user_id, "large.txt", &"a".repeat(2048)))
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(documents
+            .list_documents_by_user(DEFAULT_TENANT_ID, user_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+use crate::internal::usecase::create_assistant::dto::AssistantOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssistantListOutputDTO {
+    pub assistants: Vec<AssistantOutputDTO>,
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::usecase::create_assistant::dto::AssistantOutputDTO;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_assistants::dto::AssistantListOutputDTO;
+
+pub struct ListAssistantsUseCase {
+    assistants: Arc<dyn AssistantRepository>,
+}
+
+impl ListAssistantsUseCase {
+    pub fn new(assistants: Arc<dyn AssistantRepository>) -> Self {
+        Self { assistants }
+    }
+
+    // execute returns the assistants of the tenant ordered by name
+    #[instrument(name = "list_assistants", skip_all, fields(tenant_id = %tenant_id))]
+    pub async fn execute(&self, tenant_id: Uuid) -> Result<AssistantListOutputDTO, UseCaseError> {
+        let assistants = self.assistants.list_assistants(tenant_id).await?;
+
+        Ok(AssistantListOutputDTO {
+            assistants: assistants.iter().map(AssistantOutputDTO::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ListAuditEntriesInputDTO {
+    pub tenant_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub chat_id: Option<Uuid>,
+    pub request_id: Option<Uuid>,
+    // from is inclusive and to exclusive
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    // limit defaults to 50 entries per page
+    pub limit: Option<usize>,
+    // cursor is the next_cursor of the previous page, the first page when omitted
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntryOutputDTO {
+    pub id: Uuid,
+    pub request_id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub direction: String,
+    pub provider: String,
+    pub model: String,
+    pub content: String,
+    pub error: Option<String>,
+    pub latency_ms: Option<u64>,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntryListOutputDTO {
+    pub entries: Vec<AuditEntryOutputDTO>,
+    // next_cursor is set while more entries follow
+    pub next_cursor: Option<String>,
+}
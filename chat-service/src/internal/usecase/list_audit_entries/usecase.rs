@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::audit::{AuditCursor, AuditQuery, AuditRepository};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_audit_entries::dto::{
+    AuditEntryListOutputDTO, AuditEntryOutputDTO, ListAuditEntriesInputDTO,
+};
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 500;
+
+pub struct ListAuditEntriesUseCase {
+    audit: Arc<dyn AuditRepository>,
+}
+
+impl ListAuditEntriesUseCase {
+    pub fn new(audit: Arc<dyn AuditRepository>) -> Self {
+        Self { audit }
+    }
+
+    // execute returns a page of the audit log oldest first for compliance reviews, across
+    // tenants unless one is asked for
+    #[instrument(name = "list_audit_entries", skip_all)]
+    pub async fn execute(
+        &self,
+        input: ListAuditEntriesInputDTO,
+    ) -> Result<AuditEntryListOutputDTO, UseCaseError> {
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(UseCaseError::InvalidInput(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+        if matches!((input.from, input.to), (Some(from), Some(to)) if from >= to) {
+            return Err(UseCaseError::InvalidInput(
+                "from must be before to".to_string(),
+            ));
+        }
+        let after = input
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                decode_cursor(cursor)
+                    .ok_or_else(|| UseCaseError::InvalidInput("cursor is invalid".to_string()))
+            })
+            .transpose()?;
+
+        // one entry past the page tells whether another page follows
+        let mut entries = self
+            .audit
+            .list_entries(&AuditQuery {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                chat_id: input.chat_id,
+                request_id: input.request_id,
+                from: input.from,
+                to: input.to,
+                after,
+                limit: limit + 1,
+            })
+            .await?;
+        let next_cursor = if entries.len() > limit {
+            entries.truncate(limit);
+            entries
+                .last()
+                .map(|last| encode_cursor(&AuditCursor::of(last)))
+        } else {
+            None
+        };
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| AuditEntryOutputDTO {
+                id: entry.id,
+                request_id: entry.request_id,
+                tenant_id: entry.tenant_id,
+                user_id: entry.user_id,
+                chat_id: entry.chat_id,
+                direction: entry.direction.to_string(),
+                provider: entry.provider,
+                model: entry.model,
+                content: entry.content,
+                error: entry.error,
+                latency_ms: entry.latency_ms,
+                prompt_tokens: entry.prompt_tokens,
+                completion_tokens: entry.completion_tokens,
+                created_at: entry.created_at,
+            })
+            .collect();
+
+        Ok(AuditEntryListOutputDTO {
+            entries,
+            next_cursor,
+        })
+    }
+}
+
+// encode_cursor keeps cursors opaque to clients, the timestamp is kept to the microsecond
+// since that is the precision postgres stores
+fn encode_cursor(cursor: &AuditCursor) -> String {
+    hex::encode(format!(
+        "{}:{}",
+        cursor.created_at.timestamp_micros(),
+        cursor.id
+    ))
+}
+
+fn decode_cursor(cursor: &str) -> Option<AuditCursor> {
+    let decoded = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+    let (micros, id) = decoded.split_once(':')?;
+
+    Some(AuditCursor {
+        created_at: Utc.timestamp_micros(micros.parse().ok()?).single()?,
+        id: Uuid::parse_str(id).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::audit::{AuditDirection, AuditEntry};
+    use crate::internal::infra::repository::memory::audit::InMemoryAuditRepository;
+
+    fn entry(chat_id: Uuid, direction: AuditDirection) -> AuditEntry {
+        AuditEntry {
+            id: Uuid::new_v4(),
+            request_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            chat_id,
+            direction,
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            content: "Hello!".to_string(),
+            error: None,
+            latency_ms: None,
+            prompt_tokens: 10,
+            completion_tokens: 0,
+            created_at: Utc.timestamp_micros(Utc::now().timestamp_micros()).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_paginates() {
+        let audit = Arc::new(InMemoryAuditRepository::new());
+        let chat_id = Uuid::new_v4();
+        let mut logged = vec![];
+        for direction in [
+            AuditDirection::Prompt,
+            AuditDirection::Response,
+            AuditDirection::Prompt,
+        ] {
+            let entry = entry(chat_id, direction);
+            audit.append(&entry).await.unwrap();
+            logged.push(entry.id);
+        }
+        audit
+            .append(&entry(Uuid::new_v4(), AuditDirection::Prompt))
+            .await
+            .unwrap();
+        let usecase = ListAuditEntriesUseCase::new(audit);
+
+        let first = usecase
+            .execute(ListAuditEntriesInputDTO {
+                chat_id: Some(chat_id),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.entries.len(), 2);
+        assert!(first.next_cursor.is_some());
+
+        let second = usecase
+            .execute(ListAuditEntriesInputDTO {
+                chat_id: Some(chat_id),
+                limit: Some(2),
+                cursor: first.next_cursor.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.next_cursor, None);
+
+        let mut listed: Vec<Uuid> = first
+            .entries
+            .iter()
+            .chain(&second.entries)
+            .map(|entry| entry.id)
+            .collect();
+        listed.sort();
+        logged.sort();
+        assert_eq!(listed, logged);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_input() {
+        let usecase = ListAuditEntriesUseCase::new(Arc::new(InMemoryAuditRepository::new()));
+        let now = Utc::now();
+
+        for input in [
+            ListAuditEntriesInputDTO {
+                limit: Some(0),
+                ..Default::default()
+            },
+            ListAuditEntriesInputDTO {
+                limit: Some(MAX_LIMIT + 1),
+                ..Default::default()
+            },
+            ListAuditEntriesInputDTO {
+                cursor: Some("not-a-cursor".to_string()),
+                ..Default::default()
+            },
+            ListAuditEntriesInputDTO {
+                from: Some(now),
+                to: Some(now),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                usecase.execute(input).await,
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::message::{Message, Role};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListChatMessagesInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // roles keeps the messages of these roles, every role when empty
+    pub roles: Vec<Role>,
+    // from is inclusive and to exclusive
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    // limit defaults to 50 messages per page
+    pub limit: Option<usize>,
+    // cursor is the next_cursor of the previous page, the first page when omitted
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageOutputDTO {
+    pub id: Uuid,
+    pub role: Role,
+    pub content: String,
+    pub tokens: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    // revision_of is the message this one replaced when the user edited it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision_of: Option<Uuid>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    // candidates are the other replies offered for the message until one is selected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    // interrupted is set on replies cut short because their client went away
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    // request_id is the request the message was created for, for support to find its logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // speaker is the assistant that wrote the message in an orchestrated chat
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    // language is the language a user message was detected to be in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl From<&Message> for MessageOutputDTO {
+    fn from(message: &Message) -> Self {
+        Self {
+            id: message.id,
+            role: message.role,
+            content: message.content.clone(),
+            tokens: message.tokens,
+            created_at: message.created_at,
+            revision_of: message.revision_of,
+            attachments: message.attachments.clone(),
+            candidates: message.candidates.clone(),
+            interrupted: message.interrupted,
+            request_id: message.request_id.clone(),
+            speaker: message.speaker.clone(),
+            language: message.language.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageListOutputDTO {
+    pub messages: Vec<MessageOutputDTO>,
+    // next_cursor is set while more messages follow
+    pub next_cursor: Option<String>,
+}
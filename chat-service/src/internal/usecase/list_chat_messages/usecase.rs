@@ -0,0 +1,291 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::ChatStatus;
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::repository::chat::{ChatRepository, MessageQuery};
+use crate::internal::usecase::archive_chats::usecase::read_history;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_chat_messages::dto::{
+    ListChatMessagesInputDTO, MessageListOutputDTO, MessageOutputDTO,
+};
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 200;
+
+pub struct ListChatMessagesUseCase {
+    repository: Arc<dyn ChatRepository>,
+    blobs: Option<Arc<dyn BlobStore>>,
+}
+
+impl ListChatMessagesUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self {
+            repository,
+            blobs: None,
+        }
+    }
+
+    // with_blob_store lists the messages of cold chats, their history is read back from the
+    // store
+    pub fn with_blob_store(mut self, blobs: Arc<dyn BlobStore>) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    // execute returns a page of the chat messages in the order they were added, without the
+    // system message; chats can only be read by their owner
+    #[instrument(name = "list_chat_messages", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: ListChatMessagesInputDTO,
+    ) -> Result<MessageListOutputDTO, UseCaseError> {
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(UseCaseError::InvalidInput(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+        if let (Some(from), Some(to)) = (input.from, input.to) {
+            if from >= to {
+                return Err(UseCaseError::InvalidInput(format!(
+                    "from {} is not before to {}",
+                    from, to
+                )));
+            }
+        }
+        let after = input
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                Uuid::parse_str(cursor)
+                    .map_err(|_| UseCaseError::InvalidInput("cursor is invalid".to_string()))
+            })
+            .transpose()?;
+
+        let chat = self
+            .repository
+            .find_chat_summary(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| chat.status != ChatStatus::Deleted)
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+
+        // one message past the page tells whether another page follows
+        let query = MessageQuery {
+            roles: input.roles,
+            from: input.from,
+            to: input.to,
+            after,
+            limit: limit + 1,
+        };
+        // only archived chats can be cold, their messages are no longer in the repository
+        let cold = match chat.status {
+            ChatStatus::Archived => self
+                .repository
+                .find_chat_by_id(input.tenant_id, input.chat_id)
+                .await?
+                .filter(|chat| chat.is_cold()),
+            _ => None,
+        };
+        let mut messages = match cold {
+            Some(mut cold) => {
+                read_history(self.blobs.as_deref(), &mut cold).await?;
+                query.page(&cold.messages)
+            }
+            None => {
+                self.repository
+                    .list_messages(input.tenant_id, input.chat_id, &query)
+                    .await?
+            }
+        };
+        let next_cursor = if messages.len() > limit {
+            messages.truncate(limit);
+            messages.last().map(|last| last.id.to_string())
+        } else {
+            None
+        };
+
+        Ok(MessageListOutputDTO {
+            messages: messages.iter().map(MessageOutputDTO::from).collect(),
+            next_cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::domain::history_codec::encode_history;
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    async fn setup() -> (ListChatMessagesUseCase, Chat) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let start = chrono::Utc::now() - chrono::Duration::minutes(10);
+        let message = |role, content, minutes| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                start + chrono::Duration::minutes(minutes),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant.", 0),
+            vec![
+                message(Role::User, "Hello!", 1),
+                message(Role::Assistant, "Hi, how can I help?", 2),
+                message(Role::User, "Tell me a joke", 3),
+                message(Role::Assistant, "Why did the chicken cross the road?", 4),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        (ListChatMessagesUseCase::new(repository), chat)
+    }
+
+    fn input(chat: &Chat) -> ListChatMessagesInputDTO {
+        ListChatMessagesInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            roles: vec![],
+            from: None,
+            to: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, chat) = setup().await;
+
+        let output = usecase.execute(input(&chat)).await.unwrap();
+
+        let roles: Vec<Role> = output.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![Role::User, Role::Assistant, Role::User, Role::Assistant]
+        );
+        assert_eq!(output.messages[1].content, "Hi, how can I help?");
+        assert_eq!(output.next_cursor, None);
+
+        assert!(matches!(
+            usecase
+                .execute(ListChatMessagesInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ListChatMessagesInputDTO {
+                    tenant_id: Uuid::new_v4(),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::ChatNotFound(id)) if id == chat.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_paginates_and_filters() {
+        let (usecase, chat) = setup().await;
+
+        let first = usecase
+            .execute(ListChatMessagesInputDTO {
+                limit: Some(3),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.messages.len(), 3);
+        assert_eq!(first.next_cursor, Some(chat.messages[2].id.to_string()));
+
+        let second = usecase
+            .execute(ListChatMessagesInputDTO {
+                limit: Some(3),
+                cursor: first.next_cursor,
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = second.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![chat.messages[3].id]);
+        assert_eq!(second.next_cursor, None);
+
+        let users = usecase
+            .execute(ListChatMessagesInputDTO {
+                roles: vec![Role::User],
+                from: Some(chat.messages[1].created_at),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = users.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![chat.messages[2].id]);
+
+        let bad_cursor = usecase
+            .execute(ListChatMessagesInputDTO {
+                cursor: Some("not-a-cursor".to_string()),
+                ..input(&chat)
+            })
+            .await;
+        assert!(matches!(bad_cursor, Err(UseCaseError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reads_cold_chats() {
+        let (_, hot) = setup().await;
+        let blobs = Arc::new(InMemoryBlobStore::new());
+        blobs
+            .put("cold.jsonl", encode_history(&hot).unwrap())
+            .await
+            .unwrap();
+        let mut cold = hot.clone();
+        cold.archive_history("cold.jsonl".to_string()).unwrap();
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&cold).await.unwrap();
+
+        assert!(matches!(
+            ListChatMessagesUseCase::new(repository.clone())
+                .execute(input(&cold))
+                .await,
+            Err(UseCaseError::Domain(ChatError::ChatArchived))
+        ));
+
+        let usecase = ListChatMessagesUseCase::new(repository).with_blob_store(blobs);
+        let output = usecase
+            .execute(ListChatMessagesInputDTO {
+                roles: vec![Role::User],
+                ..input(&cold)
+            })
+            .await
+            .unwrap();
+        let contents: Vec<&str> = output.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["Hello!", "Tell me a joke"]);
+    }
+}
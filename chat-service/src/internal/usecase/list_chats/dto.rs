@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListChatsInputDTO {
+    pub tenant_id: Uuid,
+    // user_id is the user whose chats are listed, requester_id the authenticated user
+    pub user_id: Uuid,
+    pub requester_id: Uuid,
+    // limit defaults to 20 chats per page
+    pub limit: Option<usize>,
+    // cursor is the next_cursor of the previous page, the first page when omitted
+    pub cursor: Option<String>,
+    // tag keeps the chats labelled with it, it is normalized like the tags of a chat
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatUsageOutputDTO {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatSummaryOutputDTO {
+    pub id: Uuid,
+    pub status: String,
+    pub model: String,
+    // title is null until the first exchange has been titled
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub metadata: BTreeMap<String, String>,
+    pub message_count: usize,
+    // last_message_preview is the start of the last user or assistant message
+    pub last_message_preview: Option<String>,
+    // token_usage is the current prompt size, usage what the chat consumed over its lifetime
+    pub token_usage: usize,
+    pub usage: ChatUsageOutputDTO,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_activity_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatListOutputDTO {
+    pub chats: Vec<ChatSummaryOutputDTO>,
+    // next_cursor is set while more chats follow
+    pub next_cursor: Option<String>,
+}
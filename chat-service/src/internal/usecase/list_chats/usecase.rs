@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::normalize_tag;
+use crate::internal::domain::entity::usage::ChatUsage;
+use crate::internal::domain::repository::chat::{ChatCursor, ChatRepository};
+use crate::internal::domain::repository::usage::UsageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_chats::dto::{
+    ChatListOutputDTO, ChatSummaryOutputDTO, ChatUsageOutputDTO, ListChatsInputDTO,
+};
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+
+pub struct ListChatsUseCase {
+    chats: Arc<dyn ChatRepository>,
+    usage: Arc<dyn UsageRepository>,
+}
+
+impl ListChatsUseCase {
+    pub fn new(chats: Arc<dyn ChatRepository>, usage: Arc<dyn UsageRepository>) -> Self {
+        Self { chats, usage }
+    }
+
+    // execute returns a page of the user's chats, most recent activity first; users can only
+    // list their own chats and other users are reported as not found
+    #[instrument(name = "list_chats", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: ListChatsInputDTO,
+    ) -> Result<ChatListOutputDTO, UseCaseError> {
+        if input.user_id != input.requester_id {
+            return Err(UseCaseError::UserNotFound(input.user_id));
+        }
+
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(UseCaseError::InvalidInput(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+        let after = input
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                decode_cursor(cursor)
+                    .ok_or_else(|| UseCaseError::InvalidInput("cursor is invalid".to_string()))
+            })
+            .transpose()?;
+        let tag = input.tag.as_deref().map(normalize_tag).transpose()?;
+
+        // one chat past the page tells whether another page follows
+        let mut summaries = self
+            .chats
+            .list_chat_summaries(
+                input.tenant_id,
+                input.user_id,
+                tag.as_deref(),
+                after,
+                limit + 1,
+            )
+            .await?;
+        let next_cursor = if summaries.len() > limit {
+            summaries.truncate(limit);
+            summaries
+                .last()
+                .map(|last| encode_cursor(&ChatCursor::of(last)))
+        } else {
+            None
+        };
+
+        let ids: Vec<Uuid> = summaries.iter().map(|summary| summary.id).collect();
+        let mut usage: HashMap<Uuid, ChatUsage> = self
+            .usage
+            .list_chat_usage(input.tenant_id, &ids)
+            .await?
+            .into_iter()
+            .map(|usage| (usage.chat_id, usage))
+            .collect();
+
+        let chats = summaries
+            .into_iter()
+            .map(|summary| {
+                let usage = usage
+                    .remove(&summary.id)
+                    .unwrap_or_else(|| ChatUsage::new(summary.id));
+
+                ChatSummaryOutputDTO {
+                    id: summary.id,
+                    status: summary.status.to_string(),
+                    model: summary.model,
+                    title: summary.title,
+                    tags: summary.tags,
+                    metadata: summary.metadata,
+                    message_count: summary.message_count,
+                    last_message_preview: summary.last_message_preview,
+                    token_usage: summary.token_usage,
+                    usage: ChatUsageOutputDTO {
+                        requests: usage.requests,
+                        prompt_tokens: usage.prompt_tokens,
+                        completion_tokens: usage.completion_tokens,
+                        total_tokens: usage.total_tokens(),
+                        cost: usage.cost,
+                    },
+                    created_at: summary.created_at,
+                    last_activity_at: summary.last_activity_at,
+                }
+            })
+            .collect();
+
+        Ok(ChatListOutputDTO { chats, next_cursor })
+    }
+}
+
+// encode_cursor keeps cursors opaque to clients, the timestamp is kept to the microsecond
+// since that is the precision postgres stores
+fn encode_cursor(cursor: &ChatCursor) -> String {
+    hex::encode(format!(
+        "{}:{}",
+        cursor.last_activity_at.timestamp_micros(),
+        cursor.id
+    ))
+}
+
+fn decode_cursor(cursor: &str) -> Option<ChatCursor> {
+    let decoded = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+    let (micros, id) = decoded.split_once(':')?;
+
+    Some(ChatCursor {
+        last_activity_at: Utc.timestamp_micros(micros.parse().ok()?).single()?,
+        id: Uuid::parse_str(id).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::usage::UsageRecord;
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::usage::InMemoryUsageRepository;
+
+    fn new_chat(user_id: Uuid) -> Chat {
+        let model = Model::new("gpt-4o".to_string(), 128000);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            Utc::now(),
+        );
+        let user = Message::new(
+            Uuid::new_v4(),
+            Role::User,
+            "Hello!",
+            0,
+            model.clone(),
+            Utc::now(),
+        );
+
+        Chat::new(
+            Uuid::new_v4(),
+            user_id,
+            system,
+            vec![user],
+            vec![],
+            ChatStatus::Active,
+            10,
+            ChatConfig::default_for(model),
+        )
+    }
+
+    fn input(user_id: Uuid, limit: Option<usize>, cursor: Option<String>) -> ListChatsInputDTO {
+        ListChatsInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            requester_id: user_id,
+            limit,
+            cursor,
+            tag: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_paginates() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let usage = Arc::new(InMemoryUsageRepository::new());
+        let user_id = Uuid::new_v4();
+        let created: Vec<Chat> = (0..3).map(|_| new_chat(user_id)).collect();
+        for chat in &created {
+            chats.create_chat(chat).await.unwrap();
+        }
+        chats
+            .update_chat_title(DEFAULT_TENANT_ID, created[2].id, "Greetings")
+            .await
+            .unwrap();
+        usage
+            .record_usage(&UsageRecord {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: created[2].id,
+                model: "gpt-4o".to_string(),
+                prompt_tokens: 100,
+                completion_tokens: 40,
+                cost: 0.01,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let usecase = ListChatsUseCase::new(chats, usage);
+
+        let first = usecase
+            .execute(input(user_id, Some(2), None))
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = first.chats.iter().map(|chat| chat.id).collect();
+        assert_eq!(ids, vec![created[2].id, created[1].id]);
+        assert_eq!(first.chats[0].message_count, 1);
+        assert_eq!(
+            first.chats[0].last_message_preview.as_deref(),
+            Some("Hello!")
+        );
+        assert_eq!(first.chats[0].title.as_deref(), Some("Greetings"));
+        assert_eq!(first.chats[1].title, None);
+        assert_eq!(first.chats[0].usage.requests, 1);
+        assert_eq!(first.chats[0].usage.total_tokens, 140);
+        assert_eq!(first.chats[1].usage.requests, 0);
+
+        let second = usecase
+            .execute(input(user_id, Some(2), first.next_cursor))
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = second.chats.iter().map(|chat| chat.id).collect();
+        assert_eq!(ids, vec![created[0].id]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_input() {
+        let usecase = ListChatsUseCase::new(
+            Arc::new(InMemoryChatRepository::new()),
+            Arc::new(InMemoryUsageRepository::new()),
+        );
+        let user_id = Uuid::new_v4();
+
+        let other_user = usecase
+            .execute(ListChatsInputDTO {
+                requester_id: Uuid::new_v4(),
+                ..input(user_id, None, None)
+            })
+            .await;
+        assert!(matches!(other_user, Err(UseCaseError::UserNotFound(id)) if id == user_id));
+
+        let too_many = usecase
+            .execute(input(user_id, Some(MAX_LIMIT + 1), None))
+            .await;
+        assert!(matches!(too_many, Err(UseCaseError::InvalidInput(_))));
+
+        let bad_cursor = usecase
+            .execute(input(user_id, None, Some("not-a-cursor".to_string())))
+            .await;
+        assert!(matches!(bad_cursor, Err(UseCaseError::InvalidInput(_))));
+
+        let bad_tag = usecase
+            .execute(ListChatsInputDTO {
+                tag: Some("two words".to_string()),
+                ..input(user_id, None, None)
+            })
+            .await;
+        assert!(matches!(
+            bad_tag,
+            Err(UseCaseError::Domain(ChatError::InvalidTag(_)))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = ChatCursor {
+            last_activity_at: Utc.timestamp_micros(1_704_187_815_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+
+        assert_eq!(decode_cursor(&encode_cursor(&cursor)), Some(cursor));
+    }
+}
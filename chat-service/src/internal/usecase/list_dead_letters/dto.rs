@@ -0,0 +1,39 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::webhook::{DeadLetter, WebhookEvent};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeadLetterOutputDTO {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&DeadLetter> for DeadLetterOutputDTO {
+    fn from(dead_letter: &DeadLetter) -> Self {
+        let delivery = &dead_letter.delivery;
+        Self {
+            id: delivery.id,
+            webhook_id: delivery.webhook_id,
+            event_id: delivery.event_id,
+            event: delivery.event,
+            payload: delivery.payload.clone(),
+            attempts: delivery.attempts,
+            last_error: delivery.last_error.clone(),
+            created_at: delivery.created_at,
+            failed_at: dead_letter.failed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeadLetterListOutputDTO {
+    pub dead_letters: Vec<DeadLetterOutputDTO>,
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_dead_letters::dto::{
+    DeadLetterListOutputDTO, DeadLetterOutputDTO,
+};
+
+// MAX_DEAD_LETTERS bounds how many dead letters are returned at once
+pub const MAX_DEAD_LETTERS: usize = 100;
+
+pub struct ListDeadLettersUseCase {
+    webhooks: Arc<dyn WebhookRepository>,
+}
+
+impl ListDeadLettersUseCase {
+    pub fn new(webhooks: Arc<dyn WebhookRepository>) -> Self {
+        Self { webhooks }
+    }
+
+    // execute returns the latest webhook deliveries of the tenant that failed every attempt,
+    // newest first
+    #[instrument(name = "list_dead_letters", skip_all, fields(tenant_id = %tenant_id))]
+    pub async fn execute(&self, tenant_id: Uuid) -> Result<DeadLetterListOutputDTO, UseCaseError> {
+        let dead_letters = self
+            .webhooks
+            .list_dead_letters(tenant_id, MAX_DEAD_LETTERS)
+            .await?;
+
+        Ok(DeadLetterListOutputDTO {
+            dead_letters: dead_letters.iter().map(DeadLetterOutputDTO::from).collect(),
+        })
+    }
+}
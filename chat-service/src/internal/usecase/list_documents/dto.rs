@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::usecase::ingest_document::dto::DocumentOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentListOutputDTO {
+    pub documents: Vec<DocumentOutputDTO>,
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::document::DocumentRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::ingest_document::dto::DocumentOutputDTO;
+use crate::internal::usecase::list_documents::dto::DocumentListOutputDTO;
+
+pub struct ListDocumentsUseCase {
+    documents: Arc<dyn DocumentRepository>,
+}
+
+impl ListDocumentsUseCase {
+    pub fn new(documents: Arc<dyn DocumentRepository>) -> Self {
+        Self { documents }
+    }
+
+    // execute returns the documents the user uploaded, newest first
+    #[instrument(name = "list_documents", skip_all, fields(user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<DocumentListOutputDTO, UseCaseError> {
+        let documents = self
+            .documents
+            .list_documents_by_user(tenant_id, user_id)
+            .await?;
+
+        Ok(DocumentListOutputDTO {
+            documents: documents.iter().map(DocumentOutputDTO::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::user_memory::Memory;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryOutputDTO {
+    pub id: Uuid,
+    // chat_id is the chat the memory was extracted from
+    pub chat_id: Uuid,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Memory> for MemoryOutputDTO {
+    fn from(memory: &Memory) -> Self {
+        Self {
+            id: memory.id,
+            chat_id: memory.chat_id,
+            content: memory.content.clone(),
+            created_at: memory.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryListOutputDTO {
+    pub memories: Vec<MemoryOutputDTO>,
+}
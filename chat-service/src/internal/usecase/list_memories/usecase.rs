@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::user_memory::MemoryRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_memories::dto::{MemoryListOutputDTO, MemoryOutputDTO};
+
+pub struct ListMemoriesUseCase {
+    memories: Arc<dyn MemoryRepository>,
+}
+
+impl ListMemoriesUseCase {
+    pub fn new(memories: Arc<dyn MemoryRepository>) -> Self {
+        Self { memories }
+    }
+
+    // execute returns what is remembered about the user, oldest first
+    #[instrument(name = "list_memories", skip_all, fields(user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<MemoryListOutputDTO, UseCaseError> {
+        let memories = self
+            .memories
+            .list_memories_by_user(tenant_id, user_id)
+            .await?;
+
+        Ok(MemoryListOutputDTO {
+            memories: memories.iter().map(MemoryOutputDTO::from).collect(),
+        })
+    }
+}
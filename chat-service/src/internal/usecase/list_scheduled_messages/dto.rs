@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::usecase::schedule_message::dto::ScheduledMessageOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMessageListOutputDTO {
+    pub scheduled_messages: Vec<ScheduledMessageOutputDTO>,
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_scheduled_messages::dto::ScheduledMessageListOutputDTO;
+use crate::internal::usecase::schedule_message::dto::ScheduledMessageOutputDTO;
+
+pub struct ListScheduledMessagesUseCase {
+    scheduled: Arc<dyn ScheduledMessageRepository>,
+}
+
+impl ListScheduledMessagesUseCase {
+    pub fn new(scheduled: Arc<dyn ScheduledMessageRepository>) -> Self {
+        Self { scheduled }
+    }
+
+    // execute returns the messages of the user still waiting to be sent, soonest first
+    #[instrument(name = "list_scheduled_messages", skip_all, fields(user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ScheduledMessageListOutputDTO, UseCaseError> {
+        let scheduled = self
+            .scheduled
+            .list_pending_by_user(tenant_id, user_id)
+            .await?;
+
+        Ok(ScheduledMessageListOutputDTO {
+            scheduled_messages: scheduled
+                .iter()
+                .map(ScheduledMessageOutputDTO::from)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+use crate::internal::usecase::create_tenant::dto::TenantOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TenantListOutputDTO {
+    pub tenants: Vec<TenantOutputDTO>,
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::create_tenant::dto::TenantOutputDTO;
+use crate::internal::usecase::list_tenants::dto::TenantListOutputDTO;
+
+pub struct ListTenantsUseCase {
+    tenants: Arc<TenantRegistry>,
+}
+
+impl ListTenantsUseCase {
+    pub fn new(tenants: Arc<TenantRegistry>) -> Self {
+        Self { tenants }
+    }
+
+    // execute returns the tenants this instance serves, configured and stored ones alike
+    pub fn execute(&self) -> TenantListOutputDTO {
+        TenantListOutputDTO {
+            tenants: self
+                .tenants
+                .list()
+                .iter()
+                .map(TenantOutputDTO::from)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+
+    #[test]
+    fn test_execute() {
+        let acme = Tenant::new(Uuid::new_v4(), "Acme", TenantConfig::default());
+        let tenants = Arc::new(TenantRegistry::new().with_tenant(acme.clone()).unwrap());
+
+        let output = ListTenantsUseCase::new(tenants).execute();
+
+        let ids: Vec<Uuid> = output.tenants.iter().map(|tenant| tenant.id).collect();
+        assert_eq!(ids, vec![acme.id, DEFAULT_TENANT_ID]);
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+use crate::internal::usecase::create_webhook::dto::WebhookOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookListOutputDTO {
+    pub webhooks: Vec<WebhookOutputDTO>,
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::usecase::create_webhook::dto::WebhookOutputDTO;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_webhooks::dto::WebhookListOutputDTO;
+
+pub struct ListWebhooksUseCase {
+    webhooks: Arc<dyn WebhookRepository>,
+}
+
+impl ListWebhooksUseCase {
+    pub fn new(webhooks: Arc<dyn WebhookRepository>) -> Self {
+        Self { webhooks }
+    }
+
+    // execute returns the webhooks of the tenant, oldest first, without their secrets
+    #[instrument(name = "list_webhooks", skip_all, fields(tenant_id = %tenant_id))]
+    pub async fn execute(&self, tenant_id: Uuid) -> Result<WebhookListOutputDTO, UseCaseError> {
+        let webhooks = self.webhooks.list_webhooks(tenant_id).await?;
+
+        Ok(WebhookListOutputDTO {
+            webhooks: webhooks.iter().map(WebhookOutputDTO::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,61 @@
+pub mod archive_chats;
+pub mod authenticate;
+pub mod batch_completion;
+pub mod cancel_scheduled_message;
+pub mod chat_completion;
+pub mod chat_completion_stream;
+pub mod check_readiness;
+pub mod create_api_key;
+pub mod create_assistant;
+pub mod create_prompt_template;
+pub mod create_tenant;
+pub mod create_user;
+pub mod create_webhook;
+pub mod delete_assistant;
+pub mod delete_chat;
+pub mod delete_document;
+pub mod delete_memory;
+pub mod delete_webhook;
+pub mod deliver_webhooks;
+pub mod error;
+pub mod export_chat;
+pub mod fork_chat;
+pub mod get_batch;
+pub mod get_billing_report;
+pub mod get_chat;
+pub mod get_job;
+pub mod get_quota;
+pub mod get_usage;
+pub mod get_usage_summary;
+pub mod import_chat;
+pub mod ingest_document;
+pub mod list_assistants;
+pub mod list_audit_entries;
+pub mod list_chat_messages;
+pub mod list_chats;
+pub mod list_dead_letters;
+pub mod list_documents;
+pub mod list_memories;
+pub mod list_scheduled_messages;
+pub mod list_tenants;
+pub mod list_webhooks;
+pub mod openai_chat_completion;
+pub mod orchestrate_chat;
+pub mod purge_deleted_chats;
+pub mod rag_chat_completion;
+pub mod regenerate_message;
+pub mod relay_events;
+pub mod rotate_api_key;
+pub mod run_jobs;
+pub mod schedule_message;
+pub mod search_messages;
+pub mod search_transcripts;
+pub mod select_candidate;
+pub mod send_scheduled_messages;
+pub mod submit_job;
+pub mod synthesize_speech;
+pub mod transcribe_message;
+pub mod update_assistant;
+pub mod update_chat;
+pub mod update_system_prompt;
+pub mod update_tenant;
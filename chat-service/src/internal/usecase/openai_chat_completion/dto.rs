@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::message::Role;
+use crate::internal::usecase::chat_completion::dto::ChatOverridesInputDTO;
+use crate::internal::usecase::import_chat::dto::ImportedMessageDTO;
+
+pub const COMPLETION_OBJECT: &str = "chat.completion";
+pub const CHUNK_OBJECT: &str = "chat.completion.chunk";
+pub const FINISH_REASON_STOP: &str = "stop";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenAIChatCompletionInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub request: OpenAIChatCompletionRequestDTO,
+}
+
+// OpenAIChatCompletionRequestDTO is the body OpenAI clients send, the whole conversation comes
+// with every request and the fields the service has no use for are ignored
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OpenAIChatCompletionRequestDTO {
+    pub model: String,
+    pub messages: Vec<ImportedMessageDTO>,
+    #[serde(default)]
+    pub stream: bool,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub stop: Option<OpenAIStopDTO>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    // n and tools are refused rather than ignored, the reply would not be what was asked for
+    #[serde(default)]
+    pub n: Option<u32>,
+    #[serde(default)]
+    pub tools: Vec<serde_json::Value>,
+}
+
+impl OpenAIChatCompletionRequestDTO {
+    // overrides are the sampling parameters of the request, the model is the chat's own
+    pub fn overrides(&self) -> ChatOverridesInputDTO {
+        ChatOverridesInputDTO {
+            model: None,
+            temperature: self.temperature,
+            top_p: self.top_p,
+            stop: self.stop.clone().map(OpenAIStopDTO::into_vec),
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+        }
+    }
+}
+
+// OpenAIStopDTO is a single stop sequence or a list of them
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum OpenAIStopDTO {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OpenAIStopDTO {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            OpenAIStopDTO::One(stop) => vec![stop],
+            OpenAIStopDTO::Many(stop) => stop,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenAIMessageDTO {
+    pub role: Role,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenAIChoiceDTO {
+    pub index: u32,
+    pub message: OpenAIMessageDTO,
+    pub finish_reason: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OpenAIUsageDTO {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+}
+
+// OpenAIChatCompletionOutputDTO is the reply as OpenAI returns it, id names the chat the
+// conversation was saved as
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenAIChatCompletionOutputDTO {
+    pub id: String,
+    pub object: &'static str,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<OpenAIChoiceDTO>,
+    pub usage: OpenAIUsageDTO,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OpenAIDeltaDTO {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenAIChunkChoiceDTO {
+    pub index: u32,
+    pub delta: OpenAIDeltaDTO,
+    pub finish_reason: Option<&'static str>,
+}
+
+// OpenAIChatCompletionChunkDTO is a streamed delta of the reply, the last chunk has no content
+// and tells why the reply finished
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenAIChatCompletionChunkDTO {
+    pub id: String,
+    pub object: &'static str,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<OpenAIChunkChoiceDTO>,
+}
@@ -0,0 +1,556 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::chat::{Chat, ConfigOverrides};
+use crate::internal::domain::entity::message::{Message, Role};
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::repository::user::UserRepository;
+use crate::internal::domain::token_counter::TokenCounter;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionConfigInputDTO, ChatCompletionOutputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::{
+    new_chat, resolve_overrides, ChatCompletionUseCase, LoadedChat,
+};
+use crate::internal::usecase::chat_completion_stream::usecase::ChatCompletionStreamUseCase;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::import_chat::usecase::{
+    system_text, to_message, MAX_IMPORTED_MESSAGES,
+};
+use crate::internal::usecase::openai_chat_completion::dto::{
+    OpenAIChatCompletionChunkDTO, OpenAIChatCompletionInputDTO, OpenAIChatCompletionOutputDTO,
+    OpenAIChoiceDTO, OpenAIChunkChoiceDTO, OpenAIDeltaDTO, OpenAIMessageDTO, OpenAIUsageDTO,
+    CHUNK_OBJECT, COMPLETION_OBJECT, FINISH_REASON_STOP,
+};
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+const DELTA_BUFFER_SIZE: usize = 32;
+
+// OpenAIChatCompletionUseCase answers OpenAI chat completion requests through the chat
+// completion use cases, so they are limited, moderated and charged like any other message;
+// every request is saved as a new chat holding the conversation it sent
+pub struct OpenAIChatCompletionUseCase {
+    chat_completion: Arc<ChatCompletionUseCase>,
+    chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+    users: Arc<dyn UserRepository>,
+    config: ChatCompletionConfigInputDTO,
+}
+
+// Conversation is a request read as the chat it continues, the earlier messages it holds and
+// the user message to answer
+struct Conversation {
+    chat: Chat,
+    history: Vec<Message>,
+    user_message: Message,
+    overrides: ConfigOverrides,
+}
+
+impl OpenAIChatCompletionUseCase {
+    pub fn new(
+        chat_completion: Arc<ChatCompletionUseCase>,
+        chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+        users: Arc<dyn UserRepository>,
+        config: ChatCompletionConfigInputDTO,
+    ) -> Self {
+        Self {
+            chat_completion,
+            chat_completion_stream,
+            users,
+            config,
+        }
+    }
+
+    // execute answers the last user message of the conversation in a single reply
+    #[instrument(name = "openai_chat_completion", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: OpenAIChatCompletionInputDTO,
+    ) -> Result<OpenAIChatCompletionOutputDTO, UseCaseError> {
+        let Conversation {
+            chat,
+            history,
+            user_message,
+            overrides,
+        } = self.read(&input).await?;
+        let user_message = self
+            .chat_completion
+            .admit(input.tenant_id, input.user_id, None, user_message)
+            .await?;
+        let chat = with_history(chat, history, |message| {
+            self.chat_completion
+                .screen(input.tenant_id, input.user_id, None, message)
+        })
+        .await?;
+
+        let model = chat.config.model.clone();
+        let prompt_tokens = chat.token_usage + user_message.tokens;
+        let created = chrono::Utc::now().timestamp();
+        let output = self
+            .chat_completion
+            .reply_with_context(
+                LoadedChat { chat, is_new: true },
+                user_message,
+                None,
+                &overrides,
+            )
+            .await?;
+
+        Ok(completion(output, &model, prompt_tokens, created))
+    }
+
+    // execute_stream sends the reply to the stream as chunks while the model is answering, the
+    // first one carries the assistant role and the last one the finish reason
+    #[instrument(name = "openai_chat_completion_stream", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute_stream(
+        &self,
+        input: OpenAIChatCompletionInputDTO,
+        stream: mpsc::Sender<OpenAIChatCompletionChunkDTO>,
+    ) -> Result<OpenAIChatCompletionOutputDTO, UseCaseError> {
+        let Conversation {
+            chat,
+            history,
+            user_message,
+            overrides,
+        } = self.read(&input).await?;
+        let user_message = self
+            .chat_completion_stream
+            .admit(input.tenant_id, input.user_id, None, user_message)
+            .await?;
+        let chat = with_history(chat, history, |message| {
+            self.chat_completion_stream
+                .screen(input.tenant_id, input.user_id, None, message)
+        })
+        .await?;
+
+        let id = completion_id(chat.id);
+        let model = chat.config.model.clone();
+        let prompt_tokens = chat.token_usage + user_message.tokens;
+        let created = chrono::Utc::now().timestamp();
+        let chunk = |delta, finish_reason| OpenAIChatCompletionChunkDTO {
+            id: id.clone(),
+            object: CHUNK_OBJECT,
+            created,
+            model: model.name.clone(),
+            choices: vec![OpenAIChunkChoiceDTO {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+        };
+
+        let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(DELTA_BUFFER_SIZE);
+        let forward = async {
+            let mut role = Some(Role::Assistant);
+            while let Some(output) = receiver.recv().await {
+                let delta = OpenAIDeltaDTO {
+                    role: role.take(),
+                    content: Some(output.content),
+                };
+                if stream.send(chunk(delta, None)).await.is_err() {
+                    break;
+                }
+            }
+            // dropping the receiver tells the stream use case the client is gone
+            drop(receiver);
+        };
+        let reply = self.chat_completion_stream.reply(
+            LoadedChat { chat, is_new: true },
+            user_message,
+            &overrides,
+            sender,
+        );
+        let (output, _) = tokio::join!(reply, forward);
+        let output = output?;
+
+        // the reply is saved by now, a client gone before the last chunk only misses it
+        let _ = stream
+            .send(chunk(OpenAIDeltaDTO::default(), Some(FINISH_REASON_STOP)))
+            .await;
+
+        Ok(completion(output, &model, prompt_tokens, created))
+    }
+
+    // read turns the conversation of the request into a new chat of the user: the system
+    // message opens it, the configured one when there is none, and the last message is the
+    // user message to answer; the earlier messages are added once their user messages are
+    // screened
+    async fn read(
+        &self,
+        input: &OpenAIChatCompletionInputDTO,
+    ) -> Result<Conversation, UseCaseError> {
+        let request = &input.request;
+        if request.n.is_some_and(|n| n != 1) {
+            return Err(UseCaseError::InvalidInput(
+                "only one choice is returned, n has to be 1".to_string(),
+            ));
+        }
+        if !request.tools.is_empty() {
+            return Err(UseCaseError::InvalidInput(
+                "tools are not supported".to_string(),
+            ));
+        }
+
+        if self
+            .users
+            .find_user_by_id(input.tenant_id, input.user_id)
+            .await?
+            .is_none()
+        {
+            return Err(UseCaseError::UserNotFound(input.user_id));
+        }
+        let model = resolve_model(&request.model)?;
+        let overrides = resolve_overrides(&request.overrides())?;
+
+        let mut messages = request.messages.clone();
+        let system_message = match messages.first() {
+            Some(message) if message.role == Role::System => Some(messages.remove(0)),
+            _ => None,
+        };
+        let Some(last) = messages.pop().filter(|message| message.role == Role::User) else {
+            return Err(UseCaseError::InvalidInput(
+                "the last message has to be a user message".to_string(),
+            ));
+        };
+        if messages.len() > MAX_IMPORTED_MESSAGES {
+            return Err(UseCaseError::InvalidInput(format!(
+                "a conversation takes at most {} messages",
+                MAX_IMPORTED_MESSAGES
+            )));
+        }
+
+        let system_message = match system_message {
+            Some(system_message) => system_text(&system_message)?,
+            None => self.config.initial_system_message.clone(),
+        };
+        let chat = new_chat(input.user_id, &model, &self.config, &system_message)?
+            .with_tenant(input.tenant_id);
+        let mut history = Vec::with_capacity(messages.len());
+        for message in messages {
+            let message = to_message(message, &model)?;
+            message.validate()?;
+            history.push(message);
+        }
+
+        let user_message = to_message(last, &model)?;
+        user_message.validate()?;
+
+        Ok(Conversation {
+            chat,
+            history,
+            user_message,
+            overrides,
+        })
+    }
+}
+
+// with_history adds the earlier messages of a conversation to its chat, the user messages go
+// through redaction, moderation and the prompt guard like the message to answer
+async fn with_history<F, Fut>(
+    mut chat: Chat,
+    history: Vec<Message>,
+    screen: F,
+) -> Result<Chat, UseCaseError>
+where
+    F: Fn(Message) -> Fut,
+    Fut: Future<Output = Result<Message, UseCaseError>>,
+{
+    for message in history {
+        let message = match message.role {
+            Role::User => screen(message).await?,
+            _ => message,
+        };
+        chat.add_message(message)?;
+    }
+    chat.validate()?;
+
+    Ok(chat)
+}
+
+// completion_id names the chat a reply was saved in the way OpenAI names its completions
+fn completion_id(chat_id: Uuid) -> String {
+    format!("chatcmpl-{}", chat_id.simple())
+}
+
+// completion writes the reply as OpenAI returns it; the tokens are counted with the model's
+// encoding, the prompts of tool rounds are not included
+fn completion(
+    output: ChatCompletionOutputDTO,
+    model: &Model,
+    prompt_tokens: usize,
+    created: i64,
+) -> OpenAIChatCompletionOutputDTO {
+    let completion_tokens = TokenCounter::for_model(model)
+        .map(|counter| counter.count(&output.content))
+        .unwrap_or_default();
+
+    OpenAIChatCompletionOutputDTO {
+        id: completion_id(output.chat_id),
+        object: COMPLETION_OBJECT,
+        created,
+        model: model.name.clone(),
+        choices: vec![OpenAIChoiceDTO {
+            index: 0,
+            message: OpenAIMessageDTO {
+                role: Role::Assistant,
+                content: output.content,
+            },
+            finish_reason: FINISH_REASON_STOP,
+        }],
+        usage: OpenAIUsageDTO {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::redactor::Redactor;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::infra::redaction::cipher::AesGcmCipher;
+    use crate::internal::infra::redaction::detector::RegexDetector;
+    use crate::internal::infra::repository::memory::redaction::InMemoryRedactionRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+
+    fn config() -> ChatCompletionConfigInputDTO {
+        ChatCompletionConfigInputDTO {
+            temperature: 0.0,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        }
+    }
+
+    async fn setup(
+        gateway: FakeCompletionGateway,
+    ) -> (
+        OpenAIChatCompletionUseCase,
+        Arc<InMemoryChatRepository>,
+        Arc<FakeCompletionGateway>,
+        Uuid,
+    ) {
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let users = Arc::new(users);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let gateway = Arc::new(gateway);
+
+        let chat_completion = ChatCompletionUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users.clone(),
+            test_model(),
+            config(),
+        );
+        let chat_completion_stream = ChatCompletionStreamUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users.clone(),
+            test_model(),
+            config(),
+        );
+        let usecase = OpenAIChatCompletionUseCase::new(
+            Arc::new(chat_completion),
+            Arc::new(chat_completion_stream),
+            users,
+            config(),
+        );
+
+        (usecase, repository, gateway, user_id)
+    }
+
+    fn input(user_id: Uuid, request: serde_json::Value) -> OpenAIChatCompletionInputDTO {
+        OpenAIChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            request: serde_json::from_value(request).unwrap(),
+        }
+    }
+
+    fn conversation() -> serde_json::Value {
+        serde_json::json!({
+            "model": "gpt-4o",
+            "temperature": 0.2,
+            "stop": "END",
+            "messages": [
+                {"role": "system", "content": "You are terse."},
+                {"role": "user", "content": "Hello!"},
+                {"role": "assistant", "content": "Hi."},
+                {"role": "user", "content": "Tell me a joke"}
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, repository, gateway, user_id) =
+            setup(FakeCompletionGateway::new().reply("Knock knock.")).await;
+
+        let output = usecase
+            .execute(input(user_id, conversation()))
+            .await
+            .unwrap();
+        assert!(output.id.starts_with("chatcmpl-"));
+        assert_eq!(output.object, COMPLETION_OBJECT);
+        assert_eq!(output.model, "gpt-4o");
+        assert_eq!(output.choices[0].message.content, "Knock knock.");
+        assert_eq!(output.choices[0].finish_reason, FINISH_REASON_STOP);
+        assert!(output.usage.prompt_tokens > 0);
+        assert_eq!(
+            output.usage.total_tokens,
+            output.usage.prompt_tokens + output.usage.completion_tokens
+        );
+
+        // the model is prompted with the whole conversation and the sampling of the request
+        let prompt = &gateway.received()[0];
+        assert_eq!(prompt.initial_system_message().content, "You are terse.");
+        assert_eq!(prompt.messages.len(), 3);
+        assert_eq!(prompt.config.temperature, 0.2);
+        assert_eq!(prompt.config.stop, vec!["END".to_string()]);
+
+        let chat_id = Uuid::parse_str(output.id.trim_start_matches("chatcmpl-")).unwrap();
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.user_id, user_id);
+        assert_eq!(chat.messages.len(), 4);
+        assert_eq!(chat.messages[3].content, "Knock knock.");
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream() {
+        let (usecase, _, _, user_id) =
+            setup(FakeCompletionGateway::new().stream(&["Knock", " knock."])).await;
+        let (sender, mut receiver) = mpsc::channel(8);
+
+        let output = usecase
+            .execute_stream(input(user_id, conversation()), sender)
+            .await
+            .unwrap();
+        assert_eq!(output.choices[0].message.content, "Knock knock.");
+
+        let mut chunks = vec![];
+        while let Some(chunk) = receiver.recv().await {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.id == output.id));
+        assert_eq!(chunks[0].choices[0].delta.role, Some(Role::Assistant));
+        assert_eq!(chunks[0].choices[0].delta.content.as_deref(), Some("Knock"));
+        assert_eq!(chunks[1].choices[0].delta.role, None);
+        assert_eq!(chunks[2].choices[0].delta, OpenAIDeltaDTO::default());
+        assert_eq!(chunks[2].choices[0].finish_reason, Some(FINISH_REASON_STOP));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_requests() {
+        let (usecase, _, gateway, user_id) = setup(FakeCompletionGateway::new()).await;
+
+        let requests = [
+            serde_json::json!({"model": "gpt-4o", "messages": []}),
+            serde_json::json!({"model": "gpt-4o", "messages": [
+                {"role": "user", "content": "Hello!"},
+                {"role": "assistant", "content": "Hi."}
+            ]}),
+            serde_json::json!({"model": "gpt-0", "messages": [{"role": "user", "content": "Hello!"}]}),
+            serde_json::json!({"model": "gpt-4o", "n": 2, "messages": [{"role": "user", "content": "Hello!"}]}),
+            serde_json::json!({"model": "gpt-4o", "tools": [{"type": "function"}], "messages": [{"role": "user", "content": "Hello!"}]}),
+        ];
+        for request in requests {
+            assert!(matches!(
+                usecase.execute(input(user_id, request)).await,
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+        assert_eq!(gateway.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_screens_earlier_user_messages() {
+        let (_, repository, gateway, user_id) = setup(FakeCompletionGateway::new()).await;
+        let redactor = Arc::new(Redactor::new(
+            vec![Arc::new(RegexDetector::email())],
+            Arc::new(AesGcmCipher::new(&[7u8; 32]).unwrap()),
+            Arc::new(InMemoryRedactionRepository::new()),
+        ));
+        let users = Arc::new(InMemoryUserRepository::new());
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let usecase = OpenAIChatCompletionUseCase::new(
+            Arc::new(
+                ChatCompletionUseCase::new(
+                    gateway.clone(),
+                    repository.clone(),
+                    users.clone(),
+                    test_model(),
+                    config(),
+                )
+                .with_redactor(redactor.clone()),
+            ),
+            Arc::new(
+                ChatCompletionStreamUseCase::new(
+                    gateway.clone(),
+                    repository.clone(),
+                    users.clone(),
+                    test_model(),
+                    config(),
+                )
+                .with_redactor(redactor),
+            ),
+            users,
+            config(),
+        );
+        let request = serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "user", "content": "Write to ada@example.com"},
+                {"role": "assistant", "content": "Done."},
+                {"role": "user", "content": "Thanks"}
+            ]
+        });
+
+        usecase
+            .execute(input(user_id, request.clone()))
+            .await
+            .unwrap();
+        let (sender, _receiver) = mpsc::channel(8);
+        usecase
+            .execute_stream(input(user_id, request), sender)
+            .await
+            .unwrap();
+
+        assert_eq!(gateway.calls(), 2);
+        for prompt in gateway.received() {
+            assert_eq!(prompt.messages[0].content, "Write to [EMAIL]");
+            assert_eq!(prompt.messages[1].content, "Done.");
+        }
+    }
+}
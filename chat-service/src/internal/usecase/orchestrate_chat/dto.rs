@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// OrchestrateChatInputDTO has the assistants take turns after the user message, in the given
+// order, until max_rounds rounds are done; their instructions are filled with the variables
+#[derive(Debug, Clone)]
+pub struct OrchestrateChatInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    pub assistants: Vec<Uuid>,
+    pub variables: HashMap<String, String>,
+    pub max_rounds: Option<usize>,
+}
+
+// AgentTurnOutputDTO is the reply of an assistant in a round, or a delta of it while it is
+// streamed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentTurnOutputDTO {
+    pub chat_id: Uuid,
+    pub round: usize,
+    pub assistant_id: Uuid,
+    pub speaker: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrchestrateChatOutputDTO {
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    pub turns: Vec<AgentTurnOutputDTO>,
+}
@@ -0,0 +1,483 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::instrument;
+
+use crate::internal::domain::entity::chat::{Chat, ConfigOverrides};
+use crate::internal::domain::entity::message::Message;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatCompletionOutputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::LoadedChat;
+use crate::internal::usecase::chat_completion_stream::usecase::{
+    ChatCompletionStreamUseCase, Speaker,
+};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::orchestrate_chat::dto::{
+    AgentTurnOutputDTO, OrchestrateChatInputDTO, OrchestrateChatOutputDTO,
+};
+use crate::internal::usecase::update_chat::usecase::resolve_model;
+
+// DEFAULT_ROUNDS is how many rounds run when the input sets no max_rounds, enough for a critic
+// to review the reply it was given and the responder to revise it
+pub const DEFAULT_ROUNDS: usize = 2;
+pub const MAX_ROUNDS: usize = 10;
+pub const MAX_AGENTS: usize = 5;
+
+const DELTA_BUFFER_SIZE: usize = 32;
+
+pub struct OrchestrateChatUseCase {
+    chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+    assistants: Arc<dyn AssistantRepository>,
+}
+
+impl OrchestrateChatUseCase {
+    // new has the turns answered by the streaming completion use case so every turn is rate
+    // limited, tracked and saved like any other reply
+    pub fn new(
+        chat_completion_stream: Arc<ChatCompletionStreamUseCase>,
+        assistants: Arc<dyn AssistantRepository>,
+    ) -> Self {
+        Self {
+            chat_completion_stream,
+            assistants,
+        }
+    }
+
+    // execute adds the user message to the chat, then every round has each assistant reply in
+    // turn, labelled with its name, with the replies of the others in its prompt; the deltas of
+    // each turn are streamed as they arrive and the chat is saved after every turn, a client
+    // going away stops the orchestration after saving what it received
+    #[instrument(name = "orchestrate_chat", skip_all, fields(user_id = %input.user_id, chat_id))]
+    pub async fn execute(
+        &self,
+        input: OrchestrateChatInputDTO,
+        stream: mpsc::Sender<AgentTurnOutputDTO>,
+    ) -> Result<OrchestrateChatOutputDTO, UseCaseError> {
+        let rounds = match input.max_rounds {
+            None => DEFAULT_ROUNDS,
+            Some(rounds) if (1..=MAX_ROUNDS).contains(&rounds) => rounds,
+            Some(_) => {
+                return Err(UseCaseError::InvalidInput(format!(
+                    "max_rounds must be between 1 and {}",
+                    MAX_ROUNDS
+                )))
+            }
+        };
+        let speakers = self.speakers(&input).await?;
+
+        let (loaded, user_message) = self
+            .chat_completion_stream
+            .prepare(&ChatCompletionInputDTO {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                chat_id: input.chat_id,
+                user_message: input.user_message.clone(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: Default::default(),
+                labels: Default::default(),
+            })
+            .await?;
+        let LoadedChat {
+            mut chat,
+            mut is_new,
+        } = loaded;
+        let mut added = vec![user_message.clone()];
+        chat.add_message(user_message)?;
+
+        let mut turns = Vec::with_capacity(rounds * speakers.len());
+        for round in 1..=rounds {
+            for speaker in &speakers {
+                // a turn that was done before the client went away is saved, the next one
+                // would not be heard
+                if !is_new && stream.is_closed() {
+                    return Err(UseCaseError::StreamCancelled(chat.id));
+                }
+                let reply = self
+                    .turn(
+                        &mut chat,
+                        is_new,
+                        std::mem::take(&mut added),
+                        round,
+                        speaker,
+                        &stream,
+                    )
+                    .await?;
+                is_new = false;
+                turns.push(AgentTurnOutputDTO {
+                    chat_id: chat.id,
+                    round,
+                    assistant_id: speaker.assistant.id,
+                    speaker: speaker.assistant.name.clone(),
+                    content: reply.content,
+                });
+            }
+        }
+
+        Ok(OrchestrateChatOutputDTO {
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            turns,
+        })
+    }
+
+    // speakers loads the assistants of the input in their turn order, each takes part once
+    async fn speakers(
+        &self,
+        input: &OrchestrateChatInputDTO,
+    ) -> Result<Vec<Speaker>, UseCaseError> {
+        if !(2..=MAX_AGENTS).contains(&input.assistants.len()) {
+            return Err(UseCaseError::InvalidInput(format!(
+                "an orchestration takes between 2 and {} assistants",
+                MAX_AGENTS
+            )));
+        }
+
+        let mut speakers = Vec::with_capacity(input.assistants.len());
+        for (index, id) in input.assistants.iter().enumerate() {
+            if input.assistants[..index].contains(id) {
+                return Err(UseCaseError::InvalidInput(format!(
+                    "assistant {} is listed twice",
+                    id
+                )));
+            }
+
+            let assistant = self
+                .assistants
+                .find_assistant_by_id(input.tenant_id, *id)
+                .await?
+                .ok_or(UseCaseError::AssistantNotFound(*id))?;
+            let overrides = ConfigOverrides {
+                model: assistant.model.as_deref().map(resolve_model).transpose()?,
+                temperature: assistant.temperature,
+                ..Default::default()
+            };
+            speakers.push(Speaker {
+                instructions: assistant.render_instructions(&input.variables)?,
+                assistant,
+                overrides,
+            });
+        }
+
+        Ok(speakers)
+    }
+
+    // turn has the speaker reply and forwards the deltas of its reply labelled with the round
+    // and the speaker; once the client is gone the forwarding stops, which interrupts the reply
+    async fn turn(
+        &self,
+        chat: &mut Chat,
+        is_new: bool,
+        added: Vec<Message>,
+        round: usize,
+        speaker: &Speaker,
+        stream: &mpsc::Sender<AgentTurnOutputDTO>,
+    ) -> Result<Message, UseCaseError> {
+        let (sender, mut receiver) = mpsc::channel::<ChatCompletionOutputDTO>(DELTA_BUFFER_SIZE);
+        // both futures own their end of the channel, so each sees the other one finish
+        let reply = async move {
+            self.chat_completion_stream
+                .take_turn(chat, is_new, added, speaker, &sender)
+                .await
+        };
+        let forward = async move {
+            loop {
+                let delta = tokio::select! {
+                    delta = receiver.recv() => delta,
+                    _ = stream.closed() => None,
+                };
+                let Some(delta) = delta else { break };
+                let delta = AgentTurnOutputDTO {
+                    chat_id: delta.chat_id,
+                    round,
+                    assistant_id: speaker.assistant.id,
+                    speaker: speaker.assistant.name.clone(),
+                    content: delta.content,
+                };
+                if stream.send(delta).await.is_err() {
+                    break;
+                }
+            }
+        };
+
+        let (reply, ()) = tokio::join!(reply, forward);
+        reply
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::assistant::Assistant;
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::assistant::InMemoryAssistantRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    struct Setup {
+        usecase: OrchestrateChatUseCase,
+        repository: Arc<InMemoryChatRepository>,
+        gateway: Arc<FakeCompletionGateway>,
+        user_id: Uuid,
+        writer: Assistant,
+        critic: Assistant,
+    }
+
+    fn config() -> ChatCompletionConfigInputDTO {
+        ChatCompletionConfigInputDTO {
+            temperature: 0.7,
+            top_p: 1.0,
+            n: 1,
+            stop: vec![],
+            max_tokens: 4096,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            trimming_policy: TrimmingPolicy::TrimOldest,
+            initial_system_message: "You are a helpful assistant.".to_string(),
+            response_format: ResponseFormat::default(),
+        }
+    }
+
+    async fn setup(gateway: FakeCompletionGateway) -> Setup {
+        let user_id = Uuid::new_v4();
+        let users = Arc::new(InMemoryUserRepository::new());
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let gateway = Arc::new(gateway);
+
+        let assistants = Arc::new(InMemoryAssistantRepository::new());
+        let assistant = |name: &str, instructions: &str| {
+            Assistant::new(
+                Uuid::new_v4(),
+                DEFAULT_TENANT_ID,
+                name,
+                instructions,
+                chrono::Utc::now(),
+            )
+        };
+        let writer = assistant("Writer", "You write poems about {{topic}}.");
+        let critic = assistant("Critic", "You review poems.").with_temperature(Some(0.1));
+        for assistant in [&writer, &critic] {
+            assistants.create_assistant(assistant).await.unwrap();
+        }
+
+        let chat_completion_stream = ChatCompletionStreamUseCase::new(
+            gateway.clone(),
+            repository.clone(),
+            users,
+            test_model(),
+            config(),
+        );
+        let usecase = OrchestrateChatUseCase::new(Arc::new(chat_completion_stream), assistants);
+
+        Setup {
+            usecase,
+            repository,
+            gateway,
+            user_id,
+            writer,
+            critic,
+        }
+    }
+
+    fn input(setup: &Setup) -> OrchestrateChatInputDTO {
+        OrchestrateChatInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: setup.user_id,
+            chat_id: None,
+            user_message: "Write a haiku.".to_string(),
+            assistants: vec![setup.writer.id, setup.critic.id],
+            variables: HashMap::from([("topic".to_string(), "the sea".to_string())]),
+            max_rounds: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let setup = setup(
+            FakeCompletionGateway::new()
+                .stream(&["Waves", " fold"])
+                .reply("Too short.")
+                .reply("Waves fold into foam")
+                .reply("Better."),
+        )
+        .await;
+        let (sender, mut receiver) = mpsc::channel(32);
+
+        let output = setup.usecase.execute(input(&setup), sender).await.unwrap();
+        let turns: Vec<_> = output
+            .turns
+            .iter()
+            .map(|turn| (turn.round, turn.speaker.as_str(), turn.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (1, "Writer", "Waves fold"),
+                (1, "Critic", "Too short."),
+                (2, "Writer", "Waves fold into foam"),
+                (2, "Critic", "Better."),
+            ]
+        );
+        assert_eq!(output.turns[1].assistant_id, setup.critic.id);
+
+        let mut deltas = vec![];
+        while let Some(delta) = receiver.recv().await {
+            deltas.push((delta.round, delta.speaker, delta.content));
+        }
+        assert_eq!(deltas.len(), 5);
+        assert_eq!(deltas[1], (1, "Writer".to_string(), " fold".to_string()));
+        assert_eq!(
+            deltas[2],
+            (1, "Critic".to_string(), "Too short.".to_string())
+        );
+
+        // each agent is prompted with its instructions and sampling, the others' replies are
+        // labelled user messages
+        let prompts = setup.gateway.received();
+        assert_eq!(
+            prompts[0].initial_system_message().content,
+            "You write poems about the sea."
+        );
+        assert_eq!(prompts[0].config.temperature, 0.7);
+        assert_eq!(
+            prompts[1].initial_system_message().content,
+            "You review poems."
+        );
+        assert_eq!(prompts[1].config.temperature, 0.1);
+        let critic_sees: Vec<_> = prompts[3]
+            .messages
+            .iter()
+            .map(|message| (message.role, message.content.as_str()))
+            .collect();
+        assert_eq!(
+            critic_sees,
+            vec![
+                (Role::User, "Write a haiku."),
+                (Role::User, "Writer: Waves fold"),
+                (Role::Assistant, "Too short."),
+                (Role::User, "Writer: Waves fold into foam"),
+            ]
+        );
+
+        let chat = setup
+            .repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You are a helpful assistant."
+        );
+        let speakers: Vec<_> = chat
+            .messages
+            .iter()
+            .map(|message| message.speaker.as_deref())
+            .collect();
+        assert_eq!(
+            speakers,
+            vec![
+                None,
+                Some("Writer"),
+                Some("Critic"),
+                Some("Writer"),
+                Some("Critic")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_input() {
+        let setup = setup(FakeCompletionGateway::new()).await;
+        let unknown = Uuid::new_v4();
+
+        for invalid in [
+            OrchestrateChatInputDTO {
+                assistants: vec![setup.writer.id],
+                ..input(&setup)
+            },
+            OrchestrateChatInputDTO {
+                assistants: vec![setup.writer.id, setup.writer.id],
+                ..input(&setup)
+            },
+            OrchestrateChatInputDTO {
+                max_rounds: Some(0),
+                ..input(&setup)
+            },
+            OrchestrateChatInputDTO {
+                max_rounds: Some(MAX_ROUNDS + 1),
+                ..input(&setup)
+            },
+        ] {
+            let (sender, _receiver) = mpsc::channel(32);
+            assert!(matches!(
+                setup.usecase.execute(invalid, sender).await,
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+
+        let (sender, _receiver) = mpsc::channel(32);
+        let result = setup
+            .usecase
+            .execute(
+                OrchestrateChatInputDTO {
+                    assistants: vec![setup.writer.id, unknown],
+                    ..input(&setup)
+                },
+                sender,
+            )
+            .await;
+        assert!(matches!(result, Err(UseCaseError::AssistantNotFound(id)) if id == unknown));
+        assert_eq!(setup.gateway.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_stops_when_client_goes_away() {
+        let setup = setup(FakeCompletionGateway::new().stream(&["Waves", " fold"])).await;
+        let (sender, receiver) = mpsc::channel(32);
+        drop(receiver);
+
+        let result = setup.usecase.execute(input(&setup), sender).await;
+        let Err(UseCaseError::StreamCancelled(chat_id)) = result else {
+            panic!(
+                "expected the orchestration to be cancelled, got {:?}",
+                result
+            );
+        };
+        assert_eq!(setup.gateway.calls(), 1);
+
+        // what the writer said until it was noticed is kept, the critic never took its turn
+        let chat = setup
+            .repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chat.messages[0].content, "Write a haiku.");
+        assert!(chat.messages[1..]
+            .iter()
+            .all(|message| message.speaker.as_deref() == Some("Writer")));
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{instrument, warn};
+
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct PurgeDeletedChatsUseCase {
+    repository: Arc<dyn ChatRepository>,
+    retention: Duration,
+    blobs: Option<Arc<dyn BlobStore>>,
+}
+
+impl PurgeDeletedChatsUseCase {
+    // new keeps deleted chats for the retention period before they are purged
+    pub fn new(repository: Arc<dyn ChatRepository>, retention: Duration) -> Self {
+        Self {
+            repository,
+            retention,
+            blobs: None,
+        }
+    }
+
+    // with_blob_store removes the archived histories of the purged chats from the store
+    pub fn with_blob_store(mut self, blobs: Arc<dyn BlobStore>) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    // execute hard deletes the chats deleted longer than the retention ago with their archived
+    // histories and returns how many were purged; a history that cannot be removed is logged,
+    // the chat is gone already
+    #[instrument(name = "purge_deleted_chats", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let retention = chrono::Duration::from_std(self.retention)
+            .map_err(|_| UseCaseError::InvalidInput("retention is out of range".to_string()))?;
+
+        let purged = self
+            .repository
+            .purge_deleted_chats(chrono::Utc::now() - retention)
+            .await?;
+        if let Some(blobs) = &self.blobs {
+            for chat in &purged {
+                let Some(key) = &chat.history_key else {
+                    continue;
+                };
+                if let Err(e) = blobs.delete(key).await {
+                    warn!(chat_id = %chat.id, key = %key, error = %e, "failed to remove the archived history");
+                }
+            }
+        }
+
+        Ok(purged.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    fn chat() -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+
+        Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let kept = chat();
+        let mut deleted = chat();
+        deleted.delete().unwrap();
+        repository.create_chat(&kept).await.unwrap();
+        repository.create_chat(&deleted).await.unwrap();
+
+        let retained = PurgeDeletedChatsUseCase::new(repository.clone(), Duration::from_secs(3600));
+        assert_eq!(retained.execute().await.unwrap(), 0);
+
+        let usecase = PurgeDeletedChatsUseCase::new(repository.clone(), Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+        assert!(repository
+            .find_chat_by_id(deleted.tenant_id, deleted.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repository
+            .find_chat_by_id(kept.tenant_id, kept.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_execute_removes_archived_histories() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let blobs = Arc::new(InMemoryBlobStore::new());
+        let mut cold = chat();
+        let key = format!("{}/{}/0.jsonl", cold.tenant_id, cold.id);
+        blobs.put(&key, b"{}".to_vec()).await.unwrap();
+        cold.archive_history(key.clone()).unwrap();
+        cold.delete().unwrap();
+        repository.create_chat(&cold).await.unwrap();
+
+        let usecase = PurgeDeletedChatsUseCase::new(repository.clone(), Duration::ZERO)
+            .with_blob_store(blobs.clone());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+        assert_eq!(blobs.get(&key).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::embedding::{VectorKind, VectorMatch};
+
+// CitationOutputDTO is a retrieved chunk the model was given, index is the number it is
+// cited by in the reply, e.g. [1]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitationOutputDTO {
+    pub index: usize,
+    pub id: Uuid,
+    pub kind: VectorKind,
+    pub source_id: Uuid,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub score: f32,
+}
+
+impl CitationOutputDTO {
+    pub fn new(index: usize, found: VectorMatch) -> Self {
+        Self {
+            index,
+            id: found.id,
+            kind: found.kind,
+            source_id: found.source_id,
+            content: found.content,
+            metadata: found.metadata,
+            score: found.score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RagChatCompletionOutputDTO {
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+    // citations lists the chunks put in the prompt, empty when nothing relevant was found
+    pub citations: Vec<CitationOutputDTO>,
+}
@@ -0,0 +1,459 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::embedding::{VectorKind, VectorMatch};
+use crate::internal::domain::entity::idempotency::fingerprint;
+use crate::internal::domain::entity::model::Model;
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::repository::vector_store::{VectorQuery, VectorStore};
+use crate::internal::domain::token_counter::TokenCounter;
+use crate::internal::usecase::chat_completion::dto::ChatCompletionInputDTO;
+use crate::internal::usecase::chat_completion::usecase::{
+    attachments_fingerprint, new_user_message, overrides_fingerprint, template_fingerprint,
+    ChatCompletionUseCase,
+};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::rag_chat_completion::dto::{
+    CitationOutputDTO, RagChatCompletionOutputDTO,
+};
+
+const CONTEXT_INSTRUCTION: &str = "Use the context below when it is relevant to the question \
+     and cite the passages you rely on by their number, e.g. [1]. Say so when the context does \
+     not hold the answer.\n\nContext:";
+
+// RagConfig sets how many chunks are retrieved for a message and how much of the prompt they
+// may take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RagConfig {
+    pub top_k: usize,
+    // min_score drops the chunks less similar to the message than it
+    pub min_score: f32,
+    // max_context_tokens caps the context, the room left in the chat's budget caps it too
+    pub max_context_tokens: usize,
+}
+
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            min_score: 0.3,
+            max_context_tokens: 2000,
+        }
+    }
+}
+
+pub struct RagChatCompletionUseCase {
+    completion: Arc<ChatCompletionUseCase>,
+    embeddings: Arc<dyn EmbeddingsGateway>,
+    store: Arc<dyn VectorStore>,
+    config: RagConfig,
+}
+
+impl RagChatCompletionUseCase {
+    // new replies through the completion use case so grounded replies are rate limited,
+    // moderated and tracked like any other
+    pub fn new(
+        completion: Arc<ChatCompletionUseCase>,
+        embeddings: Arc<dyn EmbeddingsGateway>,
+        store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            completion,
+            embeddings,
+            store,
+            config: RagConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: RagConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // execute answers the user message with the chunks of the user's documents closest to it
+    // in the prompt, and tells which of them the model was given
+    #[instrument(name = "rag_chat_completion", skip_all, fields(user_id = %input.user_id, chat_id))]
+    pub async fn execute(
+        &self,
+        input: ChatCompletionInputDTO,
+    ) -> Result<RagChatCompletionOutputDTO, UseCaseError> {
+        let chat_id = input.chat_id.map(|id| id.to_string()).unwrap_or_default();
+        let template = input
+            .template
+            .as_ref()
+            .map(template_fingerprint)
+            .unwrap_or_default();
+        let attachments = attachments_fingerprint(&input.attachments);
+        let overrides = overrides_fingerprint(&input.overrides);
+        let fingerprint = fingerprint(&[
+            "rag",
+            &chat_id,
+            &input.user_message,
+            &attachments,
+            &template,
+            &overrides,
+        ]);
+
+        self.completion
+            .idempotent(
+                input.tenant_id,
+                input.user_id,
+                input.idempotency_key.as_deref(),
+                &fingerprint,
+                self.answer(&input),
+            )
+            .await
+    }
+
+    // answer retrieves the context before the chat is loaded, so a failed retrieval does not
+    // leave an empty chat behind; the retrieval reads the message as admitted, masked
+    async fn answer(
+        &self,
+        input: &ChatCompletionInputDTO,
+    ) -> Result<RagChatCompletionOutputDTO, UseCaseError> {
+        let overrides = self.completion.overrides_for(input)?;
+        let user_message = new_user_message(
+            &self.completion.model_for(input.tenant_id),
+            &input.user_message,
+            input.attachments.clone(),
+        )?;
+        let user_message = self
+            .completion
+            .admit(input.tenant_id, input.user_id, input.chat_id, user_message)
+            .await?;
+
+        let matches = self.retrieve(input, &user_message.content).await?;
+
+        let loaded = self.completion.load_or_create_chat(input).await?;
+        let chat = &loaded.chat;
+        tracing::Span::current().record("chat_id", tracing::field::display(chat.id));
+
+        // the context has to fit the model that answers the turn
+        let model = overrides.model.as_ref().unwrap_or(&chat.config.model);
+        let room = chat
+            .config
+            .max_tokens
+            .min(model.max_tokens as usize)
+            .saturating_sub(chat.token_usage + user_message.tokens);
+        let (context, citations) =
+            build_context(model, matches, self.config.max_context_tokens.min(room));
+
+        let output = self
+            .completion
+            .reply_with_context(loaded, user_message, context.as_deref(), &overrides)
+            .await?;
+
+        Ok(RagChatCompletionOutputDTO {
+            chat_id: output.chat_id,
+            user_id: output.user_id,
+            content: output.content,
+            candidates: output.candidates,
+            citations,
+        })
+    }
+
+    // retrieve returns the document chunks of the user closest to the message
+    async fn retrieve(
+        &self,
+        input: &ChatCompletionInputDTO,
+        message: &str,
+    ) -> Result<Vec<VectorMatch>, UseCaseError> {
+        let embedding = self
+            .embeddings
+            .embed(&[message.to_string()])
+            .await?
+            .pop()
+            .ok_or(GatewayError::EmptyResponse)?;
+
+        let matches = self
+            .store
+            .search(&VectorQuery {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                model: self.embeddings.model().to_string(),
+                embedding,
+                kinds: vec![VectorKind::DocumentChunk],
+                limit: self.config.top_k,
+                min_score: self.config.min_score,
+            })
+            .await?;
+
+        Ok(matches)
+    }
+}
+
+// build_context numbers the matches that fit in the token budget, most similar first; a match
+// too long for what is left is skipped so a shorter one can still make it, and there is no
+// context when none fits
+fn build_context(
+    model: &Model,
+    matches: Vec<VectorMatch>,
+    budget: usize,
+) -> (Option<String>, Vec<CitationOutputDTO>) {
+    // models tiktoken does not know are estimated at four characters per token
+    let counter = TokenCounter::for_model(model);
+    let count = |text: &str| counter.map_or(text.len().div_ceil(4), |counter| counter.count(text));
+
+    let mut context = CONTEXT_INSTRUCTION.to_string();
+    let mut used = count(&context);
+    let mut citations = Vec::new();
+    for found in matches {
+        let index = citations.len() + 1;
+        let passage = format!("\n\n[{}] {}", index, found.content);
+        let tokens = count(&passage);
+        if used + tokens > budget {
+            continue;
+        }
+
+        used += tokens;
+        context.push_str(&passage);
+        citations.push(CitationOutputDTO::new(index, found));
+    }
+
+    if citations.is_empty() {
+        return (None, citations);
+    }
+
+    (Some(context), citations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, TrimmingPolicy};
+    use crate::internal::domain::entity::embedding::VectorRecord;
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::ChatCompletionGateway;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+    use crate::internal::usecase::chat_completion::dto::{
+        ChatCompletionConfigInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+    };
+
+    const TOPICS: [&str; 2] = ["rust", "bread"];
+
+    // SystemGateway answers with the system message so replies show the context they were given
+    struct SystemGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for SystemGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                &chat.initial_system_message().content,
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    // TopicEmbeddings embeds a text as the topics it mentions
+    struct TopicEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingsGateway for TopicEmbeddings {
+        fn model(&self) -> &str {
+            "topics"
+        }
+
+        async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+            Ok(inputs.iter().map(|input| topics(input)).collect())
+        }
+    }
+
+    fn topics(text: &str) -> Vec<f32> {
+        let text = text.to_lowercase();
+        TOPICS
+            .iter()
+            .map(|topic| if text.contains(topic) { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    fn chunk(user_id: Uuid, content: &str) -> VectorRecord {
+        VectorRecord {
+            id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            kind: VectorKind::DocumentChunk,
+            source_id: Uuid::new_v4(),
+            content: content.to_string(),
+            model: "topics".to_string(),
+            embedding: topics(content),
+            metadata: serde_json::json!({ "position": 0 }),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    async fn setup(
+        config: RagConfig,
+    ) -> (
+        RagChatCompletionUseCase,
+        Arc<InMemoryChatRepository>,
+        Arc<InMemoryVectorStore>,
+        Uuid,
+    ) {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let users = Arc::new(InMemoryUserRepository::new());
+        let user_id = Uuid::new_v4();
+        users
+            .create_user(&User::new(
+                user_id,
+                &user_id.to_string(),
+                "Ada",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(SystemGateway),
+            repository.clone(),
+            users,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+        let store = Arc::new(InMemoryVectorStore::new());
+        let usecase =
+            RagChatCompletionUseCase::new(completion, Arc::new(TopicEmbeddings), store.clone())
+                .with_config(config);
+
+        (usecase, repository, store, user_id)
+    }
+
+    fn input(user_id: Uuid, user_message: &str) -> ChatCompletionInputDTO {
+        ChatCompletionInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id: None,
+            user_message: user_message.to_string(),
+            attachments: vec![],
+            template: None,
+            assistant: None,
+            idempotency_key: None,
+            overrides: ChatOverridesInputDTO::default(),
+            labels: ChatLabelsInputDTO::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_cites_retrieved_chunks() {
+        let (usecase, repository, store, user_id) = setup(RagConfig::default()).await;
+        let rust = chunk(user_id, "Rust has no garbage collector.");
+        store
+            .upsert(&[
+                rust.clone(),
+                chunk(user_id, "Bread needs flour, water and salt."),
+                chunk(Uuid::new_v4(), "Rust was first released in 2015."),
+            ])
+            .await
+            .unwrap();
+
+        let output = usecase
+            .execute(input(user_id, "How does Rust free memory?"))
+            .await
+            .unwrap();
+
+        assert_eq!(output.citations.len(), 1);
+        assert_eq!(output.citations[0].index, 1);
+        assert_eq!(output.citations[0].id, rust.id);
+        assert_eq!(output.citations[0].source_id, rust.source_id);
+        assert!(output.content.starts_with("You are a helpful assistant."));
+        assert!(output
+            .content
+            .ends_with("[1] Rust has no garbage collector."));
+
+        // the context is only part of the prompt, the chat keeps its system message
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, output.chat_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            chat.initial_system_message().content,
+            "You are a helpful assistant."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_context() {
+        let (usecase, _, store, user_id) = setup(RagConfig {
+            max_context_tokens: 10,
+            ..RagConfig::default()
+        })
+        .await;
+        store
+            .upsert(&[chunk(user_id, "Rust has no garbage collector.")])
+            .await
+            .unwrap();
+
+        let output = usecase
+            .execute(input(user_id, "How does Rust free memory?"))
+            .await
+            .unwrap();
+        assert!(output.citations.is_empty());
+        assert_eq!(output.content, "You are a helpful assistant.");
+
+        let output = usecase.execute(input(user_id, "Any news?")).await.unwrap();
+        assert!(output.citations.is_empty());
+    }
+
+    #[test]
+    fn test_build_context_skips_what_does_not_fit() {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let counter = TokenCounter::for_model(&model).unwrap();
+        let found = |content: &str, score: f32| VectorMatch {
+            id: Uuid::new_v4(),
+            kind: VectorKind::DocumentChunk,
+            source_id: Uuid::new_v4(),
+            content: content.to_string(),
+            metadata: serde_json::json!({}),
+            score,
+        };
+        let long = "word ".repeat(200);
+        let budget = counter.count(CONTEXT_INSTRUCTION) + 50;
+
+        let (context, citations) = build_context(
+            &model,
+            vec![found(&long, 0.9), found("short passage", 0.8)],
+            budget,
+        );
+
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].index, 1);
+        assert_eq!(citations[0].content, "short passage");
+        assert!(context.unwrap().ends_with("\n\n[1] short passage"));
+    }
+}
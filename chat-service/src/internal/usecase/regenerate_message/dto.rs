@@ -0,0 +1,15 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegenerateMessageInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // message_id is the user message the chat is regenerated from
+    pub message_id: Uuid,
+    // user_message replaces the content of the message, the original content is sent again
+    // when omitted
+    pub user_message: Option<String>,
+    // idempotency_key makes retries of the request return the first response
+    pub idempotency_key: Option<String>,
+}
@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::idempotency::fingerprint;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::chat_completion::dto::ChatCompletionOutputDTO;
+use crate::internal::usecase::chat_completion::usecase::{
+    new_user_message, ChatCompletionUseCase, LoadedChat,
+};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::regenerate_message::dto::RegenerateMessageInputDTO;
+
+pub struct RegenerateMessageUseCase {
+    repository: Arc<dyn ChatRepository>,
+    completion: Arc<ChatCompletionUseCase>,
+}
+
+impl RegenerateMessageUseCase {
+    // new replies through the completion use case so regenerated turns are rate limited,
+    // moderated and tracked like any other
+    pub fn new(
+        repository: Arc<dyn ChatRepository>,
+        completion: Arc<ChatCompletionUseCase>,
+    ) -> Self {
+        Self {
+            repository,
+            completion,
+        }
+    }
+
+    // execute replaces a previous user message with a new revision and asks the model for a new
+    // reply from there; the messages that followed are erased but kept in the history
+    #[instrument(name = "regenerate_message", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id, message_id = %input.message_id))]
+    pub async fn execute(
+        &self,
+        input: RegenerateMessageInputDTO,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let fingerprint = fingerprint(&[
+            "regenerate",
+            &input.chat_id.to_string(),
+            &input.message_id.to_string(),
+            input.user_message.as_deref().unwrap_or_default(),
+        ]);
+
+        self.completion
+            .idempotent(
+                input.tenant_id,
+                input.user_id,
+                input.idempotency_key.as_deref(),
+                &fingerprint,
+                self.regenerate(&input),
+            )
+            .await
+    }
+
+    // regenerate rewinds the chat to the message and replies to its new revision
+    async fn regenerate(
+        &self,
+        input: &RegenerateMessageInputDTO,
+    ) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+        // a cold chat is reopened through an update first, which brings its history back
+        if chat.is_cold() {
+            return Err(ChatError::ChatArchived.into());
+        }
+
+        let original = chat
+            .messages
+            .iter()
+            .find(|message| message.id == input.message_id)
+            .ok_or(UseCaseError::MessageNotFound(input.message_id))?;
+        let content = input
+            .user_message
+            .clone()
+            .unwrap_or_else(|| original.content.clone());
+
+        let revision =
+            new_user_message(&chat.config.model, &content, original.attachments.clone())?
+                .with_revision_of(original.id);
+        let revision = self
+            .completion
+            .admit(chat.tenant_id, chat.user_id, Some(chat.id), revision)
+            .await?;
+        chat.rewind_to(input.message_id)?;
+
+        self.completion
+            .reply(LoadedChat::stored(chat), revision)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, TrimmingPolicy};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::usecase::chat_completion::dto::{
+        ChatCompletionConfigInputDTO, ChatCompletionInputDTO, ChatLabelsInputDTO,
+        ChatOverridesInputDTO,
+    };
+
+    // EchoGateway answers with the last user message so replies show what they were built from
+    struct EchoGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for EchoGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let last = chat.messages.last().map_or("", |message| message.content());
+
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                &format!("you said: {}", last),
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    async fn setup() -> (RegenerateMessageUseCase, Arc<InMemoryChatRepository>, Chat) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let users = Arc::new(InMemoryUserRepository::new());
+        let user_id = Uuid::new_v4();
+        users
+            .create_user(&User::new(
+                user_id,
+                &user_id.to_string(),
+                "Ada",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(EchoGateway),
+            repository.clone(),
+            users,
+            model,
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+
+        let mut chat_id = None;
+        for user_message in ["Hello!", "Tell me a joke"] {
+            let output = completion
+                .execute(ChatCompletionInputDTO {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id,
+                    chat_id,
+                    user_message: user_message.to_string(),
+                    attachments: vec![],
+                    template: None,
+                    assistant: None,
+                    idempotency_key: None,
+                    overrides: ChatOverridesInputDTO::default(),
+                    labels: ChatLabelsInputDTO::default(),
+                })
+                .await
+                .unwrap();
+            chat_id = Some(output.chat_id);
+        }
+        let chat = repository
+            .find_chat_by_id(DEFAULT_TENANT_ID, chat_id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+
+        (
+            RegenerateMessageUseCase::new(repository.clone(), completion),
+            repository,
+            chat,
+        )
+    }
+
+    fn input(chat: &Chat, message_id: Uuid) -> RegenerateMessageInputDTO {
+        RegenerateMessageInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            message_id,
+            user_message: None,
+            idempotency_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_edits_message() {
+        let (usecase, repository, chat) = setup().await;
+        let first = chat.messages[0].clone();
+
+        let output = usecase
+            .execute(RegenerateMessageInputDTO {
+                user_message: Some("Hi there!".to_string()),
+                ..input(&chat, first.id)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.content, "you said: Hi there!");
+        let saved = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.messages.len(), 2);
+        assert_eq!(saved.messages[0].content, "Hi there!");
+        assert_eq!(saved.messages[0].revision_of, Some(first.id));
+        assert_eq!(saved.erased_messages, chat.messages);
+    }
+
+    #[tokio::test]
+    async fn test_execute_regenerates_reply() {
+        let (usecase, repository, chat) = setup().await;
+        let last = chat.messages[2].clone();
+
+        let output = usecase.execute(input(&chat, last.id)).await.unwrap();
+
+        assert_eq!(output.content, "you said: Tell me a joke");
+        let saved = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.messages.len(), 4);
+        assert_eq!(saved.messages[..2], chat.messages[..2]);
+        assert_eq!(saved.messages[2].revision_of, Some(last.id));
+        assert_eq!(saved.erased_messages, chat.messages[2..]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_messages() {
+        let (usecase, _, chat) = setup().await;
+        let missing = Uuid::new_v4();
+
+        assert!(matches!(
+            usecase.execute(input(&chat, missing)).await,
+            Err(UseCaseError::MessageNotFound(id)) if id == missing
+        ));
+        assert!(matches!(
+            usecase.execute(input(&chat, chat.messages[1].id)).await,
+            Err(UseCaseError::Domain(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(RegenerateMessageInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input(&chat, chat.messages[0].id)
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_cold_chats() {
+        let (usecase, repository, chat) = setup().await;
+        let mut cold = chat.clone();
+        cold.archive_history("cold.jsonl".to_string()).unwrap();
+        repository.archive_chat_history(&mut cold).await.unwrap();
+
+        assert!(matches!(
+            usecase.execute(input(&chat, chat.messages[0].id)).await,
+            Err(UseCaseError::Domain(ChatError::ChatArchived))
+        ));
+    }
+}
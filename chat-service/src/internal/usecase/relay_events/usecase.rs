@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::gateway::event_publisher::EventPublisher;
+use crate::internal::domain::repository::outbox::OutboxRepository;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct RelayEventsUseCase {
+    outbox: Arc<dyn OutboxRepository>,
+    publisher: Arc<dyn EventPublisher>,
+    batch_size: usize,
+}
+
+impl RelayEventsUseCase {
+    // new publishes the outbox in batches of batch_size events
+    pub fn new(
+        outbox: Arc<dyn OutboxRepository>,
+        publisher: Arc<dyn EventPublisher>,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            outbox,
+            publisher,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    // execute publishes the unpublished events oldest first until the outbox is drained and
+    // returns how many were published; a batch is marked published only once the sink took it,
+    // so a failure sends it again on the next run
+    #[instrument(name = "relay_events", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let mut published = 0;
+        loop {
+            let events = self.outbox.unpublished_events(self.batch_size).await?;
+            if events.is_empty() {
+                return Ok(published);
+            }
+
+            self.publisher.publish(&events).await?;
+            let ids: Vec<_> = events.iter().map(|event| event.id).collect();
+            self.outbox.mark_published(&ids, chrono::Utc::now()).await?;
+            published += events.len();
+
+            if events.len() < self.batch_size {
+                return Ok(published);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::gateway::event_publisher::PublishError;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    #[derive(Default)]
+    struct RecordingPublisher {
+        published: Mutex<Vec<OutboxEvent>>,
+        failing: bool,
+    }
+
+    #[async_trait]
+    impl EventPublisher for RecordingPublisher {
+        async fn publish(&self, events: &[OutboxEvent]) -> Result<(), PublishError> {
+            if self.failing {
+                return Err(PublishError("sink is down".to_string()));
+            }
+
+            self.published.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    async fn repository_with_events() -> Arc<InMemoryChatRepository> {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let system = Message::new(
+            Uuid::new_v4(),
+            Role::System,
+            "You are a helpful assistant.",
+            0,
+            model.clone(),
+            chrono::Utc::now(),
+        );
+        let mut chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            system,
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        chat.record(ChatEvent::ChatCreated {
+            model: model.name.clone(),
+        });
+        for content in ["Hello!", "Tell me a joke"] {
+            chat.add_message(Message::new(
+                Uuid::new_v4(),
+                Role::User,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            ))
+            .unwrap();
+        }
+        chat.end().unwrap();
+
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_execute_publishes_in_batches() {
+        let repository = repository_with_events().await;
+        let publisher = Arc::new(RecordingPublisher::default());
+        let usecase = RelayEventsUseCase::new(repository.clone(), publisher.clone(), 3);
+
+        assert_eq!(usecase.execute().await.unwrap(), 4);
+        assert_eq!(usecase.execute().await.unwrap(), 0);
+
+        let published = publisher.published.lock().unwrap();
+        assert_eq!(published.len(), 4);
+        assert!(matches!(published[0].event, ChatEvent::ChatCreated { .. }));
+        assert_eq!(published[3].event, ChatEvent::ChatEnded);
+    }
+
+    #[tokio::test]
+    async fn test_execute_keeps_events_when_publishing_fails() {
+        let repository = repository_with_events().await;
+        let publisher = Arc::new(RecordingPublisher {
+            failing: true,
+            ..Default::default()
+        });
+        let usecase = RelayEventsUseCase::new(repository.clone(), publisher, 10);
+
+        assert!(matches!(
+            usecase.execute().await,
+            Err(UseCaseError::Publish(_))
+        ));
+        assert_eq!(repository.unpublished_events(10).await.unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+use crate::internal::usecase::create_api_key::dto::ApiKeyOutputDTO;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RotatedApiKeyOutputDTO {
+    pub api_key: ApiKeyOutputDTO,
+    // revoked is how many keys of the user stopped working
+    pub revoked: usize,
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::repository::api_key::ApiKeyRepository;
+use crate::internal::usecase::create_api_key::usecase::CreateApiKeyUseCase;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::rotate_api_key::dto::RotatedApiKeyOutputDTO;
+
+pub struct RotateApiKeyUseCase {
+    api_keys: Arc<dyn ApiKeyRepository>,
+    create: Arc<CreateApiKeyUseCase>,
+}
+
+impl RotateApiKeyUseCase {
+    pub fn new(api_keys: Arc<dyn ApiKeyRepository>, create: Arc<CreateApiKeyUseCase>) -> Self {
+        Self { api_keys, create }
+    }
+
+    // execute issues a new key for the user and revokes the others; the new key is created
+    // first so a failed revocation never leaves the user without a working key
+    #[instrument(name = "rotate_api_key", skip_all, fields(tenant_id = %tenant_id, user_id = %user_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<RotatedApiKeyOutputDTO, UseCaseError> {
+        let api_key = self.create.execute(tenant_id, user_id).await?;
+        let revoked = self
+            .api_keys
+            .revoke_api_keys(tenant_id, user_id, api_key.id, chrono::Utc::now())
+            .await?;
+
+        Ok(RotatedApiKeyOutputDTO { api_key, revoked })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::api_key::hash_key;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::api_key::InMemoryApiKeyRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let api_keys = Arc::new(InMemoryApiKeyRepository::new());
+        let user = User::new(Uuid::new_v4(), "auth0|42", "Ada", chrono::Utc::now());
+        users.create_user(&user).await.unwrap();
+        let create = Arc::new(CreateApiKeyUseCase::new(api_keys.clone(), users));
+        let old = create.execute(user.tenant_id, user.id).await.unwrap();
+        let usecase = RotateApiKeyUseCase::new(api_keys.clone(), create);
+
+        let output = usecase.execute(user.tenant_id, user.id).await.unwrap();
+
+        assert_eq!(output.revoked, 1);
+        let old = api_keys
+            .find_api_key_by_hash(&hash_key(&old.key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(old.revoked_at.is_some());
+        let new = api_keys
+            .find_api_key_by_hash(&hash_key(&output.api_key.key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(new.revoked_at.is_none());
+
+        let missing = Uuid::new_v4();
+        assert!(matches!(
+            usecase.execute(user.tenant_id, missing).await,
+            Err(UseCaseError::UserNotFound(id)) if id == missing
+        ));
+    }
+}
@@ -0,0 +1,409 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::DurationRound;
+use futures_util::future::join_all;
+use tracing::{instrument, warn};
+
+use crate::internal::domain::deadline::with_deadline;
+use crate::internal::domain::entity::job::{Job, JobStatus};
+use crate::internal::domain::entity::webhook::{WebhookDelivery, WebhookEvent, WebhookPayload};
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::domain::repository::webhook::WebhookRepository;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatCompletionOutputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::error::UseCaseError;
+
+// JOB_CLAIM is how long a claimed job is left to its worker, one that dies mid answer gets it
+// answered by another worker once the claim is over
+const JOB_CLAIM: Duration = Duration::from_secs(300);
+
+// JOB_DEADLINE is how long a job is answered for, a minute under JOB_CLAIM so that its outcome
+// is saved before another worker can claim it
+const JOB_DEADLINE: Duration = Duration::from_secs(240);
+
+pub struct RunJobsUseCase {
+    jobs: Arc<dyn JobRepository>,
+    chat_completion: Arc<ChatCompletionUseCase>,
+    webhooks: Option<Arc<dyn WebhookRepository>>,
+    batch_size: usize,
+}
+
+impl RunJobsUseCase {
+    // new answers up to batch_size queued jobs per run, side by side, through the chat
+    // completion, so they are moderated, limited and billed like any other message
+    pub fn new(
+        jobs: Arc<dyn JobRepository>,
+        chat_completion: Arc<ChatCompletionUseCase>,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            jobs,
+            chat_completion,
+            webhooks: None,
+            batch_size,
+        }
+    }
+
+    // with_webhooks calls the tenant's webhooks subscribed to job.completed once a job is done
+    pub fn with_webhooks(mut self, webhooks: Arc<dyn WebhookRepository>) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    // execute answers the queued jobs, oldest first, and returns how many succeeded; a job is
+    // claimed for JOB_CLAIM before it runs so that it runs once when several instances run the
+    // worker, and is claimed again once that is over when its worker stopped; a job that cannot
+    // be answered within JOB_DEADLINE fails with the reason instead of being retried
+    #[instrument(name = "run_jobs", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let now = chrono::Utc::now();
+        let claimable = self.jobs.list_claimable(now, self.batch_size).await?;
+        // the claim is kept to the microsecond, as the databases store it, completing the job
+        // compares it with the stored one
+        let claimed_until = (now + chrono::Duration::from_std(JOB_CLAIM).unwrap_or_default())
+            .duration_trunc(chrono::Duration::microseconds(1))
+            .unwrap_or(now);
+
+        let mut claimed = Vec::new();
+        for job in claimable {
+            match self
+                .jobs
+                .claim_job(job.tenant_id, job.id, now, claimed_until)
+                .await
+            {
+                Ok(true) => claimed.push(job),
+                Ok(false) => {}
+                Err(err) => warn!(job_id = %job.id, error = %err, "could not claim job"),
+            }
+        }
+
+        let ran = join_all(
+            claimed
+                .into_iter()
+                .map(|job| self.run(job, claimed_until, JOB_DEADLINE)),
+        )
+        .await;
+
+        Ok(ran
+            .iter()
+            .filter(|job| matches!(job, Some(job) if job.status == JobStatus::Succeeded))
+            .count())
+    }
+
+    // run answers the job within the deadline and saves the outcome under its claim; a job
+    // whose outcome cannot be saved is logged and stays running, to be claimed again once its
+    // claim is over, and one whose claim was lost is left to the worker that claimed it next
+    async fn run(
+        &self,
+        mut job: Job,
+        claimed_until: chrono::DateTime<chrono::Utc>,
+        deadline: Duration,
+    ) -> Option<Job> {
+        let answered = with_deadline(deadline, self.answer(&job))
+            .await
+            .map_err(UseCaseError::from)
+            .and_then(|answered| answered);
+        match answered {
+            Ok(output) => job.succeed(output.chat_id, output.content),
+            Err(err) => {
+                warn!(job_id = %job.id, error = %err, "job failed");
+                job.fail(err.to_string());
+            }
+        }
+        match self.jobs.complete_job(&job, claimed_until).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(job_id = %job.id, "job claim was lost before it completed");
+                return None;
+            }
+            Err(err) => {
+                warn!(job_id = %job.id, error = %err, "could not complete job");
+                return None;
+            }
+        }
+        self.notify(&job).await;
+
+        Some(job)
+    }
+
+    async fn answer(&self, job: &Job) -> Result<ChatCompletionOutputDTO, UseCaseError> {
+        let overrides: ChatOverridesInputDTO = serde_json::from_value(job.overrides.clone())
+            .map_err(|e| UseCaseError::InvalidInput(e.to_string()))?;
+
+        self.chat_completion
+            .execute(ChatCompletionInputDTO {
+                tenant_id: job.tenant_id,
+                user_id: job.user_id,
+                chat_id: job.chat_id,
+                user_message: job.user_message.clone(),
+                attachments: job.attachments.clone(),
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides,
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+    }
+
+    // notify queues the calls of the webhooks subscribed to job.completed; the job is done
+    // either way, a webhook that cannot be queued only misses it
+    async fn notify(&self, job: &Job) {
+        let Some(webhooks) = &self.webhooks else {
+            return;
+        };
+        let subscribed = match webhooks.list_webhooks(job.tenant_id).await {
+            Ok(subscribed) => subscribed,
+            Err(err) => {
+                warn!(job_id = %job.id, error = %err, "could not list webhooks for job");
+                return;
+            }
+        };
+
+        let payload = WebhookPayload::job(job);
+        let deliveries = subscribed
+            .iter()
+            .filter(|webhook| webhook.subscribes_to(WebhookEvent::JobCompleted))
+            .filter_map(|webhook| WebhookDelivery::new(webhook, &payload).ok())
+            .collect::<Vec<_>>();
+        if deliveries.is_empty() {
+            return;
+        }
+        if let Err(err) = webhooks.enqueue_deliveries(&deliveries).await {
+            warn!(job_id = %job.id, error = %err, "could not queue job webhooks");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::TrimmingPolicy;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::entity::webhook::Webhook;
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::job::InMemoryJobRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::infra::repository::memory::webhook::InMemoryWebhookRepository;
+    use crate::internal::testing::builder::test_model;
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    async fn chat_completion(gateway: FakeCompletionGateway) -> (Arc<ChatCompletionUseCase>, Uuid) {
+        let user_id = Uuid::new_v4();
+        let users = InMemoryUserRepository::new();
+        users
+            .create_user(&User::new(user_id, "ada", "Ada", chrono::Utc::now()))
+            .await
+            .unwrap();
+        let chat_completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(gateway),
+            Arc::new(InMemoryChatRepository::new()),
+            Arc::new(users),
+            test_model(),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+
+        (chat_completion, user_id)
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_queued_jobs() {
+        let (chat_completion, user_id) =
+            chat_completion(FakeCompletionGateway::new().reply("Here is the summary.")).await;
+        let webhooks = Arc::new(InMemoryWebhookRepository::new());
+        let webhook = Webhook::new(
+            DEFAULT_TENANT_ID,
+            "https://example.com/hooks",
+            None,
+            &[WebhookEvent::JobCompleted],
+        )
+        .unwrap();
+        webhooks.create_webhook(&webhook).await.unwrap();
+
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let new_chat = Job::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        let missing_chat = Job::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            Some(Uuid::new_v4()),
+            "And the appendix?",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        jobs.create_job(&new_chat).await.unwrap();
+        jobs.create_job(&missing_chat).await.unwrap();
+
+        let usecase =
+            RunJobsUseCase::new(jobs.clone(), chat_completion, 10).with_webhooks(webhooks.clone());
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+        assert_eq!(usecase.execute().await.unwrap(), 0);
+
+        let succeeded = jobs
+            .find_job_by_id(DEFAULT_TENANT_ID, new_chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(succeeded.status, JobStatus::Succeeded);
+        assert_eq!(succeeded.content.as_deref(), Some("Here is the summary."));
+        assert!(succeeded.chat_id.is_some());
+        let failed = jobs
+            .find_job_by_id(DEFAULT_TENANT_ID, missing_chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert!(failed.error.unwrap().contains("not found"));
+
+        let deliveries = webhooks
+            .due_deliveries(chrono::Utc::now(), 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries
+            .iter()
+            .all(|delivery| delivery.event == WebhookEvent::JobCompleted));
+    }
+
+    #[tokio::test]
+    async fn test_execute_claims_abandoned_jobs_again() {
+        let (chat_completion, user_id) =
+            chat_completion(FakeCompletionGateway::new().reply("Here is the summary.")).await;
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let job = |message| {
+            Job::new(
+                DEFAULT_TENANT_ID,
+                user_id,
+                None,
+                message,
+                vec![],
+                serde_json::json!({}),
+            )
+            .unwrap()
+        };
+        let now = chrono::Utc::now();
+        let abandoned = Job {
+            status: JobStatus::Running,
+            claimed_until: Some(now - chrono::Duration::seconds(1)),
+            ..job("Summarize the report")
+        };
+        let running = Job {
+            status: JobStatus::Running,
+            claimed_until: Some(now + chrono::Duration::seconds(60)),
+            ..job("And the appendix?")
+        };
+        jobs.create_job(&abandoned).await.unwrap();
+        jobs.create_job(&running).await.unwrap();
+
+        let usecase = RunJobsUseCase::new(jobs.clone(), chat_completion, 10);
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+
+        let answered = jobs
+            .find_job_by_id(DEFAULT_TENANT_ID, abandoned.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(answered.status, JobStatus::Succeeded);
+        assert_eq!(answered.claimed_until, None);
+        let untouched = jobs
+            .find_job_by_id(DEFAULT_TENANT_ID, running.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(untouched, running);
+    }
+
+    #[tokio::test]
+    async fn test_run_fails_jobs_over_the_deadline() {
+        let (chat_completion, user_id) = chat_completion(FakeCompletionGateway::new().hang()).await;
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let job = Job::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        jobs.create_job(&job).await.unwrap();
+        let now = chrono::Utc::now();
+        let claimed_until = now + chrono::Duration::seconds(60);
+        jobs.claim_job(DEFAULT_TENANT_ID, job.id, now, claimed_until)
+            .await
+            .unwrap();
+
+        let usecase = RunJobsUseCase::new(jobs.clone(), chat_completion, 10);
+        let failed = usecase
+            .run(job, claimed_until, Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert!(failed.error.unwrap().contains("deadline"));
+    }
+
+    #[tokio::test]
+    async fn test_run_leaves_jobs_claimed_again() {
+        let (chat_completion, user_id) =
+            chat_completion(FakeCompletionGateway::new().reply("Here is the summary.")).await;
+        let jobs = Arc::new(InMemoryJobRepository::new());
+        let job = Job::new(
+            DEFAULT_TENANT_ID,
+            user_id,
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({}),
+        )
+        .unwrap();
+        jobs.create_job(&job).await.unwrap();
+        // the claim was over and another worker claimed the job before this one completed it
+        let now = chrono::Utc::now();
+        let lost = now - chrono::Duration::seconds(1);
+        let claimed_again = now + chrono::Duration::seconds(60);
+        jobs.claim_job(DEFAULT_TENANT_ID, job.id, now, claimed_again)
+            .await
+            .unwrap();
+
+        let usecase = RunJobsUseCase::new(jobs.clone(), chat_completion, 10);
+        assert!(usecase.run(job.clone(), lost, JOB_DEADLINE).await.is_none());
+
+        let running = jobs
+            .find_job_by_id(DEFAULT_TENANT_ID, job.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(running.status, JobStatus::Running);
+        assert_eq!(running.claimed_until, Some(claimed_again));
+        assert_eq!(running.content, None);
+    }
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+
+// ScheduleMessageInputDTO sends content into the chat at send_at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleMessageInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    pub content: String,
+    pub send_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMessageOutputDTO {
+    pub id: Uuid,
+    pub chat_id: Uuid,
+    pub content: String,
+    pub send_at: chrono::DateTime<chrono::Utc>,
+    pub status: ScheduledStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&ScheduledMessage> for ScheduledMessageOutputDTO {
+    fn from(scheduled: &ScheduledMessage) -> Self {
+        Self {
+            id: scheduled.id,
+            chat_id: scheduled.chat_id,
+            content: scheduled.content.clone(),
+            send_at: scheduled.send_at,
+            status: scheduled.status,
+            error: scheduled.error.clone(),
+            created_at: scheduled.created_at,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::scheduled_message::ScheduledMessage;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::schedule_message::dto::{
+    ScheduleMessageInputDTO, ScheduledMessageOutputDTO,
+};
+
+pub struct ScheduleMessageUseCase {
+    chats: Arc<dyn ChatRepository>,
+    scheduled: Arc<dyn ScheduledMessageRepository>,
+}
+
+impl ScheduleMessageUseCase {
+    pub fn new(
+        chats: Arc<dyn ChatRepository>,
+        scheduled: Arc<dyn ScheduledMessageRepository>,
+    ) -> Self {
+        Self { chats, scheduled }
+    }
+
+    // execute stores the message until send_at, when the schedule job sends it into the chat;
+    // only the owner of the chat can schedule messages into it
+    #[instrument(name = "schedule_message", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: ScheduleMessageInputDTO,
+    ) -> Result<ScheduledMessageOutputDTO, UseCaseError> {
+        let chat = self
+            .chats
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+
+        let scheduled = ScheduledMessage::new(
+            input.tenant_id,
+            input.user_id,
+            input.chat_id,
+            &input.content,
+            input.send_at,
+        )
+        .map_err(|e| UseCaseError::InvalidInput(e.to_string()))?;
+        self.scheduled.create_scheduled_message(&scheduled).await?;
+
+        Ok(ScheduledMessageOutputDTO::from(&scheduled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::scheduled_message::ScheduledStatus;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::scheduled_message::InMemoryScheduledMessageRepository;
+    use crate::internal::testing::builder::ChatBuilder;
+    use crate::internal::testing::InMemoryChatRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let scheduled = Arc::new(InMemoryScheduledMessageRepository::new());
+        let chat = ChatBuilder::new().build();
+        chats.create_chat(&chat).await.unwrap();
+        let usecase = ScheduleMessageUseCase::new(chats, scheduled.clone());
+        let input = ScheduleMessageInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: chat.user_id,
+            chat_id: chat.id,
+            content: "Remind me to stretch".to_string(),
+            send_at: chrono::Utc::now() + chrono::Duration::hours(1),
+        };
+
+        let output = usecase.execute(input.clone()).await.unwrap();
+        assert_eq!(output.status, ScheduledStatus::Pending);
+        assert_eq!(
+            scheduled
+                .list_pending_by_user(DEFAULT_TENANT_ID, chat.user_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(matches!(
+            usecase
+                .execute(ScheduleMessageInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input.clone()
+                })
+                .await,
+            Err(UseCaseError::Forbidden(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(ScheduleMessageInputDTO {
+                    send_at: chrono::Utc::now() - chrono::Duration::hours(1),
+                    ..input
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+}
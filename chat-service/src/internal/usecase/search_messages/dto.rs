@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMessagesInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub query: String,
+    // limit defaults to 10 messages
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageMatchOutputDTO {
+    pub chat_id: Uuid,
+    pub message_id: Uuid,
+    pub role: String,
+    // snippet is the opening of the message, cut at a word
+    pub snippet: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageSearchOutputDTO {
+    // results are the most similar messages first
+    pub results: Vec<MessageMatchOutputDTO>,
+}
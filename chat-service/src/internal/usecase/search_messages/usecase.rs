@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::embedding::{VectorKind, VectorMatch};
+use crate::internal::domain::gateway::chat_completion::GatewayError;
+use crate::internal::domain::gateway::embeddings::EmbeddingsGateway;
+use crate::internal::domain::repository::vector_store::{VectorQuery, VectorStore};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::search_messages::dto::{
+    MessageMatchOutputDTO, MessageSearchOutputDTO, SearchMessagesInputDTO,
+};
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 50;
+// SNIPPET_LENGTH bounds the characters of a message returned with a match
+pub const SNIPPET_LENGTH: usize = 200;
+
+pub struct SearchMessagesUseCase {
+    embeddings: Arc<dyn EmbeddingsGateway>,
+    store: Arc<dyn VectorStore>,
+    min_score: f32,
+}
+
+impl SearchMessagesUseCase {
+    pub fn new(embeddings: Arc<dyn EmbeddingsGateway>, store: Arc<dyn VectorStore>) -> Self {
+        Self {
+            embeddings,
+            store,
+            min_score: 0.3,
+        }
+    }
+
+    // with_min_score drops the messages less similar to the query than it
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    // execute returns the past messages of the user closest in meaning to the query, across
+    // all of the user's chats
+    #[instrument(name = "search_messages", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: SearchMessagesInputDTO,
+    ) -> Result<MessageSearchOutputDTO, UseCaseError> {
+        let query = input.query.trim();
+        if query.is_empty() {
+            return Err(UseCaseError::InvalidInput("query is empty".to_string()));
+        }
+
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(UseCaseError::InvalidInput(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+
+        let embedding = self
+            .embeddings
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .ok_or(GatewayError::EmptyResponse)?;
+
+        let matches = self
+            .store
+            .search(&VectorQuery {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                model: self.embeddings.model().to_string(),
+                embedding,
+                kinds: vec![VectorKind::Message],
+                limit,
+                min_score: self.min_score,
+            })
+            .await?;
+
+        Ok(MessageSearchOutputDTO {
+            results: matches.into_iter().map(to_output).collect(),
+        })
+    }
+}
+
+fn to_output(found: VectorMatch) -> MessageMatchOutputDTO {
+    MessageMatchOutputDTO {
+        chat_id: found.source_id,
+        message_id: found.id,
+        role: found.metadata["role"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        snippet: snippet(&found.content),
+        score: found.score,
+    }
+}
+
+// snippet shortens the content to SNIPPET_LENGTH characters at the last word that fits
+fn snippet(content: &str) -> String {
+    let content = content.trim();
+    if content.chars().count() <= SNIPPET_LENGTH {
+        return content.to_string();
+    }
+
+    let cut: String = content.chars().take(SNIPPET_LENGTH).collect();
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(end) => &cut[..end],
+        None => &cut,
+    };
+
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::embedding::VectorRecord;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::vector_store::InMemoryVectorStore;
+
+    // TopicEmbeddings embeds a text as whether it talks about rust and about bread
+    struct TopicEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingsGateway for TopicEmbeddings {
+        fn model(&self) -> &str {
+            "topics"
+        }
+
+        async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, GatewayError> {
+            Ok(inputs.iter().map(|input| topics(input)).collect())
+        }
+    }
+
+    fn topics(text: &str) -> Vec<f32> {
+        let text = text.to_lowercase();
+        ["rust", "bread"]
+            .iter()
+            .map(|topic| if text.contains(topic) { 1.0 } else { 0.0 })
+            .collect()
+    }
+
+    fn record(user_id: Uuid, kind: VectorKind, role: &str, content: &str) -> VectorRecord {
+        VectorRecord {
+            id: Uuid::new_v4(),
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            kind,
+            source_id: Uuid::new_v4(),
+            content: content.to_string(),
+            model: "topics".to_string(),
+            embedding: topics(content),
+            metadata: serde_json::json!({ "role": role }),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let user_id = Uuid::new_v4();
+        let question = record(user_id, VectorKind::Message, "user", "Is Rust fast?");
+        store
+            .upsert(&[
+                question.clone(),
+                record(user_id, VectorKind::Message, "assistant", "Bake the bread."),
+                record(
+                    user_id,
+                    VectorKind::DocumentChunk,
+                    "",
+                    "Rust book, chapter 1",
+                ),
+                record(Uuid::new_v4(), VectorKind::Message, "user", "I like Rust"),
+            ])
+            .await
+            .unwrap();
+        let usecase = SearchMessagesUseCase::new(Arc::new(TopicEmbeddings), store);
+
+        let output = usecase
+            .execute(SearchMessagesInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                query: "rust performance".to_string(),
+                limit: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output.results,
+            vec![MessageMatchOutputDTO {
+                chat_id: question.source_id,
+                message_id: question.id,
+                role: "user".to_string(),
+                snippet: "Is Rust fast?".to_string(),
+                score: 1.0,
+            }]
+        );
+
+        for (query, limit) in [
+            (" ", None),
+            ("rust", Some(0)),
+            ("rust", Some(MAX_LIMIT + 1)),
+        ] {
+            assert!(matches!(
+                usecase
+                    .execute(SearchMessagesInputDTO {
+                        tenant_id: DEFAULT_TENANT_ID,
+                        user_id,
+                        query: query.to_string(),
+                        limit,
+                    })
+                    .await,
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("  short  "), "short");
+
+        let long = format!("{} end", "word ".repeat(50));
+        let cut = snippet(&long);
+        assert!(cut.ends_with("word…"));
+        assert!(cut.chars().count() <= SNIPPET_LENGTH + 1);
+    }
+}
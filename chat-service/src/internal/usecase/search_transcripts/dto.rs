@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTranscriptsInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub query: String,
+    // limit defaults to 20 messages, spread over the chats they belong to
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptMessageOutputDTO {
+    pub message_id: Uuid,
+    pub role: String,
+    // highlight is the part of the message around the matched words, which are wrapped in
+    // <mark></mark>; the message text itself is HTML escaped
+    pub highlight: String,
+    pub rank: f32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptChatOutputDTO {
+    pub chat_id: Uuid,
+    pub title: Option<String>,
+    // messages are the matching messages of the chat, best match first
+    pub messages: Vec<TranscriptMessageOutputDTO>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSearchOutputDTO {
+    // chats are ordered by their best matching message
+    pub chats: Vec<TranscriptChatOutputDTO>,
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::repository::chat::{ChatRepository, TranscriptMatch};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::search_transcripts::dto::{
+    SearchTranscriptsInputDTO, TranscriptChatOutputDTO, TranscriptMessageOutputDTO,
+    TranscriptSearchOutputDTO,
+};
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+// MAX_QUERY_LENGTH bounds the characters of a query
+pub const MAX_QUERY_LENGTH: usize = 200;
+
+pub struct SearchTranscriptsUseCase {
+    repository: Arc<dyn ChatRepository>,
+}
+
+impl SearchTranscriptsUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute returns the messages of the user's chats containing the words of the query,
+    // grouped by chat; unlike SearchMessagesUseCase it needs no embeddings
+    #[instrument(name = "search_transcripts", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: SearchTranscriptsInputDTO,
+    ) -> Result<TranscriptSearchOutputDTO, UseCaseError> {
+        let query = input.query.trim();
+        if query.is_empty() {
+            return Err(UseCaseError::InvalidInput("query is empty".to_string()));
+        }
+        if query.chars().count() > MAX_QUERY_LENGTH {
+            return Err(UseCaseError::InvalidInput(format!(
+                "query is longer than {} characters",
+                MAX_QUERY_LENGTH
+            )));
+        }
+
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(UseCaseError::InvalidInput(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+
+        let matches = self
+            .repository
+            .search_transcripts(input.tenant_id, input.user_id, query, limit)
+            .await?;
+
+        Ok(TranscriptSearchOutputDTO {
+            chats: group_by_chat(matches),
+        })
+    }
+}
+
+// group_by_chat keeps the order of the matches, a chat comes where its best match does
+fn group_by_chat(matches: Vec<TranscriptMatch>) -> Vec<TranscriptChatOutputDTO> {
+    let mut chats: Vec<TranscriptChatOutputDTO> = vec![];
+    for found in matches {
+        let message = TranscriptMessageOutputDTO {
+            message_id: found.message_id,
+            role: found.role.to_string(),
+            highlight: found.highlight,
+            rank: found.rank,
+            created_at: found.created_at,
+        };
+        match chats.iter_mut().find(|chat| chat.chat_id == found.chat_id) {
+            Some(chat) => chat.messages.push(message),
+            None => chats.push(TranscriptChatOutputDTO {
+                chat_id: found.chat_id,
+                title: found.chat_title,
+                messages: vec![message],
+            }),
+        }
+    }
+
+    chats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    fn chat(user_id: Uuid, exchange: &[(Role, &str)]) -> Chat {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+
+        Chat::new(
+            Uuid::new_v4(),
+            user_id,
+            message(Role::System, "You know about rust and bread."),
+            exchange
+                .iter()
+                .map(|(role, content)| message(*role, content))
+                .collect(),
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let repository = Arc::new(InMemoryChatRepository::new());
+        let user_id = Uuid::new_v4();
+        let rust = chat(
+            user_id,
+            &[
+                (Role::User, "Why does the borrow checker reject this?"),
+                (
+                    Role::Assistant,
+                    "The borrow checker sees two mutable borrows.",
+                ),
+            ],
+        );
+        let bread = chat(user_id, &[(Role::User, "How long should bread rise?")]);
+        let other = chat(Uuid::new_v4(), &[(Role::User, "Borrow checker again")]);
+        for chat in [&rust, &bread, &other] {
+            repository.create_chat(chat).await.unwrap();
+        }
+        let usecase = SearchTranscriptsUseCase::new(repository);
+
+        let output = usecase
+            .execute(SearchTranscriptsInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                query: "Borrow checker".to_string(),
+                limit: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.chats.len(), 1);
+        assert_eq!(output.chats[0].chat_id, rust.id);
+        assert_eq!(output.chats[0].messages.len(), 2);
+        assert!(output.chats[0].messages[0]
+            .highlight
+            .contains("<mark>borrow</mark> <mark>checker</mark>"));
+
+        // the system message is not part of the transcript
+        let output = usecase
+            .execute(SearchTranscriptsInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                query: "know".to_string(),
+                limit: None,
+            })
+            .await
+            .unwrap();
+        assert!(output.chats.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_input() {
+        let usecase = SearchTranscriptsUseCase::new(Arc::new(InMemoryChatRepository::new()));
+        let input = SearchTranscriptsInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id: Uuid::new_v4(),
+            query: "rust".to_string(),
+            limit: None,
+        };
+
+        for input in [
+            SearchTranscriptsInputDTO {
+                query: "  ".to_string(),
+                ..input.clone()
+            },
+            SearchTranscriptsInputDTO {
+                query: "a".repeat(MAX_QUERY_LENGTH + 1),
+                ..input.clone()
+            },
+            SearchTranscriptsInputDTO {
+                limit: Some(MAX_LIMIT + 1),
+                ..input.clone()
+            },
+        ] {
+            assert!(matches!(
+                usecase.execute(input).await,
+                Err(UseCaseError::InvalidInput(_))
+            ));
+        }
+    }
+}
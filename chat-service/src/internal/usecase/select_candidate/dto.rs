@@ -0,0 +1,12 @@
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectCandidateInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // message_id is the last reply of the chat
+    pub message_id: Uuid,
+    // candidate is the position of the chosen reply in the candidates of the message
+    pub candidate: usize,
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::list_chat_messages::dto::MessageOutputDTO;
+use crate::internal::usecase::select_candidate::dto::SelectCandidateInputDTO;
+
+pub struct SelectCandidateUseCase {
+    repository: Arc<dyn ChatRepository>,
+}
+
+impl SelectCandidateUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self { repository }
+    }
+
+    // execute makes one of the candidates of the last reply the reply the chat continues from,
+    // the other candidates are discarded; chats can only be changed by their owner
+    #[instrument(name = "select_candidate", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id, message_id = %input.message_id))]
+    pub async fn execute(
+        &self,
+        input: SelectCandidateInputDTO,
+    ) -> Result<MessageOutputDTO, UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+
+        if !chat
+            .messages
+            .iter()
+            .any(|message| message.id == input.message_id)
+        {
+            return Err(UseCaseError::MessageNotFound(input.message_id));
+        }
+
+        let output =
+            MessageOutputDTO::from(chat.select_candidate(input.message_id, input.candidate)?);
+        self.repository.save_chat(&mut chat).await?;
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    async fn setup() -> (SelectCandidateUseCase, Arc<InMemoryChatRepository>, Chat) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Tell me a joke"),
+                message(Role::Assistant, "Why did the chicken cross the road?")
+                    .with_candidates(vec!["Knock knock.".to_string()]),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        (
+            SelectCandidateUseCase::new(repository.clone()),
+            repository,
+            chat,
+        )
+    }
+
+    fn input(chat: &Chat, message_id: Uuid) -> SelectCandidateInputDTO {
+        SelectCandidateInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            message_id,
+            candidate: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, repository, chat) = setup().await;
+        let reply = chat.messages[1].id;
+
+        let output = usecase.execute(input(&chat, reply)).await.unwrap();
+
+        assert_eq!(output.id, reply);
+        assert_eq!(output.content, "Knock knock.");
+        assert!(output.candidates.is_empty());
+        let saved = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.messages[1].content, "Knock knock.");
+        assert!(saved.messages[1].candidates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_selections() {
+        let (usecase, _, chat) = setup().await;
+        let missing = Uuid::new_v4();
+
+        assert!(matches!(
+            usecase.execute(input(&chat, missing)).await,
+            Err(UseCaseError::MessageNotFound(id)) if id == missing
+        ));
+        assert!(matches!(
+            usecase.execute(input(&chat, chat.messages[0].id)).await,
+            Err(UseCaseError::Domain(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(SelectCandidateInputDTO {
+                    candidate: 1,
+                    ..input(&chat, chat.messages[1].id)
+                })
+                .await,
+            Err(UseCaseError::Domain(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(SelectCandidateInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input(&chat, chat.messages[1].id)
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{instrument, warn};
+
+use crate::internal::domain::entity::scheduled_message::{ScheduledMessage, ScheduledStatus};
+use crate::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::error::UseCaseError;
+
+// SENDING_CLAIM is how long a claimed message is left to its worker, one that dies mid send gets
+// it sent by another worker once the claim is over
+const SENDING_CLAIM: Duration = Duration::from_secs(300);
+
+pub struct SendScheduledMessagesUseCase {
+    scheduled: Arc<dyn ScheduledMessageRepository>,
+    chat_completion: Arc<ChatCompletionUseCase>,
+    batch_size: usize,
+}
+
+impl SendScheduledMessagesUseCase {
+    // new sends up to batch_size due messages per run through the chat completion, so they
+    // are moderated, limited and billed like the messages users send themselves
+    pub fn new(
+        scheduled: Arc<dyn ScheduledMessageRepository>,
+        chat_completion: Arc<ChatCompletionUseCase>,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            scheduled,
+            chat_completion,
+            batch_size,
+        }
+    }
+
+    // execute sends the due messages, soonest first, and returns how many were sent; a message
+    // is claimed for SENDING_CLAIM before it is sent so that it is sent once when several
+    // instances run the job, and is claimed again once that is over when its worker stopped; a
+    // message that cannot be sent is marked failed with the reason instead of retried
+    #[instrument(name = "send_scheduled_messages", skip_all)]
+    pub async fn execute(&self) -> Result<usize, UseCaseError> {
+        let now = chrono::Utc::now();
+        let due = self.scheduled.list_due(now, self.batch_size).await?;
+        let claimed_until = now + chrono::Duration::from_std(SENDING_CLAIM).unwrap_or_default();
+
+        let mut sent = 0;
+        for scheduled in due {
+            match self
+                .scheduled
+                .claim_due(scheduled.tenant_id, scheduled.id, now, claimed_until)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    warn!(scheduled_id = %scheduled.id, error = %err, "could not claim scheduled message");
+                    continue;
+                }
+            }
+
+            let (status, error) = match self.send(&scheduled).await {
+                Ok(()) => (ScheduledStatus::Sent, None),
+                Err(err) => {
+                    warn!(scheduled_id = %scheduled.id, error = %err, "scheduled message was not sent");
+                    (ScheduledStatus::Failed, Some(err.to_string()))
+                }
+            };
+            // a message whose outcome cannot be saved stays sending, to be claimed again once
+            // its claim is over
+            if let Err(err) = self
+                .scheduled
+                .transition(
+                    scheduled.tenant_id,
+                    scheduled.id,
+                    ScheduledStatus::Sending,
+                    status,
+                    error.as_deref(),
+                )
+                .await
+            {
+                warn!(scheduled_id = %scheduled.id, error = %err, "could not complete scheduled message");
+                continue;
+            }
+            if status == ScheduledStatus::Sent {
+                sent += 1;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    async fn send(&self, scheduled: &ScheduledMessage) -> Result<(), UseCaseError> {
+        self.chat_completion
+            .execute(ChatCompletionInputDTO {
+                tenant_id: scheduled.tenant_id,
+                user_id: scheduled.user_id,
+                chat_id: Some(scheduled.chat_id),
+                user_message: scheduled.content.clone(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{ChatStatus, TrimmingPolicy};
+    use crate::internal::domain::entity::message::Role;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::repository::chat::ChatRepository;
+    use crate::internal::infra::repository::memory::scheduled_message::InMemoryScheduledMessageRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::testing::builder::{test_model, ChatBuilder};
+    use crate::internal::testing::gateway::FakeCompletionGateway;
+    use crate::internal::testing::InMemoryChatRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    fn due(chat_id: Uuid, user_id: Uuid, content: &str) -> ScheduledMessage {
+        ScheduledMessage {
+            send_at: chrono::Utc::now() - chrono::Duration::seconds(1),
+            ..ScheduledMessage::new(
+                DEFAULT_TENANT_ID,
+                user_id,
+                chat_id,
+                content,
+                chrono::Utc::now() + chrono::Duration::hours(1),
+            )
+            .unwrap()
+        }
+    }
+
+    fn chat_completion(chats: Arc<InMemoryChatRepository>) -> Arc<ChatCompletionUseCase> {
+        Arc::new(ChatCompletionUseCase::new(
+            Arc::new(FakeCompletionGateway::new()),
+            chats,
+            Arc::new(InMemoryUserRepository::new()),
+            test_model(),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let active = ChatBuilder::new().build();
+        let ended = ChatBuilder::new()
+            .user_id(active.user_id)
+            .status(ChatStatus::Ended)
+            .build();
+        chats.create_chat(&active).await.unwrap();
+        chats.create_chat(&ended).await.unwrap();
+        let repository = Arc::new(InMemoryScheduledMessageRepository::new());
+        let sent = due(active.id, active.user_id, "Good morning");
+        let failed = due(ended.id, active.user_id, "Good night");
+        let later = ScheduledMessage::new(
+            DEFAULT_TENANT_ID,
+            active.user_id,
+            active.id,
+            "Later",
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        for scheduled in [&sent, &failed, &later] {
+            repository
+                .create_scheduled_message(scheduled)
+                .await
+                .unwrap();
+        }
+        let usecase = SendScheduledMessagesUseCase::new(
+            repository.clone(),
+            chat_completion(chats.clone()),
+            10,
+        );
+
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+
+        let chat = chats
+            .find_chat_by_id(DEFAULT_TENANT_ID, active.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(chat
+            .messages
+            .iter()
+            .any(|message| message.role == Role::User && message.content == "Good morning"));
+        let find = |id| repository.find_scheduled_message_by_id(DEFAULT_TENANT_ID, id);
+        assert_eq!(
+            find(sent.id).await.unwrap().unwrap().status,
+            ScheduledStatus::Sent
+        );
+        let failed = find(failed.id).await.unwrap().unwrap();
+        assert_eq!(failed.status, ScheduledStatus::Failed);
+        assert!(failed.error.is_some());
+        assert_eq!(
+            find(later.id).await.unwrap().unwrap().status,
+            ScheduledStatus::Pending
+        );
+
+        // sent messages are not sent again
+        assert_eq!(usecase.execute().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_claims_abandoned_messages_again() {
+        let chats = Arc::new(InMemoryChatRepository::new());
+        let chat = ChatBuilder::new().build();
+        chats.create_chat(&chat).await.unwrap();
+        let repository = Arc::new(InMemoryScheduledMessageRepository::new());
+        let now = chrono::Utc::now();
+        let abandoned = ScheduledMessage {
+            status: ScheduledStatus::Sending,
+            claimed_until: Some(now - chrono::Duration::seconds(1)),
+            ..due(chat.id, chat.user_id, "Good morning")
+        };
+        let sending = ScheduledMessage {
+            status: ScheduledStatus::Sending,
+            claimed_until: Some(now + chrono::Duration::seconds(60)),
+            ..due(chat.id, chat.user_id, "Good night")
+        };
+        for scheduled in [&abandoned, &sending] {
+            repository
+                .create_scheduled_message(scheduled)
+                .await
+                .unwrap();
+        }
+        let usecase =
+            SendScheduledMessagesUseCase::new(repository.clone(), chat_completion(chats), 10);
+
+        assert_eq!(usecase.execute().await.unwrap(), 1);
+
+        let find = |id| repository.find_scheduled_message_by_id(DEFAULT_TENANT_ID, id);
+        let sent = find(abandoned.id).await.unwrap().unwrap();
+        assert_eq!(sent.status, ScheduledStatus::Sent);
+        assert_eq!(sent.claimed_until, None);
+        assert_eq!(find(sending.id).await.unwrap().unwrap(), sending);
+    }
+}
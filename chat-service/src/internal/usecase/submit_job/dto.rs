@@ -0,0 +1,43 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::attachment::Attachment;
+use crate::internal::domain::entity::job::{Job, JobStatus};
+use crate::internal::usecase::chat_completion::dto::ChatOverridesInputDTO;
+
+// SubmitJobInputDTO is a completion request answered in the background, a new chat is started
+// when chat_id is None
+#[derive(Debug, Clone, Default)]
+pub struct SubmitJobInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Option<Uuid>,
+    pub user_message: String,
+    pub attachments: Vec<Attachment>,
+    pub overrides: ChatOverridesInputDTO,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobOutputDTO {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub chat_id: Option<Uuid>,
+    pub content: Option<String>,
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<&Job> for JobOutputDTO {
+    fn from(job: &Job) -> Self {
+        Self {
+            id: job.id,
+            status: job.status,
+            chat_id: job.chat_id,
+            content: job.content.clone(),
+            error: job.error.clone(),
+            created_at: job.created_at,
+            completed_at: job.completed_at,
+        }
+    }
+}
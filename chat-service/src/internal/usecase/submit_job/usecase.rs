@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::job::Job;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::repository::job::JobRepository;
+use crate::internal::usecase::chat_completion::dto::{ChatCompletionInputDTO, ChatLabelsInputDTO};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::submit_job::dto::{JobOutputDTO, SubmitJobInputDTO};
+
+pub struct SubmitJobUseCase {
+    jobs: Arc<dyn JobRepository>,
+    chats: Arc<dyn ChatRepository>,
+    chat_completion: Arc<ChatCompletionUseCase>,
+}
+
+impl SubmitJobUseCase {
+    pub fn new(
+        jobs: Arc<dyn JobRepository>,
+        chats: Arc<dyn ChatRepository>,
+        chat_completion: Arc<ChatCompletionUseCase>,
+    ) -> Self {
+        Self {
+            jobs,
+            chats,
+            chat_completion,
+        }
+    }
+
+    // execute queues the completion and returns right away, the job worker answers it; what
+    // can be checked upfront is, so that a job does not fail later for a request that was
+    // never valid
+    #[instrument(name = "submit_job", skip_all, fields(user_id = %input.user_id))]
+    pub async fn execute(&self, input: SubmitJobInputDTO) -> Result<JobOutputDTO, UseCaseError> {
+        if let Some(chat_id) = input.chat_id {
+            let chat = self
+                .chats
+                .find_chat_by_id(input.tenant_id, chat_id)
+                .await?
+                .filter(|chat| !chat.is_deleted())
+                .ok_or(UseCaseError::ChatNotFound(chat_id))?;
+            if chat.user_id != input.user_id {
+                return Err(UseCaseError::Forbidden(chat_id));
+            }
+        }
+        self.chat_completion
+            .overrides_for(&ChatCompletionInputDTO {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                chat_id: input.chat_id,
+                user_message: input.user_message.clone(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: input.overrides.clone(),
+                labels: ChatLabelsInputDTO::default(),
+            })?;
+        let overrides = serde_json::to_value(&input.overrides)
+            .map_err(|e| UseCaseError::InvalidInput(e.to_string()))?;
+
+        let job = Job::new(
+            input.tenant_id,
+            input.user_id,
+            input.chat_id,
+            &input.user_message,
+            input.attachments,
+            overrides,
+        )?;
+        self.jobs.create_job(&job).await?;
+
+        Ok(JobOutputDTO::from(&job))
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::internal::domain::entity::speech::Voice;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthesizeSpeechInputDTO {
+    pub text: String,
+    pub voice: Voice,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeechOutputDTO {
+    pub voice: Voice,
+    pub mime_type: String,
+    // base64 is the encoded audio, playable as data:{mime_type};base64,{base64}
+    pub base64: String,
+}
+
+// SpokenOutputDTO is a reply with the speech of its content when the request asked for a voice;
+// the reply is saved before it is read aloud, so a failed synthesis comes back as audio_error
+// next to it instead of failing the request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpokenOutputDTO<T> {
+    #[serde(flatten)]
+    pub reply: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<SpeechOutputDTO>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_error: Option<String>,
+}
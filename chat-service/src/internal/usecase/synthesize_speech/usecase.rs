@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use tracing::instrument;
+
+use crate::internal::domain::chunker::{chunk_text, ChunkingConfig};
+use crate::internal::domain::entity::speech::{Speech, SPEECH_MIME_TYPE};
+use crate::internal::domain::gateway::speech::SpeechGateway;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::synthesize_speech::dto::{SpeechOutputDTO, SynthesizeSpeechInputDTO};
+
+pub struct SynthesizeSpeechUseCase {
+    gateway: Arc<dyn SpeechGateway>,
+}
+
+impl SynthesizeSpeechUseCase {
+    pub fn new(gateway: Arc<dyn SpeechGateway>) -> Self {
+        Self { gateway }
+    }
+
+    // execute reads the text aloud; text longer than the gateway accepts is read in pieces cut
+    // at paragraph, sentence or word breaks and the pieces are joined into one recording
+    #[instrument(name = "synthesize_speech", skip_all, fields(voice = %input.voice))]
+    pub async fn execute(
+        &self,
+        input: SynthesizeSpeechInputDTO,
+    ) -> Result<SpeechOutputDTO, UseCaseError> {
+        let config = ChunkingConfig {
+            size: self.gateway.max_input_chars(),
+            overlap: 0,
+        };
+        let chunks = chunk_text(&input.text, &config);
+        if chunks.is_empty() {
+            return Err(UseCaseError::InvalidInput(
+                "there is no text to read aloud".to_string(),
+            ));
+        }
+
+        let mut speech = Speech::new(input.voice, vec![]);
+        for chunk in chunks {
+            speech.append(self.gateway.synthesize(&chunk.content, input.voice).await?);
+        }
+
+        Ok(SpeechOutputDTO {
+            voice: speech.voice,
+            mime_type: SPEECH_MIME_TYPE.to_string(),
+            base64: STANDARD.encode(&speech.content),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    use crate::internal::domain::entity::speech::Voice;
+    use crate::internal::domain::gateway::chat_completion::GatewayError;
+
+    // EchoSpeech reads text as its own bytes and records the texts it was given
+    #[derive(Default)]
+    struct EchoSpeech {
+        inputs: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SpeechGateway for EchoSpeech {
+        fn max_input_chars(&self) -> usize {
+            16
+        }
+
+        async fn synthesize(&self, text: &str, voice: Voice) -> Result<Speech, GatewayError> {
+            self.inputs.lock().await.push(text.to_string());
+            Ok(Speech::new(voice, text.as_bytes().to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let gateway = Arc::new(EchoSpeech::default());
+        let usecase = SynthesizeSpeechUseCase::new(gateway.clone());
+
+        let output = usecase
+            .execute(SynthesizeSpeechInputDTO {
+                text: "Hello there. How are you?".to_string(),
+                voice: Voice::Nova,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *gateway.inputs.lock().await,
+            vec!["Hello there.", "How are you?"]
+        );
+        assert_eq!(output.voice, Voice::Nova);
+        assert_eq!(output.mime_type, "audio/mpeg");
+        assert_eq!(
+            STANDARD.decode(output.base64).unwrap(),
+            b"Hello there.How are you?"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_empty_text() {
+        let usecase = SynthesizeSpeechUseCase::new(Arc::new(EchoSpeech::default()));
+
+        assert!(matches!(
+            usecase
+                .execute(SynthesizeSpeechInputDTO {
+                    text: "  ".to_string(),
+                    voice: Voice::Alloy,
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+}
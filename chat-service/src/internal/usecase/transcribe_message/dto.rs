@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscribeMessageInputDTO {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub chat_id: Uuid,
+    // name is the file name of the recording, its extension tells the format when the
+    // content type does not
+    pub name: Option<String>,
+    pub content_type: Option<String>,
+    pub audio: Vec<u8>,
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscribedMessageOutputDTO {
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // transcript is the text of the recording, sent to the chat as the user message
+    pub transcript: String,
+    pub content: String,
+}
@@ -0,0 +1,280 @@
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::internal::domain::entity::audio::{Audio, AudioFormat};
+use crate::internal::domain::entity::idempotency::fingerprint;
+use crate::internal::domain::error::ChatError;
+use crate::internal::domain::gateway::transcription::TranscriptionGateway;
+use crate::internal::usecase::chat_completion::dto::{
+    ChatCompletionInputDTO, ChatLabelsInputDTO, ChatOverridesInputDTO,
+};
+use crate::internal::usecase::chat_completion::usecase::ChatCompletionUseCase;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::transcribe_message::dto::{
+    TranscribeMessageInputDTO, TranscribedMessageOutputDTO,
+};
+
+// DEFAULT_MAX_SIZE_BYTES is the largest recording Whisper accepts
+pub const DEFAULT_MAX_SIZE_BYTES: usize = 25 * 1024 * 1024;
+
+pub struct TranscribeMessageUseCase {
+    transcription: Arc<dyn TranscriptionGateway>,
+    completion: Arc<ChatCompletionUseCase>,
+    max_size_bytes: usize,
+}
+
+impl TranscribeMessageUseCase {
+    pub fn new(
+        transcription: Arc<dyn TranscriptionGateway>,
+        completion: Arc<ChatCompletionUseCase>,
+    ) -> Self {
+        Self {
+            transcription,
+            completion,
+            max_size_bytes: DEFAULT_MAX_SIZE_BYTES,
+        }
+    }
+
+    pub fn with_max_size_bytes(mut self, max_size_bytes: usize) -> Self {
+        self.max_size_bytes = max_size_bytes;
+        self
+    }
+
+    pub fn max_size_bytes(&self) -> usize {
+        self.max_size_bytes
+    }
+
+    // execute transcribes the recording and sends the transcript to the chat as a user
+    // message; a retry with the same key replays the reply without transcribing again
+    #[instrument(name = "transcribe_message", skip_all, fields(user_id = %input.user_id, chat_id = %input.chat_id))]
+    pub async fn execute(
+        &self,
+        input: TranscribeMessageInputDTO,
+    ) -> Result<TranscribedMessageOutputDTO, UseCaseError> {
+        if input.audio.len() > self.max_size_bytes {
+            return Err(UseCaseError::InvalidInput(format!(
+                "audio is larger than {} bytes",
+                self.max_size_bytes
+            )));
+        }
+
+        let format = AudioFormat::detect(input.name.as_deref(), input.content_type.as_deref())?;
+        let audio = Audio::new(format, input.audio.clone());
+        audio.validate()?;
+
+        let chat_id = input.chat_id.to_string();
+        let audio_hash = hex::encode(Sha256::digest(&audio.content));
+        let fingerprint = fingerprint(&["audio", &chat_id, &audio_hash]);
+
+        self.completion
+            .idempotent(
+                input.tenant_id,
+                input.user_id,
+                input.idempotency_key.as_deref(),
+                &fingerprint,
+                self.reply(&input, &audio),
+            )
+            .await
+    }
+
+    async fn reply(
+        &self,
+        input: &TranscribeMessageInputDTO,
+        audio: &Audio,
+    ) -> Result<TranscribedMessageOutputDTO, UseCaseError> {
+        let transcript = self.transcription.transcribe(audio).await?;
+        let transcript = transcript.trim();
+        if transcript.is_empty() {
+            return Err(ChatError::InvalidAudio("no speech was recognized".to_string()).into());
+        }
+
+        let output = self
+            .completion
+            .complete(&ChatCompletionInputDTO {
+                tenant_id: input.tenant_id,
+                user_id: input.user_id,
+                chat_id: Some(input.chat_id),
+                user_message: transcript.to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await?;
+
+        Ok(TranscribedMessageOutputDTO {
+            chat_id: output.chat_id,
+            user_id: output.user_id,
+            transcript: transcript.to_string(),
+            content: output.content,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, TrimmingPolicy};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::entity::response_format::ResponseFormat;
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::domain::entity::user::User;
+    use crate::internal::domain::gateway::chat_completion::{ChatCompletionGateway, GatewayError};
+    use crate::internal::domain::repository::user::UserRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::infra::repository::memory::user::InMemoryUserRepository;
+    use crate::internal::usecase::chat_completion::dto::ChatCompletionConfigInputDTO;
+
+    // EchoGateway answers with the last message of the chat
+    struct EchoGateway;
+
+    #[async_trait]
+    impl ChatCompletionGateway for EchoGateway {
+        async fn create_chat_completion(&self, chat: &Chat) -> Result<Message, GatewayError> {
+            let last = chat.messages.last().ok_or(GatewayError::EmptyResponse)?;
+            Ok(Message::new(
+                Uuid::new_v4(),
+                Role::Assistant,
+                &format!("You said: {}", last.content),
+                0,
+                chat.config.model.clone(),
+                chrono::Utc::now(),
+            ))
+        }
+
+        async fn create_chat_completion_stream(
+            &self,
+            chat: &Chat,
+            _sender: mpsc::Sender<String>,
+        ) -> Result<Message, GatewayError> {
+            self.create_chat_completion(chat).await
+        }
+    }
+
+    // TextTranscription reads the recording as the UTF-8 text spoken in it
+    struct TextTranscription;
+
+    #[async_trait]
+    impl TranscriptionGateway for TextTranscription {
+        async fn transcribe(&self, audio: &Audio) -> Result<String, GatewayError> {
+            Ok(String::from_utf8_lossy(&audio.content).to_string())
+        }
+    }
+
+    async fn setup() -> (TranscribeMessageUseCase, Uuid, Uuid) {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let user_id = Uuid::new_v4();
+        users
+            .create_user(&User::new(
+                user_id,
+                &user_id.to_string(),
+                "Ada",
+                chrono::Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let completion = Arc::new(ChatCompletionUseCase::new(
+            Arc::new(EchoGateway),
+            Arc::new(InMemoryChatRepository::new()),
+            users,
+            Model::new("gpt-3.5-turbo".to_string(), 4096),
+            ChatCompletionConfigInputDTO {
+                temperature: 0.0,
+                top_p: 1.0,
+                n: 1,
+                stop: vec![],
+                max_tokens: 4096,
+                presence_penalty: 0.0,
+                frequency_penalty: 0.0,
+                trimming_policy: TrimmingPolicy::TrimOldest,
+                initial_system_message: "You are a helpful assistant.".to_string(),
+                response_format: ResponseFormat::default(),
+            },
+        ));
+        let chat = completion
+            .execute(ChatCompletionInputDTO {
+                tenant_id: DEFAULT_TENANT_ID,
+                user_id,
+                chat_id: None,
+                user_message: "Hello!".to_string(),
+                attachments: vec![],
+                template: None,
+                assistant: None,
+                idempotency_key: None,
+                overrides: ChatOverridesInputDTO::default(),
+                labels: ChatLabelsInputDTO::default(),
+            })
+            .await
+            .unwrap();
+        let usecase = TranscribeMessageUseCase::new(Arc::new(TextTranscription), completion)
+            .with_max_size_bytes(64);
+
+        (usecase, user_id, chat.chat_id)
+    }
+
+    fn input(user_id: Uuid, chat_id: Uuid, name: &str, audio: &str) -> TranscribeMessageInputDTO {
+        TranscribeMessageInputDTO {
+            tenant_id: DEFAULT_TENANT_ID,
+            user_id,
+            chat_id,
+            name: Some(name.to_string()),
+            content_type: None,
+            audio: audio.as_bytes().to_vec(),
+            idempotency_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, user_id, chat_id) = setup().await;
+
+        let output = usecase
+            .execute(input(user_id, chat_id, "memo.webm", " What time is it? "))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output,
+            TranscribedMessageOutputDTO {
+                chat_id,
+                user_id,
+                transcript: "What time is it?".to_string(),
+                content: "You said: What time is it?".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_audio() {
+        let (usecase, user_id, chat_id) = setup().await;
+
+        for (name, audio) in [("memo.aiff", "Hello"), ("memo.wav", ""), ("memo.wav", " ")] {
+            assert!(matches!(
+                usecase.execute(input(user_id, chat_id, name, audio)).await,
+                Err(UseCaseError::Domain(ChatError::InvalidAudio(_)))
+            ));
+        }
+        assert!(matches!(
+            usecase
+                .execute(input(user_id, chat_id, "memo.wav", &"a".repeat(65)))
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(input(user_id, Uuid::new_v4(), "memo.wav", "Hello"))
+                .await,
+            Err(UseCaseError::ChatNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::entity::assistant::Assistant;
+use crate::internal::domain::repository::assistant::AssistantRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::domain::tool_registry::ToolRegistry;
+use crate::internal::usecase::create_assistant::dto::{AssistantInputDTO, AssistantOutputDTO};
+use crate::internal::usecase::create_assistant::usecase::check_assistant;
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct UpdateAssistantUseCase {
+    assistants: Arc<dyn AssistantRepository>,
+    tenants: Arc<TenantRegistry>,
+    tools: Option<Arc<ToolRegistry>>,
+}
+
+impl UpdateAssistantUseCase {
+    pub fn new(assistants: Arc<dyn AssistantRepository>, tenants: Arc<TenantRegistry>) -> Self {
+        Self {
+            assistants,
+            tenants,
+            tools: None,
+        }
+    }
+
+    // with_tools lets assistants list the registered tools, without it they cannot list any
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    // execute replaces the configuration of an assistant; chats started with it keep their
+    // system message and model, they are offered its new tools from their next turn
+    #[instrument(name = "update_assistant", skip_all, fields(tenant_id = %tenant_id, assistant_id = %assistant_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        assistant_id: Uuid,
+        input: AssistantInputDTO,
+    ) -> Result<AssistantOutputDTO, UseCaseError> {
+        let stored = self
+            .assistants
+            .find_assistant_by_id(tenant_id, assistant_id)
+            .await?
+            .ok_or(UseCaseError::AssistantNotFound(assistant_id))?;
+
+        let assistant = Assistant {
+            updated_at: chrono::Utc::now(),
+            ..Assistant::new(
+                stored.id,
+                tenant_id,
+                &input.name,
+                &input.instructions,
+                stored.created_at,
+            )
+        }
+        .with_model(input.model)
+        .with_temperature(input.temperature)
+        .with_tools(input.tools);
+        check_assistant(
+            self.assistants.as_ref(),
+            &self.tenants,
+            self.tools.as_deref(),
+            &assistant,
+        )
+        .await?;
+        self.assistants.update_assistant(&assistant).await?;
+
+        Ok(AssistantOutputDTO::from(&assistant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::DEFAULT_TENANT_ID;
+    use crate::internal::infra::repository::memory::assistant::InMemoryAssistantRepository;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let assistants = Arc::new(InMemoryAssistantRepository::new());
+        let billing = Assistant::new(
+            Uuid::new_v4(),
+            DEFAULT_TENANT_ID,
+            "billing",
+            "You answer about invoices.",
+            chrono::Utc::now(),
+        );
+        let sales = Assistant::new(
+            Uuid::new_v4(),
+            DEFAULT_TENANT_ID,
+            "sales",
+            "You sell.",
+            chrono::Utc::now(),
+        );
+        assistants.create_assistant(&billing).await.unwrap();
+        assistants.create_assistant(&sales).await.unwrap();
+        let usecase =
+            UpdateAssistantUseCase::new(assistants.clone(), Arc::new(TenantRegistry::new()));
+        let input = AssistantInputDTO {
+            name: "invoices".to_string(),
+            instructions: "You answer about invoices and refunds.".to_string(),
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+
+        let output = usecase
+            .execute(DEFAULT_TENANT_ID, billing.id, input.clone())
+            .await
+            .unwrap();
+        assert_eq!(output.name, "invoices");
+        assert_eq!(output.created_at, billing.created_at);
+        let stored = assistants
+            .find_assistant_by_id(DEFAULT_TENANT_ID, billing.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(AssistantOutputDTO::from(&stored), output);
+
+        assert!(matches!(
+            usecase
+                .execute(
+                    DEFAULT_TENANT_ID,
+                    billing.id,
+                    AssistantInputDTO {
+                        name: "sales".to_string(),
+                        ..input.clone()
+                    }
+                )
+                .await,
+            Err(UseCaseError::AssistantAlreadyExists(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(DEFAULT_TENANT_ID, Uuid::new_v4(), input)
+                .await,
+            Err(UseCaseError::AssistantNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,22 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+// UpdateChatInputDTO changes the fields that are set and leaves the others alone
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpdateChatInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    // model names a model of the registry
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    // response_language is the ISO 639-1 code of the language to answer in, empty to answer in
+    // the language of the user again
+    pub response_language: Option<String>,
+    // status is active, ended or archived; chats are deleted through their own endpoint
+    pub status: Option<String>,
+    // tags and metadata replace the ones of the chat, they can change whatever its status
+    pub tags: Option<Vec<String>>,
+    pub metadata: Option<BTreeMap<String, String>>,
+}
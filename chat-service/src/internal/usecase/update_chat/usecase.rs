@@ -0,0 +1,402 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::chat::ChatStatus;
+use crate::internal::domain::entity::model::{Model, ModelRegistry};
+use crate::internal::domain::gateway::blob_store::BlobStore;
+use crate::internal::domain::repository::chat::ChatRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::archive_chats::usecase::restore_history;
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+use crate::internal::usecase::update_chat::dto::UpdateChatInputDTO;
+
+pub struct UpdateChatUseCase {
+    repository: Arc<dyn ChatRepository>,
+    blobs: Option<Arc<dyn BlobStore>>,
+    tenants: Option<Arc<TenantRegistry>>,
+}
+
+impl UpdateChatUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>) -> Self {
+        Self {
+            repository,
+            blobs: None,
+            tenants: None,
+        }
+    }
+
+    // with_blob_store reopens cold chats, their history is read back from the store
+    pub fn with_blob_store(mut self, blobs: Arc<dyn BlobStore>) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    // with_tenants refuses to move chats to models their tenant does not allow
+    pub fn with_tenants(mut self, tenants: Arc<TenantRegistry>) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    // execute changes the model, temperature, response language, status, tags or metadata of the
+    // chat; settings only change on active chats, so a chat is reopened before and ended or
+    // archived after they are applied; chats can only be updated by their owner, the system message only through
+    // UpdateSystemPromptUseCase so the change is audited
+    #[instrument(name = "update_chat", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(&self, input: UpdateChatInputDTO) -> Result<ChatOutputDTO, UseCaseError> {
+        let status = input
+            .status
+            .as_deref()
+            .map(ChatStatus::from_str)
+            .transpose()?;
+        if status == Some(ChatStatus::Deleted) {
+            return Err(UseCaseError::InvalidInput(
+                "chats cannot be deleted by an update".to_string(),
+            ));
+        }
+        let model = input.model.as_deref().map(resolve_model).transpose()?;
+
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+
+        let status = status.filter(|status| *status != chat.status);
+        let mut restored = None;
+        if status == Some(ChatStatus::Active) {
+            if let Some(blobs) = &self.blobs {
+                restored = restore_history(blobs.as_ref(), &mut chat).await?;
+            }
+            chat.reopen()?;
+        }
+        if let Some(model) = model {
+            if let Some(tenants) = &self.tenants {
+                tenants.check_model(input.tenant_id, &model)?;
+            }
+            chat.set_model(model)?;
+        }
+        if let Some(temperature) = input.temperature {
+            chat.set_temperature(temperature)?;
+        }
+        if let Some(language) = input.response_language {
+            chat.set_response_language(Some(language).filter(|language| !language.is_empty()))?;
+        }
+        match status {
+            Some(ChatStatus::Ended) => chat.end()?,
+            Some(ChatStatus::Archived) => chat.archive()?,
+            _ => {}
+        }
+        if let Some(tags) = &input.tags {
+            chat.set_tags(tags)?;
+        }
+        if let Some(metadata) = input.metadata {
+            chat.set_metadata(metadata)?;
+        }
+
+        self.repository.save_chat(&mut chat).await?;
+
+        // the history is back in the database, a blob left behind only costs storage
+        if let (Some(blobs), Some(key)) = (&self.blobs, restored) {
+            if let Err(err) = blobs.delete(&key).await {
+                tracing::warn!(error = %err, key, "could not remove the archived history");
+            }
+        }
+
+        Ok(ChatOutputDTO::from(&chat))
+    }
+}
+
+// resolve_model looks the name up in the registry, dated snapshots keep their own name
+pub(crate) fn resolve_model(name: &str) -> Result<Model, UseCaseError> {
+    ModelRegistry::get(name)
+        .map(|info| Model::new(name.to_string(), info.context_window))
+        .ok_or_else(|| UseCaseError::InvalidInput(format!("model {} is not supported", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig};
+    use crate::internal::domain::error::{ChatError, ConfigError};
+    use crate::internal::infra::blob::memory::InMemoryBlobStore;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+    use crate::internal::usecase::archive_chats::usecase::ArchiveChatsUseCase;
+
+    async fn setup() -> (UpdateChatUseCase, Arc<InMemoryChatRepository>, Chat) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let message = |role, content| {
+            Message::new(
+                Uuid::new_v4(),
+                role,
+                content,
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            )
+        };
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            message(Role::System, "You are a helpful assistant."),
+            vec![
+                message(Role::User, "Hello!"),
+                message(Role::Assistant, "Hi, how can I help?"),
+            ],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model.clone()),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+
+        (UpdateChatUseCase::new(repository.clone()), repository, chat)
+    }
+
+    fn input(chat: &Chat) -> UpdateChatInputDTO {
+        UpdateChatInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, repository, chat) = setup().await;
+
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                model: Some("gpt-4o-2024-05-13".to_string()),
+                temperature: Some(0.3),
+                status: Some("ended".to_string()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.model, "gpt-4o-2024-05-13");
+        assert_eq!(output.temperature, 0.3);
+        assert_eq!(output.status, "ended");
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.config.model.max_tokens, 128000);
+        assert_eq!(stored.messages.len(), 2);
+
+        // an ended chat takes new settings once it is reopened in the same update
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                temperature: Some(1.2),
+                status: Some("active".to_string()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.status, "active");
+        assert_eq!(output.temperature, 1.2);
+
+        // an empty response language lets the chat follow the language of the user again
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                response_language: Some("de".to_string()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.response_language.as_deref(), Some("de"));
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                response_language: Some(String::new()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.response_language, None);
+
+        // tags and metadata change on archived chats too
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                status: Some("archived".to_string()),
+                tags: Some(vec!["Support".to_string(), "support".to_string()]),
+                metadata: Some([("ticket".to_string(), "T-42".to_string())].into()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.tags, vec!["support"]);
+        assert_eq!(output.metadata["ticket"], "T-42");
+        let output = usecase
+            .execute(UpdateChatInputDTO {
+                tags: Some(vec![]),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert!(output.tags.is_empty());
+        assert_eq!(output.metadata["ticket"], "T-42");
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_updates() {
+        let (usecase, _, chat) = setup().await;
+
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    model: Some("unknown-model".to_string()),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    status: Some("deleted".to_string()),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    temperature: Some(5.0),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidConfig(_)))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    response_language: Some("klingon".to_string()),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidConfig(
+                ConfigError::UnknownLanguage(_)
+            )))
+        ));
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    tags: Some(vec!["two words".to_string()]),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::InvalidTag(_)))
+        ));
+
+        usecase
+            .execute(UpdateChatInputDTO {
+                status: Some("ended".to_string()),
+                ..input(&chat)
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    temperature: Some(0.5),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::ChatEnded))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_refuses_models_the_tenant_does_not_allow() {
+        let (usecase, repository, chat) = setup().await;
+        let tenant = Tenant::new(
+            chat.tenant_id,
+            "Acme",
+            TenantConfig {
+                allowed_models: vec!["gpt-3.5-turbo".to_string()],
+                ..Default::default()
+            },
+        );
+        let usecase =
+            usecase.with_tenants(Arc::new(TenantRegistry::new().with_tenant(tenant).unwrap()));
+
+        assert!(matches!(
+            usecase
+                .execute(UpdateChatInputDTO {
+                    model: Some("gpt-4o".to_string()),
+                    ..input(&chat)
+                })
+                .await,
+            Err(UseCaseError::Domain(ChatError::ModelNotAllowed(_)))
+        ));
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.config.model.name, "gpt-3.5-turbo");
+    }
+
+    #[tokio::test]
+    async fn test_execute_reopens_cold_chat() {
+        let (usecase, repository, chat) = setup().await;
+        let blobs = Arc::new(InMemoryBlobStore::new());
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        ArchiveChatsUseCase::new(
+            repository.clone(),
+            blobs.clone(),
+            std::time::Duration::ZERO,
+            10,
+        )
+        .execute()
+        .await
+        .unwrap();
+
+        let reopen = UpdateChatInputDTO {
+            status: Some("active".to_string()),
+            ..input(&chat)
+        };
+        assert!(matches!(
+            usecase.execute(reopen.clone()).await,
+            Err(UseCaseError::Domain(ChatError::ChatArchived))
+        ));
+
+        let usecase = usecase.with_blob_store(blobs.clone());
+        let output = usecase.execute(reopen).await.unwrap();
+        assert_eq!(output.status, "active");
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!stored.is_cold());
+        assert_eq!(stored.messages, chat.messages);
+        assert!(blobs.keys().is_empty());
+    }
+}
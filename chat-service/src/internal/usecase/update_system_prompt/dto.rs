@@ -0,0 +1,10 @@
+use uuid::Uuid;
+
+// UpdateSystemPromptInputDTO replaces the system message of the chat
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpdateSystemPromptInputDTO {
+    pub tenant_id: Uuid,
+    pub chat_id: Uuid,
+    pub user_id: Uuid,
+    pub system_message: String,
+}
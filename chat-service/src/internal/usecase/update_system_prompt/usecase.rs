@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+
+use crate::internal::domain::entity::audit::{current_request_id, AuditEntry};
+use crate::internal::domain::repository::audit::AuditRepository;
+use crate::internal::domain::repository::chat::{ChatRepository, RepositoryError};
+use crate::internal::usecase::error::UseCaseError;
+use crate::internal::usecase::get_chat::dto::ChatOutputDTO;
+use crate::internal::usecase::update_system_prompt::dto::UpdateSystemPromptInputDTO;
+
+pub struct UpdateSystemPromptUseCase {
+    repository: Arc<dyn ChatRepository>,
+    audit: Arc<dyn AuditRepository>,
+}
+
+impl UpdateSystemPromptUseCase {
+    pub fn new(repository: Arc<dyn ChatRepository>, audit: Arc<dyn AuditRepository>) -> Self {
+        Self { repository, audit }
+    }
+
+    // execute replaces the system message of an active chat owned by the user; the change is
+    // appended to the audit log once the chat is saved, so a change that is not saved is never
+    // logged
+    #[instrument(name = "update_system_prompt", skip_all, fields(chat_id = %input.chat_id, user_id = %input.user_id))]
+    pub async fn execute(
+        &self,
+        input: UpdateSystemPromptInputDTO,
+    ) -> Result<ChatOutputDTO, UseCaseError> {
+        let mut chat = self
+            .repository
+            .find_chat_by_id(input.tenant_id, input.chat_id)
+            .await?
+            .filter(|chat| !chat.is_deleted())
+            .ok_or(UseCaseError::ChatNotFound(input.chat_id))?;
+
+        if chat.user_id != input.user_id {
+            return Err(UseCaseError::Forbidden(input.chat_id));
+        }
+
+        let previous = chat.replace_system_message(&input.system_message)?;
+        let entry = AuditEntry::system_prompt(current_request_id(), &chat, &previous)
+            .map_err(|e| RepositoryError::Database(e.to_string()))?;
+        self.repository.save_chat(&mut chat).await?;
+        self.audit.append(&entry).await?;
+
+        Ok(ChatOutputDTO::from(&chat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    use crate::internal::domain::entity::audit::AuditDirection;
+    use crate::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+    use crate::internal::domain::entity::message::{Message, Role};
+    use crate::internal::domain::entity::model::Model;
+    use crate::internal::domain::error::ChatError;
+    use crate::internal::domain::repository::audit::AuditQuery;
+    use crate::internal::domain::request_id;
+    use crate::internal::infra::repository::memory::audit::InMemoryAuditRepository;
+    use crate::internal::infra::repository::memory::chat::InMemoryChatRepository;
+
+    async fn setup() -> (
+        UpdateSystemPromptUseCase,
+        Arc<InMemoryChatRepository>,
+        Arc<InMemoryAuditRepository>,
+        Chat,
+    ) {
+        let model = Model::new("gpt-3.5-turbo".to_string(), 4096);
+        let chat = Chat::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Message::new(
+                Uuid::new_v4(),
+                Role::System,
+                "You are a helpful assistant.",
+                0,
+                model.clone(),
+                chrono::Utc::now(),
+            ),
+            vec![],
+            vec![],
+            ChatStatus::Active,
+            0,
+            ChatConfig::default_for(model),
+        );
+        let repository = Arc::new(InMemoryChatRepository::new());
+        repository.create_chat(&chat).await.unwrap();
+        let audit = Arc::new(InMemoryAuditRepository::new());
+
+        (
+            UpdateSystemPromptUseCase::new(repository.clone(), audit.clone()),
+            repository,
+            audit,
+            chat,
+        )
+    }
+
+    fn everything() -> AuditQuery {
+        AuditQuery {
+            tenant_id: None,
+            user_id: None,
+            chat_id: None,
+            request_id: None,
+            from: None,
+            to: None,
+            after: None,
+            limit: 10,
+        }
+    }
+
+    fn input(chat: &Chat, system_message: &str) -> UpdateSystemPromptInputDTO {
+        UpdateSystemPromptInputDTO {
+            tenant_id: chat.tenant_id,
+            chat_id: chat.id,
+            user_id: chat.user_id,
+            system_message: system_message.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let (usecase, repository, audit, chat) = setup().await;
+        let request_id = Uuid::new_v4();
+
+        request_id::with_request_id(
+            request_id.to_string(),
+            usecase.execute(input(&chat, "Answer like a pirate.")),
+        )
+        .await
+        .unwrap();
+
+        let stored = repository
+            .find_chat_by_id(chat.tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.initial_system_message().content,
+            "Answer like a pirate."
+        );
+        let entries = audit.list_entries(&everything()).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].direction, AuditDirection::SystemPrompt);
+        assert_eq!(entries[0].request_id, request_id);
+        assert!(entries[0].content.contains("You are a helpful assistant."));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_updates() {
+        let (usecase, _, audit, chat) = setup().await;
+
+        assert!(matches!(
+            usecase
+                .execute(UpdateSystemPromptInputDTO {
+                    user_id: Uuid::new_v4(),
+                    ..input(&chat, "Answer like a pirate.")
+                })
+                .await,
+            Err(UseCaseError::Forbidden(id)) if id == chat.id
+        ));
+        assert!(matches!(
+            usecase.execute(input(&chat, "")).await,
+            Err(UseCaseError::Domain(ChatError::InvalidMessage(_)))
+        ));
+        assert!(audit.list_entries(&everything()).await.unwrap().is_empty());
+    }
+}
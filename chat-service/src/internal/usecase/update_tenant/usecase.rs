@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::internal::domain::rate_limiter::RateLimiter;
+use crate::internal::domain::repository::tenant::TenantRepository;
+use crate::internal::domain::tenant_registry::TenantRegistry;
+use crate::internal::usecase::create_tenant::dto::{TenantInputDTO, TenantOutputDTO};
+use crate::internal::usecase::create_tenant::usecase::{apply_tenant, new_tenant};
+use crate::internal::usecase::error::UseCaseError;
+
+pub struct UpdateTenantUseCase {
+    repository: Arc<dyn TenantRepository>,
+    tenants: Arc<TenantRegistry>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl UpdateTenantUseCase {
+    pub fn new(
+        repository: Arc<dyn TenantRepository>,
+        tenants: Arc<TenantRegistry>,
+        rate_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            repository,
+            tenants,
+            rate_limiter,
+        }
+    }
+
+    // execute replaces the name and overrides of an existing tenant, a configured tenant is
+    // stored from then on and the stored copy wins over its settings
+    #[instrument(name = "update_tenant", skip_all, fields(tenant_id = %tenant_id))]
+    pub async fn execute(
+        &self,
+        tenant_id: Uuid,
+        input: TenantInputDTO,
+    ) -> Result<TenantOutputDTO, UseCaseError> {
+        if !self.tenants.contains(tenant_id) {
+            return Err(UseCaseError::TenantNotFound(tenant_id));
+        }
+
+        let tenant = new_tenant(tenant_id, input)?;
+        self.repository.save_tenant(&tenant).await?;
+        apply_tenant(&self.tenants, &self.rate_limiter, &tenant)?;
+
+        Ok(TenantOutputDTO::from(&tenant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+    use crate::internal::domain::rate_limiter::RateLimitConfig;
+    use crate::internal::infra::repository::memory::tenant::InMemoryTenantRepository;
+    use crate::internal::usecase::create_tenant::dto::RateLimitDTO;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let acme = Tenant::new(Uuid::new_v4(), "Acme", TenantConfig::default());
+        let repository = Arc::new(InMemoryTenantRepository::new());
+        let tenants = Arc::new(TenantRegistry::new().with_tenant(acme.clone()).unwrap());
+        let rate_limiter = Arc::new(
+            RateLimiter::new(RateLimitConfig::default()).with_tenant_config(
+                acme.id,
+                RateLimitConfig {
+                    requests_per_minute: 10,
+                    tokens_per_minute: 0,
+                },
+            ),
+        );
+        let usecase =
+            UpdateTenantUseCase::new(repository.clone(), tenants.clone(), rate_limiter.clone());
+
+        let output = usecase
+            .execute(
+                acme.id,
+                TenantInputDTO {
+                    name: "Acme Corp".to_string(),
+                    model: None,
+                    rate_limit: Some(RateLimitDTO {
+                        requests_per_minute: 0,
+                        tokens_per_minute: 1000,
+                    }),
+                    allowed_models: vec!["gpt-4o-mini".to_string()],
+                    quota: None,
+                    output_filter: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(output.name, "Acme Corp");
+        assert_eq!(
+            repository.list_tenants().await.unwrap()[0].name,
+            "Acme Corp"
+        );
+        assert_eq!(
+            tenants.find(acme.id).unwrap().config.allowed_models,
+            vec!["gpt-4o-mini".to_string()]
+        );
+        assert_eq!(
+            rate_limiter.config_for(acme.id),
+            RateLimitConfig {
+                requests_per_minute: 0,
+                tokens_per_minute: 1000,
+            }
+        );
+
+        let missing = Uuid::new_v4();
+        let input = TenantInputDTO {
+            name: "Missing".to_string(),
+            model: None,
+            rate_limit: None,
+            allowed_models: vec![],
+            quota: None,
+            output_filter: None,
+        };
+        assert!(matches!(
+            usecase.execute(missing, input.clone()).await,
+            Err(UseCaseError::TenantNotFound(id)) if id == missing
+        ));
+        assert!(matches!(
+            usecase.execute(DEFAULT_TENANT_ID, input).await,
+            Err(UseCaseError::InvalidInput(_))
+        ));
+    }
+}
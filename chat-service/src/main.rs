@@ -1,3 +1,90 @@
-fn main() {
-    println!("Hello, world!");
+use std::error::Error;
+
+use clap::{Parser, Subcommand};
+
+use chat_service::internal::app::bootstrap::bootstrap;
+use chat_service::internal::config::loader;
+use chat_service::internal::domain::entity::model::ModelRegistry;
+use chat_service::internal::infra::repository::factory::Repositories;
+use chat_service::internal::infra::telemetry::{self, TelemetryConfig};
+
+/// Chat with language models over HTTP and gRPC
+#[derive(Debug, Parser)]
+#[command(name = "chat-service", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Serve the HTTP and gRPC APIs, the default
+    Serve,
+    /// Apply pending database migrations and print the schema version
+    Migrate {
+        /// Print the schema version without applying anything
+        #[arg(long)]
+        status: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    dotenvy::dotenv().ok();
+
+    let settings = match loader::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    telemetry::init(&TelemetryConfig {
+        service_name: settings.telemetry.service_name.clone(),
+        otlp_endpoint: settings.telemetry.otlp_endpoint.clone(),
+        log_filter: settings.telemetry.log_filter.clone(),
+        json_logs: settings.telemetry.json_logs,
+    })?;
+    ModelRegistry::register_all(settings.models.clone());
+    for pricing in &settings.pricing {
+        ModelRegistry::set_pricing(pricing);
+    }
+
+    let repositories = Repositories::connect(
+        settings.database.driver,
+        &settings.database.url,
+        settings.model()?,
+    )
+    .await?;
+    if let Some(Command::Migrate { status }) = cli.command {
+        let version = if status {
+            repositories.schema_version().await?
+        } else {
+            repositories.migrate().await?
+        };
+        println!("{}", version);
+        repositories.close().await;
+        telemetry::shutdown();
+        return Ok(());
+    }
+    let version = if settings.database.migrate_on_start {
+        repositories.migrate().await?
+    } else {
+        repositories.schema_version().await?
+    };
+    if version.is_behind() {
+        eprintln!(
+            "database is behind the service, {}, run chat-service migrate",
+            version
+        );
+        std::process::exit(1);
+    }
+    tracing::info!(%version, "database schema is up to date");
+
+    let container = bootstrap(settings, repositories).await?;
+    let result = container.serve().await;
+    telemetry::shutdown();
+
+    Ok(result?)
 }
@@ -0,0 +1,335 @@
+// Contract tests of the OpenAI gateway against a mock server: the answers the API gives, and the
+// ways it fails, must come out of the gateway as the same messages, retries and errors
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use chat_service::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+use chat_service::internal::domain::entity::message::{Message, Role};
+use chat_service::internal::domain::entity::model::Model;
+use chat_service::internal::domain::gateway::chat_completion::{
+    ChatCompletionGateway, GatewayError,
+};
+use chat_service::internal::infra::http::retry::RetryPolicy;
+use chat_service::internal::infra::openai::chat_completion::OpenAIGateway;
+
+const API_KEY: &str = "sk-test";
+
+fn model() -> Model {
+    Model::new("gpt-4o".to_string(), 128000)
+}
+
+fn chat() -> Chat {
+    let message = |role: Role, content: &str| {
+        Message::new(
+            Uuid::new_v4(),
+            role,
+            content,
+            0,
+            model(),
+            chrono::Utc::now(),
+        )
+    };
+
+    Chat::new(
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        message(Role::System, "You are a helpful assistant."),
+        vec![message(Role::User, "Hello!")],
+        vec![],
+        ChatStatus::Active,
+        0,
+        ChatConfig::default_for(model()),
+    )
+}
+
+// gateway retries quickly so the retry scenarios do not slow the suite down
+fn gateway(server: &MockServer) -> OpenAIGateway {
+    OpenAIGateway::with_base_url(API_KEY.to_string(), server.uri()).with_retry_policy(RetryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        max_elapsed: Duration::from_secs(5),
+    })
+}
+
+fn completion(content: &str) -> serde_json::Value {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1700000000,
+        "model": "gpt-4o",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 12, "completion_tokens": 8, "total_tokens": 20}
+    })
+}
+
+// event is a server-sent event carrying a chunk with the given content delta
+fn event(content: &str) -> String {
+    let chunk = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion.chunk",
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}]
+    });
+
+    format!("data: {}\n\n", chunk)
+}
+
+// finish is a server-sent event carrying the last chunk of a reply, with its finish reason
+fn finish() -> String {
+    let chunk = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion.chunk",
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}]
+    });
+
+    format!("data: {}\n\n", chunk)
+}
+
+fn stream_response(body: String) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_string(body)
+}
+
+#[tokio::test]
+async fn test_completion() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", API_KEY).as_str(),
+        ))
+        .and(body_partial_json(json!({"model": "gpt-4o"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("Hi, how can I help?")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let message = gateway(&server)
+        .create_chat_completion(&chat())
+        .await
+        .unwrap();
+
+    assert_eq!(message.role, Role::Assistant);
+    assert_eq!(message.content, "Hi, how can I help?");
+}
+
+#[tokio::test]
+async fn test_rate_limit_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "0")
+                .set_body_json(json!({"error": {"type": "rate_limit_exceeded"}})),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("Hi!")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let message = gateway(&server)
+        .create_chat_completion(&chat())
+        .await
+        .unwrap();
+
+    assert_eq!(message.content, "Hi!");
+}
+
+#[tokio::test]
+async fn test_rate_limit_exhausts_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let err = gateway(&server)
+        .create_chat_completion(&chat())
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, GatewayError::Api { status: 429, .. }),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_client_error_is_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = gateway(&server)
+        .create_chat_completion(&chat())
+        .await
+        .unwrap_err();
+
+    match err {
+        GatewayError::Api { status, body } => {
+            assert_eq!(status, 401);
+            assert_eq!(body, "invalid api key");
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_json() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "application/json")
+                .set_body_string(r#"{"id": "chatcmpl-1", "choices": ["#),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = gateway(&server)
+        .create_chat_completion(&chat())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GatewayError::Request(_)), "{}", err);
+}
+
+#[tokio::test]
+async fn test_stream() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stream": true})))
+        .respond_with(stream_response(format!(
+            "{}{}data: [DONE]\n\n",
+            event("Hi, "),
+            event("how can I help?")
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (sender, mut receiver) = mpsc::channel(16);
+    let message = gateway(&server)
+        .create_chat_completion_stream(&chat(), sender)
+        .await
+        .unwrap();
+
+    assert_eq!(message.content, "Hi, how can I help?");
+    assert_eq!(receiver.recv().await.as_deref(), Some("Hi, "));
+    assert_eq!(receiver.recv().await.as_deref(), Some("how can I help?"));
+    assert_eq!(receiver.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_stream_finished_without_done() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(stream_response(format!("{}{}", event("Hi!"), finish())))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (sender, _receiver) = mpsc::channel(16);
+    let message = gateway(&server)
+        .create_chat_completion_stream(&chat(), sender)
+        .await
+        .unwrap();
+
+    assert_eq!(message.content, "Hi!");
+}
+
+#[tokio::test]
+async fn test_stream_ended_without_finish() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(stream_response(event("Hi, ")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (sender, _receiver) = mpsc::channel(16);
+    let err = gateway(&server)
+        .create_chat_completion_stream(&chat(), sender)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GatewayError::Request(_)), "{}", err);
+}
+
+#[tokio::test]
+async fn test_stream_disconnect() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(stream_response(format!(
+            "{}data: {{\"id\": \"chatcmpl-1\", \"choi",
+            event("Hi, ")
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (sender, mut receiver) = mpsc::channel(16);
+    let err = gateway(&server)
+        .create_chat_completion_stream(&chat(), sender)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GatewayError::Request(_)), "{}", err);
+    assert_eq!(receiver.recv().await.as_deref(), Some("Hi, "));
+}
+
+#[tokio::test]
+async fn test_stream_malformed_chunk() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(stream_response(format!(
+            "{}data: not json\n\ndata: [DONE]\n\n",
+            event("Hi, ")
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (sender, _receiver) = mpsc::channel(16);
+    let err = gateway(&server)
+        .create_chat_completion_stream(&chat(), sender)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, GatewayError::Request(_)), "{}", err);
+}
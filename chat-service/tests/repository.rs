@@ -0,0 +1,1319 @@
+// The same checks run against every repository backend compiled in; memory always runs, sqlite
+// when the tests are built with --features sqlite, postgres and mysql only when built with their
+// feature and TEST_POSTGRES_URL or TEST_MYSQL_URL point at a database
+use std::collections::BTreeMap;
+
+use chrono::{DurationRound, TimeZone};
+use uuid::Uuid;
+
+use chat_service::internal::domain::entity::api_key::ApiKey;
+use chat_service::internal::domain::entity::assistant::Assistant;
+use chat_service::internal::domain::entity::audit::{AuditDirection, AuditEntry};
+use chat_service::internal::domain::entity::batch::{Batch, BatchStatus};
+use chat_service::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus};
+use chat_service::internal::domain::entity::event::{ChatEvent, OutboxEvent};
+use chat_service::internal::domain::entity::job::{Job, JobStatus};
+use chat_service::internal::domain::entity::message::{Message, Role};
+use chat_service::internal::domain::entity::model::Model;
+use chat_service::internal::domain::entity::redaction::RedactionRecord;
+use chat_service::internal::domain::entity::scheduled_message::{
+    ScheduledMessage, ScheduledStatus,
+};
+use chat_service::internal::domain::entity::tenant::{Tenant, TenantConfig, DEFAULT_TENANT_ID};
+use chat_service::internal::domain::entity::usage::UsageRecord;
+use chat_service::internal::domain::entity::user::User;
+use chat_service::internal::domain::entity::user_memory::Memory;
+use chat_service::internal::domain::entity::webhook::{
+    DeadLetter, Webhook, WebhookDelivery, WebhookEvent, WebhookPayload,
+};
+use chat_service::internal::domain::output_filter::{OutputFilterConfig, OutputRule};
+use chat_service::internal::domain::quota::QuotaConfig;
+use chat_service::internal::domain::rate_limiter::RateLimitConfig;
+use chat_service::internal::domain::repository::api_key::ApiKeyRepository;
+use chat_service::internal::domain::repository::assistant::AssistantRepository;
+use chat_service::internal::domain::repository::audit::{AuditCursor, AuditQuery, AuditRepository};
+use chat_service::internal::domain::repository::batch::BatchRepository;
+use chat_service::internal::domain::repository::chat::{
+    ChatCursor, ChatRepository, MessageQuery, RepositoryError,
+};
+use chat_service::internal::domain::repository::job::JobRepository;
+use chat_service::internal::domain::repository::redaction::RedactionRepository;
+use chat_service::internal::domain::repository::scheduled_message::ScheduledMessageRepository;
+use chat_service::internal::domain::repository::tenant::TenantRepository;
+use chat_service::internal::domain::repository::usage::UsageRepository;
+use chat_service::internal::domain::repository::user::UserRepository;
+use chat_service::internal::domain::repository::user_memory::MemoryRepository;
+use chat_service::internal::domain::repository::webhook::WebhookRepository;
+use chat_service::internal::infra::repository::driver::DatabaseDriver;
+use chat_service::internal::infra::repository::factory::Repositories;
+
+fn model() -> Model {
+    Model::new("gpt-3.5-turbo".to_string(), 4096)
+}
+
+fn message(role: Role, content: &str) -> Message {
+    Message::new(
+        Uuid::new_v4(),
+        role,
+        content,
+        0,
+        model(),
+        chrono::Utc::now(),
+    )
+}
+
+fn new_chat(user_id: Uuid) -> Chat {
+    Chat::new(
+        Uuid::new_v4(),
+        user_id,
+        message(Role::System, "You are a helpful assistant."),
+        vec![],
+        vec![],
+        ChatStatus::Active,
+        0,
+        ChatConfig::default_for(model()),
+    )
+}
+
+async fn new_user(users: &dyn UserRepository) -> User {
+    let id = Uuid::new_v4();
+    let user = User::new(id, &id.to_string(), "Ada", chrono::Utc::now());
+    users.create_user(&user).await.unwrap();
+    user
+}
+
+// check_users runs the UserRepository checks
+async fn check_users(users: &dyn UserRepository) {
+    let user = new_user(users).await;
+
+    let found = users
+        .find_user_by_id(DEFAULT_TENANT_ID, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.external_id, user.external_id);
+    assert_eq!(found.display_name, "Ada");
+    let found = users
+        .find_user_by_external_id(DEFAULT_TENANT_ID, &user.external_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, user.id);
+
+    assert!(users
+        .find_user_by_id(Uuid::new_v4(), user.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(users
+        .find_user_by_external_id(DEFAULT_TENANT_ID, "nobody")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+// check_chats runs the ChatRepository checks, timestamps are compared loosely as backends
+// store them with different precision
+async fn check_chats(chats: &dyn ChatRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let mut chat = new_chat(user.id);
+    chats.create_chat(&chat).await.unwrap();
+
+    chat.add_message(message(Role::User, "Hello!").with_language("en"))
+        .unwrap();
+    chat.add_message(
+        message(Role::Assistant, "Hi, how can I help?")
+            .with_candidates(vec!["Hello there!".to_string()])
+            .with_interrupted()
+            .with_speaker("Support bot"),
+    )
+    .unwrap();
+    chat.add_message(message(Role::User, "What time is it?"))
+        .unwrap();
+    chat.set_response_language(Some("fr".to_string())).unwrap();
+    chats.save_chat(&mut chat).await.unwrap();
+
+    let found = chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.user_id, user.id);
+    assert_eq!(found.status, ChatStatus::Active);
+    assert_eq!(found.config.model.name, chat.config.model.name);
+    assert_eq!(found.config.temperature, chat.config.temperature);
+    assert_eq!(found.version, chat.version);
+    assert_eq!(found.messages[1].candidates, vec!["Hello there!"]);
+    assert!(found.messages[1].interrupted);
+    assert!(!found.messages[0].interrupted);
+    assert_eq!(found.messages[1].speaker.as_deref(), Some("Support bot"));
+    assert_eq!(found.messages[0].speaker, None);
+    assert_eq!(found.config.response_language.as_deref(), Some("fr"));
+    assert_eq!(found.messages[0].language.as_deref(), Some("en"));
+    assert_eq!(found.messages[1].language, None);
+    assert_eq!(
+        found
+            .messages
+            .iter()
+            .map(|message| (message.id, message.content.as_str()))
+            .collect::<Vec<_>>(),
+        chat.messages
+            .iter()
+            .map(|message| (message.id, message.content.as_str()))
+            .collect::<Vec<_>>()
+    );
+    assert!(chats
+        .find_chat_by_id(Uuid::new_v4(), chat.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    chats
+        .update_chat_title(DEFAULT_TENANT_ID, chat.id, "Greetings")
+        .await
+        .unwrap();
+    chats.save_chat(&mut chat).await.unwrap();
+    // the copy loaded before that save is stale now
+    let mut stale = found;
+    assert!(matches!(
+        chats.save_chat(&mut stale).await,
+        Err(RepositoryError::ConcurrentModification(id)) if id == chat.id
+    ));
+    let summary = chats
+        .find_chat_summary(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.title.as_deref(), Some("Greetings"));
+    assert_eq!(summary.message_count, 3);
+    assert_eq!(summary.token_usage, chat.token_usage);
+    assert_eq!(
+        summary.last_message_preview.as_deref(),
+        Some("What time is it?")
+    );
+
+    let page = chats
+        .list_messages(
+            DEFAULT_TENANT_ID,
+            chat.id,
+            &MessageQuery {
+                roles: vec![Role::User],
+                from: None,
+                to: None,
+                after: None,
+                limit: 10,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        page.iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Hello!", "What time is it?"]
+    );
+    let page = chats
+        .list_messages(
+            DEFAULT_TENANT_ID,
+            chat.id,
+            &MessageQuery {
+                roles: vec![],
+                from: None,
+                to: None,
+                after: Some(chat.messages[0].id),
+                limit: 1,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].id, chat.messages[1].id);
+
+    let found = chats
+        .search_transcripts(DEFAULT_TENANT_ID, user.id, "Time", 10)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].chat_id, chat.id);
+    assert_eq!(found[0].message_id, chat.messages[2].id);
+    assert_eq!(found[0].chat_title.as_deref(), Some("Greetings"));
+    assert!(found[0].highlight.contains("<mark>time</mark>"));
+    // the system message is not part of the transcript
+    assert!(chats
+        .search_transcripts(DEFAULT_TENANT_ID, user.id, "helpful", 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(chats
+        .search_transcripts(Uuid::new_v4(), user.id, "time", 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let mut other = new_chat(user.id);
+    other.set_tags(&["support".to_string()]).unwrap();
+    other
+        .set_metadata(BTreeMap::from([("ticket".to_string(), "T-42".to_string())]))
+        .unwrap();
+    chats.create_chat(&other).await.unwrap();
+    let first = chats
+        .list_chat_summaries(DEFAULT_TENANT_ID, user.id, None, None, 1)
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 1);
+    let second = chats
+        .list_chat_summaries(
+            DEFAULT_TENANT_ID,
+            user.id,
+            None,
+            Some(ChatCursor::of(&first[0])),
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(second.len(), 1);
+    assert_ne!(first[0].id, second[0].id);
+
+    let tagged = chats
+        .list_chat_summaries(DEFAULT_TENANT_ID, user.id, Some("support"), None, 10)
+        .await
+        .unwrap();
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].id, other.id);
+    assert_eq!(tagged[0].tags, vec!["support"]);
+    assert_eq!(tagged[0].metadata["ticket"], "T-42");
+    other.set_tags(&["billing".to_string()]).unwrap();
+    chats.save_chat(&mut other).await.unwrap();
+    let stored = chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, other.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.tags, vec!["billing"]);
+    assert_eq!(stored.metadata, other.metadata);
+    assert!(chats
+        .list_chat_summaries(DEFAULT_TENANT_ID, user.id, Some("support"), None, 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        chats
+            .list_chats_by_user(DEFAULT_TENANT_ID, user.id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let inactive_before = chrono::Utc::now() + chrono::Duration::seconds(1);
+    let mut cold = chats
+        .list_inactive_chats(inactive_before, 1000)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|inactive| inactive.id == chat.id)
+        .unwrap();
+    let (messages, erased_messages) = (cold.messages.clone(), cold.erased_messages.clone());
+    cold.archive_history(format!("{}/{}.jsonl", cold.tenant_id, cold.id))
+        .unwrap();
+    chats.archive_chat_history(&mut cold).await.unwrap();
+    let found = chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(found.is_cold());
+    assert!(found.messages.is_empty());
+    assert_eq!(found.status, ChatStatus::Archived);
+    assert_eq!(found.version, cold.version);
+    let summary = chats
+        .find_chat_summary(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary.title.as_deref(), Some("Greetings"));
+    assert_eq!(summary.message_count, 3);
+    assert_eq!(
+        summary.last_message_preview.as_deref(),
+        Some("What time is it?")
+    );
+    assert!(!chats
+        .list_inactive_chats(inactive_before, 1000)
+        .await
+        .unwrap()
+        .iter()
+        .any(|inactive| inactive.id == chat.id));
+
+    let mut chat = found;
+    chat.restore_history(messages, erased_messages);
+    chat.reopen().unwrap();
+    chats.save_chat(&mut chat).await.unwrap();
+    let found = chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!found.is_cold());
+    assert_eq!(found.messages.len(), 3);
+    assert_eq!(found.messages[1].candidates, vec!["Hello there!"]);
+
+    chat.status = ChatStatus::Deleted;
+    chats.save_chat(&mut chat).await.unwrap();
+    assert_eq!(
+        chats
+            .list_chats_by_user(DEFAULT_TENANT_ID, user.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    let purged = chats
+        .purge_deleted_chats(chrono::Utc::now() + chrono::Duration::seconds(1))
+        .await
+        .unwrap();
+    assert!(purged.iter().any(|purged| purged.id == chat.id));
+    assert!(chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+// check_unit_of_work runs the UnitOfWork checks, a dropped transaction writes nothing and a
+// committed one writes the chat and its usage together
+async fn check_unit_of_work(repositories: &Repositories) {
+    let user = new_user(repositories.users.as_ref()).await;
+    let mut chat = new_chat(user.id);
+    chat.add_message(message(Role::User, "Hello!")).unwrap();
+    let record = UsageRecord {
+        tenant_id: DEFAULT_TENANT_ID,
+        user_id: user.id,
+        chat_id: chat.id,
+        model: model().name,
+        prompt_tokens: 10,
+        completion_tokens: 5,
+        cost: 0.5,
+        created_at: chrono::Utc::now(),
+    };
+
+    let mut tx = repositories.unit_of_work.begin().await.unwrap();
+    tx.create_chat(&chat).await.unwrap();
+    tx.save_chat(&mut chat).await.unwrap();
+    tx.record_usage(&record).await.unwrap();
+    drop(tx);
+    assert!(repositories
+        .chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(repositories
+        .usage
+        .list_chat_usage(DEFAULT_TENANT_ID, &[chat.id])
+        .await
+        .unwrap()
+        .is_empty());
+
+    let mut tx = repositories.unit_of_work.begin().await.unwrap();
+    tx.create_chat(&chat).await.unwrap();
+    tx.save_chat(&mut chat).await.unwrap();
+    tx.record_usage(&record).await.unwrap();
+    tx.commit().await.unwrap();
+    let found = repositories
+        .chats
+        .find_chat_by_id(DEFAULT_TENANT_ID, chat.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.messages.len(), 1);
+    let usage = repositories
+        .usage
+        .list_chat_usage(DEFAULT_TENANT_ID, &[chat.id])
+        .await
+        .unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].requests, 1);
+}
+
+// check_audit runs the AuditRepository checks, entries come back as appended oldest first
+async fn check_audit(audit: &dyn AuditRepository) {
+    let chat_id = Uuid::new_v4();
+    let request_id = Uuid::new_v4();
+    // timestamps are kept to the microsecond, the precision every backend stores
+    let now = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let prompt = AuditEntry {
+        id: Uuid::new_v4(),
+        request_id,
+        tenant_id: DEFAULT_TENANT_ID,
+        user_id: Uuid::new_v4(),
+        chat_id,
+        direction: AuditDirection::Prompt,
+        provider: "openai".to_string(),
+        model: model().name,
+        content: r#"[{"role":"user","content":"Hello!"}]"#.to_string(),
+        error: None,
+        latency_ms: None,
+        prompt_tokens: 12,
+        completion_tokens: 0,
+        created_at: now,
+    };
+    let response = AuditEntry {
+        id: Uuid::new_v4(),
+        direction: AuditDirection::Response,
+        content: "Hi, how can I help?".to_string(),
+        latency_ms: Some(250),
+        completion_tokens: 6,
+        created_at: now + chrono::Duration::milliseconds(250),
+        ..prompt.clone()
+    };
+    audit.append(&response).await.unwrap();
+    audit.append(&prompt).await.unwrap();
+
+    let query = AuditQuery {
+        tenant_id: Some(DEFAULT_TENANT_ID),
+        user_id: None,
+        chat_id: Some(chat_id),
+        request_id: None,
+        from: None,
+        to: None,
+        after: None,
+        limit: 10,
+    };
+    let entries = audit.list_entries(&query).await.unwrap();
+    assert_eq!(entries, vec![prompt.clone(), response.clone()]);
+
+    let page = audit
+        .list_entries(&AuditQuery {
+            after: Some(AuditCursor::of(&prompt)),
+            ..query.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(page, vec![response.clone()]);
+    let before = audit
+        .list_entries(&AuditQuery {
+            to: Some(response.created_at),
+            ..query.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(before, vec![prompt]);
+    let elsewhere = audit
+        .list_entries(&AuditQuery {
+            from: Some(chrono::Utc.timestamp_opt(0, 0).unwrap()),
+            request_id: Some(Uuid::new_v4()),
+            ..query
+        })
+        .await
+        .unwrap();
+    assert!(elsewhere.is_empty());
+}
+
+// check_redactions runs the RedactionRepository checks, the ciphertext must come back byte
+// for byte
+async fn check_redactions(redactions: &dyn RedactionRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let record = RedactionRecord {
+        id: Uuid::new_v4(),
+        tenant_id: DEFAULT_TENANT_ID,
+        user_id: user.id,
+        chat_id: None,
+        message_id: Uuid::new_v4(),
+        findings: vec!["email".to_string(), "credit_card".to_string()],
+        ciphertext: vec![0, 159, 146, 150, 255, 10],
+        created_at: chrono::Utc::now()
+            .duration_trunc(chrono::Duration::microseconds(1))
+            .unwrap(),
+    };
+    redactions.record_redaction(&record).await.unwrap();
+
+    let found = redactions
+        .find_redaction(DEFAULT_TENANT_ID, record.message_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found, record);
+
+    assert!(redactions
+        .find_redaction(Uuid::new_v4(), record.message_id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+// check_memories runs the MemoryRepository checks, memories come back oldest first
+async fn check_memories(memories: &dyn MemoryRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let chat_id = Uuid::new_v4();
+    let created_at = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let older = Memory {
+        created_at: created_at - chrono::Duration::seconds(1),
+        ..Memory::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            chat_id,
+            "The user lives in Lisbon.",
+        )
+    };
+    let newer = Memory {
+        created_at,
+        ..Memory::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            chat_id,
+            "The user is called Ana.",
+        )
+    };
+    // the user has room for two memories, the third one is not stored
+    let third = Memory::new(DEFAULT_TENANT_ID, user.id, chat_id, "The user has a cat.");
+    assert_eq!(
+        memories
+            .create_memories(&[newer.clone(), older.clone(), third.clone()], 2)
+            .await
+            .unwrap(),
+        2
+    );
+    assert!(memories
+        .find_memory_by_id(DEFAULT_TENANT_ID, third.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    let listed = memories
+        .list_memories_by_user(DEFAULT_TENANT_ID, user.id)
+        .await
+        .unwrap();
+    assert_eq!(listed, vec![older.clone(), newer.clone()]);
+    assert_eq!(
+        memories
+            .find_memory_by_id(DEFAULT_TENANT_ID, newer.id)
+            .await
+            .unwrap(),
+        Some(newer.clone())
+    );
+    assert!(memories
+        .find_memory_by_id(Uuid::new_v4(), newer.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    memories
+        .delete_memory(DEFAULT_TENANT_ID, older.id)
+        .await
+        .unwrap();
+    let listed = memories
+        .list_memories_by_user(DEFAULT_TENANT_ID, user.id)
+        .await
+        .unwrap();
+    assert_eq!(listed, vec![newer]);
+}
+
+// check_scheduled_messages runs the ScheduledMessageRepository checks, a message changes status
+// only from the status it is in
+async fn check_scheduled_messages(
+    scheduled: &dyn ScheduledMessageRepository,
+    chats: &dyn ChatRepository,
+    users: &dyn UserRepository,
+) {
+    let user = new_user(users).await;
+    let chat = new_chat(user.id);
+    chats.create_chat(&chat).await.unwrap();
+    let now = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let sooner = ScheduledMessage {
+        created_at: now,
+        ..ScheduledMessage::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            chat.id,
+            "Good morning",
+            now + chrono::Duration::minutes(1),
+        )
+        .unwrap()
+    };
+    let later = ScheduledMessage {
+        created_at: now,
+        ..ScheduledMessage::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            chat.id,
+            "Good night",
+            now + chrono::Duration::hours(12),
+        )
+        .unwrap()
+    };
+    scheduled.create_scheduled_message(&later).await.unwrap();
+    scheduled.create_scheduled_message(&sooner).await.unwrap();
+
+    let pending = scheduled
+        .list_pending_by_user(DEFAULT_TENANT_ID, user.id)
+        .await
+        .unwrap();
+    assert_eq!(pending, vec![sooner.clone(), later.clone()]);
+    let due = scheduled
+        .list_due(now + chrono::Duration::minutes(2), 10)
+        .await
+        .unwrap();
+    assert!(due.iter().any(|due| due.id == sooner.id));
+    assert!(!due.iter().any(|due| due.id == later.id));
+
+    let claimed_until = now + chrono::Duration::minutes(7);
+    assert!(scheduled
+        .claim_due(
+            DEFAULT_TENANT_ID,
+            sooner.id,
+            now + chrono::Duration::minutes(2),
+            claimed_until,
+        )
+        .await
+        .unwrap());
+    assert!(!scheduled
+        .claim_due(
+            DEFAULT_TENANT_ID,
+            sooner.id,
+            now + chrono::Duration::minutes(2),
+            claimed_until,
+        )
+        .await
+        .unwrap());
+    // a claim that is over lets another worker claim the message
+    let later_on = claimed_until + chrono::Duration::seconds(1);
+    assert!(scheduled
+        .list_due(later_on, 10)
+        .await
+        .unwrap()
+        .iter()
+        .any(|due| due.id == sooner.id));
+    assert!(scheduled
+        .claim_due(
+            DEFAULT_TENANT_ID,
+            sooner.id,
+            later_on,
+            later_on + chrono::Duration::minutes(5),
+        )
+        .await
+        .unwrap());
+    assert!(!scheduled
+        .transition(
+            DEFAULT_TENANT_ID,
+            sooner.id,
+            ScheduledStatus::Pending,
+            ScheduledStatus::Cancelled,
+            None,
+        )
+        .await
+        .unwrap());
+    assert!(scheduled
+        .transition(
+            DEFAULT_TENANT_ID,
+            sooner.id,
+            ScheduledStatus::Sending,
+            ScheduledStatus::Failed,
+            Some("model is unavailable"),
+        )
+        .await
+        .unwrap());
+
+    let failed = scheduled
+        .find_scheduled_message_by_id(DEFAULT_TENANT_ID, sooner.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(failed.status, ScheduledStatus::Failed);
+    assert_eq!(failed.error.as_deref(), Some("model is unavailable"));
+    assert_eq!(failed.claimed_until, None);
+    assert!(scheduled
+        .find_scheduled_message_by_id(Uuid::new_v4(), sooner.id)
+        .await
+        .unwrap()
+        .is_none());
+    let pending = scheduled
+        .list_pending_by_user(DEFAULT_TENANT_ID, user.id)
+        .await
+        .unwrap();
+    assert_eq!(pending, vec![later]);
+}
+
+// check_tenants runs the TenantRepository checks, saving a tenant again replaces it
+async fn check_tenants(tenants: &dyn TenantRepository) {
+    let mut tenant = Tenant::new(
+        Uuid::new_v4(),
+        "Acme",
+        TenantConfig {
+            model: Some(Model::new("gpt-4o".to_string(), 128000)),
+            rate_limit: Some(RateLimitConfig {
+                requests_per_minute: 10,
+                tokens_per_minute: 0,
+            }),
+            allowed_models: vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()],
+            quota: Some(QuotaConfig {
+                user_daily_tokens: 1000,
+                user_monthly_tokens: 0,
+                tenant_daily_tokens: 0,
+                tenant_monthly_tokens: 1_000_000,
+            }),
+            output_filter: Some(OutputFilterConfig {
+                rules: vec![OutputRule {
+                    name: "ticket".to_string(),
+                    pattern: r"TICKET-\d+".to_string(),
+                    replacement: None,
+                }],
+                max_length: 2000,
+                ..Default::default()
+            }),
+        },
+    );
+    tenants.save_tenant(&tenant).await.unwrap();
+
+    let find = |stored: Vec<Tenant>, id: Uuid| stored.into_iter().find(|tenant| tenant.id == id);
+    let found = find(tenants.list_tenants().await.unwrap(), tenant.id).unwrap();
+    assert_eq!(found, tenant);
+
+    tenant.name = "Acme Corp".to_string();
+    tenant.config = TenantConfig::default();
+    tenants.save_tenant(&tenant).await.unwrap();
+    let found = find(tenants.list_tenants().await.unwrap(), tenant.id).unwrap();
+    assert_eq!(found, tenant);
+}
+
+// check_api_keys runs the ApiKeyRepository checks, revoking keeps the given key working
+async fn check_api_keys(api_keys: &dyn ApiKeyRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let (old, _) = ApiKey::generate(DEFAULT_TENANT_ID, user.id);
+    let (new, _) = ApiKey::generate(DEFAULT_TENANT_ID, user.id);
+    api_keys.create_api_key(&old).await.unwrap();
+    api_keys.create_api_key(&new).await.unwrap();
+
+    let revoked = api_keys
+        .revoke_api_keys(DEFAULT_TENANT_ID, user.id, new.id, chrono::Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(revoked, 1);
+
+    let old = api_keys
+        .find_api_key_by_hash(&old.key_hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(old.revoked_at.is_some());
+    let new = api_keys
+        .find_api_key_by_hash(&new.key_hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(new.revoked_at.is_none());
+}
+
+// check_usage_summary runs the usage summary and per user summary checks on a model of its
+// own, other runs may have recorded usage in the same tenant
+async fn check_usage_summary(
+    usage: &dyn UsageRepository,
+    chats: &dyn ChatRepository,
+    users: &dyn UserRepository,
+) {
+    let model = format!("gpt-4o-{}", Uuid::new_v4().simple());
+    let today = chrono::Utc::now().date_naive();
+    let mut user_ids = vec![];
+    for _ in 0..2 {
+        let user = new_user(users).await;
+        user_ids.push(user.id);
+        let chat = new_chat(user.id);
+        chats.create_chat(&chat).await.unwrap();
+        for _ in 0..2 {
+            usage
+                .record_usage(&UsageRecord {
+                    tenant_id: DEFAULT_TENANT_ID,
+                    user_id: user.id,
+                    chat_id: chat.id,
+                    model: model.clone(),
+                    prompt_tokens: 100,
+                    completion_tokens: 50,
+                    cost: 0.25,
+                    created_at: chrono::Utc::now(),
+                })
+                .await
+                .unwrap();
+        }
+    }
+
+    let summary = usage
+        .summarize_usage(Some(DEFAULT_TENANT_ID), today, today)
+        .await
+        .unwrap();
+    let found = summary.iter().find(|usage| usage.model == model).unwrap();
+    assert_eq!(found.tenant_id, DEFAULT_TENANT_ID);
+    assert_eq!(
+        (
+            found.users,
+            found.requests,
+            found.prompt_tokens,
+            found.completion_tokens
+        ),
+        (2, 4, 400, 200)
+    );
+    assert!((found.cost - 1.0).abs() < 1e-9);
+
+    assert!(usage
+        .summarize_usage(Some(Uuid::new_v4()), today, today)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(usage
+        .summarize_usage(None, today, today)
+        .await
+        .unwrap()
+        .iter()
+        .any(|usage| usage.model == model));
+
+    let per_user = usage
+        .summarize_user_usage(Some(DEFAULT_TENANT_ID), today, today)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|usage| usage.model == model)
+        .collect::<Vec<_>>();
+    assert_eq!(per_user.len(), 2);
+    for found in &per_user {
+        assert!(user_ids.contains(&found.user_id));
+        assert_eq!(
+            (found.requests, found.prompt_tokens, found.completion_tokens),
+            (2, 200, 100)
+        );
+        assert!((found.cost - 0.5).abs() < 1e-9);
+    }
+    assert!(usage
+        .summarize_user_usage(Some(Uuid::new_v4()), today, today)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+// check_webhooks runs the WebhookRepository checks, a claimed delivery cannot be claimed again
+// and dead letters outlive their webhook
+async fn check_webhooks(webhooks: &dyn WebhookRepository) {
+    let tenant_id = Uuid::new_v4();
+    let now = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let webhook = Webhook {
+        created_at: now,
+        ..Webhook::new(
+            tenant_id,
+            "https://example.com/hooks",
+            None,
+            &[WebhookEvent::ChatCreated, WebhookEvent::ChatEnded],
+        )
+        .unwrap()
+    };
+    webhooks.create_webhook(&webhook).await.unwrap();
+    assert_eq!(
+        webhooks.list_webhooks(tenant_id).await.unwrap(),
+        vec![webhook.clone()]
+    );
+    assert_eq!(
+        webhooks
+            .find_webhook_by_id(tenant_id, webhook.id)
+            .await
+            .unwrap(),
+        Some(webhook.clone())
+    );
+
+    let outbox = OutboxEvent {
+        id: Uuid::new_v4(),
+        tenant_id,
+        user_id: Uuid::new_v4(),
+        chat_id: Uuid::new_v4(),
+        event: ChatEvent::ChatEnded,
+        occurred_at: now,
+    };
+    let delivery = WebhookDelivery {
+        next_attempt_at: now,
+        created_at: now,
+        ..WebhookDelivery::new(
+            &webhook,
+            &WebhookPayload::new(WebhookEvent::ChatEnded, &outbox),
+        )
+        .unwrap()
+    };
+    webhooks
+        .enqueue_deliveries(std::slice::from_ref(&delivery))
+        .await
+        .unwrap();
+    let due = webhooks
+        .due_deliveries(now + chrono::Duration::seconds(1), 100)
+        .await
+        .unwrap();
+    assert!(due.contains(&delivery));
+
+    let lease_until = now + chrono::Duration::minutes(5);
+    assert!(webhooks
+        .claim_delivery(delivery.id, delivery.next_attempt_at, lease_until)
+        .await
+        .unwrap());
+    assert!(!webhooks
+        .claim_delivery(delivery.id, delivery.next_attempt_at, lease_until)
+        .await
+        .unwrap());
+
+    let retried = WebhookDelivery {
+        attempts: 1,
+        next_attempt_at: now + chrono::Duration::seconds(30),
+        last_error: Some("503 Service Unavailable".to_string()),
+        ..delivery.clone()
+    };
+    webhooks.reschedule_delivery(&retried).await.unwrap();
+    let due = webhooks
+        .due_deliveries(now + chrono::Duration::minutes(1), 100)
+        .await
+        .unwrap();
+    assert!(due.contains(&retried));
+
+    let dead_letter = DeadLetter {
+        delivery: WebhookDelivery {
+            attempts: 2,
+            ..retried
+        },
+        failed_at: now,
+    };
+    webhooks.dead_letter(&dead_letter).await.unwrap();
+    assert!(!webhooks
+        .due_deliveries(now + chrono::Duration::hours(1), 100)
+        .await
+        .unwrap()
+        .iter()
+        .any(|due| due.id == delivery.id));
+    assert_eq!(
+        webhooks.list_dead_letters(tenant_id, 10).await.unwrap(),
+        vec![dead_letter]
+    );
+
+    webhooks
+        .delete_webhook(tenant_id, webhook.id)
+        .await
+        .unwrap();
+    assert!(webhooks.list_webhooks(tenant_id).await.unwrap().is_empty());
+    assert_eq!(
+        webhooks
+            .list_dead_letters(tenant_id, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+// check_batches runs the BatchRepository checks, completing a batch saves its answers
+async fn check_assistants(
+    assistants: &dyn AssistantRepository,
+    chats: &dyn ChatRepository,
+    users: &dyn UserRepository,
+) {
+    let tenant_id = Uuid::new_v4();
+    let now = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let support = Assistant::new(
+        Uuid::new_v4(),
+        tenant_id,
+        "support",
+        "You help {{user_name}}.",
+        now,
+    );
+    let billing = Assistant::new(Uuid::new_v4(), tenant_id, "billing", "You bill.", now)
+        .with_model(Some("gpt-4o".to_string()))
+        .with_temperature(Some(0.5))
+        .with_tools(vec!["get_invoice".to_string()]);
+    assistants.create_assistant(&support).await.unwrap();
+    assistants.create_assistant(&billing).await.unwrap();
+    assert!(assistants
+        .create_assistant(&Assistant {
+            id: Uuid::new_v4(),
+            ..support.clone()
+        })
+        .await
+        .is_err());
+    assert_eq!(
+        assistants.list_assistants(tenant_id).await.unwrap(),
+        vec![billing.clone(), support.clone()]
+    );
+    assert_eq!(
+        assistants
+            .find_assistant_by_id(Uuid::new_v4(), billing.id)
+            .await
+            .unwrap(),
+        None
+    );
+
+    let updated = Assistant {
+        instructions: "You bill and refund.".to_string(),
+        updated_at: now + chrono::Duration::seconds(1),
+        ..billing.clone().with_model(None).with_tools(vec![])
+    };
+    assistants.update_assistant(&updated).await.unwrap();
+    assert_eq!(
+        assistants
+            .find_assistant_by_id(tenant_id, billing.id)
+            .await
+            .unwrap(),
+        Some(updated)
+    );
+
+    let user = new_user(users).await;
+    let chat = new_chat(user.id)
+        .with_tenant(tenant_id)
+        .with_assistant(Some(support.id));
+    chats.create_chat(&chat).await.unwrap();
+    assert_eq!(
+        chats
+            .find_chat_by_id(tenant_id, chat.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .assistant_id,
+        Some(support.id)
+    );
+
+    assistants
+        .delete_assistant(tenant_id, support.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        assistants.list_assistants(tenant_id).await.unwrap().len(),
+        1
+    );
+}
+
+async fn check_batches(batches: &dyn BatchRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let created_at = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let mut batch = Batch {
+        created_at,
+        ..Batch::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            vec!["France?".to_string(), "Spain?".to_string()],
+        )
+        .unwrap()
+    };
+    batches.create_batch(&batch).await.unwrap();
+    assert_eq!(
+        batches
+            .find_batch_by_id(DEFAULT_TENANT_ID, batch.id)
+            .await
+            .unwrap(),
+        Some(batch.clone())
+    );
+    assert!(batches
+        .find_batch_by_id(Uuid::new_v4(), batch.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    let mut items = batch.items.clone();
+    items[0].chat_id = Some(Uuid::new_v4());
+    items[0].content = Some("Paris".to_string());
+    items[1].error = Some("model provider returned an empty response".to_string());
+    batch.complete(items);
+    batch.completed_at = batch.completed_at.map(|at| {
+        at.duration_trunc(chrono::Duration::microseconds(1))
+            .unwrap()
+    });
+    batches.complete_batch(&batch).await.unwrap();
+
+    let completed = batches
+        .find_batch_by_id(DEFAULT_TENANT_ID, batch.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(completed, batch);
+    assert_eq!(completed.status, BatchStatus::Completed);
+}
+
+// check_jobs runs the JobRepository checks, a queued job is claimed once and completing it
+// saves its answer, under the claim it was last given only
+async fn check_jobs(jobs: &dyn JobRepository, users: &dyn UserRepository) {
+    let user = new_user(users).await;
+    let created_at = chrono::Utc::now()
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    let mut job = Job {
+        created_at,
+        ..Job::new(
+            DEFAULT_TENANT_ID,
+            user.id,
+            None,
+            "Summarize the report",
+            vec![],
+            serde_json::json!({"temperature": 0.2}),
+        )
+        .unwrap()
+    };
+    jobs.create_job(&job).await.unwrap();
+    assert_eq!(
+        jobs.find_job_by_id(DEFAULT_TENANT_ID, job.id)
+            .await
+            .unwrap(),
+        Some(job.clone())
+    );
+    assert!(jobs
+        .find_job_by_id(Uuid::new_v4(), job.id)
+        .await
+        .unwrap()
+        .is_none());
+    let now = chrono::Utc::now();
+    let claimed_until = now + chrono::Duration::seconds(60);
+    assert!(jobs
+        .list_claimable(now, 100)
+        .await
+        .unwrap()
+        .iter()
+        .any(|queued| queued.id == job.id));
+
+    assert!(jobs
+        .claim_job(DEFAULT_TENANT_ID, job.id, now, claimed_until)
+        .await
+        .unwrap());
+    assert!(!jobs
+        .claim_job(DEFAULT_TENANT_ID, job.id, now, claimed_until)
+        .await
+        .unwrap());
+    assert!(!jobs
+        .list_claimable(now, 100)
+        .await
+        .unwrap()
+        .iter()
+        .any(|queued| queued.id == job.id));
+
+    // a claim that is over lets another worker claim the job
+    let later = claimed_until + chrono::Duration::seconds(1);
+    assert!(jobs
+        .list_claimable(later, 100)
+        .await
+        .unwrap()
+        .iter()
+        .any(|abandoned| abandoned.id == job.id));
+    let claimed_again = (later + chrono::Duration::seconds(60))
+        .duration_trunc(chrono::Duration::microseconds(1))
+        .unwrap();
+    assert!(jobs
+        .claim_job(DEFAULT_TENANT_ID, job.id, later, claimed_again)
+        .await
+        .unwrap());
+
+    job.succeed(Uuid::new_v4(), "The report says...".to_string());
+    job.completed_at = job.completed_at.map(|at| {
+        at.duration_trunc(chrono::Duration::microseconds(1))
+            .unwrap()
+    });
+    // the worker whose claim was over cannot complete the job anymore
+    assert!(!jobs.complete_job(&job, claimed_until).await.unwrap());
+    assert!(jobs.complete_job(&job, claimed_again).await.unwrap());
+
+    let completed = jobs
+        .find_job_by_id(DEFAULT_TENANT_ID, job.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(completed, job);
+    assert_eq!(completed.status, JobStatus::Succeeded);
+}
+
+async fn check(repositories: &Repositories) {
+    check_users(repositories.users.as_ref()).await;
+    check_chats(repositories.chats.as_ref(), repositories.users.as_ref()).await;
+    check_unit_of_work(repositories).await;
+    check_audit(repositories.audit.as_ref()).await;
+    check_redactions(
+        repositories.redactions.as_ref(),
+        repositories.users.as_ref(),
+    )
+    .await;
+    check_memories(repositories.memories.as_ref(), repositories.users.as_ref()).await;
+    check_scheduled_messages(
+        repositories.scheduled.as_ref(),
+        repositories.chats.as_ref(),
+        repositories.users.as_ref(),
+    )
+    .await;
+    check_webhooks(repositories.webhooks.as_ref()).await;
+    check_assistants(
+        repositories.assistants.as_ref(),
+        repositories.chats.as_ref(),
+        repositories.users.as_ref(),
+    )
+    .await;
+    check_batches(repositories.batches.as_ref(), repositories.users.as_ref()).await;
+    check_jobs(repositories.jobs.as_ref(), repositories.users.as_ref()).await;
+    check_tenants(repositories.tenants.as_ref()).await;
+    check_api_keys(repositories.api_keys.as_ref(), repositories.users.as_ref()).await;
+    check_usage_summary(
+        repositories.usage.as_ref(),
+        repositories.chats.as_ref(),
+        repositories.users.as_ref(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_memory() {
+    let repositories = migrated(DatabaseDriver::Memory, "", false).await;
+
+    check(&repositories).await;
+}
+
+// migrated connects to the database and applies the migrations, checking the schema is
+// reported behind first on a fresh database
+async fn migrated(driver: DatabaseDriver, url: &str, fresh: bool) -> Repositories {
+    let repositories = Repositories::connect(driver, url, model()).await.unwrap();
+    if fresh {
+        assert!(repositories.schema_version().await.unwrap().is_behind());
+    }
+
+    let version = repositories.migrate().await.unwrap();
+    assert!(!version.is_behind());
+    assert_eq!(version.current, version.latest);
+
+    repositories
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite() {
+    let path = std::env::temp_dir().join(format!("chat-service-{}.db", Uuid::new_v4()));
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+
+    let repositories = migrated(DatabaseDriver::Sqlite, &url, true).await;
+    check(&repositories).await;
+
+    repositories.close().await;
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(feature = "mysql")]
+#[tokio::test]
+async fn test_mysql() {
+    let Ok(url) = std::env::var("TEST_MYSQL_URL") else {
+        return;
+    };
+
+    let repositories = migrated(DatabaseDriver::Mysql, &url, false).await;
+    check(&repositories).await;
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn test_postgres() {
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+        return;
+    };
+
+    let repositories = migrated(DatabaseDriver::Postgres, &url, false).await;
+    check(&repositories).await;
+}
@@ -0,0 +1,178 @@
+// Property tests of the token accounting of a chat: whatever messages are added, trimmed,
+// summarized or rewound, the usage is the size of the prompt and the prompt fits the budget
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use uuid::Uuid;
+
+use chat_service::internal::domain::entity::chat::{Chat, ChatConfig, ChatStatus, TrimmingPolicy};
+use chat_service::internal::domain::entity::message::{Message, Role};
+use chat_service::internal::domain::entity::model::Model;
+use chat_service::internal::domain::token_counter::prompt_tokens;
+
+#[derive(Debug, Clone)]
+enum Operation {
+    Add { role: Role, tokens: usize },
+    // Summarize replaces all but keep_recent messages with a summary of a percent of their size
+    Summarize { keep_recent: usize, percent: usize },
+    // Rewind goes back to one of the user messages
+    Rewind { index: Index },
+}
+
+fn model() -> Model {
+    Model::new("gpt-3.5-turbo".to_string(), 4096)
+}
+
+// message takes its size as given, the content is not what is being counted here
+fn message(role: Role, tokens: usize) -> Message {
+    let mut message = Message::new(
+        Uuid::new_v4(),
+        role,
+        "content",
+        0,
+        model(),
+        chrono::Utc::now(),
+    );
+    message.tokens = tokens;
+    message
+}
+
+fn chat(max_tokens: usize, system_tokens: usize, policy: TrimmingPolicy) -> Chat {
+    let config = ChatConfig::builder(model())
+        .max_tokens(max_tokens)
+        .trimming_policy(policy)
+        .build()
+        .unwrap();
+    let mut chat = Chat::new(
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        message(Role::System, system_tokens),
+        vec![],
+        vec![],
+        ChatStatus::Active,
+        0,
+        config,
+    );
+    chat.refresh_token_usage();
+    chat
+}
+
+fn policy() -> impl Strategy<Value = TrimmingPolicy> {
+    prop_oneof![
+        Just(TrimmingPolicy::TrimOldest),
+        Just(TrimmingPolicy::RejectNew),
+        Just(TrimmingPolicy::SummarizeAndTrim),
+    ]
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        6 => (
+            prop_oneof![Just(Role::User), Just(Role::Assistant), Just(Role::Tool)],
+            1..600usize,
+        )
+            .prop_map(|(role, tokens)| Operation::Add { role, tokens }),
+        1 => (0..8usize, 0..=100usize)
+            .prop_map(|(keep_recent, percent)| Operation::Summarize { keep_recent, percent }),
+        1 => any::<Index>().prop_map(|index| Operation::Rewind { index }),
+    ]
+}
+
+// apply runs the operation the way the use cases do, a rejected operation leaves the chat as it
+// was; the ids of the messages it brought in are added to seen
+fn apply(chat: &mut Chat, operation: Operation, seen: &mut HashSet<Uuid>) {
+    match operation {
+        Operation::Add { role, tokens } => {
+            let message = message(role, tokens);
+            let id = message.id;
+            if chat.add_message(message).is_ok() {
+                seen.insert(id);
+            }
+        }
+        Operation::Summarize {
+            keep_recent,
+            percent,
+        } => {
+            let summarized = chat.messages.len().saturating_sub(keep_recent);
+            let tokens: usize = chat.messages[..summarized]
+                .iter()
+                .map(|message| message.tokens)
+                .sum();
+            let summary = message(Role::System, tokens * percent / 100);
+            let id = summary.id;
+            chat.summarize(keep_recent, summary);
+            if summarized > 0 {
+                seen.insert(id);
+            }
+        }
+        Operation::Rewind { index } => {
+            let users: Vec<Uuid> = chat
+                .messages
+                .iter()
+                .filter(|message| message.role == Role::User)
+                .map(|message| message.id)
+                .collect();
+            if !users.is_empty() {
+                chat.rewind_to(*index.get(&users)).unwrap();
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_token_usage_is_prompt_size(
+        max_tokens in 128..4096usize,
+        system_tokens in 1..100usize,
+        policy in policy(),
+        operations in proptest::collection::vec(operation(), 0..60),
+    ) {
+        let mut chat = chat(max_tokens, system_tokens, policy);
+        let mut seen = HashSet::new();
+
+        for operation in operations {
+            apply(&mut chat, operation, &mut seen);
+
+            prop_assert_eq!(
+                chat.token_usage,
+                prompt_tokens(chat.initial_system_message(), &chat.messages)
+            );
+            prop_assert!(chat.token_usage <= chat.config.max_tokens);
+            prop_assert!(chat.validate().is_ok());
+
+            // nothing is ever lost: every message is either in the prompt or erased
+            let kept: HashSet<Uuid> = chat
+                .messages
+                .iter()
+                .chain(chat.erased_messages.iter())
+                .map(|message| message.id)
+                .collect();
+            prop_assert_eq!(&kept, &seen);
+            prop_assert_eq!(kept.len(), chat.messages.len() + chat.erased_messages.len());
+        }
+    }
+
+    #[test]
+    fn test_reject_new_keeps_history(
+        max_tokens in 128..4096usize,
+        tokens in proptest::collection::vec(1..600usize, 0..40),
+    ) {
+        let mut chat = chat(max_tokens, 10, TrimmingPolicy::RejectNew);
+
+        for tokens in tokens {
+            let before = chat.messages.clone();
+            let fits = chat.token_usage + tokens <= chat.config.max_tokens;
+
+            prop_assert_eq!(chat.add_message(message(Role::User, tokens)).is_ok(), fits);
+            if !fits {
+                prop_assert_eq!(&chat.messages, &before);
+            }
+            prop_assert!(chat.erased_messages.is_empty());
+            prop_assert_eq!(
+                chat.token_usage,
+                prompt_tokens(chat.initial_system_message(), &chat.messages)
+            );
+        }
+    }
+}